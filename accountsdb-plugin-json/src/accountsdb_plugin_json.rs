@@ -0,0 +1,449 @@
+//! A reference AccountsDb plugin which appends every notification it receives as a
+//! line of JSON to files in a configured output directory. It has no external
+//! dependencies and is mainly useful to exercise the plugin interface offline.
+//!
+//! Example config file:
+//! ```json
+//! {
+//!     "libpath": "libsolana_accountsdb_plugin_json.so",
+//!     "output_dir": "/tmp/accountsdb-plugin-json",
+//!     "accounts": true,
+//!     "transactions": true,
+//!     "evm_blocks": true
+//! }
+//! ```
+use {
+    log::*,
+    serde_derive::Deserialize,
+    serde_json::json,
+    solana_accountsdb_plugin_interface::accountsdb_plugin_interface::{
+        AccountsDbPlugin, AccountsDbPluginError, ReplicaAccountInfoVersions,
+        ReplicaEvmBlockInfoVersions, ReplicaTransactionInfoVersions, Result, SlotStatus,
+    },
+    std::{
+        fs::{self, File, OpenOptions},
+        io::{LineWriter, Write},
+        path::{Path, PathBuf},
+        sync::Mutex,
+    },
+};
+
+pub const ACCOUNTS_FILE: &str = "accounts.json";
+pub const SLOTS_FILE: &str = "slots.json";
+pub const TRANSACTIONS_FILE: &str = "transactions.json";
+pub const EVM_BLOCKS_FILE: &str = "evm_blocks.json";
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Deserialize, Debug)]
+struct AccountsDbPluginJsonConfig {
+    output_dir: PathBuf,
+    #[serde(default = "default_true")]
+    accounts: bool,
+    #[serde(default)]
+    transactions: bool,
+    #[serde(default)]
+    evm_blocks: bool,
+}
+
+#[derive(Debug)]
+struct JsonWriters {
+    accounts: Option<Mutex<LineWriter<File>>>,
+    slots: Mutex<LineWriter<File>>,
+    transactions: Option<Mutex<LineWriter<File>>>,
+    evm_blocks: Option<Mutex<LineWriter<File>>>,
+}
+
+#[derive(Debug, Default)]
+pub struct AccountsDbPluginJson {
+    writers: Option<JsonWriters>,
+}
+
+fn open_output(output_dir: &Path, name: &str) -> Result<Mutex<LineWriter<File>>> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(output_dir.join(name))?;
+    Ok(Mutex::new(LineWriter::new(file)))
+}
+
+fn write_line(writer: &Mutex<LineWriter<File>>, value: serde_json::Value) -> Result<()> {
+    let mut writer = writer.lock().unwrap();
+    writeln!(writer, "{}", value).map_err(|err| AccountsDbPluginError::Custom(Box::new(err)))
+}
+
+impl AccountsDbPluginJson {
+    fn writers(&self) -> Result<&JsonWriters> {
+        self.writers
+            .as_ref()
+            .ok_or_else(|| AccountsDbPluginError::ConfigFileReadError {
+                msg: "plugin has not been loaded".to_string(),
+            })
+    }
+}
+
+impl AccountsDbPlugin for AccountsDbPluginJson {
+    fn name(&self) -> &'static str {
+        "AccountsDbPluginJson"
+    }
+
+    fn on_load(&mut self, config_file: &str) -> Result<()> {
+        info!("Loading plugin {} from {}", self.name(), config_file);
+        let file = File::open(config_file)?;
+        let config: AccountsDbPluginJsonConfig = serde_json::from_reader(file).map_err(|err| {
+            AccountsDbPluginError::ConfigFileReadError {
+                msg: format!(
+                    "The config file is not in the JSON format expected: {}",
+                    err
+                ),
+            }
+        })?;
+        fs::create_dir_all(&config.output_dir)?;
+
+        let output_dir = &config.output_dir;
+        self.writers = Some(JsonWriters {
+            accounts: if config.accounts {
+                Some(open_output(output_dir, ACCOUNTS_FILE)?)
+            } else {
+                None
+            },
+            slots: open_output(output_dir, SLOTS_FILE)?,
+            transactions: if config.transactions {
+                Some(open_output(output_dir, TRANSACTIONS_FILE)?)
+            } else {
+                None
+            },
+            evm_blocks: if config.evm_blocks {
+                Some(open_output(output_dir, EVM_BLOCKS_FILE)?)
+            } else {
+                None
+            },
+        });
+        Ok(())
+    }
+
+    fn on_unload(&mut self) {
+        info!("Unloading plugin {}", self.name());
+        if let Some(writers) = self.writers.take() {
+            let all = std::iter::once(&writers.slots)
+                .chain(writers.accounts.as_ref())
+                .chain(writers.transactions.as_ref())
+                .chain(writers.evm_blocks.as_ref());
+            for writer in all {
+                if let Err(err) = writer.lock().unwrap().flush() {
+                    warn!("Failed to flush plugin output: {}", err);
+                }
+            }
+        }
+    }
+
+    fn update_account(
+        &mut self,
+        account: ReplicaAccountInfoVersions,
+        slot: u64,
+        is_startup: bool,
+    ) -> Result<()> {
+        let writer = match &self.writers()?.accounts {
+            Some(writer) => writer,
+            None => return Ok(()),
+        };
+        match account {
+            ReplicaAccountInfoVersions::V0_0_1(account) => write_line(
+                writer,
+                json!({
+                    "slot": slot,
+                    "pubkey": bs58::encode(account.pubkey).into_string(),
+                    "lamports": account.lamports,
+                    "owner": bs58::encode(account.owner).into_string(),
+                    "executable": account.executable,
+                    "rent_epoch": account.rent_epoch,
+                    "data": base64::encode(account.data),
+                    "write_version": account.write_version,
+                    "is_startup": is_startup,
+                }),
+            ),
+        }
+    }
+
+    fn update_slot_status(
+        &mut self,
+        slot: u64,
+        parent: Option<u64>,
+        status: SlotStatus,
+    ) -> Result<()> {
+        write_line(
+            &self.writers()?.slots,
+            json!({
+                "slot": slot,
+                "parent": parent,
+                "status": status.as_str(),
+            }),
+        )
+    }
+
+    fn notify_transaction(
+        &mut self,
+        transaction: ReplicaTransactionInfoVersions,
+        slot: u64,
+    ) -> Result<()> {
+        let writer = match &self.writers()?.transactions {
+            Some(writer) => writer,
+            None => return Ok(()),
+        };
+        match transaction {
+            ReplicaTransactionInfoVersions::V0_0_1(transaction) => {
+                let meta = serde_json::to_value(transaction.transaction_status_meta)
+                    .map_err(|err| AccountsDbPluginError::Custom(Box::new(err)))?;
+                write_line(
+                    writer,
+                    json!({
+                        "slot": slot,
+                        "signature": transaction.signature.to_string(),
+                        "is_vote": transaction.is_vote,
+                        "meta": meta,
+                    }),
+                )
+            }
+        }
+    }
+
+    fn notify_evm_block(&mut self, block: ReplicaEvmBlockInfoVersions) -> Result<()> {
+        let writer = match &self.writers()?.evm_blocks {
+            Some(writer) => writer,
+            None => return Ok(()),
+        };
+        match block {
+            ReplicaEvmBlockInfoVersions::V0_0_1(block) => {
+                let transactions: Vec<_> = block
+                    .transactions
+                    .iter()
+                    .map(|(hash, receipt)| {
+                        json!({
+                            "hash": format!("{:?}", hash),
+                            "index": receipt.index,
+                            "used_gas": receipt.used_gas,
+                            "status": format!("{:?}", receipt.status),
+                        })
+                    })
+                    .collect();
+                write_line(
+                    writer,
+                    json!({
+                        "block_hash": format!("{:?}", block.block_hash),
+                        "block_number": block.header.block_number,
+                        "parent_hash": format!("{:?}", block.header.parent_hash),
+                        "state_root": format!("{:?}", block.header.state_root),
+                        "native_chain_slot": block.header.native_chain_slot,
+                        "timestamp": block.header.timestamp,
+                        "gas_used": block.header.gas_used,
+                        "transactions": transactions,
+                    }),
+                )
+            }
+        }
+    }
+
+    fn account_data_notifications_enabled(&self) -> bool {
+        self.writers
+            .as_ref()
+            .map(|writers| writers.accounts.is_some())
+            .unwrap_or(false)
+    }
+
+    fn transaction_notifications_enabled(&self) -> bool {
+        self.writers
+            .as_ref()
+            .map(|writers| writers.transactions.is_some())
+            .unwrap_or(false)
+    }
+
+    fn evm_block_notifications_enabled(&self) -> bool {
+        self.writers
+            .as_ref()
+            .map(|writers| writers.evm_blocks.is_some())
+            .unwrap_or(false)
+    }
+}
+
+#[no_mangle]
+#[allow(improper_ctypes_definitions)]
+/// # Safety
+///
+/// This function returns the AccountsDbPluginJson pointer as trait AccountsDbPlugin.
+pub unsafe extern "C" fn _create_plugin() -> *mut dyn AccountsDbPlugin {
+    let plugin = AccountsDbPluginJson::default();
+    let plugin: Box<dyn AccountsDbPlugin> = Box::new(plugin);
+    Box::into_raw(plugin)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        evm_state::{BlockHeader, BlockVersion, H256},
+        solana_accountsdb_plugin_interface::accountsdb_plugin_interface::{
+            ReplicaAccountInfo, ReplicaEvmBlockInfo, ReplicaTransactionInfo,
+        },
+        solana_sdk::{pubkey::Pubkey, signature::Signature, transaction::Transaction},
+        solana_transaction_status::TransactionStatusMeta,
+        std::io::{BufRead, BufReader},
+    };
+
+    fn load_plugin(output_dir: &Path, config: serde_json::Value) -> AccountsDbPluginJson {
+        let config_file = output_dir.join("config.json");
+        fs::write(&config_file, config.to_string()).unwrap();
+        let mut plugin = AccountsDbPluginJson::default();
+        plugin.on_load(config_file.to_str().unwrap()).unwrap();
+        plugin
+    }
+
+    fn read_lines(path: &Path) -> Vec<serde_json::Value> {
+        BufReader::new(File::open(path).unwrap())
+            .lines()
+            .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_write_notifications() {
+        let output_dir = tempfile::TempDir::new().unwrap();
+        let mut plugin = load_plugin(
+            output_dir.path(),
+            json!({
+                "libpath": "unused",
+                "output_dir": output_dir.path(),
+                "transactions": true,
+                "evm_blocks": true,
+            }),
+        );
+        assert!(plugin.account_data_notifications_enabled());
+        assert!(plugin.transaction_notifications_enabled());
+        assert!(plugin.evm_block_notifications_enabled());
+
+        let pubkey = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let account = ReplicaAccountInfo {
+            pubkey: pubkey.as_ref(),
+            lamports: 42,
+            owner: owner.as_ref(),
+            executable: false,
+            rent_epoch: 1,
+            data: &[1, 2, 3],
+            write_version: 7,
+        };
+        plugin
+            .update_account(ReplicaAccountInfoVersions::V0_0_1(&account), 5, false)
+            .unwrap();
+        plugin
+            .update_slot_status(5, Some(4), SlotStatus::Rooted)
+            .unwrap();
+
+        let signature = Signature::new(&[3; 64]);
+        let transaction = Transaction::default();
+        let meta = TransactionStatusMeta {
+            fee: 5000,
+            ..TransactionStatusMeta::default()
+        };
+        let transaction_info = ReplicaTransactionInfo {
+            signature: &signature,
+            is_vote: false,
+            transaction: &transaction,
+            transaction_status_meta: &meta,
+        };
+        plugin
+            .notify_transaction(ReplicaTransactionInfoVersions::V0_0_1(&transaction_info), 5)
+            .unwrap();
+
+        let header = BlockHeader::new(
+            H256::repeat_byte(1),
+            1_000_000,
+            H256::repeat_byte(2),
+            3,
+            0,
+            100,
+            5,
+            H256::zero(),
+            [].iter(),
+            BlockVersion::VersionConsistentHashes,
+        );
+        let block = ReplicaEvmBlockInfo {
+            block_hash: header.hash(),
+            header: &header,
+            transactions: &[],
+        };
+        plugin
+            .notify_evm_block(ReplicaEvmBlockInfoVersions::V0_0_1(&block))
+            .unwrap();
+        plugin.on_unload();
+
+        let accounts = read_lines(&output_dir.path().join(ACCOUNTS_FILE));
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0]["slot"], 5);
+        assert_eq!(accounts[0]["pubkey"], pubkey.to_string());
+        assert_eq!(accounts[0]["owner"], owner.to_string());
+        assert_eq!(accounts[0]["lamports"], 42);
+        assert_eq!(accounts[0]["data"], base64::encode(&[1, 2, 3]));
+        assert_eq!(accounts[0]["write_version"], 7);
+        assert_eq!(accounts[0]["is_startup"], false);
+
+        let slots = read_lines(&output_dir.path().join(SLOTS_FILE));
+        assert_eq!(
+            slots,
+            vec![json!({"slot": 5, "parent": 4, "status": "rooted"})]
+        );
+
+        let transactions = read_lines(&output_dir.path().join(TRANSACTIONS_FILE));
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0]["signature"], signature.to_string());
+        assert_eq!(transactions[0]["is_vote"], false);
+        assert_eq!(transactions[0]["meta"]["fee"], 5000);
+
+        let evm_blocks = read_lines(&output_dir.path().join(EVM_BLOCKS_FILE));
+        assert_eq!(evm_blocks.len(), 1);
+        assert_eq!(evm_blocks[0]["block_number"], 3);
+        assert_eq!(evm_blocks[0]["native_chain_slot"], 5);
+        assert_eq!(evm_blocks[0]["block_hash"], format!("{:?}", header.hash()));
+    }
+
+    #[test]
+    fn test_disabled_notifications() {
+        let output_dir = tempfile::TempDir::new().unwrap();
+        let mut plugin = load_plugin(
+            output_dir.path(),
+            json!({
+                "libpath": "unused",
+                "output_dir": output_dir.path(),
+                "accounts": false,
+            }),
+        );
+        assert!(!plugin.account_data_notifications_enabled());
+        assert!(!plugin.transaction_notifications_enabled());
+        assert!(!plugin.evm_block_notifications_enabled());
+
+        let pubkey = Pubkey::new_unique();
+        let account = ReplicaAccountInfo {
+            pubkey: pubkey.as_ref(),
+            owner: pubkey.as_ref(),
+            ..ReplicaAccountInfo::default()
+        };
+        plugin
+            .update_account(ReplicaAccountInfoVersions::V0_0_1(&account), 1, true)
+            .unwrap();
+        plugin.on_unload();
+
+        assert!(!output_dir.path().join(ACCOUNTS_FILE).exists());
+        assert!(!output_dir.path().join(TRANSACTIONS_FILE).exists());
+        assert!(!output_dir.path().join(EVM_BLOCKS_FILE).exists());
+        assert!(output_dir.path().join(SLOTS_FILE).exists());
+    }
+
+    #[test]
+    fn test_not_loaded() {
+        let mut plugin = AccountsDbPluginJson::default();
+        assert!(plugin
+            .update_slot_status(1, None, SlotStatus::Processed)
+            .is_err());
+    }
+}
@@ -0,0 +1 @@
+pub mod accountsdb_plugin_json;
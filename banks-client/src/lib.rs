@@ -6,8 +6,13 @@
 //! cumbersome to use.
 
 use borsh::BorshDeserialize;
+use evm_state::{AccountState, TransactionReceipt, H160, H256, U256};
 use futures::{future::join_all, Future, FutureExt};
-pub use solana_banks_interface::{BanksClient as TarpcClient, TransactionStatus};
+pub use solana_banks_interface::{
+    BanksClient as TarpcClient, BanksTransactionResultWithMetadata,
+    BanksTransactionResultWithSimulation, EvmCall, EvmCallResult, TransactionSimulationDetails,
+    TransactionStatus,
+};
 use solana_banks_interface::{BanksRequest, BanksResponse};
 use solana_program::{
     clock::Slot, fee_calculator::FeeCalculator, hash::Hash, program_pack::Pack, pubkey::Pubkey,
@@ -95,6 +100,25 @@ impl BanksClient {
             .process_transaction_with_commitment_and_context(ctx, transaction, commitment)
     }
 
+    pub fn process_transaction_with_metadata_and_context(
+        &mut self,
+        ctx: Context,
+        transaction: Transaction,
+    ) -> impl Future<Output = io::Result<BanksTransactionResultWithMetadata>> + '_ {
+        self.inner
+            .process_transaction_with_metadata_and_context(ctx, transaction)
+    }
+
+    pub fn simulate_transaction_with_commitment_and_context(
+        &mut self,
+        ctx: Context,
+        transaction: Transaction,
+        commitment: CommitmentLevel,
+    ) -> impl Future<Output = io::Result<BanksTransactionResultWithSimulation>> + '_ {
+        self.inner
+            .simulate_transaction_with_commitment_and_context(ctx, transaction, commitment)
+    }
+
     pub fn get_account_with_commitment_and_context(
         &mut self,
         ctx: Context,
@@ -105,6 +129,57 @@ impl BanksClient {
             .get_account_with_commitment_and_context(ctx, address, commitment)
     }
 
+    pub fn get_accounts_with_commitment_and_context(
+        &mut self,
+        ctx: Context,
+        addresses: Vec<Pubkey>,
+        commitment: CommitmentLevel,
+    ) -> impl Future<Output = io::Result<Vec<Option<Account>>>> + '_ {
+        self.inner
+            .get_accounts_with_commitment_and_context(ctx, addresses, commitment)
+    }
+
+    pub fn get_evm_account_with_commitment_and_context(
+        &mut self,
+        ctx: Context,
+        address: H160,
+        commitment: CommitmentLevel,
+    ) -> impl Future<Output = io::Result<Option<AccountState>>> + '_ {
+        self.inner
+            .get_evm_account_with_commitment_and_context(ctx, address, commitment)
+    }
+
+    pub fn get_evm_storage_with_commitment_and_context(
+        &mut self,
+        ctx: Context,
+        address: H160,
+        index: H256,
+        commitment: CommitmentLevel,
+    ) -> impl Future<Output = io::Result<Option<H256>>> + '_ {
+        self.inner
+            .get_evm_storage_with_commitment_and_context(ctx, address, index, commitment)
+    }
+
+    pub fn get_evm_transaction_receipt_with_commitment_and_context(
+        &mut self,
+        ctx: Context,
+        hash: H256,
+        commitment: CommitmentLevel,
+    ) -> impl Future<Output = io::Result<Option<TransactionReceipt>>> + '_ {
+        self.inner
+            .get_evm_transaction_receipt_with_commitment_and_context(ctx, hash, commitment)
+    }
+
+    pub fn evm_call_with_commitment_and_context(
+        &mut self,
+        ctx: Context,
+        call: EvmCall,
+        commitment: CommitmentLevel,
+    ) -> impl Future<Output = io::Result<Result<EvmCallResult, String>>> + '_ {
+        self.inner
+            .evm_call_with_commitment_and_context(ctx, call, commitment)
+    }
+
     /// Send a transaction and return immediately. The server will resend the
     /// transaction until either it is accepted by the cluster or the transaction's
     /// blockhash expires.
@@ -168,6 +243,38 @@ impl BanksClient {
         self.process_transaction_with_commitment(transaction, CommitmentLevel::default())
     }
 
+    /// Process a transaction and return the result with metadata: logs, inner
+    /// instructions, balance changes and return data. The transaction is executed
+    /// directly on the server's working bank.
+    pub fn process_transaction_with_metadata(
+        &mut self,
+        transaction: Transaction,
+    ) -> impl Future<Output = io::Result<BanksTransactionResultWithMetadata>> + '_ {
+        self.process_transaction_with_metadata_and_context(context::current(), transaction)
+    }
+
+    /// Simulate a transaction at the given commitment level. The result carries the
+    /// logs, compute units consumed, return data and the resulting account states.
+    pub fn simulate_transaction_with_commitment(
+        &mut self,
+        transaction: Transaction,
+        commitment: CommitmentLevel,
+    ) -> impl Future<Output = io::Result<BanksTransactionResultWithSimulation>> + '_ {
+        self.simulate_transaction_with_commitment_and_context(
+            context::current(),
+            transaction,
+            commitment,
+        )
+    }
+
+    /// Simulate a transaction at the default commitment level
+    pub fn simulate_transaction(
+        &mut self,
+        transaction: Transaction,
+    ) -> impl Future<Output = io::Result<BanksTransactionResultWithSimulation>> + '_ {
+        self.simulate_transaction_with_commitment(transaction, CommitmentLevel::default())
+    }
+
     pub async fn process_transactions_with_commitment(
         &mut self,
         transactions: Vec<Transaction>,
@@ -217,6 +324,25 @@ impl BanksClient {
         self.get_account_with_commitment(address, CommitmentLevel::default())
     }
 
+    /// Return the accounts at the given addresses at the slot corresponding to the
+    /// given commitment level. Missing accounts are returned as None.
+    pub fn get_accounts_with_commitment(
+        &mut self,
+        addresses: Vec<Pubkey>,
+        commitment: CommitmentLevel,
+    ) -> impl Future<Output = io::Result<Vec<Option<Account>>>> + '_ {
+        self.get_accounts_with_commitment_and_context(context::current(), addresses, commitment)
+    }
+
+    /// Return the accounts at the given addresses at the time of the most recent root
+    /// slot. Missing accounts are returned as None.
+    pub fn get_accounts(
+        &mut self,
+        addresses: Vec<Pubkey>,
+    ) -> impl Future<Output = io::Result<Vec<Option<Account>>>> + '_ {
+        self.get_accounts_with_commitment(addresses, CommitmentLevel::default())
+    }
+
     /// Return the unpacked account data at the given address
     /// If the account is not found, an error is returned
     pub fn get_packed_account_data<T: Pack>(
@@ -261,6 +387,95 @@ impl BanksClient {
         self.get_balance_with_commitment(address, CommitmentLevel::default())
     }
 
+    /// Return the EVM account state at the given address at the slot corresponding
+    /// to the given commitment level. If the account is not found, None is returned.
+    pub fn get_evm_account_with_commitment(
+        &mut self,
+        address: H160,
+        commitment: CommitmentLevel,
+    ) -> impl Future<Output = io::Result<Option<AccountState>>> + '_ {
+        self.get_evm_account_with_commitment_and_context(context::current(), address, commitment)
+    }
+
+    /// Return the EVM account state at the given address at the time of the most
+    /// recent root slot. If the account is not found, None is returned.
+    pub fn get_evm_account(
+        &mut self,
+        address: H160,
+    ) -> impl Future<Output = io::Result<Option<AccountState>>> + '_ {
+        self.get_evm_account_with_commitment(address, CommitmentLevel::default())
+    }
+
+    /// Return the balance in wei of an EVM account at the given address at the time
+    /// of the most recent root slot.
+    pub fn get_evm_balance(
+        &mut self,
+        address: H160,
+    ) -> impl Future<Output = io::Result<U256>> + '_ {
+        self.get_evm_account(address)
+            .map(|result| Ok(result?.map(|x| x.balance).unwrap_or_default()))
+    }
+
+    /// Return the nonce of an EVM account at the given address at the time of the
+    /// most recent root slot.
+    pub fn get_evm_nonce(&mut self, address: H160) -> impl Future<Output = io::Result<U256>> + '_ {
+        self.get_evm_account(address)
+            .map(|result| Ok(result?.map(|x| x.nonce).unwrap_or_default()))
+    }
+
+    /// Return the contract code of an EVM account at the given address at the time
+    /// of the most recent root slot. Accounts without code return an empty vector.
+    pub fn get_evm_code(
+        &mut self,
+        address: H160,
+    ) -> impl Future<Output = io::Result<Vec<u8>>> + '_ {
+        self.get_evm_account(address)
+            .map(|result| Ok(result?.map(|x| x.code.into()).unwrap_or_default()))
+    }
+
+    /// Return the value of a storage slot of an EVM account at the time of the most
+    /// recent root slot. Unset slots return zero.
+    pub fn get_evm_storage_at(
+        &mut self,
+        address: H160,
+        index: H256,
+    ) -> impl Future<Output = io::Result<H256>> + '_ {
+        self.get_evm_storage_with_commitment_and_context(
+            context::current(),
+            address,
+            index,
+            CommitmentLevel::default(),
+        )
+        .map(|result| Ok(result?.unwrap_or_default()))
+    }
+
+    /// Return the receipt of an EVM transaction with the given hash, if it was executed
+    /// in the bank at the time of the most recent root slot or in one of its ancestors.
+    pub fn get_evm_transaction_receipt(
+        &mut self,
+        hash: H256,
+    ) -> impl Future<Output = io::Result<Option<TransactionReceipt>>> + '_ {
+        self.get_evm_transaction_receipt_with_commitment_and_context(
+            context::current(),
+            hash,
+            CommitmentLevel::default(),
+        )
+    }
+
+    /// Execute an EVM call against the state at the time of the most recent root slot,
+    /// like `eth_call`. The state changes made by the call are discarded.
+    pub fn evm_call(
+        &mut self,
+        call: EvmCall,
+    ) -> impl Future<Output = io::Result<EvmCallResult>> + '_ {
+        self.evm_call_with_commitment_and_context(
+            context::current(),
+            call,
+            CommitmentLevel::default(),
+        )
+        .map(|result| result?.map_err(|err| io::Error::new(io::ErrorKind::Other, err)))
+    }
+
     /// Return the status of a transaction with a signature matching the transaction's first
     /// signature. Return None if the transaction is not found, which may be because the
     /// blockhash was expired or the fee-paying account had insufficient funds to pay the
@@ -403,4 +618,61 @@ mod tests {
             Ok(())
         })
     }
+
+    #[test]
+    fn test_banks_server_simulate_and_process_with_metadata() -> io::Result<()> {
+        let genesis = create_genesis_config(10);
+        let bank = Bank::new(&genesis.genesis_config);
+        let slot = bank.slot();
+        let block_commitment_cache = Arc::new(RwLock::new(
+            BlockCommitmentCache::new_for_tests_with_slots(slot, slot),
+        ));
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+
+        let mint_pubkey = genesis.mint_keypair.pubkey();
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let instruction = system_instruction::transfer(&mint_pubkey, &bob_pubkey, 1);
+        let message = Message::new(&[instruction], Some(&mint_pubkey));
+
+        Runtime::new()?.block_on(async {
+            let client_transport = start_local_server(bank_forks, block_commitment_cache).await;
+            let mut banks_client = start_client(client_transport).await?;
+            let recent_blockhash = banks_client.get_recent_blockhash().await?;
+            let transaction = Transaction::new(&[&genesis.mint_keypair], message, recent_blockhash);
+
+            let simulation = banks_client
+                .simulate_transaction(transaction.clone())
+                .await?;
+            assert_eq!(simulation.result, Some(Ok(())));
+            let details = simulation.simulation_details.unwrap();
+            assert!(!details.logs.is_empty());
+            let (_, bob_account) = details
+                .accounts
+                .iter()
+                .find(|(pubkey, _)| *pubkey == bob_pubkey)
+                .unwrap();
+            assert_eq!(bob_account.lamports, 1);
+            // Simulation doesn't commit
+            assert_eq!(banks_client.get_balance(bob_pubkey).await?, 0);
+
+            let processed = banks_client
+                .process_transaction_with_metadata(transaction)
+                .await?;
+            assert_eq!(processed.result, Ok(()));
+            let metadata = processed.metadata.unwrap();
+            assert_eq!(metadata.post_balances[1], 1);
+            assert!(!metadata.log_messages.unwrap().is_empty());
+
+            let accounts = banks_client
+                .get_accounts(vec![bob_pubkey, solana_sdk::pubkey::new_rand()])
+                .await?;
+            assert_eq!(accounts.len(), 2);
+            assert_eq!(
+                accounts[0].as_ref().map(|account| account.lamports),
+                Some(1)
+            );
+            assert_eq!(accounts[1], None);
+            Ok(())
+        })
+    }
 }
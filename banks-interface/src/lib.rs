@@ -1,3 +1,4 @@
+use evm_state::{AccountState, ExitReason, TransactionReceipt, H160, H256, U256};
 use serde::{Deserialize, Serialize};
 use solana_sdk::{
     account::Account,
@@ -5,10 +6,12 @@ use solana_sdk::{
     commitment_config::CommitmentLevel,
     fee_calculator::FeeCalculator,
     hash::Hash,
+    process_instruction::TransactionReturnData,
     pubkey::Pubkey,
     signature::Signature,
     transaction::{self, Transaction, TransactionError},
 };
+use solana_transaction_status::TransactionStatusMeta;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum TransactionConfirmationStatus {
@@ -25,6 +28,45 @@ pub struct TransactionStatus {
     pub confirmation_status: Option<TransactionConfirmationStatus>,
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransactionSimulationDetails {
+    pub logs: Vec<String>,
+    pub units_consumed: u64,
+    pub return_data: Option<TransactionReturnData>,
+    /// Post-simulation states of the transaction's accounts, empty if the simulation failed
+    pub accounts: Vec<(Pubkey, Account)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BanksTransactionResultWithSimulation {
+    pub result: Option<transaction::Result<()>>,
+    pub simulation_details: Option<TransactionSimulationDetails>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BanksTransactionResultWithMetadata {
+    pub result: transaction::Result<()>,
+    pub metadata: Option<TransactionStatusMeta>, // None = transaction was not committed
+}
+
+/// An `eth_call`-style message executed against a bank's EVM state without
+/// committing its effects.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EvmCall {
+    pub caller: H160,
+    pub to: Option<H160>, // None = contract creation
+    pub value: U256,
+    pub input: Vec<u8>,
+    pub gas_limit: Option<U256>, // None = unlimited
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EvmCallResult {
+    pub exit_reason: ExitReason,
+    pub exit_data: Vec<u8>,
+    pub used_gas: u64,
+}
+
 #[tarpc::service]
 pub trait Banks {
     async fn send_transaction_with_context(transaction: Transaction);
@@ -38,10 +80,38 @@ pub trait Banks {
         transaction: Transaction,
         commitment: CommitmentLevel,
     ) -> Option<transaction::Result<()>>;
+    async fn process_transaction_with_metadata_and_context(
+        transaction: Transaction,
+    ) -> BanksTransactionResultWithMetadata;
+    async fn simulate_transaction_with_commitment_and_context(
+        transaction: Transaction,
+        commitment: CommitmentLevel,
+    ) -> BanksTransactionResultWithSimulation;
     async fn get_account_with_commitment_and_context(
         address: Pubkey,
         commitment: CommitmentLevel,
     ) -> Option<Account>;
+    async fn get_accounts_with_commitment_and_context(
+        addresses: Vec<Pubkey>,
+        commitment: CommitmentLevel,
+    ) -> Vec<Option<Account>>;
+    async fn get_evm_account_with_commitment_and_context(
+        address: H160,
+        commitment: CommitmentLevel,
+    ) -> Option<AccountState>;
+    async fn get_evm_storage_with_commitment_and_context(
+        address: H160,
+        index: H256,
+        commitment: CommitmentLevel,
+    ) -> Option<H256>;
+    async fn get_evm_transaction_receipt_with_commitment_and_context(
+        hash: H256,
+        commitment: CommitmentLevel,
+    ) -> Option<TransactionReceipt>;
+    async fn evm_call_with_commitment_and_context(
+        call: EvmCall,
+        commitment: CommitmentLevel,
+    ) -> Result<EvmCallResult, String>;
 }
 
 #[cfg(test)]
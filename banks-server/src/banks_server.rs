@@ -1,24 +1,38 @@
 use crate::send_transaction_service::{SendTransactionService, TransactionInfo};
 use bincode::{deserialize, serialize};
+use evm_state::{
+    AccountProvider, AccountState, EvmState, TransactionAction, TransactionReceipt, H160, H256,
+};
 use futures::{
     future,
     prelude::stream::{self, StreamExt},
 };
 use solana_banks_interface::{
-    Banks, BanksRequest, BanksResponse, TransactionConfirmationStatus, TransactionStatus,
+    Banks, BanksRequest, BanksResponse, BanksTransactionResultWithMetadata,
+    BanksTransactionResultWithSimulation, EvmCall, EvmCallResult, TransactionConfirmationStatus,
+    TransactionSimulationDetails, TransactionStatus,
+};
+use solana_runtime::{
+    bank::{Bank, ExecuteTimings, NonceRollbackInfo, TransactionSimulationResult},
+    bank_forks::BankForks,
+    commitment::BlockCommitmentCache,
 };
-use solana_runtime::{bank::Bank, bank_forks::BankForks, commitment::BlockCommitmentCache};
 use solana_sdk::{
     account::Account,
-    clock::Slot,
+    clock::{Slot, MAX_PROCESSING_AGE},
     commitment_config::CommitmentLevel,
+    feature_set::{self, FeatureSet},
     fee_calculator::FeeCalculator,
     hash::Hash,
     pubkey::Pubkey,
     signature::Signature,
     transaction::{self, Transaction},
 };
+use solana_transaction_status::{
+    token_balances::collect_token_balances, InnerInstructions, Reward, TransactionStatusMeta,
+};
 use std::{
+    collections::HashMap,
     io,
     net::{Ipv4Addr, SocketAddr},
     sync::{
@@ -126,12 +140,174 @@ impl BanksServer {
         }
         status
     }
+
+    /// Search the evm state of `bank` and its ancestors, newest first, for a receipt
+    fn find_evm_transaction_receipt(&self, bank: &Bank, hash: H256) -> Option<TransactionReceipt> {
+        let mut slots: Vec<Slot> = bank.ancestors.keys().copied().collect();
+        slots.sort_unstable_by(|a, b| b.cmp(a));
+        let bank_forks = self.bank_forks.read().unwrap();
+        slots.into_iter().find_map(|slot| {
+            let bank = bank_forks.get(slot)?;
+            let evm_state = bank.evm_state.read().unwrap();
+            match &*evm_state {
+                EvmState::Incomming(i) => i.find_transaction_receipt(hash).cloned(),
+                EvmState::Committed(c) => c.find_committed_transaction(hash).cloned(),
+            }
+        })
+    }
+}
+
+fn simulate_evm_call(bank: &Bank, call: EvmCall) -> Result<EvmCallResult, String> {
+    let evm_state = bank
+        .take_evm_state_form_simulation()
+        .ok_or_else(|| "evm state is not available".to_string())?;
+    let mut executor = evm_state::Executor::with_config(
+        evm_state,
+        evm_state::ChainContext::new(bank.evm_hashes()),
+        evm_state::EvmConfig {
+            estimate: true,
+            chain_id: bank.evm_chain_id,
+            ..Default::default()
+        },
+        evm_state::executor::FeatureSet::new(
+            bank.feature_set
+                .is_active(&feature_set::sophon::unsigned_tx_fix::id()),
+            bank.feature_set
+                .is_active(&feature_set::sophon::clear_logs_on_error::id()),
+        ),
+    );
+
+    let evm_state_balance = bank
+        .get_account(&solana_sdk::evm_state::id())
+        .unwrap_or_default()
+        .lamports;
+    let action = match call.to {
+        Some(address) => TransactionAction::Call(address),
+        None => TransactionAction::Create,
+    };
+    let nonce = executor.nonce(call.caller);
+    let chain_id = executor.chain_id();
+    let support_precompile = executor.support_precompile();
+    let evm_state::ExecutionResult {
+        exit_reason,
+        exit_data,
+        used_gas,
+        ..
+    } = executor
+        .transaction_execute_raw(
+            call.caller,
+            nonce,
+            // Simulate with zero gas price, to avoid out of funds errors.
+            0.into(),
+            call.gas_limit.unwrap_or_else(|| u64::MAX.into()),
+            action,
+            call.input,
+            call.value,
+            Some(chain_id),
+            H256::random(),
+            solana_evm_loader_program::precompiles::simulation_entrypoint(
+                support_precompile,
+                evm_state_balance,
+                &[],
+            ),
+        )
+        .map_err(|err| err.to_string())?;
+
+    Ok(EvmCallResult {
+        exit_reason,
+        exit_data,
+        used_gas,
+    })
 }
 
-fn verify_transaction(transaction: &Transaction) -> transaction::Result<()> {
+/// Execute and commit a transaction directly on `bank`, collecting the same metadata
+/// the transaction status service records for confirmed transactions
+fn process_transaction_with_metadata(
+    bank: &Bank,
+    transaction: &Transaction,
+) -> BanksTransactionResultWithMetadata {
+    let batch = bank.prepare_batch(std::iter::once(transaction));
+    let mut mint_decimals = HashMap::new();
+    let mut pre_token_balances = collect_token_balances(bank, &batch, &mut mint_decimals);
+    let (results, mut balances, mut inner_instructions, mut transaction_logs, mut return_data) =
+        bank.load_execute_and_commit_transactions(
+            &batch,
+            MAX_PROCESSING_AGE,
+            true,
+            true,
+            true,
+            &mut ExecuteTimings::default(),
+        );
+    let mut post_token_balances = collect_token_balances(bank, &batch, &mut mint_decimals);
+
+    let (status, nonce_rollback) = results.execution_results.into_iter().next().unwrap();
+    if !Bank::can_commit(&status) {
+        return BanksTransactionResultWithMetadata {
+            result: status,
+            metadata: None,
+        };
+    }
+
+    let fee = nonce_rollback
+        .map(|nonce_rollback| nonce_rollback.fee_calculator())
+        .unwrap_or_else(|| bank.get_fee_calculator(&transaction.message().recent_blockhash))
+        .map(|fee_calculator| fee_calculator.calculate_fee(transaction.message()))
+        .unwrap_or_default()
+        .saturating_add(bank.get_prioritization_fee(transaction.message()));
+    let inner_instructions = inner_instructions
+        .pop()
+        .flatten()
+        .map(|inner_instructions| {
+            inner_instructions
+                .into_iter()
+                .enumerate()
+                .map(|(index, instructions)| InnerInstructions {
+                    index: index as u8,
+                    instructions,
+                })
+                .filter(|i| !i.instructions.is_empty())
+                .collect()
+        });
+    let rewards = results
+        .rent_debits
+        .into_iter()
+        .next()
+        .unwrap_or_default()
+        .0
+        .into_iter()
+        .map(|(pubkey, reward_info)| Reward {
+            pubkey: pubkey.to_string(),
+            lamports: reward_info.lamports,
+            post_balance: reward_info.post_balance,
+            reward_type: Some(reward_info.reward_type),
+        })
+        .collect();
+
+    BanksTransactionResultWithMetadata {
+        result: status.clone(),
+        metadata: Some(TransactionStatusMeta {
+            status,
+            fee,
+            pre_balances: balances.pre_balances.pop().unwrap_or_default(),
+            post_balances: balances.post_balances.pop().unwrap_or_default(),
+            inner_instructions,
+            log_messages: transaction_logs.pop(),
+            pre_token_balances: pre_token_balances.pop(),
+            post_token_balances: post_token_balances.pop(),
+            rewards: Some(rewards),
+            return_data: return_data.pop().flatten(),
+            loaded_addresses: None,
+        }),
+    }
+}
+
+fn verify_transaction(
+    transaction: &Transaction,
+    feature_set: &FeatureSet,
+) -> transaction::Result<()> {
     if let Err(err) = transaction.verify() {
         Err(err)
-    } else if let Err(err) = transaction.verify_precompiles() {
+    } else if let Err(err) = transaction.verify_precompiles(feature_set) {
         Err(err)
     } else {
         Ok(())
@@ -212,7 +388,7 @@ impl Banks for BanksServer {
         transaction: Transaction,
         commitment: CommitmentLevel,
     ) -> Option<transaction::Result<()>> {
-        if let Err(err) = verify_transaction(&transaction) {
+        if let Err(err) = verify_transaction(&transaction, &self.bank(commitment).feature_set) {
             return Some(Err(err));
         }
 
@@ -232,6 +408,66 @@ impl Banks for BanksServer {
             .await
     }
 
+    async fn process_transaction_with_metadata_and_context(
+        self,
+        _: Context,
+        transaction: Transaction,
+    ) -> BanksTransactionResultWithMetadata {
+        let bank = self.bank_forks.read().unwrap().working_bank();
+        if let Err(err) = verify_transaction(&transaction, &bank.feature_set) {
+            return BanksTransactionResultWithMetadata {
+                result: Err(err),
+                metadata: None,
+            };
+        }
+
+        process_transaction_with_metadata(&bank, &transaction)
+    }
+
+    async fn simulate_transaction_with_commitment_and_context(
+        self,
+        _: Context,
+        transaction: Transaction,
+        commitment: CommitmentLevel,
+    ) -> BanksTransactionResultWithSimulation {
+        let bank = self.bank(commitment);
+        if let Err(err) = verify_transaction(&transaction, &bank.feature_set) {
+            return BanksTransactionResultWithSimulation {
+                result: Some(Err(err)),
+                simulation_details: None,
+            };
+        }
+
+        let TransactionSimulationResult {
+            result,
+            logs,
+            post_simulation_accounts,
+            units_consumed,
+            return_data,
+            ..
+        } = bank.simulate_transaction_unchecked(&transaction);
+        let accounts = if result.is_ok() {
+            transaction
+                .message
+                .account_keys
+                .iter()
+                .copied()
+                .zip(post_simulation_accounts.into_iter().map(Account::from))
+                .collect()
+        } else {
+            vec![]
+        };
+        BanksTransactionResultWithSimulation {
+            result: Some(result),
+            simulation_details: Some(TransactionSimulationDetails {
+                logs,
+                units_consumed: units_consumed.iter().sum(),
+                return_data,
+                accounts,
+            }),
+        }
+    }
+
     async fn get_account_with_commitment_and_context(
         self,
         _: Context,
@@ -241,6 +477,62 @@ impl Banks for BanksServer {
         let bank = self.bank(commitment);
         bank.get_account(&address).map(Account::from)
     }
+
+    async fn get_accounts_with_commitment_and_context(
+        self,
+        _: Context,
+        addresses: Vec<Pubkey>,
+        commitment: CommitmentLevel,
+    ) -> Vec<Option<Account>> {
+        let bank = self.bank(commitment);
+        addresses
+            .iter()
+            .map(|address| bank.get_account(address).map(Account::from))
+            .collect()
+    }
+
+    async fn get_evm_account_with_commitment_and_context(
+        self,
+        _: Context,
+        address: H160,
+        commitment: CommitmentLevel,
+    ) -> Option<AccountState> {
+        let bank = self.bank(commitment);
+        let evm_state = bank.take_evm_state_form_simulation()?;
+        evm_state.get_account_state(address)
+    }
+
+    async fn get_evm_storage_with_commitment_and_context(
+        self,
+        _: Context,
+        address: H160,
+        index: H256,
+        commitment: CommitmentLevel,
+    ) -> Option<H256> {
+        let bank = self.bank(commitment);
+        let evm_state = bank.take_evm_state_form_simulation()?;
+        evm_state.get_storage(address, index)
+    }
+
+    async fn get_evm_transaction_receipt_with_commitment_and_context(
+        self,
+        _: Context,
+        hash: H256,
+        commitment: CommitmentLevel,
+    ) -> Option<TransactionReceipt> {
+        let bank = self.bank(commitment);
+        self.find_evm_transaction_receipt(&bank, hash)
+    }
+
+    async fn evm_call_with_commitment_and_context(
+        self,
+        _: Context,
+        call: EvmCall,
+        commitment: CommitmentLevel,
+    ) -> Result<EvmCallResult, String> {
+        let bank = self.bank(commitment);
+        simulate_evm_call(&bank, call)
+    }
 }
 
 pub async fn start_local_server(
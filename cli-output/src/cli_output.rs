@@ -26,7 +26,7 @@ use {
         pubkey::Pubkey,
         signature::Signature,
         stake_history::StakeHistoryEntry,
-        transaction::{Transaction, TransactionError},
+        transaction::{Transaction, TransactionError, VersionedTransaction},
     },
     solana_stake_program::stake_state::{Authorized, Lockup},
     solana_transaction_status::{
@@ -2076,9 +2076,17 @@ pub enum CliSignatureVerificationStatus {
 
 impl CliSignatureVerificationStatus {
     pub fn verify_transaction(tx: &Transaction) -> Vec<Self> {
-        tx.verify_with_results()
+        Self::from_results(&tx.verify_with_results(), &tx.signatures)
+    }
+
+    pub fn verify_versioned_transaction(tx: &VersionedTransaction) -> Vec<Self> {
+        Self::from_results(&tx.verify_with_results(), &tx.signatures)
+    }
+
+    fn from_results(results: &[bool], signatures: &[Signature]) -> Vec<Self> {
+        results
             .iter()
-            .zip(&tx.signatures)
+            .zip(signatures)
             .map(|(stat, sig)| match stat {
                 true => CliSignatureVerificationStatus::Pass,
                 false if sig == &Signature::default() => CliSignatureVerificationStatus::None,
@@ -2186,7 +2194,10 @@ impl fmt::Display for CliBlock {
             writeln!(f, "Transaction {}:", index)?;
             writeln_transaction(
                 f,
-                &transaction_with_meta.transaction.decode().unwrap(),
+                &transaction_with_meta
+                    .transaction
+                    .decode_versioned()
+                    .unwrap(),
                 &transaction_with_meta.meta,
                 "  ",
                 None,
@@ -2206,7 +2217,7 @@ pub struct CliTransaction {
     #[serde(skip_serializing)]
     pub slot: Option<Slot>,
     #[serde(skip_serializing)]
-    pub decoded_transaction: Transaction,
+    pub decoded_transaction: VersionedTransaction,
     #[serde(skip_serializing)]
     pub prefix: String,
     #[serde(skip_serializing_if = "Vec::is_empty")]
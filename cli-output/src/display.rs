@@ -4,10 +4,18 @@ use {
     console::style,
     indicatif::{ProgressBar, ProgressStyle},
     solana_sdk::{
-        clock::UnixTimestamp, hash::Hash, message::Message, native_token::lamports_to_sol,
-        program_utils::limited_deserialize, pubkey::Pubkey, transaction::Transaction,
+        clock::UnixTimestamp,
+        hash::Hash,
+        message::{
+            v0::{LoadedAddresses, LoadedMessage},
+            Message, VersionedMessage,
+        },
+        native_token::lamports_to_sol,
+        program_utils::limited_deserialize,
+        pubkey::Pubkey,
+        transaction::VersionedTransaction,
     },
-    solana_transaction_status::UiTransactionStatusMeta,
+    solana_transaction_status::{UiLoadedAddresses, UiTransactionStatusMeta},
     spl_memo::id as spl_memo_id,
     spl_memo::v1::id as spl_memo_v1_id,
     std::{collections::HashMap, fmt, io},
@@ -157,15 +165,48 @@ fn format_account_mode(message: &Message, index: usize) -> String {
     )
 }
 
+fn parse_loaded_addresses(loaded_addresses: &UiLoadedAddresses) -> Option<LoadedAddresses> {
+    let parse = |addresses: &[String]| -> Option<Vec<Pubkey>> {
+        addresses
+            .iter()
+            .map(|address| address.parse().ok())
+            .collect()
+    };
+    Some(LoadedAddresses {
+        writable: parse(&loaded_addresses.writable)?,
+        readonly: parse(&loaded_addresses.readonly)?,
+    })
+}
+
+/// The message of `transaction` with every account inline. The accounts a v0 message loads
+/// from lookup tables are only known from its status, without it only the static accounts
+/// are listed.
+fn expanded_message(
+    transaction: &VersionedTransaction,
+    transaction_status: &Option<UiTransactionStatusMeta>,
+) -> Message {
+    match &transaction.message {
+        VersionedMessage::Legacy(message) => message.clone(),
+        VersionedMessage::V0(message) => {
+            let loaded_addresses = transaction_status
+                .as_ref()
+                .and_then(|status| status.loaded_addresses.as_ref())
+                .and_then(parse_loaded_addresses)
+                .unwrap_or_default();
+            LoadedMessage::new(message.clone(), loaded_addresses).to_legacy_message()
+        }
+    }
+}
+
 pub fn write_transaction<W: io::Write>(
     w: &mut W,
-    transaction: &Transaction,
+    transaction: &VersionedTransaction,
     transaction_status: &Option<UiTransactionStatusMeta>,
     prefix: &str,
     sigverify_status: Option<&[CliSignatureVerificationStatus]>,
     block_time: Option<UnixTimestamp>,
 ) -> io::Result<()> {
-    let message = &transaction.message;
+    let message = &expanded_message(transaction, transaction_status);
     if let Some(block_time) = block_time {
         writeln!(
             w,
@@ -227,12 +268,18 @@ pub fn write_transaction<W: io::Write>(
             prefix, program_pubkey, instruction.program_id_index
         )?;
         for (account_index, account) in instruction.accounts.iter().enumerate() {
-            let account_pubkey = message.account_keys[*account as usize];
-            writeln!(
-                w,
-                "{}  Account {}: {} ({})",
-                prefix, account_index, account_pubkey, account
-            )?;
+            match message.account_keys.get(*account as usize) {
+                Some(account_pubkey) => writeln!(
+                    w,
+                    "{}  Account {}: {} ({})",
+                    prefix, account_index, account_pubkey, account
+                )?,
+                None => writeln!(
+                    w,
+                    "{}  Account {}: unknown, loaded from a lookup table ({})",
+                    prefix, account_index, account
+                )?,
+            }
         }
 
         let mut raw = true;
@@ -271,6 +318,29 @@ pub fn write_transaction<W: io::Write>(
             writeln!(w, "{}  Data: {:?}", prefix, instruction.data)?;
         }
     }
+    for (lookup_index, lookup) in transaction
+        .message
+        .address_table_lookups()
+        .unwrap_or_default()
+        .iter()
+        .enumerate()
+    {
+        writeln!(
+            w,
+            "{}Address Table Lookup {}: {}",
+            prefix, lookup_index, lookup.account_key
+        )?;
+        writeln!(
+            w,
+            "{}  Writable Indexes: {:?}",
+            prefix, lookup.writable_indexes
+        )?;
+        writeln!(
+            w,
+            "{}  Readonly Indexes: {:?}",
+            prefix, lookup.readonly_indexes
+        )?;
+    }
 
     if let Some(transaction_status) = transaction_status {
         writeln!(
@@ -365,7 +435,7 @@ pub fn write_transaction<W: io::Write>(
 }
 
 pub fn println_transaction(
-    transaction: &Transaction,
+    transaction: &VersionedTransaction,
     transaction_status: &Option<UiTransactionStatusMeta>,
     prefix: &str,
     sigverify_status: Option<&[CliSignatureVerificationStatus]>,
@@ -390,7 +460,7 @@ pub fn println_transaction(
 
 pub fn writeln_transaction(
     f: &mut dyn fmt::Write,
-    transaction: &Transaction,
+    transaction: &VersionedTransaction,
     transaction_status: &Option<UiTransactionStatusMeta>,
     prefix: &str,
     sigverify_status: Option<&[CliSignatureVerificationStatus]>,
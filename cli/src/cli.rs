@@ -7,6 +7,7 @@ use log::*;
 use num_traits::FromPrimitive;
 use serde_json::{self, Value};
 use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_address_lookup_table_program::state::AddressLookupTable;
 use solana_clap_utils::{
     self, fee_payer::FEE_PAYER_ARG, input_parsers::*, input_validators::*, keypair::*,
     memo::MEMO_ARG, nonce::*, offline::*,
@@ -35,12 +36,15 @@ use solana_sdk::{
     decode_error::DecodeError,
     hash::Hash,
     instruction::InstructionError,
-    message::Message,
+    message::{
+        v0::{self, LoadedAddresses, LoadedMessage},
+        Message, VersionedMessage,
+    },
     pubkey::Pubkey,
     signature::{Signature, Signer, SignerError},
     system_instruction::{self, SystemError},
     system_program,
-    transaction::{Transaction, TransactionError},
+    transaction::{Transaction, TransactionError, VersionedTransaction},
 };
 use solana_stake_program::{
     stake_instruction::LockupArgs,
@@ -249,6 +253,26 @@ pub enum CliCommand {
         memo: Option<String>,
         fee_payer: SignerIndex,
     },
+    RedelegateStake {
+        stake_account_pubkey: Pubkey,
+        vote_account_pubkey: Pubkey,
+        stake_authority: SignerIndex,
+        sign_only: bool,
+        dump_transaction_message: bool,
+        blockhash_query: BlockhashQuery,
+        nonce_account: Option<Pubkey>,
+        nonce_authority: SignerIndex,
+        memo: Option<String>,
+        redelegation_stake_account: SignerIndex,
+        seed: Option<String>,
+        fee_payer: SignerIndex,
+    },
+    DeactivateDelinquentStake {
+        stake_account_pubkey: Pubkey,
+        reference_vote_account_pubkey: Option<Pubkey>,
+        memo: Option<String>,
+        fee_payer: SignerIndex,
+    },
     ShowStakeHistory {
         use_lamports_unit: bool,
         limit_results: usize,
@@ -355,7 +379,7 @@ pub enum CliCommand {
         use_lamports_unit: bool,
     },
     Confirm(Signature),
-    DecodeTransaction(Transaction),
+    DecodeTransaction(VersionedTransaction),
     ResolveSigner(Option<String>),
     ShowAccount {
         pubkey: Pubkey,
@@ -747,6 +771,12 @@ pub fn parse_command(
         ("merge-stake", Some(matches)) => {
             parse_merge_stake(matches, default_signer, wallet_manager)
         }
+        ("redelegate-stake", Some(matches)) => {
+            parse_redelegate_stake(matches, default_signer, wallet_manager)
+        }
+        ("deactivate-delinquent-stake", Some(matches)) => {
+            parse_deactivate_delinquent_stake(matches, default_signer, wallet_manager)
+        }
         ("stake-authorize", Some(matches)) => {
             parse_stake_authorize(matches, default_signer, wallet_manager)
         }
@@ -838,7 +868,7 @@ pub fn parse_command(
             };
 
             let encoded_transaction = EncodedTransaction::Binary(blob, encoding);
-            if let Some(transaction) = encoded_transaction.decode() {
+            if let Some(transaction) = encoded_transaction.decode_versioned() {
                 Ok(CliCommandInfo {
                     command: CliCommand::DecodeTransaction(transaction),
                     signers: vec![],
@@ -1073,10 +1103,11 @@ fn process_confirm(
                             let decoded_transaction = confirmed_transaction
                                 .transaction
                                 .transaction
-                                .decode()
+                                .decode_versioned()
                                 .expect("Successful decode");
-                            let json_transaction = EncodedTransaction::encode(
+                            let json_transaction = EncodedTransaction::encode_versioned(
                                 decoded_transaction.clone(),
+                                None,
                                 UiTransactionEncoding::Json,
                             );
 
@@ -1116,12 +1147,42 @@ fn process_confirm(
     }
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn process_decode_transaction(config: &CliConfig, transaction: &Transaction) -> ProcessResult {
-    let sigverify_status = CliSignatureVerificationStatus::verify_transaction(transaction);
+fn process_decode_transaction(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    transaction: &VersionedTransaction,
+) -> ProcessResult {
+    let sigverify_status =
+        CliSignatureVerificationStatus::verify_versioned_transaction(transaction);
+    let (decoded_transaction, loaded_addresses) = match &transaction.message {
+        VersionedMessage::Legacy(message) => (
+            Transaction {
+                signatures: transaction.signatures.clone(),
+                message: message.clone(),
+            },
+            None,
+        ),
+        VersionedMessage::V0(message) => {
+            let loaded_addresses = load_lookup_table_addresses(rpc_client, message)?;
+            let legacy_message =
+                LoadedMessage::new(message.clone(), loaded_addresses.clone()).to_legacy_message();
+            (
+                Transaction {
+                    signatures: transaction.signatures.clone(),
+                    message: legacy_message,
+                },
+                Some(loaded_addresses),
+            )
+        }
+    };
     let decode_transaction = CliTransaction {
-        decoded_transaction: transaction.clone(),
-        transaction: EncodedTransaction::encode(transaction.clone(), UiTransactionEncoding::Json),
+        // Listed with the accounts loaded from lookup tables inline
+        decoded_transaction: decoded_transaction.into(),
+        transaction: EncodedTransaction::encode_versioned(
+            transaction.clone(),
+            loaded_addresses,
+            UiTransactionEncoding::Json,
+        ),
         meta: None,
         block_time: None,
         slot: None,
@@ -1131,6 +1192,55 @@ fn process_decode_transaction(config: &CliConfig, transaction: &Transaction) ->
     Ok(config.output_format.formatted_string(&decode_transaction))
 }
 
+/// Resolves the addresses referenced by a v0 message from the current state of
+/// its address lookup tables
+fn load_lookup_table_addresses(
+    rpc_client: &RpcClient,
+    message: &v0::Message,
+) -> Result<LoadedAddresses, Box<dyn std::error::Error>> {
+    let mut loaded_addresses = LoadedAddresses::default();
+    for lookup in &message.address_table_lookups {
+        let account = rpc_client.get_account(&lookup.account_key)?;
+        if account.owner != solana_address_lookup_table_program::id() {
+            return Err(CliError::BadParameter(format!(
+                "{} is not an address lookup table",
+                lookup.account_key
+            ))
+            .into());
+        }
+        let lookup_table = AddressLookupTable::deserialize(&account.data).map_err(|_| {
+            CliError::BadParameter(format!(
+                "Invalid address lookup table data in {}",
+                lookup.account_key
+            ))
+        })?;
+        let lookup_addresses = |indexes: &[u8]| {
+            indexes
+                .iter()
+                .map(|index| {
+                    lookup_table
+                        .addresses
+                        .get(usize::from(*index))
+                        .copied()
+                        .ok_or_else(|| {
+                            CliError::BadParameter(format!(
+                                "Invalid index {} into address lookup table {}",
+                                index, lookup.account_key
+                            ))
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        loaded_addresses
+            .writable
+            .extend(lookup_addresses(&lookup.writable_indexes)?);
+        loaded_addresses
+            .readonly
+            .extend(lookup_addresses(&lookup.readonly_indexes)?);
+    }
+    Ok(loaded_addresses)
+}
+
 fn process_show_account(
     rpc_client: &RpcClient,
     config: &CliConfig,
@@ -1676,6 +1786,48 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             memo.as_ref(),
             *fee_payer,
         ),
+        CliCommand::RedelegateStake {
+            stake_account_pubkey,
+            vote_account_pubkey,
+            stake_authority,
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            nonce_account,
+            nonce_authority,
+            memo,
+            redelegation_stake_account,
+            seed,
+            fee_payer,
+        } => process_redelegate_stake(
+            &rpc_client,
+            config,
+            stake_account_pubkey,
+            vote_account_pubkey,
+            *stake_authority,
+            *sign_only,
+            *dump_transaction_message,
+            blockhash_query,
+            *nonce_account,
+            *nonce_authority,
+            memo.as_ref(),
+            *redelegation_stake_account,
+            seed,
+            *fee_payer,
+        ),
+        CliCommand::DeactivateDelinquentStake {
+            stake_account_pubkey,
+            reference_vote_account_pubkey,
+            memo,
+            fee_payer,
+        } => process_deactivate_delinquent_stake(
+            &rpc_client,
+            config,
+            stake_account_pubkey,
+            reference_vote_account_pubkey.as_ref(),
+            memo.as_ref(),
+            *fee_payer,
+        ),
         CliCommand::ShowStakeAccount {
             pubkey: stake_account_pubkey,
             use_lamports_unit,
@@ -1894,7 +2046,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
         // Confirm the last client transaction by signature
         CliCommand::Confirm(signature) => process_confirm(&rpc_client, config, signature),
         CliCommand::DecodeTransaction(transaction) => {
-            process_decode_transaction(config, transaction)
+            process_decode_transaction(&rpc_client, config, transaction)
         }
         CliCommand::ResolveSigner(path) => {
             if let Some(path) = path {
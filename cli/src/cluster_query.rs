@@ -1973,7 +1973,7 @@ pub fn process_transaction_history(
                             &confirmed_transaction
                                 .transaction
                                 .transaction
-                                .decode()
+                                .decode_versioned()
                                 .expect("Successful decode"),
                             &confirmed_transaction.transaction.meta,
                             "  ",
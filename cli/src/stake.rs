@@ -47,7 +47,9 @@ use solana_sdk::{
 use solana_stake_program::{
     stake_instruction::{self, LockupArgs, StakeError},
     stake_state::{
-        Authorized, Lockup, Meta, StakeAuthorize, StakeState, MIN_DELEGATE_STAKE_AMOUNT,
+        acceptable_reference_epoch_credits, eligible_for_deactivate_delinquent, Authorized, Lockup,
+        Meta, StakeAuthorize, StakeState, MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION,
+        MIN_DELEGATE_STAKE_AMOUNT,
     },
 };
 use solana_vote_program::vote_state::VoteState;
@@ -329,6 +331,68 @@ impl StakeSubCommands for App<'_, '_> {
                 .nonce_args(false)
                 .arg(fee_payer_arg())
         )
+        .subcommand(
+            SubCommand::with_name("redelegate-stake")
+                .about("Move the active stake of a stake account into a new stake account \
+                        delegated to another vote account, without waiting for a full \
+                        cooldown")
+                .arg(
+                    pubkey!(Arg::with_name("stake_account_pubkey")
+                        .index(1)
+                        .value_name("STAKE_ACCOUNT_ADDRESS")
+                        .required(true),
+                        "Fully active stake account to redelegate")
+                )
+                .arg(
+                    pubkey!(Arg::with_name("vote_account_pubkey")
+                        .index(2)
+                        .value_name("VOTE_ACCOUNT_ADDRESS")
+                        .required(true),
+                        "The vote account to which the stake will be redelegated")
+                )
+                .arg(
+                    Arg::with_name("redelegation_stake_account")
+                        .index(3)
+                        .value_name("REDELEGATION_STAKE_ACCOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_signer)
+                        .help("Keypair of the new stake account that will hold the \
+                               redelegated stake")
+                )
+                .arg(
+                    Arg::with_name("seed")
+                        .long("seed")
+                        .value_name("STRING")
+                        .takes_value(true)
+                        .help("Seed for address generation; if specified, the resulting account \
+                               will be at a derived address of REDELEGATION_STAKE_ACCOUNT")
+                )
+                .arg(stake_authority_arg())
+                .offline_args()
+                .nonce_args(false)
+                .arg(fee_payer_arg())
+        )
+        .subcommand(
+            SubCommand::with_name("deactivate-delinquent-stake")
+                .about("Deactivate stake delegated to a vote account that has stopped voting. \
+                        No stake authority is required")
+                .arg(
+                    pubkey!(Arg::with_name("stake_account_pubkey")
+                        .index(1)
+                        .value_name("STAKE_ACCOUNT_ADDRESS")
+                        .required(true),
+                        "Stake account delegated to a delinquent vote account")
+                )
+                .arg(
+                    pubkey!(Arg::with_name("reference_vote_account_pubkey")
+                        .long("reference-vote-account")
+                        .value_name("VOTE_ACCOUNT_ADDRESS"),
+                        "Vote account that has voted in each of the recent epochs \
+                         [default: the highest staked vote account that qualifies]")
+                )
+                .arg(fee_payer_arg())
+        )
         .subcommand(
             SubCommand::with_name("withdraw-stake")
                 .about("Withdraw the unstaked SOPHON from the stake account")
@@ -765,6 +829,84 @@ pub fn parse_merge_stake(
     })
 }
 
+pub fn parse_redelegate_stake(
+    matches: &ArgMatches<'_>,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let stake_account_pubkey =
+        pubkey_of_signer(matches, "stake_account_pubkey", wallet_manager)?.unwrap();
+    let vote_account_pubkey =
+        pubkey_of_signer(matches, "vote_account_pubkey", wallet_manager)?.unwrap();
+    let (redelegation_stake_account, redelegation_stake_account_pubkey) =
+        signer_of(matches, "redelegation_stake_account", wallet_manager)?;
+    let seed = matches.value_of("seed").map(|s| s.to_string());
+
+    let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
+    let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let blockhash_query = BlockhashQuery::new_from_matches(matches);
+    let nonce_account = pubkey_of(matches, NONCE_ARG.name);
+    let memo = matches.value_of(MEMO_ARG.name).map(String::from);
+    let (stake_authority, stake_authority_pubkey) =
+        signer_of(matches, STAKE_AUTHORITY_ARG.name, wallet_manager)?;
+    let (nonce_authority, nonce_authority_pubkey) =
+        signer_of(matches, NONCE_AUTHORITY_ARG.name, wallet_manager)?;
+    let (fee_payer, fee_payer_pubkey) = signer_of(matches, FEE_PAYER_ARG.name, wallet_manager)?;
+
+    let mut bulk_signers = vec![stake_authority, fee_payer, redelegation_stake_account];
+    if nonce_account.is_some() {
+        bulk_signers.push(nonce_authority);
+    }
+    let signer_info =
+        default_signer.generate_unique_signers(bulk_signers, matches, wallet_manager)?;
+
+    Ok(CliCommandInfo {
+        command: CliCommand::RedelegateStake {
+            stake_account_pubkey,
+            vote_account_pubkey,
+            stake_authority: signer_info.index_of(stake_authority_pubkey).unwrap(),
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            nonce_account,
+            nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
+            memo,
+            redelegation_stake_account: signer_info
+                .index_of(redelegation_stake_account_pubkey)
+                .unwrap(),
+            seed,
+            fee_payer: signer_info.index_of(fee_payer_pubkey).unwrap(),
+        },
+        signers: signer_info.signers,
+    })
+}
+
+pub fn parse_deactivate_delinquent_stake(
+    matches: &ArgMatches<'_>,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let stake_account_pubkey =
+        pubkey_of_signer(matches, "stake_account_pubkey", wallet_manager)?.unwrap();
+    let reference_vote_account_pubkey =
+        pubkey_of_signer(matches, "reference_vote_account_pubkey", wallet_manager)?;
+    let memo = matches.value_of(MEMO_ARG.name).map(String::from);
+    let (fee_payer, fee_payer_pubkey) = signer_of(matches, FEE_PAYER_ARG.name, wallet_manager)?;
+
+    let signer_info =
+        default_signer.generate_unique_signers(vec![fee_payer], matches, wallet_manager)?;
+
+    Ok(CliCommandInfo {
+        command: CliCommand::DeactivateDelinquentStake {
+            stake_account_pubkey,
+            reference_vote_account_pubkey,
+            memo,
+            fee_payer: signer_info.index_of(fee_payer_pubkey).unwrap(),
+        },
+        signers: signer_info.signers,
+    })
+}
+
 pub fn parse_stake_deactivate_stake(
     matches: &ArgMatches<'_>,
     default_signer: &DefaultSigner,
@@ -1624,6 +1766,272 @@ pub fn process_merge_stake(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+pub fn process_redelegate_stake(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    stake_account_pubkey: &Pubkey,
+    vote_account_pubkey: &Pubkey,
+    stake_authority: SignerIndex,
+    sign_only: bool,
+    dump_transaction_message: bool,
+    blockhash_query: &BlockhashQuery,
+    nonce_account: Option<Pubkey>,
+    nonce_authority: SignerIndex,
+    memo: Option<&String>,
+    redelegation_stake_account: SignerIndex,
+    redelegation_stake_account_seed: &Option<String>,
+    fee_payer: SignerIndex,
+) -> ProcessResult {
+    let redelegation_stake_account = config.signers[redelegation_stake_account];
+    let fee_payer = config.signers[fee_payer];
+
+    if redelegation_stake_account_seed.is_none() {
+        check_unique_pubkeys(
+            (&fee_payer.pubkey(), "fee-payer keypair".to_string()),
+            (
+                &redelegation_stake_account.pubkey(),
+                "redelegation_stake_account".to_string(),
+            ),
+        )?;
+    }
+    check_unique_pubkeys(
+        (&fee_payer.pubkey(), "fee-payer keypair".to_string()),
+        (stake_account_pubkey, "stake_account".to_string()),
+    )?;
+    check_unique_pubkeys(
+        (stake_account_pubkey, "stake_account".to_string()),
+        (
+            &redelegation_stake_account.pubkey(),
+            "redelegation_stake_account".to_string(),
+        ),
+    )?;
+
+    let stake_authority = config.signers[stake_authority];
+
+    let redelegation_stake_account_address = if let Some(seed) = redelegation_stake_account_seed {
+        Pubkey::create_with_seed(
+            &redelegation_stake_account.pubkey(),
+            seed,
+            &solana_stake_program::id(),
+        )?
+    } else {
+        redelegation_stake_account.pubkey()
+    };
+
+    if !sign_only {
+        if let Ok(stake_account) = rpc_client.get_account(&redelegation_stake_account_address) {
+            let err_msg = if stake_account.owner == solana_stake_program::id() {
+                format!(
+                    "Stake account {} already exists",
+                    redelegation_stake_account_address
+                )
+            } else {
+                format!(
+                    "Account {} already exists and is not a stake account",
+                    redelegation_stake_account_address
+                )
+            };
+            return Err(CliError::BadParameter(err_msg).into());
+        }
+
+        let vote_account = rpc_client.get_account(vote_account_pubkey).map_err(|err| {
+            CliError::RpcRequestError(format!(
+                "Vote account not found: {}. error: {}",
+                vote_account_pubkey, err,
+            ))
+        })?;
+        if vote_account.owner != solana_vote_program::id() {
+            return Err(CliError::BadParameter(format!(
+                "{} is not a vote account",
+                vote_account_pubkey
+            ))
+            .into());
+        }
+    }
+
+    let (recent_blockhash, fee_calculator) =
+        blockhash_query.get_blockhash_and_fee_calculator(rpc_client, config.commitment)?;
+
+    let ixs = if let Some(seed) = redelegation_stake_account_seed {
+        stake_instruction::redelegate_with_seed(
+            stake_account_pubkey,
+            &stake_authority.pubkey(),
+            vote_account_pubkey,
+            &redelegation_stake_account_address,
+            &redelegation_stake_account.pubkey(),
+            seed,
+        )
+        .with_memo(memo)
+    } else {
+        stake_instruction::redelegate(
+            stake_account_pubkey,
+            &stake_authority.pubkey(),
+            vote_account_pubkey,
+            &redelegation_stake_account_address,
+        )
+        .with_memo(memo)
+    };
+
+    let nonce_authority = config.signers[nonce_authority];
+
+    let message = if let Some(nonce_account) = &nonce_account {
+        Message::new_with_nonce(
+            ixs,
+            Some(&fee_payer.pubkey()),
+            nonce_account,
+            &nonce_authority.pubkey(),
+        )
+    } else {
+        Message::new(&ixs, Some(&fee_payer.pubkey()))
+    };
+    let mut tx = Transaction::new_unsigned(message);
+
+    if sign_only {
+        tx.try_partial_sign(&config.signers, recent_blockhash)?;
+        return_signers_with_config(
+            &tx,
+            &config.output_format,
+            &ReturnSignersConfig {
+                dump_transaction_message,
+            },
+        )
+    } else {
+        tx.try_sign(&config.signers, recent_blockhash)?;
+        if let Some(nonce_account) = &nonce_account {
+            let nonce_account = nonce_utils::get_account_with_commitment(
+                rpc_client,
+                nonce_account,
+                config.commitment,
+            )?;
+            check_nonce_account(&nonce_account, &nonce_authority.pubkey(), &recent_blockhash)?;
+        }
+        check_account_for_fee_with_commitment(
+            rpc_client,
+            &tx.message.account_keys[0],
+            &fee_calculator,
+            &tx.message,
+            config.commitment,
+        )?;
+        let result = rpc_client.send_and_confirm_transaction_with_spinner(&tx);
+        log_instruction_custom_error::<StakeError>(result, config)
+    }
+}
+
+fn get_vote_state(
+    rpc_client: &RpcClient,
+    vote_account_pubkey: &Pubkey,
+) -> Result<VoteState, CliError> {
+    let vote_account_data = rpc_client
+        .get_account(vote_account_pubkey)
+        .map_err(|err| {
+            CliError::RpcRequestError(format!(
+                "Vote account not found: {}. error: {}",
+                vote_account_pubkey, err,
+            ))
+        })?
+        .data;
+    VoteState::deserialize(&vote_account_data).map_err(|_| {
+        CliError::RpcRequestError(
+            "Account data could not be deserialized to vote state".to_string(),
+        )
+    })
+}
+
+pub fn process_deactivate_delinquent_stake(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    stake_account_pubkey: &Pubkey,
+    reference_vote_account_pubkey: Option<&Pubkey>,
+    memo: Option<&String>,
+    fee_payer: SignerIndex,
+) -> ProcessResult {
+    let stake_account = rpc_client.get_account(stake_account_pubkey)?;
+    if stake_account.owner != solana_stake_program::id() {
+        return Err(CliError::BadParameter(format!(
+            "{} is not a stake account",
+            stake_account_pubkey,
+        ))
+        .into());
+    }
+    let delinquent_vote_account_pubkey = match stake_account.state() {
+        Ok(StakeState::Stake(_, stake)) => stake.delegation.voter_pubkey,
+        _ => {
+            return Err(CliError::BadParameter(format!(
+                "{} is not a delegated stake account",
+                stake_account_pubkey,
+            ))
+            .into())
+        }
+    };
+
+    let current_epoch = rpc_client.get_epoch_info()?.epoch;
+
+    let delinquent_vote_state = get_vote_state(rpc_client, &delinquent_vote_account_pubkey)?;
+    if !eligible_for_deactivate_delinquent(delinquent_vote_state.epoch_credits(), current_epoch) {
+        return Err(CliError::BadParameter(format!(
+            "Stake account {} is delegated to vote account {}, \
+             which has voted within the last {} epochs",
+            stake_account_pubkey,
+            delinquent_vote_account_pubkey,
+            MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION,
+        ))
+        .into());
+    }
+
+    let reference_vote_account_pubkey = if let Some(reference_vote_account_pubkey) =
+        reference_vote_account_pubkey
+    {
+        let reference_vote_state = get_vote_state(rpc_client, reference_vote_account_pubkey)?;
+        if !acceptable_reference_epoch_credits(reference_vote_state.epoch_credits(), current_epoch)
+        {
+            return Err(CliError::BadParameter(format!(
+                "Reference vote account {} has not voted in each of the last {} epochs",
+                reference_vote_account_pubkey, MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION,
+            ))
+            .into());
+        }
+        *reference_vote_account_pubkey
+    } else {
+        let mut current_vote_accounts = rpc_client.get_vote_accounts()?.current;
+        current_vote_accounts.sort_by(|a, b| b.activated_stake.cmp(&a.activated_stake));
+        current_vote_accounts
+            .into_iter()
+            .find(|vote_account_info| {
+                acceptable_reference_epoch_credits(&vote_account_info.epoch_credits, current_epoch)
+            })
+            .and_then(|vote_account_info| vote_account_info.vote_pubkey.parse().ok())
+            .ok_or_else(|| {
+                CliError::RpcRequestError(
+                    "Unable to find a suitable reference vote account".to_string(),
+                )
+            })?
+    };
+
+    let (recent_blockhash, fee_calculator) = rpc_client.get_recent_blockhash()?;
+
+    let ixs = vec![stake_instruction::deactivate_delinquent_stake(
+        stake_account_pubkey,
+        &delinquent_vote_account_pubkey,
+        &reference_vote_account_pubkey,
+    )]
+    .with_memo(memo);
+    let fee_payer = config.signers[fee_payer];
+
+    let message = Message::new(&ixs, Some(&fee_payer.pubkey()));
+    let mut tx = Transaction::new_unsigned(message);
+    tx.try_sign(&config.signers, recent_blockhash)?;
+    check_account_for_fee_with_commitment(
+        rpc_client,
+        &tx.message.account_keys[0],
+        &fee_calculator,
+        &tx.message,
+        config.commitment,
+    )?;
+    let result = rpc_client.send_and_confirm_transaction_with_spinner(&tx);
+    log_instruction_custom_error::<StakeError>(result, config)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn process_stake_set_lockup(
     rpc_client: &RpcClient,
@@ -3647,5 +4055,84 @@ mod tests {
                 signers: vec![read_keypair_file(&default_keypair_file).unwrap().into(),],
             }
         );
+
+        // Test RedelegateStake SubCommand
+        let (redelegation_stake_account_keypair_file, mut tmp_file) = make_tmp_file();
+        let redelegation_stake_account_keypair = Keypair::new();
+        write_keypair(&redelegation_stake_account_keypair, tmp_file.as_file_mut()).unwrap();
+
+        let vote_account_pubkey = solana_sdk::pubkey::new_rand();
+        let test_redelegate_stake = test_commands.clone().get_matches_from(vec![
+            "test",
+            "redelegate-stake",
+            &keypair_file,
+            &vote_account_pubkey.to_string(),
+            &redelegation_stake_account_keypair_file,
+        ]);
+        assert_eq!(
+            parse_command(&test_redelegate_stake, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::RedelegateStake {
+                    stake_account_pubkey: stake_account_keypair.pubkey(),
+                    vote_account_pubkey,
+                    stake_authority: 0,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    blockhash_query: BlockhashQuery::default(),
+                    nonce_account: None,
+                    nonce_authority: 0,
+                    memo: None,
+                    redelegation_stake_account: 1,
+                    seed: None,
+                    fee_payer: 0,
+                },
+                signers: vec![
+                    read_keypair_file(&default_keypair_file).unwrap().into(),
+                    read_keypair_file(&redelegation_stake_account_keypair_file)
+                        .unwrap()
+                        .into()
+                ],
+            }
+        );
+
+        // Test DeactivateDelinquentStake SubCommand
+        let test_deactivate_delinquent = test_commands.clone().get_matches_from(vec![
+            "test",
+            "deactivate-delinquent-stake",
+            &keypair_file,
+        ]);
+        assert_eq!(
+            parse_command(&test_deactivate_delinquent, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::DeactivateDelinquentStake {
+                    stake_account_pubkey: stake_account_keypair.pubkey(),
+                    reference_vote_account_pubkey: None,
+                    memo: None,
+                    fee_payer: 0,
+                },
+                signers: vec![read_keypair_file(&default_keypair_file).unwrap().into(),],
+            }
+        );
+
+        let reference_vote_account_pubkey = solana_sdk::pubkey::new_rand();
+        let test_deactivate_delinquent = test_commands.clone().get_matches_from(vec![
+            "test",
+            "deactivate-delinquent-stake",
+            &keypair_file,
+            "--reference-vote-account",
+            &reference_vote_account_pubkey.to_string(),
+        ]);
+        assert_eq!(
+            parse_command(&test_deactivate_delinquent, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::DeactivateDelinquentStake {
+                    stake_account_pubkey: stake_account_keypair.pubkey(),
+                    reference_vote_account_pubkey: Some(reference_vote_account_pubkey),
+                    memo: None,
+                    fee_payer: 0,
+                },
+                signers: vec![read_keypair_file(&default_keypair_file).unwrap().into(),],
+            }
+        );
     }
 }
@@ -0,0 +1,261 @@
+//! Sends transactions to a TPU over QUIC, one unidirectional stream per transaction.
+//!
+//! The client presents a certificate of its identity keypair, so that the TPU can attribute the
+//! connection to a node and grant it stream limits according to its stake.
+
+use {
+    futures_util::future::join_all,
+    quinn::{
+        ClientConfig, ConnectError, Connection, ConnectionError, Endpoint, IdleTimeout,
+        NewConnection, VarInt, WriteError,
+    },
+    rcgen::RcgenError,
+    rustls::client::{ServerCertVerified, ServerCertVerifier},
+    solana_sdk::{
+        quic::{QUIC_KEEP_ALIVE_MS, QUIC_MAX_TIMEOUT_MS, QUIC_PORT_OFFSET, QUIC_TPU_ALPN},
+        signature::Keypair,
+    },
+    solana_streamer::tls_certificates::new_self_signed_tls_certificate_chain,
+    std::{
+        collections::HashMap,
+        io,
+        net::{IpAddr, Ipv4Addr, SocketAddr},
+        sync::{Arc, Mutex},
+        time::{Duration, SystemTime},
+    },
+    thiserror::Error,
+    tokio::runtime::{Builder, Runtime},
+};
+
+#[derive(Error, Debug)]
+pub enum QuicClientError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("connect error: {0}")]
+    Connect(#[from] ConnectError),
+
+    #[error("connection error: {0}")]
+    Connection(#[from] ConnectionError),
+
+    #[error("write error: {0}")]
+    Write(#[from] WriteError),
+
+    #[error("certificate error: {0}")]
+    Certificate(#[from] RcgenError),
+
+    #[error("TLS error: {0}")]
+    Tls(#[from] rustls::Error),
+}
+
+type Result<T> = std::result::Result<T, QuicClientError>;
+
+/// Address of the QUIC TPU of a node advertising `tpu_addr` for UDP
+pub fn tpu_quic_addr(tpu_addr: &SocketAddr) -> SocketAddr {
+    SocketAddr::new(
+        tpu_addr.ip(),
+        tpu_addr.port().saturating_add(QUIC_PORT_OFFSET),
+    )
+}
+
+/// TPU certificates are self-signed node identities, there is no authority to check them against
+struct SkipServerVerification;
+
+impl ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+pub struct QuicClient {
+    runtime: Runtime,
+    endpoint: Endpoint,
+    connections: Mutex<HashMap<SocketAddr, Connection>>,
+}
+
+impl QuicClient {
+    /// Creates a client presenting `identity` to the TPUs it connects to, or an ephemeral
+    /// unstaked identity if there is none
+    pub fn new(identity: Option<&Keypair>) -> Result<Self> {
+        let ephemeral_identity;
+        let identity = match identity {
+            Some(identity) => identity,
+            None => {
+                ephemeral_identity = Keypair::new();
+                &ephemeral_identity
+            }
+        };
+        let (cert_chain, private_key) =
+            new_self_signed_tls_certificate_chain(identity, IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)))?;
+
+        let mut crypto = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(SkipServerVerification))
+            .with_single_cert(cert_chain, private_key)?;
+        crypto.enable_early_data = true;
+        crypto.alpn_protocols = vec![QUIC_TPU_ALPN.to_vec()];
+
+        let mut client_config = ClientConfig::new(Arc::new(crypto));
+        let transport_config = Arc::get_mut(&mut client_config.transport).unwrap();
+        transport_config
+            .max_idle_timeout(Some(IdleTimeout::from(VarInt::from(QUIC_MAX_TIMEOUT_MS))));
+        transport_config.keep_alive_interval(Some(Duration::from_millis(QUIC_KEEP_ALIVE_MS)));
+
+        let runtime = Builder::new_multi_thread()
+            .thread_name("solana-quic-client")
+            .enable_all()
+            .build()?;
+        let mut endpoint = {
+            let _guard = runtime.enter();
+            Endpoint::client(SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), 0))?
+        };
+        endpoint.set_default_client_config(client_config);
+
+        Ok(Self {
+            runtime,
+            endpoint,
+            connections: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Sends a transaction to the QUIC TPU at `tpu_quic_addr`
+    pub fn send_wire_transaction(
+        &self,
+        tpu_quic_addr: &SocketAddr,
+        wire_transaction: &[u8],
+    ) -> Result<()> {
+        self.send_wire_transaction_batch(tpu_quic_addr, &[wire_transaction])
+    }
+
+    /// Sends transactions concurrently over a single connection to the QUIC TPU at
+    /// `tpu_quic_addr`
+    pub fn send_wire_transaction_batch<T: AsRef<[u8]>>(
+        &self,
+        tpu_quic_addr: &SocketAddr,
+        wire_transactions: &[T],
+    ) -> Result<()> {
+        self.runtime.block_on(async {
+            let connection = self.connection(tpu_quic_addr).await?;
+            let result = Self::send_on_connection(&connection, wire_transactions).await;
+            if let Err(QuicClientError::Connection(_)) = result {
+                // The cached connection went stale, reconnect once
+                self.connections.lock().unwrap().remove(tpu_quic_addr);
+                let connection = self.connection(tpu_quic_addr).await?;
+                return Self::send_on_connection(&connection, wire_transactions).await;
+            }
+            result
+        })
+    }
+
+    async fn connection(&self, addr: &SocketAddr) -> Result<Connection> {
+        if let Some(connection) = self.connections.lock().unwrap().get(addr) {
+            return Ok(connection.clone());
+        }
+        let NewConnection { connection, .. } = self.endpoint.connect(*addr, "connect")?.await?;
+        self.connections
+            .lock()
+            .unwrap()
+            .insert(*addr, connection.clone());
+        Ok(connection)
+    }
+
+    async fn send_on_connection<T: AsRef<[u8]>>(
+        connection: &Connection,
+        wire_transactions: &[T],
+    ) -> Result<()> {
+        let results = join_all(wire_transactions.iter().map(|wire_transaction| async move {
+            let mut send_stream = connection.open_uni().await?;
+            send_stream.write_all(wire_transaction.as_ref()).await?;
+            send_stream.finish().await?;
+            Ok::<(), QuicClientError>(())
+        }))
+        .await;
+        results.into_iter().collect()
+    }
+}
+
+impl Drop for QuicClient {
+    fn drop(&mut self) {
+        self.endpoint.close(VarInt::from(0u8), b"done");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::signature::Signer,
+        solana_streamer::{quic::spawn_server, staked_nodes::StakedNodes},
+        std::{
+            net::UdpSocket,
+            sync::{
+                atomic::{AtomicBool, Ordering},
+                mpsc::channel,
+                RwLock,
+            },
+        },
+    };
+
+    #[test]
+    fn test_quic_client_loopback() {
+        solana_logger::setup();
+        let server_keypair = Keypair::new();
+        let client_keypair = Keypair::new();
+        let sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let server_addr = sock.local_addr().unwrap();
+        let (sender, receiver) = channel();
+        let exit = Arc::new(AtomicBool::new(false));
+        let staked_nodes = Arc::new(RwLock::new(StakedNodes::new(
+            vec![(client_keypair.pubkey(), 1)].into_iter().collect(),
+        )));
+        let server = spawn_server(
+            sock,
+            &server_keypair,
+            server_addr.ip(),
+            sender,
+            exit.clone(),
+            staked_nodes,
+        )
+        .unwrap();
+
+        let client = QuicClient::new(Some(&client_keypair)).unwrap();
+        let wire_transactions: Vec<_> = (0..10u8).map(|i| vec![i; 100 + i as usize]).collect();
+        client
+            .send_wire_transaction_batch(&server_addr, &wire_transactions)
+            .unwrap();
+
+        let mut received = vec![];
+        while received.len() < wire_transactions.len() {
+            let packets = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+            received.extend(
+                packets
+                    .packets
+                    .iter()
+                    .map(|packet| packet.data[..packet.meta.size].to_vec()),
+            );
+        }
+        received.sort();
+        assert_eq!(received, wire_transactions);
+
+        drop(client);
+        exit.store(true, Ordering::Relaxed);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_tpu_quic_addr() {
+        let tpu_addr: SocketAddr = "127.0.0.1:8003".parse().unwrap();
+        assert_eq!(
+            tpu_quic_addr(&tpu_addr),
+            "127.0.0.1:8009".parse::<SocketAddr>().unwrap()
+        );
+    }
+}
@@ -346,6 +346,18 @@ impl RpcClient {
         self.send(RpcRequest::GetSignatureStatuses, json!([signatures]))
     }
 
+    /// Returns how many times the node sent a transaction it is still retrying, or `None` once
+    /// the transaction has landed, expired or exhausted its retries
+    pub fn get_send_transaction_status(
+        &self,
+        signature: &Signature,
+    ) -> ClientResult<Option<RpcSendTransactionStatus>> {
+        self.send(
+            RpcRequest::GetSendTransactionStatus,
+            json!([signature.to_string()]),
+        )
+    }
+
     pub fn get_signature_statuses_with_history(
         &self,
         signatures: &[Signature],
@@ -798,6 +810,19 @@ impl RpcClient {
         self.send(RpcRequest::GetRecentPerformanceSamples, json!([limit]))
     }
 
+    /// Returns the minimum compute unit price paid in recent slots by transactions
+    /// that write lock all of `addresses`
+    pub fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<RpcPrioritizationFee>> {
+        let addresses: Vec<_> = addresses
+            .iter()
+            .map(|address| address.to_string())
+            .collect();
+        self.send(RpcRequest::GetRecentPrioritizationFees, json!([addresses]))
+    }
+
     pub fn get_identity(&self) -> ClientResult<Pubkey> {
         let rpc_identity: RpcIdentity = self.send(RpcRequest::GetIdentity, Value::Null)?;
 
@@ -1817,6 +1842,21 @@ impl RpcClient {
         .map(|h| h.0)
     }
 
+    pub fn get_evm_block_number(&self) -> ClientResult<u64> {
+        self.send::<evm_rpc::Hex<u64>>(RpcRequest::EthBlockNumber, Value::Null)
+            .map(|h| h.0)
+    }
+
+    pub fn get_evm_block_by_number(
+        &self,
+        block_number: u64,
+    ) -> ClientResult<Option<evm_rpc::RPCBlock>> {
+        self.send::<Option<evm_rpc::RPCBlock>>(
+            RpcRequest::EthGetBlockByNumber,
+            json!([evm_rpc::Hex(block_number), false]),
+        )
+    }
+
     pub fn get_evm_transaction_receipt(&self, hash: &evm_state::H256) -> ClientResult<Option<evm_rpc::RPCReceipt>> {
         self.send::<Option<evm_rpc::RPCReceipt>>(
             RpcRequest::EthGetTransactionReceipt,
@@ -21,6 +21,9 @@ pub struct RpcSendTransactionConfig {
     pub skip_preflight: bool,
     pub preflight_commitment: Option<CommitmentLevel>,
     pub encoding: Option<UiTransactionEncoding>,
+    /// Maximum number of times the node retries sending the transaction to the leaders,
+    /// bounded by the node configuration. Unspecified, the node default applies.
+    pub max_retries: Option<usize>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
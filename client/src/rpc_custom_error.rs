@@ -156,6 +156,7 @@ impl From<RpcCustomError> for Error {
                         AccountIndex::SophonAccountOwner => "Sophon Account Owner",
                         AccountIndex::SophonAccountOperational => "Sophon Account Operational",
                         AccountIndex::SophonRelyingOwner => "Sophon Relying Owner",
+                        AccountIndex::Custom(_) => "Custom",
                     }),
                 data: None,
             },
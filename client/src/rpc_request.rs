@@ -48,6 +48,8 @@ pub enum RpcRequest {
     GetProgramAccounts,
     GetRecentBlockhash,
     GetRecentPerformanceSamples,
+    GetRecentPrioritizationFees,
+    GetSendTransactionStatus,
     GetSnapshotSlot,
     GetSignatureStatuses,
     GetSlot,
@@ -105,6 +107,8 @@ pub enum RpcRequest {
     EthTraceCallMany,
     EthTraceReplayTransaction,
     EthTraceReplayBlock,
+    EvmGetTokenTransfers,
+    EvmGetTokenBalances,
 
     /// Sophon Account scope
     GetSophonAccountsByOperationalKey,
@@ -151,6 +155,8 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetProgramAccounts => "getProgramAccounts",
             RpcRequest::GetRecentBlockhash => "getRecentBlockhash",
             RpcRequest::GetRecentPerformanceSamples => "getRecentPerformanceSamples",
+            RpcRequest::GetRecentPrioritizationFees => "getRecentPrioritizationFees",
+            RpcRequest::GetSendTransactionStatus => "getSendTransactionStatus",
             RpcRequest::GetSnapshotSlot => "getSnapshotSlot",
             RpcRequest::GetSignatureStatuses => "getSignatureStatuses",
             RpcRequest::GetSlot => "getSlot",
@@ -197,6 +203,8 @@ impl fmt::Display for RpcRequest {
             RpcRequest::EthGetTransactionByBlockNumberAndIndex => "eth_getTransactionByBlockNumberAndIndex",
             RpcRequest::EthTraceReplayTransaction => "trace_replayTransaction",
             RpcRequest::EthTraceReplayBlock => "trace_replayBlockTransactions",
+            RpcRequest::EvmGetTokenTransfers => "evm_getTokenTransfers",
+            RpcRequest::EvmGetTokenBalances => "evm_getTokenBalances",
             RpcRequest::EthEstimateGas => "eth_estimateGas",
             RpcRequest::EthGetLogs => "eth_getLogs",
             RpcRequest::EthSyncing => "eth_syncing",
@@ -217,6 +225,7 @@ pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
 pub const NUM_LARGEST_ACCOUNTS: usize = 20;
 pub const MAX_GET_PROGRAM_ACCOUNT_FILTERS: usize = 4;
 pub const MAX_GET_SLOT_LEADERS: usize = 5000;
+pub const MAX_GET_PRIORITIZATION_FEES_QUERY_ITEMS: usize = 128;
 
 // Validators that are this number of slots behind are considered delinquent
 pub const DELINQUENT_VALIDATOR_SLOT_DISTANCE: u64 = 128;
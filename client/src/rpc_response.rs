@@ -1,5 +1,7 @@
 use {
     crate::client_error,
+    evm_rpc::{Hex, RPCReceipt},
+    evm_state::{H160, H256, U256},
     solana_account_decoder::{parse_token::UiTokenAmount, UiAccount},
     solana_sdk::{
         clock::{Epoch, Slot, UnixTimestamp},
@@ -9,8 +11,13 @@ use {
     },
     solana_transaction_status::{
         ConfirmedTransactionStatusWithSignature, TransactionConfirmationStatus,
+        UiInnerInstructions, UiTransactionReturnData, UiTransactionTokenBalance,
+    },
+    std::{
+        collections::{BTreeMap, HashMap},
+        fmt,
+        net::SocketAddr,
     },
-    std::{collections::HashMap, fmt, net::SocketAddr},
 };
 
 pub type RpcResult<T> = client_error::Result<Response<T>>;
@@ -321,6 +328,37 @@ pub struct RpcSimulateTransactionResult {
     pub err: Option<TransactionError>,
     pub logs: Option<Vec<String>>,
     pub accounts: Option<Vec<Option<UiAccount>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_data: Option<UiTransactionReturnData>,
+    /// Total compute units consumed by the transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub units_consumed: Option<u64>,
+    /// Compute units consumed by each executed instruction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instruction_units_consumed: Option<Vec<u64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inner_instructions: Option<Vec<UiInnerInstructions>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_token_balances: Option<Vec<UiTransactionTokenBalance>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_token_balances: Option<Vec<UiTransactionTokenBalance>>,
+    /// Receipts of the EVM transactions executed by the simulated transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evm_receipts: Option<Vec<RPCReceipt>>,
+    /// EVM accounts changed by the simulated transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evm_state_changes: Option<Vec<RpcEvmStateChange>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcEvmStateChange {
+    pub address: Hex<H160>,
+    /// `None` if the account was removed
+    pub nonce: Option<Hex<U256>>,
+    pub balance: Option<Hex<U256>>,
+    pub code_hash: Option<Hex<H256>>,
+    pub storage: BTreeMap<Hex<H256>, Hex<H256>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -391,6 +429,23 @@ pub struct RpcPerfSample {
     pub sample_period_secs: u16,
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcPrioritizationFee {
+    pub slot: Slot,
+    /// Compute unit price in micro-lamports
+    pub prioritization_fee: u64,
+}
+
+/// Status of a transaction queued for retries by the node it was sent to
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSendTransactionStatus {
+    /// Number of times the node sent the transaction to the leaders
+    pub send_count: usize,
+    pub last_valid_slot: Slot,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcInflationReward {
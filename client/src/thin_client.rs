@@ -4,7 +4,12 @@
 //! unstable and may change in future releases.
 
 use {
-    crate::{rpc_client::RpcClient, rpc_config::RpcProgramAccountsConfig, rpc_response::Response},
+    crate::{
+        quic_client::{tpu_quic_addr, QuicClient},
+        rpc_client::RpcClient,
+        rpc_config::RpcProgramAccountsConfig,
+        rpc_response::Response,
+    },
     bincode::{serialize_into, serialized_size},
     log::*,
     solana_sdk::{
@@ -31,7 +36,7 @@ use {
         net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket},
         sync::{
             atomic::{AtomicBool, AtomicUsize, Ordering},
-            RwLock,
+            Arc, RwLock,
         },
         time::{Duration, Instant},
     },
@@ -122,6 +127,7 @@ pub struct ThinClient {
     tpu_addrs: Vec<SocketAddr>,
     rpc_clients: Vec<RpcClient>,
     optimizer: ClientOptimizer,
+    quic_client: Option<Arc<QuicClient>>,
 }
 
 impl ThinClient {
@@ -155,6 +161,7 @@ impl ThinClient {
             tpu_addrs: vec![tpu_addr],
             rpc_clients: vec![rpc_client],
             optimizer: ClientOptimizer::new(0),
+            quic_client: None,
         }
     }
 
@@ -173,13 +180,32 @@ impl ThinClient {
             tpu_addrs,
             rpc_clients,
             optimizer,
+            quic_client: None,
         }
     }
 
+    /// Send transactions to the QUIC port of the Tpu using `quic_client` instead of over UDP
+    pub fn with_quic_client(mut self, quic_client: Arc<QuicClient>) -> Self {
+        self.quic_client = Some(quic_client);
+        self
+    }
+
     fn tpu_addr(&self) -> &SocketAddr {
         &self.tpu_addrs[self.optimizer.best()]
     }
 
+    fn send_wire_transaction(&self, wire_transaction: &[u8]) -> io::Result<()> {
+        match &self.quic_client {
+            Some(quic_client) => quic_client
+                .send_wire_transaction(&tpu_quic_addr(self.tpu_addr()), wire_transaction)
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string())),
+            None => self
+                .transactions_socket
+                .send_to(wire_transaction, &self.tpu_addr())
+                .map(|_| ()),
+        }
+    }
+
     fn rpc_client(&self) -> &RpcClient {
         &self.rpc_clients[self.optimizer.best()]
     }
@@ -225,8 +251,7 @@ impl ThinClient {
             while now.elapsed().as_secs() < wait_time as u64 {
                 if num_confirmed == 0 {
                     // Send the transaction if there has been no confirmation (e.g. the first time)
-                    self.transactions_socket
-                        .send_to(&buf[..], &self.tpu_addr())?;
+                    self.send_wire_transaction(&buf[..])?;
                 }
 
                 if let Ok(confirmed_blocks) = self.poll_for_signature_confirmation(
@@ -578,8 +603,7 @@ impl AsyncClient for ThinClient {
         serialize_into(&mut wr, &transaction)
             .expect("serialize Transaction in pub fn transfer_signed");
         assert!(buf.len() < PACKET_DATA_SIZE);
-        self.transactions_socket
-            .send_to(&buf[..], &self.tpu_addr())?;
+        self.send_wire_transaction(&buf[..])?;
         Ok(transaction.signatures[0])
     }
     fn async_send_message<T: Signers>(
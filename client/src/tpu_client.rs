@@ -1,5 +1,6 @@
 use crate::{
     pubsub_client::{PubsubClient, PubsubClientError, PubsubClientSubscription},
+    quic_client::{tpu_quic_addr, QuicClient, QuicClientError},
     rpc_client::RpcClient,
     rpc_response::SlotUpdate,
 };
@@ -27,6 +28,8 @@ pub enum TpuSenderError {
     RpcError(#[from] crate::client_error::ClientError),
     #[error("IO error: {0:?}")]
     IoError(#[from] std::io::Error),
+    #[error("QUIC error: {0:?}")]
+    QuicError(#[from] QuicClientError),
 }
 
 type Result<T> = std::result::Result<T, TpuSenderError>;
@@ -43,20 +46,24 @@ pub struct TpuClientConfig {
     /// The range of upcoming slots to include when determining which
     /// leaders to send transactions to (min: 1, max: 100)
     pub fanout_slots: u64,
+    /// Send transactions to the leaders' QUIC TPU ports instead of over UDP
+    pub use_quic: bool,
 }
 
 impl Default for TpuClientConfig {
     fn default() -> Self {
         Self {
             fanout_slots: DEFAULT_FANOUT_SLOTS,
+            use_quic: false,
         }
     }
 }
 
-/// Client which sends transactions directly to the current leader's TPU port over UDP.
+/// Client which sends transactions directly to the current leader's TPU port over UDP or QUIC.
 /// The client uses RPC to determine the current leader and fetch node contact info
 pub struct TpuClient {
     send_socket: UdpSocket,
+    quic_client: Option<QuicClient>,
     fanout_slots: u64,
     leader_tpu_service: LeaderTpuService,
     exit: Arc<AtomicBool>,
@@ -76,11 +83,16 @@ impl TpuClient {
             .leader_tpu_service
             .leader_tpu_sockets(self.fanout_slots)
         {
-            if self
-                .send_socket
-                .send_to(wire_transaction, tpu_address)
-                .is_ok()
-            {
+            let sent_to_leader = match &self.quic_client {
+                Some(quic_client) => quic_client
+                    .send_wire_transaction(&tpu_quic_addr(&tpu_address), wire_transaction)
+                    .is_ok(),
+                None => self
+                    .send_socket
+                    .send_to(wire_transaction, tpu_address)
+                    .is_ok(),
+            };
+            if sent_to_leader {
                 sent = true;
             }
         }
@@ -95,9 +107,15 @@ impl TpuClient {
     ) -> Result<Self> {
         let exit = Arc::new(AtomicBool::new(false));
         let leader_tpu_service = LeaderTpuService::new(rpc_client, websocket_url, exit.clone())?;
+        let quic_client = if config.use_quic {
+            Some(QuicClient::new(None)?)
+        } else {
+            None
+        };
 
         Ok(Self {
             send_socket: UdpSocket::bind("0.0.0.0:0").unwrap(),
+            quic_client,
             fanout_slots: config.fanout_slots.min(MAX_FANOUT_SLOTS).max(1),
             leader_tpu_service,
             exit,
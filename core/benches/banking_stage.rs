@@ -12,7 +12,7 @@ use solana_core::cluster_info::ClusterInfo;
 use solana_core::cluster_info::Node;
 use solana_core::poh_recorder::WorkingBankEntry;
 use solana_ledger::blockstore_processor::process_entries;
-use solana_ledger::entry::{next_hash, Entry};
+use solana_ledger::entry::Entry;
 use solana_ledger::genesis_utils::{create_genesis_config, GenesisConfigInfo};
 use solana_ledger::{blockstore::Blockstore, get_tmp_ledger_path};
 use solana_perf::packet::to_packets_chunked;
@@ -75,7 +75,7 @@ fn bench_consume_buffered(bencher: &mut Bencher) {
         let mut packets = VecDeque::new();
         for batch in batches {
             let batch_len = batch.packets.len();
-            packets.push_back((batch, vec![0usize; batch_len], false));
+            packets.push_back((batch, vec![0usize; batch_len], false, vec![0; batch_len]));
         }
         let (s, _r) = unbounded();
         // This tests the performance of buffering packets.
@@ -297,11 +297,7 @@ fn simulate_process_entries(
     }
 
     // Transfer lamports to each other
-    let entry = Entry {
-        num_hashes: 1,
-        hash: next_hash(&bank.last_blockhash(), 1, &tx_vector),
-        transactions: tx_vector,
-    };
+    let entry = Entry::new(&bank.last_blockhash(), 1, tx_vector);
     process_entries(&bank, &mut [entry], randomize_txs, None, None).unwrap();
 }
 
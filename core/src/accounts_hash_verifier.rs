@@ -155,9 +155,21 @@ impl AccountsHashVerifier {
             }
         }
 
-        if accounts_package.block_height % snapshot_interval_slots == 0 {
+        let is_incremental = accounts_package
+            .snapshot_type
+            .incremental_base_slot()
+            .is_some();
+        if is_incremental || accounts_package.block_height % snapshot_interval_slots == 0 {
             if let Some(pending_snapshot_package) = pending_snapshot_package.as_ref() {
-                *pending_snapshot_package.lock().unwrap() = Some(accounts_package);
+                let mut pending_snapshot_package = pending_snapshot_package.lock().unwrap();
+                // Incremental snapshots are useless without their full snapshot,
+                // so a pending full snapshot is never replaced by an incremental one.
+                let is_pending_full = pending_snapshot_package.as_ref().map_or(false, |package| {
+                    package.snapshot_type.incremental_base_slot().is_none()
+                });
+                if !(is_incremental && is_pending_full) {
+                    *pending_snapshot_package = Some(accounts_package);
+                }
             }
         }
         cluster_info.push_accounts_hashes(hashes.clone());
@@ -0,0 +1,177 @@
+//! Forwards account updates from `AccountsDb` to the plugins.
+//!
+//! Accounts are stored from the replay and banking hot paths, so updates are
+//! queued to a dedicated thread instead of calling the plugins in place.
+use {
+    super::accountsdb_plugin_manager::AccountsDbPluginManager,
+    crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender},
+    solana_accountsdb_plugin_interface::accountsdb_plugin_interface::{
+        ReplicaAccountInfo, ReplicaAccountInfoVersions,
+    },
+    solana_measure::measure::Measure,
+    solana_runtime::{
+        accounts_update_notifier_interface::AccountsUpdateNotifierInterface, append_vec::StoredMeta,
+    },
+    solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        clock::Slot,
+    },
+    std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, RwLock,
+        },
+        thread::{Builder, JoinHandle},
+        time::Duration,
+    },
+};
+
+/// Updates waiting for the plugins, storing accounts blocks once it is full
+const MAX_QUEUED_ACCOUNT_UPDATES: usize = 100_000;
+
+#[derive(Debug)]
+enum AccountUpdate {
+    Update {
+        slot: Slot,
+        meta: StoredMeta,
+        account: AccountSharedData,
+        is_startup: bool,
+    },
+    EndOfStartup,
+}
+
+#[derive(Debug)]
+pub(crate) struct AccountsUpdateNotifierImpl {
+    sender: Sender<AccountUpdate>,
+}
+
+impl AccountsUpdateNotifierInterface for AccountsUpdateNotifierImpl {
+    fn notify_account_update(&self, slot: Slot, meta: &StoredMeta, account: &AccountSharedData) {
+        self.send(AccountUpdate::Update {
+            slot,
+            meta: meta.clone(),
+            account: account.clone(),
+            is_startup: false,
+        });
+    }
+
+    fn notify_account_restore_from_snapshot(
+        &self,
+        slot: Slot,
+        meta: &StoredMeta,
+        account: &AccountSharedData,
+    ) {
+        self.send(AccountUpdate::Update {
+            slot,
+            meta: meta.clone(),
+            account: account.clone(),
+            is_startup: true,
+        });
+    }
+
+    fn notify_end_of_restore_from_snapshot(&self) {
+        self.send(AccountUpdate::EndOfStartup);
+    }
+}
+
+impl AccountsUpdateNotifierImpl {
+    pub(crate) fn new(
+        plugin_manager: Arc<RwLock<AccountsDbPluginManager>>,
+        exit: &Arc<AtomicBool>,
+    ) -> (Self, JoinHandle<()>) {
+        let (sender, receiver) = bounded(MAX_QUEUED_ACCOUNT_UPDATES);
+        let exit = exit.clone();
+        let thread_hdl = Builder::new()
+            .name("solana-accountsdb-plugin-accounts".to_string())
+            .spawn(move || Self::run(&plugin_manager, &receiver, &exit))
+            .unwrap();
+        (AccountsUpdateNotifierImpl { sender }, thread_hdl)
+    }
+
+    fn send(&self, update: AccountUpdate) {
+        // Fails only once the service is shut down, nobody is listening anymore
+        let _ = self.sender.send(update);
+    }
+
+    fn run(
+        plugin_manager: &RwLock<AccountsDbPluginManager>,
+        receiver: &Receiver<AccountUpdate>,
+        exit: &AtomicBool,
+    ) {
+        loop {
+            match receiver.recv_timeout(Duration::from_millis(100)) {
+                Ok(update) => Self::notify_plugins(plugin_manager, update),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            if exit.load(Ordering::Relaxed) {
+                // Deliver what was queued before the shutdown
+                for update in receiver.try_iter() {
+                    Self::notify_plugins(plugin_manager, update);
+                }
+                break;
+            }
+        }
+    }
+
+    fn notify_plugins(plugin_manager: &RwLock<AccountsDbPluginManager>, update: AccountUpdate) {
+        let (slot, meta, account, is_startup) = match update {
+            AccountUpdate::Update {
+                slot,
+                meta,
+                account,
+                is_startup,
+            } => (slot, meta, account, is_startup),
+            AccountUpdate::EndOfStartup => {
+                let mut plugin_manager = plugin_manager.write().unwrap();
+                for plugin in plugin_manager.plugins.iter_mut() {
+                    if let Err(err) = plugin.notify_end_of_startup() {
+                        error!(
+                            "Failed to notify the end of startup, error: {} to plugin {}",
+                            err,
+                            plugin.name()
+                        );
+                    }
+                }
+                return;
+            }
+        };
+        let account_info = ReplicaAccountInfo {
+            pubkey: meta.pubkey.as_ref(),
+            lamports: account.lamports(),
+            owner: account.owner().as_ref(),
+            executable: account.executable(),
+            rent_epoch: account.rent_epoch(),
+            data: account.data(),
+            write_version: meta.write_version,
+        };
+
+        let mut measure = Measure::start("accountsdb-plugin-update-account");
+        let mut plugin_manager = plugin_manager.write().unwrap();
+        for plugin in plugin_manager.plugins.iter_mut() {
+            if !plugin.account_data_notifications_enabled() {
+                continue;
+            }
+            if let Err(err) = plugin.update_account(
+                ReplicaAccountInfoVersions::V0_0_1(&account_info),
+                slot,
+                is_startup,
+            ) {
+                error!(
+                    "Failed to update account {} at slot {}, error: {} to plugin {}",
+                    meta.pubkey,
+                    slot,
+                    err,
+                    plugin.name()
+                );
+            }
+        }
+        measure.stop();
+        inc_new_counter_debug!(
+            "accountsdb-plugin-update-account-us",
+            measure.as_us() as usize,
+            100000,
+            100000
+        );
+    }
+}
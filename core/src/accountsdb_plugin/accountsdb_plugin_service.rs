@@ -0,0 +1,221 @@
+use {
+    super::{
+        accounts_update_notifier::AccountsUpdateNotifierImpl,
+        accountsdb_plugin_manager::AccountsDbPluginManager,
+        evm_block_notifier::{EvmBlockNotifier, EvmBlockNotifierImpl},
+        slot_status_notifier::{SlotStatusNotifier, SlotStatusNotifierImpl},
+        transaction_notifier::{TransactionNotifier, TransactionNotifierImpl},
+    },
+    solana_runtime::accounts_update_notifier_interface::AccountsUpdateNotifier,
+    std::{
+        fs::File,
+        io::Read,
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, RwLock,
+        },
+        thread::{self, JoinHandle},
+    },
+    thiserror::Error,
+};
+
+#[derive(Error, Debug)]
+pub enum AccountsDbPluginServiceError {
+    #[error("Cannot open the the plugin config file")]
+    CannotOpenConfigFile(String),
+
+    #[error("Cannot read the the plugin config file")]
+    CannotReadConfigFile(String),
+
+    #[error("The config file is not in a valid Json format")]
+    InvalidConfigFileFormat(String),
+
+    #[error("Plugin library path is not specified in the config file")]
+    LibPathNotSet,
+
+    #[error("Invalid plugin path")]
+    InvalidPluginPath,
+
+    #[error("Cannot load plugin shared library")]
+    PluginLoadError(String),
+}
+
+/// The service managing the AccountsDb plugin workflow.
+pub struct AccountsDbPluginService {
+    plugin_manager: Arc<RwLock<AccountsDbPluginManager>>,
+    accounts_update_notifier: Option<AccountsUpdateNotifier>,
+    accounts_update_thread: Option<JoinHandle<()>>,
+    exit: Arc<AtomicBool>,
+    slot_status_notifier: Option<SlotStatusNotifier>,
+    transaction_notifier: Option<TransactionNotifier>,
+    evm_block_notifier: Option<EvmBlockNotifier>,
+}
+
+impl AccountsDbPluginService {
+    /// Creates and returns the AccountsDbPluginService.
+    /// # Arguments
+    /// * `accountsdb_plugin_config_files` - the list of plugin config files,
+    ///    one per plugin. The config file is in JSON format and must include
+    ///    a field "libpath", the full path name of the shared library
+    ///    implementing the `AccountsDbPlugin` trait (relative paths are resolved
+    ///    against the directory of the config file). The rest of the config
+    ///    is plugin specific.
+    pub fn new(
+        accountsdb_plugin_config_files: &[PathBuf],
+    ) -> Result<Self, AccountsDbPluginServiceError> {
+        info!(
+            "Starting AccountsDbPluginService from config files: {:?}",
+            accountsdb_plugin_config_files
+        );
+        let mut plugin_manager = AccountsDbPluginManager::new();
+
+        for accountsdb_plugin_config_file in accountsdb_plugin_config_files {
+            Self::load_plugin(&mut plugin_manager, accountsdb_plugin_config_file)?;
+        }
+        let account_data_notifications_enabled =
+            plugin_manager.account_data_notifications_enabled();
+        let transaction_notifications_enabled = plugin_manager.transaction_notifications_enabled();
+        let evm_block_notifications_enabled = plugin_manager.evm_block_notifications_enabled();
+        let has_plugins = !plugin_manager.plugins.is_empty();
+
+        let plugin_manager = Arc::new(RwLock::new(plugin_manager));
+
+        let exit = Arc::new(AtomicBool::new(false));
+        let (accounts_update_notifier, accounts_update_thread) =
+            if account_data_notifications_enabled {
+                let (accounts_update_notifier, accounts_update_thread) =
+                    AccountsUpdateNotifierImpl::new(plugin_manager.clone(), &exit);
+                let accounts_update_notifier: AccountsUpdateNotifier =
+                    Arc::new(RwLock::new(accounts_update_notifier));
+                (Some(accounts_update_notifier), Some(accounts_update_thread))
+            } else {
+                (None, None)
+            };
+        let slot_status_notifier: Option<SlotStatusNotifier> = if has_plugins {
+            let slot_status_notifier = SlotStatusNotifierImpl::new(plugin_manager.clone());
+            Some(Arc::new(RwLock::new(slot_status_notifier)))
+        } else {
+            None
+        };
+        let transaction_notifier: Option<TransactionNotifier> = if transaction_notifications_enabled
+        {
+            let transaction_notifier = TransactionNotifierImpl::new(plugin_manager.clone());
+            Some(Arc::new(RwLock::new(transaction_notifier)))
+        } else {
+            None
+        };
+        let evm_block_notifier: Option<EvmBlockNotifier> = if evm_block_notifications_enabled {
+            let evm_block_notifier = EvmBlockNotifierImpl::new(plugin_manager.clone());
+            Some(Arc::new(RwLock::new(evm_block_notifier)))
+        } else {
+            None
+        };
+
+        info!("Started AccountsDbPluginService");
+        Ok(AccountsDbPluginService {
+            plugin_manager,
+            accounts_update_notifier,
+            accounts_update_thread,
+            exit,
+            slot_status_notifier,
+            transaction_notifier,
+            evm_block_notifier,
+        })
+    }
+
+    fn load_plugin(
+        plugin_manager: &mut AccountsDbPluginManager,
+        accountsdb_plugin_config_file: &Path,
+    ) -> Result<(), AccountsDbPluginServiceError> {
+        let mut file = match File::open(accountsdb_plugin_config_file) {
+            Ok(file) => file,
+            Err(err) => {
+                return Err(AccountsDbPluginServiceError::CannotOpenConfigFile(format!(
+                    "Failed to open the plugin config file {:?}, error: {:?}",
+                    accountsdb_plugin_config_file, err
+                )));
+            }
+        };
+
+        let mut contents = String::new();
+        if let Err(err) = file.read_to_string(&mut contents) {
+            return Err(AccountsDbPluginServiceError::CannotReadConfigFile(format!(
+                "Failed to read the plugin config file {:?}, error: {:?}",
+                accountsdb_plugin_config_file, err
+            )));
+        }
+
+        let result: serde_json::Value = match serde_json::from_str(&contents) {
+            Ok(value) => value,
+            Err(err) => {
+                return Err(AccountsDbPluginServiceError::InvalidConfigFileFormat(
+                    format!(
+                        "The config file {:?} is not in a valid Json format, error: {:?}",
+                        accountsdb_plugin_config_file, err
+                    ),
+                ));
+            }
+        };
+
+        let libpath = result["libpath"]
+            .as_str()
+            .ok_or(AccountsDbPluginServiceError::LibPathNotSet)?;
+        let mut libpath = PathBuf::from(libpath);
+        if libpath.is_relative() {
+            let config_dir = accountsdb_plugin_config_file.parent().ok_or_else(|| {
+                AccountsDbPluginServiceError::CannotOpenConfigFile(format!(
+                    "Failed to resolve parent of {:?}",
+                    accountsdb_plugin_config_file,
+                ))
+            })?;
+            libpath = config_dir.join(libpath);
+        }
+
+        let config_file = accountsdb_plugin_config_file
+            .as_os_str()
+            .to_str()
+            .ok_or(AccountsDbPluginServiceError::InvalidPluginPath)?;
+        let libpath = libpath
+            .as_os_str()
+            .to_str()
+            .ok_or(AccountsDbPluginServiceError::InvalidPluginPath)?;
+
+        unsafe {
+            let result = plugin_manager.load_plugin(libpath, config_file);
+            if let Err(err) = result {
+                let msg = format!(
+                    "Failed to load the plugin library: {:?}, error: {:?}",
+                    libpath, err
+                );
+                return Err(AccountsDbPluginServiceError::PluginLoadError(msg));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_accounts_update_notifier(&self) -> Option<AccountsUpdateNotifier> {
+        self.accounts_update_notifier.clone()
+    }
+
+    pub fn get_slot_status_notifier(&self) -> Option<SlotStatusNotifier> {
+        self.slot_status_notifier.clone()
+    }
+
+    pub fn get_transaction_notifier(&self) -> Option<TransactionNotifier> {
+        self.transaction_notifier.clone()
+    }
+
+    pub fn get_evm_block_notifier(&self) -> Option<EvmBlockNotifier> {
+        self.evm_block_notifier.clone()
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.exit.store(true, Ordering::Relaxed);
+        if let Some(accounts_update_thread) = self.accounts_update_thread {
+            accounts_update_thread.join()?;
+        }
+        self.plugin_manager.write().unwrap().unload();
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+//! Forwards EVM blocks, committed by banks, from `EvmRecorderService` to the plugins.
+use {
+    super::accountsdb_plugin_manager::AccountsDbPluginManager,
+    evm_state::Block,
+    solana_accountsdb_plugin_interface::accountsdb_plugin_interface::{
+        ReplicaEvmBlockInfo, ReplicaEvmBlockInfoVersions,
+    },
+    solana_measure::measure::Measure,
+    std::sync::{Arc, RwLock},
+};
+
+pub trait EvmBlockNotifierInterface {
+    fn notify_evm_block(&self, block: &Block);
+}
+
+pub type EvmBlockNotifier = Arc<RwLock<dyn EvmBlockNotifierInterface + Sync + Send>>;
+
+pub(crate) struct EvmBlockNotifierImpl {
+    plugin_manager: Arc<RwLock<AccountsDbPluginManager>>,
+}
+
+impl EvmBlockNotifierInterface for EvmBlockNotifierImpl {
+    fn notify_evm_block(&self, block: &Block) {
+        let block_info = ReplicaEvmBlockInfo {
+            block_hash: block.header.hash(),
+            header: &block.header,
+            transactions: &block.transactions,
+        };
+
+        let mut measure = Measure::start("accountsdb-plugin-notify-evm-block");
+        let mut plugin_manager = self.plugin_manager.write().unwrap();
+        for plugin in plugin_manager.plugins.iter_mut() {
+            if !plugin.evm_block_notifications_enabled() {
+                continue;
+            }
+            if let Err(err) =
+                plugin.notify_evm_block(ReplicaEvmBlockInfoVersions::V0_0_1(&block_info))
+            {
+                error!(
+                    "Failed to notify evm block {}, error: {} to plugin {}",
+                    block.header.block_number,
+                    err,
+                    plugin.name()
+                );
+            }
+        }
+        measure.stop();
+        inc_new_counter_debug!(
+            "accountsdb-plugin-notify-evm-block-us",
+            measure.as_us() as usize,
+            1000,
+            1000
+        );
+    }
+}
+
+impl EvmBlockNotifierImpl {
+    pub(crate) fn new(plugin_manager: Arc<RwLock<AccountsDbPluginManager>>) -> Self {
+        Self { plugin_manager }
+    }
+}
@@ -0,0 +1,15 @@
+//! Support for validator plugins, loaded from dynamic libraries, which receive
+//! account updates, slot status changes, transactions and EVM blocks
+//! as soon as the validator processes them.
+
+pub mod accounts_update_notifier;
+pub mod accountsdb_plugin_manager;
+pub mod accountsdb_plugin_service;
+pub mod evm_block_notifier;
+pub mod slot_status_notifier;
+pub mod transaction_notifier;
+
+pub use accountsdb_plugin_service::*;
+pub use evm_block_notifier::{EvmBlockNotifier, EvmBlockNotifierInterface};
+pub use slot_status_notifier::{SlotStatusNotifier, SlotStatusNotifierInterface};
+pub use transaction_notifier::{TransactionNotifier, TransactionNotifierInterface};
@@ -0,0 +1,79 @@
+//! Forwards transactions with their status metadata from `TransactionStatusService`
+//! to the plugins.
+use {
+    super::accountsdb_plugin_manager::AccountsDbPluginManager,
+    solana_accountsdb_plugin_interface::accountsdb_plugin_interface::{
+        ReplicaTransactionInfo, ReplicaTransactionInfoVersions,
+    },
+    solana_measure::measure::Measure,
+    solana_runtime::bank::is_simple_vote_transaction,
+    solana_sdk::{clock::Slot, signature::Signature, transaction::Transaction},
+    solana_transaction_status::TransactionStatusMeta,
+    std::sync::{Arc, RwLock},
+};
+
+pub trait TransactionNotifierInterface {
+    fn notify_transaction(
+        &self,
+        slot: Slot,
+        signature: &Signature,
+        transaction_status_meta: &TransactionStatusMeta,
+        transaction: &Transaction,
+    );
+}
+
+pub type TransactionNotifier = Arc<RwLock<dyn TransactionNotifierInterface + Sync + Send>>;
+
+pub(crate) struct TransactionNotifierImpl {
+    plugin_manager: Arc<RwLock<AccountsDbPluginManager>>,
+}
+
+impl TransactionNotifierInterface for TransactionNotifierImpl {
+    fn notify_transaction(
+        &self,
+        slot: Slot,
+        signature: &Signature,
+        transaction_status_meta: &TransactionStatusMeta,
+        transaction: &Transaction,
+    ) {
+        let transaction_info = ReplicaTransactionInfo {
+            signature,
+            is_vote: is_simple_vote_transaction(transaction),
+            transaction,
+            transaction_status_meta,
+        };
+
+        let mut measure = Measure::start("accountsdb-plugin-notify_plugins_of_transaction_info");
+        let mut plugin_manager = self.plugin_manager.write().unwrap();
+        for plugin in plugin_manager.plugins.iter_mut() {
+            if !plugin.transaction_notifications_enabled() {
+                continue;
+            }
+            if let Err(err) = plugin.notify_transaction(
+                ReplicaTransactionInfoVersions::V0_0_1(&transaction_info),
+                slot,
+            ) {
+                error!(
+                    "Failed to notify transaction {} at slot {}, error: {} to plugin {}",
+                    signature,
+                    slot,
+                    err,
+                    plugin.name()
+                );
+            }
+        }
+        measure.stop();
+        inc_new_counter_debug!(
+            "accountsdb-plugin-notify_plugins_of_transaction_info-us",
+            measure.as_us() as usize,
+            10000,
+            10000
+        );
+    }
+}
+
+impl TransactionNotifierImpl {
+    pub(crate) fn new(plugin_manager: Arc<RwLock<AccountsDbPluginManager>>) -> Self {
+        Self { plugin_manager }
+    }
+}
@@ -3,11 +3,13 @@
 //! can do its processing in parallel with signature verification on the GPU.
 use crate::{
     cluster_info::ClusterInfo,
+    data_budget::DataBudget,
     packet_hasher::PacketHasher,
     poh_recorder::{PohRecorder, PohRecorderError, TransactionRecorder, WorkingBankEntry},
     poh_service::{self, PohService},
+    transaction_scheduler::{conflict_free_batches, TransactionScheduler},
 };
-use crossbeam_channel::{Receiver as CrossbeamReceiver, RecvTimeoutError};
+use crossbeam_channel::{unbounded, Receiver as CrossbeamReceiver, RecvTimeoutError};
 use itertools::Itertools;
 use lru::LruCache;
 use retain_mut::RetainMut;
@@ -19,7 +21,7 @@ use solana_measure::{measure::Measure, thread_mem_usage};
 use solana_metrics::{inc_new_counter_debug, inc_new_counter_info};
 use solana_perf::{
     cuda_runtime::PinnedVec,
-    packet::{limited_deserialize, Packet, Packets, PACKETS_PER_BATCH},
+    packet::{limited_deserialize, Packet, Packets, PACKETS_PER_BATCH, PACKET_DATA_SIZE},
     perf_libs,
 };
 use solana_runtime::{
@@ -38,24 +40,24 @@ use solana_sdk::{
         Slot, DEFAULT_TICKS_PER_SLOT, MAX_PROCESSING_AGE, MAX_TRANSACTION_FORWARDING_DELAY,
         MAX_TRANSACTION_FORWARDING_DELAY_GPU,
     },
-    message::Message,
+    compute_budget::ComputeBudgetLimits,
+    message::{Message, VersionedMessage},
     poh_config::PohConfig,
     pubkey::Pubkey,
     short_vec::decode_shortu16_len,
     signature::Signature,
     timing::{duration_as_ms, timestamp},
-    transaction::{self, Transaction, TransactionError},
+    transaction::{self, TransactionError, VersionedTransaction},
 };
 use solana_transaction_status::token_balances::{
     collect_token_balances, TransactionTokenBalancesSet,
 };
 use std::{
-    borrow::Cow,
     cmp,
-    collections::{HashMap, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
     env,
     mem::size_of,
-    net::UdpSocket,
+    net::{IpAddr, UdpSocket},
     ops::DerefMut,
     sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
     sync::mpsc::Receiver,
@@ -65,9 +67,10 @@ use std::{
     time::Instant,
 };
 
-/// (packets, valid_indexes, forwarded)
-/// Set of packets with a list of which are valid and if this batch has been forwarded.
-type PacketsAndOffsets = (Packets, Vec<usize>, bool);
+/// (packets, valid_indexes, forwarded, compute_unit_prices)
+/// Set of packets with a list of which are valid, if this batch has been forwarded and the
+/// compute unit price of each packet.
+type PacketsAndOffsets = (Packets, Vec<usize>, bool, Vec<u64>);
 
 pub type UnprocessedPackets = VecDeque<PacketsAndOffsets>;
 
@@ -78,12 +81,20 @@ pub const HOLD_TRANSACTIONS_SLOT_OFFSET: u64 = 20;
 // Fixed thread size seems to be fastest on GCP setup
 pub const NUM_THREADS: u32 = 4;
 
+// Threads deserializing and dispatching non-vote packets to the banking threads
+const NUM_SCHEDULER_SHARDS: usize = 2;
+
 const TOTAL_BUFFERED_PACKETS: usize = 500_000;
 
 const MAX_NUM_TRANSACTIONS_PER_BATCH: usize = 128;
 
 const DEFAULT_LRU_SIZE: usize = 200_000;
 
+// Budget of each banking thread for forwarding packets, ~10k full packets per second
+const FORWARD_BUDGET_INTERVAL_MS: u64 = 100;
+const FORWARD_BYTES_PER_INTERVAL: usize = 1_000 * PACKET_DATA_SIZE;
+const MAX_FORWARD_BUDGET_MULTIPLE: usize = 5; // allow budget build-up to 5x the interval default
+
 #[derive(Debug, Default)]
 pub struct BankingStageStats {
     last_report: AtomicU64,
@@ -260,11 +271,16 @@ impl BankingStage {
             LruCache::new(DEFAULT_LRU_SIZE),
             PacketHasher::default(),
         )));
+        // Non-vote packets are dispatched to the banking threads by the accounts they lock
+        let (lane_senders, lane_receivers): (Vec<_>, Vec<_>) =
+            (0..num_threads - 1).map(|_| unbounded()).unzip();
+        let scheduler_thread_hdls =
+            TransactionScheduler::new(lane_senders).spawn(verified_receiver, NUM_SCHEDULER_SHARDS);
         // Many banks that process transactions in parallel.
-        let bank_thread_hdls: Vec<JoinHandle<()>> = (0..num_threads)
+        let mut bank_thread_hdls: Vec<JoinHandle<()>> = (0..num_threads)
             .map(|i| {
                 let (verified_receiver, enable_forwarding) = if i < num_threads - 1 {
-                    (verified_receiver.clone(), true)
+                    (lane_receivers[i as usize].clone(), true)
                 } else {
                     // Disable forwarding of vote transactions, as votes are gossiped
                     (verified_vote_receiver.clone(), false)
@@ -297,6 +313,7 @@ impl BankingStage {
                     .unwrap()
             })
             .collect();
+        bank_thread_hdls.extend(scheduler_thread_hdls);
         Self { bank_thread_hdls }
     }
 
@@ -304,25 +321,85 @@ impl BankingStage {
         all_packets: impl Iterator<Item = &'a PacketsAndOffsets>,
     ) -> Vec<&'a Packet> {
         all_packets
-            .filter(|(_p, _indexes, forwarded)| !forwarded)
-            .flat_map(|(p, valid_indexes, _forwarded)| {
+            .filter(|(_p, _indexes, forwarded, _prices)| !forwarded)
+            .flat_map(|(p, valid_indexes, _forwarded, _prices)| {
                 valid_indexes.iter().map(move |x| &p.packets[*x])
             })
             .collect()
     }
 
+    /// Orders packets so that any prefix of the result holds packets of each sender in
+    /// proportion to the sender's stake, and packets of unstaked senders only after all of the
+    /// staked ones. Senders are told apart by source address, and packets of a sender keep their
+    /// order.
+    fn stake_weighted_forwarding_order(packets: Vec<&Packet>) -> Vec<&Packet> {
+        let mut num_packets_by_sender: HashMap<IpAddr, u64> = HashMap::new();
+        let mut packets: Vec<_> = packets
+            .into_iter()
+            .map(|packet| {
+                let num_packets = num_packets_by_sender
+                    .entry(packet.meta.addr().ip())
+                    .or_default();
+                *num_packets += 1;
+                // Virtual finish time of weighted fair queueing, unstaked senders are given the
+                // minimal weight
+                let finish_time = *num_packets as f64 / packet.meta.sender_stake.max(1) as f64;
+                (finish_time, packet)
+            })
+            .collect();
+        // Stable, so that arrival order breaks ties
+        packets.sort_by(|(finish_time, _), (other_finish_time, _)| {
+            finish_time.partial_cmp(other_finish_time).unwrap()
+        });
+        packets.into_iter().map(|(_, packet)| packet).collect()
+    }
+
+    /// Forwards the packets that fit in the data budget, and marks the batches whose valid
+    /// packets were all sent as forwarded
     fn forward_buffered_packets(
         socket: &std::net::UdpSocket,
         tpu_forwards: &std::net::SocketAddr,
-        unprocessed_packets: &UnprocessedPackets,
+        unprocessed_packets: &mut UnprocessedPackets,
+        data_budget: &DataBudget,
     ) -> std::io::Result<()> {
-        let packets = Self::filter_valid_packets_for_forwarding(unprocessed_packets.iter());
-        inc_new_counter_info!("banking_stage-forwarded_packets", packets.len());
+        let packets = Self::stake_weighted_forwarding_order(
+            Self::filter_valid_packets_for_forwarding(unprocessed_packets.iter()),
+        );
+        data_budget.update(FORWARD_BUDGET_INTERVAL_MS, |bytes| {
+            cmp::min(
+                bytes + FORWARD_BYTES_PER_INTERVAL,
+                MAX_FORWARD_BUDGET_MULTIPLE * FORWARD_BYTES_PER_INTERVAL,
+            )
+        });
+        let num_packets = packets.len();
+        let mut forwarded_packets = HashSet::new();
+        let mut result = Ok(());
         for p in packets {
-            socket.send_to(&p.data[..p.meta.size], &tpu_forwards)?;
+            if !data_budget.take(p.meta.size) {
+                break;
+            }
+            if let Err(err) = socket.send_to(&p.data[..p.meta.size], &tpu_forwards) {
+                result = Err(err);
+                break;
+            }
+            forwarded_packets.insert(p as *const Packet);
         }
+        let num_forwarded = forwarded_packets.len();
+        inc_new_counter_info!("banking_stage-forwarded_packets", num_forwarded);
+        inc_new_counter_info!(
+            "banking_stage-forward_budget_dropped_packets",
+            num_packets - num_forwarded
+        );
 
-        Ok(())
+        for (p, valid_indexes, forwarded, _prices) in unprocessed_packets.iter_mut() {
+            if valid_indexes
+                .iter()
+                .all(|x| forwarded_packets.contains(&(&p.packets[*x] as *const Packet)))
+            {
+                *forwarded = true;
+            }
+        }
+        result
     }
 
     // Returns whether the given `Packets` has any more remaining unprocessed
@@ -356,7 +433,12 @@ impl BankingStage {
         let mut proc_start = Measure::start("consume_buffered_process");
         let mut reached_end_of_slot = None;
 
-        buffered_packets.retain_mut(|(msgs, ref mut original_unprocessed_indexes, _forwarded)| {
+        let bank = poh_recorder.lock().unwrap().bank();
+        if bank.map_or(false, |bank| bank.compute_budget_program_enabled()) {
+            Self::prioritize_buffered_packets(buffered_packets);
+        }
+
+        buffered_packets.retain_mut(|(msgs, ref mut original_unprocessed_indexes, ..)| {
             if let Some((next_leader, bank)) = &reached_end_of_slot {
                 // We've hit the end of this slot, no need to perform more processing,
                 // just filter the remaining packets for the invalid (e.g. too old) ones
@@ -486,6 +568,7 @@ impl BankingStage {
         gossip_vote_sender: &ReplayVoteSender,
         banking_stage_stats: &BankingStageStats,
         recorder: &TransactionRecorder,
+        data_budget: &DataBudget,
     ) -> BufferedPacketsDecision {
         let bank_start;
         let (
@@ -536,6 +619,7 @@ impl BankingStage {
                     poh_recorder,
                     socket,
                     false,
+                    data_budget,
                 );
             }
             BufferedPacketsDecision::ForwardAndHold => {
@@ -546,6 +630,7 @@ impl BankingStage {
                     poh_recorder,
                     socket,
                     true,
+                    data_budget,
                 );
             }
             _ => (),
@@ -560,6 +645,7 @@ impl BankingStage {
         poh_recorder: &Arc<Mutex<PohRecorder>>,
         socket: &UdpSocket,
         hold: bool,
+        data_budget: &DataBudget,
     ) {
         if !enable_forwarding {
             if !hold {
@@ -572,12 +658,9 @@ impl BankingStage {
             Some(addr) => addr,
             None => return,
         };
-        let _ = Self::forward_buffered_packets(socket, &addr, buffered_packets);
+        let _ = Self::forward_buffered_packets(socket, &addr, buffered_packets, data_budget);
         if hold {
-            buffered_packets.retain(|(_, index, _)| !index.is_empty());
-            for (_, _, forwarded) in buffered_packets.iter_mut() {
-                *forwarded = true;
-            }
+            buffered_packets.retain(|(_, index, _, _)| !index.is_empty());
         } else {
             buffered_packets.clear();
         }
@@ -601,6 +684,7 @@ impl BankingStage {
         let socket = UdpSocket::bind("0.0.0.0:0").unwrap();
         let mut buffered_packets = VecDeque::with_capacity(batch_limit);
         let banking_stage_stats = BankingStageStats::new(id);
+        let data_budget = DataBudget::default();
         loop {
             while !buffered_packets.is_empty() {
                 let decision = Self::process_buffered_packets(
@@ -614,6 +698,7 @@ impl BankingStage {
                     &gossip_vote_sender,
                     &banking_stage_stats,
                     &recorder,
+                    &data_budget,
                 );
                 if matches!(decision, BufferedPacketsDecision::Hold)
                     || matches!(decision, BufferedPacketsDecision::ForwardAndHold)
@@ -669,9 +754,9 @@ impl BankingStage {
     }
 
     #[allow(clippy::match_wild_err_arm)]
-    fn record_transactions<'a>(
+    fn record_transactions(
         bank_slot: Slot,
-        txs: impl Iterator<Item = &'a Transaction>,
+        txs: &[HashedTransaction],
         results: &[TransactionExecutionResult],
         recorder: &TransactionRecorder,
     ) -> (Result<usize, PohRecorderError>, Vec<usize>) {
@@ -682,7 +767,7 @@ impl BankingStage {
             .enumerate()
             .filter_map(|(i, ((r, _n), x))| {
                 if Bank::can_commit(r) {
-                    Some((x.clone(), i))
+                    Some((x.to_versioned_transaction(), i))
                 } else {
                     None
                 }
@@ -759,6 +844,8 @@ impl BankingStage {
             results,
             inner_instructions,
             transaction_logs,
+            return_data,
+            _units_consumed,
             mut retryable_txs,
             tx_count,
             signature_count,
@@ -778,7 +865,7 @@ impl BankingStage {
 
         let mut record_time = Measure::start("record_time");
         let (num_to_commit, retryable_record_txs) =
-            Self::record_transactions(bank.slot(), batch.transactions_iter(), &results, poh);
+            Self::record_transactions(bank.slot(), batch.hashed_transactions(), &results, poh);
         inc_new_counter_info!(
             "banking_stage-record_transactions_num_to_commit",
             *num_to_commit.as_ref().unwrap_or(&0)
@@ -809,7 +896,12 @@ impl BankingStage {
 
             bank_utils::find_and_send_votes(hashed_txs, &tx_results, Some(gossip_vote_sender));
             if let Some(transaction_status_sender) = transaction_status_sender {
-                let txs = batch.transactions_iter().cloned().collect();
+                let txs = batch
+                    .hashed_transactions()
+                    .iter()
+                    .cloned()
+                    .map(HashedTransaction::into_owned)
+                    .collect();
                 let post_balances = bank.collect_balances(batch);
                 let post_token_balances = collect_token_balances(bank, batch, &mut mint_decimals);
                 transaction_status_sender.send_transaction_status_batch(
@@ -820,6 +912,7 @@ impl BankingStage {
                     TransactionTokenBalancesSet::new(pre_token_balances, post_token_balances),
                     inner_instructions,
                     transaction_logs,
+                    return_data,
                     tx_results.rent_debits,
                 );
             }
@@ -858,6 +951,7 @@ impl BankingStage {
         // same account state
         let batch = bank.prepare_hashed_batch(txs);
         lock_time.stop();
+        Self::report_lock_contention(txs, batch.lock_results(), lock_time.as_us());
 
         let (result, mut retryable_txs) = Self::process_and_record_transactions_locked(
             bank,
@@ -884,6 +978,26 @@ impl BankingStage {
         (result, retryable_txs)
     }
 
+    /// Counts the transactions that failed to lock accounts held by other banking threads, or
+    /// by replay, separately for EVM transactions, as they all contend for the EVM state
+    fn report_lock_contention(
+        txs: &[HashedTransaction],
+        lock_results: &[transaction::Result<()>],
+        lock_time_us: u64,
+    ) {
+        let (num_account_in_use, num_evm_account_in_use) = txs
+            .iter()
+            .zip(lock_results)
+            .filter(|(_, result)| matches!(result, Err(TransactionError::AccountInUse)))
+            .fold((0, 0), |(num, num_evm), (tx, _)| {
+                let is_evm = tx.transaction().message().is_modify_evm_state();
+                (num + 1, num_evm + is_evm as usize)
+            });
+        inc_new_counter_info!("banking_stage-account_in_use", num_account_in_use);
+        inc_new_counter_info!("banking_stage-evm_account_in_use", num_evm_account_in_use);
+        inc_new_counter_info!("banking_stage-lock_time_us", lock_time_us as usize);
+    }
+
     /// Sends transactions to the bank.
     ///
     /// Returns the number of transactions successfully processed by the bank, which may be less
@@ -896,19 +1010,25 @@ impl BankingStage {
         transaction_status_sender: Option<TransactionStatusSender>,
         gossip_vote_sender: &ReplayVoteSender,
     ) -> (usize, Vec<usize>) {
-        let mut chunk_start = 0;
+        // Batches are free of account lock conflicts among their own transactions, so that
+        // they only fail to lock accounts held by other banking threads
+        let batches = conflict_free_batches(
+            transactions,
+            MAX_NUM_TRANSACTIONS_PER_BATCH,
+            bank.demote_sysvar_write_locks(),
+        );
+        let mut processed = 0;
         let mut unprocessed_txs = vec![];
-        while chunk_start != transactions.len() {
-            let chunk_end = std::cmp::min(
-                transactions.len(),
-                chunk_start + MAX_NUM_TRANSACTIONS_PER_BATCH,
-            );
-
-            let (result, retryable_txs_in_chunk) = Self::process_and_record_transactions(
+        for (batch_index, batch) in batches.iter().enumerate() {
+            let batch_txs: Vec<_> = batch
+                .iter()
+                .map(|index| transactions[*index].borrowed())
+                .collect();
+            let (result, retryable_txs_in_batch) = Self::process_and_record_transactions(
                 bank,
-                &transactions[chunk_start..chunk_end],
+                &batch_txs,
                 poh,
-                chunk_start,
+                0,
                 transaction_status_sender.clone(),
                 gossip_vote_sender,
             );
@@ -916,7 +1036,7 @@ impl BankingStage {
 
             // Add the retryable txs (transactions that errored in a way that warrants a retry)
             // to the list of unprocessed txs.
-            unprocessed_txs.extend_from_slice(&retryable_txs_in_chunk);
+            unprocessed_txs.extend(retryable_txs_in_batch.into_iter().map(|i| batch[i]));
 
             // If `bank_creation_time` is None, it's a test so ignore the option so
             // allow processing
@@ -930,18 +1050,19 @@ impl BankingStage {
                         bank.tick_height()
                     );
                     // process_and_record_transactions has returned all retryable errors in
-                    // transactions[chunk_start..chunk_end], so we just need to push the remaining
-                    // transactions into the unprocessed queue.
-                    unprocessed_txs.extend(chunk_end..transactions.len());
+                    // the batch, so we just need to push the transactions of the remaining
+                    // batches into the unprocessed queue.
+                    unprocessed_txs.extend(batches[batch_index + 1..].iter().flatten());
                     break;
                 }
                 _ => (),
             }
             // Don't exit early on any other type of error, continue processing...
-            chunk_start = chunk_end;
+            processed += batch.len();
         }
+        unprocessed_txs.sort_unstable();
 
-        (chunk_start, unprocessed_txs)
+        (processed, unprocessed_txs)
     }
 
     // This function creates a filter of transaction results with Ok() for every pending
@@ -984,29 +1105,111 @@ impl BankingStage {
     }
 
     // This function deserializes packets into transactions, computes the blake3 hash of transaction messages,
-    // and verifies secp256k1 instructions. A list of valid transactions are returned with their message hashes
-    // and packet indexes.
+    // expands versioned messages with the accounts of their lookup tables in `bank`, and verifies the
+    // instructions of the precompile programs active in `bank`. A list of valid transactions are returned
+    // with their message hashes and packet indexes. If the compute budget program is enabled, transactions
+    // are ordered by their compute unit price, highest first, so that prioritized transactions are processed first.
     fn transactions_from_packets(
         msgs: &Packets,
         transaction_indexes: &[usize],
-        secp256k1_program_enabled: bool,
+        bank: &Bank,
+        compute_budget_program_enabled: bool,
     ) -> (Vec<HashedTransaction<'static>>, Vec<usize>) {
-        transaction_indexes
+        let mut transactions: Vec<_> = transaction_indexes
             .iter()
             .filter_map(|tx_index| {
                 let p = &msgs.packets[*tx_index];
-                let tx: Transaction = limited_deserialize(&p.data[0..p.meta.size]).ok()?;
-                if secp256k1_program_enabled {
-                    tx.verify_precompiles().ok()?;
-                }
+                let tx: VersionedTransaction = limited_deserialize(&p.data[0..p.meta.size]).ok()?;
                 let message_bytes = Self::packet_message(p)?;
                 let message_hash = Message::hash_raw_message(message_bytes);
-                Some((
-                    HashedTransaction::new(Cow::Owned(tx), message_hash),
-                    tx_index,
-                ))
+                let tx = bank.expand_versioned_transaction(tx, message_hash).ok()?;
+                tx.transaction()
+                    .verify_precompiles(&bank.feature_set)
+                    .ok()?;
+                Some((tx, *tx_index))
             })
-            .unzip()
+            .collect();
+        if compute_budget_program_enabled {
+            // Stable sort, transactions with the same price keep their arrival order
+            transactions.sort_by_cached_key(|(tx, _)| {
+                cmp::Reverse(Self::compute_unit_price(tx.transaction().message()))
+            });
+        }
+        transactions.into_iter().unzip()
+    }
+
+    pub(crate) fn compute_unit_price(message: &Message) -> u64 {
+        ComputeBudgetLimits::process_message(message)
+            .map(|limits| limits.compute_unit_price)
+            .unwrap_or_default()
+    }
+
+    /// The message of a transaction with only the accounts it lists inline. Packets are
+    /// priced and routed before a bank can load the accounts of their lookup tables.
+    pub(crate) fn static_message(message: VersionedMessage) -> Message {
+        match message {
+            VersionedMessage::Legacy(message) => message,
+            VersionedMessage::V0(message) => Message {
+                header: message.header,
+                account_keys: message.account_keys,
+                recent_blockhash: message.recent_blockhash,
+                instructions: message.instructions,
+            },
+        }
+    }
+
+    /// Compute unit price of each packet of `msgs`, packets which are not in `packet_indexes`
+    /// or do not deserialize are given a price of 0
+    fn packet_compute_unit_prices(msgs: &Packets, packet_indexes: &[usize]) -> Vec<u64> {
+        let mut compute_unit_prices = vec![0; msgs.packets.len()];
+        for index in packet_indexes {
+            let p = &msgs.packets[*index];
+            if let Ok(tx) = limited_deserialize::<VersionedTransaction>(&p.data[0..p.meta.size]) {
+                let message = Self::static_message(tx.message);
+                compute_unit_prices[*index] = Self::compute_unit_price(&message);
+            }
+        }
+        compute_unit_prices
+    }
+
+    /// Orders the whole buffer, not only each batch, by compute unit price, highest first.
+    /// Packets are only copied into new batches if the buffer is out of order, and packets
+    /// with the same price keep their arrival order.
+    fn prioritize_buffered_packets(buffered_packets: &mut UnprocessedPackets) {
+        let mut last_price = u64::MAX;
+        let is_prioritized = buffered_packets
+            .iter()
+            .flat_map(|(_, indexes, _, prices)| indexes.iter().map(move |index| prices[*index]))
+            .all(|price| {
+                let is_ordered = price <= last_price;
+                last_price = price;
+                is_ordered
+            });
+        if is_prioritized {
+            return;
+        }
+
+        let mut packets: Vec<_> = buffered_packets
+            .drain(..)
+            .flat_map(|(msgs, indexes, forwarded, prices)| {
+                indexes
+                    .into_iter()
+                    .map(move |index| (prices[index], forwarded, msgs.packets[index].clone()))
+            })
+            .collect();
+        packets.sort_by_key(|(price, _, _)| cmp::Reverse(*price));
+
+        let mut packets = packets.into_iter().peekable();
+        while packets.peek().is_some() {
+            let batch: Vec<_> = packets.by_ref().take(PACKETS_PER_BATCH).collect();
+            let forwarded = batch.iter().all(|(_, forwarded, _)| *forwarded);
+            let (prices, batch): (Vec<_>, Vec<_>) = batch
+                .into_iter()
+                .map(|(price, _, packet)| (price, packet))
+                .unzip();
+            let indexes = (0..batch.len()).collect();
+            buffered_packets.push_back((Packets::new(batch), indexes, forwarded, prices));
+        }
     }
 
     /// This function filters pending packets that are still valid
@@ -1062,7 +1265,8 @@ impl BankingStage {
         let (transactions, transaction_to_packet_indexes) = Self::transactions_from_packets(
             msgs,
             &packet_indexes,
-            bank.secp256k1_program_enabled(),
+            bank,
+            bank.compute_budget_program_enabled(),
         );
         packet_conversion_time.stop();
 
@@ -1133,7 +1337,8 @@ impl BankingStage {
         let (transactions, transaction_to_packet_indexes) = Self::transactions_from_packets(
             msgs,
             transaction_indexes,
-            bank.secp256k1_program_enabled(),
+            bank,
+            bank.compute_budget_program_enabled(),
         );
 
         let tx_count = transaction_to_packet_indexes.len();
@@ -1346,12 +1551,25 @@ impl BankingStage {
                 .fetch_add(packet_duplicate_check_time.as_us(), Ordering::Relaxed);
         }
         if Self::packet_has_more_unprocessed_transactions(&packet_indexes) {
+            let compute_unit_prices = Self::packet_compute_unit_prices(&packets, &packet_indexes);
+            // Stable, so that packets with the same price keep their arrival order
+            packet_indexes.sort_by_key(|index| cmp::Reverse(compute_unit_prices[*index]));
             if unprocessed_packets.len() >= batch_limit {
                 *dropped_batches_count += 1;
-                unprocessed_packets.pop_front();
+                // Drop the batch whose best packet pays the least, the oldest one on ties
+                let lowest_priority_batch = unprocessed_packets
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, (_, indexes, _, prices))| {
+                        indexes.iter().map(|index| prices[*index]).max()
+                    })
+                    .map(|(batch, _)| batch);
+                if let Some(batch) = lowest_priority_batch {
+                    unprocessed_packets.remove(batch);
+                }
             }
             *newly_buffered_packets_count += packet_indexes.len();
-            unprocessed_packets.push_back((packets, packet_indexes, false));
+            unprocessed_packets.push_back((packets, packet_indexes, false, compute_unit_prices));
         }
     }
 
@@ -1453,13 +1671,16 @@ mod tests {
         get_tmp_ledger_path,
     };
     use solana_perf::packet::to_packets_chunked;
+    use solana_runtime::genesis_utils::activate_all_features;
     use solana_sdk::{
+        compute_budget::ComputeBudgetInstruction,
         hash::Hash,
-        instruction::InstructionError,
+        instruction::{CompiledInstruction, InstructionError},
+        message::{v0, MessageHeader},
         signature::{Keypair, Signer},
-        system_instruction::SystemError,
-        system_transaction,
-        transaction::TransactionError,
+        system_instruction::{self, SystemError},
+        system_program, system_transaction,
+        transaction::{Transaction, TransactionError},
     };
     use solana_transaction_status::TransactionWithStatusMeta;
     use std::{
@@ -1668,7 +1889,12 @@ mod tests {
                 if !entries.is_empty() {
                     blockhash = entries.last().unwrap().hash;
                     for entry in entries {
-                        bank.process_transactions(&entry.transactions)
+                        let transactions: Vec<_> = entry
+                            .transactions
+                            .into_iter()
+                            .map(|tx| tx.into_legacy_transaction().unwrap())
+                            .collect();
+                        bank.process_transactions(&transactions)
                             .iter()
                             .for_each(|x| assert_eq!(*x, Ok(())));
                     }
@@ -1778,8 +2004,13 @@ mod tests {
                 .collect();
 
             let bank = Bank::new_no_wallclock_throttle(&genesis_config);
-            for entry in &entries {
-                bank.process_transactions(&entry.transactions)
+            for entry in entries {
+                let transactions: Vec<_> = entry
+                    .transactions
+                    .into_iter()
+                    .map(|tx| tx.into_legacy_transaction().unwrap())
+                    .collect();
+                bank.process_transactions(&transactions)
                     .iter()
                     .for_each(|x| assert_eq!(*x, Ok(())));
             }
@@ -1837,17 +2068,23 @@ mod tests {
             let pubkey2 = solana_sdk::pubkey::new_rand();
 
             let transactions = vec![
-                system_transaction::transfer(&mint_keypair, &pubkey, 1, genesis_config.hash()),
-                system_transaction::transfer(&keypair2, &pubkey2, 1, genesis_config.hash()),
+                HashedTransaction::from(system_transaction::transfer(
+                    &mint_keypair,
+                    &pubkey,
+                    1,
+                    genesis_config.hash(),
+                )),
+                HashedTransaction::from(system_transaction::transfer(
+                    &keypair2,
+                    &pubkey2,
+                    1,
+                    genesis_config.hash(),
+                )),
             ];
 
             let mut results = vec![(Ok(()), None), (Ok(()), None)];
-            let _ = BankingStage::record_transactions(
-                bank.slot(),
-                transactions.iter(),
-                &results,
-                &recorder,
-            );
+            let _ =
+                BankingStage::record_transactions(bank.slot(), &transactions, &results, &recorder);
             let (_bank, (entry, _tick_height)) = entry_receiver.recv().unwrap();
             assert_eq!(entry.transactions.len(), transactions.len());
 
@@ -1859,12 +2096,8 @@ mod tests {
                 )),
                 None,
             );
-            let (res, retryable) = BankingStage::record_transactions(
-                bank.slot(),
-                transactions.iter(),
-                &results,
-                &recorder,
-            );
+            let (res, retryable) =
+                BankingStage::record_transactions(bank.slot(), &transactions, &results, &recorder);
             res.unwrap();
             assert!(retryable.is_empty());
             let (_bank, (entry, _tick_height)) = entry_receiver.recv().unwrap();
@@ -1872,12 +2105,8 @@ mod tests {
 
             // Other TransactionErrors should not be recorded
             results[0] = (Err(TransactionError::AccountNotFound), None);
-            let (res, retryable) = BankingStage::record_transactions(
-                bank.slot(),
-                transactions.iter(),
-                &results,
-                &recorder,
-            );
+            let (res, retryable) =
+                BankingStage::record_transactions(bank.slot(), &transactions, &results, &recorder);
             res.unwrap();
             assert!(retryable.is_empty());
             let (_bank, (entry, _tick_height)) = entry_receiver.recv().unwrap();
@@ -1888,7 +2117,7 @@ mod tests {
             // txs
             let (res, retryable) = BankingStage::record_transactions(
                 bank.slot() + 1,
-                transactions.iter(),
+                &transactions,
                 &results,
                 &recorder,
             );
@@ -2278,7 +2507,7 @@ mod tests {
                 let valid_indexes = (0..32)
                     .filter_map(|x| if x % 2 != 0 { Some(x as usize) } else { None })
                     .collect_vec();
-                (packets, valid_indexes, false)
+                (packets, valid_indexes, false, vec![0; 32])
             })
             .collect_vec();
 
@@ -2301,6 +2530,216 @@ mod tests {
         assert_eq!(result.len(), 240);
     }
 
+    #[test]
+    fn test_stake_weighted_forwarding_order() {
+        let packet = |ip: [u8; 4], sender_stake: u64, port: u16| {
+            let mut p = Packet::default();
+            p.meta.set_addr(&std::net::SocketAddr::from((ip, port)));
+            p.meta.sender_stake = sender_stake;
+            p
+        };
+        let packets = vec![
+            packet([10, 0, 0, 1], 0, 0),
+            packet([10, 0, 0, 1], 0, 1),
+            packet([10, 0, 0, 2], 0, 2),
+            packet([10, 0, 0, 3], 100, 3),
+            packet([10, 0, 0, 3], 100, 4),
+            packet([10, 0, 0, 3], 100, 5),
+            packet([10, 0, 0, 3], 100, 6),
+            packet([10, 0, 0, 4], 200, 7),
+            packet([10, 0, 0, 4], 200, 8),
+            packet([10, 0, 0, 4], 200, 9),
+            packet([10, 0, 0, 4], 200, 10),
+        ];
+        let order: Vec<_> = BankingStage::stake_weighted_forwarding_order(packets.iter().collect())
+            .into_iter()
+            .map(|p| p.meta.port)
+            .collect();
+        // Staked senders are interleaved in proportion to stake, then unstaked senders take
+        // turns
+        assert_eq!(order, vec![7, 3, 8, 9, 4, 10, 5, 6, 0, 2, 1]);
+    }
+
+    #[test]
+    fn test_forward_buffered_packets_marks_sent_batches() {
+        solana_logger::setup();
+        let recv_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let send_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut unprocessed_packets: UnprocessedPackets = (0..3)
+            .map(|_| {
+                let packets = Packets::new(
+                    (0..400)
+                        .map(|_| {
+                            let mut p = Packet::default();
+                            p.meta.size = PACKET_DATA_SIZE;
+                            p
+                        })
+                        .collect_vec(),
+                );
+                (packets, (0..400).collect_vec(), false, vec![0; 400])
+            })
+            .collect();
+        let data_budget = DataBudget::default();
+
+        // The budget holds 1000 full packets, so the third batch is only partially sent
+        BankingStage::forward_buffered_packets(
+            &send_socket,
+            &recv_socket.local_addr().unwrap(),
+            &mut unprocessed_packets,
+            &data_budget,
+        )
+        .unwrap();
+        let forwarded: Vec<_> = unprocessed_packets
+            .iter()
+            .map(|(_, _, forwarded, _)| *forwarded)
+            .collect();
+        assert_eq!(forwarded, vec![true, true, false]);
+    }
+
+    #[test]
+    fn test_transactions_from_packets_orders_by_priority() {
+        let keypair = Keypair::new();
+        let prioritized_transaction = |compute_unit_price: u64| {
+            Transaction::new_signed_with_payer(
+                &[
+                    ComputeBudgetInstruction::set_compute_unit_price(compute_unit_price),
+                    system_instruction::transfer(
+                        &keypair.pubkey(),
+                        &solana_sdk::pubkey::new_rand(),
+                        1,
+                    ),
+                ],
+                Some(&keypair.pubkey()),
+                &[&keypair],
+                Hash::default(),
+            )
+        };
+        let transactions = vec![
+            prioritized_transaction(1),
+            prioritized_transaction(0),
+            prioritized_transaction(10),
+            prioritized_transaction(1),
+        ];
+        let packets = to_packets_chunked(&transactions, 4).pop().unwrap();
+        let packet_indexes = vec![0, 1, 2, 3];
+        let bank = Bank::new(&create_genesis_config(10_000).genesis_config);
+
+        let (_, transaction_to_packet_indexes) =
+            BankingStage::transactions_from_packets(&packets, &packet_indexes, &bank, false);
+        assert_eq!(transaction_to_packet_indexes, vec![0, 1, 2, 3]);
+
+        let (hashed_transactions, transaction_to_packet_indexes) =
+            BankingStage::transactions_from_packets(&packets, &packet_indexes, &bank, true);
+        assert_eq!(transaction_to_packet_indexes, vec![2, 0, 3, 1]);
+        assert_eq!(hashed_transactions[0].transaction(), &transactions[2]);
+    }
+
+    #[test]
+    fn test_transactions_from_packets_versioned() {
+        let GenesisConfigInfo {
+            mut genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+        let recipient = solana_sdk::pubkey::new_rand();
+        let message = v0::Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![mint_keypair.pubkey(), recipient, system_program::id()],
+            recent_blockhash: genesis_config.hash(),
+            instructions: vec![CompiledInstruction::new(
+                2,
+                &system_instruction::SystemInstruction::Transfer { lamports: 1 },
+                vec![0, 1],
+            )],
+            address_table_lookups: vec![],
+        };
+        let transaction =
+            VersionedTransaction::try_new(VersionedMessage::V0(message), &[&mint_keypair]).unwrap();
+        let packets = to_packets_chunked(&[transaction.clone()], 1).pop().unwrap();
+
+        // Versioned messages are dropped until the feature is activated
+        let bank = Bank::new(&genesis_config);
+        let (hashed_transactions, _) =
+            BankingStage::transactions_from_packets(&packets, &[0], &bank, false);
+        assert!(hashed_transactions.is_empty());
+
+        activate_all_features(&mut genesis_config);
+        let bank = Bank::new(&genesis_config);
+        let (hashed_transactions, transaction_to_packet_indexes) =
+            BankingStage::transactions_from_packets(&packets, &[0], &bank, false);
+        assert_eq!(transaction_to_packet_indexes, vec![0]);
+        assert_eq!(
+            hashed_transactions[0].transaction().message.account_keys,
+            vec![mint_keypair.pubkey(), recipient, system_program::id()]
+        );
+        assert_eq!(
+            hashed_transactions[0].message_hash,
+            transaction.message.hash()
+        );
+        assert_eq!(
+            hashed_transactions[0].to_versioned_transaction(),
+            transaction
+        );
+    }
+
+    #[test]
+    fn test_prioritize_buffered_packets() {
+        let keypair = Keypair::new();
+        let prioritized_transaction = |compute_unit_price: u64| {
+            Transaction::new_signed_with_payer(
+                &[
+                    ComputeBudgetInstruction::set_compute_unit_price(compute_unit_price),
+                    system_instruction::transfer(
+                        &keypair.pubkey(),
+                        &solana_sdk::pubkey::new_rand(),
+                        1,
+                    ),
+                ],
+                Some(&keypair.pubkey()),
+                &[&keypair],
+                Hash::default(),
+            )
+        };
+        let transactions = vec![
+            prioritized_transaction(1),
+            prioritized_transaction(0),
+            prioritized_transaction(10),
+            prioritized_transaction(5),
+        ];
+        let mut buffered_packets: UnprocessedPackets = to_packets_chunked(&transactions, 2)
+            .into_iter()
+            .map(|packets| {
+                let packet_indexes = vec![0, 1];
+                let prices = BankingStage::packet_compute_unit_prices(&packets, &packet_indexes);
+                (packets, packet_indexes, true, prices)
+            })
+            .collect();
+        buffered_packets[1].2 = false;
+
+        BankingStage::prioritize_buffered_packets(&mut buffered_packets);
+        assert_eq!(buffered_packets.len(), 1);
+        let (packets, packet_indexes, forwarded, prices) = &buffered_packets[0];
+        assert_eq!(packet_indexes, &vec![0, 1, 2, 3]);
+        assert_eq!(prices, &vec![10, 5, 1, 0]);
+        assert!(!forwarded);
+        let bank = Bank::new(&create_genesis_config(10_000).genesis_config);
+        let (hashed_transactions, _) =
+            BankingStage::transactions_from_packets(packets, packet_indexes, &bank, false);
+        assert_eq!(hashed_transactions[0].transaction(), &transactions[2]);
+        assert_eq!(hashed_transactions[3].transaction(), &transactions[1]);
+
+        // Already ordered buffers are left as they are
+        let first_packet = buffered_packets[0].0.packets[0].clone();
+        BankingStage::prioritize_buffered_packets(&mut buffered_packets);
+        assert_eq!(buffered_packets.len(), 1);
+        assert_eq!(buffered_packets[0].0.packets[0], first_packet);
+        assert_eq!(buffered_packets[0].3, vec![10, 5, 1, 0]);
+    }
+
     #[test]
     fn test_process_transactions_returns_unprocessed_txs() {
         solana_logger::setup();
@@ -2435,6 +2874,8 @@ mod tests {
             let transaction_status_service = TransactionStatusService::new(
                 transaction_status_receiver,
                 Arc::new(AtomicU64::default()),
+                true,
+                None,
                 blockstore.clone(),
                 &Arc::new(AtomicBool::new(false)),
             );
@@ -2559,6 +3000,7 @@ mod tests {
                 all_packets,
                 (0..num_conflicting_transactions).into_iter().collect(),
                 false,
+                vec![0; num_conflicting_transactions],
             )]
             .into_iter()
             .collect();
@@ -2626,7 +3068,7 @@ mod tests {
             let mut buffered_packets: UnprocessedPackets = packets_vec
                 .clone()
                 .into_iter()
-                .map(|single_packets| (single_packets, vec![0], false))
+                .map(|single_packets| (single_packets, vec![0], false, vec![0]))
                 .collect();
 
             let (continue_sender, continue_receiver) = unbounded();
@@ -2708,7 +3150,7 @@ mod tests {
         // Create `Packets` with 1 unprocessed element
         let single_element_packets = Packets::new(vec![Packet::default()]);
         let mut unprocessed_packets: UnprocessedPackets =
-            vec![(single_element_packets.clone(), vec![0], false)]
+            vec![(single_element_packets.clone(), vec![0], false, vec![0])]
                 .into_iter()
                 .collect();
         // Set the limit to 2
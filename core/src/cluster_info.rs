@@ -43,8 +43,8 @@ use solana_measure::measure::Measure;
 use solana_measure::thread_mem_usage;
 use solana_metrics::{inc_new_counter_debug, inc_new_counter_error};
 use solana_net_utils::{
-    bind_common, bind_common_in_range, bind_in_range, find_available_port_in_range,
-    multi_bind_in_range, PortRange,
+    bind_common, bind_common_in_range, bind_in_range, bind_to, bind_two_in_range_with_offset,
+    find_available_port_in_range, multi_bind_in_range, PortRange,
 };
 use solana_perf::packet::{
     limited_deserialize, to_packets_with_destination, Packet, Packets, PacketsRecycler,
@@ -57,6 +57,7 @@ use solana_sdk::{
     feature_set::{self, FeatureSet},
     hash::Hash,
     pubkey::Pubkey,
+    quic::QUIC_PORT_OFFSET,
     signature::{Keypair, Signable, Signature, Signer},
     timing::timestamp,
     transaction::Transaction,
@@ -2825,6 +2826,8 @@ pub struct Sockets {
     pub tvu_forwards: Vec<UdpSocket>,
     pub tpu: Vec<UdpSocket>,
     pub tpu_forwards: Vec<UdpSocket>,
+    /// Bound at the TPU port plus `QUIC_PORT_OFFSET`
+    pub tpu_quic: UdpSocket,
     pub broadcast: Vec<UdpSocket>,
     pub repair: UdpSocket,
     pub retransmit_sockets: Vec<UdpSocket>,
@@ -2844,7 +2847,12 @@ impl Node {
     }
     pub fn new_localhost_with_pubkey(pubkey: &Pubkey) -> Self {
         let bind_ip_addr = IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0));
-        let tpu = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let ((_, tpu), (_, tpu_quic)) = bind_two_in_range_with_offset(
+            IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+            (1024, 65535),
+            QUIC_PORT_OFFSET,
+        )
+        .unwrap();
         let (gossip_port, (gossip, ip_echo)) =
             bind_common_in_range(bind_ip_addr, (1024, 65535)).unwrap();
         let gossip_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), gossip_port);
@@ -2886,6 +2894,7 @@ impl Node {
                 tvu_forwards: vec![tvu_forwards],
                 tpu: vec![tpu],
                 tpu_forwards: vec![tpu_forwards],
+                tpu_quic,
                 broadcast,
                 repair,
                 retransmit_sockets: vec![retransmit_socket],
@@ -2924,7 +2933,9 @@ impl Node {
             Self::get_gossip_port(gossip_addr, port_range, bind_ip_addr);
         let (tvu_port, tvu) = Self::bind(bind_ip_addr, port_range);
         let (tvu_forwards_port, tvu_forwards) = Self::bind(bind_ip_addr, port_range);
-        let (tpu_port, tpu) = Self::bind(bind_ip_addr, port_range);
+        let ((tpu_port, tpu), (_, tpu_quic)) =
+            bind_two_in_range_with_offset(bind_ip_addr, port_range, QUIC_PORT_OFFSET)
+                .expect("Failed to bind");
         let (tpu_forwards_port, tpu_forwards) = Self::bind(bind_ip_addr, port_range);
         let (_, retransmit_socket) = Self::bind(bind_ip_addr, port_range);
         let (repair_port, repair) = Self::bind(bind_ip_addr, port_range);
@@ -2960,6 +2971,7 @@ impl Node {
                 tvu_forwards: vec![tvu_forwards],
                 tpu: vec![tpu],
                 tpu_forwards: vec![tpu_forwards],
+                tpu_quic,
                 broadcast: vec![broadcast],
                 repair,
                 retransmit_sockets: vec![retransmit_socket],
@@ -2983,8 +2995,16 @@ impl Node {
         let (tvu_forwards_port, tvu_forwards_sockets) =
             multi_bind_in_range(bind_ip_addr, port_range, 8).expect("tvu_forwards multi_bind");
 
-        let (tpu_port, tpu_sockets) =
-            multi_bind_in_range(bind_ip_addr, port_range, 32).expect("tpu multi_bind");
+        let ((tpu_port, tpu_probe), (_, tpu_quic)) =
+            bind_two_in_range_with_offset(bind_ip_addr, port_range, QUIC_PORT_OFFSET)
+                .expect("tpu_quic bind");
+        // The probe socket only reserves a port whose QUIC offset is free as well; drop it
+        // so the TPU port can be shared by several reuseport sockets.
+        drop(tpu_probe);
+        let tpu_sockets = (0..32)
+            .map(|_| bind_to(bind_ip_addr, tpu_port, true))
+            .collect::<std::io::Result<Vec<_>>>()
+            .expect("tpu multi_bind");
 
         let (tpu_forwards_port, tpu_forwards_sockets) =
             multi_bind_in_range(bind_ip_addr, port_range, 8).expect("tpu_forwards multi_bind");
@@ -3023,6 +3043,7 @@ impl Node {
                 tvu_forwards: tvu_forwards_sockets,
                 tpu: tpu_sockets,
                 tpu_forwards: tpu_forwards_sockets,
+                tpu_quic,
                 broadcast,
                 repair,
                 retransmit_sockets,
@@ -8,8 +8,15 @@ use crate::rpc::JsonRpcRequestProcessor;
 use evm_rpc::error::EvmStateError;
 use evm_rpc::{
     chain::ChainERPC,
-    error::{into_native_error, BlockNotFound, Error, StateNotFoundForBlock},
+    error::{
+        into_native_error, BlockNotFound, Error, StateNotFoundForBlock, TokenIndexNotEnabled,
+        TokenIndexRangeUnavailable,
+    },
     general::GeneralERPC,
+    token::{
+        RPCTokenBalance, RPCTokenBalances, RPCTokenStandard, RPCTokenTransfer,
+        RPCTokenTransferCursor, TokenERPC,
+    },
     trace::{TraceERPC, TraceMeta},
     BlockId, BlockRelId, Bytes, Either, Hex, RPCBlock, RPCLog, RPCLogFilter, RPCReceipt,
     RPCTopicFilter, RPCTransaction,
@@ -20,7 +27,9 @@ use evm_state::{
 use jsonrpc_core::BoxFuture;
 use snafu::ensure;
 use snafu::ResultExt;
+use solana_ledger::blockstore_db::EvmLogLocation;
 use solana_runtime::bank::Bank;
+use solana_transaction_status::parse_evm_token::EvmTokenStandard;
 use std::{cell::RefCell, future::ready, sync::Arc};
 use crate::rpc_health::RpcHealthStatus;
 
@@ -528,7 +537,7 @@ impl ChainERPC for ChainErpcImpl {
         log_filter: RPCLogFilter,
     ) -> BoxFuture<Result<Vec<RPCLog>, Error>> {
         Box::pin(async move {
-            const MAX_NUM_BLOCKS: u64 = 2000;
+            let max_num_blocks = meta.max_evm_logs_block_range();
             let block_num = meta
                 .get_last_available_evm_block()
                 .ok_or(Error::ArchiveNotSupported)?;
@@ -538,7 +547,7 @@ impl ChainERPC for ChainErpcImpl {
             let from = block_parse_confirmed_num(log_filter.from_block, &meta)
                 .await
                 .unwrap_or(block_num);
-            if to > from + MAX_NUM_BLOCKS {
+            if to > from.saturating_add(max_num_blocks) {
                 warn!(
                     "Log filter, block range is too big, reducing, to={}, from={}",
                     to, from
@@ -546,7 +555,7 @@ impl ChainERPC for ChainErpcImpl {
                 return Err(Error::InvalidBlocksRange {
                     starting: from,
                     ending: to,
-                    batch_size: Some(MAX_NUM_BLOCKS),
+                    batch_size: Some(max_num_blocks),
                 });
             }
 
@@ -735,6 +744,112 @@ impl TraceERPC for TraceErpcImpl {
     }
 }
 
+/// Maximum number of transfers returned by `evm_getTokenTransfers`.
+const MAX_TOKEN_TRANSFERS_LIMIT: usize = 1000;
+
+fn token_standard(standard: EvmTokenStandard) -> RPCTokenStandard {
+    match standard {
+        EvmTokenStandard::Erc20 => RPCTokenStandard::Erc20,
+        EvmTokenStandard::Erc721 => RPCTokenStandard::Erc721,
+    }
+}
+
+/// Returns the first block covered by token index.
+fn token_index_first_block(meta: &JsonRpcRequestProcessor) -> Result<u64, Error> {
+    ensure!(meta.evm_token_index_enabled(), TokenIndexNotEnabled);
+    let range = meta.get_evm_token_index_range().map_err(|e| {
+        warn!("Failed to read evm token index range: {:?}", e);
+        Error::ServerError {}
+    })?;
+    // Nothing is indexed yet
+    Ok(range.map_or(u64::MAX, |(first, _last)| first))
+}
+
+pub struct TokenErpcImpl;
+impl TokenERPC for TokenErpcImpl {
+    type Metadata = JsonRpcRequestProcessor;
+
+    #[instrument(skip(self, meta))]
+    fn token_transfers(
+        &self,
+        meta: Self::Metadata,
+        address: Hex<Address>,
+        token: Option<Hex<Address>>,
+        limit: Option<usize>,
+        before: Option<RPCTokenTransferCursor>,
+    ) -> Result<Vec<RPCTokenTransfer>, Error> {
+        let first_indexed_block = token_index_first_block(&meta)?;
+        let limit = limit
+            .unwrap_or(MAX_TOKEN_TRANSFERS_LIMIT)
+            .min(MAX_TOKEN_TRANSFERS_LIMIT);
+        let before = before.map(|cursor| EvmLogLocation {
+            block_num: cursor.block_number.0,
+            transaction_id: cursor.transaction_index.0,
+            log_index: cursor.log_index.0,
+        });
+        if let Some(before) = before {
+            ensure!(
+                before.block_num >= first_indexed_block,
+                TokenIndexRangeUnavailable {
+                    block: before.block_num,
+                    first_indexed_block,
+                }
+            );
+        }
+        let transfers = meta
+            .get_evm_token_transfers(address.0, token.map(|token| token.0), before, limit)
+            .map_err(|e| {
+                warn!(
+                    "Failed to read evm token transfers of {:?}: {:?}",
+                    address, e
+                );
+                Error::ServerError {}
+            })?;
+        Ok(transfers
+            .into_iter()
+            .map(|(location, transfer)| RPCTokenTransfer {
+                block_number: Hex(location.block_num),
+                transaction_hash: Hex(transfer.transaction_hash),
+                transaction_index: Hex(location.transaction_id),
+                log_index: Hex(location.log_index),
+                token: Hex(transfer.token),
+                standard: token_standard(transfer.standard),
+                from: Hex(transfer.from),
+                to: Hex(transfer.to),
+                value: Hex(transfer.value),
+            })
+            .collect())
+    }
+
+    #[instrument(skip(self, meta))]
+    fn token_balances(
+        &self,
+        meta: Self::Metadata,
+        address: Hex<Address>,
+    ) -> Result<RPCTokenBalances, Error> {
+        let from_block = token_index_first_block(&meta)?;
+        let balances = meta.get_evm_token_balances(address.0).map_err(|e| {
+            warn!(
+                "Failed to read evm token balances of {:?}: {:?}",
+                address, e
+            );
+            Error::ServerError {}
+        })?;
+        Ok(RPCTokenBalances {
+            from_block: Hex(from_block),
+            balances: balances
+                .into_iter()
+                .map(|balance| RPCTokenBalance {
+                    token: Hex(balance.token),
+                    standard: token_standard(balance.standard),
+                    amount: Hex(balance.amount),
+                    token_ids: balance.token_ids.into_iter().map(Hex).collect(),
+                })
+                .collect(),
+        })
+    }
+}
+
 struct TxOutput {
     exit_reason: evm_state::ExitReason,
     exit_data: Vec<u8>,
@@ -1,3 +1,4 @@
+use crate::accountsdb_plugin::EvmBlockNotifier;
 use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use solana_ledger::blockstore::Blockstore;
 use std::{
@@ -23,6 +24,8 @@ impl EvmRecorderService {
     pub fn new(
         evm_recorder_receiver: EvmRecorderReceiver,
         blockstore: Arc<Blockstore>,
+        enable_rpc_transaction_history: bool,
+        evm_block_notifier: Option<EvmBlockNotifier>,
         exit: &Arc<AtomicBool>,
     ) -> Self {
         let exit = exit.clone();
@@ -32,9 +35,12 @@ impl EvmRecorderService {
                 if exit.load(Ordering::Relaxed) {
                     break;
                 }
-                if let Err(RecvTimeoutError::Disconnected) =
-                    Self::write_evm_record(&evm_recorder_receiver, &blockstore)
-                {
+                if let Err(RecvTimeoutError::Disconnected) = Self::write_evm_record(
+                    &evm_recorder_receiver,
+                    &blockstore,
+                    enable_rpc_transaction_history,
+                    &evm_block_notifier,
+                ) {
                     break;
                 }
             })
@@ -45,8 +51,16 @@ impl EvmRecorderService {
     fn write_evm_record(
         evm_records_receiver: &EvmRecorderReceiver,
         blockstore: &Arc<Blockstore>,
+        enable_rpc_transaction_history: bool,
+        evm_block_notifier: &Option<EvmBlockNotifier>,
     ) -> Result<(), RecvTimeoutError> {
         let block = evm_records_receiver.recv_timeout(Duration::from_secs(1))?;
+        if let Some(evm_block_notifier) = evm_block_notifier {
+            evm_block_notifier.read().unwrap().notify_evm_block(&block);
+        }
+        if !enable_rpc_transaction_history {
+            return Ok(());
+        }
         let block_header = block.header;
         debug!("Writing evm block num = {}", block_header.block_number);
         blockstore
@@ -1,5 +1,7 @@
 pub mod block_recorder;
 pub mod state_recorder;
+pub mod token_indexer;
 // pub use transaction_recorder;
 pub use block_recorder::*;
 pub use state_recorder::*;
+pub use token_indexer::*;
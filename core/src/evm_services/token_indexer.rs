@@ -0,0 +1,76 @@
+use solana_ledger::blockstore::Blockstore;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, sleep, Builder, JoinHandle},
+    time::Duration,
+};
+
+use evm_state::BlockNum;
+
+const INDEXER_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Follows confirmed evm blocks, and stores ERC-20/ERC-721 transfers by holder address.
+pub struct EvmTokenIndexerService {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl EvmTokenIndexerService {
+    pub fn new(blockstore: Arc<Blockstore>, exit: &Arc<AtomicBool>) -> Self {
+        let exit = exit.clone();
+        let thread_hdl = Builder::new()
+            .name("evm-token-indexer".to_string())
+            .spawn(move || loop {
+                if exit.load(Ordering::Relaxed) {
+                    break;
+                }
+                Self::index_confirmed_blocks(&blockstore, &exit);
+                sleep(INDEXER_POLL_INTERVAL);
+            })
+            .unwrap();
+        Self { thread_hdl }
+    }
+
+    fn next_block_to_index(blockstore: &Blockstore) -> Option<BlockNum> {
+        let first_available = blockstore.get_first_available_evm_block().ok()?;
+        let next_block = match blockstore.read_evm_token_index_progress() {
+            Ok(Some(last_indexed)) => last_indexed + 1,
+            Ok(None) => first_available,
+            Err(e) => {
+                warn!("Failed to read evm token index progress: {:?}", e);
+                return None;
+            }
+        };
+        Some(next_block.max(first_available))
+    }
+
+    fn index_confirmed_blocks(blockstore: &Blockstore, exit: &AtomicBool) {
+        let mut block_num = match Self::next_block_to_index(blockstore) {
+            Some(block_num) => block_num,
+            None => return,
+        };
+        while !exit.load(Ordering::Relaxed) {
+            match blockstore.get_evm_block(block_num) {
+                // Wait until block is rooted, transfers from forks should not be indexed.
+                Ok((block, true)) => {
+                    debug!("Indexing evm token transfers, block num = {}", block_num);
+                    if let Err(e) = blockstore.write_evm_token_transfers(&block) {
+                        warn!(
+                            "Failed to index evm token transfers, block num = {}: {:?}",
+                            block_num, e
+                        );
+                        return;
+                    }
+                    block_num += 1;
+                }
+                _ => return,
+            }
+        }
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
@@ -0,0 +1,107 @@
+//! The `find_packet_sender_stake_stage` tags each packet received by the TPU with the stake of
+//! the node that sent it, so that the following stages can favor staked peers under load.
+
+use solana_measure::measure::Measure;
+use solana_metrics::datapoint_debug;
+use solana_perf::packet::Packets;
+use solana_streamer::streamer::{self, PacketReceiver, PacketSender, StreamerError};
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{mpsc::RecvTimeoutError, Arc, RwLock},
+    thread::{self, Builder, JoinHandle},
+};
+
+const RECV_BATCH_MAX: usize = 1_000;
+
+pub struct FindPacketSenderStakeStage {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl FindPacketSenderStakeStage {
+    pub fn new(
+        packet_receiver: PacketReceiver,
+        sender: PacketSender,
+        ip_to_stake: Arc<RwLock<HashMap<IpAddr, u64>>>,
+    ) -> Self {
+        let thread_hdl = Builder::new()
+            .name("solana-find-packet-sender-stake".to_string())
+            .spawn(move || loop {
+                match streamer::recv_batch(&packet_receiver, RECV_BATCH_MAX) {
+                    Ok((mut batches, num_packets, recv_time)) => {
+                        let mut apply_stake_time = Measure::start("apply_sender_stakes");
+                        Self::apply_sender_stakes(&mut batches, &ip_to_stake.read().unwrap());
+                        apply_stake_time.stop();
+                        datapoint_debug!(
+                            "find_packet_sender_stake_stage",
+                            ("num_packets", num_packets, i64),
+                            ("recv_time_ms", recv_time, i64),
+                            ("apply_stake_time_us", apply_stake_time.as_us(), i64),
+                        );
+                        for batch in batches {
+                            if sender.send(batch).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(StreamerError::RecvTimeoutError(RecvTimeoutError::Disconnected)) => break,
+                    Err(StreamerError::RecvTimeoutError(RecvTimeoutError::Timeout)) => (),
+                    Err(e) => error!("{:?}", e),
+                }
+            })
+            .unwrap();
+        Self { thread_hdl }
+    }
+
+    /// Tags packets with the stake of the node at their source address. Packets already tagged,
+    /// such as the ones received over QUIC from an authenticated identity, keep the larger stake.
+    fn apply_sender_stakes(batches: &mut [Packets], ip_to_stake: &HashMap<IpAddr, u64>) {
+        for packet in batches
+            .iter_mut()
+            .flat_map(|batch| batch.packets.iter_mut())
+        {
+            if let Some(stake) = ip_to_stake.get(&packet.meta.addr().ip()) {
+                packet.meta.sender_stake = packet.meta.sender_stake.max(*stake);
+            }
+        }
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_perf::packet::Packet;
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    #[test]
+    fn test_apply_sender_stakes() {
+        let staked_ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let unstaked_ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+        let ip_to_stake: HashMap<_, _> = vec![(staked_ip, 42)].into_iter().collect();
+
+        let packet = |ip, sender_stake| {
+            let mut packet = Packet::default();
+            packet.meta.set_addr(&SocketAddr::new(ip, 8000));
+            packet.meta.sender_stake = sender_stake;
+            packet
+        };
+        let mut batches = vec![Packets::new(vec![
+            packet(staked_ip, 0),
+            packet(unstaked_ip, 0),
+            packet(unstaked_ip, 7),
+            packet(staked_ip, 100),
+        ])];
+        FindPacketSenderStakeStage::apply_sender_stakes(&mut batches, &ip_to_stake);
+
+        let stakes: Vec<_> = batches[0]
+            .packets
+            .iter()
+            .map(|packet| packet.meta.sender_stake)
+            .collect();
+        assert_eq!(stakes, vec![42, 0, 7, 100]);
+    }
+}
@@ -8,6 +8,7 @@
 //!
 
 pub mod accounts_hash_verifier;
+pub mod accountsdb_plugin;
 pub mod banking_stage;
 pub mod bigtable_upload_service;
 pub mod broadcast_stage;
@@ -40,6 +41,7 @@ pub mod epoch_slots;
 pub mod evm_rpc_impl;
 pub mod evm_services;
 pub mod fetch_stage;
+pub mod find_packet_sender_stake_stage;
 pub mod fork_choice;
 pub mod gen_keys;
 pub mod gossip_service;
@@ -76,8 +78,10 @@ pub mod sigverify;
 pub mod sigverify_shreds;
 pub mod sigverify_stage;
 pub mod snapshot_packager_service;
+pub mod staked_nodes_updater_service;
 pub mod test_validator;
 pub mod tpu;
+pub mod transaction_scheduler;
 pub mod transaction_status_service;
 pub mod tree_diff;
 pub mod tvu;
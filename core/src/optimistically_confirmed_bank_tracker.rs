@@ -2,7 +2,7 @@
 //! most recent optimistically confirmed bank for use in rpc services, and triggers gossip
 //! subscription notifications
 
-use crate::rpc_subscriptions::RpcSubscriptions;
+use crate::{accountsdb_plugin::SlotStatusNotifier, rpc_subscriptions::RpcSubscriptions};
 use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use solana_client::rpc_response::{SlotTransactionStats, SlotUpdate};
 use solana_runtime::{bank::Bank, bank_forks::BankForks};
@@ -61,6 +61,7 @@ impl OptimisticallyConfirmedBankTracker {
         bank_forks: Arc<RwLock<BankForks>>,
         optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
         subscriptions: Arc<RpcSubscriptions>,
+        slot_status_notifier: Option<SlotStatusNotifier>,
     ) -> Self {
         let exit_ = exit.clone();
         let mut pending_optimistically_confirmed_banks = HashSet::new();
@@ -77,6 +78,7 @@ impl OptimisticallyConfirmedBankTracker {
                     &optimistically_confirmed_bank,
                     &subscriptions,
                     &mut pending_optimistically_confirmed_banks,
+                    &slot_status_notifier,
                 ) {
                     break;
                 }
@@ -91,8 +93,22 @@ impl OptimisticallyConfirmedBankTracker {
         optimistically_confirmed_bank: &Arc<RwLock<OptimisticallyConfirmedBank>>,
         subscriptions: &Arc<RpcSubscriptions>,
         mut pending_optimistically_confirmed_banks: &mut HashSet<Slot>,
+        slot_status_notifier: &Option<SlotStatusNotifier>,
     ) -> Result<(), RecvTimeoutError> {
         let notification = receiver.recv_timeout(Duration::from_secs(1))?;
+        if let (Some(slot_status_notifier), BankNotification::OptimisticallyConfirmed(slot)) =
+            (slot_status_notifier, &notification)
+        {
+            let parent = bank_forks
+                .read()
+                .unwrap()
+                .get(*slot)
+                .map(|bank| bank.parent_slot());
+            slot_status_notifier
+                .read()
+                .unwrap()
+                .notify_slot_confirmed(*slot, parent);
+        }
         Self::process_notification(
             notification,
             bank_forks,
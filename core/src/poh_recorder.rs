@@ -25,7 +25,7 @@ use solana_sdk::hash::Hash;
 use solana_sdk::poh_config::PohConfig;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::timing;
-use solana_sdk::transaction::Transaction;
+use solana_sdk::transaction::VersionedTransaction;
 use std::cmp;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
@@ -60,14 +60,14 @@ pub type BankStart = (Arc<Bank>, Arc<Instant>);
 
 pub struct Record {
     pub mixin: Hash,
-    pub transactions: Vec<Transaction>,
+    pub transactions: Vec<VersionedTransaction>,
     pub slot: Slot,
     pub sender: CrossbeamSender<Result<()>>,
 }
 impl Record {
     pub fn new(
         mixin: Hash,
-        transactions: Vec<Transaction>,
+        transactions: Vec<VersionedTransaction>,
         slot: Slot,
         sender: CrossbeamSender<Result<()>>,
     ) -> Self {
@@ -105,7 +105,7 @@ impl TransactionRecorder {
         &self,
         bank_slot: Slot,
         mixin: Hash,
-        transactions: Vec<Transaction>,
+        transactions: Vec<VersionedTransaction>,
     ) -> Result<()> {
         // create a new channel so that there is only 1 sender and when it goes out of scope, the receiver fails
         let (result_sender, result_receiver) = unbounded();
@@ -565,7 +565,7 @@ impl PohRecorder {
         &mut self,
         bank_slot: Slot,
         mixin: Hash,
-        transactions: Vec<Transaction>,
+        transactions: Vec<VersionedTransaction>,
     ) -> Result<()> {
         // Entries without transactions are used to track real-time passing in the ledger and
         // cannot be generated by `record()`
@@ -996,7 +996,7 @@ mod tests {
             };
             poh_recorder.set_working_bank(working_bank);
             poh_recorder.tick();
-            let tx = test_tx();
+            let tx = test_tx().into();
             let h1 = hash(b"hello world!");
             assert!(poh_recorder.record(bank.slot(), h1, vec![tx]).is_err());
             assert!(entry_receiver.try_recv().is_err());
@@ -1037,7 +1037,7 @@ mod tests {
             poh_recorder.tick();
             assert_eq!(poh_recorder.tick_cache.len(), 1);
             assert_eq!(poh_recorder.tick_height, 1);
-            let tx = test_tx();
+            let tx = test_tx().into();
             let h1 = hash(b"hello world!");
             assert_matches!(
                 poh_recorder.record(bank.slot() + 1, h1, vec![tx]),
@@ -1080,7 +1080,7 @@ mod tests {
             poh_recorder.tick();
             assert_eq!(poh_recorder.tick_cache.len(), 1);
             assert_eq!(poh_recorder.tick_height, 1);
-            let tx = test_tx();
+            let tx = test_tx().into();
             let h1 = hash(b"hello world!");
             assert!(poh_recorder.record(bank.slot(), h1, vec![tx]).is_ok());
             assert_eq!(poh_recorder.tick_cache.len(), 0);
@@ -1127,7 +1127,7 @@ mod tests {
             poh_recorder.tick();
             poh_recorder.tick();
             assert_eq!(poh_recorder.tick_height, 2);
-            let tx = test_tx();
+            let tx = test_tx().into();
             let h1 = hash(b"hello world!");
             assert!(poh_recorder.record(bank.slot(), h1, vec![tx]).is_err());
 
@@ -1376,7 +1376,7 @@ mod tests {
                 poh_recorder.tick();
             }
 
-            let tx = test_tx();
+            let tx = test_tx().into();
             let h1 = hash(b"hello world!");
             assert!(poh_recorder.record(bank.slot(), h1, vec![tx]).is_err());
             assert!(poh_recorder.working_bank.is_none());
@@ -432,7 +432,7 @@ mod tests {
                             let _ = poh_recorder.lock().unwrap().record(
                                 bank.slot(),
                                 h1,
-                                vec![tx.clone()],
+                                vec![tx.clone().into()],
                             );
                             time.stop();
                             total_us += time.as_us();
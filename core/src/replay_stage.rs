@@ -1,6 +1,7 @@
 //! The `replay_stage` replays transactions broadcast by the leader.
 
 use crate::{
+    accountsdb_plugin::SlotStatusNotifier,
     broadcast_stage::RetransmitSlotsSender,
     cache_block_meta_service::CacheBlockMetaSender,
     cluster_info::ClusterInfo,
@@ -126,6 +127,7 @@ pub struct ReplayStageConfig {
     pub evm_block_recorder_sender: Option<EvmRecorderSender>,
     pub evm_state_recorder_sender: Option<EvmStateRecorderSender>,
     pub bank_notification_sender: Option<BankNotificationSender>,
+    pub slot_status_notifier: Option<SlotStatusNotifier>,
     pub wait_for_vote_to_start_leader: bool,
 }
 
@@ -310,6 +312,7 @@ impl ReplayStage {
             evm_block_recorder_sender,
             evm_state_recorder_sender,
             bank_notification_sender,
+            slot_status_notifier,
             wait_for_vote_to_start_leader,
         } = config;
 
@@ -390,6 +393,7 @@ impl ReplayStage {
                         &mut heaviest_subtree_fork_choice,
                         &replay_vote_sender,
                         &bank_notification_sender,
+                        &slot_status_notifier,
                         &rewards_recorder_sender,
                         &subscriptions,
                         &evm_block_recorder_sender,
@@ -592,6 +596,7 @@ impl ReplayStage {
                             &block_commitment_cache,
                             &mut heaviest_subtree_fork_choice,
                             &bank_notification_sender,
+                            &slot_status_notifier,
                             &mut duplicate_slots_tracker,
                             &mut gossip_duplicate_confirmed_slots,
                             &mut unfrozen_gossip_verified_vote_hashes,
@@ -1340,6 +1345,7 @@ impl ReplayStage {
         block_commitment_cache: &Arc<RwLock<BlockCommitmentCache>>,
         heaviest_subtree_fork_choice: &mut HeaviestSubtreeForkChoice,
         bank_notification_sender: &Option<BankNotificationSender>,
+        slot_status_notifier: &Option<SlotStatusNotifier>,
         duplicate_slots_tracker: &mut DuplicateSlotsTracker,
         gossip_duplicate_confirmed_slots: &mut GossipDuplicateConfirmedSlots,
         unfrozen_gossip_verified_vote_hashes: &mut UnfrozenGossipVerifiedVoteHashes,
@@ -1376,6 +1382,19 @@ impl ReplayStage {
             blockstore
                 .set_roots(&rooted_slots)
                 .expect("Ledger set roots failed");
+            if let Some(slot_status_notifier) = slot_status_notifier {
+                let old_root = bank_forks.read().unwrap().root();
+                let slot_status_notifier = slot_status_notifier.read().unwrap();
+                let mut newly_rooted_slots: Vec<_> = rooted_banks
+                    .iter()
+                    .filter(|bank| bank.slot() > old_root)
+                    .map(|bank| (bank.slot(), bank.parent_slot()))
+                    .collect();
+                newly_rooted_slots.sort_unstable();
+                for (slot, parent) in newly_rooted_slots {
+                    slot_status_notifier.notify_slot_rooted(slot, Some(parent));
+                }
+            }
             let highest_confirmed_root = Some(
                 block_commitment_cache
                     .read()
@@ -1676,6 +1695,7 @@ impl ReplayStage {
         heaviest_subtree_fork_choice: &mut HeaviestSubtreeForkChoice,
         replay_vote_sender: &ReplayVoteSender,
         bank_notification_sender: &Option<BankNotificationSender>,
+        slot_status_notifier: &Option<SlotStatusNotifier>,
         rewards_recorder_sender: &Option<RewardsRecorderSender>,
         subscriptions: &Arc<RpcSubscriptions>,
         evm_block_recorder_sender: &Option<EvmRecorderSender>,
@@ -1801,6 +1821,12 @@ impl ReplayStage {
                         .send(BankNotification::Frozen(bank.clone()))
                         .unwrap_or_else(|err| warn!("bank_notification_sender failed: {:?}", err));
                 }
+                if let Some(slot_status_notifier) = slot_status_notifier {
+                    slot_status_notifier
+                        .read()
+                        .unwrap()
+                        .notify_slot_processed(bank.slot(), Some(bank.parent_slot()));
+                }
                 blockstore_processor::cache_block_meta(&bank, cache_block_meta_sender);
 
                 let bank_hash = bank.hash();
@@ -3431,6 +3457,8 @@ pub(crate) mod tests {
         let transaction_status_service = TransactionStatusService::new(
             transaction_status_receiver,
             max_complete_transaction_status_slot,
+            true,
+            None,
             blockstore,
             &Arc::new(AtomicBool::new(false)),
         );
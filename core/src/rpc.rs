@@ -7,7 +7,7 @@ use crate::{
     non_circulating_supply::calculate_non_circulating_supply,
     optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
     rpc_health::*,
-    send_transaction_service::{SendTransactionService, TransactionInfo},
+    send_transaction_service::{self, SendTransactionService, TransactionInfo, TransactionQueue},
     validator::ValidatorExit,
 };
 use bincode::{config::Options, serialize};
@@ -30,15 +30,18 @@ use solana_client::{
     rpc_request::{
         TokenAccountsFilter, DELINQUENT_VALIDATOR_SLOT_DISTANCE, MAX_GET_CONFIRMED_BLOCKS_RANGE,
         MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
-        MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS_SLOT_RANGE, MAX_GET_PROGRAM_ACCOUNT_FILTERS,
+        MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS_SLOT_RANGE,
+        MAX_GET_PRIORITIZATION_FEES_QUERY_ITEMS, MAX_GET_PROGRAM_ACCOUNT_FILTERS,
         MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS, MAX_GET_SLOT_LEADERS, MAX_MULTIPLE_ACCOUNTS,
         NUM_LARGEST_ACCOUNTS,
     },
     rpc_response::{Response as RpcResponse, *},
 };
-use solana_faucet::faucet::request_airdrop_transaction;
+use solana_faucet::faucet::{request_airdrop_transaction, request_evm_airdrop_transaction};
 use solana_ledger::{
-    blockstore::Blockstore, blockstore_db::BlockstoreError, get_tmp_ledger_path,
+    blockstore::Blockstore,
+    blockstore_db::{BlockstoreError, EvmLogLocation},
+    get_tmp_ledger_path,
     leader_schedule_cache::LeaderScheduleCache,
 };
 use solana_metrics::inc_new_counter_info;
@@ -46,9 +49,10 @@ use solana_perf::packet::PACKET_DATA_SIZE;
 use solana_runtime::{
     accounts::AccountAddressFilter,
     accounts_index::{AccountIndex, AccountSecondaryIndexes, IndexKey},
-    bank::Bank,
+    bank::{Bank, TransactionSimulationResult},
     bank_forks::{BankForks, SnapshotConfig},
     commitment::{BlockCommitmentArray, BlockCommitmentCache, CommitmentSlots},
+    hashed_transaction::HashedTransaction,
     inline_spl_token_v2_0::{SPL_TOKEN_ACCOUNT_MINT_OFFSET, SPL_TOKEN_ACCOUNT_OWNER_OFFSET},
     snapshot_utils::get_highest_snapshot_archive_path,
 };
@@ -66,12 +70,15 @@ use solana_sdk::{
     stake_history::StakeHistory,
     system_instruction,
     sysvar::stake_history,
-    transaction::{self, Transaction},
+    transaction::{self, Transaction, VersionedTransaction},
 };
 use solana_stake_program::stake_state::StakeState;
 use solana_transaction_status::{
-    EncodedConfirmedTransaction, Reward, RewardType, TransactionConfirmationStatus,
-    TransactionStatus, UiConfirmedBlock, UiTransactionEncoding,
+    parse_evm_token::{EvmTokenBalance, EvmTokenTransfer},
+    token_balances::collect_simulation_token_balances,
+    EncodedConfirmedTransaction, InnerInstructions, Reward, RewardType,
+    TransactionConfirmationStatus, TransactionStatus, UiConfirmedBlock, UiInnerInstructions,
+    UiTransactionEncoding,
 };
 use solana_vote_program::vote_state::{VoteState, MAX_LOCKOUT_HISTORY};
 use spl_token_v2_0::{
@@ -99,6 +106,7 @@ use sophon_relying_party_program::RelyingPartyData;
 
 pub const MAX_REQUEST_PAYLOAD_SIZE: usize = 200 * (1 << 10); // 200kB perviously: 50 * (1 << 10); // 50kB
 pub const PERFORMANCE_SAMPLES_LIMIT: usize = 720;
+pub const MAX_EVM_LOGS_BLOCK_RANGE: u64 = 2000;
 
 // Limit the length of the `epoch_credits` array for each validator in a `get_vote_accounts`
 // response
@@ -142,8 +150,20 @@ pub struct JsonRpcConfig {
     pub account_indexes: AccountSecondaryIndexes,
     pub rpc_threads: usize,
     pub rpc_bigtable_timeout: Option<Duration>,
+    /// Backend used for `enable_bigtable_ledger_storage` and `enable_bigtable_ledger_upload`.
+    pub ledger_storage_config: solana_storage_bigtable::LedgerStorageConfig,
     pub minimal_api: bool,
     pub rpc_scan_and_fix_roots: bool,
+    /// Maximum number of blocks in a single `eth_getLogs` request,
+    /// `MAX_EVM_LOGS_BLOCK_RANGE` if not set.
+    pub max_evm_logs_block_range: Option<u64>,
+    /// Use address and topic indexes of the blockstore to select blocks for `eth_getLogs`.
+    /// Logs of blocks written before the index was introduced are not found,
+    /// unless the index was rebuilt with `ledger-tool evm_blockstore reindex-logs`.
+    pub enable_evm_log_index: bool,
+    /// Index ERC-20/ERC-721 transfers of confirmed EVM blocks,
+    /// and serve `evm_getTokenTransfers` and `evm_getTokenBalances`.
+    pub enable_evm_token_index: bool,
 }
 
 #[derive(Clone)]
@@ -158,6 +178,7 @@ pub struct JsonRpcRequestProcessor {
     cluster_info: Arc<ClusterInfo>,
     genesis_hash: Hash,
     transaction_sender: Arc<Mutex<Sender<TransactionInfo>>>,
+    transaction_queue: TransactionQueue,
     bigtable_ledger_storage: Option<solana_storage_bigtable::LedgerStorage>,
     optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
     largest_accounts_cache: Arc<RwLock<LargestAccountsCache>>,
@@ -267,6 +288,7 @@ impl JsonRpcRequestProcessor {
                 cluster_info,
                 genesis_hash,
                 transaction_sender: Arc::new(Mutex::new(sender)),
+                transaction_queue: TransactionQueue::default(),
                 bigtable_ledger_storage,
                 optimistically_confirmed_bank,
                 largest_accounts_cache,
@@ -291,7 +313,19 @@ impl JsonRpcRequestProcessor {
         let cluster_info = Arc::new(ClusterInfo::default());
         let tpu_address = cluster_info.my_contact_info().tpu;
         let (sender, receiver) = channel();
-        SendTransactionService::new(tpu_address, &bank_forks, None, receiver, 1000, 1);
+        let transaction_queue = TransactionQueue::default();
+        SendTransactionService::new_with_config(
+            tpu_address,
+            &bank_forks,
+            None,
+            receiver,
+            transaction_queue.clone(),
+            send_transaction_service::Config {
+                retry_rate_ms: 1000,
+                leader_forward_count: 1,
+                ..send_transaction_service::Config::default()
+            },
+        );
 
         Self {
             config: JsonRpcConfig::default(),
@@ -308,6 +342,7 @@ impl JsonRpcRequestProcessor {
             cluster_info,
             genesis_hash,
             transaction_sender: Arc::new(Mutex::new(sender)),
+            transaction_queue,
             bigtable_ledger_storage: None,
             optimistically_confirmed_bank: Arc::new(RwLock::new(OptimisticallyConfirmedBank {
                 bank: bank.clone(),
@@ -320,6 +355,11 @@ impl JsonRpcRequestProcessor {
         }
     }
 
+    /// Transactions queued for retries by the send transaction service of this node
+    pub fn transaction_queue(&self) -> TransactionQueue {
+        self.transaction_queue.clone()
+    }
+
     pub fn get_health(&self) -> RpcHealthStatus {
         self.health.check()
     }
@@ -541,6 +581,35 @@ impl JsonRpcRequestProcessor {
         }
     }
 
+    pub fn get_recent_prioritization_fees(
+        &self,
+        pubkeys: Vec<Pubkey>,
+    ) -> Vec<RpcPrioritizationFee> {
+        // The prioritization fee cache is shared by all banks, so any commitment level works
+        self.bank(None)
+            .get_recent_prioritization_fees(&pubkeys)
+            .into_iter()
+            .map(|(slot, prioritization_fee)| RpcPrioritizationFee {
+                slot,
+                prioritization_fee,
+            })
+            .collect()
+    }
+
+    pub fn get_send_transaction_status(
+        &self,
+        signature: &Signature,
+    ) -> Option<RpcSendTransactionStatus> {
+        self.transaction_queue
+            .read()
+            .unwrap()
+            .get(signature)
+            .map(|transaction_info| RpcSendTransactionStatus {
+                send_count: transaction_info.retries + 1,
+                last_valid_slot: transaction_info.last_valid_slot,
+            })
+    }
+
     pub fn get_epoch_schedule(&self) -> EpochSchedule {
         // Since epoch schedule data comes from the genesis config, any commitment level should be
         // fine
@@ -1778,6 +1847,15 @@ impl JsonRpcRequestProcessor {
                 RpcFilterType::Memcmp(compare) => compare.bytes_match(account.data()),
             })
         };
+        if let Some(index_key) =
+            get_custom_index_filter(&self.config.account_indexes, program_id, &filters)
+        {
+            // Like the program-id index, a custom index may still reference accounts which
+            // were wiped or reassigned, so the filters are checked again.
+            return Ok(bank.get_filtered_indexed_accounts(&index_key, |account| {
+                account.owner == *program_id && filter_closure(account)
+            }));
+        }
         if self
             .config
             .account_indexes
@@ -2025,11 +2103,15 @@ impl JsonRpcRequestProcessor {
 
         filter_request_time += filter_request.elapsed();
 
+        let blocks = if self.config.enable_evm_log_index {
+            self.get_evm_log_candidate_blocks(&filter).await?
+        } else {
+            self.get_evm_blocks_by_ids(filter.from_block, filter.to_block)
+                .await?
+        };
+
         let mut logs = Vec::new();
-        for block in self
-            .get_evm_blocks_by_ids(filter.from_block, filter.to_block)
-            .await?
-        {
+        for block in blocks {
             let filter_request = Instant::now();
             logs.extend(Blockstore::filter_block_logs(&block, &masks, &filter)?);
             filter_request_time += filter_request.elapsed();
@@ -2039,6 +2121,101 @@ impl JsonRpcRequestProcessor {
         Ok(logs)
     }
 
+    ///
+    /// Get blocks that can contain logs matching `filter`, using log indexes of blockstore.
+    /// Blocks that are missing in blockstore are requested from bigtable.
+    ///
+
+    #[instrument(skip(self))]
+    async fn get_evm_log_candidate_blocks(
+        &self,
+        filter: &evm_state::LogFilter,
+    ) -> solana_ledger::blockstore_db::Result<Vec<evm_state::Block>> {
+        if filter.to_block < filter.from_block {
+            return Err(BlockstoreError::InvalidBlocksRange {
+                starting_block: filter.from_block,
+                ending_block: filter.to_block,
+            });
+        }
+        let first_local_block = self.blockstore.get_first_available_evm_block()?;
+        let mut blocks = Vec::new();
+        // Older blocks are not indexed locally, scan them as usual.
+        if filter.from_block < first_local_block {
+            blocks.extend(
+                self.get_evm_blocks_by_ids(
+                    filter.from_block,
+                    filter.to_block.min(first_local_block - 1),
+                )
+                .await?,
+            );
+        }
+        if filter.to_block < first_local_block {
+            return Ok(blocks);
+        }
+
+        let local_filter = evm_state::LogFilter {
+            from_block: filter.from_block.max(first_local_block),
+            ..filter.clone()
+        };
+        let candidates = match self.blockstore.find_evm_log_blocks(&local_filter)? {
+            Some(candidates) => candidates,
+            // Filter has no indexed restrictions, check every block.
+            None => {
+                blocks.extend(
+                    self.get_evm_blocks_by_ids(local_filter.from_block, local_filter.to_block)
+                        .await?,
+                );
+                return Ok(blocks);
+            }
+        };
+        trace!(target: "evm", "Log index candidate blocks = {:?}", candidates);
+        for block_num in candidates {
+            match self.blockstore.get_evm_block(block_num) {
+                Ok((block, _confirmed)) => blocks.push(block),
+                Err(_) => blocks.extend(self.get_evm_blocks_by_ids(block_num, block_num).await?),
+            }
+        }
+        Ok(blocks)
+    }
+
+    pub fn max_evm_logs_block_range(&self) -> u64 {
+        self.config
+            .max_evm_logs_block_range
+            .unwrap_or(MAX_EVM_LOGS_BLOCK_RANGE)
+    }
+
+    pub fn evm_token_index_enabled(&self) -> bool {
+        self.config.enable_evm_token_index
+    }
+
+    /// Returns up to `limit` token transfers of `holder` before the `before` location,
+    /// newest first.
+    pub fn get_evm_token_transfers(
+        &self,
+        holder: evm_state::H160,
+        token: Option<evm_state::H160>,
+        before: Option<EvmLogLocation>,
+        limit: usize,
+    ) -> std::result::Result<Vec<(EvmLogLocation, EvmTokenTransfer)>, BlockstoreError> {
+        self.blockstore
+            .find_evm_token_transfers(holder, token, before, limit)
+    }
+
+    pub fn get_evm_token_balances(
+        &self,
+        holder: evm_state::H160,
+    ) -> std::result::Result<Vec<EvmTokenBalance>, BlockstoreError> {
+        self.blockstore.read_evm_token_balances(holder)
+    }
+
+    /// Returns the first and the last evm blocks covered by token index.
+    pub fn get_evm_token_index_range(
+        &self,
+    ) -> std::result::Result<Option<(evm_state::BlockNum, evm_state::BlockNum)>, BlockstoreError>
+    {
+        self.blockstore.read_evm_token_index_range()
+    }
+
     #[instrument(skip(self))]
     pub async fn get_first_available_evm_block(&self) -> u64 {
         let block = self
@@ -2258,16 +2435,133 @@ impl JsonRpcRequestProcessor {
     }
 }
 
-fn verify_transaction(transaction: &Transaction) -> Result<()> {
-    if transaction.verify().is_err() {
-        return Err(RpcCustomError::TransactionSignatureVerificationFailure.into());
-    }
+/// Sanitize a transaction without verifying its signatures, and expand the accounts it loads
+/// from lookup tables
+fn sanitize_transaction(
+    transaction: VersionedTransaction,
+    bank: &Bank,
+) -> Result<HashedTransaction<'static>> {
+    let message_hash = transaction.message.hash();
+    bank.expand_versioned_transaction(transaction, message_hash)
+        .map_err(|err| Error::invalid_params(format!("invalid transaction: {}", err)))
+}
 
-    if let Err(e) = transaction.verify_precompiles() {
+/// Verify the signatures and precompiles of a transaction, and expand the accounts it loads
+/// from lookup tables
+fn verify_transaction(
+    transaction: VersionedTransaction,
+    bank: &Bank,
+) -> Result<HashedTransaction<'static>> {
+    let message_hash = match transaction.verify_and_hash_message() {
+        Ok(message_hash) => message_hash,
+        Err(_) => return Err(RpcCustomError::TransactionSignatureVerificationFailure.into()),
+    };
+    let transaction = bank
+        .expand_versioned_transaction(transaction, message_hash)
+        .map_err(|err| Error::invalid_params(format!("invalid transaction: {}", err)))?;
+
+    if let Err(e) = transaction
+        .transaction()
+        .verify_precompiles(&bank.feature_set)
+    {
         return Err(RpcCustomError::TransactionPrecompileVerificationFailure(e).into());
     }
 
-    Ok(())
+    Ok(transaction)
+}
+
+/// Describe the effects of a simulated transaction, except for the post-simulation
+/// accounts which are selected by the request
+fn new_rpc_simulate_transaction_result(
+    bank: &Bank,
+    transaction: &Transaction,
+    simulation: &TransactionSimulationResult,
+) -> Result<RpcSimulateTransactionResult> {
+    let inner_instructions = simulation
+        .inner_instructions
+        .as_ref()
+        .map(|inner_instructions| {
+            inner_instructions
+                .iter()
+                .enumerate()
+                .map(|(index, instructions)| InnerInstructions {
+                    index: index as u8,
+                    instructions: instructions.clone(),
+                })
+                .filter(|i| !i.instructions.is_empty())
+                .map(UiInnerInstructions::from)
+                .collect()
+        });
+
+    let (pre_token_balances, post_token_balances) = if simulation.result.is_ok() {
+        let (pre_token_balances, post_token_balances) = collect_simulation_token_balances(
+            bank,
+            transaction,
+            &simulation.post_simulation_accounts,
+        );
+        (
+            Some(pre_token_balances.into_iter().map(Into::into).collect()),
+            Some(post_token_balances.into_iter().map(Into::into).collect()),
+        )
+    } else {
+        (None, None)
+    };
+
+    let (evm_receipts, evm_state_changes) = if transaction.message.is_modify_evm_state() {
+        let evm_receipts = simulation
+            .evm_receipts
+            .iter()
+            .map(|(hash, receipt)| {
+                // Simulated transactions are not part of any block
+                evm_rpc::RPCReceipt::new_from_receipt(
+                    receipt.clone(),
+                    *hash,
+                    evm_state::H256::zero(),
+                    None,
+                )
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let mut evm_state_changes: Vec<_> = simulation
+            .evm_state_changes
+            .iter()
+            .map(|(address, (account, storage))| {
+                let account: Option<evm_state::AccountState> = account.clone().into();
+                RpcEvmStateChange {
+                    address: evm_rpc::Hex(*address),
+                    nonce: account.as_ref().map(|account| evm_rpc::Hex(account.nonce)),
+                    balance: account
+                        .as_ref()
+                        .map(|account| evm_rpc::Hex(account.balance)),
+                    code_hash: account.map(|account| evm_rpc::Hex(account.code.hash())),
+                    storage: storage
+                        .iter()
+                        .map(|(index, value)| (evm_rpc::Hex(*index), evm_rpc::Hex(*value)))
+                        .collect(),
+                }
+            })
+            .collect();
+        evm_state_changes.sort_by_key(|change| change.address);
+        (Some(evm_receipts), Some(evm_state_changes))
+    } else {
+        (None, None)
+    };
+
+    Ok(RpcSimulateTransactionResult {
+        err: simulation.result.clone().err(),
+        logs: Some(simulation.logs.clone()),
+        accounts: None,
+        return_data: simulation
+            .return_data
+            .clone()
+            .map(|return_data| return_data.into()),
+        units_consumed: Some(simulation.units_consumed.iter().sum()),
+        instruction_units_consumed: Some(simulation.units_consumed.clone()),
+        inner_instructions,
+        pre_token_balances,
+        post_token_balances,
+        evm_receipts,
+        evm_state_changes,
+    })
 }
 
 fn verify_filter(input: &RpcFilterType) -> Result<()> {
@@ -2282,6 +2576,22 @@ fn verify_pubkey(input: &str) -> Result<Pubkey> {
         .map_err(|e| Error::invalid_params(format!("Invalid param: {:?}", e)))
 }
 
+enum AirdropRecipient {
+    Native(Pubkey),
+    Evm(evm_state::Address),
+}
+
+/// Airdrops accept either a base58 native pubkey or a `0x` prefixed EVM address
+fn verify_airdrop_recipient(input: &str) -> Result<AirdropRecipient> {
+    if input.starts_with("0x") {
+        evm_rpc::Hex::<evm_state::Address>::from_hex(input)
+            .map(|address| AirdropRecipient::Evm(address.0))
+            .map_err(|e| Error::invalid_params(format!("Invalid param: {:?}", e)))
+    } else {
+        verify_pubkey(input).map(AirdropRecipient::Native)
+    }
+}
+
 fn verify_hash(input: &str) -> Result<Hash> {
     input
         .parse()
@@ -2397,6 +2707,35 @@ fn get_spl_token_owner_filter(program_id: &Pubkey, filters: &[RpcFilterType]) ->
     }
 }
 
+/// Find a custom account index of `program_id` able to answer one of the memcmp `filters`
+fn get_custom_index_filter(
+    account_indexes: &AccountSecondaryIndexes,
+    program_id: &Pubkey,
+    filters: &[RpcFilterType],
+) -> Option<IndexKey> {
+    filters.iter().find_map(|filter| match filter {
+        RpcFilterType::Memcmp(Memcmp {
+            offset,
+            bytes: MemcmpEncodedBytes::Binary(bytes),
+            ..
+        }) => {
+            let bytes = bs58::decode(bytes).into_vec().ok()?;
+            account_indexes.custom_indexes().find_map(|custom_index| {
+                if custom_index.program_id != *program_id || custom_index.offset != *offset {
+                    return None;
+                }
+                let key = custom_index.index_key(&bytes)?;
+                // Excluded keys are missing from the index, the accounts are scanned instead
+                if !account_indexes.include_key(&key) {
+                    return None;
+                }
+                Some(IndexKey::Custom(*custom_index, key))
+            })
+        }
+        _ => None,
+    })
+}
+
 fn get_spl_token_mint_filter(program_id: &Pubkey, filters: &[RpcFilterType]) -> Option<Pubkey> {
     if program_id != &spl_token_id_v2_0() {
         return None;
@@ -2533,10 +2872,11 @@ fn get_mint_decimals(data: &[u8]) -> Result<u8> {
 
 fn _send_transaction(
     meta: JsonRpcRequestProcessor,
-    transaction: Transaction,
+    transaction: &Transaction,
     wire_transaction: Vec<u8>,
     last_valid_slot: Slot,
     durable_nonce_info: Option<(Pubkey, Hash)>,
+    max_retries: Option<usize>,
 ) -> Result<String> {
     if transaction.signatures.is_empty() {
         return Err(RpcCustomError::TransactionSignatureVerificationFailure.into());
@@ -2547,6 +2887,7 @@ fn _send_transaction(
         wire_transaction,
         last_valid_slot,
         durable_nonce_info,
+        max_retries,
     );
     meta.transaction_sender
         .lock()
@@ -2861,6 +3202,13 @@ pub mod rpc_full {
             limit: Option<usize>,
         ) -> Result<Vec<RpcPerfSample>>;
 
+        #[rpc(meta, name = "getRecentPrioritizationFees")]
+        fn get_recent_prioritization_fees(
+            &self,
+            meta: Self::Metadata,
+            pubkey_strs: Option<Vec<String>>,
+        ) -> Result<Vec<RpcPrioritizationFee>>;
+
         #[rpc(meta, name = "getBlockCommitment")]
         fn get_block_commitment(
             &self,
@@ -2907,6 +3255,13 @@ pub mod rpc_full {
             config: Option<RpcSignatureStatusConfig>,
         ) -> BoxFuture<Result<RpcResponse<Vec<Option<TransactionStatus>>>>>;
 
+        #[rpc(meta, name = "getSendTransactionStatus")]
+        fn get_send_transaction_status(
+            &self,
+            meta: Self::Metadata,
+            signature_str: String,
+        ) -> Result<Option<RpcSendTransactionStatus>>;
+
         #[rpc(meta, name = "getMaxRetransmitSlot")]
         fn get_max_retransmit_slot(&self, meta: Self::Metadata) -> Result<Slot>;
 
@@ -3276,6 +3631,29 @@ pub mod rpc_full {
                 .collect())
         }
 
+        fn get_recent_prioritization_fees(
+            &self,
+            meta: Self::Metadata,
+            pubkey_strs: Option<Vec<String>>,
+        ) -> Result<Vec<RpcPrioritizationFee>> {
+            let pubkey_strs = pubkey_strs.unwrap_or_default();
+            debug!(
+                "get_recent_prioritization_fees rpc request received: {:?} pubkeys",
+                pubkey_strs.len()
+            );
+            if pubkey_strs.len() > MAX_GET_PRIORITIZATION_FEES_QUERY_ITEMS {
+                return Err(Error::invalid_params(format!(
+                    "Too many inputs provided; max {}",
+                    MAX_GET_PRIORITIZATION_FEES_QUERY_ITEMS
+                )));
+            }
+            let pubkeys = pubkey_strs
+                .into_iter()
+                .map(|pubkey_str| verify_pubkey(&pubkey_str))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(meta.get_recent_prioritization_fees(pubkeys))
+        }
+
         fn get_cluster_nodes(&self, meta: Self::Metadata) -> Result<Vec<RpcContactInfo>> {
             debug!("get_cluster_nodes rpc request received");
             let cluster_info = &meta.cluster_info;
@@ -3395,6 +3773,19 @@ pub mod rpc_full {
             Box::pin(async move { meta.get_signature_statuses(signatures, config).await })
         }
 
+        fn get_send_transaction_status(
+            &self,
+            meta: Self::Metadata,
+            signature_str: String,
+        ) -> Result<Option<RpcSendTransactionStatus>> {
+            debug!(
+                "get_send_transaction_status rpc request received: {:?}",
+                signature_str
+            );
+            let signature = verify_signature(&signature_str)?;
+            Ok(meta.get_send_transaction_status(&signature))
+        }
+
         fn get_max_retransmit_slot(&self, meta: Self::Metadata) -> Result<Slot> {
             debug!("get_max_retransmit_slot rpc request received");
             Ok(meta.get_max_retransmit_slot())
@@ -3448,7 +3839,7 @@ pub mod rpc_full {
             );
 
             let faucet_addr = meta.config.faucet_addr.ok_or_else(Error::invalid_request)?;
-            let pubkey = verify_pubkey(&pubkey_str)?;
+            let recipient = verify_airdrop_recipient(&pubkey_str)?;
 
             let config = config.unwrap_or_default();
             let bank = meta.bank(config.commitment);
@@ -3460,20 +3851,32 @@ pub mod rpc_full {
             };
             let last_valid_slot = bank.get_blockhash_last_valid_slot(&blockhash).unwrap_or(0);
 
-            let transaction =
-                request_airdrop_transaction(&faucet_addr, &pubkey, lamports, blockhash).map_err(
-                    |err| {
-                        info!("request_airdrop_transaction failed: {:?}", err);
-                        Error::internal_error()
-                    },
-                )?;
+            let transaction = match recipient {
+                AirdropRecipient::Native(pubkey) => {
+                    request_airdrop_transaction(&faucet_addr, &pubkey, lamports, blockhash)
+                }
+                AirdropRecipient::Evm(address) => {
+                    request_evm_airdrop_transaction(&faucet_addr, &address, lamports, blockhash)
+                }
+            }
+            .map_err(|err| {
+                info!("request_airdrop_transaction failed: {:?}", err);
+                Error::internal_error()
+            })?;
 
             let wire_transaction = serialize(&transaction).map_err(|err| {
                 info!("request_airdrop: serialize error: {:?}", err);
                 Error::internal_error()
             })?;
 
-            _send_transaction(meta, transaction, wire_transaction, last_valid_slot, None)
+            _send_transaction(
+                meta,
+                &transaction,
+                wire_transaction,
+                last_valid_slot,
+                None,
+                None,
+            )
         }
 
         fn send_transaction(
@@ -3485,22 +3888,39 @@ pub mod rpc_full {
             debug!("send_transaction rpc request received");
             let config = config.unwrap_or_default();
             let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Base58);
-            let (wire_transaction, transaction) = deserialize_transaction(data, encoding)?;
+            let (wire_transaction, unsanitized_tx) = deserialize_transaction(data, encoding)?;
 
             let preflight_commitment = config
                 .preflight_commitment
                 .map(|commitment| CommitmentConfig { commitment });
             let preflight_bank = &*meta.bank(preflight_commitment);
+            let hashed_transaction = if config.skip_preflight {
+                sanitize_transaction(unsanitized_tx, preflight_bank)?
+            } else {
+                verify_transaction(unsanitized_tx, preflight_bank)?
+            };
+            let transaction = hashed_transaction.transaction();
 
             let mut last_valid_slot = preflight_bank
                 .get_blockhash_last_valid_slot(&transaction.message.recent_blockhash)
+                .or_else(|| {
+                    // Skipping the preflight, blockhashes too recent for the preflight bank are
+                    // accepted, so the transaction is retried for as long as the processed bank
+                    // knows its blockhash rather than being dropped at the first retry
+                    if config.skip_preflight {
+                        meta.bank(Some(CommitmentConfig::processed()))
+                            .get_blockhash_last_valid_slot(&transaction.message.recent_blockhash)
+                    } else {
+                        None
+                    }
+                })
                 .unwrap_or(0);
 
-            let durable_nonce_info = solana_sdk::transaction::uses_durable_nonce(&transaction)
+            let durable_nonce_info = solana_sdk::transaction::uses_durable_nonce(transaction)
                 .and_then(|nonce_ix| {
                     solana_sdk::transaction::get_nonce_pubkey_from_instruction(
                         nonce_ix,
-                        &transaction,
+                        transaction,
                     )
                 })
                 .map(|&pubkey| (pubkey, transaction.message.recent_blockhash));
@@ -3513,10 +3933,6 @@ pub mod rpc_full {
             }
 
             if !config.skip_preflight {
-                if let Err(e) = verify_transaction(&transaction) {
-                    return Err(e);
-                }
-
                 match meta.health.check() {
                     RpcHealthStatus::Ok => (),
                     RpcHealthStatus::Unknown => {
@@ -3533,14 +3949,15 @@ pub mod rpc_full {
                     }
                 }
 
-                if let (Err(err), logs, _) = preflight_bank.simulate_transaction(&transaction) {
+                let simulation = preflight_bank.simulate_transaction(transaction);
+                if let Err(err) = &simulation.result {
                     return Err(RpcCustomError::SendTransactionPreflightFailure {
                         message: format!("Transaction simulation failed: {}", err),
-                        result: RpcSimulateTransactionResult {
-                            err: Some(err),
-                            logs: Some(logs),
-                            accounts: None,
-                        },
+                        result: new_rpc_simulate_transaction_result(
+                            preflight_bank,
+                            transaction,
+                            &simulation,
+                        )?,
                     }
                     .into());
                 }
@@ -3552,6 +3969,7 @@ pub mod rpc_full {
                 wire_transaction,
                 last_valid_slot,
                 durable_nonce_info,
+                config.max_retries,
             )
         }
 
@@ -3564,24 +3982,28 @@ pub mod rpc_full {
             debug!("simulate_transaction rpc request received");
             let config = config.unwrap_or_default();
             let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Base58);
-            let (_, mut transaction) = deserialize_transaction(data, encoding)?;
+            let (_, mut unsanitized_tx) = deserialize_transaction(data, encoding)?;
 
-            if config.sig_verify {
+            let bank = &*meta.bank(config.commitment);
+            let hashed_transaction = if config.sig_verify {
                 if config.replace_recent_blockhash {
                     return Err(Error::invalid_params(
                         "sigVerify may not be used with replaceRecentBlockhash",
                     ));
                 }
 
-                if let Err(e) = verify_transaction(&transaction) {
-                    return Err(e);
+                verify_transaction(unsanitized_tx, bank)?
+            } else {
+                if config.replace_recent_blockhash {
+                    unsanitized_tx
+                        .message
+                        .set_recent_blockhash(bank.last_blockhash());
                 }
-            }
-            let bank = &*meta.bank(config.commitment);
-            if config.replace_recent_blockhash {
-                transaction.message.recent_blockhash = bank.last_blockhash();
-            }
-            let (result, logs, post_simulation_accounts) = bank.simulate_transaction(&transaction);
+                sanitize_transaction(unsanitized_tx, bank)?
+            };
+            let transaction = hashed_transaction.transaction();
+            let simulation = bank.simulate_transaction(transaction);
+            let post_simulation_accounts = &simulation.post_simulation_accounts;
 
             let accounts = if let Some(config_accounts) = config.accounts {
                 let accounts_encoding = config_accounts
@@ -3604,7 +4026,7 @@ pub mod rpc_full {
                 let mut accounts = vec![];
                 for address_str in config_accounts.addresses {
                     let address = verify_pubkey(&address_str)?;
-                    accounts.push(if result.is_err() {
+                    accounts.push(if simulation.result.is_err() {
                         None
                     } else {
                         transaction
@@ -3627,9 +4049,8 @@ pub mod rpc_full {
             Ok(new_response(
                 bank,
                 RpcSimulateTransactionResult {
-                    err: result.err(),
-                    logs: Some(logs),
                     accounts,
+                    ..new_rpc_simulate_transaction_result(bank, transaction, &simulation)?
                 },
             ))
         }
@@ -4146,7 +4567,7 @@ const WORST_CASE_BASE64_TX: usize = 1644; // Golden, bump if PACKET_DATA_SIZE ch
 fn deserialize_transaction(
     encoded_transaction: String,
     encoding: UiTransactionEncoding,
-) -> Result<(Vec<u8>, Transaction)> {
+) -> Result<(Vec<u8>, VersionedTransaction)> {
     let wire_transaction = match encoding {
         UiTransactionEncoding::Base58 => {
             inc_new_counter_info!("rpc-base58_encoded_tx", 1);
@@ -4200,7 +4621,7 @@ fn deserialize_transaction(
             info!("transaction deserialize error: {:?}", err);
             Error::invalid_params(&err.to_string())
         })
-        .and_then(|transaction: Transaction| {
+        .and_then(|transaction: VersionedTransaction| {
             if let Err(err) = transaction.sanitize() {
                 Err(Error::invalid_params(format!(
                     "invalid transaction: {}",
@@ -4242,15 +4663,19 @@ pub mod tests {
         genesis_utils::{create_genesis_config, GenesisConfigInfo},
     };
     use solana_runtime::{
-        accounts_background_service::AbsRequestSender, commitment::BlockCommitment,
+        accounts_background_service::AbsRequestSender,
+        accounts_index::{AccountSecondaryIndexesIncludeExclude, CustomAccountIndex},
+        commitment::BlockCommitment,
+        genesis_utils::activate_all_features,
+        prioritization_fee::PrioritizationFee,
     };
     use solana_sdk::{
         account::Account,
         clock::MAX_RECENT_BLOCKHASHES,
         fee_calculator::DEFAULT_BURN_PERCENT,
         hash::{hash, Hash},
-        instruction::InstructionError,
-        message::Message,
+        instruction::{CompiledInstruction, InstructionError},
+        message::{v0, Message, MessageHeader, VersionedMessage},
         nonce, rpc_port,
         signature::{Keypair, Signer},
         system_program, system_transaction,
@@ -4591,6 +5016,53 @@ pub mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_rpc_get_recent_prioritization_fees() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler { io, meta, bank, .. } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let mut prioritization_fee = PrioritizationFee::default();
+        prioritization_fee.update(5, [bob_pubkey].iter());
+        prioritization_fee.update(2, [solana_sdk::pubkey::new_rand()].iter());
+        bank.prioritization_fee_cache
+            .write()
+            .unwrap()
+            .insert(42, prioritization_fee);
+        bank.prioritization_fee_cache
+            .write()
+            .unwrap()
+            .finalize(&[42]);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getRecentPrioritizationFees"}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let expected = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": [{ "slot": 42, "prioritizationFee": 2 }],
+        });
+        let expected: Response =
+            serde_json::from_value(expected).expect("expected response deserialization");
+        assert_eq!(expected, result);
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getRecentPrioritizationFees","params":[["{}"]]}}"#,
+            bob_pubkey
+        );
+        let res = io.handle_request_sync(&req, meta);
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let expected = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": [{ "slot": 42, "prioritizationFee": 5 }],
+        });
+        let expected: Response =
+            serde_json::from_value(expected).expect("expected response deserialization");
+        assert_eq!(expected, result);
+    }
+
     #[test]
     fn test_rpc_get_recent_performance_samples_invalid_limit() {
         let bob_pubkey = solana_sdk::pubkey::new_rand();
@@ -5523,7 +5995,12 @@ pub mod tests {
                     "logs":[
                         "Program 11111111111111111111111111111111 invoke [1]",
                         "Program 11111111111111111111111111111111 success"
-                    ]
+                    ],
+                    "unitsConsumed": 0,
+                    "instructionUnitsConsumed": [0],
+                    "innerInstructions": [],
+                    "preTokenBalances": [],
+                    "postTokenBalances": []
                 }
             },
             "id": 1,
@@ -5602,10 +6079,19 @@ pub mod tests {
             "jsonrpc": "2.0",
             "result": {
                 "context":{"slot":0},
-                "value":{"accounts": null, "err":null, "logs":[
-                    "Program 11111111111111111111111111111111 invoke [1]",
-                    "Program 11111111111111111111111111111111 success"
-                ]}
+                "value":{
+                    "accounts": null,
+                    "err":null,
+                    "logs":[
+                        "Program 11111111111111111111111111111111 invoke [1]",
+                        "Program 11111111111111111111111111111111 success"
+                    ],
+                    "unitsConsumed": 0,
+                    "instructionUnitsConsumed": [0],
+                    "innerInstructions": [],
+                    "preTokenBalances": [],
+                    "postTokenBalances": []
+                }
             },
             "id": 1,
         });
@@ -5625,10 +6111,19 @@ pub mod tests {
             "jsonrpc": "2.0",
             "result": {
                 "context":{"slot":0},
-                "value":{"accounts": null, "err":null, "logs":[
-                    "Program 11111111111111111111111111111111 invoke [1]",
-                    "Program 11111111111111111111111111111111 success"
-                ]}
+                "value":{
+                    "accounts": null,
+                    "err":null,
+                    "logs":[
+                        "Program 11111111111111111111111111111111 invoke [1]",
+                        "Program 11111111111111111111111111111111 success"
+                    ],
+                    "unitsConsumed": 0,
+                    "instructionUnitsConsumed": [0],
+                    "innerInstructions": [],
+                    "preTokenBalances": [],
+                    "postTokenBalances": []
+                }
             },
             "id": 1,
         });
@@ -5673,7 +6168,13 @@ pub mod tests {
             "jsonrpc":"2.0",
             "result": {
                 "context":{"slot":0},
-                "value":{"err": "BlockhashNotFound", "accounts": null, "logs":[]}
+                "value":{
+                    "err": "BlockhashNotFound",
+                    "accounts": null,
+                    "logs":[],
+                    "unitsConsumed": 0,
+                    "instructionUnitsConsumed": []
+                }
             },
             "id":1
         });
@@ -5694,10 +6195,19 @@ pub mod tests {
             "jsonrpc": "2.0",
             "result": {
                 "context":{"slot":0},
-                "value":{"accounts": null, "err":null, "logs":[
-                    "Program 11111111111111111111111111111111 invoke [1]",
-                    "Program 11111111111111111111111111111111 success"
-                ]}
+                "value":{
+                    "accounts": null,
+                    "err":null,
+                    "logs":[
+                        "Program 11111111111111111111111111111111 invoke [1]",
+                        "Program 11111111111111111111111111111111 success"
+                    ],
+                    "unitsConsumed": 0,
+                    "instructionUnitsConsumed": [0],
+                    "innerInstructions": [],
+                    "preTokenBalances": [],
+                    "postTokenBalances": []
+                }
             },
             "id": 1,
         });
@@ -5983,6 +6493,53 @@ pub mod tests {
         assert_eq!(error["code"], ErrorCode::InvalidParams.code());
     }
 
+    #[test]
+    fn test_rpc_get_send_transaction_status() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler { io, meta, .. } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let queued_signature = Signature::new(&[1; 64]);
+        let mut transaction_info =
+            TransactionInfo::new(queued_signature, vec![], 42, None, Some(5));
+        transaction_info.retries = 2;
+        meta.transaction_queue()
+            .write()
+            .unwrap()
+            .insert(queued_signature, transaction_info);
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getSendTransactionStatus","params":["{}"]}}"#,
+            queued_signature
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let expected = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": { "sendCount": 3, "lastValidSlot": 42 },
+        });
+        let expected: Response =
+            serde_json::from_value(expected).expect("expected response deserialization");
+        assert_eq!(expected, result);
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getSendTransactionStatus","params":["{}"]}}"#,
+            Signature::default()
+        );
+        let res = io.handle_request_sync(&req, meta);
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let expected = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": null,
+        });
+        let expected: Response =
+            serde_json::from_value(expected).expect("expected response deserialization");
+        assert_eq!(expected, result);
+    }
+
     #[test]
     fn test_rpc_send_transaction_preflight() {
         let exit = Arc::new(AtomicBool::new(false));
@@ -6038,7 +6595,7 @@ pub mod tests {
         assert_eq!(
             res,
             Some(
-                r#"{"jsonrpc":"2.0","error":{"code":-32002,"message":"Transaction simulation failed: Blockhash not found","data":{"accounts":null,"err":"BlockhashNotFound","logs":[]}},"id":1}"#.to_string(),
+                r#"{"jsonrpc":"2.0","error":{"code":-32002,"message":"Transaction simulation failed: Blockhash not found","data":{"accounts":null,"err":"BlockhashNotFound","instructionUnitsConsumed":[],"logs":[],"unitsConsumed":0}},"id":1}"#.to_string(),
             )
         );
 
@@ -6153,6 +6710,21 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_rpc_verify_airdrop_recipient() {
+        let pubkey = solana_sdk::pubkey::new_rand();
+        assert!(matches!(
+            verify_airdrop_recipient(&pubkey.to_string()).unwrap(),
+            AirdropRecipient::Native(recipient) if recipient == pubkey
+        ));
+        let address = evm_state::Address::repeat_byte(0xab);
+        assert!(matches!(
+            verify_airdrop_recipient(&format!("{:?}", address)).unwrap(),
+            AirdropRecipient::Evm(recipient) if recipient == address
+        ));
+        assert!(verify_airdrop_recipient("0xa1b2c3d4").is_err());
+    }
+
     #[test]
     fn test_rpc_verify_signature() {
         let tx = system_transaction::transfer(
@@ -7671,6 +8243,68 @@ pub mod tests {
         .is_none());
     }
 
+    #[test]
+    fn test_get_custom_index_filter() {
+        let program_id = Pubkey::new_unique();
+        let custom_index = CustomAccountIndex::new(program_id, 8, 4).unwrap();
+        let mut account_indexes = AccountSecondaryIndexes::default();
+        account_indexes
+            .indexes
+            .insert(AccountIndex::Custom(custom_index));
+        let memcmp = |offset, bytes: &[u8]| {
+            RpcFilterType::Memcmp(Memcmp {
+                offset,
+                bytes: MemcmpEncodedBytes::Binary(bs58::encode(bytes).into_string()),
+                encoding: None,
+            })
+        };
+
+        assert_eq!(
+            get_custom_index_filter(
+                &account_indexes,
+                &program_id,
+                &[RpcFilterType::DataSize(64), memcmp(8, &[1, 2, 3, 4])],
+            ),
+            Some(IndexKey::Custom(
+                custom_index,
+                custom_index.index_key(&[1, 2, 3, 4]).unwrap()
+            ))
+        );
+
+        // Wrong offset, length or program id
+        assert!(get_custom_index_filter(
+            &account_indexes,
+            &program_id,
+            &[memcmp(0, &[1, 2, 3, 4])]
+        )
+        .is_none());
+        assert!(
+            get_custom_index_filter(&account_indexes, &program_id, &[memcmp(8, &[1, 2, 3])])
+                .is_none()
+        );
+        assert!(get_custom_index_filter(
+            &account_indexes,
+            &Pubkey::new_unique(),
+            &[memcmp(8, &[1, 2, 3, 4])]
+        )
+        .is_none());
+
+        // Excluded key
+        account_indexes.keys = Some(AccountSecondaryIndexesIncludeExclude {
+            exclude: true,
+            keys: [custom_index.index_key(&[1, 2, 3, 4]).unwrap()]
+                .iter()
+                .cloned()
+                .collect(),
+        });
+        assert!(get_custom_index_filter(
+            &account_indexes,
+            &program_id,
+            &[memcmp(8, &[1, 2, 3, 4])]
+        )
+        .is_none());
+    }
+
     #[test]
     fn test_rpc_single_gossip() {
         let exit = Arc::new(AtomicBool::new(false));
@@ -7863,4 +8497,64 @@ pub mod tests {
             expect58
         );
     }
+
+    #[test]
+    fn test_sanitize_versioned_transaction() {
+        let mut genesis = create_genesis_config(100);
+        let payer = &genesis.mint_keypair;
+        let transfer = system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1);
+        let message = VersionedMessage::V0(v0::Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![payer.pubkey(), system_program::id()],
+            recent_blockhash: genesis.genesis_config.hash(),
+            instructions: vec![CompiledInstruction {
+                program_id_index: 1,
+                accounts: vec![0, 2],
+                data: transfer.data,
+            }],
+            address_table_lookups: vec![v0::MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![],
+            }],
+        });
+        let transaction = VersionedTransaction::try_new(message, &[payer]).unwrap();
+        let wire_transaction = serialize(&transaction).unwrap();
+        let (_, deserialized) = deserialize_transaction(
+            base64::encode(&wire_transaction),
+            UiTransactionEncoding::Base64,
+        )
+        .unwrap();
+        assert_eq!(deserialized, transaction);
+
+        let bank = Bank::new(&genesis.genesis_config);
+        assert_eq!(
+            sanitize_transaction(transaction.clone(), &bank).unwrap_err(),
+            Error::invalid_params(format!(
+                "invalid transaction: {}",
+                TransactionError::UnsupportedVersion
+            ))
+        );
+
+        activate_all_features(&mut genesis.genesis_config);
+        let bank = Bank::new(&genesis.genesis_config);
+        assert_eq!(
+            verify_transaction(transaction.clone(), &bank).unwrap_err(),
+            Error::invalid_params(format!(
+                "invalid transaction: {}",
+                TransactionError::AddressLookupTableNotFound
+            ))
+        );
+
+        let mut unsigned_transaction = transaction;
+        unsigned_transaction.signatures = vec![Signature::default()];
+        assert_eq!(
+            verify_transaction(unsigned_transaction, &bank).unwrap_err(),
+            Error::from(RpcCustomError::TransactionSignatureVerificationFailure)
+        );
+    }
 }
@@ -5,10 +5,9 @@ use crate::{
     cluster_info::ClusterInfo,
     max_slots::MaxSlots,
     optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
-    poh_recorder::PohRecorder,
     rpc::{rpc_full::*, rpc_minimal::*, *},
     rpc_health::*,
-    send_transaction_service::{LeaderInfo, SendTransactionService},
+    send_transaction_service::{self, LeaderInfo, SendTransactionService},
     validator::ValidatorExit,
 };
 use evm_rpc::*;
@@ -32,7 +31,7 @@ use std::{
     net::SocketAddr,
     path::{Path, PathBuf},
     sync::atomic::{AtomicBool, AtomicU64, Ordering},
-    sync::{mpsc::channel, Arc, Mutex, RwLock},
+    sync::{mpsc::channel, Arc, RwLock},
     thread::{self, Builder, JoinHandle},
 };
 use tokio_util::codec::{BytesCodec, FramedRead};
@@ -72,7 +71,7 @@ impl RpcRequestMiddleware {
         Self {
             ledger_path,
             snapshot_archive_path_regex: Regex::new(
-                r"^/snapshot-\d+-[[:alnum:]]+\.(tar|tar\.bz2|tar\.zst|tar\.gz)$",
+                r"^/(snapshot-\d+|incremental-snapshot-\d+-\d+)-[[:alnum:]]+\.(tar|tar\.bz2|tar\.zst|tar\.gz)$",
             )
             .unwrap(),
             snapshot_config,
@@ -213,6 +212,33 @@ impl RequestMiddleware for RpcRequestMiddleware {
                 }
                 .into();
             }
+            if request.uri().path() == "/incremental-snapshot.tar.bz2" {
+                // Convenience redirect to the latest incremental snapshot,
+                // which is based on the latest full snapshot
+                let incremental_snapshot_archive =
+                    snapshot_utils::get_highest_snapshot_archive_path(
+                        &snapshot_config.snapshot_package_output_path,
+                    )
+                    .and_then(|(_, (base_slot, ..))| {
+                        snapshot_utils::get_highest_incremental_snapshot_archive_path(
+                            &snapshot_config.snapshot_package_output_path,
+                            base_slot,
+                        )
+                    });
+                return if let Some((snapshot_archive, _)) = incremental_snapshot_archive {
+                    RpcRequestMiddleware::redirect(&format!(
+                        "/{}",
+                        snapshot_archive
+                            .file_name()
+                            .unwrap_or_else(|| std::ffi::OsStr::new(""))
+                            .to_str()
+                            .unwrap_or("")
+                    ))
+                } else {
+                    RpcRequestMiddleware::not_found()
+                }
+                .into();
+            }
         }
 
         if let Some(result) = process_rest(&self.bank_forks, request.uri().path()) {
@@ -268,15 +294,13 @@ impl JsonRpcService {
         block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
         blockstore: Arc<Blockstore>,
         cluster_info: Arc<ClusterInfo>,
-        poh_recorder: Option<Arc<Mutex<PohRecorder>>>,
         genesis_hash: Hash,
         ledger_path: &Path,
         validator_exit: Arc<RwLock<ValidatorExit>>,
         trusted_validators: Option<HashSet<Pubkey>>,
         override_health_check: Arc<AtomicBool>,
         optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
-        send_transaction_retry_ms: u64,
-        send_transaction_leader_forward_count: u64,
+        send_transaction_service_config: send_transaction_service::Config,
         max_slots: Arc<MaxSlots>,
         leader_schedule_cache: Arc<LeaderScheduleCache>,
         current_transaction_status_slot: Arc<AtomicU64>,
@@ -320,12 +344,16 @@ impl JsonRpcService {
         let (bigtable_ledger_storage, _bigtable_ledger_upload_service) =
             if config.enable_bigtable_ledger_storage || config.enable_bigtable_ledger_upload {
                 runtime
-                    .block_on(solana_storage_bigtable::LedgerStorage::new(
+                    .block_on(solana_storage_bigtable::LedgerStorage::new_with_config(
+                        config.ledger_storage_config.clone(),
                         !config.enable_bigtable_ledger_upload,
                         config.rpc_bigtable_timeout,
                     ))
                     .map(|bigtable_ledger_storage| {
-                        info!("BigTable ledger storage initialized");
+                        info!(
+                            "Ledger storage initialized: {}",
+                            config.ledger_storage_config
+                        );
 
                         let bigtable_ledger_upload_service = if config.enable_bigtable_ledger_upload
                         {
@@ -346,7 +374,10 @@ impl JsonRpcService {
                         )
                     })
                     .unwrap_or_else(|err| {
-                        error!("Failed to initialize BigTable ledger storage: {:?}", err);
+                        error!(
+                            "Failed to initialize ledger storage {}: {:?}",
+                            config.ledger_storage_config, err
+                        );
                         (None, None)
                     })
             } else {
@@ -368,20 +399,19 @@ impl JsonRpcService {
             optimistically_confirmed_bank,
             largest_accounts_cache,
             max_slots,
-            leader_schedule_cache,
+            leader_schedule_cache.clone(),
             current_transaction_status_slot,
             evm_state_archive,
         );
 
-        let leader_info =
-            poh_recorder.map(|recorder| LeaderInfo::new(cluster_info.clone(), recorder));
-        let _send_transaction_service = Arc::new(SendTransactionService::new(
+        let leader_info = LeaderInfo::new(cluster_info.clone(), leader_schedule_cache);
+        let _send_transaction_service = Arc::new(SendTransactionService::new_with_config(
             tpu_address,
             &bank_forks,
-            leader_info,
+            Some(leader_info),
             receiver,
-            send_transaction_retry_ms,
-            send_transaction_leader_forward_count,
+            request_processor.transaction_queue(),
+            send_transaction_service_config,
         ));
         if let Some(collector) = jaeger_collector_url {
             // init tracer
@@ -430,6 +460,7 @@ impl JsonRpcService {
                 io.extend_with(super::evm_rpc_impl::ChainErpcImpl.to_delegate());
                 io.extend_with(super::evm_rpc_impl::GeneralErpcImpl.to_delegate());
                 io.extend_with(super::evm_rpc_impl::TraceErpcImpl.to_delegate());
+                io.extend_with(super::evm_rpc_impl::TokenErpcImpl.to_delegate());
 
                 let request_middleware = RpcRequestMiddleware::new(
                     ledger_path,
@@ -539,15 +570,17 @@ mod tests {
             block_commitment_cache,
             blockstore,
             cluster_info,
-            None,
             Hash::default(),
             &PathBuf::from("farf"),
             validator_exit,
             None,
             Arc::new(AtomicBool::new(false)),
             optimistically_confirmed_bank,
-            1000,
-            1,
+            send_transaction_service::Config {
+                retry_rate_ms: 1000,
+                leader_forward_count: 1,
+                ..send_transaction_service::Config::default()
+            },
             Arc::new(MaxSlots::default()),
             Arc::new(LeaderScheduleCache::default()),
             Arc::new(AtomicU64::default()),
@@ -601,6 +634,7 @@ mod tests {
             PathBuf::from("/"),
             Some(SnapshotConfig {
                 snapshot_interval_slots: 0,
+                incremental_snapshot_interval_slots: 0,
                 snapshot_package_output_path: PathBuf::from("/"),
                 snapshot_path: PathBuf::from("/"),
                 archive_format: ArchiveFormat::TarBzip2,
@@ -635,6 +669,14 @@ mod tests {
 
         assert!(!rrm_with_snapshot_config.is_file_get_path("../../../test/snapshot-123-xxx.tar"));
 
+        assert!(!rrm.is_file_get_path("/incremental-snapshot.tar.bz2")); // This is a redirect
+        assert!(rrm_with_snapshot_config.is_file_get_path(
+            "/incremental-snapshot-100-110-AvFf9oS8A8U78HdjT9YG2sTTThLHJZmhaMn2g8vkWYnr.tar.zst"
+        ));
+        assert!(!rrm_with_snapshot_config.is_file_get_path(
+            "/incremental-snapshot-110-AvFf9oS8A8U78HdjT9YG2sTTThLHJZmhaMn2g8vkWYnr.tar.zst"
+        ));
+
         assert!(!rrm.is_file_get_path("/"));
         assert!(!rrm.is_file_get_path(".."));
         assert!(!rrm.is_file_get_path("🎣"));
@@ -1,7 +1,7 @@
 // TODO: Merge this implementation with the one at `banks-server/src/send_transaction_service.rs`
 use crate::cluster_info::ClusterInfo;
-use crate::poh_recorder::PohRecorder;
 use log::*;
+use solana_ledger::leader_schedule_cache::LeaderScheduleCache;
 use solana_metrics::{datapoint_warn, inc_new_counter_info};
 use solana_runtime::{bank::Bank, bank_forks::BankForks};
 use solana_sdk::{
@@ -11,7 +11,6 @@ use solana_sdk::{
     pubkey::Pubkey,
     signature::Signature,
 };
-use std::sync::Mutex;
 use std::{
     collections::HashMap,
     net::{SocketAddr, UdpSocket},
@@ -26,6 +25,10 @@ use std::{
 /// Maximum size of the transaction queue
 const MAX_TRANSACTION_QUEUE_SIZE: usize = 10_000; // This seems like a lot but maybe it needs to be bigger one day
 
+/// Transactions queued for retries, by signature. Shared with the RPC service, which reports
+/// whether a transaction is still queued and how many times it was sent.
+pub type TransactionQueue = Arc<RwLock<HashMap<Signature, TransactionInfo>>>;
+
 pub struct SendTransactionService {
     thread: JoinHandle<()>,
 }
@@ -35,6 +38,10 @@ pub struct TransactionInfo {
     pub wire_transaction: Vec<u8>,
     pub last_valid_slot: Slot,
     pub durable_nonce_info: Option<(Pubkey, Hash)>,
+    /// Maximum number of retries requested by the sender, `None` for the service default
+    pub max_retries: Option<usize>,
+    /// Number of times the transaction was sent again after the first time
+    pub retries: usize,
 }
 
 impl TransactionInfo {
@@ -43,27 +50,65 @@ impl TransactionInfo {
         wire_transaction: Vec<u8>,
         last_valid_slot: Slot,
         durable_nonce_info: Option<(Pubkey, Hash)>,
+        max_retries: Option<usize>,
     ) -> Self {
         Self {
             signature,
             wire_transaction,
             last_valid_slot,
             durable_nonce_info,
+            max_retries,
+            retries: 0,
         }
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub retry_rate_ms: u64,
+    /// Number of upcoming leaders the transactions are sent to
+    pub leader_forward_count: u64,
+    /// Maximum number of retries of transactions whose sender did not specify one, `None` to
+    /// retry them until their blockhash expires
+    pub default_max_retries: Option<usize>,
+    /// Upper bound of the maximum number of retries requested by senders
+    pub service_max_retries: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            retry_rate_ms: 2000,
+            leader_forward_count: 2,
+            default_max_retries: None,
+            service_max_retries: usize::MAX,
+        }
+    }
+}
+
+impl Config {
+    fn max_retries(&self, transaction_info: &TransactionInfo) -> Option<usize> {
+        transaction_info
+            .max_retries
+            .or(self.default_max_retries)
+            .map(|max_retries| max_retries.min(self.service_max_retries))
+    }
+}
+
 pub struct LeaderInfo {
     cluster_info: Arc<ClusterInfo>,
-    poh_recorder: Arc<Mutex<PohRecorder>>,
+    leader_schedule_cache: Arc<LeaderScheduleCache>,
     recent_peers: HashMap<Pubkey, SocketAddr>,
 }
 
 impl LeaderInfo {
-    pub fn new(cluster_info: Arc<ClusterInfo>, poh_recorder: Arc<Mutex<PohRecorder>>) -> Self {
+    pub fn new(
+        cluster_info: Arc<ClusterInfo>,
+        leader_schedule_cache: Arc<LeaderScheduleCache>,
+    ) -> Self {
         Self {
             cluster_info,
-            poh_recorder,
+            leader_schedule_cache,
             recent_peers: HashMap::new(),
         }
     }
@@ -77,12 +122,15 @@ impl LeaderInfo {
             .collect();
     }
 
-    pub fn get_leader_tpus(&self, max_count: u64) -> Vec<&SocketAddr> {
-        let recorder = self.poh_recorder.lock().unwrap();
+    /// TPU addresses of the leaders of the `max_count` leader windows following the slot of
+    /// `bank`, computing the leader schedule of the next epoch if needed
+    pub fn get_leader_tpus(&self, bank: &Bank, max_count: u64) -> Vec<&SocketAddr> {
         let leaders: Vec<_> = (0..max_count)
-            .filter_map(|i| recorder.leader_after_n_slots(i * NUM_CONSECUTIVE_LEADER_SLOTS))
+            .filter_map(|i| {
+                self.leader_schedule_cache
+                    .slot_leader_at(bank.slot() + i * NUM_CONSECUTIVE_LEADER_SLOTS, Some(bank))
+            })
             .collect();
-        drop(recorder);
         let mut unique_leaders = vec![];
         for leader in leaders.iter() {
             if let Some(addr) = self.recent_peers.get(leader) {
@@ -100,6 +148,7 @@ struct ProcessTransactionsResult {
     rooted: u64,
     expired: u64,
     retried: u64,
+    max_retries_elapsed: u64,
     failed: u64,
     retained: u64,
 }
@@ -112,14 +161,37 @@ impl SendTransactionService {
         receiver: Receiver<TransactionInfo>,
         retry_rate_ms: u64,
         leader_forward_count: u64,
+    ) -> Self {
+        let config = Config {
+            retry_rate_ms,
+            leader_forward_count,
+            ..Config::default()
+        };
+        Self::new_with_config(
+            tpu_address,
+            bank_forks,
+            leader_info,
+            receiver,
+            TransactionQueue::default(),
+            config,
+        )
+    }
+
+    pub fn new_with_config(
+        tpu_address: SocketAddr,
+        bank_forks: &Arc<RwLock<BankForks>>,
+        leader_info: Option<LeaderInfo>,
+        receiver: Receiver<TransactionInfo>,
+        transaction_queue: TransactionQueue,
+        config: Config,
     ) -> Self {
         let thread = Self::retry_thread(
             tpu_address,
             receiver,
             bank_forks.clone(),
             leader_info,
-            retry_rate_ms,
-            leader_forward_count,
+            transaction_queue,
+            config,
         );
         Self { thread }
     }
@@ -129,12 +201,11 @@ impl SendTransactionService {
         receiver: Receiver<TransactionInfo>,
         bank_forks: Arc<RwLock<BankForks>>,
         mut leader_info: Option<LeaderInfo>,
-        retry_rate_ms: u64,
-        leader_forward_count: u64,
+        transactions: TransactionQueue,
+        config: Config,
     ) -> JoinHandle<()> {
         let mut last_status_check = Instant::now();
         let mut last_leader_refresh = Instant::now();
-        let mut transactions = HashMap::new();
         let send_socket = UdpSocket::bind("0.0.0.0:0").unwrap();
 
         if let Some(leader_info) = leader_info.as_mut() {
@@ -144,22 +215,17 @@ impl SendTransactionService {
         Builder::new()
             .name("send-tx-sv2".to_string())
             .spawn(move || loop {
-                match receiver.recv_timeout(Duration::from_millis(1000.min(retry_rate_ms))) {
+                match receiver.recv_timeout(Duration::from_millis(1000.min(config.retry_rate_ms))) {
                     Err(RecvTimeoutError::Disconnected) => break,
                     Err(RecvTimeoutError::Timeout) => {}
                     Ok(transaction_info) => {
-                        let addresses = leader_info
-                            .as_ref()
-                            .map(|leader_info| leader_info.get_leader_tpus(leader_forward_count));
-                        let addresses = addresses
-                            .map(|address_list| {
-                                if address_list.is_empty() {
-                                    vec![&tpu_address]
-                                } else {
-                                    address_list
-                                }
-                            })
-                            .unwrap_or_else(|| vec![&tpu_address]);
+                        let working_bank = bank_forks.read().unwrap().working_bank();
+                        let addresses = Self::get_tpu_addresses(
+                            &tpu_address,
+                            leader_info.as_ref(),
+                            &working_bank,
+                            &config,
+                        );
                         for address in addresses {
                             Self::send_transaction(
                                 &send_socket,
@@ -167,6 +233,7 @@ impl SendTransactionService {
                                 &transaction_info.wire_transaction,
                             );
                         }
+                        let mut transactions = transactions.write().unwrap();
                         if transactions.len() < MAX_TRANSACTION_QUEUE_SIZE {
                             transactions.insert(transaction_info.signature, transaction_info);
                         } else {
@@ -175,7 +242,8 @@ impl SendTransactionService {
                     }
                 }
 
-                if last_status_check.elapsed().as_millis() as u64 >= retry_rate_ms {
+                if last_status_check.elapsed().as_millis() as u64 >= config.retry_rate_ms {
+                    let mut transactions = transactions.write().unwrap();
                     if !transactions.is_empty() {
                         datapoint_info!(
                             "send_transaction_service-queue-size",
@@ -189,15 +257,26 @@ impl SendTransactionService {
                             )
                         };
 
-                        let _result = Self::process_transactions(
+                        let (_result, wire_transactions) = Self::process_transactions(
                             &working_bank,
                             &root_bank,
-                            &send_socket,
-                            &tpu_address,
                             &mut transactions,
-                            &leader_info,
-                            leader_forward_count,
+                            &config,
+                        );
+                        // The queue is shared with the RPC service, so it is not held while
+                        // sending
+                        drop(transactions);
+                        let addresses = Self::get_tpu_addresses(
+                            &tpu_address,
+                            leader_info.as_ref(),
+                            &working_bank,
+                            &config,
                         );
+                        for wire_transaction in wire_transactions.iter() {
+                            for address in addresses.iter() {
+                                Self::send_transaction(&send_socket, address, wire_transaction);
+                            }
+                        }
                     }
                     last_status_check = Instant::now();
                     if last_leader_refresh.elapsed().as_millis() > 1000 {
@@ -211,16 +290,16 @@ impl SendTransactionService {
             .unwrap()
     }
 
+    /// Drops the transactions which no longer need to be sent, returning the wire transactions
+    /// of the ones to send again
     fn process_transactions(
         working_bank: &Arc<Bank>,
         root_bank: &Arc<Bank>,
-        send_socket: &UdpSocket,
-        tpu_address: &SocketAddr,
         transactions: &mut HashMap<Signature, TransactionInfo>,
-        leader_info: &Option<LeaderInfo>,
-        leader_forward_count: u64,
-    ) -> ProcessTransactionsResult {
+        config: &Config,
+    ) -> (ProcessTransactionsResult, Vec<Vec<u8>>) {
         let mut result = ProcessTransactionsResult::default();
+        let mut wire_transactions = vec![];
 
         transactions.retain(|signature, transaction_info| {
             if transaction_info.durable_nonce_info.is_some() {
@@ -254,28 +333,19 @@ impl SendTransactionService {
                 None => {
                     // Transaction is unknown to the working bank, it might have been
                     // dropped or landed in another fork.  Re-send it
+                    if let Some(max_retries) = config.max_retries(transaction_info) {
+                        if transaction_info.retries >= max_retries {
+                            info!("Dropping transaction due to max retries: {}", signature);
+                            result.max_retries_elapsed += 1;
+                            inc_new_counter_info!("send_transaction_service-max_retries", 1);
+                            return false;
+                        }
+                    }
                     info!("Retrying transaction: {}", signature);
                     result.retried += 1;
+                    transaction_info.retries += 1;
                     inc_new_counter_info!("send_transaction_service-retry", 1);
-                    let addresses = leader_info
-                        .as_ref()
-                        .map(|leader_info| leader_info.get_leader_tpus(leader_forward_count));
-                    let addresses = addresses
-                        .map(|address_list| {
-                            if address_list.is_empty() {
-                                vec![tpu_address]
-                            } else {
-                                address_list
-                            }
-                        })
-                        .unwrap_or_else(|| vec![tpu_address]);
-                    for address in addresses {
-                        Self::send_transaction(
-                            send_socket,
-                            address,
-                            &transaction_info.wire_transaction,
-                        );
-                    }
+                    wire_transactions.push(transaction_info.wire_transaction.clone());
                     true
                 }
                 Some((_slot, status)) => {
@@ -292,7 +362,26 @@ impl SendTransactionService {
             }
         });
 
-        result
+        (result, wire_transactions)
+    }
+
+    /// The TPUs of the upcoming leaders, or `tpu_address` when none of them is known
+    fn get_tpu_addresses<'a>(
+        tpu_address: &'a SocketAddr,
+        leader_info: Option<&'a LeaderInfo>,
+        working_bank: &Bank,
+        config: &Config,
+    ) -> Vec<&'a SocketAddr> {
+        let addresses = leader_info
+            .map(|leader_info| {
+                leader_info.get_leader_tpus(working_bank, config.leader_forward_count)
+            })
+            .unwrap_or_default();
+        if addresses.is_empty() {
+            vec![tpu_address]
+        } else {
+            addresses
+        }
     }
 
     fn send_transaction(
@@ -314,9 +403,6 @@ impl SendTransactionService {
 mod test {
     use super::*;
     use crate::contact_info::ContactInfo;
-    use solana_ledger::{
-        blockstore::Blockstore, get_tmp_ledger_path, leader_schedule_cache::LeaderScheduleCache,
-    };
     use solana_runtime::genesis_utils::{
         create_genesis_config_with_vote_accounts, GenesisConfigInfo, ValidatorVoteKeypairs,
     };
@@ -325,13 +411,12 @@ mod test {
         fee_calculator::FeeCalculator,
         genesis_config::create_genesis_config,
         nonce,
-        poh_config::PohConfig,
         pubkey::Pubkey,
         signature::{Keypair, Signer},
         system_program, system_transaction,
         timing::timestamp,
     };
-    use std::sync::{atomic::AtomicBool, mpsc::channel};
+    use std::sync::mpsc::channel;
 
     #[test]
     fn service_exit() {
@@ -354,9 +439,7 @@ mod test {
         let (genesis_config, mint_keypair) = create_genesis_config(4);
         let bank = Bank::new(&genesis_config);
         let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
-        let send_socket = UdpSocket::bind("0.0.0.0:0").unwrap();
-        let tpu_address = "127.0.0.1:0".parse().unwrap();
-        let leader_forward_count = 1;
+        let config = Config::default();
 
         let root_bank = Arc::new(Bank::new_from_parent(
             &bank_forks.read().unwrap().working_bank(),
@@ -387,16 +470,19 @@ mod test {
         info!("Expired transactions are dropped...");
         transactions.insert(
             Signature::default(),
-            TransactionInfo::new(Signature::default(), vec![], root_bank.slot() - 1, None),
+            TransactionInfo::new(
+                Signature::default(),
+                vec![],
+                root_bank.slot() - 1,
+                None,
+                None,
+            ),
         );
-        let result = SendTransactionService::process_transactions(
+        let (result, _) = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
-            &tpu_address,
             &mut transactions,
-            &None,
-            leader_forward_count,
+            &config,
         );
         assert!(transactions.is_empty());
         assert_eq!(
@@ -410,16 +496,13 @@ mod test {
         info!("Rooted transactions are dropped...");
         transactions.insert(
             rooted_signature,
-            TransactionInfo::new(rooted_signature, vec![], working_bank.slot(), None),
+            TransactionInfo::new(rooted_signature, vec![], working_bank.slot(), None, None),
         );
-        let result = SendTransactionService::process_transactions(
+        let (result, _) = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
-            &tpu_address,
             &mut transactions,
-            &None,
-            leader_forward_count,
+            &config,
         );
         assert!(transactions.is_empty());
         assert_eq!(
@@ -433,16 +516,13 @@ mod test {
         info!("Failed transactions are dropped...");
         transactions.insert(
             failed_signature,
-            TransactionInfo::new(failed_signature, vec![], working_bank.slot(), None),
+            TransactionInfo::new(failed_signature, vec![], working_bank.slot(), None, None),
         );
-        let result = SendTransactionService::process_transactions(
+        let (result, _) = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
-            &tpu_address,
             &mut transactions,
-            &None,
-            leader_forward_count,
+            &config,
         );
         assert!(transactions.is_empty());
         assert_eq!(
@@ -456,16 +536,19 @@ mod test {
         info!("Non-rooted transactions are kept...");
         transactions.insert(
             non_rooted_signature,
-            TransactionInfo::new(non_rooted_signature, vec![], working_bank.slot(), None),
+            TransactionInfo::new(
+                non_rooted_signature,
+                vec![],
+                working_bank.slot(),
+                None,
+                None,
+            ),
         );
-        let result = SendTransactionService::process_transactions(
+        let (result, _) = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
-            &tpu_address,
             &mut transactions,
-            &None,
-            leader_forward_count,
+            &config,
         );
         assert_eq!(transactions.len(), 1);
         assert_eq!(
@@ -480,18 +563,22 @@ mod test {
         info!("Unknown transactions are retried...");
         transactions.insert(
             Signature::default(),
-            TransactionInfo::new(Signature::default(), vec![], working_bank.slot(), None),
+            TransactionInfo::new(
+                Signature::default(),
+                vec![1, 2, 3],
+                working_bank.slot(),
+                None,
+                None,
+            ),
         );
-        let result = SendTransactionService::process_transactions(
+        let (result, wire_transactions) = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
-            &tpu_address,
             &mut transactions,
-            &None,
-            leader_forward_count,
+            &config,
         );
         assert_eq!(transactions.len(), 1);
+        assert_eq!(wire_transactions, vec![vec![1, 2, 3]]);
         assert_eq!(
             result,
             ProcessTransactionsResult {
@@ -499,6 +586,98 @@ mod test {
                 ..ProcessTransactionsResult::default()
             }
         );
+        transactions.clear();
+
+        info!("Transactions are retried at most max_retries times...");
+        transactions.insert(
+            Signature::default(),
+            TransactionInfo::new(
+                Signature::default(),
+                vec![],
+                working_bank.slot(),
+                None,
+                Some(1),
+            ),
+        );
+        let (result, _) = SendTransactionService::process_transactions(
+            &working_bank,
+            &root_bank,
+            &mut transactions,
+            &config,
+        );
+        assert_eq!(transactions[&Signature::default()].retries, 1);
+        assert_eq!(
+            result,
+            ProcessTransactionsResult {
+                retried: 1,
+                ..ProcessTransactionsResult::default()
+            }
+        );
+        let (result, _) = SendTransactionService::process_transactions(
+            &working_bank,
+            &root_bank,
+            &mut transactions,
+            &config,
+        );
+        assert!(transactions.is_empty());
+        assert_eq!(
+            result,
+            ProcessTransactionsResult {
+                max_retries_elapsed: 1,
+                ..ProcessTransactionsResult::default()
+            }
+        );
+
+        info!("The service bounds max_retries, and defaults it when unspecified...");
+        let config = Config {
+            default_max_retries: Some(0),
+            service_max_retries: 1,
+            ..config
+        };
+        transactions.insert(
+            Signature::default(),
+            TransactionInfo::new(
+                Signature::default(),
+                vec![],
+                working_bank.slot(),
+                None,
+                None,
+            ),
+        );
+        let capped_signature = Signature::new(&[1; 64]);
+        transactions.insert(
+            capped_signature,
+            TransactionInfo::new(capped_signature, vec![], working_bank.slot(), None, Some(5)),
+        );
+        let (result, _) = SendTransactionService::process_transactions(
+            &working_bank,
+            &root_bank,
+            &mut transactions,
+            &config,
+        );
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(
+            result,
+            ProcessTransactionsResult {
+                retried: 1,
+                max_retries_elapsed: 1,
+                ..ProcessTransactionsResult::default()
+            }
+        );
+        let (result, _) = SendTransactionService::process_transactions(
+            &working_bank,
+            &root_bank,
+            &mut transactions,
+            &config,
+        );
+        assert!(transactions.is_empty());
+        assert_eq!(
+            result,
+            ProcessTransactionsResult {
+                max_retries_elapsed: 1,
+                ..ProcessTransactionsResult::default()
+            }
+        );
     }
 
     #[test]
@@ -508,9 +687,7 @@ mod test {
         let (genesis_config, mint_keypair) = create_genesis_config(4);
         let bank = Bank::new(&genesis_config);
         let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
-        let send_socket = UdpSocket::bind("0.0.0.0:0").unwrap();
-        let tpu_address = "127.0.0.1:0".parse().unwrap();
-        let leader_forward_count = 1;
+        let config = Config::default();
 
         let root_bank = Arc::new(Bank::new_from_parent(
             &bank_forks.read().unwrap().working_bank(),
@@ -559,16 +736,14 @@ mod test {
                 vec![],
                 last_valid_slot,
                 Some((nonce_address, durable_nonce)),
+                None,
             ),
         );
-        let result = SendTransactionService::process_transactions(
+        let (result, _) = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
-            &tpu_address,
             &mut transactions,
-            &None,
-            leader_forward_count,
+            &config,
         );
         assert!(transactions.is_empty());
         assert_eq!(
@@ -586,16 +761,14 @@ mod test {
                 vec![],
                 last_valid_slot,
                 Some((nonce_address, Hash::new_unique())),
+                None,
             ),
         );
-        let result = SendTransactionService::process_transactions(
+        let (result, _) = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
-            &tpu_address,
             &mut transactions,
-            &None,
-            leader_forward_count,
+            &config,
         );
         assert!(transactions.is_empty());
         assert_eq!(
@@ -615,16 +788,14 @@ mod test {
                 vec![],
                 last_valid_slot,
                 Some((nonce_address, Hash::new_unique())),
+                None,
             ),
         );
-        let result = SendTransactionService::process_transactions(
+        let (result, _) = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
-            &tpu_address,
             &mut transactions,
-            &None,
-            leader_forward_count,
+            &config,
         );
         assert!(transactions.is_empty());
         assert_eq!(
@@ -642,16 +813,14 @@ mod test {
                 vec![],
                 root_bank.slot() - 1,
                 Some((nonce_address, durable_nonce)),
+                None,
             ),
         );
-        let result = SendTransactionService::process_transactions(
+        let (result, _) = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
-            &tpu_address,
             &mut transactions,
-            &None,
-            leader_forward_count,
+            &config,
         );
         assert!(transactions.is_empty());
         assert_eq!(
@@ -670,16 +839,14 @@ mod test {
                 vec![],
                 last_valid_slot,
                 Some((nonce_address, Hash::new_unique())), // runtime should advance nonce on failed transactions
+                None,
             ),
         );
-        let result = SendTransactionService::process_transactions(
+        let (result, _) = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
-            &tpu_address,
             &mut transactions,
-            &None,
-            leader_forward_count,
+            &config,
         );
         assert!(transactions.is_empty());
         assert_eq!(
@@ -698,16 +865,14 @@ mod test {
                 vec![],
                 last_valid_slot,
                 Some((nonce_address, Hash::new_unique())), // runtime advances nonce when transaction lands
+                None,
             ),
         );
-        let result = SendTransactionService::process_transactions(
+        let (result, _) = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
-            &tpu_address,
             &mut transactions,
-            &None,
-            leader_forward_count,
+            &config,
         );
         assert_eq!(transactions.len(), 1);
         assert_eq!(
@@ -727,16 +892,14 @@ mod test {
                 vec![],
                 last_valid_slot,
                 Some((nonce_address, durable_nonce)),
+                None,
             ),
         );
-        let result = SendTransactionService::process_transactions(
+        let (result, _) = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
-            &tpu_address,
             &mut transactions,
-            &None,
-            leader_forward_count,
+            &config,
         );
         assert_eq!(transactions.len(), 1);
         assert_eq!(
@@ -757,14 +920,11 @@ mod test {
         let nonce_account =
             AccountSharedData::new_data(43, &new_nonce_state, &system_program::id()).unwrap();
         working_bank.store_account(&nonce_address, &nonce_account);
-        let result = SendTransactionService::process_transactions(
+        let (result, _) = SendTransactionService::process_transactions(
             &working_bank,
             &root_bank,
-            &send_socket,
-            &tpu_address,
             &mut transactions,
-            &None,
-            leader_forward_count,
+            &config,
         );
         assert_eq!(transactions.len(), 0);
         assert_eq!(
@@ -778,111 +938,98 @@ mod test {
 
     #[test]
     fn test_get_leader_tpus() {
-        let ledger_path = get_tmp_ledger_path!();
-        {
-            let blockstore = Blockstore::open(&ledger_path).unwrap();
-
-            let validator_vote_keypairs0 = ValidatorVoteKeypairs::new_rand();
-            let validator_vote_keypairs1 = ValidatorVoteKeypairs::new_rand();
-            let validator_vote_keypairs2 = ValidatorVoteKeypairs::new_rand();
-            let validator_keypairs = vec![
-                &validator_vote_keypairs0,
-                &validator_vote_keypairs1,
-                &validator_vote_keypairs2,
-            ];
-            let GenesisConfigInfo {
-                genesis_config,
-                mint_keypair: _,
-                voting_keypair: _,
-            } = create_genesis_config_with_vote_accounts(
-                1_000_000_000,
-                &validator_keypairs,
-                vec![10_000; 3],
-            );
-            let bank = Arc::new(Bank::new(&genesis_config));
-
-            let (poh_recorder, _entry_receiver, _record_receiver) = PohRecorder::new(
-                0,
-                bank.last_blockhash(),
-                0,
-                Some((2, 2)),
-                bank.ticks_per_slot(),
-                &Pubkey::default(),
-                &Arc::new(blockstore),
-                &Arc::new(LeaderScheduleCache::new_from_bank(&bank)),
-                &Arc::new(PohConfig::default()),
-                Arc::new(AtomicBool::default()),
-            );
-
-            let node_keypair = Arc::new(Keypair::new());
-            let cluster_info = Arc::new(ClusterInfo::new(
-                ContactInfo::new_localhost(&node_keypair.pubkey(), timestamp()),
-                node_keypair,
-            ));
-
-            let validator0_socket = SocketAddr::from(([127, 0, 0, 1], 1111));
-            let validator1_socket = SocketAddr::from(([127, 0, 0, 1], 2222));
-            let validator2_socket = SocketAddr::from(([127, 0, 0, 1], 3333));
-            let recent_peers: HashMap<_, _> = vec![
-                (
-                    validator_vote_keypairs0.node_keypair.pubkey(),
-                    validator0_socket,
-                ),
-                (
-                    validator_vote_keypairs1.node_keypair.pubkey(),
-                    validator1_socket,
-                ),
-                (
-                    validator_vote_keypairs2.node_keypair.pubkey(),
-                    validator2_socket,
-                ),
-            ]
-            .iter()
-            .cloned()
-            .collect();
-            let leader_info = LeaderInfo {
-                cluster_info,
-                poh_recorder: Arc::new(Mutex::new(poh_recorder)),
-                recent_peers: recent_peers.clone(),
-            };
-
-            let slot = bank.slot();
-            let first_leader =
-                solana_ledger::leader_schedule_utils::slot_leader_at(slot, &bank).unwrap();
-            assert_eq!(
-                leader_info.get_leader_tpus(1),
-                vec![recent_peers.get(&first_leader).unwrap()]
-            );
-
-            let second_leader = solana_ledger::leader_schedule_utils::slot_leader_at(
-                slot + NUM_CONSECUTIVE_LEADER_SLOTS,
-                &bank,
-            )
-            .unwrap();
-            let mut expected_leader_sockets = vec![
-                recent_peers.get(&first_leader).unwrap(),
-                recent_peers.get(&second_leader).unwrap(),
-            ];
-            expected_leader_sockets.dedup();
-            assert_eq!(leader_info.get_leader_tpus(2), expected_leader_sockets);
-
-            let third_leader = solana_ledger::leader_schedule_utils::slot_leader_at(
-                slot + (2 * NUM_CONSECUTIVE_LEADER_SLOTS),
-                &bank,
-            )
-            .unwrap();
-            let mut expected_leader_sockets = vec![
-                recent_peers.get(&first_leader).unwrap(),
-                recent_peers.get(&second_leader).unwrap(),
-                recent_peers.get(&third_leader).unwrap(),
-            ];
-            expected_leader_sockets.dedup();
-            assert_eq!(leader_info.get_leader_tpus(3), expected_leader_sockets);
-
-            for x in 4..8 {
-                assert!(leader_info.get_leader_tpus(x).len() <= recent_peers.len());
-            }
+        let validator_vote_keypairs0 = ValidatorVoteKeypairs::new_rand();
+        let validator_vote_keypairs1 = ValidatorVoteKeypairs::new_rand();
+        let validator_vote_keypairs2 = ValidatorVoteKeypairs::new_rand();
+        let validator_keypairs = vec![
+            &validator_vote_keypairs0,
+            &validator_vote_keypairs1,
+            &validator_vote_keypairs2,
+        ];
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair: _,
+            voting_keypair: _,
+        } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 3],
+        );
+        let bank = Arc::new(Bank::new(&genesis_config));
+
+        let node_keypair = Arc::new(Keypair::new());
+        let cluster_info = Arc::new(ClusterInfo::new(
+            ContactInfo::new_localhost(&node_keypair.pubkey(), timestamp()),
+            node_keypair,
+        ));
+
+        let validator0_socket = SocketAddr::from(([127, 0, 0, 1], 1111));
+        let validator1_socket = SocketAddr::from(([127, 0, 0, 1], 2222));
+        let validator2_socket = SocketAddr::from(([127, 0, 0, 1], 3333));
+        let recent_peers: HashMap<_, _> = vec![
+            (
+                validator_vote_keypairs0.node_keypair.pubkey(),
+                validator0_socket,
+            ),
+            (
+                validator_vote_keypairs1.node_keypair.pubkey(),
+                validator1_socket,
+            ),
+            (
+                validator_vote_keypairs2.node_keypair.pubkey(),
+                validator2_socket,
+            ),
+        ]
+        .iter()
+        .cloned()
+        .collect();
+        let leader_info = LeaderInfo {
+            cluster_info,
+            leader_schedule_cache: Arc::new(LeaderScheduleCache::new_from_bank(&bank)),
+            recent_peers: recent_peers.clone(),
+        };
+
+        let slot = bank.slot();
+        let first_leader =
+            solana_ledger::leader_schedule_utils::slot_leader_at(slot, &bank).unwrap();
+        assert_eq!(
+            leader_info.get_leader_tpus(&bank, 1),
+            vec![recent_peers.get(&first_leader).unwrap()]
+        );
+
+        let second_leader = solana_ledger::leader_schedule_utils::slot_leader_at(
+            slot + NUM_CONSECUTIVE_LEADER_SLOTS,
+            &bank,
+        )
+        .unwrap();
+        let mut expected_leader_sockets = vec![
+            recent_peers.get(&first_leader).unwrap(),
+            recent_peers.get(&second_leader).unwrap(),
+        ];
+        expected_leader_sockets.dedup();
+        assert_eq!(
+            leader_info.get_leader_tpus(&bank, 2),
+            expected_leader_sockets
+        );
+
+        let third_leader = solana_ledger::leader_schedule_utils::slot_leader_at(
+            slot + (2 * NUM_CONSECUTIVE_LEADER_SLOTS),
+            &bank,
+        )
+        .unwrap();
+        let mut expected_leader_sockets = vec![
+            recent_peers.get(&first_leader).unwrap(),
+            recent_peers.get(&second_leader).unwrap(),
+            recent_peers.get(&third_leader).unwrap(),
+        ];
+        expected_leader_sockets.dedup();
+        assert_eq!(
+            leader_info.get_leader_tpus(&bank, 3),
+            expected_leader_sockets
+        );
+
+        for x in 4..8 {
+            assert!(leader_info.get_leader_tpus(&bank, x).len() <= recent_peers.len());
         }
-        Blockstore::destroy(&ledger_path).unwrap();
     }
 }
@@ -34,4 +34,8 @@ impl SigVerifier for TransactionSigVerifier {
         sigverify::ed25519_verify(&mut batch, &self.recycler, &self.recycler_out);
         batch
     }
+
+    fn shed_backlog(&self) -> bool {
+        true
+    }
 }
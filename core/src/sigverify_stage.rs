@@ -8,7 +8,7 @@
 use crate::sigverify;
 use crossbeam_channel::{SendError, Sender as CrossbeamSender};
 use solana_measure::measure::Measure;
-use solana_metrics::datapoint_debug;
+use solana_metrics::{datapoint_debug, inc_new_counter_info};
 use solana_perf::packet::Packets;
 use solana_perf::perf_libs;
 use solana_sdk::timing;
@@ -21,6 +21,10 @@ use thiserror::Error;
 const RECV_BATCH_MAX_CPU: usize = 1_000;
 const RECV_BATCH_MAX_GPU: usize = 5_000;
 
+// Packets backlogged beyond this multiple of a verification batch are shed, rather than delaying
+// every later packet behind them
+const MAX_BACKLOG_BATCHES: usize = 4;
+
 #[derive(Error, Debug)]
 pub enum SigVerifyServiceError {
     #[error("send packets batch error")]
@@ -38,6 +42,11 @@ pub struct SigVerifyStage {
 
 pub trait SigVerifier {
     fn verify_batch(&self, batch: Vec<Packets>) -> Vec<Packets>;
+
+    /// Whether packets backlogged beyond a verification batch are shed, see `shed_packets`
+    fn shed_backlog(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Default, Clone)]
@@ -50,6 +59,48 @@ impl SigVerifier for DisabledSigVerifier {
     }
 }
 
+/// Drops all but `max_packets` of the packets in `batches`, keeping those from the senders with
+/// the most stake and, among equally staked senders, the earliest received. Votes and packets
+/// forwarded by staked nodes thus get through a spam wave of unstaked traffic. Returns the
+/// number of packets dropped.
+pub fn shed_packets(batches: &mut Vec<Packets>, max_packets: usize) -> usize {
+    let num_packets: usize = batches.iter().map(|batch| batch.packets.len()).sum();
+    if num_packets <= max_packets {
+        return 0;
+    }
+    let mut packet_stakes: Vec<_> = batches
+        .iter()
+        .enumerate()
+        .flat_map(|(batch_index, batch)| {
+            batch
+                .packets
+                .iter()
+                .enumerate()
+                .map(move |(packet_index, packet)| {
+                    (packet.meta.sender_stake, batch_index, packet_index)
+                })
+        })
+        .collect();
+    // Stable, so that arrival order breaks ties
+    packet_stakes.sort_by(|(stake, _, _), (other_stake, _, _)| other_stake.cmp(stake));
+    let mut keep: Vec<Vec<usize>> = vec![vec![]; batches.len()];
+    for (_, batch_index, packet_index) in packet_stakes.into_iter().take(max_packets) {
+        keep[batch_index].push(packet_index);
+    }
+
+    for (batch, mut keep) in batches.iter_mut().zip(keep) {
+        keep.sort_unstable();
+        // Compact the kept packets to the front of the batch in place
+        for (new_index, old_index) in keep.iter().enumerate() {
+            batch.packets[..].swap(new_index, *old_index);
+        }
+        batch.packets.truncate(keep.len());
+    }
+    batches.retain(|batch| !batch.packets.is_empty());
+    inc_new_counter_info!("sigverify_stage-shed_packets", num_packets - max_packets);
+    num_packets - max_packets
+}
+
 impl SigVerifyStage {
     #[allow(clippy::new_ret_no_self)]
     pub fn new<T: SigVerifier + 'static + Send + Clone>(
@@ -67,14 +118,26 @@ impl SigVerifyStage {
         id: usize,
         verifier: &T,
     ) -> Result<()> {
-        let (batch, len, recv_time) = streamer::recv_batch(
+        let max_packets = if perf_libs::api().is_some() {
+            RECV_BATCH_MAX_GPU
+        } else {
+            RECV_BATCH_MAX_CPU
+        };
+        let shed_backlog = verifier.shed_backlog();
+        let (mut batch, len, recv_time) = streamer::recv_batch(
             &recvr.lock().expect("'recvr' lock in fn verifier"),
-            if perf_libs::api().is_some() {
-                RECV_BATCH_MAX_GPU
+            if shed_backlog {
+                max_packets * MAX_BACKLOG_BATCHES
             } else {
-                RECV_BATCH_MAX_CPU
+                max_packets
             },
         )?;
+        let num_shed = if shed_backlog {
+            shed_packets(&mut batch, max_packets)
+        } else {
+            0
+        };
+        let len = len - num_shed;
 
         let mut verify_batch_time = Measure::start("sigverify_batch_time");
         let batch_len = batch.len();
@@ -107,6 +170,7 @@ impl SigVerifyStage {
             "sigverify_stage-total_verify_time",
             ("num_batches", batch_len, i64),
             ("num_packets", len, i64),
+            ("num_shed_packets", num_shed, i64),
             ("verify_time_ms", verify_batch_time.as_ms(), i64),
             ("recv_time", recv_time, i64),
         );
@@ -162,3 +226,50 @@ impl SigVerifyStage {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_perf::packet::Packet;
+
+    fn packet_with_stake(sender_stake: u64, port: u16) -> Packet {
+        let mut packet = Packet::default();
+        packet.meta.sender_stake = sender_stake;
+        packet.meta.port = port;
+        packet
+    }
+
+    #[test]
+    fn test_shed_packets() {
+        let mut batches = vec![
+            Packets::new(vec![
+                packet_with_stake(0, 0),
+                packet_with_stake(10, 1),
+                packet_with_stake(0, 2),
+            ]),
+            Packets::new(vec![packet_with_stake(0, 3), packet_with_stake(0, 4)]),
+            Packets::new(vec![packet_with_stake(5, 5), packet_with_stake(10, 6)]),
+        ];
+
+        // Nothing is shed below the limit
+        assert_eq!(shed_packets(&mut batches, 7), 0);
+        assert_eq!(batches.len(), 3);
+
+        // Staked packets are kept first, then the earliest unstaked ones
+        assert_eq!(shed_packets(&mut batches, 4), 3);
+        let kept: Vec<Vec<u16>> = batches
+            .iter()
+            .map(|batch| {
+                batch
+                    .packets
+                    .iter()
+                    .map(|packet| packet.meta.port)
+                    .collect()
+            })
+            .collect();
+        assert_eq!(kept, vec![vec![0, 1], vec![5, 6]]);
+
+        assert_eq!(shed_packets(&mut batches, 0), 4);
+        assert!(batches.is_empty());
+    }
+}
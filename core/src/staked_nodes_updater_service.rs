@@ -0,0 +1,131 @@
+//! The `staked_nodes_updater_service` keeps the epoch stakes of node identities, and of the IP
+//! addresses they advertise in gossip, in sync with the root bank. The TPU uses them to give
+//! staked peers a larger share of its ingestion and forwarding capacity.
+
+use crate::cluster_info::ClusterInfo;
+use solana_runtime::bank_forks::BankForks;
+use solana_sdk::pubkey::Pubkey;
+use solana_streamer::staked_nodes::StakedNodes;
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    thread::{self, sleep, Builder, JoinHandle},
+    time::{Duration, Instant},
+};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+const SLEEP_INTERVAL: Duration = Duration::from_millis(100);
+
+pub struct StakedNodesUpdaterService {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl StakedNodesUpdaterService {
+    pub fn new(
+        exit: &Arc<AtomicBool>,
+        cluster_info: &Arc<ClusterInfo>,
+        bank_forks: &Arc<RwLock<BankForks>>,
+        staked_nodes: &Arc<RwLock<StakedNodes>>,
+        ip_to_stake: &Arc<RwLock<HashMap<IpAddr, u64>>>,
+    ) -> Self {
+        let exit = exit.clone();
+        let cluster_info = cluster_info.clone();
+        let bank_forks = bank_forks.clone();
+        let staked_nodes = staked_nodes.clone();
+        let ip_to_stake = ip_to_stake.clone();
+        let thread_hdl = Builder::new()
+            .name("solana-staked-nodes-updater".to_string())
+            .spawn(move || {
+                let mut last_refresh: Option<Instant> = None;
+                while !exit.load(Ordering::Relaxed) {
+                    if last_refresh.map_or(true, |last| last.elapsed() >= REFRESH_INTERVAL) {
+                        Self::refresh(&cluster_info, &bank_forks, &staked_nodes, &ip_to_stake);
+                        last_refresh = Some(Instant::now());
+                    }
+                    sleep(SLEEP_INTERVAL);
+                }
+            })
+            .unwrap();
+        Self { thread_hdl }
+    }
+
+    fn refresh(
+        cluster_info: &ClusterInfo,
+        bank_forks: &RwLock<BankForks>,
+        staked_nodes: &RwLock<StakedNodes>,
+        ip_to_stake: &RwLock<HashMap<IpAddr, u64>>,
+    ) {
+        let root_bank = bank_forks.read().unwrap().root_bank();
+        let stakes = root_bank
+            .epoch_staked_nodes(root_bank.epoch())
+            .unwrap_or_else(|| root_bank.staked_nodes());
+        let new_ip_to_stake = Self::ip_to_stake(
+            cluster_info
+                .all_peers()
+                .into_iter()
+                .map(|(contact_info, _)| (contact_info.id, contact_info.gossip.ip())),
+            &stakes,
+        );
+        *ip_to_stake.write().unwrap() = new_ip_to_stake;
+
+        let new_staked_nodes = StakedNodes::new(stakes);
+        let mut staked_nodes = staked_nodes.write().unwrap();
+        if *staked_nodes != new_staked_nodes {
+            *staked_nodes = new_staked_nodes;
+        }
+    }
+
+    /// Stake of the nodes advertising each IP address. Hosts running several staked identities
+    /// are attributed the largest of their stakes.
+    fn ip_to_stake(
+        nodes: impl Iterator<Item = (Pubkey, IpAddr)>,
+        stakes: &HashMap<Pubkey, u64>,
+    ) -> HashMap<IpAddr, u64> {
+        let mut ip_to_stake = HashMap::new();
+        for (pubkey, ip) in nodes {
+            let stake = stakes.get(&pubkey).copied().unwrap_or_default();
+            if stake > 0 {
+                let entry = ip_to_stake.entry(ip).or_default();
+                *entry = stake.max(*entry);
+            }
+        }
+        ip_to_stake
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_ip_to_stake() {
+        let staked = Pubkey::new_unique();
+        let other_staked = Pubkey::new_unique();
+        let unstaked = Pubkey::new_unique();
+        let stakes: HashMap<_, _> = vec![(staked, 10), (other_staked, 20)].into_iter().collect();
+        let ip = |i| IpAddr::V4(Ipv4Addr::new(10, 0, 0, i));
+
+        let ip_to_stake = StakedNodesUpdaterService::ip_to_stake(
+            vec![
+                (staked, ip(1)),
+                (unstaked, ip(2)),
+                (staked, ip(3)),
+                (other_staked, ip(3)),
+            ]
+            .into_iter(),
+            &stakes,
+        );
+        assert_eq!(ip_to_stake.len(), 2);
+        assert_eq!(ip_to_stake[&ip(1)], 10);
+        assert_eq!(ip_to_stake[&ip(3)], 20);
+    }
+}
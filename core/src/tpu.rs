@@ -10,11 +10,13 @@ use crate::{
         VerifiedVoteSender, VoteTracker,
     },
     fetch_stage::FetchStage,
+    find_packet_sender_stake_stage::FindPacketSenderStakeStage,
     optimistically_confirmed_bank_tracker::BankNotificationSender,
     poh_recorder::{PohRecorder, WorkingBankEntry},
     rpc_subscriptions::RpcSubscriptions,
     sigverify::TransactionSigVerifier,
     sigverify_stage::SigVerifyStage,
+    staked_nodes_updater_service::StakedNodesUpdaterService,
 };
 use crossbeam_channel::unbounded;
 use solana_ledger::{blockstore::Blockstore, blockstore_processor::TransactionStatusSender};
@@ -22,20 +24,25 @@ use solana_runtime::{
     bank_forks::BankForks,
     vote_sender_types::{ReplayVoteReceiver, ReplayVoteSender},
 };
+use solana_streamer::{quic::spawn_server, staked_nodes::StakedNodes};
 use std::{
+    collections::HashMap,
     net::UdpSocket,
     sync::{
         atomic::AtomicBool,
         mpsc::{channel, Receiver},
         Arc, Mutex, RwLock,
     },
-    thread,
+    thread::{self, JoinHandle},
 };
 
 pub const DEFAULT_TPU_COALESCE_MS: u64 = 5;
 
 pub struct Tpu {
     fetch_stage: FetchStage,
+    tpu_quic_t: JoinHandle<()>,
+    staked_nodes_updater_service: StakedNodesUpdaterService,
+    find_packet_sender_stake_stage: FindPacketSenderStakeStage,
     sigverify_stage: SigVerifyStage,
     banking_stage: BankingStage,
     cluster_info_vote_listener: ClusterInfoVoteListener,
@@ -51,6 +58,7 @@ impl Tpu {
         retransmit_slots_receiver: RetransmitSlotsReceiver,
         transactions_sockets: Vec<UdpSocket>,
         tpu_forwards_sockets: Vec<UdpSocket>,
+        tpu_quic_socket: UdpSocket,
         broadcast_sockets: Vec<UdpSocket>,
         subscriptions: &Arc<RpcSubscriptions>,
         transaction_status_sender: Option<TransactionStatusSender>,
@@ -80,11 +88,35 @@ impl Tpu {
             Some(20_000),
             tpu_coalesce_ms,
         );
+
+        let staked_nodes = Arc::new(RwLock::new(StakedNodes::default()));
+        let ip_to_stake = Arc::new(RwLock::new(HashMap::new()));
+        let staked_nodes_updater_service = StakedNodesUpdaterService::new(
+            exit,
+            cluster_info,
+            &bank_forks,
+            &staked_nodes,
+            &ip_to_stake,
+        );
+        let tpu_quic_t = spawn_server(
+            tpu_quic_socket,
+            &cluster_info.keypair,
+            cluster_info.my_contact_info().tpu.ip(),
+            packet_sender,
+            exit.clone(),
+            staked_nodes,
+        )
+        .expect("Failed to start the QUIC TPU");
+
+        let (sender_stake_sender, sender_stake_receiver) = channel();
+        let find_packet_sender_stake_stage =
+            FindPacketSenderStakeStage::new(packet_receiver, sender_stake_sender, ip_to_stake);
+
         let (verified_sender, verified_receiver) = unbounded();
 
         let sigverify_stage = {
             let verifier = TransactionSigVerifier::default();
-            SigVerifyStage::new(packet_receiver, verified_sender, verifier)
+            SigVerifyStage::new(sender_stake_receiver, verified_sender, verifier)
         };
 
         let (verified_vote_packets_sender, verified_vote_packets_receiver) = unbounded();
@@ -125,6 +157,9 @@ impl Tpu {
 
         Self {
             fetch_stage,
+            tpu_quic_t,
+            staked_nodes_updater_service,
+            find_packet_sender_stake_stage,
             sigverify_stage,
             banking_stage,
             cluster_info_vote_listener,
@@ -135,6 +170,9 @@ impl Tpu {
     pub fn join(self) -> thread::Result<()> {
         let results = vec![
             self.fetch_stage.join(),
+            self.tpu_quic_t.join(),
+            self.staked_nodes_updater_service.join(),
+            self.find_packet_sender_stake_stage.join(),
             self.sigverify_stage.join(),
             self.cluster_info_vote_listener.join(),
             self.banking_stage.join(),
@@ -0,0 +1,492 @@
+//! The `transaction_scheduler` module keeps banking threads from contending for account locks.
+//!
+//! Verified packets are dispatched to banking threads ("lanes") by the accounts their
+//! transactions write: transactions writing an account recently routed to a lane follow it
+//! there, so that hot accounts are only ever locked by one thread. EVM transactions all write
+//! the EVM state account, and are serialized into a dedicated lane, which other transactions
+//! only borrow while it is idle. Packets are dispatched in priority order, and within a lane
+//! transactions are packed into batches whose account locks do not conflict.
+//!
+//! Deserializing packets dominates the cost of the scheduler, so it runs as several shards
+//! pulling from the same receiver and sharing the account affinities.
+
+use crate::banking_stage::BankingStage;
+use crossbeam_channel::{
+    Receiver as CrossbeamReceiver, RecvTimeoutError, Sender as CrossbeamSender,
+};
+use solana_metrics::datapoint_info;
+use solana_perf::packet::{limited_deserialize, Packet, Packets};
+use solana_runtime::hashed_transaction::HashedTransaction;
+use solana_sdk::{message::Message, pubkey::Pubkey, transaction::VersionedTransaction};
+use std::{
+    cmp,
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    thread::{Builder, JoinHandle},
+    time::{Duration, Instant},
+};
+
+// Affinities of accounts to lanes are forgotten after this long, so that lanes rebalance
+const ACCOUNT_LANES_RESET_INTERVAL: Duration = Duration::from_millis(400);
+// Bounds the memory held by account affinities within a reset interval
+const MAX_ACCOUNT_LANES: usize = 100_000;
+const RECV_TIMEOUT: Duration = Duration::from_millis(100);
+const STATS_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Accounts locked by a batch of transactions
+#[derive(Default)]
+struct AccountLocks {
+    writable: HashSet<Pubkey>,
+    readonly: HashSet<Pubkey>,
+}
+
+impl AccountLocks {
+    /// Adds the locks of a transaction unless they conflict with the ones already held,
+    /// returning whether they were added
+    fn try_lock(&mut self, writable_keys: &[&Pubkey], readonly_keys: &[&Pubkey]) -> bool {
+        let conflicts = writable_keys
+            .iter()
+            .any(|key| self.writable.contains(key) || self.readonly.contains(key))
+            || readonly_keys.iter().any(|key| self.writable.contains(key));
+        if conflicts {
+            return false;
+        }
+        self.writable.extend(writable_keys.iter().copied().copied());
+        self.readonly.extend(readonly_keys.iter().copied().copied());
+        true
+    }
+}
+
+/// Packs `transactions`, in priority order, into batches of at most `max_batch_size` whose
+/// account locks do not conflict. Each transaction goes into the first batch it fits, so every
+/// batch keeps the priority order and earlier batches hold the higher priority transactions.
+/// Returns the indexes of the transactions of each batch.
+pub fn conflict_free_batches(
+    transactions: &[HashedTransaction],
+    max_batch_size: usize,
+    demote_sysvar_write_locks: bool,
+) -> Vec<Vec<usize>> {
+    let mut batches: Vec<(AccountLocks, Vec<usize>)> = vec![];
+    for (index, transaction) in transactions.iter().enumerate() {
+        let (writable_keys, readonly_keys) = transaction
+            .transaction()
+            .message()
+            .get_account_keys_by_lock_type(demote_sysvar_write_locks);
+        let batch = batches.iter_mut().position(|(locks, batch)| {
+            batch.len() < max_batch_size && locks.try_lock(&writable_keys, &readonly_keys)
+        });
+        match batch {
+            Some(batch) => batches[batch].1.push(index),
+            None => {
+                let mut locks = AccountLocks::default();
+                // Transactions conflicting with themselves, e.g. with duplicate keys, are left
+                // for the bank to reject
+                locks.try_lock(&writable_keys, &readonly_keys);
+                batches.push((locks, vec![index]));
+            }
+        }
+    }
+    batches.into_iter().map(|(_, batch)| batch).collect()
+}
+
+#[derive(Default)]
+struct SchedulerStats {
+    num_packets: usize,
+    num_dropped: usize,
+    num_evm: usize,
+    num_affinity_hits: usize,
+    num_affinity_conflicts: usize,
+}
+
+/// Lanes of the accounts written by recently dispatched transactions, shared by the shards
+struct AccountLanes {
+    lanes: HashMap<Pubkey, usize>,
+    last_reset: Instant,
+}
+
+impl Default for AccountLanes {
+    fn default() -> Self {
+        Self {
+            lanes: HashMap::new(),
+            last_reset: Instant::now(),
+        }
+    }
+}
+
+/// Dispatches packets to banking lanes by the accounts their transactions write
+pub struct TransactionScheduler {
+    lanes: Vec<CrossbeamSender<Vec<Packets>>>,
+    evm_lane: Option<usize>,
+    account_lanes: Arc<Mutex<AccountLanes>>,
+    stats: SchedulerStats,
+    last_report: Instant,
+}
+
+impl TransactionScheduler {
+    /// With more than one lane, the first one is dedicated to EVM transactions
+    pub fn new(lanes: Vec<CrossbeamSender<Vec<Packets>>>) -> Self {
+        assert!(!lanes.is_empty());
+        let evm_lane = if lanes.len() > 1 { Some(0) } else { None };
+        Self {
+            lanes,
+            evm_lane,
+            account_lanes: Arc::default(),
+            stats: SchedulerStats::default(),
+            last_report: Instant::now(),
+        }
+    }
+
+    /// Another scheduler dispatching to the same lanes with the same account affinities
+    fn shard(&self) -> Self {
+        Self {
+            lanes: self.lanes.clone(),
+            evm_lane: self.evm_lane,
+            account_lanes: self.account_lanes.clone(),
+            stats: SchedulerStats::default(),
+            last_report: Instant::now(),
+        }
+    }
+
+    /// Dispatches the packets of `verified_receiver` with `num_shards` threads, until it, or
+    /// any of the lanes, disconnects
+    pub fn spawn(
+        self,
+        verified_receiver: CrossbeamReceiver<Vec<Packets>>,
+        num_shards: usize,
+    ) -> Vec<JoinHandle<()>> {
+        assert!(num_shards > 0);
+        (0..num_shards)
+            .map(|shard| {
+                let mut scheduler = self.shard();
+                let verified_receiver = verified_receiver.clone();
+                Builder::new()
+                    .name(format!("solana-banking-scheduler-{}", shard))
+                    .spawn(move || loop {
+                        match verified_receiver.recv_timeout(RECV_TIMEOUT) {
+                            Ok(mut batches) => {
+                                batches.extend(verified_receiver.try_iter().flatten());
+                                if !scheduler.dispatch(batches) {
+                                    break;
+                                }
+                            }
+                            Err(RecvTimeoutError::Timeout) => (),
+                            Err(RecvTimeoutError::Disconnected) => break,
+                        }
+                    })
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    /// Routes the verified packets of `batches` to lanes, highest compute unit price first,
+    /// returning false once the lanes are disconnected
+    pub fn dispatch(&mut self, batches: Vec<Packets>) -> bool {
+        // Deserialized outside of the account lanes lock, so that shards do it in parallel
+        let mut transactions: Vec<(u64, Message, &Packet)> = batches
+            .iter()
+            .flat_map(|batch| batch.packets.iter())
+            .filter(|packet| !packet.meta.discard)
+            .filter_map(|packet| {
+                self.stats.num_packets += 1;
+                match limited_deserialize::<VersionedTransaction>(&packet.data[0..packet.meta.size])
+                {
+                    // Accounts loaded from lookup tables are not known without a bank, so
+                    // versioned messages are routed by the accounts they list inline
+                    Ok(tx) => {
+                        let message = BankingStage::static_message(tx.message);
+                        Some((BankingStage::compute_unit_price(&message), message, packet))
+                    }
+                    Err(_) => {
+                        self.stats.num_dropped += 1;
+                        None
+                    }
+                }
+            })
+            .collect();
+        // Stable, so that packets with the same price keep their arrival order
+        transactions.sort_by_key(|(price, _, _)| cmp::Reverse(*price));
+
+        let mut lane_packets: Vec<Vec<Packet>> = vec![vec![]; self.lanes.len()];
+        {
+            let account_lanes = self.account_lanes.clone();
+            let mut account_lanes = account_lanes.lock().unwrap();
+            if account_lanes.last_reset.elapsed() >= ACCOUNT_LANES_RESET_INTERVAL
+                || account_lanes.lanes.len() >= MAX_ACCOUNT_LANES
+            {
+                *account_lanes = AccountLanes::default();
+            }
+            let has_evm = transactions
+                .iter()
+                .any(|(_, message, _)| message.is_modify_evm_state());
+            for (_, message, packet) in transactions {
+                let lane = self.route(&mut account_lanes.lanes, &message, &lane_packets, has_evm);
+                lane_packets[lane].push(packet.clone());
+            }
+        }
+
+        for (lane, packets) in self.lanes.iter().zip(lane_packets) {
+            if !packets.is_empty() && lane.send(vec![Packets::new(packets)]).is_err() {
+                return false;
+            }
+        }
+        self.report();
+        true
+    }
+
+    fn route(
+        &mut self,
+        account_lanes: &mut HashMap<Pubkey, usize>,
+        message: &Message,
+        lane_packets: &[Vec<Packet>],
+        has_evm: bool,
+    ) -> usize {
+        // Sysvar and builtin program write locks are always demoted by the bank once the
+        // feature is active, so they make no affinities
+        let (writable_keys, _) = message.get_account_keys_by_lock_type(true);
+        let evm_lane = if message.is_modify_evm_state() {
+            self.stats.num_evm += 1;
+            self.evm_lane
+        } else {
+            None
+        };
+        let lane = match evm_lane {
+            Some(evm_lane) => evm_lane,
+            None => {
+                let mut lanes = writable_keys
+                    .iter()
+                    .filter_map(|key| account_lanes.get(*key).copied());
+                match lanes.next() {
+                    Some(lane) => {
+                        self.stats.num_affinity_hits += 1;
+                        if lanes.any(|other_lane| other_lane != lane) {
+                            // Writes accounts hot on different lanes, whichever lane it goes
+                            // to may contend for locks
+                            self.stats.num_affinity_conflicts += 1;
+                        }
+                        lane
+                    }
+                    None => self.least_loaded_lane(lane_packets, has_evm),
+                }
+            }
+        };
+        // The EVM state account is written by every EVM transaction and already has its lane
+        for key in writable_keys
+            .into_iter()
+            .filter(|key| **key != solana_sdk::evm_state::id())
+        {
+            account_lanes.entry(*key).or_insert(lane);
+        }
+        lane
+    }
+
+    /// The lane with the fewest batches still queued, and then the fewest packets routed to it
+    /// so far. The EVM lane is only a candidate while idle: nothing queued to it, and no EVM
+    /// transactions among the packets being dispatched.
+    fn least_loaded_lane(&self, lane_packets: &[Vec<Packet>], has_evm: bool) -> usize {
+        (0..self.lanes.len())
+            .filter(|lane| match self.evm_lane {
+                Some(evm_lane) if *lane == evm_lane => !has_evm && self.lanes[*lane].is_empty(),
+                _ => true,
+            })
+            .min_by_key(|lane| (self.lanes[*lane].len(), lane_packets[*lane].len()))
+            .unwrap()
+    }
+
+    fn report(&mut self) {
+        if self.last_report.elapsed() < STATS_REPORT_INTERVAL {
+            return;
+        }
+        let stats = std::mem::take(&mut self.stats);
+        let num_account_lanes = self.account_lanes.lock().unwrap().lanes.len();
+        datapoint_info!(
+            "banking_stage-scheduler",
+            ("num_packets", stats.num_packets, i64),
+            ("num_dropped", stats.num_dropped, i64),
+            ("num_evm", stats.num_evm, i64),
+            ("num_affinity_hits", stats.num_affinity_hits, i64),
+            ("num_affinity_conflicts", stats.num_affinity_conflicts, i64),
+            ("num_account_lanes", num_account_lanes, i64),
+        );
+        self.last_report = Instant::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossbeam_channel::unbounded;
+    use solana_perf::packet::to_packets;
+    use solana_sdk::{
+        compute_budget::ComputeBudgetInstruction,
+        instruction::{AccountMeta, Instruction},
+        transaction::Transaction,
+    };
+
+    fn transaction(writable: &[Pubkey], readonly: &[Pubkey]) -> Transaction {
+        transaction_with_price(writable, readonly, 0)
+    }
+
+    fn transaction_with_price(
+        writable: &[Pubkey],
+        readonly: &[Pubkey],
+        compute_unit_price: u64,
+    ) -> Transaction {
+        let accounts = writable
+            .iter()
+            .map(|key| AccountMeta::new(*key, false))
+            .chain(
+                readonly
+                    .iter()
+                    .map(|key| AccountMeta::new_readonly(*key, false)),
+            )
+            .collect();
+        let instructions = [
+            ComputeBudgetInstruction::set_compute_unit_price(compute_unit_price),
+            Instruction::new_with_bytes(Pubkey::new_unique(), &[], accounts),
+        ];
+        Transaction::new_unsigned(Message::new(&instructions, Some(&Pubkey::new_unique())))
+    }
+
+    fn lane_transactions(receivers: &[CrossbeamReceiver<Vec<Packets>>]) -> Vec<Vec<Transaction>> {
+        receivers
+            .iter()
+            .map(|receiver| {
+                receiver
+                    .try_iter()
+                    .flatten()
+                    .flat_map(|batch| batch.packets.into_iter())
+                    .map(|packet| limited_deserialize(&packet.data[0..packet.meta.size]).unwrap())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_conflict_free_batches() {
+        let (x, y, z, w) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let transactions: Vec<HashedTransaction> = vec![
+            transaction(&[x], &[]),
+            transaction(&[x], &[]),
+            transaction(&[], &[x]),
+            transaction(&[y], &[z]),
+            transaction(&[], &[z]),
+            transaction(&[w], &[]),
+        ]
+        .into_iter()
+        .map(HashedTransaction::from)
+        .collect();
+
+        assert_eq!(
+            conflict_free_batches(&transactions, 3, true),
+            vec![vec![0, 3, 4], vec![1, 5], vec![2]]
+        );
+        assert_eq!(
+            conflict_free_batches(&transactions, 128, true),
+            vec![vec![0, 3, 4, 5], vec![1], vec![2]]
+        );
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let (senders, receivers): (Vec<_>, Vec<_>) = (0..3).map(|_| unbounded()).unzip();
+        let mut scheduler = TransactionScheduler::new(senders);
+        let (x, y) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let transactions = vec![
+            transaction(&[x], &[]),
+            transaction(&[solana_sdk::evm_state::id()], &[]),
+            transaction(&[y], &[x]),
+            transaction(&[x], &[]),
+        ];
+        let mut batches = to_packets(&transactions);
+        let mut discarded = to_packets(&[transaction(&[y], &[])]);
+        discarded[0].packets[0].meta.discard = true;
+        batches.extend(discarded);
+        assert!(scheduler.dispatch(batches));
+
+        // EVM transactions get the first lane, and transactions writing the same accounts
+        // follow each other
+        assert_eq!(
+            lane_transactions(&receivers),
+            vec![
+                vec![transactions[1].clone()],
+                vec![transactions[0].clone(), transactions[3].clone()],
+                vec![transactions[2].clone()],
+            ]
+        );
+
+        drop(receivers);
+        assert!(!scheduler.dispatch(to_packets(&transactions)));
+    }
+
+    #[test]
+    fn test_dispatch_evm_lane() {
+        let (senders, receivers): (Vec<_>, Vec<_>) = (0..3).map(|_| unbounded()).unzip();
+        let mut scheduler = TransactionScheduler::new(senders);
+        let (x, y, z) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+
+        // The idle EVM lane takes other transactions too
+        let transactions = vec![transaction(&[x], &[]), transaction(&[y], &[])];
+        assert!(scheduler.dispatch(to_packets(&transactions)));
+        assert_eq!(
+            lane_transactions(&receivers),
+            vec![
+                vec![transactions[0].clone()],
+                vec![transactions[1].clone()],
+                vec![],
+            ]
+        );
+
+        // Not while EVM transactions are dispatched, and accounts written by EVM transactions
+        // follow them to the EVM lane
+        let transactions = vec![
+            transaction(&[z], &[]),
+            transaction(&[solana_sdk::evm_state::id(), y], &[]),
+            transaction(&[y], &[]),
+        ];
+        scheduler.account_lanes.lock().unwrap().lanes.clear();
+        assert!(scheduler.dispatch(to_packets(&transactions)));
+        assert_eq!(
+            lane_transactions(&receivers),
+            vec![
+                vec![transactions[1].clone(), transactions[2].clone()],
+                vec![transactions[0].clone()],
+                vec![],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dispatch_priority() {
+        let (senders, receivers): (Vec<_>, Vec<_>) = (0..2).map(|_| unbounded()).unzip();
+        let mut scheduler = TransactionScheduler::new(senders);
+        let x = Pubkey::new_unique();
+        let transactions = vec![
+            transaction_with_price(&[x], &[], 1),
+            transaction_with_price(&[x], &[], 5),
+            transaction_with_price(&[x], &[], 0),
+            transaction_with_price(&[x], &[], 5),
+        ];
+        assert!(scheduler.dispatch(to_packets(&transactions)));
+        assert_eq!(
+            lane_transactions(&receivers),
+            vec![
+                vec![
+                    transactions[1].clone(),
+                    transactions[3].clone(),
+                    transactions[0].clone(),
+                    transactions[2].clone(),
+                ],
+                vec![],
+            ]
+        );
+    }
+}
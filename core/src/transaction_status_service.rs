@@ -1,3 +1,4 @@
+use crate::accountsdb_plugin::TransactionNotifier;
 use crossbeam_channel::{Receiver, RecvTimeoutError};
 use itertools::izip;
 use solana_ledger::{
@@ -26,6 +27,8 @@ impl TransactionStatusService {
     pub fn new(
         write_transaction_status_receiver: Receiver<TransactionStatusMessage>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
+        enable_rpc_transaction_history: bool,
+        transaction_notifier: Option<TransactionNotifier>,
         blockstore: Arc<Blockstore>,
         exit: &Arc<AtomicBool>,
     ) -> Self {
@@ -39,6 +42,8 @@ impl TransactionStatusService {
                 if let Err(RecvTimeoutError::Disconnected) = Self::write_transaction_status_batch(
                     &write_transaction_status_receiver,
                     &max_complete_transaction_status_slot,
+                    enable_rpc_transaction_history,
+                    &transaction_notifier,
                     &blockstore,
                 ) {
                     break;
@@ -51,6 +56,8 @@ impl TransactionStatusService {
     fn write_transaction_status_batch(
         write_transaction_status_receiver: &Receiver<TransactionStatusMessage>,
         max_complete_transaction_status_slot: &Arc<AtomicU64>,
+        enable_rpc_transaction_history: bool,
+        transaction_notifier: &Option<TransactionNotifier>,
         blockstore: &Arc<Blockstore>,
     ) -> Result<(), RecvTimeoutError> {
         match write_transaction_status_receiver.recv_timeout(Duration::from_secs(1))? {
@@ -62,6 +69,7 @@ impl TransactionStatusService {
                 token_balances,
                 inner_instructions,
                 transaction_logs,
+                return_data,
                 rent_debits,
             }) => {
                 let slot = bank.slot();
@@ -87,6 +95,7 @@ impl TransactionStatusService {
                     post_token_balances,
                     inner_instructions,
                     log_messages,
+                    return_data,
                     rent_debits,
                 ) in izip!(
                     &transactions,
@@ -97,8 +106,11 @@ impl TransactionStatusService {
                     token_balances.post_token_balances,
                     inner_instructions_iter,
                     transaction_logs_iter,
+                    return_data.into_iter(),
                     rent_debits.into_iter(),
                 ) {
+                    let loaded_addresses = transaction.loaded_addresses().cloned();
+                    let transaction = transaction.transaction();
                     if Bank::can_commit(&status) && !transaction.signatures.is_empty() {
                         let fee_calculator = nonce_rollback
                             .map(|nonce_rollback| nonce_rollback.fee_calculator())
@@ -106,7 +118,9 @@ impl TransactionStatusService {
                                 bank.get_fee_calculator(&transaction.message().recent_blockhash)
                             })
                             .expect("FeeCalculator must exist");
-                        let fee = fee_calculator.calculate_fee(transaction.message());
+                        let fee = fee_calculator
+                            .calculate_fee(transaction.message())
+                            .saturating_add(bank.get_prioritization_fee(transaction.message()));
                         let (writable_keys, readonly_keys) = transaction
                             .message
                             .get_account_keys_by_lock_type(bank.demote_sysvar_write_locks());
@@ -139,25 +153,39 @@ impl TransactionStatusService {
                                 .collect(),
                         );
 
-                        blockstore
-                            .write_transaction_status(
+                        let transaction_status_meta = TransactionStatusMeta {
+                            status,
+                            fee,
+                            pre_balances,
+                            post_balances,
+                            inner_instructions,
+                            log_messages,
+                            pre_token_balances,
+                            post_token_balances,
+                            rewards,
+                            return_data,
+                            loaded_addresses,
+                        };
+
+                        if let Some(transaction_notifier) = transaction_notifier.as_ref() {
+                            transaction_notifier.read().unwrap().notify_transaction(
                                 slot,
-                                transaction.signatures[0],
-                                writable_keys,
-                                readonly_keys,
-                                TransactionStatusMeta {
-                                    status,
-                                    fee,
-                                    pre_balances,
-                                    post_balances,
-                                    inner_instructions,
-                                    log_messages,
-                                    pre_token_balances,
-                                    post_token_balances,
-                                    rewards,
-                                },
-                            )
-                            .expect("Expect database write to succeed");
+                                &transaction.signatures[0],
+                                &transaction_status_meta,
+                                transaction,
+                            );
+                        }
+                        if enable_rpc_transaction_history {
+                            blockstore
+                                .write_transaction_status(
+                                    slot,
+                                    transaction.signatures[0],
+                                    writable_keys,
+                                    readonly_keys,
+                                    transaction_status_meta,
+                                )
+                                .expect("Expect database write to succeed");
+                        }
                     }
                 }
             }
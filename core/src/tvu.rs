@@ -3,6 +3,7 @@
 
 use crate::{
     accounts_hash_verifier::AccountsHashVerifier,
+    accountsdb_plugin::SlotStatusNotifier,
     broadcast_stage::RetransmitSlotsSender,
     cache_block_meta_service::CacheBlockMetaSender,
     cluster_info::ClusterInfo,
@@ -129,6 +130,7 @@ impl Tvu {
         replay_vote_sender: ReplayVoteSender,
         completed_data_sets_sender: CompletedDataSetsSender,
         bank_notification_sender: Option<BankNotificationSender>,
+        slot_status_notifier: Option<SlotStatusNotifier>,
         gossip_confirmed_slots_receiver: GossipDuplicateConfirmedSlotsReceiver,
         tvu_config: TvuConfig,
         max_slots: &Arc<MaxSlots>,
@@ -268,6 +270,7 @@ impl Tvu {
             evm_block_recorder_sender,
             evm_state_recorder_sender,
             bank_notification_sender,
+            slot_status_notifier,
             wait_for_vote_to_start_leader: tvu_config.wait_for_vote_to_start_leader,
         };
 
@@ -434,6 +437,7 @@ pub mod tests {
             replay_vote_sender,
             completed_data_sets_sender,
             None,
+            None,
             gossip_confirmed_slots_receiver,
             TvuConfig::default(),
             &Arc::new(MaxSlots::default()),
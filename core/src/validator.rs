@@ -1,6 +1,7 @@
 //! The `validator` module hosts all the validator microservices.
 
 use crate::{
+    accountsdb_plugin::{AccountsDbPluginService, EvmBlockNotifier, TransactionNotifier},
     broadcast_stage::BroadcastStageType,
     cache_block_meta_service::{CacheBlockMetaSender, CacheBlockMetaService},
     cluster_info::{
@@ -13,6 +14,7 @@ use crate::{
     contact_info::ContactInfo,
     evm_services::{
         EvmRecorderSender, EvmRecorderService, EvmStateRecorderSender, EvmStateRecorderService,
+        EvmTokenIndexerService,
     },
     gossip_service::GossipService,
     max_slots::MaxSlots,
@@ -27,6 +29,7 @@ use crate::{
     rpc_service::JsonRpcService,
     rpc_subscriptions::RpcSubscriptions,
     sample_performance_service::SamplePerformanceService,
+    send_transaction_service,
     serve_repair::ServeRepair,
     serve_repair_service::ServeRepairService,
     sigverify,
@@ -125,6 +128,8 @@ pub struct ValidatorConfig {
     pub bpf_jit: bool,
     pub send_transaction_retry_ms: u64,
     pub send_transaction_leader_forward_count: u64,
+    pub send_transaction_default_max_retries: Option<usize>,
+    pub send_transaction_service_max_retries: usize,
     pub no_poh_speed_test: bool,
     pub poh_pinned_cpu_core: usize,
     pub poh_hashes_per_batch: u64,
@@ -137,6 +142,7 @@ pub struct ValidatorConfig {
     pub validator_exit: Arc<RwLock<ValidatorExit>>,
     pub no_wait_for_vote_to_start_leader: bool,
     pub verify_evm_state: bool,
+    pub accountsdb_plugin_config_files: Option<Vec<PathBuf>>,
     pub jaeger_collector_url: Option<String>,
 }
 
@@ -183,6 +189,8 @@ impl Default for ValidatorConfig {
             bpf_jit: false,
             send_transaction_retry_ms: 2000,
             send_transaction_leader_forward_count: 2,
+            send_transaction_default_max_retries: None,
+            send_transaction_service_max_retries: usize::MAX,
             no_poh_speed_test: true,
             poh_pinned_cpu_core: poh_service::DEFAULT_PINNED_CPU_CORE,
             poh_hashes_per_batch: poh_service::DEFAULT_HASHES_PER_BATCH,
@@ -195,6 +203,7 @@ impl Default for ValidatorConfig {
             validator_exit: Arc::new(RwLock::new(ValidatorExit::default())),
             no_wait_for_vote_to_start_leader: true,
             verify_evm_state: false,
+            accountsdb_plugin_config_files: None,
             jaeger_collector_url: None,
         }
     }
@@ -268,6 +277,7 @@ struct TransactionHistoryServices {
     evm_block_recorder_service: Option<EvmRecorderService>,
     evm_state_recorder_sender: Option<EvmStateRecorderSender>,
     evm_state_recorder_service: Option<EvmStateRecorderService>,
+    evm_token_indexer_service: Option<EvmTokenIndexerService>,
 }
 
 pub struct Validator {
@@ -281,6 +291,8 @@ pub struct Validator {
     sample_performance_service: Option<SamplePerformanceService>,
     evm_block_recorder_service: Option<EvmRecorderService>,
     evm_state_recorder_service: Option<EvmStateRecorderService>,
+    evm_token_indexer_service: Option<EvmTokenIndexerService>,
+    accountsdb_plugin_service: Option<AccountsDbPluginService>,
     gossip_service: GossipService,
     serve_repair_service: ServeRepairService,
     completed_data_sets_service: CompletedDataSetsService,
@@ -392,6 +404,22 @@ impl Validator {
                 .register_exit(Box::new(move || exit.store(true, Ordering::Relaxed)));
         }
 
+        let accountsdb_plugin_service =
+            if let Some(accountsdb_plugin_config_files) = &config.accountsdb_plugin_config_files {
+                match AccountsDbPluginService::new(accountsdb_plugin_config_files) {
+                    Ok(accountsdb_plugin_service) => Some(accountsdb_plugin_service),
+                    Err(err) => {
+                        error!("Failed to load the AccountsDb plugin: {:?}", err);
+                        abort();
+                    }
+                }
+            } else {
+                None
+            };
+        let slot_status_notifier = accountsdb_plugin_service
+            .as_ref()
+            .and_then(|service| service.get_slot_status_notifier());
+
         let (replay_vote_sender, replay_vote_receiver) = unbounded();
         let (
             genesis_config,
@@ -413,6 +441,7 @@ impl Validator {
                 evm_block_recorder_service,
                 evm_state_recorder_sender,
                 evm_state_recorder_service,
+                evm_token_indexer_service,
             },
             tower,
         ) = new_banks_from_ledger(
@@ -426,6 +455,7 @@ impl Validator {
             &start_progress,
             config.no_poh_speed_test,
             evm_state_archive.clone(),
+            accountsdb_plugin_service.as_ref(),
         );
 
         *start_progress.write().unwrap() = ValidatorStartProgress::StartingServices;
@@ -435,6 +465,13 @@ impl Validator {
         if let Some(ref shrink_paths) = config.account_shrink_paths {
             bank.set_shrink_paths(shrink_paths.clone());
         }
+        if let Some(accounts_update_notifier) = accountsdb_plugin_service
+            .as_ref()
+            .and_then(|service| service.get_accounts_update_notifier())
+        {
+            bank.set_accounts_update_notifier(accounts_update_notifier);
+            bank.notify_account_restore_from_snapshot();
+        }
         let bank_forks = Arc::new(RwLock::new(bank_forks));
 
         let sample_performance_service =
@@ -562,15 +599,18 @@ impl Validator {
                     block_commitment_cache.clone(),
                     blockstore.clone(),
                     cluster_info.clone(),
-                    Some(poh_recorder.clone()),
                     genesis_config.hash(),
                     ledger_path,
                     config.validator_exit.clone(),
                     config.trusted_validators.clone(),
                     rpc_override_health_check.clone(),
                     optimistically_confirmed_bank.clone(),
-                    config.send_transaction_retry_ms,
-                    config.send_transaction_leader_forward_count,
+                    send_transaction_service::Config {
+                        retry_rate_ms: config.send_transaction_retry_ms,
+                        leader_forward_count: config.send_transaction_leader_forward_count,
+                        default_max_retries: config.send_transaction_default_max_retries,
+                        service_max_retries: config.send_transaction_service_max_retries,
+                    },
                     max_slots.clone(),
                     leader_schedule_cache.clone(),
                     max_complete_transaction_status_slot,
@@ -593,6 +633,23 @@ impl Validator {
                     bank_forks.clone(),
                     optimistically_confirmed_bank,
                     subscriptions.clone(),
+                    slot_status_notifier.clone(),
+                )),
+                Some(bank_notification_sender),
+            )
+        } else if slot_status_notifier.is_some() {
+            // Plugins are notified of optimistically confirmed slots by the tracker
+            let (bank_notification_sender, bank_notification_receiver) = unbounded();
+            (
+                None,
+                None,
+                Some(OptimisticallyConfirmedBankTracker::new(
+                    bank_notification_receiver,
+                    &exit,
+                    bank_forks.clone(),
+                    optimistically_confirmed_bank,
+                    subscriptions.clone(),
+                    slot_status_notifier.clone(),
                 )),
                 Some(bank_notification_sender),
             )
@@ -749,6 +806,7 @@ impl Validator {
             replay_vote_sender.clone(),
             completed_data_sets_sender,
             bank_notification_sender.clone(),
+            slot_status_notifier,
             cluster_confirmed_slot_receiver,
             TvuConfig {
                 max_ledger_shreds: config.max_ledger_shreds,
@@ -775,6 +833,7 @@ impl Validator {
             retransmit_slots_receiver,
             node.sockets.tpu,
             node.sockets.tpu_forwards,
+            node.sockets.tpu_quic,
             node.sockets.broadcast,
             &subscriptions,
             transaction_status_sender,
@@ -809,6 +868,8 @@ impl Validator {
             completed_data_sets_service,
             evm_block_recorder_service,
             evm_state_recorder_service,
+            evm_token_indexer_service,
+            accountsdb_plugin_service,
             tpu,
             tvu,
             poh_recorder,
@@ -909,6 +970,12 @@ impl Validator {
                 .expect("evm_state_recorder_service");
         }
 
+        if let Some(evm_token_indexer_service) = self.evm_token_indexer_service {
+            evm_token_indexer_service
+                .join()
+                .expect("evm_token_indexer_service");
+        }
+
         if let Some(s) = self.snapshot_packager_service {
             s.join().expect("snapshot_packager_service");
         }
@@ -925,6 +992,12 @@ impl Validator {
         if let Some(ip_echo_server) = self.ip_echo_server {
             ip_echo_server.shutdown_background();
         }
+
+        if let Some(accountsdb_plugin_service) = self.accountsdb_plugin_service {
+            accountsdb_plugin_service
+                .join()
+                .expect("accountsdb_plugin_service");
+        }
     }
 }
 
@@ -1070,6 +1143,7 @@ fn new_banks_from_ledger(
     start_progress: &Arc<RwLock<ValidatorStartProgress>>,
     no_poh_speed_test: bool,
     evm_archive: Option<evm_state::Storage>,
+    accountsdb_plugin_service: Option<&AccountsDbPluginService>,
 ) -> (
     GenesisConfig,
     BankForks,
@@ -1159,17 +1233,29 @@ fn new_banks_from_ledger(
         ..blockstore_processor::ProcessOptions::default()
     };
 
-    let transaction_history_services =
-        if config.rpc_addrs.is_some() && config.rpc_config.enable_rpc_transaction_history {
-            initialize_rpc_transaction_history_services(
-                blockstore.clone(),
-                exit,
-                config.rpc_config.enable_cpi_and_log_storage,
-                evm_archive.clone(),
-            )
-        } else {
-            TransactionHistoryServices::default()
-        };
+    let enable_rpc_transaction_history =
+        config.rpc_addrs.is_some() && config.rpc_config.enable_rpc_transaction_history;
+    let transaction_notifier =
+        accountsdb_plugin_service.and_then(|service| service.get_transaction_notifier());
+    let evm_block_notifier =
+        accountsdb_plugin_service.and_then(|service| service.get_evm_block_notifier());
+    let transaction_history_services = if enable_rpc_transaction_history
+        || transaction_notifier.is_some()
+        || evm_block_notifier.is_some()
+    {
+        initialize_rpc_transaction_history_services(
+            blockstore.clone(),
+            exit,
+            enable_rpc_transaction_history,
+            config.rpc_config.enable_cpi_and_log_storage,
+            evm_archive.clone(),
+            config.rpc_config.enable_evm_token_index,
+            transaction_notifier,
+            evm_block_notifier,
+        )
+    } else {
+        TransactionHistoryServices::default()
+    };
 
     // TODO: Add evm-state to config.
     let evm_state_path = ledger_path.join("evm-state");
@@ -1367,24 +1453,67 @@ fn backup_and_clear_blockstore(ledger_path: &Path, start_slot: Slot, shred_versi
     drop(blockstore);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn initialize_rpc_transaction_history_services(
     blockstore: Arc<Blockstore>,
     exit: &Arc<AtomicBool>,
+    enable_rpc_transaction_history: bool,
     enable_cpi_and_log_storage: bool,
     archive_evm_state: Option<evm_state::Storage>,
+    enable_evm_token_index: bool,
+    transaction_notifier: Option<TransactionNotifier>,
+    evm_block_notifier: Option<EvmBlockNotifier>,
 ) -> TransactionHistoryServices {
     let max_complete_transaction_status_slot = Arc::new(AtomicU64::new(blockstore.max_root()));
-    let (transaction_status_sender, transaction_status_receiver) = unbounded();
-    let transaction_status_sender = Some(TransactionStatusSender {
-        sender: transaction_status_sender,
-        enable_cpi_and_log_storage,
-    });
-    let transaction_status_service = Some(TransactionStatusService::new(
-        transaction_status_receiver,
-        max_complete_transaction_status_slot.clone(),
-        blockstore.clone(),
-        exit,
-    ));
+    // Plugins may ask only for transactions or evm blocks, without storing history in the ledger
+    let (transaction_status_sender, transaction_status_service) =
+        if enable_rpc_transaction_history || transaction_notifier.is_some() {
+            let (transaction_status_sender, transaction_status_receiver) = unbounded();
+            (
+                Some(TransactionStatusSender {
+                    sender: transaction_status_sender,
+                    enable_cpi_and_log_storage,
+                }),
+                Some(TransactionStatusService::new(
+                    transaction_status_receiver,
+                    max_complete_transaction_status_slot.clone(),
+                    enable_rpc_transaction_history,
+                    transaction_notifier,
+                    blockstore.clone(),
+                    exit,
+                )),
+            )
+        } else {
+            (None, None)
+        };
+
+    let (evm_block_recorder_sender, evm_block_recorder_service) =
+        if enable_rpc_transaction_history || evm_block_notifier.is_some() {
+            let (evm_block_recorder_sender, evm_block_recorder_receiver) = unbounded();
+            (
+                Some(evm_block_recorder_sender),
+                Some(EvmRecorderService::new(
+                    evm_block_recorder_receiver,
+                    blockstore.clone(),
+                    enable_rpc_transaction_history,
+                    evm_block_notifier,
+                    exit,
+                )),
+            )
+        } else {
+            (None, None)
+        };
+
+    if !enable_rpc_transaction_history {
+        return TransactionHistoryServices {
+            transaction_status_sender,
+            transaction_status_service,
+            max_complete_transaction_status_slot,
+            evm_block_recorder_sender,
+            evm_block_recorder_service,
+            ..TransactionHistoryServices::default()
+        };
+    }
 
     let (rewards_recorder_sender, rewards_receiver) = unbounded();
     let rewards_recorder_sender = Some(rewards_recorder_sender);
@@ -1402,13 +1531,11 @@ fn initialize_rpc_transaction_history_services(
         exit,
     ));
 
-    let (evm_block_recorder_sender, evm_block_recorder_receiver) = unbounded();
-    let evm_block_recorder_sender = Some(evm_block_recorder_sender);
-    let evm_block_recorder_service = Some(EvmRecorderService::new(
-        evm_block_recorder_receiver,
-        blockstore,
-        exit,
-    ));
+    let evm_token_indexer_service = if enable_evm_token_index {
+        Some(EvmTokenIndexerService::new(blockstore, exit))
+    } else {
+        None
+    };
 
     let (evm_state_recorder_service, evm_state_recorder_sender) =
         if let Some(archive) = archive_evm_state {
@@ -1438,6 +1565,7 @@ fn initialize_rpc_transaction_history_services(
         evm_block_recorder_service,
         evm_state_recorder_sender,
         evm_state_recorder_service,
+        evm_token_indexer_service,
     }
 }
 
@@ -51,6 +51,7 @@ mod tests {
         bank::{Bank, BankSlotDelta},
         bank_forks::{ArchiveFormat, BankForks, SnapshotConfig},
         genesis_utils::{create_genesis_config, GenesisConfigInfo},
+        snapshot_package::SnapshotType,
         snapshot_utils,
         snapshot_utils::SnapshotVersion,
         status_cache::MAX_CACHE_ENTRIES,
@@ -124,6 +125,7 @@ mod tests {
 
             let snapshot_config = SnapshotConfig {
                 snapshot_interval_slots,
+                incremental_snapshot_interval_slots: std::u64::MAX,
                 snapshot_package_output_path: PathBuf::from(snapshot_output_path.path()),
                 snapshot_path: PathBuf::from(snapshot_dir.path()),
                 archive_format: ArchiveFormat::TarBzip2,
@@ -387,6 +389,7 @@ mod tests {
                 snapshot_config.snapshot_version,
                 &snapshot_config.archive_format,
                 None,
+                SnapshotType::Full,
             )
             .unwrap();
 
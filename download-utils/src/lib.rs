@@ -216,3 +216,55 @@ pub fn download_snapshot(
     }
     Err("Snapshot couldn't be downloaded".to_string())
 }
+
+/// Download the latest incremental snapshot served by `rpc_addr`, if it is
+/// based on the full snapshot at `base_slot`.
+pub fn download_incremental_snapshot(
+    rpc_addr: &SocketAddr,
+    snapshot_output_dir: &Path,
+    base_slot: Slot,
+    use_progress_bar: bool,
+) -> Result<(), String> {
+    // Node redirects to its latest incremental snapshot, resolve the archive name first
+    let response = reqwest::blocking::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .and_then(|client| {
+            client
+                .get(&format!("http://{}/incremental-snapshot.tar.bz2", rpc_addr))
+                .send()
+        })
+        .map_err(|err| err.to_string())?;
+    let archive_filename = response
+        .headers()
+        .get(reqwest::header::LOCATION)
+        .and_then(|location| location.to_str().ok())
+        .map(|location| location.trim_start_matches('/').to_string())
+        .ok_or_else(|| format!("No incremental snapshot available at {}", rpc_addr))?;
+
+    match snapshot_utils::incremental_snapshot_hash_of(&archive_filename) {
+        Some((archive_base_slot, ..)) if archive_base_slot == base_slot => {}
+        Some((archive_base_slot, ..)) => {
+            return Err(format!(
+                "Incremental snapshot {} is based on slot {}, expected {}",
+                archive_filename, archive_base_slot, base_slot
+            ))
+        }
+        None => {
+            return Err(format!(
+                "Invalid incremental snapshot archive name: {}",
+                archive_filename
+            ))
+        }
+    }
+
+    let desired_snapshot_package = snapshot_output_dir.join(&archive_filename);
+    if desired_snapshot_package.is_file() {
+        return Ok(());
+    }
+    download_file(
+        &format!("http://{}/{}", rpc_addr, archive_filename),
+        &desired_snapshot_package,
+        use_progress_bar,
+    )
+}
@@ -16,6 +16,7 @@ use std::{
 use evm_rpc::bridge::BridgeERPC;
 use evm_rpc::chain::ChainERPC;
 use evm_rpc::general::GeneralERPC;
+use evm_rpc::token::{RPCTokenBalances, RPCTokenTransfer, RPCTokenTransferCursor, TokenERPC};
 use evm_rpc::trace::TraceERPC;
 use evm_rpc::error::{Error, *};
 use evm_rpc::trace::TraceMeta;
@@ -33,8 +34,11 @@ use snafu::ResultExt;
 use derivative::*;
 use solana_evm_loader_program::scope::*;
 use solana_sdk::{
-    clock::MS_PER_TICK, fee_calculator::DEFAULT_TARGET_LAMPORTS_PER_SIGNATURE, pubkey::Pubkey,
-    signers::Signers, transaction::TransactionError,
+    clock::{MAX_PROCESSING_AGE, MS_PER_TICK},
+    fee_calculator::DEFAULT_TARGET_LAMPORTS_PER_SIGNATURE,
+    pubkey::Pubkey,
+    signers::Signers,
+    transaction::TransactionError,
 };
 
 use solana_client::{
@@ -66,8 +70,6 @@ use secp256k1::Message;
 use std::result::Result as StdResult;
 type EvmResult<T> = StdResult<T, evm_rpc::Error>;
 
-const MAX_NUM_BLOCKS_IN_BATCH: u64 = 2000; // should be less or equal to const core::evm_rpc_impl::logs::MAX_NUM_BLOCKS
-
 // A compatibility layer, to make software more fluently.
 mod compatibility {
     use evm_rpc::Hex;
@@ -195,12 +197,15 @@ pub struct EvmBridge {
     rpc_client: RpcClient,
     verbose_errors: bool,
     simulate: bool,
+    max_retries: Option<usize>,
     max_logs_blocks: u64,
+    logs_batch_size: u64,
     pool: EthPool<SystemClock>,
     min_gas_price: U256,
 }
 
 impl EvmBridge {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         evm_chain_id: u64,
         keypath: &str,
@@ -208,7 +213,9 @@ impl EvmBridge {
         addr: String,
         verbose_errors: bool,
         simulate: bool,
+        max_retries: Option<usize>,
         max_logs_blocks: u64,
+        logs_batch_size: u64,
         min_gas_price: U256,
     ) -> Self {
         info!("EVM chain id {}", evm_chain_id);
@@ -239,7 +246,9 @@ impl EvmBridge {
             rpc_client,
             verbose_errors,
             simulate,
+            max_retries,
             max_logs_blocks,
+            logs_batch_size,
             pool,
             min_gas_price,
         }
@@ -322,6 +331,20 @@ impl EvmBridge {
 
         is_receipt_exists(self, hash).or_else(|| is_signature_exists(self, hash))
     }
+
+    /// Whether the node still retries sending the native transaction of `hash` to the leaders,
+    /// which makes redeploying it pointless
+    pub fn is_transaction_queued(&self, hash: &H256) -> bool {
+        self.pool
+            .signature_of_cached_transaction(hash)
+            .and_then(|signature| {
+                self.rpc_client
+                    .get_send_transaction_status(&signature)
+                    .ok()
+                    .flatten()
+            })
+            .is_some()
+    }
 }
 
 #[derive(Debug)]
@@ -838,7 +861,7 @@ impl ChainERPC for ChainErpcProxy {
         Box::pin(async move {
             let mut collector = Vec::new();
             while starting <= ending_block {
-                let ending = (starting.saturating_add(MAX_NUM_BLOCKS_IN_BATCH)).min(ending_block);
+                let ending = (starting.saturating_add(meta.logs_batch_size)).min(ending_block);
                 log_filter.from_block = Some(starting.into());
                 log_filter.to_block = Some(ending.into());
 
@@ -854,7 +877,7 @@ impl ChainERPC for ChainErpcProxy {
                     result
                 }));
 
-                starting = starting.saturating_add(MAX_NUM_BLOCKS_IN_BATCH + 1);
+                starting = starting.saturating_add(meta.logs_batch_size + 1);
             }
             // join all execution, fast fail on any error.
             let mut result = Vec::new();
@@ -968,6 +991,40 @@ impl TraceERPC for TraceErpcProxy {
     }
 }
 
+#[derive(Debug)]
+pub struct TokenErpcProxy;
+impl TokenERPC for TokenErpcProxy {
+    type Metadata = Arc<EvmBridge>;
+
+    #[instrument]
+    fn token_transfers(
+        &self,
+        meta: Self::Metadata,
+        address: Hex<Address>,
+        token: Option<Hex<Address>>,
+        limit: Option<usize>,
+        before: Option<RPCTokenTransferCursor>,
+    ) -> EvmResult<Vec<RPCTokenTransfer>> {
+        proxy_evm_rpc!(
+            meta.rpc_client,
+            EvmGetTokenTransfers,
+            address,
+            token,
+            limit,
+            before
+        )
+    }
+
+    #[instrument]
+    fn token_balances(
+        &self,
+        meta: Self::Metadata,
+        address: Hex<Address>,
+    ) -> EvmResult<RPCTokenBalances> {
+        proxy_evm_rpc!(meta.rpc_client, EvmGetTokenBalances, address)
+    }
+}
+
 pub(crate) fn from_client_error(client_error: ClientError) -> evm_rpc::Error {
     let client_error_kind = client_error.kind();
     match client_error_kind {
@@ -1030,12 +1087,24 @@ struct Args {
     verbose_errors: bool,
     #[structopt(long = "no-simulate")]
     no_simulate: bool, // parse inverted to keep false default
+    /// How many times the node resends each deployed transaction to the leaders,
+    /// until its blockhash expires by default.
+    #[structopt(long = "max-retries")]
+    max_retries: Option<usize>,
     /// Maximum number of blocks to return in eth_getLogs rpc.
     #[structopt(long = "max-logs-block-count", default_value = "500")]
     max_logs_blocks: u64,
+    /// Number of blocks in a single eth_getLogs request to the node,
+    /// should not exceed `--rpc-max-evm-logs-block-range` of the node.
+    #[structopt(long = "logs-batch-size", default_value = "2000")]
+    logs_batch_size: u64,
 
     #[structopt(long = "jaeger-collector-url", short = "j")]
     jaeger_collector_url: Option<String>,
+
+    /// Serve the bridge metrics in Prometheus format on this address.
+    #[structopt(long = "prometheus-bind-address")]
+    prometheus_bind_address: Option<SocketAddr>,
 }
 
 impl Args {
@@ -1111,6 +1180,10 @@ async fn main(args: Args) -> StdResult<(), Box<dyn std::error::Error>> {
         registry.try_init().unwrap();
     }
 
+    if let Some(address) = args.prometheus_bind_address {
+        solana_metrics::prometheus::start_exporter(address)?;
+    }
+
     let meta = EvmBridge::new(
         args.evm_chain_id,
         &keyfile_path,
@@ -1118,7 +1191,9 @@ async fn main(args: Args) -> StdResult<(), Box<dyn std::error::Error>> {
         server_path,
         args.verbose_errors,
         !args.no_simulate, // invert argument
+        args.max_retries,
         args.max_logs_blocks,
+        args.logs_batch_size,
         min_gas_price,
     );
     let meta = Arc::new(meta);
@@ -1144,6 +1219,8 @@ async fn main(args: Args) -> StdResult<(), Box<dyn std::error::Error>> {
     io.extend_with(ether_general.to_delegate());
     let ether_trace = TraceErpcProxy;
     io.extend_with(ether_trace.to_delegate());
+    let ether_token = TokenErpcProxy;
+    io.extend_with(ether_token.to_delegate());
 
     let mempool_worker = worker_deploy(meta.clone());
 
@@ -1194,73 +1271,79 @@ async fn main(args: Args) -> StdResult<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Sends `transactions` and waits for them to be confirmed while their blockhash is valid, the
+/// node resends them to the leaders up to `max_retries` times meanwhile. Transactions that are
+/// still unconfirmed once the blockhash expires are re-signed with a new one and sent again.
 fn send_and_confirm_transactions<T: Signers>(
     rpc_client: &RpcClient,
     mut transactions: Vec<solana::Transaction>,
     signer_keys: &T,
+    max_retries: Option<usize>,
 ) -> StdResult<(), anyhow::Error> {
     const SEND_RETRIES: usize = 5;
-    const STATUS_RETRIES: usize = 15;
 
     for _ in 0..SEND_RETRIES {
-        // Send all transactions
-        let mut transactions_signatures = transactions
-            .drain(..)
-            .map(|transaction| {
-                if cfg!(not(test)) {
-                    // Delay ~1 tick between write transactions in an attempt to reduce AccountInUse errors
-                    // when all the write transactions modify the same program account (eg, deploying a
-                    // new program)
-                    sleep(Duration::from_millis(MS_PER_TICK));
-                }
+        // The transactions were signed with a blockhash fetched before this point, so it
+        // expires no later than this block height
+        let last_valid_block_height = rpc_client.get_block_height()? + MAX_PROCESSING_AGE as u64;
 
-                debug!("Sending {:?}", transaction.signatures);
-
-                let signature = rpc_client
-                    .send_transaction_with_config(
-                        &transaction,
-                        RpcSendTransactionConfig {
-                            skip_preflight: true, // NOTE: was true
-                            ..RpcSendTransactionConfig::default()
-                        },
-                    )
-                    .map_err(|e| error!("Send transaction error: {:?}", e))
-                    .ok();
+        // Send all transactions
+        for transaction in &transactions {
+            if cfg!(not(test)) {
+                // Delay ~1 tick between write transactions in an attempt to reduce AccountInUse errors
+                // when all the write transactions modify the same program account (eg, deploying a
+                // new program)
+                sleep(Duration::from_millis(MS_PER_TICK));
+            }
 
-                (transaction, signature)
-            })
-            .collect::<Vec<_>>();
+            debug!("Sending {:?}", transaction.signatures);
 
-        for _ in 0..STATUS_RETRIES {
-            // Collect statuses for all the transactions, drop those that are confirmed
+            if let Err(e) = rpc_client.send_transaction_with_config(
+                transaction,
+                RpcSendTransactionConfig {
+                    skip_preflight: true, // NOTE: was true
+                    max_retries,
+                    ..RpcSendTransactionConfig::default()
+                },
+            ) {
+                error!("Send transaction error: {:?}", e);
+            }
+        }
 
+        // Collect statuses for all the transactions, drop those that are confirmed, until the
+        // blockhash expires
+        loop {
             if cfg!(not(test)) {
                 // Retry twice a second
                 sleep(Duration::from_millis(500));
             }
 
-            transactions_signatures.retain(|(_transaction, signature)| {
-                signature
-                    .and_then(|signature| rpc_client.get_signature_statuses(&[signature]).ok())
+            transactions.retain(|transaction| {
+                rpc_client
+                    .get_signature_statuses(&transaction.signatures[..1])
+                    .ok()
                     .and_then(|RpcResponse { mut value, .. }| value.remove(0))
                     .and_then(|status| status.confirmations)
                     .map(|confirmations| confirmations == 0) // retain unconfirmed only
                     .unwrap_or(true)
             });
 
-            if transactions_signatures.is_empty() {
+            if transactions.is_empty() {
                 return Ok(());
             }
+
+            if rpc_client.get_block_height()? > last_valid_block_height {
+                break;
+            }
         }
 
         // Re-sign any failed transactions with a new blockhash and retry
-        let (blockhash, _) = rpc_client
-            .get_new_blockhash(&transactions_signatures[0].0.message().recent_blockhash)?;
+        let (blockhash, _) =
+            rpc_client.get_new_blockhash(&transactions[0].message().recent_blockhash)?;
 
-        for (mut transaction, _) in transactions_signatures {
+        for transaction in &mut transactions {
             transaction.try_sign(signer_keys, blockhash)?;
             debug!("Resending {:?}", transaction);
-            transactions.push(transaction);
         }
     }
     Err(anyhow::Error::msg("Transactions failed"))
@@ -1291,7 +1374,9 @@ mod tests {
             rpc_client: RpcClient::new("".to_string()),
             verbose_errors: true,
             simulate: false,
+            max_retries: None,
             max_logs_blocks: 0u64,
+            logs_batch_size: 0u64,
             pool: EthPool::new(SystemClock),
             min_gas_price: 0.into(),
         });
@@ -494,6 +494,13 @@ pub async fn worker_signature_checker(bridge: Arc<EvmBridge>) {
                 }
                 Some(false) | None => {
                     if now - generated > TX_REIMPORT_THRESHOLD.as_millis() as u64 {
+                        if bridge.is_transaction_queued(&hash) {
+                            debug!(
+                                "Transaction {} is still being retried by the node, redeploy postponed",
+                                &hash
+                            );
+                            continue;
+                        }
                         info!("Transaction {} needs to redeploy", &hash);
                         let evm_tx = bridge.pool.transaction_for_redeploy(&hash);
                         match evm_tx {
@@ -637,6 +644,7 @@ fn process_tx(
             RpcSendTransactionConfig {
                 preflight_commitment: Some(CommitmentLevel::Processed),
                 skip_preflight: !bridge.simulate,
+                max_retries: bridge.max_retries,
                 ..Default::default()
             },
         )
@@ -710,6 +718,7 @@ fn deploy_big_tx(
     let rpc_send_cfg = RpcSendTransactionConfig {
         skip_preflight: !bridge.simulate,
         preflight_commitment: Some(CommitmentLevel::Processed),
+        max_retries: bridge.max_retries,
         ..Default::default()
     };
 
@@ -762,12 +771,17 @@ fn deploy_big_tx(
 
     debug!("Write data txs: {:?}", write_data_txs);
 
-    send_and_confirm_transactions(&bridge.rpc_client, write_data_txs, &signers)
-        .map(|_| debug!("All write txs for storage {} was done", storage_pubkey))
-        .map_err(|e| {
-            error!("Error on write data to storage {}: {:?}", storage_pubkey, e);
-            into_native_error(e, bridge.verbose_errors)
-        })?;
+    send_and_confirm_transactions(
+        &bridge.rpc_client,
+        write_data_txs,
+        &signers,
+        bridge.max_retries,
+    )
+    .map(|_| debug!("All write txs for storage {} was done", storage_pubkey))
+    .map_err(|e| {
+        error!("Error on write data to storage {}: {:?}", storage_pubkey, e);
+        into_native_error(e, bridge.verbose_errors)
+    })?;
 
     let (blockhash, _, _) = bridge
         .rpc_client
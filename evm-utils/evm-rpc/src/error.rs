@@ -64,6 +64,19 @@ pub enum Error {
     #[snafu(display("Validator node didn't support archive history"))]
     ArchiveNotSupported,
 
+    #[snafu(display("Validator node didn't enable token transfers index"))]
+    TokenIndexNotEnabled,
+
+    #[snafu(display(
+        "Token transfers before block {} are not indexed, first indexed block = {}",
+        block,
+        first_indexed_block
+    ))]
+    TokenIndexRangeUnavailable {
+        block: u64,
+        first_indexed_block: u64,
+    },
+
     #[snafu(display("Failed to find archive state for block {}", block))]
     StateNotFoundForBlock { block: BlockId },
 
@@ -168,6 +181,8 @@ const FATAL_EVM_ERROR: i64 = 2004;
 const GAS_PRICE_TOO_LOW: i64 = 2005;
 const TRANSACTION_REPLACED: i64 = 2006;
 const ARCHIVE_NOT_SUPPORTED_ERROR: i64 = 2007;
+const TOKEN_INDEX_NOT_ENABLED_ERROR: i64 = 2008;
+const TOKEN_INDEX_RANGE_UNAVAILABLE_ERROR: i64 = 2009;
 
 const EVM_EXECUTION_ERROR: i64 = 3; // from geth docs
 const ERROR_EVM_BASE_SUBCODE: i64 = 100; //reserved place for evm errors range: 100 - 200
@@ -212,6 +227,10 @@ impl From<Error> for JRpcError {
             }
             Error::BlockNotFound { .. } => internal_error(BLOCK_NOT_FOUND_RPC_ERROR, &err),
             Error::ArchiveNotSupported => internal_error(ARCHIVE_NOT_SUPPORTED_ERROR, &err),
+            Error::TokenIndexNotEnabled => internal_error(TOKEN_INDEX_NOT_ENABLED_ERROR, &err),
+            Error::TokenIndexRangeUnavailable { .. } => {
+                internal_error(TOKEN_INDEX_RANGE_UNAVAILABLE_ERROR, &err)
+            }
             Error::StateNotFoundForBlock { .. } => internal_error(STATE_NOT_FOUND_RPC_ERROR, &err),
             Error::KeyNotFound { .. } => internal_error(KEY_NOT_FOUND_RPC_ERROR, &err),
             Error::Unimplemented {} => {
@@ -631,9 +631,90 @@ pub mod trace {
     }
 }
 
+pub mod token {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "UPPERCASE")]
+    pub enum RPCTokenStandard {
+        Erc20,
+        Erc721,
+    }
+
+    /// ERC-20 or ERC-721 transfer, `value` is token id for ERC-721.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RPCTokenTransfer {
+        pub block_number: Hex<u64>,
+        pub transaction_hash: Hex<H256>,
+        pub transaction_index: Hex<u64>,
+        pub log_index: Hex<u64>,
+        pub token: Hex<Address>,
+        pub standard: RPCTokenStandard,
+        pub from: Hex<Address>,
+        pub to: Hex<Address>,
+        pub value: Hex<U256>,
+    }
+
+    /// Location of a transfer, the one of the last returned transfer is used as `before`
+    /// cursor to get the next page of `evm_getTokenTransfers`.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RPCTokenTransferCursor {
+        pub block_number: Hex<u64>,
+        pub transaction_index: Hex<u64>,
+        pub log_index: Hex<u64>,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RPCTokenBalance {
+        pub token: Hex<Address>,
+        pub standard: RPCTokenStandard,
+        pub amount: Hex<U256>,
+        #[serde(skip_serializing_if = "Vec::is_empty", default)]
+        pub token_ids: Vec<Hex<U256>>,
+    }
+
+    /// Balances accumulated from transfers since `from_block`, they miss transfers of earlier
+    /// blocks unless it is the first evm block.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RPCTokenBalances {
+        pub from_block: Hex<u64>,
+        pub balances: Vec<RPCTokenBalance>,
+    }
+
+    #[rpc]
+    pub trait TokenERPC {
+        type Metadata;
+
+        /// Returns up to `limit` token transfers of `address` before the `before` transfer,
+        /// newest first.
+        #[rpc(meta, name = "evm_getTokenTransfers")]
+        fn token_transfers(
+            &self,
+            meta: Self::Metadata,
+            address: Hex<Address>,
+            token: Option<Hex<Address>>,
+            limit: Option<usize>,
+            before: Option<RPCTokenTransferCursor>,
+        ) -> Result<Vec<RPCTokenTransfer>, Error>;
+
+        /// Returns token holdings of `address`, accumulated from indexed transfers.
+        #[rpc(meta, name = "evm_getTokenBalances")]
+        fn token_balances(
+            &self,
+            meta: Self::Metadata,
+            address: Hex<Address>,
+        ) -> Result<RPCTokenBalances, Error>;
+    }
+}
+
 pub use bridge::BridgeERPC;
 pub use chain::ChainERPC;
 pub use general::GeneralERPC;
+pub use token::TokenERPC;
 pub use trace::TraceERPC;
 
 pub mod general {
@@ -334,6 +334,11 @@ impl EvmBackend<Incomming> {
         &self.kvs
     }
 
+    /// Account changes that wasn't flushed into kvs yet.
+    pub fn changed_state(&self) -> &ChangedState {
+        &self.state.state_updates
+    }
+
     fn state_updates(&self, address: &H160) -> Option<&(Maybe<AccountState>, HashMap<H256, H256>)> {
         self.state.state_updates.get(address)
     }
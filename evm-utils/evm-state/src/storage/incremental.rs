@@ -0,0 +1,277 @@
+//! Support for incremental snapshots of evm-state.
+//!
+//! A full snapshot remembers every trie node and code hash reachable from its root
+//! (`ReachableSet`). Incremental snapshots, taken later on top of that full snapshot,
+//! only carry nodes and codes that are reachable from the newer root but missing
+//! in the base (`TrieDiff`).
+
+use std::{collections::HashSet, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use log::*;
+use primitive_types::H256;
+use rlp::Rlp;
+use serde::{Deserialize, Serialize};
+use triedb::merkle::{MerkleNode, MerkleValue};
+
+use super::inspectors::{
+    encoding::SecTrie,
+    memorizer::{AccountStorageRootsCollector, TrieCollector},
+    NoopInspector,
+};
+use super::walker::Walker;
+use super::{Codes, Storage};
+use crate::types::{Account, Code};
+
+/// Sorted set of trie nodes and code hashes, reachable from some evm state root.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReachableSet {
+    pub root: H256,
+    nodes: Vec<H256>,
+    codes: Vec<H256>,
+}
+
+impl ReachableSet {
+    /// Walk accounts trie (and storage tries of every account) starting from `root`.
+    pub fn collect(storage: &Storage, root: H256) -> Result<Self> {
+        let trie_collector = Arc::new(TrieCollector::default());
+        let walker: Walker<_, _, SecTrie<_, H256, Account>> = Walker::new_sec_encoding(
+            storage.clone(),
+            trie_collector.clone(),
+            AccountStorageRootsCollector::default(),
+        );
+        walker.traverse(root)?;
+
+        let accounts = &walker.data_inspector.inner;
+        for storage_root in accounts.storage_roots.iter() {
+            Walker::new_raw(storage.clone(), trie_collector.clone(), NoopInspector)
+                .traverse(*storage_root)?;
+        }
+
+        let mut nodes: Vec<_> = trie_collector.trie_keys.iter().map(|key| *key).collect();
+        nodes.sort_unstable();
+        let empty_code_hash = Code::empty().hash();
+        let mut codes: Vec<_> = accounts
+            .code_hashes
+            .iter()
+            .map(|key| *key)
+            .filter(|code_hash| *code_hash != empty_code_hash)
+            .collect();
+        codes.sort_unstable();
+
+        debug!(
+            "Collected {} trie nodes and {} codes reachable from {:?}",
+            nodes.len(),
+            codes.len(),
+            root
+        );
+        Ok(Self { root, nodes, codes })
+    }
+
+    pub fn contains_node(&self, hash: H256) -> bool {
+        self.nodes.binary_search(&hash).is_ok()
+    }
+
+    pub fn contains_code(&self, code_hash: H256) -> bool {
+        self.codes.binary_search(&code_hash).is_ok()
+    }
+}
+
+/// Trie nodes and codes required to rebuild `root` on top of some base state.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrieDiff {
+    pub base_root: H256,
+    pub root: H256,
+    pub nodes: Vec<(H256, Vec<u8>)>,
+    pub codes: Vec<(H256, Code)>,
+}
+
+impl TrieDiff {
+    /// Collect everything reachable from `root` that is not part of `base`.
+    /// Subtries which root is known to `base` are skipped entirely.
+    pub fn collect(storage: &Storage, root: H256, base: &ReachableSet) -> Result<Self> {
+        let mut collector = DiffCollector {
+            storage,
+            base,
+            visited: HashSet::new(),
+            diff: TrieDiff {
+                base_root: base.root,
+                root,
+                ..Default::default()
+            },
+        };
+        collector.collect_trie(root, true)?;
+        info!(
+            "Collected evm-state diff {:?} -> {:?}: {} nodes, {} codes",
+            base.root,
+            root,
+            collector.diff.nodes.len(),
+            collector.diff.codes.len()
+        );
+        Ok(collector.diff)
+    }
+
+    /// Write nodes and codes into `storage`, without touching reference counters.
+    /// Caller is responsible to link `root` afterwards (e.g. by `copy_and_purge`).
+    pub fn apply(&self, storage: &Storage) -> Result<()> {
+        for (hash, node) in &self.nodes {
+            storage.db().put(hash, node)?;
+        }
+        for (code_hash, code) in &self.codes {
+            storage.set::<Codes>(*code_hash, code.clone());
+        }
+        Ok(())
+    }
+}
+
+struct DiffCollector<'a> {
+    storage: &'a Storage,
+    base: &'a ReachableSet,
+    visited: HashSet<H256>,
+    diff: TrieDiff,
+}
+
+impl<'a> DiffCollector<'a> {
+    fn collect_trie(&mut self, hash: H256, is_accounts_trie: bool) -> Result<()> {
+        if hash == triedb::empty_trie_hash()
+            || self.base.contains_node(hash)
+            || !self.visited.insert(hash)
+        {
+            return Ok(());
+        }
+        let bytes = self
+            .storage
+            .db()
+            .get(hash)?
+            .ok_or_else(|| anyhow!("hash {:?} not found in database", hash))?;
+
+        let rlp = Rlp::new(bytes.as_slice());
+        let node = MerkleNode::decode(&rlp)?;
+        self.collect_node(&node, is_accounts_trie)?;
+
+        self.diff.nodes.push((hash, bytes.to_vec()));
+        Ok(())
+    }
+
+    fn collect_node(&mut self, node: &MerkleNode, is_accounts_trie: bool) -> Result<()> {
+        match node {
+            MerkleNode::Leaf(_, data) => {
+                if is_accounts_trie {
+                    self.collect_account(data)?;
+                }
+                Ok(())
+            }
+            MerkleNode::Extension(_, value) => self.collect_value(value, is_accounts_trie),
+            MerkleNode::Branch(values, mb_data) => {
+                for value in values.iter() {
+                    self.collect_value(value, is_accounts_trie)?;
+                }
+                if let Some(data) = mb_data {
+                    if is_accounts_trie {
+                        self.collect_account(data)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn collect_value(&mut self, value: &MerkleValue, is_accounts_trie: bool) -> Result<()> {
+        match value {
+            MerkleValue::Empty => Ok(()),
+            MerkleValue::Full(node) => self.collect_node(node, is_accounts_trie),
+            MerkleValue::Hash(hash) => self.collect_trie(*hash, is_accounts_trie),
+        }
+    }
+
+    fn collect_account(&mut self, data: &[u8]) -> Result<()> {
+        let account: Account = rlp::decode(data)?;
+        self.collect_trie(account.storage_root, false)?;
+
+        let code_hash = account.code_hash;
+        if code_hash != Code::empty().hash()
+            && !self.base.contains_code(code_hash)
+            && self.visited.insert(code_hash)
+        {
+            let code = self
+                .storage
+                .get::<Codes>(code_hash)
+                .ok_or_else(|| anyhow!("Code data for {:?} is missed in storage", code_hash))?;
+            self.diff.codes.push((code_hash, code));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{
+        storage::copy_and_purge,
+        types::{AccountState, Maybe, H160, U256},
+        ChangedState,
+    };
+
+    fn account(balance: u64, code: Code) -> Maybe<AccountState> {
+        Maybe::Just(AccountState {
+            nonce: U256::zero(),
+            balance: balance.into(),
+            code,
+        })
+    }
+
+    #[test]
+    fn diff_contains_only_new_nodes() {
+        let storage = Storage::create_temporary().unwrap();
+
+        let mut changes = ChangedState::new();
+        for i in 0..16u64 {
+            changes.insert(
+                H160::from_low_u64_be(i),
+                (account(i, Code::empty()), HashMap::new()),
+            );
+        }
+        let base_root = storage.flush_changes(triedb::empty_trie_hash(), changes);
+        let base = ReachableSet::collect(&storage, base_root).unwrap();
+        assert!(base.contains_node(base_root));
+
+        let code = Code::from(vec![0x60, 0x00]);
+        let mut changes = ChangedState::new();
+        changes.insert(
+            H160::from_low_u64_be(1),
+            (
+                account(1, code.clone()),
+                vec![(H256::from_low_u64_be(1), H256::from_low_u64_be(2))]
+                    .into_iter()
+                    .collect(),
+            ),
+        );
+        let root = storage.flush_changes(base_root, changes);
+
+        let diff = TrieDiff::collect(&storage, root, &base).unwrap();
+        assert_eq!(diff.base_root, base_root);
+        assert_eq!(diff.root, root);
+        assert!(diff.nodes.iter().any(|(hash, _)| *hash == root));
+        assert!(diff
+            .nodes
+            .iter()
+            .all(|(hash, _)| !base.contains_node(*hash)));
+        assert_eq!(diff.codes, vec![(code.hash(), code.clone())]);
+
+        // base state with applied diff should be enough to restore new root
+        let target = Storage::create_temporary().unwrap();
+        copy_and_purge(storage.clone(), &[target.clone()], base_root).unwrap();
+        assert!(!target.check_root_exist(root));
+        diff.apply(&target).unwrap();
+        let restored = ReachableSet::collect(&target, root).unwrap();
+        assert!(restored.contains_code(code.hash()));
+
+        // nothing changed since root
+        let current = ReachableSet::collect(&storage, root).unwrap();
+        let empty = TrieDiff::collect(&storage, root, &current).unwrap();
+        assert!(empty.nodes.is_empty());
+        assert!(empty.codes.is_empty());
+    }
+}
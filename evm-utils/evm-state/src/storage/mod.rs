@@ -32,6 +32,7 @@ use triedb::{
     FixedSecureTrieMut,
 };
 
+pub mod incremental;
 pub mod inspectors;
 pub mod walker;
 
@@ -206,7 +206,7 @@ pub struct LogWithLocation {
     pub topics: Vec<H256>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct LogFilter {
     pub from_block: u64,
     pub to_block: u64,
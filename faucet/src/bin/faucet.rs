@@ -1,7 +1,10 @@
 use {
-    clap::{crate_description, crate_name, values_t, App, Arg},
+    clap::{crate_description, crate_name, value_t, values_t, App, Arg},
     log::*,
-    solana_clap_utils::input_parsers::{lamports_of_sol, value_of},
+    solana_clap_utils::{
+        input_parsers::{lamports_of_sol, value_of},
+        input_validators::is_parsable,
+    },
     solana_faucet::{
         faucet::{run_faucet, Faucet, FAUCET_PORT},
         socketaddr,
@@ -10,6 +13,7 @@ use {
     std::{
         collections::HashSet,
         net::{IpAddr, Ipv4Addr, SocketAddr},
+        process::exit,
         sync::{Arc, Mutex},
         thread,
     },
@@ -67,8 +71,26 @@ async fn main() {
                     recipient address will be used to check request limits instead",
                 ),
         )
+        .arg(
+            Arg::with_name("prometheus_bind_address")
+                .long("prometheus-bind-address")
+                .value_name("HOST:PORT")
+                .takes_value(true)
+                .validator(is_parsable::<SocketAddr>)
+                .help("Serve the faucet metrics in Prometheus format on this address"),
+        )
         .get_matches();
 
+    if let Ok(address) = value_t!(matches, "prometheus_bind_address", SocketAddr) {
+        if let Err(err) = solana_metrics::prometheus::start_exporter(address) {
+            eprintln!(
+                "Unable to start the Prometheus exporter on {}: {}",
+                address, err
+            );
+            exit(1);
+        }
+    }
+
     let faucet_keypair = read_keypair_file(matches.value_of("keypair").unwrap())
         .expect("failed to read client keypair");
 
@@ -2,7 +2,9 @@
 //! which is the custodian of any remaining lamports in a mint.
 //! The Solana Faucet builds and sends airdrop transactions,
 //! checking requests against a single-request cap and a per-IP limit
-//! for a given time time_slice.
+//! for a given time time_slice. Airdrops can target either a native
+//! address or an EVM address, the latter being funded with a native to
+//! EVM swap from the faucet account.
 
 use {
     bincode::{deserialize, serialize, serialized_size},
@@ -83,6 +85,28 @@ pub enum FaucetRequest {
         to: Pubkey,
         blockhash: Hash,
     },
+    GetEvmAirdrop {
+        lamports: u64,
+        to: evm_state::Address,
+        blockhash: Hash,
+    },
+}
+
+impl FaucetRequest {
+    /// Serialized size of the request variant identified by its leading bincode tag,
+    /// so that the faucet knows how many bytes to read for each request.
+    fn serialized_size_for_tag(tag: u32) -> Option<usize> {
+        let request = match tag {
+            0 => Self::default(),
+            1 => Self::GetEvmAirdrop {
+                lamports: u64::default(),
+                to: evm_state::Address::default(),
+                blockhash: Hash::default(),
+            },
+            _ => return None,
+        };
+        serialized_size(&request).ok().map(|size| size as usize)
+    }
 }
 
 impl Default for FaucetRequest {
@@ -104,6 +128,7 @@ pub struct Faucet {
     faucet_keypair: Keypair,
     ip_cache: HashMap<IpAddr, u64>,
     address_cache: HashMap<Pubkey, u64>,
+    evm_address_cache: HashMap<evm_state::Address, u64>,
     pub time_slice: Duration,
     per_time_cap: Option<u64>,
     per_request_cap: Option<u64>,
@@ -148,6 +173,7 @@ impl Faucet {
             faucet_keypair,
             ip_cache: HashMap::new(),
             address_cache: HashMap::new(),
+            evm_address_cache: HashMap::new(),
             time_slice,
             per_time_cap,
             per_request_cap,
@@ -178,69 +204,111 @@ impl Faucet {
     pub fn clear_caches(&mut self) {
         self.ip_cache.clear();
         self.address_cache.clear();
+        self.evm_address_cache.clear();
     }
 
     /// Checks per-request and per-time-ip limits; if both pass, this method returns a signed
-    /// SystemProgram::Transfer transaction from the faucet keypair to the requested recipient. If
-    /// the request exceeds this per-request limit, this method returns a signed SPL Memo
-    /// transaction with the memo: "request too large; req: <REQUEST> SOL cap: <CAP> SOL"
+    /// SystemProgram::Transfer transaction from the faucet keypair to the requested recipient, or
+    /// a signed native to EVM swap when the recipient is an EVM address. If the request exceeds
+    /// this per-request limit, this method returns a signed SPL Memo transaction with the memo:
+    /// "request too large; req: <REQUEST> SOL cap: <CAP> SOL"
     pub fn build_airdrop_transaction(
         &mut self,
         req: FaucetRequest,
         ip: IpAddr,
     ) -> Result<FaucetTransaction, FaucetError> {
         trace!("build_airdrop_transaction: {:?}", req);
+        let mint_pubkey = self.faucet_keypair.pubkey();
         match req {
             FaucetRequest::GetAirdrop {
                 lamports,
                 to,
                 blockhash,
             } => {
-                let mint_pubkey = self.faucet_keypair.pubkey();
                 info!(
                     "Requesting airdrop of {} SOL to {:?}",
                     lamports_to_sol(lamports),
                     to
                 );
 
-                if let Some(cap) = self.per_request_cap {
-                    if lamports > cap {
-                        let memo = format!(
-                            "{}",
-                            FaucetError::PerRequestCapExceeded(
-                                lamports_to_sol(lamports),
-                                lamports_to_sol(cap),
-                            )
-                        );
-                        let memo_instruction = Instruction {
-                            program_id: Pubkey::new(&spl_memo::id().to_bytes()),
-                            accounts: vec![],
-                            data: memo.as_bytes().to_vec(),
-                        };
-                        let message = Message::new(&[memo_instruction], Some(&mint_pubkey));
-                        return Ok(FaucetTransaction::Memo((
-                            Transaction::new(&[&self.faucet_keypair], message, blockhash),
-                            memo,
-                        )));
-                    }
-                }
-                if !ip.is_loopback() && !self.allowed_ips.contains(&ip) {
-                    self.check_time_request_limit(lamports, ip)?;
+                if let Some(memo_transaction) = self.check_request_cap(lamports, blockhash) {
+                    return Ok(memo_transaction);
                 }
+                self.check_ip_request_limit(lamports, ip)?;
                 self.check_time_request_limit(lamports, to)?;
 
                 let transfer_instruction =
                     system_instruction::transfer(&mint_pubkey, &to, lamports);
-                let message = Message::new(&[transfer_instruction], Some(&mint_pubkey));
-                Ok(FaucetTransaction::Airdrop(Transaction::new(
-                    &[&self.faucet_keypair],
-                    message,
-                    blockhash,
-                )))
+                Ok(self.airdrop_transaction(&[transfer_instruction], blockhash))
+            }
+            FaucetRequest::GetEvmAirdrop {
+                lamports,
+                to,
+                blockhash,
+            } => {
+                info!(
+                    "Requesting EVM airdrop of {} SOL to {:?}",
+                    lamports_to_sol(lamports),
+                    to
+                );
+
+                if let Some(memo_transaction) = self.check_request_cap(lamports, blockhash) {
+                    return Ok(memo_transaction);
+                }
+                self.check_ip_request_limit(lamports, ip)?;
+                self.check_time_request_limit(lamports, to)?;
+
+                let swap_instructions = solana_evm_loader_program::transfer_native_to_evm_ixs(
+                    mint_pubkey,
+                    lamports,
+                    to,
+                );
+                Ok(self.airdrop_transaction(&swap_instructions, blockhash))
             }
         }
     }
 
+    fn check_request_cap(&self, lamports: u64, blockhash: Hash) -> Option<FaucetTransaction> {
+        let cap = self.per_request_cap?;
+        if lamports <= cap {
+            return None;
+        }
+        let memo = format!(
+            "{}",
+            FaucetError::PerRequestCapExceeded(lamports_to_sol(lamports), lamports_to_sol(cap))
+        );
+        let memo_instruction = Instruction {
+            program_id: Pubkey::new(&spl_memo::id().to_bytes()),
+            accounts: vec![],
+            data: memo.as_bytes().to_vec(),
+        };
+        let message = Message::new(&[memo_instruction], Some(&self.faucet_keypair.pubkey()));
+        Some(FaucetTransaction::Memo((
+            Transaction::new(&[&self.faucet_keypair], message, blockhash),
+            memo,
+        )))
+    }
+
+    fn check_ip_request_limit(&mut self, lamports: u64, ip: IpAddr) -> Result<(), FaucetError> {
+        if !ip.is_loopback() && !self.allowed_ips.contains(&ip) {
+            self.check_time_request_limit(lamports, ip)?;
+        }
+        Ok(())
+    }
+
+    fn airdrop_transaction(
+        &self,
+        instructions: &[Instruction],
+        blockhash: Hash,
+    ) -> FaucetTransaction {
+        let message = Message::new(instructions, Some(&self.faucet_keypair.pubkey()));
+        FaucetTransaction::Airdrop(Transaction::new(
+            &[&self.faucet_keypair],
+            message,
+            blockhash,
+        ))
+    }
+
     /// Deserializes a received airdrop request, and returns a serialized transaction
     pub fn process_faucet_request(
         &mut self,
@@ -296,14 +364,40 @@ pub fn request_airdrop_transaction(
         faucet_addr, id, lamports, blockhash
     );
 
-    let mut stream = TcpStream::connect_timeout(faucet_addr, Duration::new(3, 0))?;
-    stream.set_read_timeout(Some(Duration::new(10, 0)))?;
     let req = FaucetRequest::GetAirdrop {
         lamports,
         blockhash,
         to: *id,
     };
-    let req = serialize(&req).expect("serialize faucet request");
+    send_faucet_request(faucet_addr, &req)
+}
+
+pub fn request_evm_airdrop_transaction(
+    faucet_addr: &SocketAddr,
+    address: &evm_state::Address,
+    lamports: u64,
+    blockhash: Hash,
+) -> Result<Transaction, FaucetError> {
+    info!(
+        "request_evm_airdrop_transaction: faucet_addr={} address={:?} lamports={} blockhash={}",
+        faucet_addr, address, lamports, blockhash
+    );
+
+    let req = FaucetRequest::GetEvmAirdrop {
+        lamports,
+        blockhash,
+        to: *address,
+    };
+    send_faucet_request(faucet_addr, &req)
+}
+
+fn send_faucet_request(
+    faucet_addr: &SocketAddr,
+    req: &FaucetRequest,
+) -> Result<Transaction, FaucetError> {
+    let mut stream = TcpStream::connect_timeout(faucet_addr, Duration::new(3, 0))?;
+    stream.set_read_timeout(Some(Duration::new(10, 0)))?;
+    let req = serialize(req).expect("serialize faucet request");
     stream.write_all(&req)?;
 
     // Read length of transaction
@@ -417,8 +511,20 @@ async fn process(
     mut stream: TokioTcpStream,
     faucet: Arc<Mutex<Faucet>>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut request = vec![0u8; serialized_size(&FaucetRequest::default()).unwrap() as usize];
-    while stream.read_exact(&mut request).await.is_ok() {
+    let mut tag = [0u8; 4];
+    while stream.read_exact(&mut tag).await.is_ok() {
+        let request_size =
+            match FaucetRequest::serialized_size_for_tag(LittleEndian::read_u32(&tag)) {
+                Some(request_size) => request_size,
+                None => {
+                    info!("Unknown faucet request: {:?}", tag);
+                    stream.write_all(&ERROR_RESPONSE).await?;
+                    break;
+                }
+            };
+        let mut request = tag.to_vec();
+        request.resize(request_size, 0);
+        stream.read_exact(&mut request[tag.len()..]).await?;
         trace!("{:?}", request);
 
         let response = {
@@ -493,6 +599,25 @@ impl LimitByTime for Pubkey {
     }
 }
 
+impl LimitByTime for evm_state::Address {
+    fn check_cache(&self, faucet: &mut Faucet, request_amount: u64) -> u64 {
+        *faucet
+            .evm_address_cache
+            .entry(*self)
+            .and_modify(|total| *total = total.saturating_add(request_amount))
+            .or_insert(request_amount)
+    }
+
+    fn datapoint_info(&self, request_amount: u64, new_total: u64) {
+        datapoint_info!(
+            "faucet-airdrop",
+            ("request_amount", request_amount, i64),
+            ("evm_address", format!("{:?}", self), String),
+            ("new_total", new_total, i64)
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -652,6 +777,73 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_faucet_build_evm_airdrop_transaction() {
+        let to = evm_state::Address::repeat_byte(0x11);
+        let blockhash = Hash::default();
+        let request = FaucetRequest::GetEvmAirdrop {
+            lamports: 2,
+            to,
+            blockhash,
+        };
+        let ip = socketaddr!([203, 0, 113, 1], 1234).ip();
+
+        let mint = Keypair::new();
+        let mint_pubkey = mint.pubkey();
+        let mut faucet = Faucet::new(mint, None, Some(2), None);
+
+        if let FaucetTransaction::Airdrop(tx) =
+            faucet.build_airdrop_transaction(request, ip).unwrap()
+        {
+            assert_eq!(tx.signatures.len(), 1);
+            let expected_message = Message::new(
+                &solana_evm_loader_program::transfer_native_to_evm_ixs(mint_pubkey, 2, to),
+                Some(&mint_pubkey),
+            );
+            assert_eq!(tx.message, expected_message);
+        } else {
+            panic!("evm airdrop should succeed");
+        }
+        assert_eq!(faucet.evm_address_cache.get(&to), Some(&2));
+
+        // Test per-time request cap is shared with native airdrops from the same ip
+        assert!(faucet.build_airdrop_transaction(request, ip).is_err());
+        let native_request = FaucetRequest::GetAirdrop {
+            lamports: 1,
+            to: Pubkey::new_unique(),
+            blockhash,
+        };
+        assert!(faucet
+            .build_airdrop_transaction(native_request, ip)
+            .is_err());
+
+        // Test per-request cap
+        let mut faucet = Faucet::new(Keypair::new(), None, None, Some(1));
+        assert!(matches!(
+            faucet.build_airdrop_transaction(request, ip).unwrap(),
+            FaucetTransaction::Memo(_)
+        ));
+    }
+
+    #[test]
+    fn test_faucet_request_serialized_size_for_tag() {
+        let native_request = FaucetRequest::default();
+        let evm_request = FaucetRequest::GetEvmAirdrop {
+            lamports: 42,
+            to: evm_state::Address::repeat_byte(0x22),
+            blockhash: Hash::new_unique(),
+        };
+        for (tag, request) in [native_request, evm_request].iter().enumerate() {
+            let bytes = serialize(request).unwrap();
+            assert_eq!(LittleEndian::read_u32(&bytes), tag as u32);
+            assert_eq!(
+                FaucetRequest::serialized_size_for_tag(tag as u32),
+                Some(bytes.len())
+            );
+        }
+        assert_eq!(FaucetRequest::serialized_size_for_tag(2), None);
+    }
+
     #[test]
     fn test_process_faucet_request() {
         let to = solana_sdk::pubkey::new_rand();
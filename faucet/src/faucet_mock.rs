@@ -25,3 +25,12 @@ pub fn request_airdrop_transaction(
         Ok(tx)
     }
 }
+
+pub fn request_evm_airdrop_transaction(
+    faucet_addr: &SocketAddr,
+    _address: &evm_state::Address,
+    lamports: u64,
+    blockhash: Hash,
+) -> Result<Transaction, Error> {
+    request_airdrop_transaction(faucet_addr, &Pubkey::default(), lamports, blockhash)
+}
@@ -393,12 +393,14 @@ fn main() -> Result<(), Box<dyn error::Error>> {
             Arg::with_name("evm-root")
                 .long("evm-root")
                 .takes_value(true)
-                .help("Root hash for evm state snapshot, Used to verify snapshot integrity."),
+                .help("Root hash for evm state snapshot, Used to verify snapshot integrity. \
+                       If omitted, root is computed from --evm-state-file."),
         ).arg(
             Arg::with_name("evm-state-file")
                 .long("evm-state-file")
                 .takes_value(true)
-                .help("Path to EVM state json file, can be retrived from `parity export state` command."),
+                .help("Path to EVM state json file, can be retrived from `parity export state` command. \
+                       Geth-style genesis.json is also accepted, accounts are taken from its `alloc` section."),
         ).arg(
             Arg::with_name("evm-chain-id")
                 .required(false)
@@ -554,14 +556,22 @@ fn main() -> Result<(), Box<dyn error::Error>> {
         ..GenesisConfig::default()
     };
 
+    let evm_state_json = matches.value_of("evm-state-file").map(PathBuf::from);
+
     if cfg!(feature = "with_evm") {
         let root = value_t!(matches, "evm-root", String);
-        match root {
-            Ok(root) => {
+        match (root, &evm_state_json) {
+            (Ok(root), _) => {
                 let root_hash = evm_rpc::Hex::<evm_state::H256>::from_hex(&root).unwrap();
                 genesis_config.set_evm_root_hash(root_hash.0)
             }
-            Err(e) => {
+            (Err(_), Some(evm_state_json)) => {
+                info!("Computing evm state root from {}", evm_state_json.display());
+                let root_hash = GenesisConfig::compute_evm_root_hash(evm_state_json)?;
+                println!("EVM state root: {:?}", root_hash);
+                genesis_config.set_evm_root_hash(root_hash)
+            }
+            (Err(e), None) => {
                 error!(
                     "EVM root was not found but genesis was compiled with `with_evm` feature {}",
                     e
@@ -570,8 +580,6 @@ fn main() -> Result<(), Box<dyn error::Error>> {
         }
     }
 
-    let evm_state_json = matches.value_of("evm-state-file").map(PathBuf::from);
-
     if let Ok(raw_inflation) = value_t!(matches, "inflation", String) {
         let inflation = match raw_inflation.as_str() {
             "pico" => Inflation::pico(),
@@ -2,7 +2,7 @@
 use clap::{value_t, value_t_or_exit, App, AppSettings, Arg, ArgMatches, SubCommand};
 use solana_clap_utils::{
     input_parsers::pubkey_of,
-    input_validators::{is_slot, is_valid_pubkey},
+    input_validators::{is_parsable, is_slot, is_valid_pubkey},
 };
 use solana_cli_output::{
     display::println_transaction, CliBlock, CliTransaction, CliTransactionConfirmation,
@@ -10,6 +10,7 @@ use solana_cli_output::{
 };
 use solana_ledger::{blockstore::Blockstore, blockstore_db::AccessType};
 use solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature};
+use solana_storage_bigtable::LedgerStorageConfig;
 use solana_transaction_status::{ConfirmedBlock, EncodedTransaction, UiTransactionEncoding};
 use std::{
     path::Path,
@@ -21,13 +22,14 @@ use std::{
 mod evm {
     use super::*;
     pub async fn upload(
+        config: LedgerStorageConfig,
         blockstore: Blockstore,
         starting_block: Slot,
         ending_block: Option<Slot>,
         push_not_confirmed: bool,
         force_reupload: bool,
     ) -> Result<Slot, Box<dyn std::error::Error>> {
-        let bigtable = solana_storage_bigtable::LedgerStorage::new(false, None)
+        let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config, false, None)
             .await
             .map_err(|err| format!("Failed to connect to storage: {:?}", err))?;
 
@@ -43,8 +45,11 @@ mod evm {
         .await
     }
 
-    pub async fn first_available_block() -> Result<(), Box<dyn std::error::Error>> {
-        let bigtable = solana_storage_bigtable::LedgerStorage::new(true, None).await?;
+    pub async fn first_available_block(
+        config: LedgerStorageConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let bigtable =
+            solana_storage_bigtable::LedgerStorage::new_with_config(config, true, None).await?;
         match bigtable.get_evm_first_available_block().await? {
             Some(block) => println!("{}", block),
             None => println!("No blocks available"),
@@ -53,8 +58,11 @@ mod evm {
         Ok(())
     }
 
-    pub async fn block(block_num: evm_state::BlockNum) -> Result<(), Box<dyn std::error::Error>> {
-        let bigtable = solana_storage_bigtable::LedgerStorage::new(false, None)
+    pub async fn block(
+        config: LedgerStorageConfig,
+        block_num: evm_state::BlockNum,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config, false, None)
             .await
             .map_err(|err| format!("Failed to connect to storage: {:?}", err))?;
 
@@ -65,10 +73,11 @@ mod evm {
     }
 
     pub async fn blocks(
+        config: LedgerStorageConfig,
         starting_slot: Slot,
         limit: usize,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let bigtable = solana_storage_bigtable::LedgerStorage::new(false, None)
+        let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config, false, None)
             .await
             .map_err(|err| format!("Failed to connect to storage: {:?}", err))?;
 
@@ -83,13 +92,14 @@ mod evm {
 }
 
 async fn upload(
+    config: LedgerStorageConfig,
     blockstore: Blockstore,
     starting_slot: Slot,
     ending_slot: Option<Slot>,
     allow_missing_metadata: bool,
     force_reupload: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new(false, None)
+    let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config, false, None)
         .await
         .map_err(|err| format!("Failed to connect to storage: {:?}", err))?;
 
@@ -105,8 +115,11 @@ async fn upload(
     .await
 }
 
-async fn first_available_block() -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new(true, None).await?;
+async fn first_available_block(
+    config: LedgerStorageConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let bigtable =
+        solana_storage_bigtable::LedgerStorage::new_with_config(config, true, None).await?;
     match bigtable.get_first_available_block().await? {
         Some(block) => println!("{}", block),
         None => println!("No blocks available"),
@@ -115,8 +128,12 @@ async fn first_available_block() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-async fn block(slot: Slot, output_format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new(false, None)
+async fn block(
+    config: LedgerStorageConfig,
+    slot: Slot,
+    output_format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config, false, None)
         .await
         .map_err(|err| format!("Failed to connect to storage: {:?}", err))?;
 
@@ -130,8 +147,12 @@ async fn block(slot: Slot, output_format: OutputFormat) -> Result<(), Box<dyn st
     Ok(())
 }
 
-async fn blocks(starting_slot: Slot, limit: usize) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new(false, None)
+async fn blocks(
+    config: LedgerStorageConfig,
+    starting_slot: Slot,
+    limit: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config, false, None)
         .await
         .map_err(|err| format!("Failed to connect to storage: {:?}", err))?;
 
@@ -143,11 +164,12 @@ async fn blocks(starting_slot: Slot, limit: usize) -> Result<(), Box<dyn std::er
 }
 
 async fn confirm(
+    config: LedgerStorageConfig,
     signature: &Signature,
     verbose: bool,
     output_format: OutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new(false, None)
+    let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config, false, None)
         .await
         .map_err(|err| format!("Failed to connect to storage: {:?}", err))?;
 
@@ -159,8 +181,9 @@ async fn confirm(
         match bigtable.get_confirmed_transaction(signature).await {
             Ok(Some(confirmed_transaction)) => {
                 transaction = Some(CliTransaction {
-                    transaction: EncodedTransaction::encode(
+                    transaction: EncodedTransaction::encode_versioned(
                         confirmed_transaction.transaction.transaction.clone(),
+                        None,
                         UiTransactionEncoding::Json,
                     ),
                     meta: confirmed_transaction.transaction.meta.map(|m| m.into()),
@@ -188,6 +211,7 @@ async fn confirm(
 }
 
 pub async fn transaction_history(
+    config: LedgerStorageConfig,
     address: &Pubkey,
     mut limit: usize,
     mut before: Option<Signature>,
@@ -196,7 +220,8 @@ pub async fn transaction_history(
     show_transactions: bool,
     query_chunk_size: usize,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new(true, None).await?;
+    let bigtable =
+        solana_storage_bigtable::LedgerStorage::new_with_config(config, true, None).await?;
 
     let mut loaded_block: Option<(Slot, ConfirmedBlock)> = None;
     while limit > 0 {
@@ -287,6 +312,20 @@ impl BigTableSubCommand for App<'_, '_> {
                 .about("Ledger data on a BigTable instance")
                 .setting(AppSettings::InferSubcommands)
                 .setting(AppSettings::SubcommandRequiredElseHelp)
+                .arg(
+                    Arg::with_name("storage")
+                        .long("storage")
+                        .value_name("LOCATION")
+                        .takes_value(true)
+                        .global(true)
+                        .validator(is_parsable::<LedgerStorageConfig>)
+                        .default_value("bigtable")
+                        .help(
+                            "Long-term ledger storage to use instead of BigTable: \
+                             local directory 'file://PATH' or S3 compatible storage \
+                             's3://BUCKET[/PREFIX]'",
+                        ),
+                )
                 .subcommand(
                     SubCommand::with_name("evm")
                     .about("Evm scope of bigtable ledger")
@@ -550,6 +589,8 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
             OutputFormat::Display
         });
 
+    let config = value_t_or_exit!(matches, "storage", LedgerStorageConfig);
+
     let future = match matches.subcommand() {
         ("upload", Some(arg_matches)) => {
             let starting_slot = value_t!(arg_matches, "starting_slot", Slot).unwrap_or(0);
@@ -560,6 +601,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 crate::open_blockstore(ledger_path, AccessType::TryPrimaryThenSecondary, None);
 
             runtime.block_on(upload(
+                config,
                 blockstore,
                 starting_slot,
                 ending_slot,
@@ -567,16 +609,18 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 force_reupload,
             ))
         }
-        ("first-available-block", Some(_arg_matches)) => runtime.block_on(first_available_block()),
+        ("first-available-block", Some(_arg_matches)) => {
+            runtime.block_on(first_available_block(config))
+        }
         ("block", Some(arg_matches)) => {
             let slot = value_t_or_exit!(arg_matches, "slot", Slot);
-            runtime.block_on(block(slot, output_format))
+            runtime.block_on(block(config, slot, output_format))
         }
         ("blocks", Some(arg_matches)) => {
             let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
             let limit = value_t_or_exit!(arg_matches, "limit", usize);
 
-            runtime.block_on(blocks(starting_slot, limit))
+            runtime.block_on(blocks(config, starting_slot, limit))
         }
         ("confirm", Some(arg_matches)) => {
             let signature = arg_matches
@@ -585,7 +629,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 .parse()
                 .expect("Invalid signature");
 
-            runtime.block_on(confirm(&signature, verbose, output_format))
+            runtime.block_on(confirm(config, &signature, verbose, output_format))
         }
         ("transaction-history", Some(arg_matches)) => {
             let address = pubkey_of(arg_matches, "address").unwrap();
@@ -600,6 +644,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
             let show_transactions = arg_matches.is_present("show_transactions");
 
             runtime.block_on(transaction_history(
+                config,
                 &address,
                 limit,
                 before,
@@ -620,6 +665,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
 
                 runtime
                     .block_on(evm::upload(
+                        config,
                         blockstore,
                         starting_block,
                         ending_block,
@@ -629,17 +675,17 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                     .map(drop)
             }
             ("first-available-block", Some(_arg_matches)) => {
-                runtime.block_on(evm::first_available_block())
+                runtime.block_on(evm::first_available_block(config))
             }
             ("block", Some(arg_matches)) => {
                 let block_num = value_t_or_exit!(arg_matches, "block", Slot);
-                runtime.block_on(evm::block(block_num))
+                runtime.block_on(evm::block(config, block_num))
             }
             ("blocks", Some(arg_matches)) => {
                 let starting_block = value_t_or_exit!(arg_matches, "starting_block", Slot);
                 let limit = value_t_or_exit!(arg_matches, "limit", usize);
 
-                runtime.block_on(evm::blocks(starting_block, limit))
+                runtime.block_on(evm::blocks(config, starting_block, limit))
             }
             _ => unreachable!(),
         },
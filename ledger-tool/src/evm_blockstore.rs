@@ -1,9 +1,18 @@
 /// The `bigtable` subcommand
-use clap::{value_t, value_t_or_exit, App, AppSettings, Arg, ArgMatches, SubCommand};
-use solana_clap_utils::input_validators::is_slot;
+use clap::{value_t, value_t_or_exit, values_t, App, AppSettings, Arg, ArgMatches, SubCommand};
+use solana_clap_utils::input_validators::{is_parsable, is_slot};
 
-use solana_ledger::{blockstore::Blockstore, blockstore_db::AccessType};
-use solana_sdk::clock::Slot;
+use solana_ledger::{
+    blockstore::Blockstore, blockstore_db::AccessType, blockstore_processor::ProcessOptions,
+};
+use solana_runtime::{
+    bank_forks::BankForks,
+    hardened_unpack::{open_genesis_config, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE},
+};
+use solana_sdk::{clock::Slot, genesis_config::GenesisConfig};
+use tempfile::TempDir;
+
+use crate::evm_replay::{replay_blocks, EvmFeatureActivations, FeatureActivation};
 use std::{path::Path, process::exit, result::Result};
 
 pub fn modify_block(
@@ -99,6 +108,38 @@ pub fn blocks(
     Ok(())
 }
 
+pub fn reindex_logs(
+    blockstore: Blockstore,
+    starting_block: evm_state::BlockNum,
+    ending_block: Option<evm_state::BlockNum>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut blocks = 0;
+    let mut logs = 0;
+    for ((block_num, _slot), header) in blockstore.evm_blocks_iterator(starting_block)? {
+        if ending_block
+            .map(|ending| block_num > ending)
+            .unwrap_or(false)
+        {
+            break;
+        }
+        for hash in &header.transactions {
+            let receipt = blockstore
+                .read_evm_transaction((*hash, block_num, Some(header.native_chain_slot)))?
+                .ok_or_else(|| {
+                    format!(
+                        "Transaction {:?} of block {} not found in blockstore",
+                        hash, block_num
+                    )
+                })?;
+            blockstore.write_evm_log_locations(block_num, header.native_chain_slot, &receipt)?;
+            logs += receipt.logs.len();
+        }
+        blocks += 1;
+    }
+    println!("Indexed {} logs in {} blocks", logs, blocks);
+    Ok(())
+}
+
 pub trait EvmBlockstoreSubcommand {
     fn evm_blockstore_subcommand(self) -> Self;
 }
@@ -193,11 +234,114 @@ impl EvmBlockstoreSubcommand for App<'_, '_> {
                                 .takes_value(false)
                                 .help("Do not Check next blocks parent_hash"),
                         ),
+                )
+                .subcommand(
+                    SubCommand::with_name("reindex-logs")
+                        .about("Rebuild evm log address and topic indexes of stored blocks")
+                        .arg(
+                            Arg::with_name("starting_block")
+                                .long("starting-block")
+                                .validator(is_slot)
+                                .value_name("BLOCK")
+                                .takes_value(true)
+                                .index(1)
+                                .default_value("0")
+                                .help("First block to index"),
+                        )
+                        .arg(
+                            Arg::with_name("ending_block")
+                                .long("ending-block")
+                                .validator(is_slot)
+                                .value_name("BLOCK")
+                                .takes_value(true)
+                                .index(2)
+                                .help("Last block to index [default: last available block]"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("replay")
+                        .about("Re-execute evm blocks and compare results with stored blocks")
+                        .after_help(
+                            "NOTE: Blocks are replayed into a temporary copy of the ledger \
+                             evm-state database, feature activations are read from the root bank.",
+                        )
+                        .arg(
+                            Arg::with_name("starting_block")
+                                .long("starting-block")
+                                .validator(is_slot)
+                                .value_name("BLOCK")
+                                .takes_value(true)
+                                .index(1)
+                                .required(true)
+                                .help("First block to replay"),
+                        )
+                        .arg(
+                            Arg::with_name("ending_block")
+                                .long("ending-block")
+                                .validator(is_slot)
+                                .value_name("BLOCK")
+                                .takes_value(true)
+                                .index(2)
+                                .help("Last block to replay [default: starting block]"),
+                        )
+                        .arg(
+                            Arg::with_name("chain_id")
+                                .long("chain-id")
+                                .validator(is_parsable::<u64>)
+                                .value_name("CHAIN_ID")
+                                .takes_value(true)
+                                .help("EVM chain id [default: chain id from genesis config]"),
+                        )
+                        .arg(
+                            Arg::with_name("feature_activation")
+                                .long("feature-activation")
+                                .validator(is_parsable::<FeatureActivation>)
+                                .value_name("FEATURE=SLOT")
+                                .takes_value(true)
+                                .multiple(true)
+                                .help(
+                                    "Override native slot where evm feature was activated, or `inactive`. \
+                                     Features that are not specified are activated as recorded in the root bank. \
+                                     Supported features: unsigned_tx_fix, clear_logs_on_error, \
+                                     native_swap_in_evm_history, evm_new_error_handling, \
+                                     ignore_reset_on_cleared, burn_fee",
+                                ),
+                        ),
                 ),
         )
     }
 }
 
+/// Loads the root bank, to read activations of features recorded in the ledger and the native
+/// accounts of precompiles. Its evm state is loaded into the returned temporary directory,
+/// leaving the ledger evm-state intact.
+fn load_root_bank(
+    arg_matches: &ArgMatches<'_>,
+    genesis_config: &GenesisConfig,
+    blockstore: &Blockstore,
+) -> Result<(BankForks, TempDir), Box<dyn std::error::Error>> {
+    let evm_state_dir = tempfile::Builder::new()
+        .prefix("evm-state.ledger-tool-")
+        .tempdir_in(blockstore.ledger_path())?;
+    let process_options = ProcessOptions {
+        dev_halt_at_slot: Some(0),
+        poh_verify: false,
+        ..ProcessOptions::default()
+    };
+    let (bank_forks, _leader_schedule_cache, _snapshot_hash) =
+        crate::load_bank_forks_with_evm_state(
+            arg_matches,
+            genesis_config,
+            blockstore,
+            process_options,
+            None,
+            false,
+            evm_state_dir.path(),
+        )
+        .map_err(|err| format!("Failed to load ledger: {:?}", err))?;
+    Ok((bank_forks, evm_state_dir))
+}
+
 pub fn evm_blockstore_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
     let blockstore = crate::open_blockstore(ledger_path, AccessType::TryPrimaryThenSecondary, None);
 
@@ -219,6 +363,50 @@ pub fn evm_blockstore_process_command(ledger_path: &Path, matches: &ArgMatches<'
                 skip_consistency_check,
             )
         }
+        ("replay", Some(arg_matches)) => {
+            let starting_block = value_t_or_exit!(arg_matches, "starting_block", Slot);
+            let ending_block =
+                value_t!(arg_matches, "ending_block", Slot).unwrap_or(starting_block);
+            let genesis_config =
+                open_genesis_config(ledger_path, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE);
+            let chain_id =
+                value_t!(arg_matches, "chain_id", u64).unwrap_or(genesis_config.evm_chain_id);
+
+            load_root_bank(arg_matches, &genesis_config, &blockstore).and_then(
+                |(bank_forks, evm_state_dir)| {
+                    let root_bank = bank_forks.root_bank();
+                    println!(
+                        "Using feature activations of root bank at slot {}, \
+                         features activated after it are treated as inactive",
+                        root_bank.slot()
+                    );
+                    let features = EvmFeatureActivations::new(
+                        &root_bank.feature_set,
+                        values_t!(arg_matches, "feature_activation", FeatureActivation)
+                            .unwrap_or_default(),
+                    );
+                    drop(root_bank);
+                    let result = replay_blocks(
+                        &blockstore,
+                        &bank_forks,
+                        &ledger_path.join("evm-state"),
+                        starting_block,
+                        ending_block,
+                        chain_id,
+                        features,
+                    );
+                    // Banks keep their evm state open until dropped
+                    drop(bank_forks);
+                    drop(evm_state_dir);
+                    result.map_err(Into::into)
+                },
+            )
+        }
+        ("reindex-logs", Some(arg_matches)) => {
+            let starting_block = value_t_or_exit!(arg_matches, "starting_block", Slot);
+            let ending_block = value_t!(arg_matches, "ending_block", Slot).ok();
+            reindex_logs(blockstore, starting_block, ending_block)
+        }
         ("first-available-block", Some(_arg_matches)) => first_available_block(blockstore),
         ("last-available-block", Some(_arg_matches)) => last_available_block(blockstore),
         ("block", Some(arg_matches)) => {
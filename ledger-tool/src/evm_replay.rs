@@ -0,0 +1,882 @@
+/// Offline re-execution of EVM blocks stored in blockstore.
+///
+/// Every block is executed on top of its parent state root (taken from the previous block header)
+/// and resulting receipts, logs and roots are compared with stored ones.
+/// Blocks are replayed into a copy of the ledger evm-state, the original one is never opened.
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashMap},
+    fmt, fs,
+    path::Path,
+    str::FromStr,
+};
+
+use anyhow::{anyhow, ensure, Result};
+use log::*;
+
+use evm_state::{
+    executor::FeatureSet, AccountState, Block, BlockHeader, BlockNum, BlockVersion, ChainContext,
+    Committed, Context, EvmBackend, EvmConfig, EvmState, Executor, PrecompileCallResult, Storage,
+    TransactionAction, TransactionInReceipt, TransactionReceipt, H160, H256,
+};
+use solana_evm_loader_program::{
+    precompiles::{self, ETH_TO_SOPHON_ADDR, ETH_TO_SOPHON_CODE},
+    processor::BURN_ADDR,
+};
+use solana_ledger::blockstore::Blockstore;
+use solana_runtime::bank_forks::BankForks;
+use solana_sdk::{
+    account::AccountSharedData, clock::Slot, feature_set, keyed_account::KeyedAccount,
+    pubkey::Pubkey,
+};
+use tempfile::TempDir;
+
+/// Native feature gates that changes the way evm transactions are executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum EvmFeature {
+    UnsignedTxFix,
+    ClearLogsOnError,
+    NativeSwapInEvmHistory,
+    EvmNewErrorHandling,
+    IgnoreResetOnCleared,
+    BurnFee,
+}
+
+impl EvmFeature {
+    pub const ALL: &'static [EvmFeature] = &[
+        EvmFeature::UnsignedTxFix,
+        EvmFeature::ClearLogsOnError,
+        EvmFeature::NativeSwapInEvmHistory,
+        EvmFeature::EvmNewErrorHandling,
+        EvmFeature::IgnoreResetOnCleared,
+        EvmFeature::BurnFee,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EvmFeature::UnsignedTxFix => "unsigned_tx_fix",
+            EvmFeature::ClearLogsOnError => "clear_logs_on_error",
+            EvmFeature::NativeSwapInEvmHistory => "native_swap_in_evm_history",
+            EvmFeature::EvmNewErrorHandling => "evm_new_error_handling",
+            EvmFeature::IgnoreResetOnCleared => "ignore_reset_on_cleared",
+            EvmFeature::BurnFee => "burn_fee",
+        }
+    }
+
+    pub fn id(&self) -> Pubkey {
+        match self {
+            EvmFeature::UnsignedTxFix => feature_set::sophon::unsigned_tx_fix::id(),
+            EvmFeature::ClearLogsOnError => feature_set::sophon::clear_logs_on_error::id(),
+            EvmFeature::NativeSwapInEvmHistory => {
+                feature_set::sophon::native_swap_in_evm_history::id()
+            }
+            EvmFeature::EvmNewErrorHandling => feature_set::sophon::evm_new_error_handling::id(),
+            EvmFeature::IgnoreResetOnCleared => feature_set::sophon::ignore_reset_on_cleared::id(),
+            EvmFeature::BurnFee => feature_set::sophon::burn_fee::id(),
+        }
+    }
+}
+
+impl FromStr for EvmFeature {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EvmFeature::ALL
+            .iter()
+            .find(|feature| feature.name() == s)
+            .copied()
+            .ok_or_else(|| format!("Unknown evm feature: {}", s))
+    }
+}
+
+/// Activation of single feature, parsed from `NAME=SLOT` or `NAME=inactive`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeatureActivation {
+    pub feature: EvmFeature,
+    pub slot: Option<Slot>,
+}
+
+impl FromStr for FeatureActivation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, slot) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Expected NAME=SLOT, got: {}", s))?;
+        let feature = name.parse()?;
+        let slot = match slot {
+            "inactive" => None,
+            slot => Some(
+                slot.parse()
+                    .map_err(|e| format!("Unable to parse slot {}: {}", slot, e))?,
+            ),
+        };
+        Ok(FeatureActivation { feature, slot })
+    }
+}
+
+/// Activation slots of evm features.
+#[derive(Debug, Clone)]
+pub struct EvmFeatureActivations {
+    activations: HashMap<EvmFeature, Option<Slot>>,
+}
+
+impl EvmFeatureActivations {
+    /// Activation slots recorded in the bank `feature_set`, with `overrides` on top of them.
+    pub fn new(
+        feature_set: &feature_set::FeatureSet,
+        overrides: impl IntoIterator<Item = FeatureActivation>,
+    ) -> Self {
+        let mut this = Self {
+            activations: EvmFeature::ALL
+                .iter()
+                .map(|feature| (*feature, feature_set.activated_slot(&feature.id())))
+                .collect(),
+        };
+        for FeatureActivation { feature, slot } in overrides {
+            this.activations.insert(feature, slot);
+        }
+        this
+    }
+
+    pub fn is_active(&self, feature: EvmFeature, slot: Slot) -> bool {
+        matches!(self.activations.get(&feature), Some(Some(activation)) if *activation <= slot)
+    }
+}
+
+/// Difference between replayed and stored data.
+#[derive(Debug)]
+pub enum Divergence {
+    /// Executor rejected transaction, that was recorded in block.
+    TransactionRejected {
+        index: usize,
+        tx_hash: H256,
+        error: String,
+    },
+    /// Receipt of replayed transaction differ from stored one.
+    Receipt {
+        index: usize,
+        tx_hash: H256,
+        stored: Box<TransactionReceipt>,
+        replayed: Box<TransactionReceipt>,
+    },
+    /// Block header fields differ, receipts of each transaction was equal.
+    Header {
+        fields: Vec<(&'static str, String, String)>,
+        accounts: Vec<AccountDiff>,
+    },
+}
+
+/// Account state at stored and replayed state roots.
+/// `None` in `stored` means that the stored root is not available in evm-state anymore.
+#[derive(Debug)]
+pub struct AccountDiff {
+    pub address: H160,
+    pub stored: Option<Option<AccountState>>,
+    pub replayed: Option<AccountState>,
+    pub storage: Vec<(H256, Option<H256>, Option<H256>)>,
+    /// Indexes of transactions that changed this account during replay.
+    pub changed_by: Vec<usize>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Divergence::TransactionRejected {
+                index,
+                tx_hash,
+                error,
+            } => writeln!(
+                f,
+                "Transaction #{} ({:?}) was rejected during replay: {}",
+                index, tx_hash, error
+            ),
+            Divergence::Receipt {
+                index,
+                tx_hash,
+                stored,
+                replayed,
+            } => {
+                writeln!(
+                    f,
+                    "Receipt mismatch in transaction #{} ({:?})",
+                    index, tx_hash
+                )?;
+                if stored.status != replayed.status {
+                    writeln!(
+                        f,
+                        "->Status: stored = {:?}, replayed = {:?}",
+                        stored.status, replayed.status
+                    )?;
+                }
+                if stored.used_gas != replayed.used_gas {
+                    writeln!(
+                        f,
+                        "->Used gas: stored = {}, replayed = {}",
+                        stored.used_gas, replayed.used_gas
+                    )?;
+                }
+                if stored.logs != replayed.logs {
+                    writeln!(f, "->Stored logs: {:?}", stored.logs)?;
+                    writeln!(f, "->Replayed logs: {:?}", replayed.logs)?;
+                }
+                Ok(())
+            }
+            Divergence::Header { fields, accounts } => {
+                writeln!(f, "Block header mismatch")?;
+                for (name, stored, replayed) in fields {
+                    writeln!(
+                        f,
+                        "->{}: stored = {}, replayed = {}",
+                        name, stored, replayed
+                    )?;
+                }
+                for account in accounts {
+                    write!(f, "{}", account)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for AccountDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn describe(state: &Option<AccountState>) -> String {
+            match state {
+                Some(state) => format!(
+                    "nonce = {}, balance = {}, code_hash = {:?}",
+                    state.nonce,
+                    state.balance,
+                    state.code.hash()
+                ),
+                None => "<none>".to_string(),
+            }
+        }
+        writeln!(
+            f,
+            "->Account {:?} (changed by transactions {:?})",
+            self.address, self.changed_by
+        )?;
+        match &self.stored {
+            Some(stored) => writeln!(f, "-->Stored: {}", describe(stored))?,
+            None => writeln!(f, "-->Stored: <root not found in evm-state>")?,
+        }
+        writeln!(f, "-->Replayed: {}", describe(&self.replayed))?;
+        for (index, stored, replayed) in &self.storage {
+            writeln!(
+                f,
+                "-->Storage {:?}: stored = {:?}, replayed = {:?}",
+                index, stored, replayed
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Copies evm-state database from `source` into `destination`, without opening it.
+/// Table files are never modified by rocksdb, so they are hard linked when possible.
+fn copy_evm_state(source: &Path, destination: &Path) -> Result<()> {
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        // Backups and custom locations are not part of the database
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let from = entry.path();
+        let to = destination.join(entry.file_name());
+        let linked = from
+            .extension()
+            .map_or(false, |extension| extension == "sst")
+            && fs::hard_link(&from, &to).is_ok();
+        if !linked {
+            fs::copy(&from, &to)?;
+        }
+    }
+    Ok(())
+}
+
+pub struct BlockReplayer<'a> {
+    blockstore: &'a Blockstore,
+    /// Native accounts read by precompiles.
+    bank_forks: &'a BankForks,
+    /// Copy of the ledger evm-state, replayed blocks are committed into it.
+    storage: Storage,
+    // Should be declared after `storage`, to be removed after the database is closed.
+    _storage_dir: TempDir,
+    chain_id: u64,
+    features: EvmFeatureActivations,
+}
+
+impl<'a> BlockReplayer<'a> {
+    pub fn new(
+        blockstore: &'a Blockstore,
+        bank_forks: &'a BankForks,
+        evm_state_path: &Path,
+        chain_id: u64,
+        features: EvmFeatureActivations,
+    ) -> Result<Self> {
+        ensure!(
+            evm_state_path.is_dir(),
+            "EVM state not found at {}",
+            evm_state_path.display()
+        );
+        // Keep the copy on the same filesystem, so table files can be hard linked
+        let storage_dir = tempfile::Builder::new()
+            .prefix("evm-state.replay-")
+            .tempdir_in(evm_state_path.parent().unwrap_or(evm_state_path))?;
+        info!(
+            "Copying evm-state from {} into {}",
+            evm_state_path.display(),
+            storage_dir.path().display()
+        );
+        copy_evm_state(evm_state_path, storage_dir.path())?;
+        let storage = Storage::open_persistent(
+            storage_dir.path(),
+            true, // enable gc
+        )?;
+        Ok(Self {
+            blockstore,
+            bank_forks,
+            storage,
+            _storage_dir: storage_dir,
+            chain_id,
+            features,
+        })
+    }
+
+    fn load_block(&self, block_number: BlockNum) -> Result<Block> {
+        self.blockstore
+            .get_evm_block(block_number)
+            .map(|(block, _confirmed)| block)
+            .map_err(|e| anyhow!("Unable to load evm block {}: {:?}", block_number, e))
+    }
+
+    /// Native account as of `slot`: from the bank of that slot while it is still in bank forks,
+    /// otherwise from the root bank, which is the closest state available offline.
+    fn native_account(&self, pubkey: &Pubkey, slot: Slot) -> AccountSharedData {
+        let bank = self
+            .bank_forks
+            .get(slot)
+            .cloned()
+            .unwrap_or_else(|| self.bank_forks.root_bank());
+        bank.get_account(pubkey).unwrap_or_default()
+    }
+
+    /// Collect hashes of 256 blocks before `block_number`, in order that bank stores them.
+    fn chain_context(&self, block_number: BlockNum) -> ChainContext {
+        let mut last_hashes = [H256::zero(); 256];
+        let hashes = (1..block_number)
+            .rev()
+            .take(last_hashes.len())
+            .zip((0..last_hashes.len()).rev());
+        for (num, idx) in hashes {
+            match self.blockstore.get_evm_block(num) {
+                Ok((block, _confirmed)) => last_hashes[idx] = block.header.hash(),
+                Err(_) => warn!("Block {} not found, using empty hash in chain context", num),
+            }
+        }
+        ChainContext::new(last_hashes)
+    }
+
+    /// Replays single block, returns `None` if all data match stored values.
+    pub fn replay_block(&self, block_number: BlockNum) -> Result<Option<Divergence>> {
+        ensure!(
+            block_number > 1,
+            "Block 1 is built on top of genesis, start replay from block 2"
+        );
+        let block = self.load_block(block_number)?;
+        let parent = self.load_block(block_number - 1)?;
+        ensure!(
+            self.storage.check_root_exist(parent.header.state_root),
+            "Parent state root {:?} not found in evm-state, it was probably purged",
+            parent.header.state_root
+        );
+
+        let slot = block.header.native_chain_slot;
+        let parent = EvmBackend::new(
+            Committed {
+                block: parent.header,
+                committed_transactions: vec![],
+            },
+            self.storage.clone(),
+        );
+        let spv_compatibility = block.header.version >= BlockVersion::VersionConsistentHashes;
+        let evm_backend = match EvmState::from(parent)
+            .new_from_parent(block.header.timestamp as i64, spv_compatibility)
+        {
+            EvmState::Incomming(incomming) => incomming,
+            EvmState::Committed(_) => unreachable!("new_from_parent always return incomming state"),
+        };
+
+        let mut executor = Executor::with_config(
+            evm_backend,
+            self.chain_context(block_number),
+            EvmConfig::new(
+                self.chain_id,
+                self.features.is_active(EvmFeature::BurnFee, slot),
+            ),
+            FeatureSet::new(
+                self.features.is_active(EvmFeature::UnsignedTxFix, slot),
+                self.features.is_active(EvmFeature::ClearLogsOnError, slot),
+            ),
+        );
+
+        let mut changed_by: HashMap<H160, Vec<usize>> = HashMap::new();
+        for (index, (tx_hash, stored)) in block.transactions.iter().enumerate() {
+            let before = executor.evm_backend.changed_state().clone();
+            if let Err(error) = self.replay_transaction(&mut executor, slot, stored) {
+                return Ok(Some(Divergence::TransactionRejected {
+                    index,
+                    tx_hash: *tx_hash,
+                    error,
+                }));
+            }
+            let replayed = executor
+                .evm_backend
+                .find_transaction_receipt(*tx_hash)
+                .cloned();
+            match replayed {
+                Some(replayed)
+                    if replayed.status == stored.status
+                        && replayed.used_gas == stored.used_gas
+                        && replayed.logs == stored.logs => {}
+                Some(replayed) => {
+                    return Ok(Some(Divergence::Receipt {
+                        index,
+                        tx_hash: *tx_hash,
+                        stored: Box::new(stored.clone()),
+                        replayed: Box::new(replayed),
+                    }))
+                }
+                None => {
+                    return Ok(Some(Divergence::TransactionRejected {
+                        index,
+                        tx_hash: *tx_hash,
+                        error: "replayed transaction has different hash".to_string(),
+                    }))
+                }
+            }
+
+            for (address, change) in executor.evm_backend.changed_state() {
+                if before.get(address) != Some(change) {
+                    changed_by.entry(*address).or_default().push(index);
+                }
+            }
+        }
+
+        let changed_storage: HashMap<H160, BTreeSet<H256>> = executor
+            .evm_backend
+            .changed_state()
+            .iter()
+            .map(|(address, (_, storage))| (*address, storage.keys().copied().collect()))
+            .collect();
+
+        let mut state = EvmState::from(executor.deconstruct());
+        state.try_commit(slot, block.header.native_chain_hash.to_fixed_bytes())?;
+        let replayed = state
+            .get_block()
+            .ok_or_else(|| anyhow!("Replayed block {} has no changes", block_number))?;
+
+        let fields = header_diff(&block.header, &replayed.header);
+        if fields.is_empty() {
+            return Ok(None);
+        }
+
+        // Accounts changed during replay, and accounts mentioned in stored receipts.
+        let mut addresses: BTreeSet<H160> = changed_by.keys().copied().collect();
+        for (_, receipt) in &block.transactions {
+            addresses.extend(receipt.caller());
+            if let TransactionAction::Call(address) = transaction_action(&receipt.transaction) {
+                addresses.insert(address);
+            }
+            addresses.extend(receipt.logs.iter().map(|log| log.address));
+        }
+
+        let stored_root =
+            Some(block.header.state_root).filter(|root| self.storage.check_root_exist(*root));
+        let backend = EvmBackend::new((), self.storage.clone());
+        let accounts = addresses
+            .into_iter()
+            .filter_map(|address| {
+                let stored =
+                    stored_root.map(|root| backend.get_account_state_from_kvs(root, address));
+                let replayed_state =
+                    backend.get_account_state_from_kvs(replayed.header.state_root, address);
+                let storage: Vec<_> = changed_storage
+                    .get(&address)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|index| {
+                        let stored = stored_root
+                            .and_then(|root| backend.get_storage_from_kvs(root, address, *index));
+                        let replayed = backend.get_storage_from_kvs(
+                            replayed.header.state_root,
+                            address,
+                            *index,
+                        );
+                        (stored != replayed).then(|| (*index, stored, replayed))
+                    })
+                    .collect();
+
+                if stored.as_ref() == Some(&replayed_state) && storage.is_empty() {
+                    return None;
+                }
+                Some(AccountDiff {
+                    address,
+                    stored,
+                    replayed: replayed_state,
+                    storage,
+                    changed_by: changed_by.get(&address).cloned().unwrap_or_default(),
+                })
+            })
+            .collect();
+
+        Ok(Some(Divergence::Header { fields, accounts }))
+    }
+
+    /// Execute transaction the same way as evm_loader processor and bank does.
+    /// Returns error if executor rejects the transaction.
+    fn replay_transaction(
+        &self,
+        executor: &mut Executor,
+        slot: Slot,
+        stored: &TransactionReceipt,
+    ) -> Result<(), String> {
+        let new_error_handling = self
+            .features
+            .is_active(EvmFeature::EvmNewErrorHandling, slot);
+        let backup = executor.evm_backend.clone();
+
+        let result = match &stored.transaction {
+            TransactionInReceipt::Signed(tx) => {
+                let precompiles = replay_precompiles(executor.support_precompile(), |pubkey| {
+                    self.native_account(pubkey, slot)
+                });
+                executor.transaction_execute(tx.clone(), precompiles)
+            }
+            // Swap from native is registered as unsigned transaction from swap address.
+            TransactionInReceipt::Unsigned(tx) if tx.caller == *ETH_TO_SOPHON_ADDR => {
+                let recipient = match tx.unsigned_tx.action {
+                    TransactionAction::Call(recipient) => recipient,
+                    TransactionAction::Create => {
+                        return Err("Swap transaction cannot create contract".to_string())
+                    }
+                };
+                executor.deposit(recipient, tx.unsigned_tx.value);
+                executor.register_swap_tx_in_evm(tx.caller, recipient, tx.unsigned_tx.value);
+                self.reset_swap_balance(executor, slot);
+                return Ok(());
+            }
+            TransactionInReceipt::Unsigned(tx) => {
+                let precompiles = replay_precompiles(executor.support_precompile(), |pubkey| {
+                    self.native_account(pubkey, slot)
+                });
+                executor.transaction_execute_unsinged(
+                    tx.caller,
+                    tx.unsigned_tx.clone(),
+                    precompiles,
+                )
+            }
+        };
+        let result = result.map_err(|e| e.to_string())?;
+        self.reset_swap_balance(executor, slot);
+
+        if result.exit_reason.is_succeed() {
+            let burn_fee = executor.config().burn_gas_price * result.used_gas;
+            if !burn_fee.is_zero() {
+                executor.deposit(BURN_ADDR, burn_fee)
+            }
+        } else if new_error_handling {
+            // Native transaction fails, bank keeps only the receipt and nonce increment.
+            let failed = std::mem::replace(&mut executor.evm_backend, backup);
+            executor.evm_backend.apply_failed_update(&failed);
+        }
+        Ok(())
+    }
+
+    fn reset_swap_balance(&self, executor: &mut Executor, slot: Slot) {
+        if self
+            .features
+            .is_active(EvmFeature::NativeSwapInEvmHistory, slot)
+        {
+            executor.reset_balance(
+                *ETH_TO_SOPHON_ADDR,
+                self.features
+                    .is_active(EvmFeature::IgnoreResetOnCleared, slot),
+            )
+        }
+    }
+}
+
+/// Precompiles with the recipient of `transferToNative` loaded by `native_account`.
+/// Changes to the recipient are not kept, native state is never modified by replay.
+fn replay_precompiles(
+    activate_precompile: bool,
+    native_account: impl Fn(&Pubkey) -> AccountSharedData,
+) -> impl FnMut(H160, &[u8], Option<u64>, &Context) -> Option<PrecompileCallResult> {
+    move |address, function_abi_input, gas_left, cx| {
+        let recipient = if address == *ETH_TO_SOPHON_ADDR {
+            ETH_TO_SOPHON_CODE.parse_abi(function_abi_input).ok()
+        } else {
+            None
+        };
+        let recipient_account =
+            RefCell::new(recipient.as_ref().map(&native_account).unwrap_or_default());
+        let users: Vec<_> = recipient
+            .iter()
+            .map(|pubkey| KeyedAccount::new(pubkey, false, &recipient_account))
+            .collect();
+        let mut entrypoint =
+            precompiles::simulation_entrypoint(activate_precompile, u64::MAX, &users);
+        entrypoint(address, function_abi_input, gas_left, cx)
+    }
+}
+
+fn transaction_action(tx: &TransactionInReceipt) -> TransactionAction {
+    match tx {
+        TransactionInReceipt::Signed(tx) => tx.action,
+        TransactionInReceipt::Unsigned(tx) => tx.unsigned_tx.action,
+    }
+}
+
+fn header_diff(
+    stored: &BlockHeader,
+    replayed: &BlockHeader,
+) -> Vec<(&'static str, String, String)> {
+    let mut fields = Vec::new();
+    macro_rules! compare {
+        ($($field:ident),*) => {
+            $(
+                if stored.$field != replayed.$field {
+                    fields.push((
+                        stringify!($field),
+                        format!("{:?}", stored.$field),
+                        format!("{:?}", replayed.$field),
+                    ));
+                }
+            )*
+        };
+    }
+    compare!(
+        state_root,
+        receipts_root,
+        transactions_root,
+        logs_bloom,
+        gas_used,
+        transactions
+    );
+    fields
+}
+
+/// Replay blocks in range `[starting_block, ending_block]`, and stop on first divergence.
+pub fn replay_blocks(
+    blockstore: &Blockstore,
+    bank_forks: &BankForks,
+    evm_state_path: &Path,
+    starting_block: BlockNum,
+    ending_block: BlockNum,
+    chain_id: u64,
+    features: EvmFeatureActivations,
+) -> Result<()> {
+    let replayer = BlockReplayer::new(blockstore, bank_forks, evm_state_path, chain_id, features)?;
+    for block_number in starting_block..=ending_block {
+        match replayer.replay_block(block_number)? {
+            None => println!("Block {}: ok", block_number),
+            Some(divergence) => {
+                println!("Block {}: divergence found", block_number);
+                print!("{}", divergence);
+                return Err(anyhow!("Block {} diverged", block_number));
+            }
+        }
+    }
+    println!(
+        "Replayed {} blocks, no divergence found",
+        ending_block.saturating_sub(starting_block) + 1
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use evm_state::{
+        secp256k1::{rand, SecretKey},
+        FromKey, Incomming, UnsignedTransaction, TEST_CHAIN_ID,
+    };
+    use solana_runtime::bank::Bank;
+    use solana_sdk::genesis_config::create_genesis_config;
+
+    #[test]
+    fn parse_feature_activation() {
+        assert_eq!(
+            "burn_fee=100".parse::<FeatureActivation>().unwrap(),
+            FeatureActivation {
+                feature: EvmFeature::BurnFee,
+                slot: Some(100)
+            }
+        );
+        assert_eq!(
+            "unsigned_tx_fix=inactive"
+                .parse::<FeatureActivation>()
+                .unwrap(),
+            FeatureActivation {
+                feature: EvmFeature::UnsignedTxFix,
+                slot: None
+            }
+        );
+        assert!("burn_fee".parse::<FeatureActivation>().is_err());
+        assert!("unknown=1".parse::<FeatureActivation>().is_err());
+    }
+
+    #[test]
+    fn feature_activations() {
+        let mut feature_set = feature_set::FeatureSet::default();
+        feature_set
+            .active
+            .insert(feature_set::sophon::unsigned_tx_fix::id(), 0);
+        feature_set
+            .active
+            .insert(feature_set::sophon::clear_logs_on_error::id(), 5);
+        let features = EvmFeatureActivations::new(
+            &feature_set,
+            vec![
+                FeatureActivation {
+                    feature: EvmFeature::BurnFee,
+                    slot: Some(10),
+                },
+                FeatureActivation {
+                    feature: EvmFeature::ClearLogsOnError,
+                    slot: None,
+                },
+            ],
+        );
+        assert!(features.is_active(EvmFeature::UnsignedTxFix, 0));
+        assert!(!features.is_active(EvmFeature::EvmNewErrorHandling, u64::MAX));
+        assert!(!features.is_active(EvmFeature::BurnFee, 9));
+        assert!(features.is_active(EvmFeature::BurnFee, 10));
+        assert!(!features.is_active(EvmFeature::ClearLogsOnError, u64::MAX));
+    }
+
+    fn commit_block(state: &mut EvmState, slot: Slot, blockstore: &Blockstore) -> Block {
+        state.try_commit(slot, [slot as u8; 32]).unwrap();
+        let block = state.get_block().unwrap();
+        blockstore.write_evm_block_header(&block.header).unwrap();
+        for (hash, receipt) in &block.transactions {
+            blockstore
+                .write_evm_transaction(block.header.block_number, slot, *hash, receipt.clone())
+                .unwrap();
+        }
+        block
+    }
+
+    fn next_executor(state: &EvmState, timestamp: i64) -> Executor {
+        let evm_backend = match state.new_from_parent(timestamp, true) {
+            EvmState::Incomming(incomming) => incomming,
+            EvmState::Committed(_) => unreachable!(),
+        };
+        Executor::with_config(
+            evm_backend,
+            ChainContext::default(),
+            EvmConfig::new(TEST_CHAIN_ID, false),
+            FeatureSet::new(false, false),
+        )
+    }
+
+    fn test_bank_forks() -> BankForks {
+        let (genesis_config, _mint_keypair) = create_genesis_config(1_000_000);
+        BankForks::new(Bank::new(&genesis_config))
+    }
+
+    fn list_files(path: &Path) -> BTreeSet<(String, u64)> {
+        fs::read_dir(path)
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                (
+                    entry.file_name().to_string_lossy().into_owned(),
+                    entry.metadata().unwrap().len(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn replay_detects_divergence() {
+        let ledger_path = solana_ledger::get_tmp_ledger_path!();
+        {
+            let blockstore = Blockstore::open(&ledger_path).unwrap();
+            let evm_state_path = ledger_path.join("evm-state");
+            let alice = SecretKey::new(&mut rand::thread_rng());
+            let bob = H160::repeat_byte(0x42);
+
+            let mut state =
+                EvmState::load_from(&evm_state_path, Incomming::default(), true).unwrap();
+            let mut executor = next_executor(&state, 0);
+            executor.deposit(alice.to_address(), 1_000_000.into());
+            state = executor.deconstruct().into();
+            commit_block(&mut state, 1, &blockstore);
+
+            let mut executor = next_executor(&state, 1);
+            executor.deposit(bob, 1.into());
+            state = executor.deconstruct().into();
+            commit_block(&mut state, 2, &blockstore);
+
+            let mut executor = next_executor(&state, 2);
+            let transfer = UnsignedTransaction {
+                nonce: 0.into(),
+                gas_price: 0.into(),
+                gas_limit: 300_000.into(),
+                action: TransactionAction::Call(bob),
+                value: 1_000.into(),
+                input: vec![],
+            }
+            .sign(&alice, Some(TEST_CHAIN_ID));
+            let precompiles = replay_precompiles(executor.support_precompile(), |_| {
+                AccountSharedData::default()
+            });
+            executor.transaction_execute(transfer, precompiles).unwrap();
+            state = executor.deconstruct().into();
+            let block = commit_block(&mut state, 3, &blockstore);
+            assert_eq!(block.header.block_number, 2);
+            drop(state);
+
+            let inactive = EvmFeatureActivations::new(&feature_set::FeatureSet::default(), vec![]);
+            let bank_forks = test_bank_forks();
+            let files = list_files(&evm_state_path);
+            let replayer = BlockReplayer::new(
+                &blockstore,
+                &bank_forks,
+                &evm_state_path,
+                TEST_CHAIN_ID,
+                inactive.clone(),
+            )
+            .unwrap();
+            assert!(replayer.replay_block(2).unwrap().is_none());
+            drop(replayer);
+
+            // Replay writes nothing into the ledger evm-state
+            assert_eq!(list_files(&evm_state_path), files);
+
+            // Corrupt the stored receipt
+            let (tx_hash, mut receipt) = block.transactions[0].clone();
+            receipt.used_gas += 1;
+            blockstore
+                .write_evm_transaction(2, 3, tx_hash, receipt)
+                .unwrap();
+            let replayer = BlockReplayer::new(
+                &blockstore,
+                &bank_forks,
+                &evm_state_path,
+                TEST_CHAIN_ID,
+                inactive,
+            )
+            .unwrap();
+            assert!(matches!(
+                replayer.replay_block(2).unwrap(),
+                Some(Divergence::Receipt { index: 0, tx_hash: hash, .. }) if hash == tx_hash
+            ));
+        }
+        Blockstore::destroy(&ledger_path).expect("Expected successful database destruction");
+    }
+}
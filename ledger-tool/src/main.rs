@@ -66,6 +66,8 @@ use bigtable::*;
 mod evm_blockstore;
 use evm_blockstore::*;
 
+mod evm_replay;
+
 mod evm_state;
 use crate::evm_state::*;
 
@@ -205,9 +207,9 @@ fn output_slot(
             transactions += entry.transactions.len();
             hashes += entry.num_hashes;
             for transaction in &entry.transactions {
-                for instruction in &transaction.message().instructions {
-                    let program_id =
-                        transaction.message().account_keys[instruction.program_id_index as usize];
+                for instruction in transaction.message.instructions() {
+                    let program_id = transaction.message.static_account_keys()
+                        [instruction.program_id_index as usize];
                     *program_ids.entry(program_id).or_insert(0) += 1;
                 }
             }
@@ -676,6 +678,27 @@ fn load_bank_forks(
     process_options: ProcessOptions,
     snapshot_archive_path: Option<PathBuf>,
     verify_evm_state: bool,
+) -> bank_forks_utils::LoadResult {
+    load_bank_forks_with_evm_state(
+        arg_matches,
+        genesis_config,
+        blockstore,
+        process_options,
+        snapshot_archive_path,
+        verify_evm_state,
+        &blockstore.ledger_path().join("evm-state"),
+    )
+}
+
+/// Same as `load_bank_forks`, but the evm state is loaded into `evm_state_path`
+fn load_bank_forks_with_evm_state(
+    arg_matches: &ArgMatches,
+    genesis_config: &GenesisConfig,
+    blockstore: &Blockstore,
+    process_options: ProcessOptions,
+    snapshot_archive_path: Option<PathBuf>,
+    verify_evm_state: bool,
+    evm_state_path: &Path,
 ) -> bank_forks_utils::LoadResult {
     let snapshot_path = blockstore
         .ledger_path()
@@ -691,6 +714,7 @@ fn load_bank_forks(
             snapshot_archive_path.unwrap_or_else(|| blockstore.ledger_path().to_path_buf());
         Some(SnapshotConfig {
             snapshot_interval_slots: 0, // Value doesn't matter
+            incremental_snapshot_interval_slots: 0,
             snapshot_package_output_path,
             snapshot_path,
             archive_format: ArchiveFormat::TarBzip2,
@@ -716,7 +740,6 @@ fn load_bank_forks(
         vec![non_primary_accounts_path]
     };
 
-    let evm_state_path = blockstore.ledger_path().join("evm-state");
     let evm_genesis_path = blockstore
         .ledger_path()
         .join(solana_sdk::genesis_config::EVM_GENESIS);
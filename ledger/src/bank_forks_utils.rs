@@ -62,6 +62,7 @@ pub fn load(
             &snapshot_config.snapshot_package_output_path,
         ) {
             Some((archive_filename, (archive_slot, archive_snapshot_hash, compression))) => {
+                let full_snapshot_slot = archive_slot;
                 info!("Loading snapshot package: {:?}", archive_filename);
                 // Fail hard here if snapshot fails to load, don't silently continue
 
@@ -70,13 +71,32 @@ pub fn load(
                     process::exit(1);
                 }
 
-                let deserialized_bank = snapshot_utils::bank_from_archive(
+                let incremental_snapshot_archive =
+                    snapshot_utils::get_highest_incremental_snapshot_archive_path(
+                        &snapshot_config.snapshot_package_output_path,
+                        archive_slot,
+                    );
+                let (archive_slot, archive_snapshot_hash) = match &incremental_snapshot_archive {
+                    Some((incremental_filename, (_, slot, hash, _))) => {
+                        info!(
+                            "Loading incremental snapshot package: {:?}",
+                            incremental_filename
+                        );
+                        (*slot, *hash)
+                    }
+                    None => (archive_slot, archive_snapshot_hash),
+                };
+
+                let deserialized_bank = snapshot_utils::bank_from_snapshot_archives(
                     evm_state_path.as_ref(),
                     &account_paths,
                     &process_options.frozen_accounts,
                     &snapshot_config.snapshot_path,
                     &archive_filename,
                     compression,
+                    incremental_snapshot_archive
+                        .as_ref()
+                        .map(|(path, (.., format))| (path.as_path(), *format)),
                     genesis_config,
                     process_options.debug_keys.clone(),
                     Some(&crate::builtins::get(process_options.bpf_jit)),
@@ -89,6 +109,19 @@ pub fn load(
                 if let Some(shrink_paths) = shrink_paths {
                     deserialized_bank.set_shrink_paths(shrink_paths);
                 }
+                if incremental_snapshot_archive.is_none()
+                    && !matches!(
+                        snapshot_config.incremental_snapshot_interval_slots,
+                        0 | std::u64::MAX
+                    )
+                {
+                    // Resume incremental snapshots on top of the full snapshot we booted from
+                    deserialized_bank
+                        .rc
+                        .accounts
+                        .accounts_db
+                        .set_last_full_snapshot_slot(full_snapshot_slot);
+                }
 
                 let deserialized_snapshot_hash = (
                     deserialized_bank.slot(),
@@ -4,7 +4,7 @@
 use crate::{
     ancestor_iterator::AncestorIterator,
     blockstore_db::{
-        columns as cf, AccessType, BlockstoreRecoveryMode, Column, Database,
+        columns as cf, AccessType, BlockstoreRecoveryMode, Column, Database, EvmLogLocation,
         EvmTransactionReceiptsIndex, IteratorDirection, IteratorMode, LedgerColumn, Result,
         WriteBatch,
     },
@@ -36,10 +36,11 @@ use solana_sdk::{
     sanitize::Sanitize,
     signature::{Keypair, Signature, Signer},
     timing::timestamp,
-    transaction::Transaction,
+    transaction::VersionedTransaction,
 };
 use solana_storage_proto::{StoredExtendedRewards, StoredTransactionStatusMeta};
 use solana_transaction_status::{
+    parse_evm_token::{parse_evm_token_log, EvmTokenBalance, EvmTokenEvent, EvmTokenTransfer},
     ConfirmedBlock, ConfirmedTransaction, ConfirmedTransactionStatusWithSignature, Rewards,
     TransactionStatusMeta, TransactionWithStatusMeta,
 };
@@ -50,7 +51,7 @@ use std::{
     borrow::Cow,
     cell::RefCell,
     cmp,
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet},
     convert::TryInto,
     fs,
     io::{Error as IoError, ErrorKind},
@@ -160,6 +161,11 @@ pub struct Blockstore {
     evm_transactions_cf: LedgerColumn<cf::EvmTransactionReceipts>,
     evm_blocks_by_hash_cf: LedgerColumn<cf::EvmHeaderIndexByHash>,
     evm_blocks_by_slot_cf: LedgerColumn<cf::EvmHeaderIndexBySlot>,
+    evm_logs_by_address_cf: LedgerColumn<cf::EvmLogsByAddress>,
+    evm_logs_by_topic_cf: LedgerColumn<cf::EvmLogsByTopic>,
+    evm_token_transfers_cf: LedgerColumn<cf::EvmTokenTransfers>,
+    evm_token_balances_cf: LedgerColumn<cf::EvmTokenBalances>,
+    evm_token_index_progress_cf: LedgerColumn<cf::EvmTokenIndexProgress>,
 }
 
 pub struct IndexMetaWorkingSetEntry {
@@ -330,6 +336,11 @@ impl Blockstore {
         let evm_transactions_cf = db.column();
         let evm_blocks_by_hash_cf = db.column();
         let evm_blocks_by_slot_cf = db.column();
+        let evm_logs_by_address_cf = db.column();
+        let evm_logs_by_topic_cf = db.column();
+        let evm_token_transfers_cf = db.column();
+        let evm_token_balances_cf = db.column();
+        let evm_token_index_progress_cf = db.column();
 
         let db = Arc::new(db);
 
@@ -388,6 +399,11 @@ impl Blockstore {
             evm_transactions_cf,
             evm_blocks_by_hash_cf,
             evm_blocks_by_slot_cf,
+            evm_logs_by_address_cf,
+            evm_logs_by_topic_cf,
+            evm_token_transfers_cf,
+            evm_token_balances_cf,
+            evm_token_index_progress_cf,
         };
         if initialize_transaction_status_index {
             blockstore.initialize_transaction_status_index()?;
@@ -2048,7 +2064,7 @@ impl Blockstore {
     fn map_transactions_to_statuses<'a>(
         &self,
         slot: Slot,
-        iterator: impl Iterator<Item = Transaction> + 'a,
+        iterator: impl Iterator<Item = VersionedTransaction> + 'a,
     ) -> Vec<TransactionWithStatusMeta> {
         iterator
             .map(|transaction| {
@@ -2347,7 +2363,7 @@ impl Blockstore {
         &self,
         slot: Slot,
         signature: Signature,
-    ) -> Result<Option<Transaction>> {
+    ) -> Result<Option<VersionedTransaction>> {
         let slot_entries = self.get_slot_entries(slot, 0)?;
         Ok(slot_entries
             .iter()
@@ -3030,6 +3046,9 @@ impl Blockstore {
             self.active_transaction_status_index.write().unwrap();
         let index =
             self.get_primary_index_to_write(block_num, &w_active_transaction_status_index)?;
+        let logs_index =
+            self.get_primary_index_to_write(slot_index, &w_active_transaction_status_index)?;
+        self.put_evm_log_locations(logs_index, block_num, &status)?;
         let status = status.into();
         self.evm_transactions_cf.put_protobuf(
             EvmTransactionReceiptsIndex {
@@ -3042,6 +3061,302 @@ impl Blockstore {
         )?;
         Ok(())
     }
+
+    /// Index logs of transaction `receipt` by address and first topic.
+    /// Logs are indexed by `write_evm_transaction`, this is only needed to rebuild the index.
+    pub fn write_evm_log_locations(
+        &self,
+        block_num: evm_state::BlockNum,
+        slot_index: Slot,
+        receipt: &evm::TransactionReceipt,
+    ) -> Result<()> {
+        let w_active_transaction_status_index =
+            self.active_transaction_status_index.write().unwrap();
+        let index =
+            self.get_primary_index_to_write(slot_index, &w_active_transaction_status_index)?;
+        self.put_evm_log_locations(index, block_num, receipt)
+    }
+
+    fn put_evm_log_locations(
+        &self,
+        primary_index: u64,
+        block_num: evm_state::BlockNum,
+        receipt: &evm::TransactionReceipt,
+    ) -> Result<()> {
+        for (log_index, log) in receipt.logs.iter().enumerate() {
+            let location = EvmLogLocation {
+                block_num,
+                transaction_id: receipt.index,
+                log_index: log_index as u64,
+            };
+            self.evm_logs_by_address_cf
+                .put_bytes((primary_index, log.address, location), &[])?;
+            if let Some(topic) = log.topics.first() {
+                self.evm_logs_by_topic_cf
+                    .put_bytes((primary_index, *topic, location), &[])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns locations of logs emitted by `address` in blocks `start_block..=end_block`.
+    pub fn find_evm_logs_by_address(
+        &self,
+        address: evm::H160,
+        start_block: evm::BlockNum,
+        end_block: evm::BlockNum,
+    ) -> Result<Vec<EvmLogLocation>> {
+        let mut locations = vec![];
+        for primary_index in 0..=1 {
+            let index_iterator = self.evm_logs_by_address_cf.iter(IteratorMode::From(
+                (
+                    primary_index,
+                    address,
+                    EvmLogLocation {
+                        block_num: start_block,
+                        ..EvmLogLocation::default()
+                    },
+                ),
+                IteratorDirection::Forward,
+            ))?;
+            for ((i, log_address, location), _) in index_iterator {
+                if i != primary_index || log_address != address || location.block_num > end_block {
+                    break;
+                }
+                locations.push(location);
+            }
+        }
+        locations.sort_unstable();
+        locations.dedup();
+        Ok(locations)
+    }
+
+    /// Returns locations of logs with first topic equal to `topic` in blocks
+    /// `start_block..=end_block`.
+    pub fn find_evm_logs_by_topic(
+        &self,
+        topic: H256,
+        start_block: evm::BlockNum,
+        end_block: evm::BlockNum,
+    ) -> Result<Vec<EvmLogLocation>> {
+        let mut locations = vec![];
+        for primary_index in 0..=1 {
+            let index_iterator = self.evm_logs_by_topic_cf.iter(IteratorMode::From(
+                (
+                    primary_index,
+                    topic,
+                    EvmLogLocation {
+                        block_num: start_block,
+                        ..EvmLogLocation::default()
+                    },
+                ),
+                IteratorDirection::Forward,
+            ))?;
+            for ((i, log_topic, location), _) in index_iterator {
+                if i != primary_index || log_topic != topic || location.block_num > end_block {
+                    break;
+                }
+                locations.push(location);
+            }
+        }
+        locations.sort_unstable();
+        locations.dedup();
+        Ok(locations)
+    }
+
+    /// Use address and first topic indexes to find blocks that can contain logs matching `filter`.
+    /// Returns `None` if filter doesn't restrict neither address nor first topic,
+    /// so every block in range should be checked.
+    ///
+    /// Blocks are only candidates, logs should be matched precisely with `filter_block_logs`.
+    pub fn find_evm_log_blocks(
+        &self,
+        filter: &evm::LogFilter,
+    ) -> Result<Option<BTreeSet<evm::BlockNum>>> {
+        let (start_block, end_block) = (filter.from_block, filter.to_block);
+
+        let by_address = if filter.address.is_empty() {
+            None
+        } else {
+            let mut blocks = BTreeSet::new();
+            for address in &filter.address {
+                blocks.extend(
+                    self.find_evm_logs_by_address(*address, start_block, end_block)?
+                        .into_iter()
+                        .map(|location| location.block_num),
+                );
+            }
+            Some(blocks)
+        };
+
+        let first_topics = match filter.topics.first() {
+            Some(evm::LogFilterTopicEntry::One(topic)) => Some(vec![*topic]),
+            Some(evm::LogFilterTopicEntry::Or(topics)) => Some(topics.clone()),
+            Some(evm::LogFilterTopicEntry::Any) | None => None,
+        };
+        let by_topic = match first_topics {
+            Some(topics) => {
+                let mut blocks = BTreeSet::new();
+                for topic in topics {
+                    blocks.extend(
+                        self.find_evm_logs_by_topic(topic, start_block, end_block)?
+                            .into_iter()
+                            .map(|location| location.block_num),
+                    );
+                }
+                Some(blocks)
+            }
+            None => None,
+        };
+
+        Ok(match (by_address, by_topic) {
+            (Some(by_address), Some(by_topic)) => {
+                Some(by_address.intersection(&by_topic).copied().collect())
+            }
+            (by_address, by_topic) => by_address.or(by_topic),
+        })
+    }
+
+    /// Store ERC-20/ERC-721 transfers from `block` for every involved holder, update their
+    /// balances, and mark `block` as the last processed by token indexer.
+    ///
+    /// Blocks should be written in order, by a single writer.
+    pub fn write_evm_token_transfers(&self, block: &evm::Block) -> Result<()> {
+        let block_num = block.header.block_number;
+        let mut write_batch = self.db.batch()?;
+        let mut balances: HashMap<(evm::H160, evm::H160), EvmTokenBalance> = HashMap::new();
+        for (hash, receipt) in &block.transactions {
+            for (log_index, log) in receipt.logs.iter().enumerate() {
+                let (token, standard, from, to, value) = match parse_evm_token_log(log) {
+                    Some(EvmTokenEvent::Transfer {
+                        token,
+                        standard,
+                        from,
+                        to,
+                        value,
+                    }) => (token, standard, from, to, value),
+                    _ => continue,
+                };
+                let location = EvmLogLocation {
+                    block_num,
+                    transaction_id: receipt.index,
+                    log_index: log_index as u64,
+                };
+                let transfer = EvmTokenTransfer {
+                    transaction_hash: *hash,
+                    token,
+                    standard,
+                    from,
+                    to,
+                    value,
+                };
+                // zero address means mint or burn
+                let mut holders = vec![from, to];
+                holders.retain(|holder| !holder.is_zero());
+                holders.dedup();
+                for holder in holders {
+                    write_batch.put::<cf::EvmTokenTransfers>((holder, location), &transfer)?;
+                    if !balances.contains_key(&(holder, token)) {
+                        let balance = self
+                            .evm_token_balances_cf
+                            .get((holder, token))?
+                            .unwrap_or_else(|| EvmTokenBalance::new(token, standard));
+                        balances.insert((holder, token), balance);
+                    }
+                    balances
+                        .get_mut(&(holder, token))
+                        .unwrap()
+                        .apply(holder, &transfer);
+                }
+            }
+        }
+        for ((holder, token), balance) in balances {
+            if balance.is_empty() {
+                write_batch.delete::<cf::EvmTokenBalances>((holder, token))?;
+            } else {
+                write_batch.put::<cf::EvmTokenBalances>((holder, token), &balance)?;
+            }
+        }
+        if self.read_evm_token_index_range()?.is_none() {
+            write_batch.put::<cf::EvmTokenIndexProgress>(1, &block_num)?;
+        }
+        write_batch.put::<cf::EvmTokenIndexProgress>(0, &block_num)?;
+        self.db.write(write_batch)?;
+        Ok(())
+    }
+
+    /// Returns the last evm block processed by token indexer.
+    pub fn read_evm_token_index_progress(&self) -> Result<Option<evm::BlockNum>> {
+        self.evm_token_index_progress_cf.get(0)
+    }
+
+    /// Returns the first and the last evm blocks processed by token indexer, transfers from
+    /// earlier blocks are missing from the index.
+    pub fn read_evm_token_index_range(&self) -> Result<Option<(evm::BlockNum, evm::BlockNum)>> {
+        let last = match self.evm_token_index_progress_cf.get(0)? {
+            Some(last) => last,
+            None => return Ok(None),
+        };
+        // Indexes written before the first block was tracked start from the first available
+        let first = match self.evm_token_index_progress_cf.get(1)? {
+            Some(first) => first,
+            None => self.get_first_available_evm_block()?.min(last),
+        };
+        Ok(Some((first, last)))
+    }
+
+    /// Returns up to `limit` token transfers of `holder` with their log locations, optionally
+    /// filtered by `token` contract, newest first. Listing starts right before the `before`
+    /// location if provided, so the location of the last transfer can be used to get the next
+    /// page.
+    pub fn find_evm_token_transfers(
+        &self,
+        holder: evm::H160,
+        token: Option<evm::H160>,
+        before: Option<EvmLogLocation>,
+        limit: usize,
+    ) -> Result<Vec<(EvmLogLocation, EvmTokenTransfer)>> {
+        let start = before.unwrap_or(EvmLogLocation {
+            block_num: evm::BlockNum::MAX,
+            transaction_id: u64::MAX,
+            log_index: u64::MAX,
+        });
+        let index_iterator = self.evm_token_transfers_cf.iter(IteratorMode::From(
+            (holder, start),
+            IteratorDirection::Reverse,
+        ))?;
+        let mut transfers = vec![];
+        for ((transfer_holder, location), data) in index_iterator {
+            if transfers.len() >= limit || transfer_holder != holder {
+                break;
+            }
+            if Some(location) == before {
+                continue;
+            }
+            let transfer = deserialize::<EvmTokenTransfer>(&data)?;
+            if token.map_or(true, |token| token == transfer.token) {
+                transfers.push((location, transfer));
+            }
+        }
+        Ok(transfers)
+    }
+
+    /// Returns current token balances of `holder`, accumulated by token indexer.
+    pub fn read_evm_token_balances(&self, holder: evm::H160) -> Result<Vec<EvmTokenBalance>> {
+        let index_iterator = self.evm_token_balances_cf.iter(IteratorMode::From(
+            (holder, evm::H160::zero()),
+            IteratorDirection::Forward,
+        ))?;
+        let mut balances = vec![];
+        for ((balance_holder, _token), data) in index_iterator {
+            if balance_holder != holder {
+                break;
+            }
+            balances.push(deserialize::<EvmTokenBalance>(&data)?);
+        }
+        Ok(balances)
+    }
+
     /// Returns the entry vector for the slot starting with `shred_start_index`
     pub fn get_slot_entries(&self, slot: Slot, shred_start_index: u64) -> Result<Vec<Entry>> {
         self.get_slot_entries_with_shred_info(slot, shred_start_index, false)
@@ -4281,10 +4596,12 @@ pub mod tests {
     use solana_sdk::{
         hash::{self, hash, Hash},
         instruction::CompiledInstruction,
+        message::v0::LoadedAddresses,
         packet::PACKET_DATA_SIZE,
+        process_instruction::TransactionReturnData,
         pubkey::Pubkey,
         signature::Signature,
-        transaction::TransactionError,
+        transaction::{Transaction, TransactionError},
     };
     use solana_storage_proto::convert::generated;
     use solana_transaction_status::{InnerInstructions, Reward, Rewards, TransactionTokenBalance};
@@ -6461,7 +6778,9 @@ pub mod tests {
             .map(|transaction| {
                 let mut pre_balances: Vec<u64> = vec![];
                 let mut post_balances: Vec<u64> = vec![];
-                for (i, _account_key) in transaction.message.account_keys.iter().enumerate() {
+                for (i, _account_key) in
+                    transaction.message.static_account_keys().iter().enumerate()
+                {
                     pre_balances.push(i as u64 * 10);
                     post_balances.push(i as u64 * 11);
                 }
@@ -6476,6 +6795,8 @@ pub mod tests {
                     pre_token_balances: Some(vec![]),
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
+                    return_data: None,
+                    loaded_addresses: None,
                 }
                 .into();
                 ledger
@@ -6492,6 +6813,8 @@ pub mod tests {
                     pre_token_balances: Some(vec![]),
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
+                    return_data: None,
+                    loaded_addresses: None,
                 }
                 .into();
                 ledger
@@ -6508,6 +6831,8 @@ pub mod tests {
                     pre_token_balances: Some(vec![]),
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
+                    return_data: None,
+                    loaded_addresses: None,
                 }
                 .into();
                 ledger
@@ -6526,6 +6851,8 @@ pub mod tests {
                         pre_token_balances: Some(vec![]),
                         post_token_balances: Some(vec![]),
                         rewards: Some(vec![]),
+                        return_data: None,
+                        loaded_addresses: None,
                     }),
                 }
             })
@@ -6630,6 +6957,14 @@ pub mod tests {
             let pre_token_balances_vec = vec![];
             let post_token_balances_vec = vec![];
             let rewards_vec = vec![];
+            let return_data_value = TransactionReturnData {
+                program_id: Pubkey::new_unique(),
+                data: vec![1, 2, 3],
+            };
+            let loaded_addresses_value = LoadedAddresses {
+                writable: vec![Pubkey::new_unique()],
+                readonly: vec![Pubkey::new_unique()],
+            };
 
             // result not found
             assert!(transaction_status_cf
@@ -6654,6 +6989,8 @@ pub mod tests {
                 pre_token_balances: Some(pre_token_balances_vec.clone()),
                 post_token_balances: Some(post_token_balances_vec.clone()),
                 rewards: Some(rewards_vec.clone()),
+                return_data: None,
+                loaded_addresses: None,
             }
             .into();
             assert!(transaction_status_cf
@@ -6671,6 +7008,8 @@ pub mod tests {
                 pre_token_balances,
                 post_token_balances,
                 rewards,
+                return_data,
+                loaded_addresses,
             } = transaction_status_cf
                 .get_protobuf_or_bincode::<StoredTransactionStatusMeta>((
                     0,
@@ -6690,6 +7029,8 @@ pub mod tests {
             assert_eq!(pre_token_balances.unwrap(), pre_token_balances_vec);
             assert_eq!(post_token_balances.unwrap(), post_token_balances_vec);
             assert_eq!(rewards.unwrap(), rewards_vec);
+            assert_eq!(return_data, None);
+            assert_eq!(loaded_addresses, None);
 
             // insert value
             let status = TransactionStatusMeta {
@@ -6702,6 +7043,8 @@ pub mod tests {
                 pre_token_balances: Some(pre_token_balances_vec.clone()),
                 post_token_balances: Some(post_token_balances_vec.clone()),
                 rewards: Some(rewards_vec.clone()),
+                return_data: Some(return_data_value.clone()),
+                loaded_addresses: Some(loaded_addresses_value.clone()),
             }
             .into();
             assert!(transaction_status_cf
@@ -6719,6 +7062,8 @@ pub mod tests {
                 pre_token_balances,
                 post_token_balances,
                 rewards,
+                return_data,
+                loaded_addresses,
             } = transaction_status_cf
                 .get_protobuf_or_bincode::<StoredTransactionStatusMeta>((
                     0,
@@ -6740,6 +7085,8 @@ pub mod tests {
             assert_eq!(pre_token_balances.unwrap(), pre_token_balances_vec);
             assert_eq!(post_token_balances.unwrap(), post_token_balances_vec);
             assert_eq!(rewards.unwrap(), rewards_vec);
+            assert_eq!(return_data, Some(return_data_value));
+            assert_eq!(loaded_addresses, Some(loaded_addresses_value));
         }
         Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
     }
@@ -6971,6 +7318,8 @@ pub mod tests {
                 pre_token_balances: Some(vec![]),
                 post_token_balances: Some(vec![]),
                 rewards: Some(vec![]),
+                return_data: None,
+                loaded_addresses: None,
             }
             .into();
 
@@ -7167,6 +7516,8 @@ pub mod tests {
                 pre_token_balances: Some(vec![]),
                 post_token_balances: Some(vec![]),
                 rewards: Some(vec![]),
+                return_data: None,
+                loaded_addresses: None,
             }
             .into();
 
@@ -7331,7 +7682,9 @@ pub mod tests {
             .map(|transaction| {
                 let mut pre_balances: Vec<u64> = vec![];
                 let mut post_balances: Vec<u64> = vec![];
-                for (i, _account_key) in transaction.message.account_keys.iter().enumerate() {
+                for (i, _account_key) in
+                    transaction.message.static_account_keys().iter().enumerate()
+                {
                     pre_balances.push(i as u64 * 10);
                     post_balances.push(i as u64 * 11);
                 }
@@ -7354,6 +7707,8 @@ pub mod tests {
                     pre_token_balances: pre_token_balances.clone(),
                     post_token_balances: post_token_balances.clone(),
                     rewards: rewards.clone(),
+                    return_data: None,
+                    loaded_addresses: None,
                 }
                 .into();
                 blockstore
@@ -7372,6 +7727,8 @@ pub mod tests {
                         pre_token_balances,
                         post_token_balances,
                         rewards,
+                        return_data: None,
+                        loaded_addresses: None,
                     }),
                 }
             })
@@ -7430,7 +7787,9 @@ pub mod tests {
             .map(|transaction| {
                 let mut pre_balances: Vec<u64> = vec![];
                 let mut post_balances: Vec<u64> = vec![];
-                for (i, _account_key) in transaction.message.account_keys.iter().enumerate() {
+                for (i, _account_key) in
+                    transaction.message.static_account_keys().iter().enumerate()
+                {
                     pre_balances.push(i as u64 * 10);
                     post_balances.push(i as u64 * 11);
                 }
@@ -7453,6 +7812,8 @@ pub mod tests {
                     pre_token_balances: pre_token_balances.clone(),
                     post_token_balances: post_token_balances.clone(),
                     rewards: rewards.clone(),
+                    return_data: None,
+                    loaded_addresses: None,
                 }
                 .into();
                 blockstore
@@ -7471,6 +7832,8 @@ pub mod tests {
                         pre_token_balances,
                         post_token_balances,
                         rewards,
+                        return_data: None,
+                        loaded_addresses: None,
                     }),
                 }
             })
@@ -7792,7 +8155,7 @@ pub mod tests {
                             .write_transaction_status(
                                 slot,
                                 transaction.signatures[0],
-                                transaction.message.account_keys.iter().collect(),
+                                transaction.message.static_account_keys().iter().collect(),
                                 vec![],
                                 TransactionStatusMeta::default(),
                             )
@@ -7816,7 +8179,7 @@ pub mod tests {
                             .write_transaction_status(
                                 slot,
                                 transaction.signatures[0],
-                                transaction.message.account_keys.iter().collect(),
+                                transaction.message.static_account_keys().iter().collect(),
                                 vec![],
                                 TransactionStatusMeta::default(),
                             )
@@ -8199,6 +8562,8 @@ pub mod tests {
                     pre_token_balances: Some(vec![]),
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
+                    return_data: None,
+                    loaded_addresses: None,
                 }
                 .into();
                 transaction_status_cf
@@ -8215,7 +8580,8 @@ pub mod tests {
                 vec![CompiledInstruction::new(1, &(), vec![0])],
             ));
 
-            let map = blockstore.map_transactions_to_statuses(slot, transactions.into_iter());
+            let map = blockstore
+                .map_transactions_to_statuses(slot, transactions.into_iter().map(Into::into));
             assert_eq!(map.len(), 5);
             for (x, m) in map.iter().take(4).enumerate() {
                 assert_eq!(m.meta.as_ref().unwrap().fee, x as u64);
@@ -8739,6 +9105,8 @@ pub mod tests {
                     post_balance: 42,
                     reward_type: Some(RewardType::Rent),
                 }]),
+                return_data: None,
+                loaded_addresses: None,
             };
             let deprecated_status: StoredTransactionStatusMeta = status.clone().into();
             let protobuf_status: generated::TransactionStatusMeta = status.into();
@@ -8884,4 +9252,292 @@ pub mod tests {
         }
         Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
     }
+
+    #[test]
+    fn test_find_evm_log_blocks() {
+        use evm::{
+            ExitReason, ExitSucceed, Log, LogFilter, LogFilterTopicEntry, TransactionAction,
+            TransactionInReceipt, TransactionReceipt, UnsignedTransaction,
+            UnsignedTransactionWithCaller, H160,
+        };
+
+        let blockstore_path = get_tmp_ledger_path!();
+        {
+            let blockstore = Blockstore::open(&blockstore_path).unwrap();
+            let address = |byte| H160::repeat_byte(byte);
+            let topic = |byte| H256::repeat_byte(byte);
+            let log = |address, topics| Log {
+                address,
+                topics,
+                data: vec![],
+            };
+            // (block_num, logs)
+            let blocks = vec![
+                (1, vec![log(address(1), vec![topic(1)])]),
+                (2, vec![log(address(2), vec![topic(1)])]),
+                (
+                    3,
+                    vec![log(address(1), vec![]), log(address(2), vec![topic(2)])],
+                ),
+                (4, vec![log(address(1), vec![topic(2), topic(1)])]),
+            ];
+            for (block_num, logs) in blocks {
+                let transaction = UnsignedTransaction {
+                    nonce: block_num.into(),
+                    gas_price: Default::default(),
+                    gas_limit: Default::default(),
+                    action: TransactionAction::Create,
+                    value: Default::default(),
+                    input: vec![],
+                };
+                let receipt = TransactionReceipt {
+                    transaction: TransactionInReceipt::Unsigned(UnsignedTransactionWithCaller {
+                        unsigned_tx: transaction.clone(),
+                        caller: Default::default(),
+                        chain_id: 0,
+                        signed_compatible: false,
+                    }),
+                    status: ExitReason::Succeed(ExitSucceed::Stopped),
+                    block_number: block_num,
+                    index: 0,
+                    used_gas: 0,
+                    logs_bloom: Default::default(),
+                    logs,
+                };
+                blockstore
+                    .write_evm_transaction(
+                        block_num,
+                        block_num,
+                        transaction.signing_hash(None),
+                        receipt,
+                    )
+                    .unwrap();
+            }
+
+            let find = |addresses: Vec<H160>, topics: Vec<LogFilterTopicEntry>| {
+                blockstore
+                    .find_evm_log_blocks(&LogFilter {
+                        from_block: 0,
+                        to_block: 3,
+                        address: addresses,
+                        topics,
+                    })
+                    .unwrap()
+                    .map(|blocks| blocks.into_iter().collect::<Vec<_>>())
+            };
+            assert_eq!(find(vec![], vec![]), None);
+            assert_eq!(find(vec![], vec![LogFilterTopicEntry::Any]), None);
+            assert_eq!(find(vec![address(1)], vec![]), Some(vec![1, 3]));
+            assert_eq!(
+                find(vec![address(1), address(2)], vec![]),
+                Some(vec![1, 2, 3])
+            );
+            assert_eq!(
+                find(vec![], vec![LogFilterTopicEntry::One(topic(1))]),
+                Some(vec![1, 2])
+            );
+            assert_eq!(
+                find(
+                    vec![address(2)],
+                    vec![LogFilterTopicEntry::Or(vec![topic(1), topic(2)])]
+                ),
+                Some(vec![2, 3])
+            );
+            assert_eq!(
+                find(vec![address(3)], vec![LogFilterTopicEntry::One(topic(1))]),
+                Some(vec![])
+            );
+            // Only the first topic is indexed
+            assert_eq!(
+                find(
+                    vec![],
+                    vec![LogFilterTopicEntry::Any, LogFilterTopicEntry::One(topic(1))]
+                ),
+                None
+            );
+        }
+        Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
+    }
+
+    #[test]
+    fn test_write_evm_token_transfers() {
+        use evm::{
+            Block, BlockHeader, BlockVersion, ExitReason, ExitSucceed, Log, TransactionAction,
+            TransactionInReceipt, TransactionReceipt, UnsignedTransaction,
+            UnsignedTransactionWithCaller, H160, U256,
+        };
+        use solana_transaction_status::parse_evm_token::{
+            EvmTokenStandard, APPROVAL_EVENT_TOPIC, TRANSFER_EVENT_TOPIC,
+        };
+
+        let blockstore_path = get_tmp_ledger_path!();
+        {
+            let blockstore = Blockstore::open(&blockstore_path).unwrap();
+            let alice = H160::repeat_byte(1);
+            let bob = H160::repeat_byte(2);
+            let erc20 = H160::repeat_byte(0xaa);
+            let erc721 = H160::repeat_byte(0xbb);
+            let address_topic = |address: H160| H256::from(address);
+            let erc20_log = |event, from, to, value: u64| {
+                let mut data = vec![0; 32];
+                U256::from(value).to_big_endian(&mut data);
+                Log {
+                    address: erc20,
+                    topics: vec![event, address_topic(from), address_topic(to)],
+                    data,
+                }
+            };
+            let erc721_log = |from, to, token_id| Log {
+                address: erc721,
+                topics: vec![
+                    TRANSFER_EVENT_TOPIC,
+                    address_topic(from),
+                    address_topic(to),
+                    H256::from_low_u64_be(token_id),
+                ],
+                data: vec![],
+            };
+            // (block_num, logs)
+            let blocks = vec![
+                (
+                    1,
+                    vec![
+                        erc20_log(TRANSFER_EVENT_TOPIC, H160::zero(), alice, 100),
+                        erc721_log(H160::zero(), alice, 7),
+                    ],
+                ),
+                (
+                    2,
+                    vec![
+                        erc20_log(APPROVAL_EVENT_TOPIC, alice, bob, 50),
+                        erc20_log(TRANSFER_EVENT_TOPIC, alice, bob, 40),
+                    ],
+                ),
+            ];
+            for (block_num, logs) in blocks {
+                let transaction = UnsignedTransaction {
+                    nonce: block_num.into(),
+                    gas_price: Default::default(),
+                    gas_limit: Default::default(),
+                    action: TransactionAction::Create,
+                    value: Default::default(),
+                    input: vec![],
+                };
+                let receipt = TransactionReceipt {
+                    transaction: TransactionInReceipt::Unsigned(UnsignedTransactionWithCaller {
+                        unsigned_tx: transaction.clone(),
+                        caller: Default::default(),
+                        chain_id: 0,
+                        signed_compatible: false,
+                    }),
+                    status: ExitReason::Succeed(ExitSucceed::Stopped),
+                    block_number: block_num,
+                    index: 0,
+                    used_gas: 0,
+                    logs_bloom: Default::default(),
+                    logs,
+                };
+                let transactions = vec![(transaction.signing_hash(None), receipt)];
+                let header = BlockHeader::new(
+                    H256::zero(),
+                    0,
+                    H256::zero(),
+                    block_num,
+                    0,
+                    0,
+                    block_num,
+                    H256::zero(),
+                    transactions.iter(),
+                    BlockVersion::InitVersion,
+                );
+                blockstore
+                    .write_evm_token_transfers(&Block {
+                        header,
+                        transactions,
+                    })
+                    .unwrap();
+            }
+            assert_eq!(blockstore.read_evm_token_index_progress().unwrap(), Some(2));
+            assert_eq!(
+                blockstore.read_evm_token_index_range().unwrap(),
+                Some((1, 2))
+            );
+
+            let alice_transfers = blockstore
+                .find_evm_token_transfers(alice, None, None, 10)
+                .unwrap();
+            assert_eq!(
+                alice_transfers
+                    .iter()
+                    .map(|(location, transfer)| (
+                        location.block_num,
+                        transfer.token,
+                        transfer.standard,
+                        transfer.value
+                    ))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (2, erc20, EvmTokenStandard::Erc20, 40.into()),
+                    (1, erc721, EvmTokenStandard::Erc721, 7.into()),
+                    (1, erc20, EvmTokenStandard::Erc20, 100.into()),
+                ]
+            );
+            // Pages continue right before the last returned transfer
+            let first_page = blockstore
+                .find_evm_token_transfers(alice, None, None, 2)
+                .unwrap();
+            assert_eq!(first_page, alice_transfers[..2].to_vec());
+            assert_eq!(
+                blockstore
+                    .find_evm_token_transfers(alice, None, Some(first_page[1].0), 2)
+                    .unwrap(),
+                alice_transfers[2..].to_vec()
+            );
+            assert_eq!(
+                blockstore
+                    .find_evm_token_transfers(alice, Some(erc721), None, 10)
+                    .unwrap()
+                    .len(),
+                1
+            );
+            let bob_transfers = blockstore
+                .find_evm_token_transfers(bob, None, None, 10)
+                .unwrap();
+            assert_eq!(bob_transfers.len(), 1);
+            assert_eq!(bob_transfers[0].1.from, alice);
+
+            assert_eq!(
+                blockstore.read_evm_token_balances(alice).unwrap(),
+                vec![
+                    EvmTokenBalance {
+                        token: erc20,
+                        standard: EvmTokenStandard::Erc20,
+                        amount: 60.into(),
+                        token_ids: vec![],
+                    },
+                    EvmTokenBalance {
+                        token: erc721,
+                        standard: EvmTokenStandard::Erc721,
+                        amount: 1.into(),
+                        token_ids: vec![7.into()],
+                    },
+                ]
+            );
+            assert_eq!(
+                blockstore.read_evm_token_balances(bob).unwrap(),
+                vec![EvmTokenBalance {
+                    token: erc20,
+                    standard: EvmTokenStandard::Erc20,
+                    amount: 40.into(),
+                    token_ids: vec![],
+                }]
+            );
+            // Mint source is not indexed
+            assert!(blockstore
+                .find_evm_token_transfers(H160::zero(), None, None, 10)
+                .unwrap()
+                .is_empty());
+        }
+        Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
+    }
 }
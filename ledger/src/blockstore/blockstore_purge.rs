@@ -364,6 +364,14 @@ impl Blockstore {
                 .evm_transactions_cf
                 .compact_range(0, 2)
                 .unwrap_or(false)
+            && self
+                .evm_logs_by_address_cf
+                .compact_range(0, 2)
+                .unwrap_or(false)
+            && self
+                .evm_logs_by_topic_cf
+                .compact_range(0, 2)
+                .unwrap_or(false)
             && match evm_block_range {
                 Some((from, to)) => self.evm_blocks_cf.compact_range(from, to),
                 None => self.evm_blocks_cf.compact_range(u64::MIN, u64::MAX),
@@ -399,11 +407,22 @@ impl Blockstore {
                 .flat_map(|entry| entry.transactions)
             {
                 if let Some(&signature) = transaction.signatures.get(0) {
+                    // Accounts loaded from lookup tables are only known from the status meta
+                    let loaded_addresses = self
+                        .read_transaction_status((signature, slot))?
+                        .and_then(|meta| meta.loaded_addresses)
+                        .unwrap_or_default();
                     batch.delete::<cf::TransactionStatus>((0, signature, slot))?;
                     batch.delete::<cf::TransactionStatus>((1, signature, slot))?;
-                    for pubkey in transaction.message.account_keys {
-                        batch.delete::<cf::AddressSignatures>((0, pubkey, slot, signature))?;
-                        batch.delete::<cf::AddressSignatures>((1, pubkey, slot, signature))?;
+                    for pubkey in transaction
+                        .message
+                        .static_account_keys()
+                        .iter()
+                        .chain(&loaded_addresses.writable)
+                        .chain(&loaded_addresses.readonly)
+                    {
+                        batch.delete::<cf::AddressSignatures>((0, *pubkey, slot, signature))?;
+                        batch.delete::<cf::AddressSignatures>((1, *pubkey, slot, signature))?;
                     }
                 }
             }
@@ -413,6 +432,29 @@ impl Blockstore {
                         batch.delete::<cf::EvmHeaderIndexByHash>((0, header.hash()))?;
                         batch.delete::<cf::EvmHeaderIndexByHash>((1, header.hash()))?;
                         for tx_hash in header.transactions {
+                            if let Ok(Some(receipt)) =
+                                self.read_evm_transaction((tx_hash, block_num, Some(slot)))
+                            {
+                                for (log_index, log) in receipt.logs.iter().enumerate() {
+                                    let location = EvmLogLocation {
+                                        block_num,
+                                        transaction_id: receipt.index,
+                                        log_index: log_index as u64,
+                                    };
+                                    for index in 0..=1 {
+                                        batch.delete::<cf::EvmLogsByAddress>((
+                                            index,
+                                            log.address,
+                                            location,
+                                        ))?;
+                                        if let Some(topic) = log.topics.first() {
+                                            batch.delete::<cf::EvmLogsByTopic>((
+                                                index, *topic, location,
+                                            ))?;
+                                        }
+                                    }
+                                }
+                            }
                             for slot in std::iter::once(Some(slot)).chain(None) {
                                 for index in 0..=1 {
                                     batch.delete::<cf::EvmTransactionReceipts>(
@@ -486,6 +528,22 @@ impl Blockstore {
                         purged_index,
                         purged_index + 1,
                     )
+                    .is_ok()
+                & self
+                    .db
+                    .delete_range_cf::<cf::EvmLogsByAddress>(
+                        write_batch,
+                        purged_index,
+                        purged_index + 1,
+                    )
+                    .is_ok()
+                & self
+                    .db
+                    .delete_range_cf::<cf::EvmLogsByTopic>(
+                        write_batch,
+                        purged_index,
+                        purged_index + 1,
+                    )
                     .is_ok();
         }
         Ok(())
@@ -501,8 +559,9 @@ pub mod tests {
     };
     use bincode::serialize;
     use evm_state::{
-        BlockHeader, BlockNum, ExitReason, ExitSucceed, TransactionAction, TransactionInReceipt,
-        TransactionReceipt, UnsignedTransaction, UnsignedTransactionWithCaller,
+        BlockHeader, BlockNum, ExitReason, ExitSucceed, Log, TransactionAction,
+        TransactionInReceipt, TransactionReceipt, UnsignedTransaction,
+        UnsignedTransactionWithCaller, H160,
     };
     use solana_sdk::{
         hash::{hash, Hash},
@@ -1396,6 +1455,8 @@ pub mod tests {
 
         let mut block_hashes = vec![];
         let mut tx_hashes = vec![];
+        let log_address = H160::repeat_byte(0x11);
+        let log_topic = H256::repeat_byte(0x22);
 
         for block_num in 0..5 {
             let transaction = create_dummy_evm_transaction();
@@ -1411,7 +1472,11 @@ pub mod tests {
                 index: 0,
                 used_gas: 0,
                 logs_bloom: Default::default(),
-                logs: vec![],
+                logs: vec![Log {
+                    address: log_address,
+                    topics: vec![log_topic],
+                    data: vec![],
+                }],
             };
             let evm_block = BlockHeader {
                 parent_hash: Default::default(),
@@ -1463,6 +1528,96 @@ pub mod tests {
             .unwrap()
             .is_some());
 
+        // check that log indexes of purged blocks are removed
+        let blocks_with_logs = |locations: Vec<EvmLogLocation>| -> Vec<BlockNum> {
+            locations
+                .into_iter()
+                .map(|location| location.block_num)
+                .collect()
+        };
+        assert_eq!(
+            blocks_with_logs(
+                blockstore
+                    .find_evm_logs_by_address(log_address, 0, 4)
+                    .unwrap()
+            ),
+            vec![2, 3, 4]
+        );
+        assert_eq!(
+            blocks_with_logs(blockstore.find_evm_logs_by_topic(log_topic, 0, 3).unwrap()),
+            vec![2, 3]
+        );
+
+        drop(blockstore);
+        Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
+    }
+
+    #[test]
+    fn test_purge_keeps_evm_token_index() {
+        use evm_state::{Block, U256};
+        use solana_transaction_status::parse_evm_token::TRANSFER_EVENT_TOPIC;
+
+        let blockstore_path = get_tmp_ledger_path!();
+        let blockstore = Blockstore::open(&blockstore_path).unwrap();
+        let token = H160::repeat_byte(0xaa);
+        let holder = H160::repeat_byte(1);
+
+        for block_num in 0..3 {
+            let transaction = create_dummy_evm_transaction();
+            let mut data = vec![0; 32];
+            U256::from(10).to_big_endian(&mut data);
+            let receipt = TransactionReceipt {
+                transaction: TransactionInReceipt::Unsigned(UnsignedTransactionWithCaller {
+                    unsigned_tx: transaction.clone(),
+                    caller: Default::default(),
+                    chain_id: 0,
+                    signed_compatible: false,
+                }),
+                status: ExitReason::Succeed(ExitSucceed::Stopped),
+                block_number: block_num,
+                index: 0,
+                used_gas: 0,
+                logs_bloom: Default::default(),
+                logs: vec![Log {
+                    address: token,
+                    topics: vec![
+                        TRANSFER_EVENT_TOPIC,
+                        H256::from(H160::zero()),
+                        H256::from(holder),
+                    ],
+                    data,
+                }],
+            };
+            let header = create_dummy_evm_block(block_num, block_num);
+            blockstore.write_evm_block_header(&header).unwrap();
+            blockstore
+                .write_evm_token_transfers(&Block {
+                    header,
+                    transactions: vec![(transaction.signing_hash(None), receipt)],
+                })
+                .unwrap();
+        }
+
+        blockstore.purge_and_compact_slots(0, 1);
+        test_all_empty_or_min(&blockstore, 2);
+
+        // Token index is never purged, balances are accumulated over its whole history
+        assert_eq!(
+            blockstore
+                .find_evm_token_transfers(holder, None, None, 10)
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_eq!(
+            blockstore.read_evm_token_balances(holder).unwrap()[0].amount,
+            30.into()
+        );
+        assert_eq!(
+            blockstore.read_evm_token_index_range().unwrap(),
+            Some((0, 2))
+        );
+
         drop(blockstore);
         Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
     }
@@ -1,8 +1,11 @@
 use crate::blockstore_meta;
 use bincode::{deserialize, serialize};
 use byteorder::{BigEndian, ByteOrder};
-use columns::{EvmBlockHeader, EvmHeaderIndexByHash, EvmHeaderIndexBySlot, EvmTransactionReceipts};
-use evm_state::{BlockNum, H256};
+use columns::{
+    EvmBlockHeader, EvmHeaderIndexByHash, EvmHeaderIndexBySlot, EvmLogsByAddress, EvmLogsByTopic,
+    EvmTokenBalances, EvmTokenIndexProgress, EvmTokenTransfers, EvmTransactionReceipts,
+};
+use evm_state::{BlockNum, H160, H256};
 use log::*;
 use prost::Message;
 pub use rocksdb::Direction as IteratorDirection;
@@ -81,6 +84,11 @@ const EVM_HEADERS: &str = "evm_headers";
 const EVM_BLOCK_BY_HASH: &str = "evm_block_by_hash";
 const EVM_BLOCK_BY_SLOT: &str = "evm_block_by_slot";
 const EVM_TRANSACTIONS: &str = "evm_transactions";
+const EVM_LOGS_BY_ADDRESS: &str = "evm_logs_by_address";
+const EVM_LOGS_BY_TOPIC: &str = "evm_logs_by_topic";
+const EVM_TOKEN_TRANSFERS: &str = "evm_token_transfers";
+const EVM_TOKEN_BALANCES: &str = "evm_token_balances";
+const EVM_TOKEN_INDEX_PROGRESS: &str = "evm_token_index_progress";
 
 #[derive(Error, Debug)]
 pub enum BlockstoreError {
@@ -201,6 +209,29 @@ pub mod columns {
     #[derive(Debug)]
     /// The evm transaction with statuses.
     pub struct EvmTransactionReceipts;
+
+    #[derive(Debug)]
+    /// The evm logs location by emitting contract address.
+    pub struct EvmLogsByAddress;
+
+    #[derive(Debug)]
+    /// The evm logs location by first topic.
+    pub struct EvmLogsByTopic;
+
+    #[derive(Debug)]
+    /// The ERC-20/ERC-721 token transfers by holder address, in chronological order.
+    ///
+    /// Token columns are never purged: balances are accumulated over the whole indexed history,
+    /// so they are excluded from compaction filters and from `run_purge`.
+    pub struct EvmTokenTransfers;
+
+    #[derive(Debug)]
+    /// The ERC-20/ERC-721 token balances by holder address and token contract.
+    pub struct EvmTokenBalances;
+
+    #[derive(Debug)]
+    /// The last (index 0) and the first (index 1) evm blocks processed by token transfers indexer.
+    pub struct EvmTokenIndexProgress;
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -211,6 +242,33 @@ pub struct EvmTransactionReceiptsIndex {
     pub slot: Option<Slot>,
 }
 
+/// Location of a single log, used as key suffix in `EvmLogsByAddress`, `EvmLogsByTopic`
+/// and `EvmTokenTransfers`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EvmLogLocation {
+    pub block_num: evm_state::BlockNum,
+    pub transaction_id: u64,
+    pub log_index: u64,
+}
+
+impl EvmLogLocation {
+    const SIZE: usize = 8 + 8 + 8; // size_of BlockNum + size_of u64 + size_of u64
+
+    fn write(&self, key: &mut [u8]) {
+        BigEndian::write_u64(&mut key[0..8], self.block_num);
+        BigEndian::write_u64(&mut key[8..16], self.transaction_id);
+        BigEndian::write_u64(&mut key[16..24], self.log_index);
+    }
+
+    fn read(key: &[u8]) -> Self {
+        Self {
+            block_num: BigEndian::read_u64(&key[0..8]),
+            transaction_id: BigEndian::read_u64(&key[8..16]),
+            log_index: BigEndian::read_u64(&key[16..24]),
+        }
+    }
+}
+
 pub enum AccessType {
     PrimaryOnly,
     PrimaryOnlyForMaintenance, // this indicates no compaction
@@ -408,6 +466,26 @@ impl Rocks {
             EvmTransactionReceipts::NAME,
             get_cf_options::<EvmTransactionReceipts>(&access_type, &oldest_slot, &oldest_block_num),
         );
+        let evm_logs_by_address_cf_descriptor = ColumnFamilyDescriptor::new(
+            EvmLogsByAddress::NAME,
+            get_cf_options::<EvmLogsByAddress>(&access_type, &oldest_slot, &oldest_block_num),
+        );
+        let evm_logs_by_topic_cf_descriptor = ColumnFamilyDescriptor::new(
+            EvmLogsByTopic::NAME,
+            get_cf_options::<EvmLogsByTopic>(&access_type, &oldest_slot, &oldest_block_num),
+        );
+        let evm_token_transfers_cf_descriptor = ColumnFamilyDescriptor::new(
+            EvmTokenTransfers::NAME,
+            get_cf_options::<EvmTokenTransfers>(&access_type, &oldest_slot, &oldest_block_num),
+        );
+        let evm_token_balances_cf_descriptor = ColumnFamilyDescriptor::new(
+            EvmTokenBalances::NAME,
+            get_cf_options::<EvmTokenBalances>(&access_type, &oldest_slot, &oldest_block_num),
+        );
+        let evm_token_index_progress_cf_descriptor = ColumnFamilyDescriptor::new(
+            EvmTokenIndexProgress::NAME,
+            get_cf_options::<EvmTokenIndexProgress>(&access_type, &oldest_slot, &oldest_block_num),
+        );
 
         let cfs = vec![
             (SlotMeta::NAME, meta_cf_descriptor),
@@ -440,6 +518,14 @@ impl Rocks {
                 EvmHeaderIndexBySlot::NAME,
                 evm_headers_by_slot_cf_descriptor,
             ),
+            (EvmLogsByAddress::NAME, evm_logs_by_address_cf_descriptor),
+            (EvmLogsByTopic::NAME, evm_logs_by_topic_cf_descriptor),
+            (EvmTokenTransfers::NAME, evm_token_transfers_cf_descriptor),
+            (EvmTokenBalances::NAME, evm_token_balances_cf_descriptor),
+            (
+                EvmTokenIndexProgress::NAME,
+                evm_token_index_progress_cf_descriptor,
+            ),
         ];
         let cf_names: Vec<_> = cfs.iter().map(|c| c.0).collect();
 
@@ -485,7 +571,12 @@ impl Rocks {
                 // this special column family must be excluded from LedgerCleanupService's rocksdb
                 // compactions
                 if cf_name == TransactionStatusIndex::NAME || cf_name == EvmTransactionReceipts::NAME
-                    || cf_name == EvmHeaderIndexByHash::NAME {
+                    || cf_name == EvmHeaderIndexByHash::NAME
+                    || cf_name == EvmLogsByAddress::NAME
+                    || cf_name == EvmLogsByTopic::NAME
+                    || cf_name == EvmTokenTransfers::NAME
+                    || cf_name == EvmTokenBalances::NAME
+                    || cf_name == EvmTokenIndexProgress::NAME {
                     continue;
                 }
 
@@ -569,6 +660,11 @@ impl Rocks {
             EvmTransactionReceipts::NAME,
             EvmHeaderIndexByHash::NAME,
             EvmHeaderIndexBySlot::NAME,
+            EvmLogsByAddress::NAME,
+            EvmLogsByTopic::NAME,
+            EvmTokenTransfers::NAME,
+            EvmTokenBalances::NAME,
+            EvmTokenIndexProgress::NAME,
         ]
     }
 
@@ -1157,6 +1253,195 @@ impl ColumnName for columns::EvmTransactionReceipts {
 impl ProtobufColumn for columns::EvmTransactionReceipts {
     type Type = generated_evm::TransactionReceipt;
 }
+
+impl Column for columns::EvmLogsByAddress {
+    type Index = (u64, H160, EvmLogLocation);
+
+    fn key((index, address, location): (u64, H160, EvmLogLocation)) -> Vec<u8> {
+        let mut key = vec![0; 8 + 20 + EvmLogLocation::SIZE]; // size_of u64 + size_of H160 + size_of EvmLogLocation
+        BigEndian::write_u64(&mut key[0..8], index);
+        key[8..28].clone_from_slice(address.as_bytes());
+        location.write(&mut key[28..]);
+        key
+    }
+
+    fn index(key: &[u8]) -> (u64, H160, EvmLogLocation) {
+        if key.len() != 8 + 20 + EvmLogLocation::SIZE {
+            return Self::as_index(0);
+        }
+        let index = BigEndian::read_u64(&key[0..8]);
+        let address = H160::from_slice(&key[8..28]);
+        (index, address, EvmLogLocation::read(&key[28..]))
+    }
+
+    fn primary_index(index: Self::Index) -> u64 {
+        index.0
+    }
+
+    fn as_index(index: u64) -> Self::Index {
+        (index, H160::default(), EvmLogLocation::default())
+    }
+
+    fn slot(_index: Self::Index) -> Slot {
+        unimplemented!()
+    }
+}
+
+impl ColumnName for columns::EvmLogsByAddress {
+    const NAME: &'static str = EVM_LOGS_BY_ADDRESS;
+}
+
+impl Column for columns::EvmLogsByTopic {
+    type Index = (u64, H256, EvmLogLocation);
+
+    fn key((index, topic, location): (u64, H256, EvmLogLocation)) -> Vec<u8> {
+        let mut key = vec![0; 8 + 32 + EvmLogLocation::SIZE]; // size_of u64 + size_of H256 + size_of EvmLogLocation
+        BigEndian::write_u64(&mut key[0..8], index);
+        key[8..40].clone_from_slice(topic.as_bytes());
+        location.write(&mut key[40..]);
+        key
+    }
+
+    fn index(key: &[u8]) -> (u64, H256, EvmLogLocation) {
+        if key.len() != 8 + 32 + EvmLogLocation::SIZE {
+            return Self::as_index(0);
+        }
+        let index = BigEndian::read_u64(&key[0..8]);
+        let topic = H256::from_slice(&key[8..40]);
+        (index, topic, EvmLogLocation::read(&key[40..]))
+    }
+
+    fn primary_index(index: Self::Index) -> u64 {
+        index.0
+    }
+
+    fn as_index(index: u64) -> Self::Index {
+        (index, H256::default(), EvmLogLocation::default())
+    }
+
+    fn slot(_index: Self::Index) -> Slot {
+        unimplemented!()
+    }
+}
+
+impl ColumnName for columns::EvmLogsByTopic {
+    const NAME: &'static str = EVM_LOGS_BY_TOPIC;
+}
+
+impl Column for columns::EvmTokenTransfers {
+    type Index = (H160, EvmLogLocation);
+
+    fn key((holder, location): (H160, EvmLogLocation)) -> Vec<u8> {
+        let mut key = vec![0; 20 + EvmLogLocation::SIZE]; // size_of H160 + size_of EvmLogLocation
+        key[0..20].clone_from_slice(holder.as_bytes());
+        location.write(&mut key[20..]);
+        key
+    }
+
+    fn index(key: &[u8]) -> (H160, EvmLogLocation) {
+        if key.len() != 20 + EvmLogLocation::SIZE {
+            return Self::as_index(0);
+        }
+        let holder = H160::from_slice(&key[0..20]);
+        (holder, EvmLogLocation::read(&key[20..]))
+    }
+
+    // Never purged, so there is a single primary index
+    fn primary_index(_index: Self::Index) -> u64 {
+        0
+    }
+
+    fn as_index(_index: u64) -> Self::Index {
+        (H160::default(), EvmLogLocation::default())
+    }
+
+    fn slot(_index: Self::Index) -> Slot {
+        unimplemented!()
+    }
+}
+
+impl ColumnName for columns::EvmTokenTransfers {
+    const NAME: &'static str = EVM_TOKEN_TRANSFERS;
+}
+
+impl TypedColumn for columns::EvmTokenTransfers {
+    type Type = solana_transaction_status::parse_evm_token::EvmTokenTransfer;
+}
+
+impl Column for columns::EvmTokenBalances {
+    type Index = (H160, H160);
+
+    fn key((holder, token): (H160, H160)) -> Vec<u8> {
+        let mut key = vec![0; 20 + 20]; // size_of H160 + size_of H160
+        key[0..20].clone_from_slice(holder.as_bytes());
+        key[20..40].clone_from_slice(token.as_bytes());
+        key
+    }
+
+    fn index(key: &[u8]) -> (H160, H160) {
+        if key.len() != 20 + 20 {
+            return Self::as_index(0);
+        }
+        let holder = H160::from_slice(&key[0..20]);
+        let token = H160::from_slice(&key[20..40]);
+        (holder, token)
+    }
+
+    // Never purged, so there is a single primary index
+    fn primary_index(_index: Self::Index) -> u64 {
+        0
+    }
+
+    fn as_index(_index: u64) -> Self::Index {
+        (H160::default(), H160::default())
+    }
+
+    fn slot(_index: Self::Index) -> Slot {
+        unimplemented!()
+    }
+}
+
+impl ColumnName for columns::EvmTokenBalances {
+    const NAME: &'static str = EVM_TOKEN_BALANCES;
+}
+
+impl TypedColumn for columns::EvmTokenBalances {
+    type Type = solana_transaction_status::parse_evm_token::EvmTokenBalance;
+}
+
+impl Column for columns::EvmTokenIndexProgress {
+    type Index = u64;
+
+    fn key(index: u64) -> Vec<u8> {
+        let mut key = vec![0; 8];
+        BigEndian::write_u64(&mut key[..], index);
+        key
+    }
+
+    fn index(key: &[u8]) -> u64 {
+        BigEndian::read_u64(&key[..8])
+    }
+
+    fn primary_index(index: u64) -> u64 {
+        index
+    }
+
+    fn as_index(index: u64) -> u64 {
+        index
+    }
+
+    fn slot(_index: Self::Index) -> Slot {
+        unimplemented!()
+    }
+}
+
+impl ColumnName for columns::EvmTokenIndexProgress {
+    const NAME: &'static str = EVM_TOKEN_INDEX_PROGRESS;
+}
+
+impl TypedColumn for columns::EvmTokenIndexProgress {
+    type Type = evm_state::BlockNum;
+}
 #[derive(Debug, Clone)]
 pub struct Database {
     backend: Arc<Rocks>,
@@ -1603,6 +1888,11 @@ fn get_cf_options<C: 'static + Column + ColumnName>(
         && C::NAME != columns::EvmTransactionReceipts::NAME
         && C::NAME != columns::EvmHeaderIndexByHash::NAME
         && C::NAME != columns::EvmBlockHeader::NAME
+        && C::NAME != columns::EvmLogsByAddress::NAME
+        && C::NAME != columns::EvmLogsByTopic::NAME
+        && C::NAME != columns::EvmTokenTransfers::NAME
+        && C::NAME != columns::EvmTokenBalances::NAME
+        && C::NAME != columns::EvmTokenIndexProgress::NAME
     {
         options.set_compaction_filter_factory(PurgedSlotFilterFactory::<C> {
             oldest_slot: oldest_slot.clone(),
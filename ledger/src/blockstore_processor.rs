@@ -25,6 +25,7 @@ use solana_runtime::{
     bank_forks::BankForks,
     bank_utils,
     commitment::VOTE_THRESHOLD_SIZE,
+    hashed_transaction::HashedTransaction,
     transaction_batch::TransactionBatch,
     vote_account::ArcVoteAccount,
     vote_sender_types::ReplayVoteSender,
@@ -33,10 +34,11 @@ use solana_sdk::{
     clock::{Slot, MAX_PROCESSING_AGE},
     genesis_config::GenesisConfig,
     hash::Hash,
+    process_instruction::TransactionReturnData,
     pubkey::Pubkey,
     signature::{Keypair, Signature},
     timing,
-    transaction::{Result, Transaction, TransactionError},
+    transaction::{Result, TransactionError},
 };
 use solana_transaction_status::token_balances::{
     collect_token_balances, TransactionTokenBalancesSet,
@@ -116,7 +118,7 @@ fn execute_batch(
         vec![]
     };
 
-    let (tx_results, balances, inner_instructions, transaction_logs) =
+    let (tx_results, balances, inner_instructions, transaction_logs, return_data) =
         batch.bank().load_execute_and_commit_transactions(
             batch,
             MAX_PROCESSING_AGE,
@@ -136,7 +138,12 @@ fn execute_batch(
     } = tx_results;
 
     if let Some(transaction_status_sender) = transaction_status_sender {
-        let txs = batch.transactions_iter().cloned().collect();
+        let txs = batch
+            .hashed_transactions()
+            .iter()
+            .cloned()
+            .map(HashedTransaction::into_owned)
+            .collect();
         let post_token_balances = if record_token_balances {
             collect_token_balances(bank, batch, &mut mint_decimals)
         } else {
@@ -154,6 +161,7 @@ fn execute_batch(
             token_balances,
             inner_instructions,
             transaction_logs,
+            return_data,
             rent_debits,
         );
     }
@@ -214,7 +222,7 @@ pub fn process_entries(
     replay_vote_sender: Option<&ReplayVoteSender>,
 ) -> Result<()> {
     let mut timings = ExecuteTimings::default();
-    let mut entry_types: Vec<_> = entries.iter().map(EntryType::from).collect();
+    let mut entry_types = entries.verify_and_hash_transactions(true, bank)?;
     let result = process_entries_with_callback(
         bank,
         &mut entry_types,
@@ -761,15 +769,19 @@ pub fn confirm_slot(
     };
 
     let check_start = Instant::now();
-    let check_result =
-        entries.verify_and_hash_transactions(skip_verification, bank.secp256k1_program_enabled());
-    if check_result.is_none() {
-        warn!("Ledger proof of history failed at slot: {}", slot);
-        return Err(BlockError::InvalidEntryHash.into());
-    }
+    let check_result = entries.verify_and_hash_transactions(skip_verification, bank);
+    let mut entries = match check_result {
+        Ok(entries) => entries,
+        Err(err) => {
+            warn!(
+                "Ledger transaction verification failed at slot: {}: {:?}",
+                slot, err
+            );
+            return Err(err.into());
+        }
+    };
     let transaction_duration_us = timing::duration_as_us(&check_start.elapsed());
 
-    let mut entries = check_result.unwrap();
     let mut replay_elapsed = Measure::start("replay_elapsed");
     let mut execute_timings = ExecuteTimings::default();
     // Note: This will shuffle entries' transactions in-place.
@@ -1183,12 +1195,13 @@ pub enum TransactionStatusMessage {
 
 pub struct TransactionStatusBatch {
     pub bank: Arc<Bank>,
-    pub transactions: Vec<Transaction>,
+    pub transactions: Vec<HashedTransaction<'static>>,
     pub statuses: Vec<TransactionExecutionResult>,
     pub balances: TransactionBalancesSet,
     pub token_balances: TransactionTokenBalancesSet,
     pub inner_instructions: Option<Vec<Option<InnerInstructionsList>>>,
     pub transaction_logs: Option<Vec<TransactionLogMessages>>,
+    pub return_data: Vec<Option<TransactionReturnData>>,
     pub rent_debits: Vec<RentDebits>,
 }
 
@@ -1199,15 +1212,17 @@ pub struct TransactionStatusSender {
 }
 
 impl TransactionStatusSender {
+    #[allow(clippy::too_many_arguments)]
     pub fn send_transaction_status_batch(
         &self,
         bank: Arc<Bank>,
-        transactions: Vec<Transaction>,
+        transactions: Vec<HashedTransaction<'static>>,
         statuses: Vec<TransactionExecutionResult>,
         balances: TransactionBalancesSet,
         token_balances: TransactionTokenBalancesSet,
         inner_instructions: Vec<Option<InnerInstructionsList>>,
         transaction_logs: Vec<TransactionLogMessages>,
+        return_data: Vec<Option<TransactionReturnData>>,
         rent_debits: Vec<RentDebits>,
     ) {
         let slot = bank.slot();
@@ -1226,6 +1241,7 @@ impl TransactionStatusSender {
                 token_balances,
                 inner_instructions,
                 transaction_logs,
+                return_data,
                 rent_debits,
             }))
         {
@@ -1309,11 +1325,13 @@ pub mod tests {
         account::{AccountSharedData, WritableAccount},
         epoch_schedule::EpochSchedule,
         hash::Hash,
+        instruction::CompiledInstruction,
+        message::{v0, MessageHeader, VersionedMessage},
         pubkey::Pubkey,
         signature::{Keypair, Signer},
-        system_instruction::SystemError,
-        system_transaction,
-        transaction::{Transaction, TransactionError},
+        system_instruction::{SystemError, SystemInstruction},
+        system_program, system_transaction,
+        transaction::{Transaction, TransactionError, VersionedTransaction},
     };
     use solana_vote_program::{
         self,
@@ -2596,8 +2614,16 @@ pub mod tests {
         assert_eq!(bank.get_balance(&keypair2.pubkey()), 4);
 
         // Check all accounts are unlocked
-        let txs1 = &entry_1_to_mint.transactions[..];
-        let txs2 = &entry_2_to_3_mint_to_1.transactions[..];
+        let txs1 = entry_1_to_mint
+            .transactions
+            .into_iter()
+            .map(|tx| tx.into_legacy_transaction().unwrap())
+            .collect::<Vec<_>>();
+        let txs2 = entry_2_to_3_mint_to_1
+            .transactions
+            .into_iter()
+            .map(|tx| tx.into_legacy_transaction().unwrap())
+            .collect::<Vec<_>>();
         let batch1 = bank.prepare_batch(txs1.iter());
         for result in batch1.lock_results() {
             assert!(result.is_ok());
@@ -2755,6 +2781,59 @@ pub mod tests {
         assert_eq!(bank.last_blockhash(), blockhash);
     }
 
+    #[test]
+    fn test_process_entries_versioned_transaction() {
+        let GenesisConfigInfo {
+            mut genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(1000);
+        let recipient = Pubkey::new_unique();
+        let versioned_tx = |blockhash| {
+            let message = v0::Message {
+                header: MessageHeader {
+                    num_required_signatures: 1,
+                    num_readonly_signed_accounts: 0,
+                    num_readonly_unsigned_accounts: 1,
+                },
+                account_keys: vec![mint_keypair.pubkey(), recipient, system_program::id()],
+                recent_blockhash: blockhash,
+                instructions: vec![CompiledInstruction::new(
+                    2,
+                    &SystemInstruction::Transfer { lamports: 1 },
+                    vec![0, 1],
+                )],
+                address_table_lookups: vec![],
+            };
+            VersionedTransaction::try_new(VersionedMessage::V0(message), &[&mint_keypair]).unwrap()
+        };
+
+        // Versioned messages are rejected until the feature is activated
+        let bank = Arc::new(Bank::new(&genesis_config));
+        let entry = Entry::new_versioned(
+            &bank.last_blockhash(),
+            1,
+            vec![versioned_tx(bank.last_blockhash())],
+        );
+        assert_eq!(
+            process_entries(&bank, &mut [entry], true, None, None),
+            Err(TransactionError::UnsupportedVersion)
+        );
+
+        genesis_utils::activate_all_features(&mut genesis_config);
+        let bank = Arc::new(Bank::new(&genesis_config));
+        let entry = Entry::new_versioned(
+            &bank.last_blockhash(),
+            1,
+            vec![versioned_tx(bank.last_blockhash())],
+        );
+        assert_eq!(
+            process_entries(&bank, &mut [entry], true, None, None),
+            Ok(())
+        );
+        assert_eq!(bank.get_balance(&recipient), 1);
+    }
+
     #[test]
     fn test_process_entry_tx_random_execution_with_error() {
         let GenesisConfigInfo {
@@ -3390,6 +3469,7 @@ pub mod tests {
             _balances,
             _inner_instructions,
             _log_messages,
+            _return_data,
         ) = batch.bank().load_execute_and_commit_transactions(
             &batch,
             MAX_PROCESSING_AGE,
@@ -17,12 +17,12 @@ use solana_perf::cuda_runtime::PinnedVec;
 use solana_perf::perf_libs;
 use solana_perf::recycler::Recycler;
 use solana_rayon_threadlimit::get_thread_count;
+use solana_runtime::bank::Bank;
 use solana_runtime::hashed_transaction::HashedTransaction;
 use solana_sdk::hash::Hash;
 use solana_sdk::packet::PACKET_DATA_SIZE;
 use solana_sdk::timing;
-use solana_sdk::transaction::Transaction;
-use std::borrow::Cow;
+use solana_sdk::transaction::{Result, Transaction, TransactionError, VersionedTransaction};
 use std::cell::RefCell;
 use std::ffi::OsStr;
 use std::sync::mpsc::{Receiver, Sender};
@@ -117,7 +117,7 @@ pub struct Entry {
     /// An unordered list of transactions that were observed before the Entry ID was
     /// generated. They may have been observed before a previous Entry ID but were
     /// pushed back into this list to ensure deterministic interpretation of the ledger.
-    pub transactions: Vec<Transaction>,
+    pub transactions: Vec<VersionedTransaction>,
 }
 
 /// Typed entry to distinguish between transaction and tick entries
@@ -126,25 +126,20 @@ pub enum EntryType<'a> {
     Tick(Hash),
 }
 
-impl<'a> From<&'a Entry> for EntryType<'a> {
-    fn from(entry: &'a Entry) -> Self {
-        if entry.transactions.is_empty() {
-            EntryType::Tick(entry.hash)
-        } else {
-            EntryType::Transactions(
-                entry
-                    .transactions
-                    .iter()
-                    .map(HashedTransaction::from)
-                    .collect(),
-            )
-        }
-    }
-}
-
 impl Entry {
     /// Creates the next Entry `num_hashes` after `start_hash`.
-    pub fn new(prev_hash: &Hash, mut num_hashes: u64, transactions: Vec<Transaction>) -> Self {
+    pub fn new(prev_hash: &Hash, num_hashes: u64, transactions: Vec<Transaction>) -> Self {
+        let transactions = transactions.into_iter().map(Into::into).collect();
+        Self::new_versioned(prev_hash, num_hashes, transactions)
+    }
+
+    /// Creates the next Entry `num_hashes` after `start_hash`, with transactions of any
+    /// message version.
+    pub fn new_versioned(
+        prev_hash: &Hash,
+        mut num_hashes: u64,
+        transactions: Vec<VersionedTransaction>,
+    ) -> Self {
         // If you passed in transactions, but passed in num_hashes == 0, then
         // next_hash will generate the next hash and set num_hashes == 1
         if num_hashes == 0 && !transactions.is_empty() {
@@ -199,7 +194,7 @@ impl Entry {
     }
 }
 
-pub fn hash_transactions(transactions: &[Transaction]) -> Hash {
+pub fn hash_transactions(transactions: &[VersionedTransaction]) -> Hash {
     // a hash of a slice of transactions only needs to hash the signatures
     let signatures: Vec<_> = transactions
         .iter()
@@ -217,7 +212,11 @@ pub fn hash_transactions(transactions: &[Transaction]) -> Hash {
 /// a signature, the final hash will be a hash of both the previous ID and
 /// the signature.  If num_hashes is zero and there's no transaction data,
 ///  start_hash is returned.
-pub fn next_hash(start_hash: &Hash, num_hashes: u64, transactions: &[Transaction]) -> Hash {
+pub fn next_hash(
+    start_hash: &Hash,
+    num_hashes: u64,
+    transactions: &[VersionedTransaction],
+) -> Hash {
     if num_hashes == 0 && transactions.is_empty() {
         return *start_hash;
     }
@@ -364,11 +363,13 @@ pub trait EntrySlice {
     fn verify_tick_hash_count(&self, tick_hash_count: &mut u64, hashes_per_tick: u64) -> bool;
     /// Counts tick entries
     fn tick_count(&self) -> u64;
+    /// Verifies the transactions of the entries unless `skip_verification`, and expands them
+    /// into legacy transactions with the accounts loaded from lookup tables of `bank`
     fn verify_and_hash_transactions(
         &self,
         skip_verification: bool,
-        secp256k1_program_enabled: bool,
-    ) -> Option<Vec<EntryType<'_>>>;
+        bank: &Bank,
+    ) -> Result<Vec<EntryType<'static>>>;
 }
 
 impl EntrySlice for [Entry] {
@@ -520,27 +521,28 @@ impl EntrySlice for [Entry] {
         }
     }
 
-    fn verify_and_hash_transactions<'a>(
-        &'a self,
+    fn verify_and_hash_transactions(
+        &self,
         skip_verification: bool,
-        secp256k1_program_enabled: bool,
-    ) -> Option<Vec<EntryType<'a>>> {
-        let verify_and_hash = |tx: &'a Transaction| -> Option<HashedTransaction<'a>> {
+        bank: &Bank,
+    ) -> Result<Vec<EntryType<'static>>> {
+        let verify_and_hash = |tx: &VersionedTransaction| -> Result<HashedTransaction<'static>> {
             let message_hash = if !skip_verification {
-                let size = bincode::serialized_size(tx).ok()?;
+                let size =
+                    bincode::serialized_size(tx).map_err(|_| TransactionError::SanitizeFailure)?;
                 if size > PACKET_DATA_SIZE as u64 {
-                    return None;
-                }
-                if secp256k1_program_enabled {
-                    // Verify tx precompiles if secp256k1 program is enabled.
-                    tx.verify_precompiles().ok()?;
+                    return Err(TransactionError::SanitizeFailure);
                 }
-                tx.verify_and_hash_message().ok()?
+                tx.verify_and_hash_message()?
             } else {
-                tx.message().hash()
+                tx.message.hash()
             };
+            let tx = bank.expand_versioned_transaction(tx.clone(), message_hash)?;
+            if !skip_verification {
+                tx.transaction().verify_precompiles(&bank.feature_set)?;
+            }
 
-            Some(HashedTransaction::new(Cow::Borrowed(tx), message_hash))
+            Ok(tx)
         };
 
         PAR_THREAD_POOL.with(|thread_pool| {
@@ -548,14 +550,14 @@ impl EntrySlice for [Entry] {
                 self.par_iter()
                     .map(|entry| {
                         if entry.transactions.is_empty() {
-                            Some(EntryType::Tick(entry.hash))
+                            Ok(EntryType::Tick(entry.hash))
                         } else {
-                            Some(EntryType::Transactions(
+                            Ok(EntryType::Transactions(
                                 entry
                                     .transactions
                                     .par_iter()
                                     .map(verify_and_hash)
-                                    .collect::<Option<Vec<HashedTransaction>>>()?,
+                                    .collect::<Result<Vec<HashedTransaction>>>()?,
                             ))
                         }
                     })
@@ -720,6 +722,8 @@ pub fn create_random_ticks(num_ticks: u64, max_hashes_per_tick: u64, mut hash: H
 /// Creates the next Tick or Transaction Entry `num_hashes` after `start_hash`.
 pub fn next_entry(prev_hash: &Hash, num_hashes: u64, transactions: Vec<Transaction>) -> Entry {
     assert!(num_hashes > 0 || transactions.is_empty());
+    let transactions: Vec<VersionedTransaction> =
+        transactions.into_iter().map(Into::into).collect();
     Entry {
         num_hashes,
         hash: next_hash(prev_hash, num_hashes, &transactions),
@@ -730,7 +734,7 @@ pub fn next_entry(prev_hash: &Hash, num_hashes: u64, transactions: Vec<Transacti
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::entry::Entry;
+    use crate::{entry::Entry, genesis_utils::create_genesis_config};
     use chrono::prelude::Utc;
     use solana_budget_program::budget_instruction;
     use solana_sdk::{
@@ -787,8 +791,8 @@ mod tests {
         assert!(e0.verify(&zero));
 
         // Next, swap two transactions and ensure verification fails.
-        e0.transactions[0] = tx1; // <-- attack
-        e0.transactions[1] = tx0;
+        e0.transactions[0] = tx1.into(); // <-- attack
+        e0.transactions[1] = tx0.into();
         assert!(!e0.verify(&zero));
     }
 
@@ -838,8 +842,8 @@ mod tests {
         assert!(e0.verify(&zero));
 
         // Next, swap two witness transactions and ensure verification fails.
-        e0.transactions[0] = tx1; // <-- attack
-        e0.transactions[1] = tx0;
+        e0.transactions[0] = tx1.into(); // <-- attack
+        e0.transactions[1] = tx0.into();
         assert!(!e0.verify(&zero));
     }
 
@@ -858,7 +862,7 @@ mod tests {
         let tx0 = create_sample_timestamp(&keypair, zero);
         let entry0 = next_entry(&zero, 1, vec![tx0.clone()]);
         assert_eq!(entry0.num_hashes, 1);
-        assert_eq!(entry0.hash, next_hash(&zero, 1, &[tx0]));
+        assert_eq!(entry0.hash, next_hash(&zero, 1, &[tx0.into()]));
     }
 
     #[test]
@@ -946,6 +950,7 @@ mod tests {
 
     #[test]
     fn test_verify_and_hash_transactions_packet_data_size() {
+        let bank = Bank::new(&create_genesis_config(10_000).genesis_config);
         let mut rng = rand::thread_rng();
         let recent_blockhash = hash_new_rand(&mut rng);
         let keypair = Keypair::new();
@@ -968,8 +973,8 @@ mod tests {
             let entries = vec![next_entry(&recent_blockhash, 1, vec![tx.clone()])];
             assert!(bincode::serialized_size(&tx).unwrap() <= PACKET_DATA_SIZE as u64);
             assert!(entries[..]
-                .verify_and_hash_transactions(false, false)
-                .is_some());
+                .verify_and_hash_transactions(false, &bank)
+                .is_ok());
         }
         // Big transaction.
         {
@@ -977,8 +982,8 @@ mod tests {
             let entries = vec![next_entry(&recent_blockhash, 1, vec![tx.clone()])];
             assert!(bincode::serialized_size(&tx).unwrap() > PACKET_DATA_SIZE as u64);
             assert!(entries[..]
-                .verify_and_hash_transactions(false, false)
-                .is_none());
+                .verify_and_hash_transactions(false, &bank)
+                .is_err());
         }
         // Assert that verify fails as soon as serialized
         // size exceeds packet data size.
@@ -988,8 +993,8 @@ mod tests {
             assert_eq!(
                 bincode::serialized_size(&tx).unwrap() <= PACKET_DATA_SIZE as u64,
                 entries[..]
-                    .verify_and_hash_transactions(false, false)
-                    .is_some(),
+                    .verify_and_hash_transactions(false, &bank)
+                    .is_ok(),
             );
         }
     }
@@ -997,7 +1002,7 @@ mod tests {
     #[test]
     fn test_verify_tick_hash_count() {
         let hashes_per_tick = 10;
-        let tx = Transaction::default();
+        let tx = VersionedTransaction::default();
 
         let no_hash_tx_entry = Entry {
             transactions: vec![tx.clone()],
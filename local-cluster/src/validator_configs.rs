@@ -44,6 +44,8 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         bpf_jit: config.bpf_jit,
         send_transaction_retry_ms: config.send_transaction_retry_ms,
         send_transaction_leader_forward_count: config.send_transaction_leader_forward_count,
+        send_transaction_default_max_retries: config.send_transaction_default_max_retries,
+        send_transaction_service_max_retries: config.send_transaction_service_max_retries,
         no_poh_speed_test: config.no_poh_speed_test,
         poh_pinned_cpu_core: config.poh_pinned_cpu_core,
         account_indexes: config.account_indexes.clone(),
@@ -57,6 +59,7 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         no_wait_for_vote_to_start_leader: config.no_wait_for_vote_to_start_leader,
         verify_evm_state: config.verify_evm_state,
         jaeger_collector_url: config.jaeger_collector_url.clone(),
+        accountsdb_plugin_config_files: config.accountsdb_plugin_config_files.clone(),
     }
 }
 
@@ -2,4 +2,5 @@
 pub mod counter;
 pub mod datapoint;
 mod metrics;
+pub mod prometheus;
 pub use crate::metrics::{flush, query, set_host_id, set_panic_hook, submit};
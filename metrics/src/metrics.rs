@@ -1,6 +1,7 @@
-//! The `metrics` module enables sending measurements to an `InfluxDB` instance
+//! The `metrics` module enables sending measurements to an `InfluxDB` instance,
+//! and to the Prometheus exporter when it is running
 
-use crate::{counter::CounterPoint, datapoint::DataPoint};
+use crate::{counter::CounterPoint, datapoint::DataPoint, prometheus};
 use gethostname::gethostname;
 use lazy_static::lazy_static;
 use log::*;
@@ -346,6 +347,9 @@ pub fn set_host_id(host_id: String) {
 /// Submits a new point from any thread.  Note that points are internally queued
 /// and transmitted periodically in batches.
 pub fn submit(point: DataPoint, level: log::Level) {
+    if prometheus::is_enabled() {
+        prometheus::record_point(&point, &HOST_ID.read().unwrap());
+    }
     let agent_mutex = get_singleton_agent();
     let agent = agent_mutex.lock().unwrap();
     agent.submit(point, level);
@@ -354,6 +358,9 @@ pub fn submit(point: DataPoint, level: log::Level) {
 /// Submits a new counter or updates an existing counter from any thread.  Note that points are
 /// internally queued and transmitted periodically in batches.
 pub(crate) fn submit_counter(point: CounterPoint, level: log::Level, bucket: u64) {
+    if prometheus::is_enabled() {
+        prometheus::record_counter(&point, &HOST_ID.read().unwrap());
+    }
     let agent_mutex = get_singleton_agent();
     let agent = agent_mutex.lock().unwrap();
     agent.submit_counter(point, level, bucket);
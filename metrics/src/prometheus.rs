@@ -0,0 +1,632 @@
+//! The `prometheus` module exposes the submitted counters and datapoints on an
+//! HTTP `/metrics` endpoint in the Prometheus text exposition format.
+//!
+//! Every numeric datapoint field becomes a gauge named `<datapoint>_<field>`,
+//! string fields become labels, and counters become `<counter>_total`.  Timing
+//! fields (suffixed `_us`, `_ms` or `_ns`) are also aggregated into a
+//! `<datapoint>_<field>_histogram`.  String fields named like a label set by
+//! the exporter are prefixed with `field_`.
+
+use crate::{counter::CounterPoint, datapoint::DataPoint};
+use lazy_static::lazy_static;
+use log::*;
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    fmt::Write as _,
+    hash::{Hash, Hasher},
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex, MutexGuard,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Upper bounds of the histogram buckets, in the unit of the timing field
+const HISTOGRAM_BUCKETS: [f64; 8] = [
+    1.0,
+    10.0,
+    100.0,
+    1_000.0,
+    10_000.0,
+    100_000.0,
+    1_000_000.0,
+    10_000_000.0,
+];
+const TIMING_FIELD_SUFFIXES: [&str; 3] = ["_us", "_ms", "_ns"];
+// Labels set by the exporter, which string fields must not override
+const RESERVED_LABELS: [&str; 2] = ["host_id", "le"];
+// Bounds memory use when string fields carry high cardinality values
+const MAX_SERIES_PER_METRIC: usize = 1_000;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+// Request line and headers, the body of a scrape request is never read
+const MAX_REQUEST_HEAD_BYTES: u64 = 8 * 1024;
+const MAX_CONCURRENT_REQUESTS: usize = 8;
+// Datapoints are submitted from every thread, each shard is locked on its own
+const REGISTRY_SHARDS: usize = 16;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static ACTIVE_REQUESTS: AtomicUsize = AtomicUsize::new(0);
+
+lazy_static! {
+    static ref REGISTRY: Vec<Mutex<Registry>> = (0..REGISTRY_SHARDS)
+        .map(|_| Mutex::new(Registry::default()))
+        .collect();
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Histogram {
+    buckets: [u64; HISTOGRAM_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(HISTOGRAM_BUCKETS.iter()) {
+            if value <= *bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += value;
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Series {
+    Counter(f64),
+    Gauge(f64),
+    Histogram(Histogram),
+}
+
+impl Series {
+    fn kind(&self) -> &'static str {
+        match self {
+            Series::Counter(_) => "counter",
+            Series::Gauge(_) => "gauge",
+            Series::Histogram(_) => "histogram",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum FieldValue {
+    Label(String),
+    Number(f64),
+}
+
+/// Metric name -> rendered label set -> series
+#[derive(Debug, Default)]
+struct Registry {
+    metrics: BTreeMap<String, BTreeMap<String, Series>>,
+}
+
+/// Metrics of one or more registries, by metric name and rendered label set
+type MergedMetrics<'a> = BTreeMap<&'a str, BTreeMap<&'a str, &'a Series>>;
+
+impl Registry {
+    fn series_mut(
+        &mut self,
+        name: String,
+        labels: &str,
+        new_series: impl FnOnce() -> Series,
+    ) -> Option<&mut Series> {
+        let metric = self.metrics.entry(name).or_default();
+        if !metric.contains_key(labels) && metric.len() >= MAX_SERIES_PER_METRIC {
+            return None;
+        }
+        Some(metric.entry(labels.to_string()).or_insert_with(new_series))
+    }
+
+    fn record_point(&mut self, point: &DataPoint, host_id: &str) {
+        let mut labels = vec![("host_id".to_string(), host_id.to_string())];
+        let mut values = vec![];
+        for (field, value) in &point.fields {
+            match parse_field(value) {
+                FieldValue::Label(label) => labels.push((label_name(field), label)),
+                FieldValue::Number(number) => values.push((*field, number)),
+            }
+        }
+        let labels = render_labels(&labels);
+        let point_name = sanitize_name(point.name);
+
+        for (field, value) in values {
+            let name = format!("{}_{}", point_name, sanitize_name(field));
+            if is_timing_field(field) {
+                let histogram_name = format!("{}_histogram", name);
+                if let Some(Series::Histogram(histogram)) =
+                    self.series_mut(histogram_name, &labels, || {
+                        Series::Histogram(Histogram::default())
+                    })
+                {
+                    histogram.observe(value);
+                }
+            }
+            if let Some(Series::Gauge(gauge)) =
+                self.series_mut(name, &labels, || Series::Gauge(0.0))
+            {
+                *gauge = value;
+            }
+        }
+    }
+
+    fn record_counter(&mut self, counter: &CounterPoint, host_id: &str) {
+        let name = format!("{}_total", sanitize_name(counter.name));
+        let labels = render_labels(&[("host_id".to_string(), host_id.to_string())]);
+        if let Some(Series::Counter(total)) =
+            self.series_mut(name, &labels, || Series::Counter(0.0))
+        {
+            *total += counter.count as f64;
+        }
+    }
+
+    fn render(&self) -> String {
+        render_metrics(&merge_metrics(std::iter::once(self)))
+    }
+}
+
+/// Merge the series of metrics found in several registries, datapoints whose
+/// names only differ by sanitized characters may be recorded in different shards
+fn merge_metrics<'a>(registries: impl IntoIterator<Item = &'a Registry>) -> MergedMetrics<'a> {
+    let mut merged = MergedMetrics::new();
+    for registry in registries {
+        for (name, metric) in &registry.metrics {
+            let merged_metric = merged.entry(name.as_str()).or_default();
+            for (labels, series) in metric {
+                merged_metric.entry(labels.as_str()).or_insert(series);
+            }
+        }
+    }
+    merged
+}
+
+fn render_metrics(metrics: &MergedMetrics) -> String {
+    let mut out = String::new();
+    for (name, metric) in metrics {
+        let kind = match metric.values().next() {
+            Some(series) => series.kind(),
+            None => continue,
+        };
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        // A metric has a single type, series of another type under the same name are dropped
+        for (labels, series) in metric.iter().filter(|(_, series)| series.kind() == kind) {
+            match series {
+                Series::Counter(value) | Series::Gauge(value) => {
+                    let _ = writeln!(out, "{}{{{}}} {}", name, labels, format_value(*value));
+                }
+                Series::Histogram(histogram) => {
+                    for (bound, count) in HISTOGRAM_BUCKETS.iter().zip(histogram.buckets.iter()) {
+                        let _ = writeln!(
+                            out,
+                            "{}_bucket{{{},le=\"{}\"}} {}",
+                            name, labels, bound, count
+                        );
+                    }
+                    let _ = writeln!(
+                        out,
+                        "{}_bucket{{{},le=\"+Inf\"}} {}",
+                        name, labels, histogram.count
+                    );
+                    let _ = writeln!(
+                        out,
+                        "{}_sum{{{}}} {}",
+                        name,
+                        labels,
+                        format_value(histogram.sum)
+                    );
+                    let _ = writeln!(out, "{}_count{{{}}} {}", name, labels, histogram.count);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Recover the value of a field formatted by `DataPoint::add_field_*`
+fn parse_field(value: &str) -> FieldValue {
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        return FieldValue::Label(value[1..value.len() - 1].replace("\\\"", "\""));
+    }
+    match value {
+        "true" => return FieldValue::Number(1.0),
+        "false" => return FieldValue::Number(0.0),
+        _ => {}
+    }
+    if let Some(Ok(number)) = value.strip_suffix('i').map(str::parse::<i64>) {
+        return FieldValue::Number(number as f64);
+    }
+    value
+        .parse::<f64>()
+        .map(FieldValue::Number)
+        .unwrap_or_else(|_| FieldValue::Label(value.to_string()))
+}
+
+/// Format a sample value, non-finite values are spelled the Prometheus way
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn is_timing_field(field: &str) -> bool {
+    TIMING_FIELD_SUFFIXES
+        .iter()
+        .any(|suffix| field.ends_with(suffix))
+}
+
+/// Replace the characters not allowed in Prometheus metric and label names
+fn sanitize_name(name: &str) -> String {
+    let mut sanitized: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+        sanitized.insert(0, '_');
+    }
+    sanitized
+}
+
+/// The label name of a string field
+fn label_name(field: &str) -> String {
+    let name = sanitize_name(field);
+    if RESERVED_LABELS.contains(&name.as_str()) {
+        format!("field_{}", name)
+    } else {
+        name
+    }
+}
+
+fn render_labels(labels: &[(String, String)]) -> String {
+    labels
+        .iter()
+        .map(|(name, value)| {
+            format!(
+                "{}=\"{}\"",
+                name,
+                value
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"")
+                    .replace('\n', "\\n")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+pub(crate) fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// All the series of a datapoint or counter live in the same shard
+fn registry_shard(name: &str) -> MutexGuard<'static, Registry> {
+    let mut hasher = DefaultHasher::new();
+    name.hash(&mut hasher);
+    REGISTRY[hasher.finish() as usize % REGISTRY_SHARDS]
+        .lock()
+        .unwrap()
+}
+
+pub(crate) fn record_point(point: &DataPoint, host_id: &str) {
+    registry_shard(point.name).record_point(point, host_id);
+}
+
+pub(crate) fn record_counter(counter: &CounterPoint, host_id: &str) {
+    registry_shard(counter.name).record_counter(counter, host_id);
+}
+
+fn render_registry() -> String {
+    let shards: Vec<_> = REGISTRY.iter().map(|shard| shard.lock().unwrap()).collect();
+    render_metrics(&merge_metrics(shards.iter().map(|shard| &**shard)))
+}
+
+/// Fails the reads once the deadline has passed, however slowly the client
+/// keeps sending
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining == Duration::ZERO {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        let mut stream = self.stream;
+        stream.read(buf)
+    }
+}
+
+fn handle_request(mut stream: TcpStream) -> io::Result<()> {
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut reader = BufReader::new(
+        DeadlineReader {
+            stream: &stream,
+            deadline: Instant::now() + REQUEST_TIMEOUT,
+        }
+        .take(MAX_REQUEST_HEAD_BYTES),
+    );
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut request = request_line.split_whitespace();
+    let (status, body) = match (request.next(), request.next()) {
+        (Some("GET"), Some(path)) if path == "/metrics" || path.starts_with("/metrics?") => {
+            ("200 OK", render_registry())
+        }
+        _ => ("404 Not Found", "Not Found\n".to_string()),
+    };
+
+    drop(reader);
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\n\
+         Content-Type: text/plain; version=0.0.4\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// Handle the request on its own thread so a slow client doesn't hold up the
+/// others, dropping the connection when too many requests are in flight
+fn spawn_request_handler(stream: TcpStream) {
+    if ACTIVE_REQUESTS.fetch_add(1, Ordering::Relaxed) >= MAX_CONCURRENT_REQUESTS {
+        ACTIVE_REQUESTS.fetch_sub(1, Ordering::Relaxed);
+        debug!("prometheus request dropped: too many requests in flight");
+        return;
+    }
+    let spawned = thread::Builder::new()
+        .name("solana-prometheus-request".to_string())
+        .spawn(move || {
+            if let Err(err) = handle_request(stream) {
+                debug!("prometheus request failed: {}", err);
+            }
+            ACTIVE_REQUESTS.fetch_sub(1, Ordering::Relaxed);
+        });
+    if let Err(err) = spawned {
+        ACTIVE_REQUESTS.fetch_sub(1, Ordering::Relaxed);
+        warn!("prometheus request thread failed to start: {}", err);
+    }
+}
+
+/// Serve the counters and datapoints on `http://<bind_address>/metrics`.  Only
+/// points submitted after the exporter has started are exported.
+pub fn start_exporter(bind_address: SocketAddr) -> io::Result<JoinHandle<()>> {
+    let listener = TcpListener::bind(bind_address)?;
+    info!(
+        "prometheus metrics available at http://{}/metrics",
+        listener.local_addr()?
+    );
+    ENABLED.store(true, Ordering::Relaxed);
+
+    thread::Builder::new()
+        .name("solana-prometheus-exporter".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => spawn_request_handler(stream),
+                    Err(err) => warn!("prometheus connection failed: {}", err),
+                }
+            }
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_parse_field() {
+        let mut point = DataPoint::new("point");
+        point
+            .add_field_str("str", "a \"quoted\" value")
+            .add_field_i64("int", -42)
+            .add_field_f64("float", 0.5)
+            .add_field_bool("bool", true);
+        let values: Vec<_> = point
+            .fields
+            .iter()
+            .map(|(_, value)| parse_field(value))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                FieldValue::Label("a \"quoted\" value".to_string()),
+                FieldValue::Number(-42.0),
+                FieldValue::Number(0.5),
+                FieldValue::Number(1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sanitize_name() {
+        assert_eq!(
+            sanitize_name("bank-process_transactions"),
+            "bank_process_transactions"
+        );
+        assert_eq!(sanitize_name("0x.name"), "_0x_name");
+    }
+
+    #[test]
+    fn test_render() {
+        let mut registry = Registry::default();
+        registry.record_point(
+            DataPoint::new("replay-slot-stats")
+                .add_field_str("program", "validator")
+                .add_field_i64("slot", 7)
+                .add_field_i64("execute_us", 150),
+            "host",
+        );
+        registry.record_point(
+            DataPoint::new("replay-slot-stats")
+                .add_field_str("program", "validator")
+                .add_field_i64("slot", 8)
+                .add_field_i64("execute_us", 5),
+            "host",
+        );
+        for count in &[3, 4] {
+            registry.record_counter(
+                &CounterPoint {
+                    name: "bank-process_transactions",
+                    count: *count,
+                    timestamp: 0,
+                },
+                "host",
+            );
+        }
+
+        let rendered = registry.render();
+        let labels = "host_id=\"host\",program=\"validator\"";
+        assert!(rendered.contains("# TYPE bank_process_transactions_total counter\n"));
+        assert!(rendered.contains("bank_process_transactions_total{host_id=\"host\"} 7\n"));
+        assert!(rendered.contains("# TYPE replay_slot_stats_slot gauge\n"));
+        assert!(rendered.contains(&format!("replay_slot_stats_slot{{{}}} 8\n", labels)));
+        assert!(rendered.contains(&format!("replay_slot_stats_execute_us{{{}}} 5\n", labels)));
+        assert!(rendered.contains("# TYPE replay_slot_stats_execute_us_histogram histogram\n"));
+        assert!(rendered.contains(&format!(
+            "replay_slot_stats_execute_us_histogram_bucket{{{},le=\"10\"}} 1\n",
+            labels
+        )));
+        assert!(rendered.contains(&format!(
+            "replay_slot_stats_execute_us_histogram_bucket{{{},le=\"1000\"}} 2\n",
+            labels
+        )));
+        assert!(rendered.contains(&format!(
+            "replay_slot_stats_execute_us_histogram_sum{{{}}} 155\n",
+            labels
+        )));
+        assert!(rendered.contains(&format!(
+            "replay_slot_stats_execute_us_histogram_count{{{}}} 2\n",
+            labels
+        )));
+    }
+
+    #[test]
+    fn test_render_reserved_labels() {
+        let mut registry = Registry::default();
+        registry.record_point(
+            DataPoint::new("point")
+                .add_field_str("host_id", "other")
+                .add_field_str("le", "1")
+                .add_field_i64("value", 1),
+            "host",
+        );
+        assert!(registry
+            .render()
+            .contains("point_value{host_id=\"host\",field_host_id=\"other\",field_le=\"1\"} 1\n"));
+    }
+
+    #[test]
+    fn test_render_non_finite() {
+        let mut registry = Registry::default();
+        registry.record_point(
+            DataPoint::new("point")
+                .add_field_f64("nan", f64::NAN)
+                .add_field_f64("inf", f64::INFINITY)
+                .add_field_f64("neg_inf", f64::NEG_INFINITY),
+            "host",
+        );
+        let rendered = registry.render();
+        assert!(rendered.contains("point_nan{host_id=\"host\"} NaN\n"));
+        assert!(rendered.contains("point_inf{host_id=\"host\"} +Inf\n"));
+        assert!(rendered.contains("point_neg_inf{host_id=\"host\"} -Inf\n"));
+    }
+
+    #[test]
+    fn test_merge_metrics() {
+        let mut registry0 = Registry::default();
+        let mut registry1 = Registry::default();
+        registry0.record_point(DataPoint::new("a-point").add_field_i64("value", 1), "host0");
+        registry1.record_point(DataPoint::new("a_point").add_field_i64("value", 2), "host1");
+        registry1.record_counter(
+            &CounterPoint {
+                name: "a_point_value",
+                count: 1,
+                timestamp: 0,
+            },
+            "host1",
+        );
+
+        let rendered = render_metrics(&merge_metrics(vec![&registry0, &registry1]));
+        assert_eq!(rendered.matches("# TYPE a_point_value gauge\n").count(), 1);
+        assert!(rendered.contains("a_point_value{host_id=\"host0\"} 1\n"));
+        assert!(rendered.contains("a_point_value{host_id=\"host1\"} 2\n"));
+    }
+
+    #[test]
+    fn test_max_series_per_metric() {
+        let mut registry = Registry::default();
+        for i in 0..MAX_SERIES_PER_METRIC + 10 {
+            registry.record_point(
+                DataPoint::new("point")
+                    .add_field_str("id", &i.to_string())
+                    .add_field_i64("value", 1),
+                "host",
+            );
+        }
+        assert_eq!(registry.metrics["point_value"].len(), MAX_SERIES_PER_METRIC);
+    }
+
+    #[test]
+    fn test_exporter() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let bind_address = listener.local_addr().unwrap();
+        drop(listener);
+        start_exporter(bind_address).unwrap();
+        record_point(
+            DataPoint::new("exporter-test").add_field_i64("value", 42),
+            "host",
+        );
+
+        let request = |path: &str| {
+            let mut stream = TcpStream::connect(bind_address).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        let response = request("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("exporter_test_value{host_id=\"host\"} 42\n"));
+        assert!(request("/").starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+        // A client that never finishes its request doesn't hold up the others
+        let _idle = TcpStream::connect(bind_address).unwrap();
+        let start = Instant::now();
+        assert!(request("/metrics").starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(start.elapsed() < REQUEST_TIMEOUT);
+
+        // Reading stops at the size cap instead of waiting for the end of the headers
+        let mut head = b"GET /metrics HTTP/1.1\r\nX-Padding: ".to_vec();
+        head.resize(MAX_REQUEST_HEAD_BYTES as usize, b'a');
+        let mut stream = TcpStream::connect(bind_address).unwrap();
+        stream.write_all(&head).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    }
+}
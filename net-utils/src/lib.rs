@@ -427,6 +427,34 @@ pub fn bind_in_range(ip_addr: IpAddr, range: PortRange) -> io::Result<(u16, UdpS
     ))
 }
 
+/// Binds two sockets whose ports are `offset` apart, such as the UDP and QUIC TPU sockets
+pub fn bind_two_in_range_with_offset(
+    ip_addr: IpAddr,
+    range: PortRange,
+    offset: u16,
+) -> io::Result<((u16, UdpSocket), (u16, UdpSocket))> {
+    if range.1.saturating_sub(range.0) < offset {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("range {:?} is too small for an offset of {}", range, offset),
+        ));
+    }
+    for port in range.0..range.1.saturating_sub(offset) {
+        if let Ok(first_bind) = bind_to(ip_addr, port, false) {
+            if let Ok(second_bind) = bind_to(ip_addr, port + offset, false) {
+                return Ok((
+                    (first_bind.local_addr().unwrap().port(), first_bind),
+                    (second_bind.local_addr().unwrap().port(), second_bind),
+                ));
+            }
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        format!("No available UDP ports {} apart in {:?}", offset, range),
+    ))
+}
+
 // binds many sockets to the same port in a range
 pub fn multi_bind_in_range(
     ip_addr: IpAddr,
@@ -617,6 +645,25 @@ mod tests {
         bind_common_in_range(ip_addr, (port, port + 1)).unwrap_err();
     }
 
+    #[test]
+    fn test_bind_two_in_range_with_offset() {
+        solana_logger::setup();
+        let ip_addr = IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0));
+        let offset = 6;
+        if let Ok(((port1, _), (port2, _))) =
+            bind_two_in_range_with_offset(ip_addr, (1024, 65535), offset)
+        {
+            assert!(port2 == port1 + offset);
+        }
+        let offset = 42;
+        if let Ok(((port1, _), (port2, _))) =
+            bind_two_in_range_with_offset(ip_addr, (1024, 65535), offset)
+        {
+            assert!(port2 == port1 + offset);
+        }
+        assert!(bind_two_in_range_with_offset(ip_addr, (1024, 1044), offset).is_err());
+    }
+
     #[test]
     fn test_get_public_ip_addr_none() {
         solana_logger::setup();
@@ -0,0 +1,252 @@
+use {
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::HashMap,
+        fmt,
+        time::{Duration, Instant},
+    },
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Default for Severity {
+    fn default() -> Self {
+        Self::Info
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Info => write!(f, "INFO"),
+            Self::Warning => write!(f, "WARNING"),
+            Self::Critical => write!(f, "CRITICAL"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertStatus {
+    Firing,
+    Resolved,
+}
+
+/// A structured notification about a condition identified by `key`.
+///
+/// Firing alerts open an incident for their key, and a later resolved alert with the same key
+/// closes it. Repeated firing alerts for an open incident are only delivered when their severity
+/// escalates or their summary changes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub key: String,
+    pub severity: Severity,
+    pub status: AlertStatus,
+    pub summary: String,
+}
+
+impl Alert {
+    pub fn firing(key: impl Into<String>, severity: Severity, summary: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            severity,
+            status: AlertStatus::Firing,
+            summary: summary.into(),
+        }
+    }
+
+    pub fn resolved(key: impl Into<String>, summary: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            severity: Severity::Info,
+            status: AlertStatus::Resolved,
+            summary: summary.into(),
+        }
+    }
+
+    /// Rendering of the alert for free-text channels
+    pub fn text(&self) -> String {
+        match self.status {
+            AlertStatus::Firing => format!("[{}] {}", self.severity, self.summary),
+            AlertStatus::Resolved => format!("[RESOLVED] {}", self.summary),
+        }
+    }
+}
+
+struct AlertState {
+    severity: Severity,
+    summary: String,
+    channels: Vec<usize>,
+    open: bool,
+    last_sent: Instant,
+}
+
+/// Deduplicates and rate limits alerts per key, remembering which channels an open incident was
+/// delivered to so that its resolution reaches the same channels.
+#[derive(Default)]
+pub(crate) struct AlertTracker {
+    rate_limit: Duration,
+    states: HashMap<String, AlertState>,
+}
+
+impl AlertTracker {
+    pub fn new(rate_limit: Duration) -> Self {
+        Self {
+            rate_limit,
+            states: HashMap::new(),
+        }
+    }
+
+    /// Returns the channels `alert` should be delivered to, which is empty when it is suppressed
+    pub fn track(&mut self, alert: &Alert, channels: Vec<usize>, now: Instant) -> Vec<usize> {
+        // A resolved incident is only remembered while it can rate limit the next one
+        let rate_limit = self.rate_limit;
+        self.states.retain(|_, state| {
+            state.open || now.saturating_duration_since(state.last_sent) < rate_limit
+        });
+
+        match alert.status {
+            AlertStatus::Firing => {
+                let mut all_channels = channels.clone();
+                if let Some(state) = self.states.get(&alert.key) {
+                    let rate_limited =
+                        now.saturating_duration_since(state.last_sent) < self.rate_limit;
+                    if state.open {
+                        // Escalations of an open incident are never held back
+                        if alert.severity <= state.severity
+                            && (alert.summary == state.summary || rate_limited)
+                        {
+                            return vec![];
+                        }
+                        for channel in &state.channels {
+                            if !all_channels.contains(channel) {
+                                all_channels.push(*channel);
+                            }
+                        }
+                    } else if rate_limited {
+                        return vec![];
+                    }
+                }
+                self.states.insert(
+                    alert.key.clone(),
+                    AlertState {
+                        severity: alert.severity,
+                        summary: alert.summary.clone(),
+                        channels: all_channels,
+                        open: true,
+                        last_sent: now,
+                    },
+                );
+                channels
+            }
+            AlertStatus::Resolved => match self.states.get_mut(&alert.key) {
+                Some(state) if state.open => {
+                    state.open = false;
+                    state.last_sent = now;
+                    state.channels.clone()
+                }
+                _ => vec![],
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alert_tracker_dedup_and_resolve() {
+        let mut tracker = AlertTracker::new(Duration::default());
+        let now = Instant::now();
+
+        let alert = Alert::firing("delinquent", Severity::Warning, "validator is delinquent");
+        assert_eq!(tracker.track(&alert, vec![0], now), vec![0]);
+        assert!(tracker.track(&alert, vec![0], now).is_empty());
+
+        // Escalation is delivered to the newly routed channels
+        let escalated = Alert::firing("delinquent", Severity::Critical, "validator is delinquent");
+        assert_eq!(tracker.track(&escalated, vec![1], now), vec![1]);
+        assert!(tracker.track(&escalated, vec![1], now).is_empty());
+
+        // Resolution reaches every channel the incident was delivered to
+        let resolved = Alert::resolved("delinquent", "all clear");
+        let mut channels = tracker.track(&resolved, vec![], now);
+        channels.sort_unstable();
+        assert_eq!(channels, vec![0, 1]);
+        assert!(tracker.track(&resolved, vec![], now).is_empty());
+
+        // Resolving an unknown key is dropped
+        assert!(tracker
+            .track(&Alert::resolved("unknown", ""), vec![0], now)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_alert_tracker_rate_limit() {
+        let rate_limit = Duration::from_secs(60);
+        let mut tracker = AlertTracker::new(rate_limit);
+        let now = Instant::now();
+
+        let alert = Alert::firing("balance", Severity::Warning, "balance is 1");
+        assert_eq!(tracker.track(&alert, vec![0], now), vec![0]);
+
+        // Summary updates within the window are suppressed until it elapses
+        let update = Alert::firing("balance", Severity::Warning, "balance is 0.5");
+        assert!(tracker.track(&update, vec![0], now).is_empty());
+        assert_eq!(tracker.track(&update, vec![0], now + rate_limit), vec![0]);
+
+        // Escalation bypasses the rate limit
+        let now = now + rate_limit;
+        let escalated = Alert::firing("balance", Severity::Critical, "balance is 0");
+        assert_eq!(tracker.track(&escalated, vec![0], now), vec![0]);
+
+        // Flapping conditions are rate limited too
+        let resolved = Alert::resolved("balance", "balance is 10");
+        assert_eq!(tracker.track(&resolved, vec![], now), vec![0]);
+        assert!(tracker.track(&escalated, vec![0], now).is_empty());
+        assert_eq!(
+            tracker.track(&escalated, vec![0], now + rate_limit),
+            vec![0]
+        );
+    }
+
+    #[test]
+    fn test_alert_tracker_evicts_resolved() {
+        let rate_limit = Duration::from_secs(60);
+        let mut tracker = AlertTracker::new(rate_limit);
+        let now = Instant::now();
+
+        for key in &["a", "b"] {
+            let alert = Alert::firing(*key, Severity::Warning, "down");
+            assert_eq!(tracker.track(&alert, vec![0], now), vec![0]);
+        }
+        assert_eq!(
+            tracker.track(&Alert::resolved("a", "up"), vec![], now),
+            vec![0]
+        );
+        assert_eq!(tracker.states.len(), 2);
+
+        // Open incidents are kept however old they are
+        let later = now + rate_limit;
+        assert!(tracker
+            .track(&Alert::resolved("unknown", ""), vec![], later)
+            .is_empty());
+        assert_eq!(tracker.states.keys().collect::<Vec<_>>(), vec!["b"]);
+    }
+
+    #[test]
+    fn test_alert_text() {
+        assert_eq!(
+            Alert::firing("key", Severity::Critical, "bad").text(),
+            "[CRITICAL] bad"
+        );
+        assert_eq!(Alert::resolved("key", "good").text(), "[RESOLVED] good");
+    }
+}
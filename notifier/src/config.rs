@@ -0,0 +1,183 @@
+use {
+    crate::{alert::Severity, smtp::SmtpConfig},
+    serde::Deserialize,
+    std::{fs::File, path::Path},
+};
+
+/// Notifier configuration, loaded from a YAML file such as:
+/// ```yaml
+/// rate_limit_secs: 300
+/// channels:
+///   - name: ops
+///     type: slack
+///     url: https://hooks.slack.com/services/...
+///   - name: oncall
+///     type: pager_duty
+///     routing_key: ...
+/// routes:
+///   - channels: [ops]
+///     min_severity: warning
+///   - channels: [oncall]
+///     min_severity: critical
+///     key_prefix: watchtower/
+/// ```
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct NotifierConfig {
+    /// Minimum interval between two notifications for the same alert key, escalations excepted
+    pub rate_limit_secs: u64,
+    /// Reported as the alert source by the webhook and PagerDuty channels
+    pub source: Option<String>,
+    pub channels: Vec<ChannelConfig>,
+    /// Alerts go to the channels of every matching route, or to all channels if there are no
+    /// routes
+    pub routes: Vec<RouteConfig>,
+}
+
+impl NotifierConfig {
+    pub fn load(config_file: impl AsRef<Path>) -> Result<Self, String> {
+        let config_file = config_file.as_ref();
+        let file = File::open(config_file).map_err(|err| {
+            format!(
+                "Unable to open notifier config {}: {}",
+                config_file.display(),
+                err
+            )
+        })?;
+        serde_yaml::from_reader(file).map_err(|err| {
+            format!(
+                "Unable to parse notifier config {}: {}",
+                config_file.display(),
+                err
+            )
+        })
+    }
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct ChannelConfig {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: ChannelKind,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChannelKind {
+    Discord {
+        url: String,
+    },
+    Slack {
+        url: String,
+    },
+    Telegram {
+        bot_token: String,
+        chat_id: String,
+    },
+    Twilio {
+        account: String,
+        token: String,
+        to: String,
+        from: String,
+    },
+    /// Posts every alert as JSON to `url`
+    Webhook {
+        url: String,
+    },
+    /// PagerDuty Events API v2, the alert key is used as the incident dedup key
+    PagerDuty {
+        routing_key: String,
+    },
+    Email(SmtpConfig),
+    Log {
+        level: String,
+    },
+}
+
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct RouteConfig {
+    pub channels: Vec<String>,
+    pub min_severity: Severity,
+    /// Only alerts whose key starts with this prefix match the route
+    pub key_prefix: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::smtp::SmtpSecurity};
+
+    #[test]
+    fn test_parse_notifier_config() {
+        let config: NotifierConfig = serde_yaml::from_str(
+            r#"
+rate_limit_secs: 300
+channels:
+  - name: ops
+    type: slack
+    url: https://hooks.slack.com/services/test
+  - name: oncall
+    type: pager_duty
+    routing_key: key
+  - name: mail
+    type: email
+    server: smtp.example.com:587
+    security: starttls
+    from: watchtower@example.com
+    to: [ops@example.com]
+routes:
+  - channels: [ops, mail]
+    min_severity: warning
+  - channels: [oncall]
+    min_severity: critical
+    key_prefix: watchtower/
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            NotifierConfig {
+                rate_limit_secs: 300,
+                source: None,
+                channels: vec![
+                    ChannelConfig {
+                        name: "ops".to_string(),
+                        kind: ChannelKind::Slack {
+                            url: "https://hooks.slack.com/services/test".to_string(),
+                        },
+                    },
+                    ChannelConfig {
+                        name: "oncall".to_string(),
+                        kind: ChannelKind::PagerDuty {
+                            routing_key: "key".to_string(),
+                        },
+                    },
+                    ChannelConfig {
+                        name: "mail".to_string(),
+                        kind: ChannelKind::Email(SmtpConfig {
+                            server: "smtp.example.com:587".to_string(),
+                            security: SmtpSecurity::StartTls,
+                            from: "watchtower@example.com".to_string(),
+                            to: vec!["ops@example.com".to_string()],
+                            username: None,
+                            password: None,
+                        }),
+                    },
+                ],
+                routes: vec![
+                    RouteConfig {
+                        channels: vec!["ops".to_string(), "mail".to_string()],
+                        min_severity: Severity::Warning,
+                        key_prefix: None,
+                    },
+                    RouteConfig {
+                        channels: vec!["oncall".to_string()],
+                        min_severity: Severity::Critical,
+                        key_prefix: Some("watchtower/".to_string()),
+                    },
+                ],
+            }
+        );
+    }
+}
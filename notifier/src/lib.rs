@@ -17,10 +17,36 @@
 /// ```bash
 /// export TWILIO_CONFIG='ACCOUNT=<account>,TOKEN=<securityToken>,TO=<receivingNumber>,FROM=<sendingNumber>'
 /// ```
+///
+/// Alternatively a `Notifier` can be built from a `NotifierConfig` file, which adds generic
+/// webhook, PagerDuty and email channels, and routes structured `Alert`s to channels by severity
+/// and alert key.
 use log::*;
 use reqwest::{blocking::Client, StatusCode};
 use serde_json::json;
-use std::{env, str::FromStr, thread::sleep, time::Duration};
+use std::{
+    collections::HashSet,
+    env,
+    path::Path,
+    str::FromStr,
+    sync::Mutex,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+mod alert;
+mod config;
+mod smtp;
+
+pub use {
+    alert::{Alert, AlertStatus, Severity},
+    config::{ChannelConfig, ChannelKind, NotifierConfig, RouteConfig},
+    smtp::{SmtpConfig, SmtpSecurity},
+};
+
+use alert::AlertTracker;
+
+const PAGER_DUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
 
 struct TelegramWebHook {
     bot_token: String,
@@ -80,12 +106,72 @@ enum NotificationType {
     Slack(String),
     Telegram(TelegramWebHook),
     Twilio(TwilioWebHook),
+    Webhook(String),
+    PagerDuty(String),
+    Email(SmtpConfig),
     Log(Level),
 }
 
+impl NotificationType {
+    fn from_config(kind: ChannelKind) -> Result<Self, String> {
+        Ok(match kind {
+            ChannelKind::Discord { url } => Self::Discord(url),
+            ChannelKind::Slack { url } => Self::Slack(url),
+            ChannelKind::Telegram { bot_token, chat_id } => {
+                Self::Telegram(TelegramWebHook { bot_token, chat_id })
+            }
+            ChannelKind::Twilio {
+                account,
+                token,
+                to,
+                from,
+            } => Self::Twilio(TwilioWebHook {
+                account,
+                token,
+                to,
+                from,
+            }),
+            ChannelKind::Webhook { url } => Self::Webhook(url),
+            ChannelKind::PagerDuty { routing_key } => Self::PagerDuty(routing_key),
+            ChannelKind::Email(smtp_config) => {
+                if smtp_config.security == SmtpSecurity::None && smtp_config.username.is_some() {
+                    return Err(format!(
+                        "Email channel to {} has credentials but no TLS security",
+                        smtp_config.server
+                    ));
+                }
+                Self::Email(smtp_config)
+            }
+            ChannelKind::Log { level } => Self::Log(
+                Level::from_str(&level)
+                    .map_err(|err| format!("Invalid log level {}: {}", level, err))?,
+            ),
+        })
+    }
+}
+
+struct Route {
+    channels: Vec<usize>,
+    min_severity: Severity,
+    key_prefix: Option<String>,
+}
+
+impl Route {
+    fn matches(&self, alert: &Alert) -> bool {
+        alert.severity >= self.min_severity
+            && self
+                .key_prefix
+                .as_ref()
+                .map_or(true, |key_prefix| alert.key.starts_with(key_prefix))
+    }
+}
+
 pub struct Notifier {
     client: Client,
     notifiers: Vec<NotificationType>,
+    routes: Vec<Route>,
+    source: String,
+    alerts: Mutex<AlertTracker>,
 }
 
 impl Notifier {
@@ -134,78 +220,318 @@ impl Notifier {
         Notifier {
             client: Client::new(),
             notifiers,
+            routes: vec![],
+            source: "solana".to_string(),
+            alerts: Mutex::new(AlertTracker::default()),
+        }
+    }
+
+    pub fn from_config(config: NotifierConfig) -> Result<Self, String> {
+        let mut channel_names = vec![];
+        let mut notifiers = vec![];
+        for channel in config.channels {
+            if channel_names.contains(&channel.name) {
+                return Err(format!("Duplicate notifier channel: {}", channel.name));
+            }
+            notifiers.push(NotificationType::from_config(channel.kind)?);
+            channel_names.push(channel.name);
         }
+
+        let routes = config
+            .routes
+            .into_iter()
+            .map(|route| {
+                let channels = route
+                    .channels
+                    .iter()
+                    .map(|name| {
+                        channel_names
+                            .iter()
+                            .position(|channel_name| channel_name == name)
+                            .ok_or_else(|| format!("Unknown notifier channel: {}", name))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Route {
+                    channels,
+                    min_severity: route.min_severity,
+                    key_prefix: route.key_prefix,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        info!("{} notifiers, {} routes", notifiers.len(), routes.len());
+
+        Ok(Notifier {
+            client: Client::new(),
+            notifiers,
+            routes,
+            source: config.source.unwrap_or_else(|| "solana".to_string()),
+            alerts: Mutex::new(AlertTracker::new(Duration::from_secs(
+                config.rate_limit_secs,
+            ))),
+        })
+    }
+
+    pub fn from_config_file(config_file: impl AsRef<Path>) -> Result<Self, String> {
+        Self::from_config(NotifierConfig::load(config_file)?)
     }
 
     pub fn is_empty(&self) -> bool {
         self.notifiers.is_empty()
     }
 
+    fn route(&self, alert: &Alert) -> Vec<usize> {
+        if self.routes.is_empty() {
+            return (0..self.notifiers.len()).collect();
+        }
+        let mut channels = vec![];
+        let mut seen = HashSet::new();
+        for route in self.routes.iter().filter(|route| route.matches(alert)) {
+            for channel in &route.channels {
+                if seen.insert(*channel) {
+                    channels.push(*channel);
+                }
+            }
+        }
+        channels
+    }
+
+    /// Delivers `alert` to its routed channels, subject to deduplication and rate limiting per
+    /// alert key. Resolved alerts only go out for open incidents, to the channels that were
+    /// notified of them.
+    pub fn send_alert(&self, alert: &Alert) {
+        let channels = self.route(alert);
+        let channels = self
+            .alerts
+            .lock()
+            .unwrap()
+            .track(alert, channels, Instant::now());
+        if channels.is_empty() {
+            debug!("Alert {} suppressed", alert.key);
+            return;
+        }
+
+        for channel in channels {
+            match &self.notifiers[channel] {
+                NotificationType::Webhook(url) => {
+                    let data = json!({
+                        "key": alert.key,
+                        "severity": alert.severity,
+                        "status": alert.status,
+                        "summary": alert.summary,
+                        "source": self.source,
+                    });
+                    if let Err(err) = self.client.post(url).json(&data).send() {
+                        warn!("Failed to send webhook alert: {:?}", err);
+                    }
+                }
+                NotificationType::PagerDuty(routing_key) => {
+                    let data = match alert.status {
+                        AlertStatus::Firing => json!({
+                            "routing_key": routing_key,
+                            "event_action": "trigger",
+                            "dedup_key": alert.key,
+                            "payload": {
+                                "summary": alert.summary,
+                                "source": self.source,
+                                "severity": match alert.severity {
+                                    Severity::Info => "info",
+                                    Severity::Warning => "warning",
+                                    Severity::Critical => "critical",
+                                },
+                            },
+                        }),
+                        AlertStatus::Resolved => json!({
+                            "routing_key": routing_key,
+                            "event_action": "resolve",
+                            "dedup_key": alert.key,
+                        }),
+                    };
+                    if let Err(err) = self.client.post(PAGER_DUTY_EVENTS_URL).json(&data).send() {
+                        warn!("Failed to send PagerDuty event: {:?}", err);
+                    }
+                }
+                notifier => self.send_text(notifier, &alert.text()),
+            }
+        }
+    }
+
+    /// Sends free text to every channel except PagerDuty, which only accepts `Alert`s
     pub fn send(&self, msg: &str) {
         for notifier in &self.notifiers {
-            match notifier {
-                NotificationType::Discord(webhook) => {
-                    for line in msg.split('\n') {
-                        // Discord rate limiting is aggressive, limit to 1 message a second
-                        sleep(Duration::from_millis(1000));
+            self.send_text(notifier, msg);
+        }
+    }
+
+    fn send_text(&self, notifier: &NotificationType, msg: &str) {
+        match notifier {
+            NotificationType::Discord(webhook) => {
+                for line in msg.split('\n') {
+                    // Discord rate limiting is aggressive, limit to 1 message a second
+                    sleep(Duration::from_millis(1000));
 
-                        info!("Sending {}", line);
-                        let data = json!({ "content": line });
+                    info!("Sending {}", line);
+                    let data = json!({ "content": line });
 
-                        loop {
-                            let response = self.client.post(webhook).json(&data).send();
+                    loop {
+                        let response = self.client.post(webhook).json(&data).send();
 
-                            if let Err(err) = response {
-                                warn!("Failed to send Discord message: \"{}\": {:?}", line, err);
+                        if let Err(err) = response {
+                            warn!("Failed to send Discord message: \"{}\": {:?}", line, err);
+                            break;
+                        } else if let Ok(response) = response {
+                            info!("response status: {}", response.status());
+                            if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                                warn!("rate limited!...");
+                                warn!("response text: {:?}", response.text());
+                                sleep(Duration::from_secs(2));
+                            } else {
                                 break;
-                            } else if let Ok(response) = response {
-                                info!("response status: {}", response.status());
-                                if response.status() == StatusCode::TOO_MANY_REQUESTS {
-                                    warn!("rate limited!...");
-                                    warn!("response text: {:?}", response.text());
-                                    sleep(Duration::from_secs(2));
-                                } else {
-                                    break;
-                                }
                             }
                         }
                     }
                 }
-                NotificationType::Slack(webhook) => {
-                    let data = json!({ "text": msg });
-                    if let Err(err) = self.client.post(webhook).json(&data).send() {
-                        warn!("Failed to send Slack message: {:?}", err);
-                    }
+            }
+            NotificationType::Slack(webhook) => {
+                let data = json!({ "text": msg });
+                if let Err(err) = self.client.post(webhook).json(&data).send() {
+                    warn!("Failed to send Slack message: {:?}", err);
                 }
+            }
 
-                NotificationType::Telegram(TelegramWebHook { chat_id, bot_token }) => {
-                    let data = json!({ "chat_id": chat_id, "text": msg });
-                    let url = format!("https://api.telegram.org/bot{}/sendMessage", bot_token);
+            NotificationType::Telegram(TelegramWebHook { chat_id, bot_token }) => {
+                let data = json!({ "chat_id": chat_id, "text": msg });
+                let url = format!("https://api.telegram.org/bot{}/sendMessage", bot_token);
 
-                    if let Err(err) = self.client.post(&url).json(&data).send() {
-                        warn!("Failed to send Telegram message: {:?}", err);
-                    }
+                if let Err(err) = self.client.post(&url).json(&data).send() {
+                    warn!("Failed to send Telegram message: {:?}", err);
                 }
+            }
 
-                NotificationType::Twilio(TwilioWebHook {
-                    account,
-                    token,
-                    to,
-                    from,
-                }) => {
-                    let url = format!(
-                        "https://{}:{}@api.twilio.com/2010-04-01/Accounts/{}/Messages.json",
-                        account, token, account
-                    );
-                    let params = [("To", to), ("From", from), ("Body", &msg.to_string())];
-                    if let Err(err) = self.client.post(&url).form(&params).send() {
-                        warn!("Failed to send Twilio message: {:?}", err);
-                    }
+            NotificationType::Twilio(TwilioWebHook {
+                account,
+                token,
+                to,
+                from,
+            }) => {
+                let url = format!(
+                    "https://{}:{}@api.twilio.com/2010-04-01/Accounts/{}/Messages.json",
+                    account, token, account
+                );
+                let params = [("To", to), ("From", from), ("Body", &msg.to_string())];
+                if let Err(err) = self.client.post(&url).form(&params).send() {
+                    warn!("Failed to send Twilio message: {:?}", err);
+                }
+            }
+            NotificationType::Webhook(url) => {
+                let data = json!({ "text": msg, "source": self.source });
+                if let Err(err) = self.client.post(url).json(&data).send() {
+                    warn!("Failed to send webhook message: {:?}", err);
                 }
-                NotificationType::Log(level) => {
-                    log!(*level, "{}", msg)
+            }
+            NotificationType::PagerDuty(_) => {
+                debug!("Free-text message not sent to PagerDuty: {}", msg);
+            }
+            NotificationType::Email(smtp_config) => {
+                let subject = msg.lines().next().unwrap_or_default();
+                if let Err(err) = smtp::send_email(smtp_config, subject, msg) {
+                    warn!("Failed to send email: {:?}", err);
                 }
             }
+            NotificationType::Log(level) => {
+                log!(*level, "{}", msg)
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notifier_routing() {
+        let config: NotifierConfig = serde_yaml::from_str(
+            r#"
+channels:
+  - name: info
+    type: log
+    level: info
+  - name: error
+    type: log
+    level: error
+routes:
+  - channels: [info]
+  - channels: [error, info]
+    min_severity: critical
+    key_prefix: watchtower/
+"#,
+        )
+        .unwrap();
+        let notifier = Notifier::from_config(config).unwrap();
+
+        let alert = Alert::firing("watchtower/delinquent", Severity::Warning, "");
+        assert_eq!(notifier.route(&alert), vec![0]);
+        let alert = Alert::firing("watchtower/delinquent", Severity::Critical, "");
+        assert_eq!(notifier.route(&alert), vec![0, 1]);
+        let alert = Alert::firing("stake-monitor/account", Severity::Critical, "");
+        assert_eq!(notifier.route(&alert), vec![0]);
+    }
+
+    #[test]
+    fn test_notifier_config_errors() {
+        let config: NotifierConfig = serde_yaml::from_str(
+            r#"
+channels:
+  - name: log
+    type: log
+    level: info
+routes:
+  - channels: [slack]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            Notifier::from_config(config).err(),
+            Some("Unknown notifier channel: slack".to_string())
+        );
+
+        let config: NotifierConfig = serde_yaml::from_str(
+            r#"
+channels:
+  - name: log
+    type: log
+    level: info
+  - name: log
+    type: log
+    level: warn
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            Notifier::from_config(config).err(),
+            Some("Duplicate notifier channel: log".to_string())
+        );
+
+        let config: NotifierConfig = serde_yaml::from_str(
+            r#"
+channels:
+  - name: mail
+    type: email
+    server: smtp.example.com:25
+    from: watchtower@example.com
+    to: [ops@example.com]
+    username: watchtower
+    password: secret
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            Notifier::from_config(config).err(),
+            Some(
+                "Email channel to smtp.example.com:25 has credentials but no TLS security"
+                    .to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! A minimal SMTP client, enough to hand alert emails to a relay.
+//!
+//! The connection is encrypted with STARTTLS or implicit TLS, verified against the webpki roots.
+//! An unencrypted relay is only meant for trusted networks such as localhost, and never gets
+//! credentials.
+
+use {
+    rustls::{ClientConfig, ClientConnection, OwnedTrustAnchor, RootCertStore, StreamOwned},
+    serde::Deserialize,
+    std::{
+        convert::TryFrom,
+        io::{self, BufRead, BufReader, Read, Write},
+        net::TcpStream,
+        sync::Arc,
+        time::Duration,
+    },
+};
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// Plain connection, refused when credentials are configured
+    None,
+    /// Upgrade the plain connection with the STARTTLS command, usually on port 587
+    StartTls,
+    /// TLS from the start of the connection, usually on port 465
+    Tls,
+}
+
+impl Default for SmtpSecurity {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct SmtpConfig {
+    /// `host:port` of the SMTP relay
+    pub server: String,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    pub from: String,
+    pub to: Vec<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+struct SmtpConnection<S: Read + Write> {
+    stream: BufReader<S>,
+}
+
+impl<S: Read + Write> SmtpConnection<S> {
+    fn new(stream: S) -> Self {
+        Self {
+            stream: BufReader::new(stream),
+        }
+    }
+
+    /// Hands the stream over to TLS, the server doesn't send anything before the handshake
+    fn into_inner(self) -> S {
+        self.stream.into_inner()
+    }
+
+    /// Reads a possibly multi-line reply and checks its code
+    fn expect_reply(&mut self, expected_code: u16) -> io::Result<()> {
+        loop {
+            let mut line = String::new();
+            if self.stream.read_line(&mut line)? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "SMTP connection closed",
+                ));
+            }
+            let code = line.get(..3).and_then(|code| code.parse::<u16>().ok());
+            if code != Some(expected_code) {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("unexpected SMTP reply: {}", line.trim_end()),
+                ));
+            }
+            // "250-" continues a multi-line reply, "250 " ends it
+            if line.as_bytes().get(3) != Some(&b'-') {
+                return Ok(());
+            }
+        }
+    }
+
+    fn command(&mut self, command: &str, expected_code: u16) -> io::Result<()> {
+        let writer = self.stream.get_mut();
+        writer.write_all(command.as_bytes())?;
+        writer.write_all(b"\r\n")?;
+        writer.flush()?;
+        self.expect_reply(expected_code)
+    }
+}
+
+fn tls_stream(
+    server: &str,
+    stream: TcpStream,
+) -> io::Result<StreamOwned<ClientConnection, TcpStream>> {
+    let mut root_store = RootCertStore::empty();
+    root_store.add_server_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|anchor| {
+        OwnedTrustAnchor::from_subject_spki_name_constraints(
+            anchor.subject,
+            anchor.spki,
+            anchor.name_constraints,
+        )
+    }));
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(root_store)
+        .with_no_client_auth();
+
+    let host = server.rsplit_once(':').map_or(server, |(host, _)| host);
+    let server_name = rustls::ServerName::try_from(host)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let connection = ClientConnection::new(Arc::new(config), server_name)
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+    Ok(StreamOwned::new(connection, stream))
+}
+
+fn format_message(config: &SmtpConfig, subject: &str, body: &str) -> String {
+    let mut message = format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n",
+        config.from,
+        config.to.join(", "),
+        subject.replace(|c| c == '\r' || c == '\n', " "),
+    );
+    for line in body.lines() {
+        // Dot-stuffing, so that a line with a single "." does not end the message early
+        if line.starts_with('.') {
+            message.push('.');
+        }
+        message.push_str(line);
+        message.push_str("\r\n");
+    }
+    message.push('.');
+    message
+}
+
+pub(crate) fn send_email(config: &SmtpConfig, subject: &str, body: &str) -> io::Result<()> {
+    if config.security == SmtpSecurity::None && config.username.is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "refusing to send SMTP credentials over an unencrypted connection",
+        ));
+    }
+
+    let stream = TcpStream::connect(&config.server)?;
+    stream.set_read_timeout(Some(SMTP_TIMEOUT))?;
+    stream.set_write_timeout(Some(SMTP_TIMEOUT))?;
+    match config.security {
+        SmtpSecurity::None => {
+            let mut connection = SmtpConnection::new(stream);
+            connection.expect_reply(220)?;
+            send_message(&mut connection, config, subject, body)
+        }
+        SmtpSecurity::StartTls => {
+            let mut connection = SmtpConnection::new(stream);
+            connection.expect_reply(220)?;
+            connection.command("EHLO localhost", 250)?;
+            connection.command("STARTTLS", 220)?;
+            let stream = tls_stream(&config.server, connection.into_inner())?;
+            send_message(&mut SmtpConnection::new(stream), config, subject, body)
+        }
+        SmtpSecurity::Tls => {
+            let mut connection = SmtpConnection::new(tls_stream(&config.server, stream)?);
+            connection.expect_reply(220)?;
+            send_message(&mut connection, config, subject, body)
+        }
+    }
+}
+
+fn send_message<S: Read + Write>(
+    connection: &mut SmtpConnection<S>,
+    config: &SmtpConfig,
+    subject: &str,
+    body: &str,
+) -> io::Result<()> {
+    connection.command("EHLO localhost", 250)?;
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        let credentials = base64::encode(format!("\0{}\0{}", username, password));
+        connection.command(&format!("AUTH PLAIN {}", credentials), 235)?;
+    }
+    connection.command(&format!("MAIL FROM:<{}>", config.from), 250)?;
+    for to in &config.to {
+        connection.command(&format!("RCPT TO:<{}>", to), 250)?;
+    }
+    connection.command("DATA", 354)?;
+    connection.command(&format_message(config, subject, body), 250)?;
+    connection.command("QUIT", 221)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_message() {
+        let config = SmtpConfig {
+            server: "localhost:25".to_string(),
+            security: SmtpSecurity::None,
+            from: "watchtower@example.com".to_string(),
+            to: vec!["a@example.com".to_string(), "b@example.com".to_string()],
+            username: None,
+            password: None,
+        };
+        assert_eq!(
+            format_message(&config, "[CRITICAL]\nalert", "line 1\n.line 2"),
+            "From: watchtower@example.com\r\n\
+            To: a@example.com, b@example.com\r\n\
+            Subject: [CRITICAL] alert\r\n\
+            Content-Type: text/plain; charset=utf-8\r\n\
+            \r\n\
+            line 1\r\n\
+            ..line 2\r\n\
+            ."
+        );
+    }
+}
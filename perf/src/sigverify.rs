@@ -11,7 +11,7 @@ use crate::recycler::Recycler;
 use rayon::ThreadPool;
 use solana_metrics::inc_new_counter_debug;
 use solana_rayon_threadlimit::get_thread_count;
-use solana_sdk::message::MESSAGE_HEADER_LENGTH;
+use solana_sdk::message::{MESSAGE_HEADER_LENGTH, MESSAGE_VERSION_PREFIX};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::short_vec::decode_shortu16_len;
 use solana_sdk::signature::Signature;
@@ -66,6 +66,7 @@ pub enum PacketError {
     InvalidSignatureLen,
     MismatchSignatureLen,
     PayerNotWritable,
+    UnsupportedVersion,
 }
 
 impl std::convert::From<std::boxed::Box<bincode::ErrorKind>> for PacketError {
@@ -172,20 +173,42 @@ fn do_get_packet_offsets(
         .and_then(|v| v.checked_add(sig_size))
         .ok_or(PacketError::InvalidLen)?;
 
-    let msg_start_offset_plus_one = msg_start_offset
+    // Packet should have data at least for signatures and the first message byte
+    let _ = msg_start_offset
+        .checked_add(1)
+        .filter(|v| *v <= packet.meta.size)
+        .ok_or(PacketError::InvalidSignatureLen)?;
+
+    // The first message byte is either the version prefix of a versioned message, or the
+    // MessageHeader of a legacy message. Only v0 messages, whose header follows the prefix,
+    // are supported.
+    let message_prefix = packet.data[msg_start_offset];
+    let msg_header_offset = if message_prefix & MESSAGE_VERSION_PREFIX != 0 {
+        let version = message_prefix & !MESSAGE_VERSION_PREFIX;
+        if version != 0 {
+            return Err(PacketError::UnsupportedVersion);
+        }
+        msg_start_offset
+            .checked_add(1)
+            .ok_or(PacketError::InvalidLen)?
+    } else {
+        msg_start_offset
+    };
+
+    let msg_header_offset_plus_one = msg_header_offset
         .checked_add(1)
         .ok_or(PacketError::InvalidLen)?;
 
     // Packet should have data at least for signatures, MessageHeader, 1 byte for Message.account_keys.len
-    let _ = msg_start_offset_plus_one
+    let _ = msg_header_offset_plus_one
         .checked_add(MESSAGE_HEADER_LENGTH)
         .filter(|v| *v <= packet.meta.size)
         .ok_or(PacketError::InvalidSignatureLen)?;
 
     // read MessageHeader.num_required_signatures (serialized with u8)
-    let sig_len_maybe_trusted = packet.data[msg_start_offset];
+    let sig_len_maybe_trusted = packet.data[msg_header_offset];
 
-    let message_account_keys_len_offset = msg_start_offset
+    let message_account_keys_len_offset = msg_header_offset
         .checked_add(MESSAGE_HEADER_LENGTH)
         .ok_or(PacketError::InvalidLen)?;
 
@@ -193,7 +216,7 @@ fn do_get_packet_offsets(
     // num_readonly_signed_accounts bytes. If num_required_signatures is not larger than
     // num_readonly_signed_accounts, the first account is not debitable, and cannot be charged
     // required transaction fees.
-    let readonly_signer_offset = msg_start_offset_plus_one;
+    let readonly_signer_offset = msg_header_offset_plus_one;
     if sig_len_maybe_trusted <= packet.data[readonly_signer_offset] {
         return Err(PacketError::PayerNotWritable);
     }
@@ -472,9 +495,9 @@ mod tests {
     use crate::test_tx::{test_multisig_tx, test_tx};
     use bincode::{deserialize, serialize};
     use solana_sdk::hash::Hash;
-    use solana_sdk::message::{Message, MessageHeader};
-    use solana_sdk::signature::Signature;
-    use solana_sdk::transaction::Transaction;
+    use solana_sdk::message::{v0, Message, MessageHeader, VersionedMessage};
+    use solana_sdk::signature::{Keypair, Signature, Signer};
+    use solana_sdk::transaction::{Transaction, VersionedTransaction};
 
     const SIG_OFFSET: usize = 1;
 
@@ -715,6 +738,63 @@ mod tests {
         );
     }
 
+    fn test_v0_tx(keypair: &Keypair) -> VersionedTransaction {
+        let message = VersionedMessage::V0(v0::Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 0,
+            },
+            account_keys: vec![keypair.pubkey()],
+            ..v0::Message::default()
+        });
+        VersionedTransaction::try_new(message, &[keypair]).unwrap()
+    }
+
+    #[test]
+    fn test_versioned_packet_offsets() {
+        let tx = test_v0_tx(&Keypair::new());
+        let mut packet = Packet::from_data(None, &tx).unwrap();
+
+        // The signed message starts at the version prefix, and the header follows it
+        assert_eq!(
+            sigverify::do_get_packet_offsets(&packet, 0),
+            Ok(PacketOffsets::new(1, 1, 65, 70))
+        );
+
+        packet.data[65] = MESSAGE_VERSION_PREFIX | 1;
+        assert_eq!(
+            sigverify::do_get_packet_offsets(&packet, 0),
+            Err(PacketError::UnsupportedVersion)
+        );
+    }
+
+    #[test]
+    fn test_verify_versioned_tx() {
+        let keypair = Keypair::new();
+        let packet = Packet::from_data(None, &test_v0_tx(&keypair)).unwrap();
+        let mut batches = generate_packet_vec(&packet, 2, 1);
+        let recycler = Recycler::new_without_limit("");
+        let recycler_out = Recycler::new_without_limit("");
+        sigverify::ed25519_verify(&mut batches, &recycler, &recycler_out);
+        assert!(batches
+            .iter()
+            .flat_map(|p| &p.packets)
+            .all(|p| !p.meta.discard));
+
+        // The version prefix is covered by the signature
+        let mut tx = test_v0_tx(&keypair);
+        tx.message = VersionedMessage::Legacy(Message {
+            header: tx.message.header().clone(),
+            account_keys: tx.message.static_account_keys().to_vec(),
+            ..Message::default()
+        });
+        let packet = Packet::from_data(None, &tx).unwrap();
+        let mut batches = generate_packet_vec(&packet, 1, 1);
+        sigverify::ed25519_verify(&mut batches, &recycler, &recycler_out);
+        assert!(batches[0].packets[0].meta.discard);
+    }
+
     fn generate_packet_vec(
         packet: &Packet,
         num_packets_per_batch: usize,
@@ -4,9 +4,11 @@
 use {
     async_trait::async_trait,
     chrono_humanize::{Accuracy, HumanTime, Tense},
+    evm_state::{EvmState, MemoryAccount, H160, H256, U256},
     log::*,
     solana_banks_client::start_client,
     solana_banks_server::banks_server::start_local_server,
+    solana_bpf_loader_program::serialization::{deserialize_parameters, serialize_parameters},
     solana_runtime::{
         bank::{Bank, Builtin, ExecuteTimings},
         bank_forks::BankForks,
@@ -16,13 +18,15 @@ use {
     solana_sdk::{
         account::{Account, AccountSharedData, ReadableAccount},
         account_info::AccountInfo,
+        bpf_loader,
         clock::{Clock, Slot},
-        entrypoint::{ProgramResult, SUCCESS},
+        entrypoint::{deserialize, ProgramResult, SUCCESS},
         epoch_schedule::EpochSchedule,
         feature_set::demote_sysvar_write_locks,
         fee_calculator::{FeeCalculator, FeeRateGovernor},
         genesis_config::{ClusterType, GenesisConfig},
         hash::Hash,
+        ic_msg,
         instruction::Instruction,
         instruction::InstructionError,
         keyed_account::KeyedAccount,
@@ -44,7 +48,7 @@ use {
     solana_vote_program::vote_state::{VoteState, VoteStateVersions},
     std::{
         cell::RefCell,
-        collections::HashMap,
+        collections::BTreeMap,
         convert::TryFrom,
         fs::File,
         io::{self, Read},
@@ -62,7 +66,13 @@ use {
 };
 
 // Export types so test clients can limit their solana crate dependencies
-pub use solana_banks_client::BanksClient;
+pub use solana_banks_client::{
+    BanksClient, BanksTransactionResultWithMetadata, BanksTransactionResultWithSimulation, EvmCall,
+    EvmCallResult, TransactionSimulationDetails,
+};
+
+// Export evm-state so test clients can build EVM addresses and values
+pub use evm_state;
 
 // Export tokio for test clients
 pub use tokio;
@@ -105,57 +115,24 @@ pub fn builtin_process_instruction(
 ) -> Result<(), InstructionError> {
     set_invoke_context(invoke_context);
 
-    // Copy all the accounts into a HashMap to ensure there are no duplicates
-    let mut accounts: HashMap<Pubkey, AccountSharedData> = keyed_accounts
-        .iter()
-        .map(|ka| (*ka.unsigned_key(), ka.account.borrow().clone()))
-        .collect();
-
-    // Create shared references to each account's lamports/data/owner
-    let account_refs: HashMap<_, _> = accounts
-        .iter_mut()
-        .map(|(key, account)| {
-            (
-                *key,
-                (
-                    Rc::new(RefCell::new(&mut account.lamports)),
-                    Rc::new(RefCell::new(&mut account.data[..])),
-                    &account.owner,
-                ),
-            )
-        })
-        .collect();
-
-    // Create AccountInfos
-    let account_infos: Vec<AccountInfo> = keyed_accounts
-        .iter()
-        .map(|keyed_account| {
-            let key = keyed_account.unsigned_key();
-            let (lamports, data, owner) = &account_refs[key];
-            AccountInfo {
-                key,
-                is_signer: keyed_account.signer_key().is_some(),
-                is_writable: keyed_account.is_writable(),
-                lamports: lamports.clone(),
-                data: data.clone(),
-                owner,
-                executable: keyed_account.executable().unwrap(),
-                rent_epoch: keyed_account.rent_epoch().unwrap(),
-            }
-        })
-        .collect();
+    // Serialize the accounts the same way the BPF loader does so that programs
+    // observe the same input layout, including the spare space used by
+    // `AccountInfo::realloc`
+    let mut parameter_bytes =
+        serialize_parameters(&bpf_loader::id(), program_id, keyed_accounts, input)?;
+    let (program_id, account_infos, input) =
+        unsafe { deserialize(&mut parameter_bytes.as_slice_mut()[0] as *mut u8) };
 
     // Execute the program
     process_instruction(program_id, &account_infos, input).map_err(u64::from)?;
 
     // Commit AccountInfo changes back into KeyedAccounts
-    for keyed_account in keyed_accounts {
-        let mut account = keyed_account.account.borrow_mut();
-        let key = keyed_account.unsigned_key();
-        let (lamports, data, _owner) = &account_refs[key];
-        account.lamports = **lamports.borrow();
-        account.set_data(data.borrow().to_vec());
-    }
+    deserialize_parameters(
+        &bpf_loader::id(),
+        keyed_accounts,
+        parameter_bytes.as_slice(),
+        false,
+    )?;
 
     Ok(())
 }
@@ -343,7 +320,6 @@ impl solana_sdk::program_stubs::SyscallStubs for SyscallStubs {
                     let account = &accounts[i];
                     **account_info.try_borrow_mut_lamports().unwrap() = account.borrow().lamports;
 
-                    let mut data = account_info.try_borrow_mut_data()?;
                     let account_borrow = account.borrow();
                     let new_data = account_borrow.data();
                     if *account_info.owner != account.borrow().owner {
@@ -354,16 +330,23 @@ impl solana_sdk::program_stubs::SyscallStubs for SyscallStubs {
                             unsafe { transmute::<&Pubkey, &mut Pubkey>(account_info.owner) };
                         *account_info_mut = account.borrow().owner;
                     }
-                    if data.len() != new_data.len() {
-                        // TODO: Figure out how to allow the System Program to resize the account data
-                        panic!(
-                            "Account data resizing not supported yet: {} -> {}. \
-                            Consider making this test conditional on `#[cfg(feature = \"test-bpf\")]`",
-                            data.len(),
-                            new_data.len()
-                        );
+                    if account_info.data_len() != new_data.len() {
+                        if account_info.data_len() != 0 {
+                            // Same as the BPF loader, only `CreateAccount` may resize an
+                            // account in an inner instruction
+                            ic_msg!(
+                                invoke_context,
+                                "Inner instructions do not support realloc, only SystemProgram::CreateAccount",
+                            );
+                            return Err(ProgramError::InvalidRealloc);
+                        }
+                        // The AccountInfos are backed by the serialized input, which
+                        // reserves the same spare space as the BPF loader does
+                        account_info.realloc(new_data.len(), false)?;
                     }
-                    data.clone_from_slice(new_data);
+                    account_info
+                        .try_borrow_mut_data()?
+                        .clone_from_slice(new_data);
                 }
             }
         }
@@ -387,6 +370,21 @@ impl solana_sdk::program_stubs::SyscallStubs for SyscallStubs {
     fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
         get_sysvar::<Rent>(&rent::id(), var_addr)
     }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        get_invoke_context()
+            .set_return_data(data.to_vec())
+            .expect("return data must be set by a running program");
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        let (program_id, data) = get_invoke_context().get_return_data();
+        if data.is_empty() {
+            None
+        } else {
+            Some((program_id, data.to_vec()))
+        }
+    }
 }
 
 pub fn find_file(filename: &str) -> Option<PathBuf> {
@@ -464,6 +462,7 @@ fn setup_fee_calculator(bank: Bank) -> Bank {
 
 pub struct ProgramTest {
     accounts: Vec<(Pubkey, AccountSharedData)>,
+    evm_accounts: Vec<(H160, MemoryAccount)>,
     builtins: Vec<Builtin>,
     bpf_compute_max_units: Option<u64>,
     prefer_bpf: bool,
@@ -493,6 +492,7 @@ impl Default for ProgramTest {
 
         Self {
             accounts: vec![],
+            evm_accounts: vec![],
             builtins: vec![],
             bpf_compute_max_units: None,
             prefer_bpf,
@@ -534,6 +534,26 @@ impl ProgramTest {
             .push((address, AccountSharedData::from(account)));
     }
 
+    /// Add an EVM account to the test environment. The lamports backing `balance` are added
+    /// to the evm state account, so the account can be swapped back to native tokens
+    pub fn add_evm_account(
+        &mut self,
+        address: H160,
+        balance: U256,
+        code: Vec<u8>,
+        storage: BTreeMap<H256, H256>,
+    ) {
+        self.evm_accounts.push((
+            address,
+            MemoryAccount {
+                nonce: U256::zero(),
+                balance,
+                storage,
+                code,
+            },
+        ));
+    }
+
     /// Add an account to the test environment with the account data in the provided `filename`
     pub fn add_account_with_file_data(
         &mut self,
@@ -771,6 +791,32 @@ impl ProgramTest {
             }
             bank.store_account(address, account);
         }
+
+        if !self.evm_accounts.is_empty() {
+            let evm_balance = self
+                .evm_accounts
+                .iter()
+                .fold(U256::zero(), |balance, (_, account)| {
+                    balance + account.balance
+                });
+            let (mut evm_lamports, change) =
+                solana_evm_loader_program::scope::evm::gweis_to_lamports(evm_balance);
+            if change != U256::zero() {
+                evm_lamports += 1;
+            }
+            let mut evm_state_account = bank
+                .get_account(&solana_sdk::evm_state::id())
+                .unwrap_or_else(|| solana_evm_loader_program::create_state_account(0));
+            evm_state_account.lamports += evm_lamports;
+            bank.store_account(&solana_sdk::evm_state::id(), &evm_state_account);
+
+            match &mut *bank.evm_state.write().unwrap() {
+                EvmState::Incomming(evm_state) => {
+                    evm_state.set_initial(self.evm_accounts.iter().cloned())
+                }
+                EvmState::Committed(_) => panic!("Evm state of the genesis bank is committed"),
+            }
+        }
         bank.set_capitalization();
         if let Some(max_units) = self.bpf_compute_max_units {
             bank.set_bpf_compute_budget(Some(BpfComputeBudget {
@@ -1,14 +1,15 @@
 use {
     solana_program_test::{processor, ProgramTest},
     solana_sdk::{
+        account::Account,
         account_info::{next_account_info, AccountInfo},
         entrypoint::ProgramResult,
-        instruction::{AccountMeta, Instruction},
+        instruction::{AccountMeta, Instruction, InstructionError},
         msg,
         program::invoke,
         pubkey::Pubkey,
         signature::Signer,
-        transaction::Transaction,
+        transaction::{Transaction, TransactionError},
     },
 };
 
@@ -42,6 +43,35 @@ fn invoked_process_instruction(
     Ok(())
 }
 
+// Process instruction to invoke into another program, which reallocates the given account
+fn realloc_invoker_process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _input: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let invoked_program_info = next_account_info(account_info_iter)?;
+    let account_info = next_account_info(account_info_iter)?;
+    invoke(
+        &Instruction::new_with_bincode(
+            *invoked_program_info.key,
+            &[0],
+            vec![AccountMeta::new(*account_info.key, false)],
+        ),
+        &[invoked_program_info.clone(), account_info.clone()],
+    )
+}
+
+// Process instruction to be invoked by another program, growing the account it owns
+fn realloc_invoked_process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _input: &[u8],
+) -> ProgramResult {
+    let account_info = next_account_info(&mut accounts.iter())?;
+    account_info.realloc(account_info.data_len() + 1, false)
+}
+
 #[tokio::test]
 async fn cpi() {
     let invoker_program_id = Pubkey::new_unique();
@@ -77,3 +107,57 @@ async fn cpi() {
         .await
         .unwrap();
 }
+
+#[tokio::test]
+async fn cpi_realloc_non_empty_account() {
+    let invoker_program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "realloc_invoker",
+        invoker_program_id,
+        processor!(realloc_invoker_process_instruction),
+    );
+    let invoked_program_id = Pubkey::new_unique();
+    program_test.add_program(
+        "realloc_invoked",
+        invoked_program_id,
+        processor!(realloc_invoked_process_instruction),
+    );
+    let account = Pubkey::new_unique();
+    program_test.add_account(
+        account,
+        Account {
+            lamports: 1_000_000_000,
+            data: vec![0; 8],
+            owner: invoked_program_id,
+            ..Account::default()
+        },
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let instructions = vec![Instruction::new_with_bincode(
+        invoker_program_id,
+        &[0],
+        vec![
+            AccountMeta::new_readonly(invoked_program_id, false),
+            AccountMeta::new(account, false),
+        ],
+    )];
+
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+
+    // Inner instructions may only resize empty accounts
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidRealloc)
+    );
+}
@@ -0,0 +1,95 @@
+use solana_program_test::{
+    evm_state::{
+        ExitReason, ExitSucceed, H160, H256, HELLO_WORLD_ABI, HELLO_WORLD_CODE_SAVED,
+        HELLO_WORLD_RESULT, U256,
+    },
+    EvmCall, ProgramTest,
+};
+use std::collections::BTreeMap;
+
+#[tokio::test]
+async fn test_evm_accounts_present() {
+    // Arrange
+    let user = H160::repeat_byte(0x11);
+    let contract = H160::repeat_byte(0x22);
+    let code = hex::decode(HELLO_WORLD_CODE_SAVED).unwrap();
+    let mut storage = BTreeMap::new();
+    storage.insert(H256::repeat_byte(0x01), H256::repeat_byte(0x02));
+
+    let mut program_test = ProgramTest::default();
+    program_test.add_evm_account(user, U256::from(42), vec![], BTreeMap::new());
+    program_test.add_evm_account(contract, U256::zero(), code.clone(), storage);
+
+    // Act
+    let (mut banks_client, _payer, _recent_blockhash) = program_test.start().await;
+
+    // Assert
+    assert_eq!(
+        banks_client.get_evm_balance(user).await.unwrap(),
+        U256::from(42)
+    );
+    assert_eq!(
+        banks_client.get_evm_nonce(user).await.unwrap(),
+        U256::zero()
+    );
+    assert_eq!(banks_client.get_evm_code(contract).await.unwrap(), code);
+    assert_eq!(
+        banks_client
+            .get_evm_storage_at(contract, H256::repeat_byte(0x01))
+            .await
+            .unwrap(),
+        H256::repeat_byte(0x02)
+    );
+    assert_eq!(
+        banks_client
+            .get_evm_storage_at(contract, H256::repeat_byte(0x03))
+            .await
+            .unwrap(),
+        H256::zero()
+    );
+    assert_eq!(
+        banks_client
+            .get_evm_transaction_receipt(H256::repeat_byte(0x04))
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_evm_call() {
+    // Arrange
+    let caller = H160::repeat_byte(0x11);
+    let contract = H160::repeat_byte(0x22);
+    let mut program_test = ProgramTest::default();
+    program_test.add_evm_account(
+        contract,
+        U256::zero(),
+        hex::decode(HELLO_WORLD_CODE_SAVED).unwrap(),
+        BTreeMap::new(),
+    );
+    let (mut banks_client, _payer, _recent_blockhash) = program_test.start().await;
+
+    // Act
+    let result = banks_client
+        .evm_call(EvmCall {
+            caller,
+            to: Some(contract),
+            input: hex::decode(HELLO_WORLD_ABI).unwrap(),
+            ..EvmCall::default()
+        })
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(
+        result.exit_reason,
+        ExitReason::Succeed(ExitSucceed::Returned)
+    );
+    assert_eq!(result.exit_data, hex::decode(HELLO_WORLD_RESULT).unwrap());
+    // The call is not committed, so the caller nonce is unchanged
+    assert_eq!(
+        banks_client.get_evm_nonce(caller).await.unwrap(),
+        U256::zero()
+    );
+}
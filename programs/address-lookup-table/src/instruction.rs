@@ -0,0 +1,147 @@
+use crate::{id, state::lookup_table_space};
+use serde_derive::{Deserialize, Serialize};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_instruction,
+};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum ProgramInstruction {
+    /// Initialize an address lookup table account, which was already allocated and assigned
+    /// to this program
+    ///
+    /// # Account references
+    ///   0. `[WRITE, SIGNER]` Uninitialized address lookup table account
+    ///   1. `[]` Authority of the new address lookup table
+    InitializeLookupTable,
+
+    /// Permanently freeze an address lookup table, making it immutable.
+    ///
+    /// # Account references
+    ///   0. `[WRITE]` Address lookup table account to freeze
+    ///   1. `[SIGNER]` Current authority
+    FreezeLookupTable,
+
+    /// Extend an address lookup table with new addresses. Addresses can be used
+    /// by transactions starting from the next slot.
+    ///
+    /// # Account references
+    ///   0. `[WRITE]` Address lookup table account to extend
+    ///   1. `[SIGNER]` Current authority
+    ExtendLookupTable { new_addresses: Vec<Pubkey> },
+
+    /// Deactivate an address lookup table, making it unusable and
+    /// eligible for closure after a short period of time.
+    ///
+    /// # Account references
+    ///   0. `[WRITE]` Address lookup table account to deactivate
+    ///   1. `[SIGNER]` Current authority
+    DeactivateLookupTable,
+
+    /// Close an address lookup table account
+    ///
+    /// # Account references
+    ///   0. `[WRITE]` Address lookup table account to close
+    ///   1. `[SIGNER]` Current authority
+    ///   2. `[WRITE]` Recipient of closed account lamports
+    CloseLookupTable,
+}
+
+/// Constructs instructions which create and initialize a new address lookup table
+/// account, that can hold up to `max_addresses` addresses.
+pub fn create_lookup_table(
+    payer_address: &Pubkey,
+    lookup_table_address: &Pubkey,
+    authority_address: &Pubkey,
+    lamports: u64,
+    max_addresses: usize,
+) -> Vec<Instruction> {
+    vec![
+        system_instruction::create_account(
+            payer_address,
+            lookup_table_address,
+            lamports,
+            lookup_table_space(max_addresses) as u64,
+            &id(),
+        ),
+        Instruction::new_with_bincode(
+            id(),
+            &ProgramInstruction::InitializeLookupTable,
+            vec![
+                AccountMeta::new(*lookup_table_address, true),
+                AccountMeta::new_readonly(*authority_address, false),
+            ],
+        ),
+    ]
+}
+
+/// Constructs an instruction that freezes an address lookup
+/// table so that it can never be closed or extended again. Empty
+/// lookup tables cannot be frozen.
+pub fn freeze_lookup_table(
+    lookup_table_address: &Pubkey,
+    authority_address: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &ProgramInstruction::FreezeLookupTable,
+        vec![
+            AccountMeta::new(*lookup_table_address, false),
+            AccountMeta::new_readonly(*authority_address, true),
+        ],
+    )
+}
+
+/// Constructs an instruction which extends an address lookup
+/// table account with new addresses.
+pub fn extend_lookup_table(
+    lookup_table_address: &Pubkey,
+    authority_address: &Pubkey,
+    new_addresses: Vec<Pubkey>,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &ProgramInstruction::ExtendLookupTable { new_addresses },
+        vec![
+            AccountMeta::new(*lookup_table_address, false),
+            AccountMeta::new_readonly(*authority_address, true),
+        ],
+    )
+}
+
+/// Returns an instruction that deactivates an address lookup
+/// table so that it cannot be extended again and will be unusable
+/// and eligible for closure after a short amount of time.
+pub fn deactivate_lookup_table(
+    lookup_table_address: &Pubkey,
+    authority_address: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &ProgramInstruction::DeactivateLookupTable,
+        vec![
+            AccountMeta::new(*lookup_table_address, false),
+            AccountMeta::new_readonly(*authority_address, true),
+        ],
+    )
+}
+
+/// Returns an instruction that closes an address lookup table
+/// account. The account will be deallocated and the lamports
+/// will be drained to the recipient address.
+pub fn close_lookup_table(
+    lookup_table_address: &Pubkey,
+    authority_address: &Pubkey,
+    recipient_address: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &ProgramInstruction::CloseLookupTable,
+        vec![
+            AccountMeta::new(*lookup_table_address, false),
+            AccountMeta::new_readonly(*authority_address, true),
+            AccountMeta::new(*recipient_address, false),
+        ],
+    )
+}
@@ -0,0 +1,9 @@
+#![allow(clippy::integer_arithmetic)]
+//! Address lookup table program, stores lists of addresses that versioned
+//! transactions can reference by index.
+
+pub mod instruction;
+pub mod processor;
+pub mod state;
+
+solana_sdk::declare_id!("AddressLookupTab1e1111111111111111111111111");
@@ -0,0 +1,551 @@
+//! Address lookup table program
+
+use crate::{
+    id,
+    instruction::ProgramInstruction,
+    state::{AddressLookupTable, LookupTableMeta, LookupTableStatus, ProgramState},
+};
+use solana_sdk::{
+    account::{ReadableAccount, WritableAccount},
+    clock::Clock,
+    ic_msg,
+    instruction::InstructionError,
+    keyed_account::{next_keyed_account, KeyedAccount},
+    process_instruction::{get_sysvar, InvokeContext},
+    program_utils::limited_deserialize,
+    pubkey::Pubkey,
+    slot_hashes::SlotHashes,
+    sysvar,
+};
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    keyed_accounts: &[KeyedAccount],
+    data: &[u8],
+    invoke_context: &mut dyn InvokeContext,
+) -> Result<(), InstructionError> {
+    match limited_deserialize(data)? {
+        ProgramInstruction::InitializeLookupTable => {
+            Processor::initialize_lookup_table(keyed_accounts, invoke_context)
+        }
+        ProgramInstruction::FreezeLookupTable => {
+            Processor::freeze_lookup_table(keyed_accounts, invoke_context)
+        }
+        ProgramInstruction::ExtendLookupTable { new_addresses } => {
+            Processor::extend_lookup_table(keyed_accounts, new_addresses, invoke_context)
+        }
+        ProgramInstruction::DeactivateLookupTable => {
+            Processor::deactivate_lookup_table(keyed_accounts, invoke_context)
+        }
+        ProgramInstruction::CloseLookupTable => {
+            Processor::close_lookup_table(keyed_accounts, invoke_context)
+        }
+    }
+}
+
+pub struct Processor;
+impl Processor {
+    fn initialize_lookup_table(
+        keyed_accounts: &[KeyedAccount],
+        invoke_context: &mut dyn InvokeContext,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts_iter = &mut keyed_accounts.iter();
+        let lookup_table_account = next_keyed_account(keyed_accounts_iter)?;
+        let authority_account = next_keyed_account(keyed_accounts_iter)?;
+
+        // Table account must sign, otherwise anyone could initialize
+        // a table which was created in a separate transaction.
+        if lookup_table_account.signer_key().is_none() {
+            ic_msg!(invoke_context, "Lookup table account must be a signer");
+            return Err(InstructionError::MissingRequiredSignature);
+        }
+        if lookup_table_account.owner()? != id() {
+            return Err(InstructionError::InvalidAccountOwner);
+        }
+
+        let mut lookup_table = lookup_table_account.try_account_ref_mut()?;
+        if AddressLookupTable::deserialize_state(lookup_table.data())?
+            != ProgramState::Uninitialized
+        {
+            ic_msg!(
+                invoke_context,
+                "Lookup table account is already initialized"
+            );
+            return Err(InstructionError::AccountAlreadyInitialized);
+        }
+
+        AddressLookupTable::overwrite_meta_data(
+            lookup_table.data_as_mut_slice(),
+            LookupTableMeta::new(*authority_account.unsigned_key()),
+        )
+    }
+
+    /// Deserialize table metadata, and check that it can be modified by the signed authority
+    fn check_authority(
+        lookup_table_account: &KeyedAccount,
+        authority_account: &KeyedAccount,
+        invoke_context: &dyn InvokeContext,
+    ) -> Result<AddressLookupTable, InstructionError> {
+        if lookup_table_account.owner()? != id() {
+            return Err(InstructionError::InvalidAccountOwner);
+        }
+        let authority_key = authority_account
+            .signer_key()
+            .ok_or(InstructionError::MissingRequiredSignature)?;
+
+        let lookup_table =
+            AddressLookupTable::deserialize(lookup_table_account.try_account_ref()?.data())?;
+        match lookup_table.meta.authority {
+            None => {
+                ic_msg!(invoke_context, "Lookup table is frozen");
+                Err(InstructionError::Immutable)
+            }
+            Some(authority) if authority != *authority_key => {
+                Err(InstructionError::IncorrectAuthority)
+            }
+            Some(_) => Ok(lookup_table),
+        }
+    }
+
+    fn freeze_lookup_table(
+        keyed_accounts: &[KeyedAccount],
+        invoke_context: &mut dyn InvokeContext,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts_iter = &mut keyed_accounts.iter();
+        let lookup_table_account = next_keyed_account(keyed_accounts_iter)?;
+        let authority_account = next_keyed_account(keyed_accounts_iter)?;
+
+        let lookup_table =
+            Self::check_authority(lookup_table_account, authority_account, invoke_context)?;
+        if lookup_table.meta.deactivation_slot != u64::MAX {
+            ic_msg!(invoke_context, "Deactivated tables cannot be frozen");
+            return Err(InstructionError::InvalidArgument);
+        }
+        if lookup_table.addresses.is_empty() {
+            ic_msg!(invoke_context, "Empty lookup tables cannot be frozen");
+            return Err(InstructionError::InvalidInstructionData);
+        }
+
+        let mut meta = lookup_table.meta;
+        meta.authority = None;
+        AddressLookupTable::overwrite_meta_data(
+            lookup_table_account
+                .try_account_ref_mut()?
+                .data_as_mut_slice(),
+            meta,
+        )
+    }
+
+    fn extend_lookup_table(
+        keyed_accounts: &[KeyedAccount],
+        new_addresses: Vec<Pubkey>,
+        invoke_context: &mut dyn InvokeContext,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts_iter = &mut keyed_accounts.iter();
+        let lookup_table_account = next_keyed_account(keyed_accounts_iter)?;
+        let authority_account = next_keyed_account(keyed_accounts_iter)?;
+
+        let lookup_table =
+            Self::check_authority(lookup_table_account, authority_account, invoke_context)?;
+        if lookup_table.meta.deactivation_slot != u64::MAX {
+            ic_msg!(invoke_context, "Deactivated tables cannot be extended");
+            return Err(InstructionError::InvalidArgument);
+        }
+        if new_addresses.is_empty() {
+            ic_msg!(invoke_context, "Must extend with at least one address");
+            return Err(InstructionError::InvalidInstructionData);
+        }
+
+        let mut account = lookup_table_account.try_account_ref_mut()?;
+        let old_num_addresses = lookup_table.addresses.len();
+        let new_num_addresses = old_num_addresses.saturating_add(new_addresses.len());
+        if new_num_addresses > AddressLookupTable::capacity(account.data()) {
+            ic_msg!(
+                invoke_context,
+                "Extended lookup table length {} would exceed table capacity {}",
+                new_num_addresses,
+                AddressLookupTable::capacity(account.data()),
+            );
+            return Err(InstructionError::InvalidInstructionData);
+        }
+
+        let clock = get_sysvar::<Clock>(invoke_context, &sysvar::clock::id())?;
+        let mut meta = lookup_table.meta;
+        if clock.slot != meta.last_extended_slot {
+            meta.last_extended_slot = clock.slot;
+            meta.last_extended_slot_start_index = old_num_addresses as u8;
+        }
+        meta.num_addresses = new_num_addresses as u16;
+
+        let data = account.data_as_mut_slice();
+        AddressLookupTable::overwrite_meta_data(data, meta)?;
+        let addresses_data = &mut data[crate::state::LOOKUP_TABLE_META_SIZE..];
+        for (address, slot) in new_addresses
+            .iter()
+            .zip(addresses_data.chunks_exact_mut(32).skip(old_num_addresses))
+        {
+            slot.copy_from_slice(address.as_ref());
+        }
+        Ok(())
+    }
+
+    fn deactivate_lookup_table(
+        keyed_accounts: &[KeyedAccount],
+        invoke_context: &mut dyn InvokeContext,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts_iter = &mut keyed_accounts.iter();
+        let lookup_table_account = next_keyed_account(keyed_accounts_iter)?;
+        let authority_account = next_keyed_account(keyed_accounts_iter)?;
+
+        let lookup_table =
+            Self::check_authority(lookup_table_account, authority_account, invoke_context)?;
+        if lookup_table.meta.deactivation_slot != u64::MAX {
+            ic_msg!(invoke_context, "Lookup table is already deactivated");
+            return Err(InstructionError::InvalidArgument);
+        }
+
+        let clock = get_sysvar::<Clock>(invoke_context, &sysvar::clock::id())?;
+        let mut meta = lookup_table.meta;
+        meta.deactivation_slot = clock.slot;
+        AddressLookupTable::overwrite_meta_data(
+            lookup_table_account
+                .try_account_ref_mut()?
+                .data_as_mut_slice(),
+            meta,
+        )
+    }
+
+    fn close_lookup_table(
+        keyed_accounts: &[KeyedAccount],
+        invoke_context: &mut dyn InvokeContext,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts_iter = &mut keyed_accounts.iter();
+        let lookup_table_account = next_keyed_account(keyed_accounts_iter)?;
+        let authority_account = next_keyed_account(keyed_accounts_iter)?;
+        let recipient_account = next_keyed_account(keyed_accounts_iter)?;
+
+        if lookup_table_account.unsigned_key() == recipient_account.unsigned_key() {
+            ic_msg!(
+                invoke_context,
+                "Lookup table cannot be the recipient of reclaimed lamports"
+            );
+            return Err(InstructionError::InvalidArgument);
+        }
+
+        let lookup_table =
+            Self::check_authority(lookup_table_account, authority_account, invoke_context)?;
+        let clock = get_sysvar::<Clock>(invoke_context, &sysvar::clock::id())?;
+        let slot_hashes = get_sysvar::<SlotHashes>(invoke_context, &sysvar::slot_hashes::id())?;
+        match lookup_table.meta.status(clock.slot, &slot_hashes) {
+            LookupTableStatus::Activated => {
+                ic_msg!(invoke_context, "Lookup table is not deactivated");
+                return Err(InstructionError::InvalidArgument);
+            }
+            LookupTableStatus::Deactivating { remaining_blocks } => {
+                ic_msg!(
+                    invoke_context,
+                    "Table cannot be closed until it's fully deactivated in {} blocks",
+                    remaining_blocks
+                );
+                return Err(InstructionError::InvalidArgument);
+            }
+            LookupTableStatus::Deactivated => {}
+        }
+
+        let mut account = lookup_table_account.try_account_ref_mut()?;
+        let mut recipient = recipient_account.try_account_ref_mut()?;
+        let withdrawn_lamports = account.lamports();
+        recipient.set_lamports(
+            recipient
+                .lamports()
+                .checked_add(withdrawn_lamports)
+                .ok_or(InstructionError::ArithmeticOverflow)?,
+        );
+        account.set_lamports(0);
+        // Account data can't be deallocated by native programs, account without lamports
+        // is removed at the end of the transaction.
+        account
+            .data_as_mut_slice()
+            .iter_mut()
+            .for_each(|byte| *byte = 0);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{instruction, state::lookup_table_space};
+    use solana_sdk::{
+        account::{Account, AccountSharedData},
+        hash::Hash,
+        instruction::Instruction,
+        process_instruction::{mock_set_sysvar, MockInvokeContext},
+    };
+    use std::cell::RefCell;
+
+    fn new_table_account(max_addresses: usize) -> RefCell<AccountSharedData> {
+        RefCell::new(AccountSharedData::from(Account {
+            lamports: 100,
+            data: vec![0; lookup_table_space(max_addresses)],
+            owner: id(),
+            ..Account::default()
+        }))
+    }
+
+    fn process(
+        instruction: &Instruction,
+        accounts: &[(&Pubkey, &RefCell<AccountSharedData>)],
+        slot: u64,
+        slot_hashes: SlotHashes,
+    ) -> Result<(), InstructionError> {
+        let keyed_accounts: Vec<_> = instruction
+            .accounts
+            .iter()
+            .zip(accounts)
+            .map(|(meta, (key, account))| {
+                assert_eq!(meta.pubkey, **key);
+                if meta.is_writable {
+                    KeyedAccount::new(key, meta.is_signer, account)
+                } else {
+                    KeyedAccount::new_readonly(key, meta.is_signer, account)
+                }
+            })
+            .collect();
+        let mut invoke_context = MockInvokeContext::default();
+        mock_set_sysvar(
+            &mut invoke_context,
+            sysvar::clock::id(),
+            Clock {
+                slot,
+                ..Clock::default()
+            },
+        )
+        .unwrap();
+        mock_set_sysvar(&mut invoke_context, sysvar::slot_hashes::id(), slot_hashes).unwrap();
+        process_instruction(
+            &id(),
+            &keyed_accounts,
+            &instruction.data,
+            &mut invoke_context,
+        )
+    }
+
+    fn initialized_table(
+        table_key: &Pubkey,
+        authority_key: &Pubkey,
+        max_addresses: usize,
+    ) -> RefCell<AccountSharedData> {
+        let table_account = new_table_account(max_addresses);
+        let authority_account = RefCell::new(AccountSharedData::default());
+        let instructions = instruction::create_lookup_table(
+            &Pubkey::new_unique(),
+            table_key,
+            authority_key,
+            100,
+            max_addresses,
+        );
+        process(
+            &instructions[1],
+            &[
+                (table_key, &table_account),
+                (authority_key, &authority_account),
+            ],
+            0,
+            SlotHashes::default(),
+        )
+        .unwrap();
+        table_account
+    }
+
+    #[test]
+    fn test_initialize_lookup_table() {
+        let table_key = Pubkey::new_unique();
+        let authority_key = Pubkey::new_unique();
+        let table_account = initialized_table(&table_key, &authority_key, 2);
+
+        let table = AddressLookupTable::deserialize(table_account.borrow().data()).unwrap();
+        assert_eq!(table.meta, LookupTableMeta::new(authority_key));
+        assert!(table.addresses.is_empty());
+
+        // Can't initialize twice
+        let authority_account = RefCell::new(AccountSharedData::default());
+        let instructions = instruction::create_lookup_table(
+            &Pubkey::new_unique(),
+            &table_key,
+            &authority_key,
+            100,
+            2,
+        );
+        assert_eq!(
+            process(
+                &instructions[1],
+                &[
+                    (&table_key, &table_account),
+                    (&authority_key, &authority_account)
+                ],
+                0,
+                SlotHashes::default(),
+            ),
+            Err(InstructionError::AccountAlreadyInitialized)
+        );
+    }
+
+    #[test]
+    fn test_extend_lookup_table() {
+        let table_key = Pubkey::new_unique();
+        let authority_key = Pubkey::new_unique();
+        let authority_account = RefCell::new(AccountSharedData::default());
+        let table_account = initialized_table(&table_key, &authority_key, 3);
+        let addresses: Vec<_> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let accounts = [
+            (&table_key, &table_account),
+            (&authority_key, &authority_account),
+        ];
+
+        let extend = |new_addresses: &[Pubkey], slot| {
+            process(
+                &instruction::extend_lookup_table(
+                    &table_key,
+                    &authority_key,
+                    new_addresses.to_vec(),
+                ),
+                &accounts,
+                slot,
+                SlotHashes::default(),
+            )
+        };
+
+        assert_eq!(extend(&addresses[..1], 5), Ok(()));
+        assert_eq!(extend(&addresses[1..2], 6), Ok(()));
+        assert_eq!(extend(&addresses[2..3], 6), Ok(()));
+        let table = AddressLookupTable::deserialize(table_account.borrow().data()).unwrap();
+        assert_eq!(table.addresses, addresses[..3].to_vec());
+        assert_eq!(table.meta.last_extended_slot, 6);
+        assert_eq!(table.meta.last_extended_slot_start_index, 1);
+
+        // Table is full
+        assert_eq!(
+            extend(&addresses[3..], 7),
+            Err(InstructionError::InvalidInstructionData)
+        );
+
+        // Wrong authority
+        let other_key = Pubkey::new_unique();
+        assert_eq!(
+            process(
+                &instruction::extend_lookup_table(&table_key, &other_key, vec![addresses[3]]),
+                &[
+                    (&table_key, &table_account),
+                    (&other_key, &authority_account)
+                ],
+                7,
+                SlotHashes::default(),
+            ),
+            Err(InstructionError::IncorrectAuthority)
+        );
+    }
+
+    #[test]
+    fn test_freeze_lookup_table() {
+        let table_key = Pubkey::new_unique();
+        let authority_key = Pubkey::new_unique();
+        let authority_account = RefCell::new(AccountSharedData::default());
+        let table_account = initialized_table(&table_key, &authority_key, 2);
+        let accounts = [
+            (&table_key, &table_account),
+            (&authority_key, &authority_account),
+        ];
+        let freeze = instruction::freeze_lookup_table(&table_key, &authority_key);
+
+        // Empty table can't be frozen
+        assert_eq!(
+            process(&freeze, &accounts, 1, SlotHashes::default()),
+            Err(InstructionError::InvalidInstructionData)
+        );
+
+        process(
+            &instruction::extend_lookup_table(
+                &table_key,
+                &authority_key,
+                vec![Pubkey::new_unique()],
+            ),
+            &accounts,
+            1,
+            SlotHashes::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            process(&freeze, &accounts, 1, SlotHashes::default()),
+            Ok(())
+        );
+        let table = AddressLookupTable::deserialize(table_account.borrow().data()).unwrap();
+        assert_eq!(table.meta.authority, None);
+
+        assert_eq!(
+            process(
+                &instruction::deactivate_lookup_table(&table_key, &authority_key),
+                &accounts,
+                2,
+                SlotHashes::default()
+            ),
+            Err(InstructionError::Immutable)
+        );
+    }
+
+    #[test]
+    fn test_deactivate_and_close_lookup_table() {
+        let table_key = Pubkey::new_unique();
+        let authority_key = Pubkey::new_unique();
+        let recipient_key = Pubkey::new_unique();
+        let authority_account = RefCell::new(AccountSharedData::default());
+        let recipient_account = RefCell::new(AccountSharedData::default());
+        let table_account = initialized_table(&table_key, &authority_key, 2);
+        let accounts = [
+            (&table_key, &table_account),
+            (&authority_key, &authority_account),
+            (&recipient_key, &recipient_account),
+        ];
+        let close = instruction::close_lookup_table(&table_key, &authority_key, &recipient_key);
+
+        // Active table can't be closed
+        assert_eq!(
+            process(&close, &accounts, 10, SlotHashes::default()),
+            Err(InstructionError::InvalidArgument)
+        );
+
+        process(
+            &instruction::deactivate_lookup_table(&table_key, &authority_key),
+            &accounts,
+            10,
+            SlotHashes::default(),
+        )
+        .unwrap();
+        let table = AddressLookupTable::deserialize(table_account.borrow().data()).unwrap();
+        assert_eq!(table.meta.deactivation_slot, 10);
+
+        // Deactivation slot is still recent
+        assert_eq!(
+            process(
+                &close,
+                &accounts,
+                11,
+                SlotHashes::new(&[(10, Hash::default())])
+            ),
+            Err(InstructionError::InvalidArgument)
+        );
+
+        assert_eq!(
+            process(
+                &close,
+                &accounts,
+                11,
+                SlotHashes::new(&[(9, Hash::default())])
+            ),
+            Ok(())
+        );
+        assert_eq!(table_account.borrow().lamports(), 0);
+        assert!(table_account.borrow().data().iter().all(|byte| *byte == 0));
+        assert_eq!(recipient_account.borrow().lamports(), 100);
+    }
+}
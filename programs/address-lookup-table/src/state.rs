@@ -0,0 +1,343 @@
+use serde_derive::{Deserialize, Serialize};
+use solana_sdk::{
+    clock::Slot,
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    slot_hashes::{self, SlotHashes},
+};
+use std::convert::TryInto;
+
+/// The serialized size of lookup table metadata, addresses are stored right after it.
+pub const LOOKUP_TABLE_META_SIZE: usize = 56;
+
+/// Maximum number of addresses that a lookup table can hold.
+pub const LOOKUP_TABLE_MAX_ADDRESSES: usize = 256;
+
+/// Size of a lookup table account that can hold `max_addresses` addresses.
+///
+/// Native programs can't resize accounts, so the whole space is allocated on creation.
+pub fn lookup_table_space(max_addresses: usize) -> usize {
+    LOOKUP_TABLE_META_SIZE + max_addresses.min(LOOKUP_TABLE_MAX_ADDRESSES) * 32
+}
+
+/// Program account states
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum ProgramState {
+    /// Account is not initialized.
+    Uninitialized,
+    /// Initialized `LookupTable` account.
+    LookupTable(LookupTableMeta),
+}
+
+/// Activation status of a lookup table
+#[derive(Debug, PartialEq, Clone)]
+pub enum LookupTableStatus {
+    Activated,
+    Deactivating { remaining_blocks: usize },
+    Deactivated,
+}
+
+/// Address lookup table metadata
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct LookupTableMeta {
+    /// Lookup tables cannot be closed until the deactivation slot is
+    /// no longer "recent" (not accessible in the `SlotHashes` sysvar).
+    pub deactivation_slot: Slot,
+    /// The slot that the table was last extended. Address tables may
+    /// only be used to lookup addresses that were extended before
+    /// the current bank's slot.
+    pub last_extended_slot: Slot,
+    /// The start index where the table was last extended from during
+    /// the `last_extended_slot`.
+    pub last_extended_slot_start_index: u8,
+    /// Authority address which must sign for each modification.
+    pub authority: Option<Pubkey>,
+    /// Number of addresses stored after the metadata.
+    pub num_addresses: u16,
+}
+
+impl Default for LookupTableMeta {
+    fn default() -> Self {
+        Self {
+            deactivation_slot: Slot::MAX,
+            last_extended_slot: 0,
+            last_extended_slot_start_index: 0,
+            authority: None,
+            num_addresses: 0,
+        }
+    }
+}
+
+impl LookupTableMeta {
+    pub fn new(authority: Pubkey) -> Self {
+        LookupTableMeta {
+            authority: Some(authority),
+            ..LookupTableMeta::default()
+        }
+    }
+
+    /// Returns whether the table is considered active for address lookups
+    pub fn is_active(&self, current_slot: Slot, slot_hashes: &SlotHashes) -> bool {
+        match self.status(current_slot, slot_hashes) {
+            LookupTableStatus::Activated => true,
+            LookupTableStatus::Deactivating { .. } => true,
+            LookupTableStatus::Deactivated => false,
+        }
+    }
+
+    /// Return the current status of the lookup table
+    pub fn status(&self, current_slot: Slot, slot_hashes: &SlotHashes) -> LookupTableStatus {
+        if self.deactivation_slot == Slot::MAX {
+            LookupTableStatus::Activated
+        } else if self.deactivation_slot == current_slot {
+            LookupTableStatus::Deactivating {
+                remaining_blocks: slot_hashes::MAX_ENTRIES.saturating_add(1),
+            }
+        } else if let Some(slot_hash_position) = slot_hashes
+            .iter()
+            .position(|(slot, _)| *slot == self.deactivation_slot)
+        {
+            // Deactivation requires a cool-down period to give in-flight transactions
+            // enough time to land and to remove indeterminism caused by transactions loading
+            // addresses in the same slot when a table is closed.
+            LookupTableStatus::Deactivating {
+                remaining_blocks: slot_hashes::MAX_ENTRIES.saturating_sub(slot_hash_position),
+            }
+        } else {
+            LookupTableStatus::Deactivated
+        }
+    }
+
+    /// Number of addresses that can be used in `current_slot`. Addresses appended in the
+    /// current slot can only be used starting from the next one.
+    pub fn active_addresses_len(&self, current_slot: Slot) -> usize {
+        if current_slot > self.last_extended_slot {
+            usize::from(self.num_addresses)
+        } else {
+            usize::from(self.last_extended_slot_start_index)
+        }
+    }
+}
+
+/// Errors which can happen while loading addresses from a lookup table
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AddressLookupError {
+    /// Attempted to lookup addresses from a table that does not exist
+    LookupTableAccountNotFound,
+    /// Attempted to lookup addresses from an account owned by the wrong program
+    InvalidAccountOwner,
+    /// Attempted to lookup addresses from an invalid account
+    InvalidAccountData,
+    /// Address lookup contains an invalid index
+    InvalidLookupIndex,
+}
+
+/// Deserialized lookup table account
+#[derive(Debug, PartialEq, Clone)]
+pub struct AddressLookupTable {
+    pub meta: LookupTableMeta,
+    pub addresses: Vec<Pubkey>,
+}
+
+impl AddressLookupTable {
+    /// Serialize table metadata into the head of account data
+    pub fn overwrite_meta_data(
+        data: &mut [u8],
+        lookup_table_meta: LookupTableMeta,
+    ) -> Result<(), InstructionError> {
+        let meta_data = data
+            .get_mut(0..LOOKUP_TABLE_META_SIZE)
+            .ok_or(InstructionError::InvalidAccountData)?;
+        meta_data.fill(0);
+        bincode::serialize_into(meta_data, &ProgramState::LookupTable(lookup_table_meta))
+            .map_err(|_| InstructionError::GenericError)?;
+        Ok(())
+    }
+
+    /// Deserialize the program state stored at the head of account data
+    pub fn deserialize_state(data: &[u8]) -> Result<ProgramState, InstructionError> {
+        let meta_data = data
+            .get(0..LOOKUP_TABLE_META_SIZE)
+            .ok_or(InstructionError::InvalidAccountData)?;
+        bincode::deserialize(meta_data).map_err(|_| InstructionError::InvalidAccountData)
+    }
+
+    /// Maximum number of addresses the account data can hold
+    pub fn capacity(data: &[u8]) -> usize {
+        data.len().saturating_sub(LOOKUP_TABLE_META_SIZE) / 32
+    }
+
+    /// Efficiently deserialize an address table
+    pub fn deserialize(data: &[u8]) -> Result<AddressLookupTable, InstructionError> {
+        let meta = match Self::deserialize_state(data)? {
+            ProgramState::LookupTable(meta) => meta,
+            ProgramState::Uninitialized => return Err(InstructionError::UninitializedAccount),
+        };
+
+        let num_addresses = usize::from(meta.num_addresses);
+        if num_addresses > Self::capacity(data) {
+            return Err(InstructionError::InvalidAccountData);
+        }
+        let addresses = data[LOOKUP_TABLE_META_SIZE..]
+            .chunks_exact(32)
+            .take(num_addresses)
+            .map(|bytes| Pubkey::new_from_array(bytes.try_into().unwrap()))
+            .collect();
+
+        Ok(Self { meta, addresses })
+    }
+
+    /// Lookup addresses for provided table indexes. Since lookups are performed on
+    /// tables which are not read-locked, this implementation needs to be careful
+    /// about resolving addresses consistently.
+    pub fn lookup(
+        &self,
+        current_slot: Slot,
+        indexes: &[u8],
+        slot_hashes: &SlotHashes,
+    ) -> Result<Vec<Pubkey>, AddressLookupError> {
+        if !self.meta.is_active(current_slot, slot_hashes) {
+            // Once a lookup table is no longer active, it can be closed
+            // at any point, so returning a specific error for deactivated
+            // lookup tables could result in a race condition.
+            return Err(AddressLookupError::LookupTableAccountNotFound);
+        }
+
+        // It's impossible to know whether or not an index was valid when
+        // addresses were extended in the current slot.
+        let active_addresses = &self.addresses[..self
+            .meta
+            .active_addresses_len(current_slot)
+            .min(self.addresses.len())];
+        indexes
+            .iter()
+            .map(|idx| active_addresses.get(usize::from(*idx)).cloned())
+            .collect::<Option<_>>()
+            .ok_or(AddressLookupError::InvalidLookupIndex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::Hash;
+
+    fn table_data(meta: LookupTableMeta, addresses: &[Pubkey]) -> Vec<u8> {
+        let mut data = vec![0; lookup_table_space(addresses.len())];
+        AddressLookupTable::overwrite_meta_data(&mut data, meta).unwrap();
+        for (i, address) in addresses.iter().enumerate() {
+            let offset = LOOKUP_TABLE_META_SIZE + i * 32;
+            data[offset..offset + 32].copy_from_slice(address.as_ref());
+        }
+        data
+    }
+
+    #[test]
+    fn test_lookup_table_meta_size() {
+        let mut data = vec![0; LOOKUP_TABLE_META_SIZE];
+        let meta = LookupTableMeta {
+            deactivation_slot: Slot::MAX,
+            last_extended_slot: Slot::MAX,
+            last_extended_slot_start_index: u8::MAX,
+            authority: Some(Pubkey::new_unique()),
+            num_addresses: u16::MAX,
+        };
+        assert!(AddressLookupTable::overwrite_meta_data(&mut data, meta).is_ok());
+        assert_eq!(
+            bincode::serialized_size(&ProgramState::LookupTable(LookupTableMeta::new(
+                Pubkey::new_unique()
+            )))
+            .unwrap() as usize,
+            LOOKUP_TABLE_META_SIZE
+        );
+    }
+
+    #[test]
+    fn test_lookup_table_status() {
+        let slot_hashes = SlotHashes::new(&[(9, Hash::default()), (8, Hash::default())]);
+        let meta = |deactivation_slot| LookupTableMeta {
+            deactivation_slot,
+            ..LookupTableMeta::default()
+        };
+
+        assert_eq!(
+            meta(Slot::MAX).status(10, &slot_hashes),
+            LookupTableStatus::Activated
+        );
+        assert_eq!(
+            meta(10).status(10, &slot_hashes),
+            LookupTableStatus::Deactivating {
+                remaining_blocks: slot_hashes::MAX_ENTRIES + 1
+            }
+        );
+        assert_eq!(
+            meta(8).status(10, &slot_hashes),
+            LookupTableStatus::Deactivating {
+                remaining_blocks: slot_hashes::MAX_ENTRIES - 1
+            }
+        );
+        assert!(meta(8).is_active(10, &slot_hashes));
+        assert_eq!(
+            meta(5).status(10, &slot_hashes),
+            LookupTableStatus::Deactivated
+        );
+        assert!(!meta(5).is_active(10, &slot_hashes));
+    }
+
+    #[test]
+    fn test_lookup() {
+        let addresses: Vec<_> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let meta = LookupTableMeta {
+            last_extended_slot: 10,
+            last_extended_slot_start_index: 2,
+            num_addresses: addresses.len() as u16,
+            ..LookupTableMeta::new(Pubkey::new_unique())
+        };
+        let table = AddressLookupTable::deserialize(&table_data(meta, &addresses)).unwrap();
+        assert_eq!(table.addresses, addresses);
+
+        let slot_hashes = SlotHashes::default();
+        // addresses extended in the current slot can't be used yet
+        assert_eq!(
+            table.lookup(10, &[1, 0], &slot_hashes),
+            Ok(vec![addresses[1], addresses[0]])
+        );
+        assert_eq!(
+            table.lookup(10, &[2], &slot_hashes),
+            Err(AddressLookupError::InvalidLookupIndex)
+        );
+        assert_eq!(
+            table.lookup(11, &[3, 2], &slot_hashes),
+            Ok(vec![addresses[3], addresses[2]])
+        );
+        assert_eq!(
+            table.lookup(11, &[4], &slot_hashes),
+            Err(AddressLookupError::InvalidLookupIndex)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_invalid_data() {
+        assert_eq!(
+            AddressLookupTable::deserialize(&[0; 10]),
+            Err(InstructionError::InvalidAccountData)
+        );
+        assert_eq!(
+            AddressLookupTable::deserialize(&[0; LOOKUP_TABLE_META_SIZE]),
+            Err(InstructionError::UninitializedAccount)
+        );
+
+        // more addresses than the account can hold
+        let meta = LookupTableMeta {
+            num_addresses: 2,
+            ..LookupTableMeta::default()
+        };
+        let mut data = table_data(meta, &[Pubkey::new_unique()]);
+        data.truncate(LOOKUP_TABLE_META_SIZE + 32);
+        assert_eq!(
+            AddressLookupTable::deserialize(&data),
+            Err(InstructionError::InvalidAccountData)
+        );
+    }
+}
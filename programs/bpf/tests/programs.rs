@@ -278,14 +278,15 @@ fn process_transaction_and_record_inner(
     let signature = tx.signatures.get(0).unwrap().clone();
     let txs = vec![tx];
     let tx_batch = bank.prepare_batch(txs.iter());
-    let (mut results, _, mut inner, _transaction_logs) = bank.load_execute_and_commit_transactions(
-        &tx_batch,
-        MAX_PROCESSING_AGE,
-        false,
-        true,
-        false,
-        &mut ExecuteTimings::default(),
-    );
+    let (mut results, _, mut inner, _transaction_logs, _return_data) = bank
+        .load_execute_and_commit_transactions(
+            &tx_batch,
+            MAX_PROCESSING_AGE,
+            false,
+            true,
+            false,
+            &mut ExecuteTimings::default(),
+        );
     let inner_instructions = if inner.is_empty() {
         Some(vec![vec![]])
     } else {
@@ -317,6 +318,7 @@ fn execute_transactions(bank: &Bank, txs: &[Transaction]) -> Vec<ConfirmedTransa
         },
         mut inner_instructions,
         mut transaction_logs,
+        return_data,
     ) = bank.load_execute_and_commit_transactions(
         &batch,
         std::usize::MAX,
@@ -343,6 +345,7 @@ fn execute_transactions(bank: &Bank, txs: &[Transaction]) -> Vec<ConfirmedTransa
         tx_pre_token_balances.into_iter(),
         tx_post_token_balances.into_iter(),
         transaction_logs.into_iter(),
+        return_data.into_iter(),
     )
     .map(
         |(
@@ -354,6 +357,7 @@ fn execute_transactions(bank: &Bank, txs: &[Transaction]) -> Vec<ConfirmedTransa
             pre_token_balances,
             post_token_balances,
             log_messages,
+            return_data,
         )| {
             let fee_calculator = nonce_rollback
                 .map(|nonce_rollback| nonce_rollback.fee_calculator())
@@ -383,12 +387,14 @@ fn execute_transactions(bank: &Bank, txs: &[Transaction]) -> Vec<ConfirmedTransa
                 inner_instructions,
                 log_messages: Some(log_messages),
                 rewards: None,
+                return_data,
+                loaded_addresses: None,
             };
 
             ConfirmedTransaction {
                 slot: bank.slot(),
                 transaction: TransactionWithStatusMeta {
-                    transaction: tx.clone(),
+                    transaction: tx.clone().into(),
                     meta: Some(tx_status_meta),
                 },
                 block_time: None,
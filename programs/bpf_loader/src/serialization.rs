@@ -167,7 +167,7 @@ pub fn get_serialized_account_size_aligned(
         size_of::<u8>() // is_signer
             + size_of::<u8>() // is_writable
             + size_of::<u8>() // executable
-            + 4 // padding to 128-bit aligned
+            + size_of::<u32>() // original data len, padding to 128-bit aligned
             + size_of::<Pubkey>()  // key
             + size_of::<Pubkey>() // owner
             + size_of::<u64>()  // lamports
@@ -219,8 +219,8 @@ pub fn serialize_parameters_aligned(
                 .map_err(|_| InstructionError::InvalidArgument)?;
             v.write_u8(keyed_account.executable()? as u8)
                 .map_err(|_| InstructionError::InvalidArgument)?;
-            v.write_all(&[0u8, 0, 0, 0])
-                .map_err(|_| InstructionError::InvalidArgument)?; // 4 bytes of padding to make 128-bit aligned
+            v.write_u32::<LittleEndian>(keyed_account.data_len()? as u32)
+                .map_err(|_| InstructionError::InvalidArgument)?; // original data len, padding to make 128-bit aligned
             v.write_all(keyed_account.unsigned_key().as_ref())
                 .map_err(|_| InstructionError::InvalidArgument)?;
             v.write_all(keyed_account.owner()?.as_ref())
@@ -266,7 +266,7 @@ pub fn deserialize_parameters_aligned(
             start += size_of::<u8>() // is_signer
                 + size_of::<u8>() // is_writable
                 + size_of::<u8>() // executable
-                + 4 // padding to 128-bit aligned
+                + size_of::<u32>() // original data len, padding to 128-bit aligned
                 + size_of::<Pubkey>(); // key
             account.owner = Pubkey::new(&buffer[start..start + size_of::<Pubkey>()]);
             start += size_of::<Pubkey>(); // owner
@@ -303,6 +303,7 @@ mod tests {
         account_info::AccountInfo,
         bpf_loader,
         entrypoint::deserialize,
+        program_error::ProgramError,
     };
     use std::{
         cell::RefCell,
@@ -624,4 +625,67 @@ mod tests {
 
         (program_id, accounts, instruction_data)
     }
+
+    #[test]
+    fn test_serialize_parameters_realloc() {
+        let program_id = solana_sdk::pubkey::new_rand();
+        let keys = vec![
+            solana_sdk::pubkey::new_rand(),
+            solana_sdk::pubkey::new_rand(),
+        ];
+        let accounts = [
+            RefCell::new(AccountSharedData::from(Account {
+                lamports: 1,
+                data: vec![1u8, 2, 3, 4, 5],
+                owner: program_id,
+                executable: false,
+                rent_epoch: 100,
+            })),
+            RefCell::new(AccountSharedData::from(Account {
+                lamports: 2,
+                data: vec![6u8, 7, 8],
+                owner: program_id,
+                executable: false,
+                rent_epoch: 100,
+            })),
+        ];
+        let keyed_accounts: Vec<_> = keys
+            .iter()
+            .zip(&accounts)
+            .map(|(key, account)| KeyedAccount::new(key, false, account))
+            .collect();
+
+        let mut serialized =
+            serialize_parameters(&bpf_loader::id(), &program_id, &keyed_accounts, &[]).unwrap();
+        let (_, de_accounts, _) =
+            unsafe { deserialize(&mut serialized.as_slice_mut()[0] as *mut u8) };
+
+        assert_eq!(unsafe { de_accounts[0].original_data_len() }, 5);
+        assert_eq!(unsafe { de_accounts[1].original_data_len() }, 3);
+
+        // Grow the first account and shrink the second one
+        de_accounts[0]
+            .realloc(5 + MAX_PERMITTED_DATA_INCREASE, true)
+            .unwrap();
+        de_accounts[0].try_borrow_mut_data().unwrap()[5] = 42;
+        de_accounts[1].realloc(1, false).unwrap();
+        assert_eq!(
+            de_accounts[0].realloc(6 + MAX_PERMITTED_DATA_INCREASE, false),
+            Err(ProgramError::InvalidRealloc)
+        );
+        assert_eq!(de_accounts[0].data_len(), 5 + MAX_PERMITTED_DATA_INCREASE);
+        assert_eq!(de_accounts[1].data_len(), 1);
+
+        deserialize_parameters(
+            &bpf_loader::id(),
+            &keyed_accounts,
+            serialized.as_slice(),
+            true,
+        )
+        .unwrap();
+        let account = accounts[0].borrow();
+        assert_eq!(account.data().len(), 5 + MAX_PERMITTED_DATA_INCREASE);
+        assert_eq!(&account.data()[..6], &[1u8, 2, 3, 4, 5, 42]);
+        assert_eq!(accounts[1].borrow().data(), &[6u8]);
+    }
 }
@@ -21,7 +21,8 @@ use solana_sdk::{
     feature_set::{
         cpi_data_cost, cpi_share_ro_and_exec_accounts, demote_sysvar_write_locks,
         enforce_aligned_host_addrs, keccak256_syscall_enabled,
-        set_upgrade_authority_via_cpi_enabled, sysvar_via_syscall, update_data_on_realloc,
+        set_upgrade_authority_via_cpi_enabled, sophon::return_data_syscall_enabled,
+        sysvar_via_syscall, update_data_on_realloc,
     },
     hash::{Hasher, HASH_BYTES},
     ic_msg,
@@ -30,6 +31,7 @@ use solana_sdk::{
     keyed_account::KeyedAccount,
     native_loader,
     process_instruction::{self, stable_log, ComputeMeter, InvokeContext, Logger},
+    program::MAX_RETURN_DATA,
     pubkey::{Pubkey, PubkeyError, MAX_SEEDS},
     rent::Rent,
     sysvar::{self, fees::Fees, Sysvar, SysvarId},
@@ -37,6 +39,7 @@ use solana_sdk::{
 use std::{
     alloc::Layout,
     cell::{Ref, RefCell, RefMut},
+    cmp::min,
     mem::{align_of, size_of},
     rc::Rc,
     slice::from_raw_parts_mut,
@@ -74,6 +77,8 @@ pub enum SyscallError {
     InstructionTooLarge(usize, usize),
     #[error("Too many accounts passed to inner instruction")]
     TooManyAccounts,
+    #[error("Return data too large ({0} > {1})")]
+    ReturnDataTooLarge(u64, u64),
 }
 impl From<SyscallError> for EbpfError<BpfError> {
     fn from(error: SyscallError) -> Self {
@@ -145,6 +150,13 @@ pub fn register_syscalls(
             .register_syscall_by_name(b"sol_get_rent_sysvar", SyscallGetRentSysvar::call)?;
     }
 
+    if invoke_context.is_feature_active(&return_data_syscall_enabled::id()) {
+        syscall_registry
+            .register_syscall_by_name(b"sol_set_return_data", SyscallSetReturnData::call)?;
+        syscall_registry
+            .register_syscall_by_name(b"sol_get_return_data", SyscallGetReturnData::call)?;
+    }
+
     syscall_registry
         .register_syscall_by_name(b"sol_invoke_signed_c", SyscallInvokeSignedC::call)?;
     syscall_registry
@@ -270,6 +282,8 @@ pub fn bind_syscall_context_objects<'a>(
     );
 
     let is_sysvar_via_syscall_active = invoke_context.is_feature_active(&sysvar_via_syscall::id());
+    let is_return_data_syscall_active =
+        invoke_context.is_feature_active(&return_data_syscall_enabled::id());
 
     let invoke_context = Rc::new(RefCell::new(invoke_context));
 
@@ -306,6 +320,23 @@ pub fn bind_syscall_context_objects<'a>(
         }),
     );
 
+    bind_feature_gated_syscall_context_object!(
+        vm,
+        is_return_data_syscall_active,
+        Box::new(SyscallSetReturnData {
+            invoke_context: invoke_context.clone(),
+            loader_id,
+        }),
+    );
+    bind_feature_gated_syscall_context_object!(
+        vm,
+        is_return_data_syscall_active,
+        Box::new(SyscallGetReturnData {
+            invoke_context: invoke_context.clone(),
+            loader_id,
+        }),
+    );
+
     // Cross-program invocation syscalls
     vm.bind_syscall_context_object(
         Box::new(SyscallInvokeSignedC {
@@ -1140,6 +1171,118 @@ impl<'a> SyscallObject<BpfError> for SyscallKeccak256<'a> {
     }
 }
 
+/// Set return data
+struct SyscallSetReturnData<'a> {
+    invoke_context: Rc<RefCell<&'a mut dyn InvokeContext>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BpfError> for SyscallSetReturnData<'a> {
+    fn call(
+        &mut self,
+        addr: u64,
+        len: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BpfError>>,
+    ) {
+        let mut invoke_context = question_mark!(
+            self.invoke_context
+                .try_borrow_mut()
+                .map_err(|_| SyscallError::InvokeContextBorrowFailed),
+            result
+        );
+        let bpf_compute_budget = invoke_context.get_bpf_compute_budget();
+        let cost =
+            len / bpf_compute_budget.cpi_bytes_per_unit + bpf_compute_budget.syscall_base_cost;
+        question_mark!(invoke_context.get_compute_meter().consume(cost), result);
+
+        if len > MAX_RETURN_DATA as u64 {
+            *result = Err(SyscallError::ReturnDataTooLarge(len, MAX_RETURN_DATA as u64).into());
+            return;
+        }
+        let return_data = if len == 0 {
+            Vec::new()
+        } else {
+            question_mark!(
+                translate_slice::<u8>(memory_mapping, addr, len, self.loader_id, true),
+                result
+            )
+            .to_vec()
+        };
+        question_mark!(
+            invoke_context
+                .set_return_data(return_data)
+                .map_err(SyscallError::InstructionError),
+            result
+        );
+
+        *result = Ok(0);
+    }
+}
+
+/// Get return data
+struct SyscallGetReturnData<'a> {
+    invoke_context: Rc<RefCell<&'a mut dyn InvokeContext>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BpfError> for SyscallGetReturnData<'a> {
+    fn call(
+        &mut self,
+        return_data_addr: u64,
+        len: u64,
+        program_id_addr: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BpfError>>,
+    ) {
+        let invoke_context = question_mark!(
+            self.invoke_context
+                .try_borrow()
+                .map_err(|_| SyscallError::InvokeContextBorrowFailed),
+            result
+        );
+        let bpf_compute_budget = invoke_context.get_bpf_compute_budget();
+        question_mark!(
+            invoke_context
+                .get_compute_meter()
+                .consume(bpf_compute_budget.syscall_base_cost),
+            result
+        );
+
+        let (program_id, return_data) = invoke_context.get_return_data();
+        let length = min(return_data.len() as u64, len);
+        if length != 0 {
+            let cost =
+                (length + size_of::<Pubkey>() as u64) / bpf_compute_budget.cpi_bytes_per_unit;
+            question_mark!(invoke_context.get_compute_meter().consume(cost), result);
+
+            let return_data_result = question_mark!(
+                translate_slice_mut::<u8>(
+                    memory_mapping,
+                    return_data_addr,
+                    length,
+                    self.loader_id,
+                    true,
+                ),
+                result
+            );
+            return_data_result.copy_from_slice(&return_data[..length as usize]);
+
+            let program_id_result = question_mark!(
+                translate_type_mut::<Pubkey>(memory_mapping, program_id_addr, self.loader_id, true),
+                result
+            );
+            *program_id_result = program_id;
+        }
+
+        // Return the full length of the return data, which may be larger than what was copied
+        *result = Ok(return_data.len() as u64);
+    }
+}
+
 // Cross-program invocation syscalls
 
 struct AccountReferences<'a> {
@@ -3102,4 +3245,86 @@ mod tests {
             assert_eq!(got_rent, src_rent);
         }
     }
+
+    #[test]
+    fn test_syscall_return_data() {
+        let program_id = Pubkey::new_unique();
+        let mut invoke_context = MockInvokeContext {
+            key: program_id,
+            ..MockInvokeContext::default()
+        };
+        let invoke_context = Rc::new(RefCell::new(&mut invoke_context as &mut dyn InvokeContext));
+
+        let data = b"return data".to_vec();
+        let data_va = 4096;
+        let got_data = vec![0u8; 4];
+        let got_data_va = 8192;
+        let got_program_id = Pubkey::default();
+        let got_program_id_va = 12288;
+        let memory_mapping = MemoryMapping::new::<UserError>(
+            vec![
+                MemoryRegion::new_from_slice(&data, data_va, 0, false),
+                MemoryRegion::new_from_slice(&got_data, got_data_va, 0, true),
+                MemoryRegion::new_from_slice(got_program_id.as_ref(), got_program_id_va, 0, true),
+            ],
+            &DEFAULT_CONFIG,
+        )
+        .unwrap();
+
+        let mut set_return_data = SyscallSetReturnData {
+            invoke_context: invoke_context.clone(),
+            loader_id: &bpf_loader::id(),
+        };
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        set_return_data.call(
+            data_va,
+            data.len() as u64,
+            0,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(result.unwrap(), 0);
+
+        // Only the requested length is copied, but the full length is returned
+        let mut get_return_data = SyscallGetReturnData {
+            invoke_context: invoke_context.clone(),
+            loader_id: &bpf_loader::id(),
+        };
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        get_return_data.call(
+            got_data_va,
+            got_data.len() as u64,
+            got_program_id_va,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(result.unwrap(), data.len() as u64);
+        assert_eq!(got_data, b"retu");
+        assert_eq!(got_program_id, program_id);
+
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        set_return_data.call(
+            data_va,
+            MAX_RETURN_DATA as u64 + 1,
+            0,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert!(matches!(
+            result,
+            Err(EbpfError::UserError(BpfError::SyscallError(
+                SyscallError::ReturnDataTooLarge(_, _)
+            )))
+        ));
+        assert_eq!(
+            invoke_context.borrow().get_return_data(),
+            (program_id, &data[..])
+        );
+    }
 }
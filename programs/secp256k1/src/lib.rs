@@ -17,6 +17,7 @@ pub fn process_instruction(
 pub mod test {
     use rand::{thread_rng, Rng};
     use solana_sdk::{
+        feature_set::FeatureSet,
         hash::Hash,
         secp256k1_instruction::{
             new_secp256k1_instruction, SecpSignatureOffsets, SIGNATURE_OFFSETS_SERIALIZED_SIZE,
@@ -46,7 +47,7 @@ pub mod test {
             Hash::default(),
         );
 
-        assert!(tx.verify_precompiles().is_ok());
+        assert!(tx.verify_precompiles(&FeatureSet::all_enabled()).is_ok());
 
         let index = thread_rng().gen_range(0..secp_instruction.data.len());
         secp_instruction.data[index] = secp_instruction.data[index].wrapping_add(12);
@@ -56,6 +57,6 @@ pub mod test {
             &[&mint_keypair],
             Hash::default(),
         );
-        assert!(tx.verify_precompiles().is_err());
+        assert!(tx.verify_precompiles(&FeatureSet::all_enabled()).is_err());
     }
 }
@@ -48,6 +48,21 @@ pub enum StakeError {
 
     #[error("custodian signature not present")]
     CustodianSignatureMissing,
+
+    #[error("only fully active stake can be redelegated")]
+    RedelegateTransientOrInactiveStake,
+
+    #[error("stake cannot be redelegated to the vote account it is already delegated to")]
+    RedelegateToSameVoteAccount,
+
+    #[error("reference vote account has not voted in each of the recent epochs")]
+    InsufficientReferenceVotes,
+
+    #[error("stake account is not delegated to the provided vote account")]
+    VoteAddressMismatch,
+
+    #[error("delegated vote account has not been delinquent for the minimum number of epochs")]
+    MinimumDelinquentEpochsForDeactivationNotMet,
 }
 
 impl<E> DecodeError<E> for StakeError {
@@ -169,6 +184,37 @@ pub enum StakeInstruction {
     ///   3. Optional: [SIGNER] Lockup authority, if updating StakeAuthorize::Withdrawer before
     ///      lockup expiration
     AuthorizeWithSeed(AuthorizeWithSeedArgs),
+
+    /// Redelegate fully active stake to another vote account
+    ///
+    /// The source stake account is deactivated in the current epoch, and its active stake is
+    /// moved into the uninitialized stake account, which starts activating towards the new
+    /// vote account in the same epoch, so the stake never sits undelegated for a full epoch.
+    /// The source account keeps its rent-exempt reserve and can be withdrawn from or
+    /// redelegated again once its cooldown completes.
+    ///
+    /// # Account references
+    ///   0. [WRITE] Delegated stake account to be redelegated; must be fully active
+    ///   1. [WRITE] Uninitialized stake account that will hold the redelegated stake
+    ///   2. [] Vote account to which the stake will be redelegated
+    ///   3. [] Clock sysvar
+    ///   4. [] Stake history sysvar that carries stake warmup/cooldown history
+    ///   5. [] Address of config account that carries stake config
+    ///   6. [SIGNER] Stake authority
+    Redelegate,
+
+    /// Deactivate stake delegated to a vote account that has been delinquent for at least
+    ///   `MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION` epochs.
+    ///
+    /// No signer is required; the reference vote account must have voted in each of the last
+    ///   `MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION` epochs, so that a cluster-wide outage
+    ///   cannot be used to deactivate every stake.
+    ///
+    /// # Account references
+    ///   0. [WRITE] Delegated stake account
+    ///   1. [] Delinquent vote account for the delegated stake account
+    ///   2. [] Reference vote account that has voted in each of the recent epochs
+    DeactivateDelinquent,
 }
 
 #[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
@@ -465,6 +511,83 @@ pub fn deactivate_stake(stake_pubkey: &Pubkey, authorized_pubkey: &Pubkey) -> In
     Instruction::new_with_bincode(id(), &StakeInstruction::Deactivate, account_metas)
 }
 
+pub fn redelegate(
+    stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    vote_pubkey: &Pubkey,
+    uninitialized_stake_pubkey: &Pubkey,
+) -> Vec<Instruction> {
+    vec![
+        system_instruction::allocate(
+            uninitialized_stake_pubkey,
+            std::mem::size_of::<StakeState>() as u64,
+        ),
+        system_instruction::assign(uninitialized_stake_pubkey, &id()),
+        _redelegate(
+            stake_pubkey,
+            authorized_pubkey,
+            vote_pubkey,
+            uninitialized_stake_pubkey,
+        ),
+    ]
+}
+
+pub fn redelegate_with_seed(
+    stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    vote_pubkey: &Pubkey,
+    uninitialized_stake_pubkey: &Pubkey, // derived using create_with_seed()
+    base: &Pubkey,                       // base
+    seed: &str,                          // seed
+) -> Vec<Instruction> {
+    vec![
+        system_instruction::allocate_with_seed(
+            uninitialized_stake_pubkey,
+            base,
+            seed,
+            std::mem::size_of::<StakeState>() as u64,
+            &id(),
+        ),
+        _redelegate(
+            stake_pubkey,
+            authorized_pubkey,
+            vote_pubkey,
+            uninitialized_stake_pubkey,
+        ),
+    ]
+}
+
+fn _redelegate(
+    stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    vote_pubkey: &Pubkey,
+    uninitialized_stake_pubkey: &Pubkey,
+) -> Instruction {
+    let account_metas = vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new(*uninitialized_stake_pubkey, false),
+        AccountMeta::new_readonly(*vote_pubkey, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::stake_history::id(), false),
+        AccountMeta::new_readonly(crate::config::id(), false),
+        AccountMeta::new_readonly(*authorized_pubkey, true),
+    ];
+    Instruction::new_with_bincode(id(), &StakeInstruction::Redelegate, account_metas)
+}
+
+pub fn deactivate_delinquent_stake(
+    stake_pubkey: &Pubkey,
+    delinquent_vote_pubkey: &Pubkey,
+    reference_vote_pubkey: &Pubkey,
+) -> Instruction {
+    let account_metas = vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new_readonly(*delinquent_vote_pubkey, false),
+        AccountMeta::new_readonly(*reference_vote_pubkey, false),
+    ];
+    Instruction::new_with_bincode(id(), &StakeInstruction::DeactivateDelinquent, account_metas)
+}
+
 pub fn set_lockup(
     stake_pubkey: &Pubkey,
     lockup: &LockupArgs,
@@ -624,6 +747,34 @@ pub fn process_instruction(
             };
             me.set_lockup(&lockup, &signers, clock.as_ref())
         }
+        StakeInstruction::Redelegate => {
+            if !invoke_context.is_feature_active(&feature_set::sophon::stake_redelegate::id()) {
+                return Err(InstructionError::InvalidInstructionData);
+            }
+            let uninitialized_stake = &next_keyed_account(keyed_accounts)?;
+            let vote = next_keyed_account(keyed_accounts)?;
+
+            me.redelegate(
+                uninitialized_stake,
+                vote,
+                &from_keyed_account::<Clock>(next_keyed_account(keyed_accounts)?)?,
+                &from_keyed_account::<StakeHistory>(next_keyed_account(keyed_accounts)?)?,
+                &config::from_keyed_account(next_keyed_account(keyed_accounts)?)?,
+                &signers,
+            )
+        }
+        StakeInstruction::DeactivateDelinquent => {
+            if !invoke_context
+                .is_feature_active(&feature_set::sophon::stake_deactivate_delinquent::id())
+            {
+                return Err(InstructionError::InvalidInstructionData);
+            }
+            let delinquent_vote = next_keyed_account(keyed_accounts)?;
+            let reference_vote = next_keyed_account(keyed_accounts)?;
+            let clock = get_sysvar::<Clock>(invoke_context, &sysvar::clock::id())?;
+
+            me.deactivate_delinquent(delinquent_vote, reference_vote, clock.epoch)
+        }
     }
 }
 
@@ -816,6 +967,55 @@ mod tests {
             )),
             Err(InstructionError::InvalidAccountData),
         );
+        assert_eq!(
+            process_instruction(
+                &redelegate(
+                    &Pubkey::default(),
+                    &Pubkey::default(),
+                    &invalid_vote_state_pubkey(),
+                    &invalid_stake_state_pubkey(),
+                )[2]
+            ),
+            Err(InstructionError::InvalidAccountData),
+        );
+        assert_eq!(
+            process_instruction(&deactivate_delinquent_stake(
+                &Pubkey::default(),
+                &invalid_vote_state_pubkey(),
+                &invalid_vote_state_pubkey(),
+            )),
+            Err(InstructionError::InvalidAccountData),
+        );
+    }
+
+    #[test]
+    fn test_stake_process_instruction_feature_gated() {
+        let stake_account = create_default_stake_account();
+        let keyed_accounts = [KeyedAccount::new(&Pubkey::default(), false, &stake_account)];
+
+        let mut invoke_context = MockInvokeContext::default();
+        invoke_context.disable_feature(&feature_set::sophon::stake_redelegate::id());
+        assert_eq!(
+            super::process_instruction(
+                &Pubkey::default(),
+                &keyed_accounts,
+                &serialize(&StakeInstruction::Redelegate).unwrap(),
+                &mut invoke_context,
+            ),
+            Err(InstructionError::InvalidInstructionData),
+        );
+
+        let mut invoke_context = MockInvokeContext::default();
+        invoke_context.disable_feature(&feature_set::sophon::stake_deactivate_delinquent::id());
+        assert_eq!(
+            super::process_instruction(
+                &Pubkey::default(),
+                &keyed_accounts,
+                &serialize(&StakeInstruction::DeactivateDelinquent).unwrap(),
+                &mut invoke_context,
+            ),
+            Err(InstructionError::InvalidInstructionData),
+        );
     }
 
     #[test]
@@ -35,6 +35,10 @@ pub const MIN_STAKERS_TO_BE_MAJORITY: u64 = 1_000_000 * solana_sdk::native_token
 /// Number of stakers with lamports more than 1M, to start filtering = 19
 pub const NUM_MAJOR_STAKERS_FOR_FILTERING: usize = 19;
 
+/// Number of epochs a vote account must go without voting before stake delegated to it
+/// can be deactivated by anyone with `DeactivateDelinquent`
+pub const MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION: usize = 5;
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, AbiExample)]
 #[allow(clippy::large_enum_variant)]
 pub enum StakeState {
@@ -921,6 +925,21 @@ pub trait StakeAccount {
         signers: &HashSet<Pubkey>,
         can_merge_expired_lockups: bool,
     ) -> Result<(), InstructionError>;
+    fn redelegate(
+        &self,
+        uninitialized_stake: &KeyedAccount,
+        vote_account: &KeyedAccount,
+        clock: &Clock,
+        stake_history: &StakeHistory,
+        config: &Config,
+        signers: &HashSet<Pubkey>,
+    ) -> Result<(), InstructionError>;
+    fn deactivate_delinquent(
+        &self,
+        delinquent_vote_account: &KeyedAccount,
+        reference_vote_account: &KeyedAccount,
+        current_epoch: Epoch,
+    ) -> Result<(), InstructionError>;
     fn withdraw(
         &self,
         lamports: u64,
@@ -1284,6 +1303,122 @@ impl<'a> StakeAccount for KeyedAccount<'a> {
         Ok(())
     }
 
+    fn redelegate(
+        &self,
+        uninitialized_stake: &KeyedAccount,
+        vote_account: &KeyedAccount,
+        clock: &Clock,
+        stake_history: &StakeHistory,
+        config: &Config,
+        signers: &HashSet<Pubkey>,
+    ) -> Result<(), InstructionError> {
+        if vote_account.owner()? != solana_vote_program::id() {
+            return Err(InstructionError::IncorrectProgramId);
+        }
+        if uninitialized_stake.owner()? != id() {
+            return Err(InstructionError::IncorrectProgramId);
+        }
+        if uninitialized_stake.data_len()? != std::mem::size_of::<StakeState>() {
+            return Err(InstructionError::InvalidAccountData);
+        }
+        if uninitialized_stake.unsigned_key() == self.unsigned_key() {
+            return Err(InstructionError::InvalidArgument);
+        }
+        if !matches!(uninitialized_stake.state()?, StakeState::Uninitialized) {
+            return Err(InstructionError::InvalidAccountData);
+        }
+
+        let (meta, mut stake) = match self.state()? {
+            StakeState::Stake(meta, stake) => (meta, stake),
+            _ => return Err(InstructionError::InvalidAccountData),
+        };
+        meta.authorized.check(signers, StakeAuthorize::Staker)?;
+
+        let (effective_stake, activating_stake, deactivating_stake) = stake
+            .delegation
+            .stake_activating_and_deactivating(clock.epoch, Some(stake_history), true);
+        if effective_stake == 0 || activating_stake != 0 || deactivating_stake != 0 {
+            return Err(StakeError::RedelegateTransientOrInactiveStake.into());
+        }
+        // Redelegating to the same vote account would only churn the cluster-wide
+        // warmup/cooldown allowance
+        if stake.delegation.voter_pubkey == *vote_account.unsigned_key() {
+            return Err(StakeError::RedelegateToSameVoteAccount.into());
+        }
+
+        let redelegated_rent_exempt_reserve = calculate_split_rent_exempt_reserve(
+            meta.rent_exempt_reserve,
+            self.data_len()? as u64,
+            uninitialized_stake.data_len()? as u64,
+        );
+        // the moved lamports must cover whatever part of the destination's
+        // rent_exempt_reserve is not already prefunded
+        let rent_shortfall =
+            redelegated_rent_exempt_reserve.saturating_sub(uninitialized_stake.lamports()?);
+        if effective_stake <= rent_shortfall {
+            return Err(InstructionError::InsufficientFunds);
+        }
+
+        stake.deactivate(clock.epoch)?;
+        let mut redelegated_meta = meta;
+        redelegated_meta.rent_exempt_reserve = redelegated_rent_exempt_reserve;
+        let redelegated_stake = Stake::new(
+            effective_stake - rent_shortfall,
+            vote_account.unsigned_key(),
+            &State::<VoteStateVersions>::state(vote_account)?.convert_to_current(),
+            clock.epoch,
+            config,
+        );
+
+        self.set_state(&StakeState::Stake(meta, stake))?;
+        uninitialized_stake.set_state(&StakeState::Stake(redelegated_meta, redelegated_stake))?;
+
+        self.try_account_ref_mut()?.lamports -= effective_stake;
+        uninitialized_stake.try_account_ref_mut()?.lamports += effective_stake;
+        Ok(())
+    }
+
+    fn deactivate_delinquent(
+        &self,
+        delinquent_vote_account: &KeyedAccount,
+        reference_vote_account: &KeyedAccount,
+        current_epoch: Epoch,
+    ) -> Result<(), InstructionError> {
+        if delinquent_vote_account.owner()? != solana_vote_program::id()
+            || reference_vote_account.owner()? != solana_vote_program::id()
+        {
+            return Err(InstructionError::IncorrectProgramId);
+        }
+
+        let delinquent_vote_state =
+            State::<VoteStateVersions>::state(delinquent_vote_account)?.convert_to_current();
+        let reference_vote_state =
+            State::<VoteStateVersions>::state(reference_vote_account)?.convert_to_current();
+
+        if !acceptable_reference_epoch_credits(reference_vote_state.epoch_credits(), current_epoch)
+        {
+            return Err(StakeError::InsufficientReferenceVotes.into());
+        }
+
+        if let StakeState::Stake(meta, mut stake) = self.state()? {
+            if stake.delegation.voter_pubkey != *delinquent_vote_account.unsigned_key() {
+                return Err(StakeError::VoteAddressMismatch.into());
+            }
+
+            if eligible_for_deactivate_delinquent(
+                delinquent_vote_state.epoch_credits(),
+                current_epoch,
+            ) {
+                stake.deactivate(current_epoch)?;
+                self.set_state(&StakeState::Stake(meta, stake))
+            } else {
+                Err(StakeError::MinimumDelinquentEpochsForDeactivationNotMet.into())
+            }
+        } else {
+            Err(InstructionError::InvalidAccountData)
+        }
+    }
+
     fn withdraw(
         &self,
         lamports: u64,
@@ -1642,6 +1777,44 @@ fn calculate_split_rent_exempt_reserve(
     lamports_per_byte_year * (split_data_len + ACCOUNT_STORAGE_OVERHEAD)
 }
 
+/// Ensure the reference vote account has voted in each of the last
+/// `MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION` epochs, up to and including the current one
+pub fn acceptable_reference_epoch_credits(
+    epoch_credits: &[(Epoch, u64, u64)],
+    current_epoch: Epoch,
+) -> bool {
+    if let Some(epoch_index) = epoch_credits
+        .len()
+        .checked_sub(MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION)
+    {
+        let mut epoch = current_epoch;
+        for (vote_epoch, ..) in epoch_credits[epoch_index..].iter().rev() {
+            if *vote_epoch != epoch {
+                return false;
+            }
+            epoch = epoch.saturating_sub(1);
+        }
+        true
+    } else {
+        false
+    }
+}
+
+/// Check if the vote account has never voted, or last voted at least
+/// `MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION` epochs ago
+pub fn eligible_for_deactivate_delinquent(
+    epoch_credits: &[(Epoch, u64, u64)],
+    current_epoch: Epoch,
+) -> bool {
+    match epoch_credits.last() {
+        None => true,
+        Some((epoch, ..)) => current_epoch
+            .checked_sub(MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION as Epoch)
+            .map(|minimum_epoch| *epoch <= minimum_epoch)
+            .unwrap_or(false),
+    }
+}
+
 pub type RewriteStakeStatus = (&'static str, (u64, u64), (u64, u64));
 
 pub fn rewrite_stakes(
@@ -6397,6 +6570,351 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_redelegate() {
+        let rent = Rent::default();
+        let rent_exempt_reserve = rent.minimum_balance(std::mem::size_of::<StakeState>());
+        let staked_lamports = 1_000_000;
+        let stake_pubkey = Pubkey::new_unique();
+        let meta = Meta {
+            rent_exempt_reserve,
+            ..Meta::auto(&stake_pubkey)
+        };
+        let stake_account = AccountSharedData::new_ref_data_with_space(
+            rent_exempt_reserve + staked_lamports,
+            &StakeState::Initialized(meta),
+            std::mem::size_of::<StakeState>(),
+            &id(),
+        )
+        .expect("stake_account");
+        let stake_keyed_account = KeyedAccount::new(&stake_pubkey, true, &stake_account);
+
+        let vote_pubkey = Pubkey::new_unique();
+        let vote_account = RefCell::new(vote_state::create_account(
+            &vote_pubkey,
+            &Pubkey::new_unique(),
+            0,
+            100,
+        ));
+        let vote_keyed_account = KeyedAccount::new(&vote_pubkey, false, &vote_account);
+        let new_vote_pubkey = Pubkey::new_unique();
+        let new_vote_account = RefCell::new(vote_state::create_account(
+            &new_vote_pubkey,
+            &Pubkey::new_unique(),
+            0,
+            100,
+        ));
+        let new_vote_keyed_account = KeyedAccount::new(&new_vote_pubkey, false, &new_vote_account);
+
+        let redelegated_pubkey = Pubkey::new_unique();
+        let redelegated_account = AccountSharedData::new_ref_data_with_space(
+            0,
+            &StakeState::Uninitialized,
+            std::mem::size_of::<StakeState>(),
+            &id(),
+        )
+        .expect("redelegated_account");
+        let redelegated_keyed_account =
+            KeyedAccount::new(&redelegated_pubkey, false, &redelegated_account);
+
+        let signers = HashSet::from_iter(vec![stake_pubkey]);
+        let config = Config::default();
+        let stake_history = StakeHistory::default();
+        let mut clock = Clock::default();
+
+        // only delegated stake can be redelegated
+        assert_eq!(
+            stake_keyed_account.redelegate(
+                &redelegated_keyed_account,
+                &new_vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &signers,
+            ),
+            Err(InstructionError::InvalidAccountData)
+        );
+
+        stake_keyed_account
+            .delegate(
+                &vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &signers,
+                true,
+            )
+            .unwrap();
+
+        // activating stake can't be redelegated
+        assert_eq!(
+            stake_keyed_account.redelegate(
+                &redelegated_keyed_account,
+                &new_vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &signers,
+            ),
+            Err(StakeError::RedelegateTransientOrInactiveStake.into())
+        );
+
+        clock.epoch += 1;
+
+        // redelegating to the current vote account is refused
+        assert_eq!(
+            stake_keyed_account.redelegate(
+                &redelegated_keyed_account,
+                &vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &signers,
+            ),
+            Err(StakeError::RedelegateToSameVoteAccount.into())
+        );
+
+        // the stake authority must sign
+        assert_eq!(
+            stake_keyed_account.redelegate(
+                &redelegated_keyed_account,
+                &new_vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &HashSet::default(),
+            ),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+
+        // can't redelegate into itself
+        assert_eq!(
+            stake_keyed_account.redelegate(
+                &stake_keyed_account,
+                &new_vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &signers,
+            ),
+            Err(InstructionError::InvalidArgument)
+        );
+
+        assert_eq!(
+            stake_keyed_account.redelegate(
+                &redelegated_keyed_account,
+                &new_vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &signers,
+            ),
+            Ok(())
+        );
+
+        // source keeps its rent exempt reserve and starts cooling down
+        assert_eq!(stake_keyed_account.lamports().unwrap(), rent_exempt_reserve);
+        let stake = StakeState::stake_from(&stake_account.borrow()).unwrap();
+        assert_eq!(stake.delegation.voter_pubkey, vote_pubkey);
+        assert_eq!(stake.delegation.deactivation_epoch, clock.epoch);
+
+        // destination starts warming up towards the new vote account in the same epoch,
+        // funding its own rent exempt reserve out of the moved stake
+        assert_eq!(
+            redelegated_keyed_account.lamports().unwrap(),
+            staked_lamports
+        );
+        let redelegated_meta = StakeState::meta_from(&redelegated_account.borrow()).unwrap();
+        assert_eq!(redelegated_meta.authorized, meta.authorized);
+        assert_eq!(redelegated_meta.rent_exempt_reserve, rent_exempt_reserve);
+        let redelegated_stake = StakeState::stake_from(&redelegated_account.borrow()).unwrap();
+        assert_eq!(redelegated_stake.delegation.voter_pubkey, new_vote_pubkey);
+        assert_eq!(
+            redelegated_stake.delegation.stake,
+            staked_lamports - rent_exempt_reserve
+        );
+        assert_eq!(redelegated_stake.delegation.activation_epoch, clock.epoch);
+        assert_eq!(
+            redelegated_stake.delegation.deactivation_epoch,
+            std::u64::MAX
+        );
+
+        // destination is no longer uninitialized
+        assert_eq!(
+            stake_keyed_account.redelegate(
+                &redelegated_keyed_account,
+                &new_vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &signers,
+            ),
+            Err(InstructionError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_acceptable_reference_epoch_credits() {
+        let epoch_credits = [];
+        assert!(!acceptable_reference_epoch_credits(&epoch_credits, 0));
+
+        let epoch_credits = [(0, 42, 42), (1, 42, 42), (2, 42, 42), (3, 42, 42)];
+        assert!(!acceptable_reference_epoch_credits(&epoch_credits, 3));
+
+        let epoch_credits = [
+            (0, 42, 42),
+            (1, 42, 42),
+            (2, 42, 42),
+            (3, 42, 42),
+            (4, 42, 42),
+        ];
+        assert!(acceptable_reference_epoch_credits(&epoch_credits, 4));
+        assert!(!acceptable_reference_epoch_credits(&epoch_credits, 5));
+
+        let epoch_credits = [
+            (1, 42, 42),
+            (2, 42, 42),
+            (4, 42, 42),
+            (5, 42, 42),
+            (6, 42, 42),
+            (7, 42, 42),
+        ];
+        assert!(!acceptable_reference_epoch_credits(&epoch_credits, 7));
+    }
+
+    #[test]
+    fn test_eligible_for_deactivate_delinquent() {
+        assert!(eligible_for_deactivate_delinquent(&[], 42));
+
+        let epoch_credits = [(0, 42, 42)];
+        assert!(!eligible_for_deactivate_delinquent(&epoch_credits, 0));
+        assert!(!eligible_for_deactivate_delinquent(
+            &epoch_credits,
+            MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION as Epoch - 1
+        ));
+        assert!(eligible_for_deactivate_delinquent(
+            &epoch_credits,
+            MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION as Epoch
+        ));
+    }
+
+    #[test]
+    fn test_deactivate_delinquent() {
+        fn create_vote_account(voted_epochs: &[Epoch]) -> RefCell<AccountSharedData> {
+            let mut vote_state = VoteState::default();
+            for epoch in voted_epochs {
+                vote_state.increment_credits(*epoch);
+            }
+            let vote_account = RefCell::new(vote_state::create_account(
+                &Pubkey::new_unique(),
+                &Pubkey::new_unique(),
+                0,
+                100,
+            ));
+            KeyedAccount::new(&Pubkey::new_unique(), false, &vote_account)
+                .set_state(&VoteStateVersions::new_current(vote_state))
+                .unwrap();
+            vote_account
+        }
+
+        let current_epoch = 20;
+        let reference_pubkey = Pubkey::new_unique();
+        let reference_account = create_vote_account(&[16, 17, 18, 19, 20]);
+        let reference_keyed_account =
+            KeyedAccount::new(&reference_pubkey, false, &reference_account);
+        let idle_reference_account = create_vote_account(&[16, 17, 19, 20]);
+        let idle_reference_keyed_account =
+            KeyedAccount::new(&reference_pubkey, false, &idle_reference_account);
+
+        let vote_pubkey = Pubkey::new_unique();
+        let delinquent_account = create_vote_account(&[10, 15]);
+        let delinquent_keyed_account = KeyedAccount::new(&vote_pubkey, false, &delinquent_account);
+        let recent_account = create_vote_account(&[10, 16]);
+        let recent_keyed_account = KeyedAccount::new(&vote_pubkey, false, &recent_account);
+
+        let stake_pubkey = Pubkey::new_unique();
+        let stake_account = AccountSharedData::new_ref_data_with_space(
+            42,
+            &StakeState::Initialized(Meta::auto(&stake_pubkey)),
+            std::mem::size_of::<StakeState>(),
+            &id(),
+        )
+        .expect("stake_account");
+        let stake_keyed_account = KeyedAccount::new(&stake_pubkey, false, &stake_account);
+
+        // only delegated stake can be deactivated
+        assert_eq!(
+            stake_keyed_account.deactivate_delinquent(
+                &delinquent_keyed_account,
+                &reference_keyed_account,
+                current_epoch
+            ),
+            Err(InstructionError::InvalidAccountData)
+        );
+
+        stake_keyed_account
+            .delegate(
+                &delinquent_keyed_account,
+                &Clock::default(),
+                &StakeHistory::default(),
+                &Config::default(),
+                &vec![stake_pubkey].into_iter().collect(),
+                true,
+            )
+            .unwrap();
+
+        // the reference vote account must have voted in each recent epoch
+        assert_eq!(
+            stake_keyed_account.deactivate_delinquent(
+                &delinquent_keyed_account,
+                &idle_reference_keyed_account,
+                current_epoch
+            ),
+            Err(StakeError::InsufficientReferenceVotes.into())
+        );
+
+        // the stake must be delegated to the delinquent vote account
+        assert_eq!(
+            stake_keyed_account.deactivate_delinquent(
+                &reference_keyed_account,
+                &reference_keyed_account,
+                current_epoch
+            ),
+            Err(StakeError::VoteAddressMismatch.into())
+        );
+
+        // the delegated vote account voted too recently
+        assert_eq!(
+            stake_keyed_account.deactivate_delinquent(
+                &recent_keyed_account,
+                &reference_keyed_account,
+                current_epoch
+            ),
+            Err(StakeError::MinimumDelinquentEpochsForDeactivationNotMet.into())
+        );
+
+        // no signature is required
+        assert_eq!(
+            stake_keyed_account.deactivate_delinquent(
+                &delinquent_keyed_account,
+                &reference_keyed_account,
+                current_epoch
+            ),
+            Ok(())
+        );
+        let stake = StakeState::stake_from(&stake_account.borrow()).unwrap();
+        assert_eq!(stake.delegation.deactivation_epoch, current_epoch);
+
+        assert_eq!(
+            stake_keyed_account.deactivate_delinquent(
+                &delinquent_keyed_account,
+                &reference_keyed_account,
+                current_epoch
+            ),
+            Err(StakeError::AlreadyDeactivated.into())
+        );
+    }
+
     #[test]
     fn test_meta_rewrite_rent_exempt_reserve() {
         let right_data_len = std::mem::size_of::<StakeState>() as u64;
@@ -15,18 +15,24 @@ use dashmap::{
 };
 use log::*;
 use rand::{thread_rng, Rng};
+use solana_address_lookup_table_program::state::{AddressLookupError, AddressLookupTable};
 use solana_sdk::{
     account::{Account, AccountSharedData},
     account_utils::StateMut,
     bpf_loader_upgradeable::{self, UpgradeableLoaderState},
     clock::{Slot, INITIAL_RENT_EPOCH},
+    compute_budget::ComputeBudgetLimits,
     feature_set::{self, FeatureSet},
     fee_calculator::{FeeCalculator, FeeConfig},
     genesis_config::ClusterType,
     hash::Hash,
-    message::Message,
+    message::{
+        v0::{LoadedAddresses, MessageAddressTableLookup},
+        Message,
+    },
     native_loader, nonce,
     pubkey::Pubkey,
+    slot_hashes::SlotHashes,
     transaction::Result,
     transaction::{Transaction, TransactionError},
 };
@@ -410,7 +416,11 @@ impl Accounts {
         let fee_config = FeeConfig {
             secp256k1_program_enabled: feature_set
                 .is_active(&feature_set::secp256k1_program_enabled::id()),
+            ed25519_program_enabled: feature_set
+                .is_active(&feature_set::sophon::ed25519_program_enabled::id()),
         };
+        let compute_budget_program_enabled =
+            feature_set.is_active(&feature_set::sophon::compute_budget_program_enabled::id());
         txs.zip(lock_results)
             .map(|etx| match etx {
                 (tx, (Ok(()), nonce_rollback)) => {
@@ -427,6 +437,14 @@ impl Accounts {
                     } else {
                         return (Err(TransactionError::BlockhashNotFound), None);
                     };
+                    let fee = if compute_budget_program_enabled {
+                        match ComputeBudgetLimits::process_message(tx.message()) {
+                            Ok(limits) => fee.saturating_add(limits.prioritization_fee()),
+                            Err(e) => return (Err(e), None),
+                        }
+                    } else {
+                        fee
+                    };
 
                     let loaded_transaction = match self.load_transaction(
                         ancestors,
@@ -476,6 +494,38 @@ impl Accounts {
         }
     }
 
+    /// Load addresses referenced by a v0 message from an address lookup table account
+    pub fn load_lookup_table_addresses(
+        &self,
+        ancestors: &Ancestors,
+        address_table_lookup: &MessageAddressTableLookup,
+        current_slot: Slot,
+        slot_hashes: &SlotHashes,
+    ) -> std::result::Result<LoadedAddresses, AddressLookupError> {
+        let (table_account, _) = self
+            .load_slow(ancestors, &address_table_lookup.account_key)
+            .ok_or(AddressLookupError::LookupTableAccountNotFound)?;
+
+        if table_account.owner != solana_address_lookup_table_program::id() {
+            return Err(AddressLookupError::InvalidAccountOwner);
+        }
+
+        let lookup_table = AddressLookupTable::deserialize(&table_account.data)
+            .map_err(|_| AddressLookupError::InvalidAccountData)?;
+        Ok(LoadedAddresses {
+            writable: lookup_table.lookup(
+                current_slot,
+                &address_table_lookup.writable_indexes,
+                slot_hashes,
+            )?,
+            readonly: lookup_table.lookup(
+                current_slot,
+                &address_table_lookup.readonly_indexes,
+                slot_hashes,
+            )?,
+        })
+    }
+
     /// scans underlying accounts_db for this delta (slot) with a map function
     ///   from LoadedAccount to B
     /// returns only the latest/current version of B for this slot
@@ -1655,6 +1705,80 @@ mod tests {
         assert_eq!(loaded, vec![]);
     }
 
+    #[test]
+    fn test_load_lookup_table_addresses() {
+        use solana_address_lookup_table_program::state::{
+            lookup_table_space, LookupTableMeta, LOOKUP_TABLE_META_SIZE,
+        };
+
+        let accounts = Accounts::new_with_config(
+            Vec::new(),
+            &ClusterType::Development,
+            AccountSecondaryIndexes::default(),
+            false,
+        );
+        let ancestors = vec![(0, 0)].into_iter().collect();
+        let slot_hashes = SlotHashes::default();
+
+        let addresses = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let table_key = Pubkey::new_unique();
+        let mut table_account = AccountSharedData::new(
+            1,
+            lookup_table_space(addresses.len()),
+            &solana_address_lookup_table_program::id(),
+        );
+        AddressLookupTable::overwrite_meta_data(
+            &mut table_account.data,
+            LookupTableMeta {
+                num_addresses: addresses.len() as u16,
+                ..LookupTableMeta::new(Pubkey::new_unique())
+            },
+        )
+        .unwrap();
+        for (i, address) in addresses.iter().enumerate() {
+            let offset = LOOKUP_TABLE_META_SIZE + i * 32;
+            table_account.data[offset..offset + 32].copy_from_slice(address.as_ref());
+        }
+        accounts.store_slow_uncached(0, &table_key, &table_account);
+
+        let invalid_table_key = Pubkey::new_unique();
+        let invalid_table_account = AccountSharedData::new(1, 0, &Pubkey::new_unique());
+        accounts.store_slow_uncached(0, &invalid_table_key, &invalid_table_account);
+
+        let lookup = |account_key, writable_indexes, readonly_indexes| {
+            accounts.load_lookup_table_addresses(
+                &ancestors,
+                &MessageAddressTableLookup {
+                    account_key,
+                    writable_indexes,
+                    readonly_indexes,
+                },
+                1,
+                &slot_hashes,
+            )
+        };
+
+        assert_eq!(
+            lookup(table_key, vec![1], vec![0]),
+            Ok(LoadedAddresses {
+                writable: vec![addresses[1]],
+                readonly: vec![addresses[0]],
+            })
+        );
+        assert_eq!(
+            lookup(table_key, vec![2], vec![]),
+            Err(AddressLookupError::InvalidLookupIndex)
+        );
+        assert_eq!(
+            lookup(invalid_table_key, vec![0], vec![]),
+            Err(AddressLookupError::InvalidAccountOwner)
+        );
+        assert_eq!(
+            lookup(Pubkey::new_unique(), vec![0], vec![]),
+            Err(AddressLookupError::LookupTableAccountNotFound)
+        );
+    }
+
     #[test]
     fn test_accounts_account_not_found() {
         let accounts = Accounts::new_with_config(
@@ -5,7 +5,7 @@
 use crate::{
     bank::{Bank, BankSlotDelta, DropCallback},
     bank_forks::{BankForks, SnapshotConfig},
-    snapshot_package::AccountsPackageSender,
+    snapshot_package::{AccountsPackageSender, SnapshotType},
     snapshot_utils,
 };
 use crossbeam_channel::{Receiver, SendError, Sender};
@@ -99,6 +99,15 @@ impl SnapshotRequestHandler {
                     status_cache_slot_deltas,
                 } = snapshot_request;
 
+                let snapshot_type = self.snapshot_type(&snapshot_root_bank);
+                if snapshot_type == SnapshotType::IncrementalBase {
+                    snapshot_root_bank
+                        .rc
+                        .accounts
+                        .accounts_db
+                        .set_last_full_snapshot_slot(snapshot_root_bank.slot());
+                }
+
                 let previous_hash = if test_hash_calculation {
                     // We have to use the index version here.
                     // We cannot calculate the non-index way because cache has not been flushed and stores don't match reality.
@@ -174,6 +183,7 @@ impl SnapshotRequestHandler {
                     self.snapshot_config.snapshot_version,
                     &self.snapshot_config.archive_format,
                     hash_for_testing,
+                    snapshot_type,
                 );
                 if r.is_err() {
                     warn!(
@@ -209,6 +219,38 @@ impl SnapshotRequestHandler {
                 snapshot_root_bank.block_height()
             })
     }
+
+    fn snapshot_type(&self, snapshot_root_bank: &Bank) -> SnapshotType {
+        let block_height = snapshot_root_bank.block_height();
+        let incremental_snapshot_interval_slots =
+            self.snapshot_config.incremental_snapshot_interval_slots;
+        let incremental_snapshots_enabled =
+            !matches!(incremental_snapshot_interval_slots, 0 | std::u64::MAX);
+
+        if block_height % self.snapshot_config.snapshot_interval_slots == 0 {
+            if incremental_snapshots_enabled {
+                SnapshotType::IncrementalBase
+            } else {
+                SnapshotType::Full
+            }
+        } else {
+            let last_full_snapshot_slot = snapshot_root_bank
+                .rc
+                .accounts
+                .accounts_db
+                .last_full_snapshot_slot();
+            match last_full_snapshot_slot {
+                Some(base_slot)
+                    if incremental_snapshots_enabled
+                        && block_height % incremental_snapshot_interval_slots == 0 =>
+                {
+                    SnapshotType::Incremental(base_slot)
+                }
+                // Package is only used for accounts hash verification
+                _ => SnapshotType::Full,
+            }
+        }
+    }
 }
 
 #[derive(Default)]
@@ -25,6 +25,7 @@ use crate::{
         AccountSecondaryIndexes, AccountsIndex, AccountsIndexRootsStats, Ancestors, IndexKey,
         IsCached, SlotList, SlotSlice, ZeroLamport,
     },
+    accounts_update_notifier_interface::AccountsUpdateNotifier,
     append_vec::{AppendVec, StoredAccountMeta, StoredMeta},
     contains::Contains,
 };
@@ -749,6 +750,14 @@ pub struct AccountsDb {
     /// to drive clean_accounts
     /// Generated by get_accounts_delta_hash
     uncleaned_pubkeys: DashMap<Slot, Vec<Pubkey>>,
+
+    /// Slot of the last full snapshot, which incremental snapshots are built on top of.
+    /// Zero-lamport accounts updated after this slot should survive clean,
+    /// otherwise incremental snapshot will miss removal of accounts from the full snapshot.
+    last_full_snapshot_slot: RwLock<Option<Slot>>,
+
+    /// Receives every account update stored to a non-frozen slot (e.g. by validator plugins)
+    accounts_update_notifier: RwLock<Option<AccountsUpdateNotifier>>,
 }
 
 #[derive(Debug, Default)]
@@ -1071,6 +1080,8 @@ impl Default for AccountsDb {
             accounts_cache: AccountsCache::default(),
             recycle_stores: RwLock::new(RecycleStores::default()),
             uncleaned_pubkeys: DashMap::new(),
+            last_full_snapshot_slot: RwLock::new(None),
+            accounts_update_notifier: RwLock::new(None),
             next_id: AtomicUsize::new(0),
             shrink_candidate_slots_v1: Mutex::new(Vec::new()),
             shrink_candidate_slots: Mutex::new(HashMap::new()),
@@ -1327,6 +1338,79 @@ impl AccountsDb {
         reclaims
     }
 
+    pub fn last_full_snapshot_slot(&self) -> Option<Slot> {
+        *self.last_full_snapshot_slot.read().unwrap()
+    }
+
+    pub fn set_last_full_snapshot_slot(&self, slot: Slot) {
+        *self.last_full_snapshot_slot.write().unwrap() = Some(slot);
+    }
+
+    pub fn set_accounts_update_notifier(&self, accounts_update_notifier: AccountsUpdateNotifier) {
+        *self.accounts_update_notifier.write().unwrap() = Some(accounts_update_notifier);
+    }
+
+    fn notify_accounts_update(
+        &self,
+        slot: Slot,
+        accounts: &[(&Pubkey, &AccountSharedData)],
+        first_write_version: u64,
+    ) {
+        if let Some(accounts_update_notifier) = &*self.accounts_update_notifier.read().unwrap() {
+            let notifier = accounts_update_notifier.read().unwrap();
+            for (write_version, (pubkey, account)) in (first_write_version..).zip(accounts) {
+                let meta = StoredMeta {
+                    write_version,
+                    pubkey: **pubkey,
+                    data_len: account.data().len() as u64,
+                };
+                notifier.notify_account_update(slot, &meta, account);
+            }
+        }
+    }
+
+    /// Reports the latest stored version of every account to the notifier, so it starts
+    /// from the state the validator has booted from rather than from the first update.
+    pub fn notify_account_restore_from_snapshot(&self) {
+        if let Some(accounts_update_notifier) = &*self.accounts_update_notifier.read().unwrap() {
+            let notifier = accounts_update_notifier.read().unwrap();
+            let mut slots = self.storage.all_slots();
+            slots.sort_unstable_by(|a, b| b.cmp(a));
+
+            let mut notified = HashSet::new();
+            for slot in slots {
+                let storages = self
+                    .storage
+                    .get_slot_storage_entries(slot)
+                    .unwrap_or_default();
+                let mut accounts: Vec<_> = storages
+                    .iter()
+                    .flat_map(|storage| storage.all_accounts())
+                    .collect();
+                accounts.sort_unstable_by(|a, b| b.meta.write_version.cmp(&a.meta.write_version));
+                for account in accounts {
+                    if notified.insert(account.meta.pubkey) {
+                        notifier.notify_account_restore_from_snapshot(
+                            slot,
+                            account.meta,
+                            &account.clone_account(),
+                        );
+                    }
+                }
+            }
+            notifier.notify_end_of_restore_from_snapshot();
+        }
+    }
+
+    // Zero-lamport accounts stored after the last full snapshot are the only record of
+    // accounts removal for incremental snapshots, so they can't be purged yet.
+    fn is_needed_by_incremental_snapshot(&self, slot: Slot) -> bool {
+        self.last_full_snapshot_slot()
+            .map_or(false, |last_full_snapshot_slot| {
+                slot > last_full_snapshot_slot
+            })
+    }
+
     fn max_clean_root(&self, proposed_clean_root: Option<Slot>) -> Option<Slot> {
         match (
             self.accounts_index.min_ongoing_scan_root(),
@@ -1451,7 +1535,9 @@ impl AccountsDb {
                             {
                                 let slot_list = locked_entry.slot_list();
                                 let (slot, account_info) = &slot_list[index];
-                                if account_info.lamports == 0 {
+                                if account_info.lamports == 0
+                                    && !self.is_needed_by_incremental_snapshot(*slot)
+                                {
                                     purges.insert(
                                         *pubkey,
                                         self.accounts_index
@@ -4288,15 +4374,25 @@ impl AccountsDb {
         // hold just 1 ref from this slot.
         let reset_accounts = true;
 
+        let first_write_version = self.bulk_assign_write_version(accounts.len());
+        let mut current_version = first_write_version;
+        let write_version_producer: Box<dyn Iterator<Item = u64>> =
+            Box::new(std::iter::from_fn(move || {
+                let ret = current_version;
+                current_version += 1;
+                Some(ret)
+            }));
+
         self.store_accounts_custom(
             slot,
             accounts,
             hashes,
             None::<StorageFinder>,
-            None::<Box<dyn Iterator<Item = u64>>>,
+            Some(write_version_producer),
             is_cached_store,
             reset_accounts,
         );
+        self.notify_accounts_update(slot, accounts, first_write_version);
     }
 
     fn store_accounts_frozen<'a>(
@@ -4999,6 +5095,7 @@ pub mod tests {
         accounts_hash::MERKLE_FANOUT,
         accounts_index::RefCount,
         accounts_index::{tests::*, AccountSecondaryIndexesIncludeExclude},
+        accounts_update_notifier_interface::AccountsUpdateNotifierInterface,
         append_vec::AccountMeta,
         inline_spl_token_v2_0,
     };
@@ -5959,6 +6056,138 @@ pub mod tests {
         assert!(accounts.accounts_index.get(&pubkey, None, None).is_none());
     }
 
+    #[test]
+    fn test_clean_zero_lamport_after_last_full_snapshot() {
+        solana_logger::setup();
+
+        let accounts = AccountsDb::new(Vec::new(), &ClusterType::Development);
+        let pubkey = solana_sdk::pubkey::new_rand();
+        let account = AccountSharedData::new(1, 0, &AccountSharedData::default().owner);
+        let zero_lamport_account =
+            AccountSharedData::new(0, 0, &AccountSharedData::default().owner);
+
+        accounts.store_uncached(0, &[(&pubkey, &account)]);
+        accounts.store_uncached(1, &[(&pubkey, &zero_lamport_account)]);
+        accounts.add_root(0);
+        accounts.add_root(1);
+        accounts.set_last_full_snapshot_slot(0);
+
+        // Zero-lamport account is newer than the full snapshot, and should be kept
+        // for incremental snapshots, while slot 0 is already outdated.
+        accounts.clean_accounts(None);
+        assert_eq!(accounts.alive_account_count_in_slot(0), 0);
+        assert_eq!(accounts.alive_account_count_in_slot(1), 1);
+        assert!(accounts.accounts_index.get(&pubkey, None, None).is_some());
+
+        // Once the next full snapshot includes the removal, account can be purged.
+        accounts.set_last_full_snapshot_slot(1);
+        accounts.clean_accounts(None);
+        assert!(accounts.storage.get_slot_stores(1).is_none());
+        assert!(accounts.accounts_index.get(&pubkey, None, None).is_none());
+    }
+
+    #[derive(Debug, Default)]
+    struct AccountsUpdateCollector {
+        updates: Mutex<Vec<(Slot, StoredMeta, AccountSharedData)>>,
+        restored: Mutex<Vec<(Slot, StoredMeta, AccountSharedData)>>,
+        end_of_restore: AtomicBool,
+    }
+
+    impl AccountsUpdateNotifierInterface for AccountsUpdateCollector {
+        fn notify_account_update(
+            &self,
+            slot: Slot,
+            meta: &StoredMeta,
+            account: &AccountSharedData,
+        ) {
+            self.updates
+                .lock()
+                .unwrap()
+                .push((slot, meta.clone(), account.clone()));
+        }
+
+        fn notify_account_restore_from_snapshot(
+            &self,
+            slot: Slot,
+            meta: &StoredMeta,
+            account: &AccountSharedData,
+        ) {
+            self.restored
+                .lock()
+                .unwrap()
+                .push((slot, meta.clone(), account.clone()));
+        }
+
+        fn notify_end_of_restore_from_snapshot(&self) {
+            self.end_of_restore.store(true, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_notify_accounts_update() {
+        let accounts = AccountsDb::new(Vec::new(), &ClusterType::Development);
+        let pubkey1 = solana_sdk::pubkey::new_rand();
+        let pubkey2 = solana_sdk::pubkey::new_rand();
+        let account1 = AccountSharedData::new(1, 0, &AccountSharedData::default().owner);
+        let account2 = AccountSharedData::new(2, 10, &AccountSharedData::default().owner);
+
+        // updates before notifier is set are not reported
+        accounts.store_uncached(0, &[(&pubkey1, &account1)]);
+
+        let collector = Arc::new(RwLock::new(AccountsUpdateCollector::default()));
+        accounts.set_accounts_update_notifier(collector.clone());
+        accounts.store_uncached(1, &[(&pubkey1, &account1), (&pubkey2, &account2)]);
+        accounts.store_cached(2, &[(&pubkey2, &account1)]);
+
+        // moving accounts between storages isn't an update
+        accounts.add_root(1);
+        accounts.shrink_all_slots();
+
+        let collector = collector.read().unwrap();
+        let updates = collector.updates.lock().unwrap();
+        let summary: Vec<_> = updates
+            .iter()
+            .map(|(slot, meta, account)| (*slot, meta.pubkey, account.lamports))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![(1, pubkey1, 1), (1, pubkey2, 2), (2, pubkey2, 1)]
+        );
+        assert_eq!(updates[1].1.data_len, 10);
+        assert!(updates[0].1.write_version < updates[1].1.write_version);
+        assert!(updates[1].1.write_version < updates[2].1.write_version);
+    }
+
+    #[test]
+    fn test_notify_account_restore_from_snapshot() {
+        let accounts = AccountsDb::new(Vec::new(), &ClusterType::Development);
+        let pubkey1 = solana_sdk::pubkey::new_rand();
+        let pubkey2 = solana_sdk::pubkey::new_rand();
+        let account1 = AccountSharedData::new(1, 0, &AccountSharedData::default().owner);
+        let account2 = AccountSharedData::new(2, 0, &AccountSharedData::default().owner);
+        accounts.store_uncached(0, &[(&pubkey1, &account1), (&pubkey2, &account1)]);
+        accounts.store_uncached(1, &[(&pubkey2, &account2)]);
+
+        let collector = Arc::new(RwLock::new(AccountsUpdateCollector::default()));
+        accounts.set_accounts_update_notifier(collector.clone());
+        accounts.notify_account_restore_from_snapshot();
+
+        let collector = collector.read().unwrap();
+        assert!(collector.updates.lock().unwrap().is_empty());
+        assert!(collector.end_of_restore.load(Ordering::Relaxed));
+        let mut restored: Vec<_> = collector
+            .restored
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(slot, meta, account)| (*slot, meta.pubkey, account.lamports))
+            .collect();
+        restored.sort_unstable();
+        let mut expected = vec![(0, pubkey1, 1), (1, pubkey2, 2)];
+        expected.sort_unstable();
+        assert_eq!(restored, expected);
+    }
+
     #[test]
     fn test_clean_old_with_normal_account() {
         solana_logger::setup();
@@ -3,7 +3,7 @@ use crate::{
     inline_spl_token_v2_0::{self, SPL_TOKEN_ACCOUNT_MINT_OFFSET, SPL_TOKEN_ACCOUNT_OWNER_OFFSET},
     secondary_index::*,
 };
-use dashmap::DashSet;
+use dashmap::{DashMap, DashSet};
 use ouroboros::self_referencing;
 use solana_measure::measure::Measure;
 use solana_sdk::{
@@ -21,13 +21,14 @@ use std::{
         Bound::{Excluded, Included, Unbounded},
         Range, RangeBounds,
     },
+    str::FromStr,
     sync::{
         atomic::{AtomicU64, Ordering},
         Arc, RwLock, RwLockReadGuard, RwLockWriteGuard,
     },
 };
 
-use sophon_account_program::{VAccountStorage, SophonAccountType};
+use sophon_account_program::{SophonAccountType, VAccountStorage};
 use sophon_relying_party_program::RelyingPartyData;
 
 pub const ITER_BATCH_SIZE: usize = 1000;
@@ -62,7 +63,7 @@ enum ScanTypes<R: RangeBounds<Pubkey>> {
     Indexed(IndexKey),
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum IndexKey {
     ProgramId(Pubkey),
     SplTokenMint(Pubkey),
@@ -71,6 +72,7 @@ pub enum IndexKey {
     SophonAccountOwner(Pubkey),
     SophonAccountOperational(Pubkey),
     SophonRelyingOwner(Pubkey),
+    Custom(CustomAccountIndex, Pubkey),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -82,6 +84,87 @@ pub enum AccountIndex {
     SophonAccountOwner,
     SophonAccountOperational,
     SophonRelyingOwner,
+    Custom(CustomAccountIndex),
+}
+
+/// An operator-defined index over `length` bytes at `offset` in the data
+/// of the accounts owned by `program_id`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CustomAccountIndex {
+    pub program_id: Pubkey,
+    pub offset: usize,
+    pub length: usize,
+}
+
+impl CustomAccountIndex {
+    /// Indexed values are stored as zero-padded secondary index keys,
+    /// so they can't be longer than a pubkey
+    pub const MAX_LENGTH: usize = PUBKEY_BYTES;
+
+    pub fn new(program_id: Pubkey, offset: usize, length: usize) -> Result<Self, String> {
+        if length == 0 || length > Self::MAX_LENGTH {
+            return Err(format!(
+                "index length must be between 1 and {} bytes",
+                Self::MAX_LENGTH
+            ));
+        }
+        offset
+            .checked_add(length)
+            .ok_or_else(|| "index offset is too large".to_string())?;
+        Ok(Self {
+            program_id,
+            offset,
+            length,
+        })
+    }
+
+    /// The secondary index key for `value`, which must be exactly `length` bytes long
+    pub fn index_key(&self, value: &[u8]) -> Option<Pubkey> {
+        if value.len() != self.length {
+            return None;
+        }
+        let mut key = [0u8; PUBKEY_BYTES];
+        key[..self.length].copy_from_slice(value);
+        Some(Pubkey::new_from_array(key))
+    }
+
+    /// The secondary index key of an account owned by `program_id`,
+    /// `None` if its data is too short to be indexed
+    pub fn account_index_key(&self, account_data: &[u8]) -> Option<Pubkey> {
+        self.index_key(account_data.get(self.offset..self.offset + self.length)?)
+    }
+
+    /// Name of the metrics reported for this index
+    pub fn metrics_name(&self) -> String {
+        format!(
+            "custom_account_index_stats-{}-{}-{}",
+            self.program_id, self.offset, self.length
+        )
+    }
+}
+
+impl FromStr for CustomAccountIndex {
+    type Err = String;
+
+    /// Parses `PROGRAM_ID:OFFSET:LENGTH`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.split(':').collect();
+        if parts.len() != 3 {
+            return Err(format!(
+                "invalid custom index {}, expected PROGRAM_ID:OFFSET:LENGTH",
+                s
+            ));
+        }
+        let program_id = Pubkey::from_str(parts[0])
+            .map_err(|err| format!("invalid program id {}: {}", parts[0], err))?;
+        let offset = parts[1]
+            .parse()
+            .map_err(|err| format!("invalid offset {}: {}", parts[1], err))?;
+        let length = parts[2]
+            .parse()
+            .map_err(|err| format!("invalid length {}: {}", parts[2], err))?;
+        Self::new(program_id, offset, length)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -109,6 +192,12 @@ impl AccountSecondaryIndexes {
             None => true, // include all keys
         }
     }
+    pub fn custom_indexes(&self) -> impl Iterator<Item = &CustomAccountIndex> {
+        self.indexes.iter().filter_map(|index| match index {
+            AccountIndex::Custom(custom_index) => Some(custom_index),
+            _ => None,
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -309,6 +398,8 @@ pub struct AccountsIndex<T> {
     sophon_account_owner_index: SecondaryIndex<DashMapSecondaryIndexEntry>,
     sophon_account_operational_index: SecondaryIndex<DashMapSecondaryIndexEntry>,
     sophon_relying_party_owner_index: SecondaryIndex<DashMapSecondaryIndexEntry>,
+    // Operator-defined indices, created on first use
+    custom_indexes: DashMap<CustomAccountIndex, Arc<SecondaryIndex<DashMapSecondaryIndexEntry>>>,
 }
 
 impl<T> Default for AccountsIndex<T> {
@@ -341,6 +432,7 @@ impl<T> Default for AccountsIndex<T> {
             sophon_relying_party_owner_index: SecondaryIndex::<DashMapSecondaryIndexEntry>::new(
                 "sophon_relying_party_owner_index",
             ),
+            custom_indexes: DashMap::default(),
         }
     }
 }
@@ -561,6 +653,16 @@ impl<T: 'static + Clone + IsCached + ZeroLamport> AccountsIndex<T> {
                     &va_owner_key,
                     Some(max_root),
                 ),
+            ScanTypes::Indexed(IndexKey::Custom(custom_index, key)) => {
+                let index = self
+                    .custom_indexes
+                    .get(&custom_index)
+                    .map(|index| index.value().clone());
+                // No account was indexed yet if the index doesn't exist
+                if let Some(index) = index {
+                    self.do_scan_secondary_index(ancestors, func, &index, &key, Some(max_root));
+                }
+            }
         }
 
         {
@@ -1001,6 +1103,17 @@ impl<T: 'static + Clone + IsCached + ZeroLamport> AccountsIndex<T> {
             }
         }
 
+        for custom_index in account_indexes.custom_indexes() {
+            if custom_index.program_id != *account_owner {
+                continue;
+            }
+            if let Some(key) = custom_index.account_index_key(account_data) {
+                if account_indexes.include_key(&key) {
+                    self.custom_index(custom_index).insert(&key, pubkey);
+                }
+            }
+        }
+
         // Sophon relying party data
         if *account_owner == sophon_relying_party_program::id() {
             match RelyingPartyData::try_from(account_data) {
@@ -1113,6 +1226,33 @@ impl<T: 'static + Clone + IsCached + ZeroLamport> AccountsIndex<T> {
         if account_indexes.contains(&AccountIndex::SplTokenMint) {
             self.spl_token_mint_index.remove_by_inner_key(inner_key);
         }
+
+        for custom_index in account_indexes.custom_indexes() {
+            if let Some(index) = self.custom_indexes.get(custom_index) {
+                index.remove_by_inner_key(inner_key);
+            }
+        }
+    }
+
+    fn custom_index(
+        &self,
+        custom_index: &CustomAccountIndex,
+    ) -> Arc<SecondaryIndex<DashMapSecondaryIndexEntry>> {
+        if let Some(index) = self.custom_indexes.get(custom_index) {
+            return index.value().clone();
+        }
+        self.custom_indexes
+            .entry(*custom_index)
+            .or_insert_with(|| {
+                // Custom indexes are configured once at startup, so their metrics names are
+                // leaked to give every index its own series
+                let metrics_name = Box::leak(custom_index.metrics_name().into_boxed_str());
+                Arc::new(SecondaryIndex::<DashMapSecondaryIndexEntry>::new(
+                    metrics_name,
+                ))
+            })
+            .value()
+            .clone()
     }
 
     fn purge_older_root_entries(
@@ -2077,6 +2217,116 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_custom_account_index_from_str() {
+        let program_id = Pubkey::new_unique();
+        assert_eq!(
+            format!("{}:8:32", program_id).parse::<CustomAccountIndex>(),
+            Ok(CustomAccountIndex {
+                program_id,
+                offset: 8,
+                length: 32,
+            })
+        );
+        assert!(format!("{}:8", program_id)
+            .parse::<CustomAccountIndex>()
+            .is_err());
+        assert!(format!("{}:8:0", program_id)
+            .parse::<CustomAccountIndex>()
+            .is_err());
+        assert!(format!("{}:8:33", program_id)
+            .parse::<CustomAccountIndex>()
+            .is_err());
+        assert!("not-a-pubkey:8:32".parse::<CustomAccountIndex>().is_err());
+    }
+
+    #[test]
+    fn test_custom_account_index() {
+        let program_id = Pubkey::new_unique();
+        let custom_index = CustomAccountIndex::new(program_id, 4, 8).unwrap();
+        let mut secondary_indexes = AccountSecondaryIndexes::default();
+        secondary_indexes
+            .indexes
+            .insert(AccountIndex::Custom(custom_index));
+        let index = AccountsIndex::<bool>::default();
+
+        let value = [7u8; 8];
+        let mut account_data = vec![0u8; 16];
+        account_data[4..12].copy_from_slice(&value);
+        let index_key = custom_index.index_key(&value).unwrap();
+        assert_eq!(
+            custom_index.account_index_key(&account_data),
+            Some(index_key)
+        );
+        assert_eq!(custom_index.account_index_key(&account_data[..11]), None);
+
+        let account_key = Pubkey::new_unique();
+        let other_program_account_key = Pubkey::new_unique();
+        let short_account_key = Pubkey::new_unique();
+        let other_program_id = Pubkey::new_unique();
+        for (pubkey, owner, data) in [
+            (&account_key, &program_id, &account_data[..]),
+            (
+                &other_program_account_key,
+                &other_program_id,
+                &account_data[..],
+            ),
+            (&short_account_key, &program_id, &account_data[..8]),
+        ]
+        .iter()
+        {
+            index.upsert(
+                1,
+                pubkey,
+                owner,
+                data,
+                &secondary_indexes,
+                true,
+                &mut vec![],
+            );
+        }
+        index.add_root(1, false);
+
+        let ancestors = vec![(1, 1)].into_iter().collect();
+        let mut found = vec![];
+        index.index_scan_accounts(
+            &ancestors,
+            IndexKey::Custom(custom_index, index_key),
+            |pubkey, _| found.push(*pubkey),
+        );
+        assert_eq!(found, vec![account_key]);
+
+        index.purge_exact(
+            &account_key,
+            &vec![1].into_iter().collect::<HashSet<Slot>>(),
+            &mut vec![],
+        );
+        index.handle_dead_keys(&[&account_key], &secondary_indexes);
+        let custom_secondary_index = index.custom_index(&custom_index);
+        assert!(custom_secondary_index.index.is_empty());
+        assert!(custom_secondary_index.reverse_index.is_empty());
+
+        // Excluded keys are not indexed, like with the built-in secondary indexes
+        secondary_indexes.keys = Some(AccountSecondaryIndexesIncludeExclude {
+            exclude: true,
+            keys: [index_key].iter().cloned().collect(),
+        });
+        index.upsert(
+            2,
+            &account_key,
+            &program_id,
+            &account_data,
+            &secondary_indexes,
+            true,
+            &mut vec![],
+        );
+        assert!(custom_secondary_index.index.is_empty());
+        assert_eq!(
+            custom_index.metrics_name(),
+            format!("custom_account_index_stats-{}-4-8", program_id)
+        );
+    }
+
     #[test]
     fn test_purge_older_root_entries() {
         // No roots, should be no reclaims
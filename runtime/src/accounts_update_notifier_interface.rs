@@ -0,0 +1,23 @@
+use {
+    crate::append_vec::StoredMeta,
+    solana_sdk::{account::AccountSharedData, clock::Slot},
+    std::sync::{Arc, RwLock},
+};
+
+pub trait AccountsUpdateNotifierInterface: std::fmt::Debug {
+    /// Notified when an account is updated at runtime, due to transaction activities
+    fn notify_account_update(&self, slot: Slot, meta: &StoredMeta, account: &AccountSharedData);
+
+    /// Notified when the account is restored from the snapshot at startup
+    fn notify_account_restore_from_snapshot(
+        &self,
+        slot: Slot,
+        meta: &StoredMeta,
+        account: &AccountSharedData,
+    );
+
+    /// Notified once every account restored from the snapshot has been reported
+    fn notify_end_of_restore_from_snapshot(&self);
+}
+
+pub type AccountsUpdateNotifier = Arc<RwLock<dyn AccountsUpdateNotifierInterface + Sync + Send>>;
@@ -9,6 +9,7 @@ use crate::{
     },
     accounts_db::{ErrorCounters, SnapshotStorages},
     accounts_index::{AccountSecondaryIndexes, Ancestors, IndexKey},
+    accounts_update_notifier_interface::AccountsUpdateNotifier,
     blockhash_queue::{BlockHashEvm, BlockhashQueue},
     builtins::{self, ActivationType},
     epoch_stakes::{EpochStakes, NodeVoteAccounts},
@@ -17,6 +18,7 @@ use crate::{
     instruction_recorder::InstructionRecorder,
     log_collector::LogCollector,
     message_processor::{ExecuteDetailsTimings, Executors, MessageProcessor},
+    prioritization_fee::{PrioritizationFee, PrioritizationFeeCache},
     rent_collector::RentCollector,
     stakes::Stakes,
     status_cache::{SlotDelta, StatusCache},
@@ -30,6 +32,7 @@ use evm_state::FromKey;
 use itertools::Itertools;
 use log::*;
 use rayon::ThreadPool;
+use solana_address_lookup_table_program::state::AddressLookupError;
 use solana_measure::measure::Measure;
 use solana_metrics::{datapoint_debug, inc_new_counter_debug, inc_new_counter_info};
 use solana_sdk::{
@@ -42,6 +45,7 @@ use solana_sdk::{
         INITIAL_RENT_EPOCH, MAX_PROCESSING_AGE, MAX_RECENT_BLOCKHASHES,
         MAX_TRANSACTION_FORWARDING_DELAY, SECONDS_PER_DAY,
     },
+    compute_budget::ComputeBudgetLimits,
     epoch_info::EpochInfo,
     epoch_schedule::EpochSchedule,
     feature,
@@ -53,11 +57,16 @@ use solana_sdk::{
     incinerator,
     inflation::Inflation,
     instruction::CompiledInstruction,
-    message::Message,
+    message::{
+        v0::{LoadedAddresses, LoadedMessage, MessageAddressTableLookup},
+        Message, VersionedMessage,
+    },
     native_loader,
     native_token::sol_to_lamports,
     nonce, nonce_account,
-    process_instruction::{BpfComputeBudget, Executor, ProcessInstructionWithContext},
+    process_instruction::{
+        BpfComputeBudget, Executor, ProcessInstructionWithContext, TransactionReturnData,
+    },
     program_utils::limited_deserialize,
     pubkey::Pubkey,
     recent_blockhashes_account, recent_evm_blockhashes_account,
@@ -72,7 +81,7 @@ use solana_sdk::{
     system_transaction,
     sysvar::{self},
     timing::years_as_slots,
-    transaction::{self, Result, Transaction, TransactionError},
+    transaction::{self, Result, Transaction, TransactionError, VersionedTransaction},
 };
 use solana_stake_program::stake_state::{
     self, Delegation, InflationPointCalculationEvent, PointValue,
@@ -141,7 +150,7 @@ impl ExecuteTimings {
 }
 
 type BankStatusCache = StatusCache<Result<()>>;
-#[frozen_abi(digest = "HhY4tMP5KZU9fw9VLpMMUikfvNVCLksocZBUKjt8ZjYH")]
+#[frozen_abi(digest = "73GdisNgD4ZbLPjcX6B4YAzreWRq2XWnZ6jL2RxmKCQE")]
 pub type BankSlotDelta = SlotDelta<Result<()>>;
 type TransactionAccountRefCells = Vec<Rc<RefCell<AccountSharedData>>>;
 type TransactionAccountDepRefCells = Vec<(Pubkey, Rc<RefCell<AccountSharedData>>)>;
@@ -434,6 +443,21 @@ pub type InnerInstructionsList = Vec<InnerInstructions>;
 /// A list of log messages emitted during a transaction
 pub type TransactionLogMessages = Vec<String>;
 
+/// The effects of a transaction simulated against a frozen bank
+pub struct TransactionSimulationResult {
+    pub result: Result<()>,
+    pub logs: TransactionLogMessages,
+    pub post_simulation_accounts: Vec<AccountSharedData>,
+    /// Compute units consumed by each executed instruction
+    pub units_consumed: Vec<u64>,
+    pub inner_instructions: Option<InnerInstructionsList>,
+    pub return_data: Option<TransactionReturnData>,
+    /// Receipts of the EVM transactions executed by the simulated transaction
+    pub evm_receipts: Vec<(evm_state::H256, evm_state::TransactionReceipt)>,
+    /// EVM accounts and storage changed by the simulated transaction
+    pub evm_state_changes: evm_state::ChangedState,
+}
+
 #[derive(Serialize, Deserialize, AbiExample, AbiEnumVisitor, Debug, PartialEq)]
 pub enum TransactionLogCollectorFilter {
     All,
@@ -916,6 +940,12 @@ pub struct Bank {
     // `transaction_log_collector_config`
     pub transaction_log_collector: Arc<RwLock<TransactionLogCollector>>,
 
+    /// Compute unit prices paid by the transactions this Bank committed
+    prioritization_fee: RwLock<PrioritizationFee>,
+
+    // Prioritization fees of recently frozen banks, shared by all banks
+    pub prioritization_fee_cache: Arc<RwLock<PrioritizationFeeCache>>,
+
     pub feature_set: Arc<FeatureSet>,
 
     pub drop_callback: RwLock<OptionalDropCallback>,
@@ -1158,6 +1188,8 @@ impl Bank {
             transaction_debug_keys: parent.transaction_debug_keys.clone(),
             transaction_log_collector_config: parent.transaction_log_collector_config.clone(),
             transaction_log_collector: Arc::new(RwLock::new(TransactionLogCollector::default())),
+            prioritization_fee: RwLock::new(PrioritizationFee::default()),
+            prioritization_fee_cache: parent.prioritization_fee_cache.clone(),
             feature_set: parent.feature_set.clone(),
             drop_callback: RwLock::new(OptionalDropCallback(
                 parent
@@ -1319,6 +1351,8 @@ impl Bank {
             transaction_debug_keys: debug_keys,
             transaction_log_collector_config: new(),
             transaction_log_collector: new(),
+            prioritization_fee: new(),
+            prioritization_fee_cache: new(),
             feature_set: new(),
             drop_callback: RwLock::new(OptionalDropCallback(None)),
             freeze_started: AtomicBool::new(fields.hash != Hash::default()),
@@ -2214,6 +2248,7 @@ impl Bank {
             self.update_slot_history();
             self.run_incinerator();
             self.commit_evm();
+            self.update_prioritization_fee_cache();
 
             // freeze is a one-way trip, idempotent
             self.freeze_started.store(true, Relaxed);
@@ -2277,6 +2312,11 @@ impl Bank {
             .for_each(|slot| self.src.status_cache.write().unwrap().add_root(*slot));
         squash_cache_time.stop();
 
+        self.prioritization_fee_cache
+            .write()
+            .unwrap()
+            .finalize(&roots);
+
         datapoint_debug!(
             "tower-observed",
             ("squash_accounts_ms", squash_accounts_time.as_ms(), i64),
@@ -2606,6 +2646,102 @@ impl Bank {
             .is_active(&feature_set::demote_sysvar_write_locks::id())
     }
 
+    pub fn versioned_tx_message_enabled(&self) -> bool {
+        self.feature_set
+            .is_active(&feature_set::sophon::versioned_tx_message_enabled::id())
+    }
+
+    /// Load addresses referenced by the address table lookups of a v0 message
+    pub fn load_lookup_table_addresses(
+        &self,
+        address_table_lookups: &[MessageAddressTableLookup],
+    ) -> Result<LoadedAddresses> {
+        if !self.versioned_tx_message_enabled() {
+            return Err(TransactionError::UnsupportedVersion);
+        }
+
+        let slot_hashes = self
+            .get_account(&sysvar::slot_hashes::id())
+            .and_then(|account| from_account::<SlotHashes, _>(&account))
+            .unwrap_or_default();
+        address_table_lookups
+            .iter()
+            .map(|address_table_lookup| {
+                self.rc
+                    .accounts
+                    .load_lookup_table_addresses(
+                        &self.ancestors,
+                        address_table_lookup,
+                        self.slot,
+                        &slot_hashes,
+                    )
+                    .map_err(|err| match err {
+                        AddressLookupError::LookupTableAccountNotFound => {
+                            TransactionError::AddressLookupTableNotFound
+                        }
+                        AddressLookupError::InvalidAccountOwner => {
+                            TransactionError::InvalidAddressLookupTableOwner
+                        }
+                        AddressLookupError::InvalidAccountData => {
+                            TransactionError::InvalidAddressLookupTableData
+                        }
+                        AddressLookupError::InvalidLookupIndex => {
+                            TransactionError::InvalidAddressLookupTableIndex
+                        }
+                    })
+            })
+            .collect()
+    }
+
+    /// Verify signatures of a versioned transaction, and expand it into a legacy transaction
+    /// which lists every account inline, including the ones loaded from lookup tables.
+    pub fn verify_versioned_transaction(
+        &self,
+        tx: VersionedTransaction,
+    ) -> Result<HashedTransaction<'static>> {
+        let message_hash = tx.verify_and_hash_message()?;
+        self.expand_versioned_transaction(tx, message_hash)
+    }
+
+    /// Sanitize a versioned transaction whose signatures were already verified, and expand it
+    /// into a legacy transaction which lists every account inline, including the ones loaded
+    /// from lookup tables.
+    ///
+    /// The expanded transaction is locked, charged and executed exactly like the versioned one,
+    /// but its signatures are only valid for the original message, so it must not be
+    /// verified again. `message_hash` is the hash of the original message.
+    pub fn expand_versioned_transaction(
+        &self,
+        tx: VersionedTransaction,
+        message_hash: Hash,
+    ) -> Result<HashedTransaction<'static>> {
+        tx.sanitize()?;
+        match tx.message {
+            VersionedMessage::Legacy(message) => Ok(HashedTransaction::new(
+                Cow::Owned(Transaction {
+                    signatures: tx.signatures,
+                    message,
+                }),
+                message_hash,
+            )),
+            VersionedMessage::V0(message) => {
+                let loaded_addresses =
+                    self.load_lookup_table_addresses(&message.address_table_lookups)?;
+                let loaded_message = LoadedMessage::new(message, loaded_addresses);
+                let message = loaded_message.to_legacy_message();
+                message.sanitize()?;
+                Ok(HashedTransaction::new_expanded(
+                    Cow::Owned(Transaction {
+                        signatures: tx.signatures,
+                        message,
+                    }),
+                    message_hash,
+                    Cow::Owned(loaded_message),
+                ))
+            }
+        }
+    }
+
     pub fn prepare_batch<'a, 'b>(
         &'a self,
         txs: impl Iterator<Item = &'b Transaction>,
@@ -2672,12 +2808,18 @@ impl Bank {
     }
 
     /// Run transactions against a frozen bank without committing the results
-    pub fn simulate_transaction(
-        &self,
-        transaction: &Transaction,
-    ) -> (Result<()>, TransactionLogMessages, Vec<AccountSharedData>) {
+    pub fn simulate_transaction(&self, transaction: &Transaction) -> TransactionSimulationResult {
         assert!(self.is_frozen(), "simulation bank must be frozen");
 
+        self.simulate_transaction_unchecked(transaction)
+    }
+
+    /// Run transactions against a bank without committing the results; does not check if the bank
+    /// is frozen, enabling use in single-Bank test frameworks
+    pub fn simulate_transaction_unchecked(
+        &self,
+        transaction: &Transaction,
+    ) -> TransactionSimulationResult {
         let batch = self.prepare_simulation_batch(transaction);
 
         let mut timings = ExecuteTimings::default();
@@ -2685,29 +2827,31 @@ impl Bank {
         let (
             loaded_accounts,
             executed,
-            _inner_instructions,
+            mut inner_instructions,
             log_messages,
+            mut return_data,
+            mut units_consumed,
             _retryable_transactions,
             _transaction_count,
             _signature_count,
-            _patch,
+            evm_patch,
         ) = self.load_and_execute_transactions(
             &batch,
             // After simulation, transactions will need to be forwarded to the leader
             // for processing. During forwarding, the transaction could expire if the
             // delay is not accounted for.
             MAX_PROCESSING_AGE - MAX_TRANSACTION_FORWARDING_DELAY,
-            false,
+            true,
             true,
             &mut timings,
             Self::take_evm_state_form_simulation,
         );
 
-        let transaction_result = executed[0].0.clone().map(|_| ());
-        let log_messages = log_messages
+        let result = executed[0].0.clone().map(|_| ());
+        let logs = log_messages
             .get(0)
             .map_or(vec![], |messages| messages.to_vec());
-        let post_transaction_accounts = loaded_accounts
+        let post_simulation_accounts = loaded_accounts
             .into_iter()
             .next()
             .unwrap()
@@ -2716,9 +2860,36 @@ impl Bank {
             .map(|loaded_transaction| loaded_transaction.accounts.into_iter().collect::<Vec<_>>())
             .unwrap_or_default();
 
+        // On a frozen bank simulation starts from the committed evm state,
+        // so everything in the patch was produced by this transaction.
+        // Otherwise the patch also carries the bank's pending evm changes.
+        let (evm_receipts, evm_state_changes) = evm_patch
+            .map(|patch| {
+                let receipts = patch
+                    .get_executed_transactions()
+                    .into_iter()
+                    .filter_map(|hash| {
+                        patch
+                            .find_transaction_receipt(hash)
+                            .map(|receipt| (hash, receipt.clone()))
+                    })
+                    .collect();
+                (receipts, patch.changed_state().clone())
+            })
+            .unwrap_or_default();
+
         debug!("simulate_transaction: {:?}", timings);
 
-        (transaction_result, log_messages, post_transaction_accounts)
+        TransactionSimulationResult {
+            result,
+            logs,
+            post_simulation_accounts,
+            units_consumed: units_consumed.pop().unwrap_or_default(),
+            inner_instructions: inner_instructions.pop().flatten(),
+            return_data: return_data.pop().flatten(),
+            evm_receipts,
+            evm_state_changes,
+        }
     }
 
     pub fn unlock_accounts(&self, batch: &mut TransactionBatch) {
@@ -2740,6 +2911,22 @@ impl Bank {
         self.rc.accounts.accounts_db.set_shrink_paths(paths);
     }
 
+    /// Accounts db is shared by all descendants of the bank, so notifier receives
+    /// account updates of every later slot too.
+    pub fn set_accounts_update_notifier(&self, accounts_update_notifier: AccountsUpdateNotifier) {
+        self.rc
+            .accounts
+            .accounts_db
+            .set_accounts_update_notifier(accounts_update_notifier);
+    }
+
+    pub fn notify_account_restore_from_snapshot(&self) {
+        self.rc
+            .accounts
+            .accounts_db
+            .notify_account_restore_from_snapshot();
+    }
+
     fn check_age<'a>(
         &self,
         txs: impl Iterator<Item = &'a Transaction>,
@@ -3140,6 +3327,8 @@ impl Bank {
         Vec<TransactionExecutionResult>,
         Vec<Option<InnerInstructionsList>>,
         Vec<TransactionLogMessages>,
+        Vec<Option<TransactionReturnData>>,
+        Vec<Vec<u64>>,
         Vec<usize>,
         u64,
         u64,
@@ -3203,15 +3392,22 @@ impl Bank {
         let mut inner_instructions: Vec<Option<InnerInstructionsList>> =
             Vec::with_capacity(hashed_txs.len());
         let mut transaction_log_messages = Vec::with_capacity(hashed_txs.len());
+        let mut transaction_return_data = Vec::with_capacity(hashed_txs.len());
+        let mut transaction_units_consumed = Vec::with_capacity(hashed_txs.len());
         let bpf_compute_budget = self
             .bpf_compute_budget
             .unwrap_or_else(BpfComputeBudget::new);
+        let compute_budget_program_enabled = self.compute_budget_program_enabled();
 
         let executed: Vec<TransactionExecutionResult> = loaded_accounts
             .iter_mut()
             .zip(hashed_txs.as_transactions_iter())
             .map(|(accs, tx)| match accs {
-                (Err(e), _nonce_rollback) => (Err(e.clone()), None),
+                (Err(e), _nonce_rollback) => {
+                    transaction_return_data.push(None);
+                    transaction_units_consumed.push(vec![]);
+                    (Err(e.clone()), None)
+                }
                 (Ok(loaded_transaction), nonce_rollback) => {
                     signature_count += u64::from(tx.message().header.num_required_signatures);
                     let executors = self.get_executors(&tx.message, &loaded_transaction.loaders);
@@ -3279,6 +3475,17 @@ impl Bank {
                     // keeping InvokeContext free to sharable borrowing for logging purposes.
                     let evm_executor = evm_executor.map(RefCell::new).map(Rc::new);
 
+                    let mut bpf_compute_budget = bpf_compute_budget;
+                    if compute_budget_program_enabled {
+                        // Compute budget instructions were validated when the fee was charged
+                        let limits =
+                            ComputeBudgetLimits::process_message(tx.message()).unwrap_or_default();
+                        bpf_compute_budget.max_units =
+                            limits.compute_unit_limit_or(bpf_compute_budget.max_units);
+                    }
+
+                    let mut return_data = None;
+                    let mut instruction_units_consumed = vec![];
                     let mut process_result = self.message_processor.process_message(
                         tx.message(),
                         &loader_refcells,
@@ -3294,7 +3501,11 @@ impl Bank {
                         self.rc.accounts.clone(),
                         &self.ancestors,
                         evm_executor.clone(),
+                        &mut return_data,
+                        &mut instruction_units_consumed,
                     );
+                    transaction_return_data.push(return_data);
+                    transaction_units_consumed.push(instruction_units_consumed);
                     let evm_new_error_handling = self
                         .feature_set
                         .is_active(&solana_sdk::feature_set::sophon::evm_new_error_handling::id());
@@ -3456,6 +3667,8 @@ impl Bank {
             executed,
             inner_instructions,
             transaction_log_messages,
+            transaction_return_data,
+            transaction_units_consumed,
             retryable_txs,
             tx_count,
             signature_count,
@@ -3473,7 +3686,11 @@ impl Bank {
 
         let fee_config = FeeConfig {
             secp256k1_program_enabled: self.secp256k1_program_enabled(),
+            ed25519_program_enabled: self.ed25519_program_enabled(),
         };
+        let compute_budget_program_enabled = self.compute_budget_program_enabled();
+        let demote_sysvar_write_locks = self.demote_sysvar_write_locks();
+        let mut prioritization_fee = self.prioritization_fee.write().unwrap();
 
         let results = txs
             .zip(executed)
@@ -3492,7 +3709,22 @@ impl Bank {
                     });
                 let fee_calculator = fee_calculator.ok_or(TransactionError::BlockhashNotFound)?;
 
-                let fee = fee_calculator.calculate_fee_with_config(tx.message(), &fee_config);
+                let mut fee = fee_calculator.calculate_fee_with_config(tx.message(), &fee_config);
+                if compute_budget_program_enabled && Self::can_commit(res) {
+                    let limits =
+                        ComputeBudgetLimits::process_message(tx.message()).unwrap_or_default();
+                    fee = fee.saturating_add(limits.prioritization_fee());
+                    if !is_simple_vote_transaction(tx) {
+                        let message = tx.message();
+                        let writable_accounts = message
+                            .account_keys
+                            .iter()
+                            .enumerate()
+                            .filter(|(i, _)| message.is_writable(*i, demote_sysvar_write_locks))
+                            .map(|(_, key)| key);
+                        prioritization_fee.update(limits.compute_unit_price, writable_accounts);
+                    }
+                }
 
                 let message = tx.message();
                 match *res {
@@ -3779,6 +4011,25 @@ impl Bank {
         }
     }
 
+    fn update_prioritization_fee_cache(&self) {
+        let prioritization_fee = self.prioritization_fee.read().unwrap();
+        if !prioritization_fee.is_empty() {
+            self.prioritization_fee_cache
+                .write()
+                .unwrap()
+                .insert(self.slot(), prioritization_fee.clone());
+        }
+    }
+
+    /// Minimum compute unit prices paid in recently rooted slots by transactions that write
+    /// lock all of `accounts`
+    pub fn get_recent_prioritization_fees(&self, accounts: &[Pubkey]) -> Vec<(Slot, u64)> {
+        self.prioritization_fee_cache
+            .read()
+            .unwrap()
+            .get_prioritization_fees(accounts)
+    }
+
     fn collect_rent_eagerly(&self) {
         if !self.enable_eager_rent_collection() {
             return;
@@ -4181,6 +4432,7 @@ impl Bank {
         TransactionBalancesSet,
         Vec<Option<InnerInstructionsList>>,
         Vec<TransactionLogMessages>,
+        Vec<Option<TransactionReturnData>>,
     ) {
         let pre_balances = if collect_balances {
             self.collect_balances(batch)
@@ -4192,6 +4444,8 @@ impl Bank {
             executed,
             inner_instructions,
             transaction_logs,
+            return_data,
+            _,
             _,
             tx_count,
             signature_count,
@@ -4224,6 +4478,7 @@ impl Bank {
             TransactionBalancesSet::new(pre_balances, post_balances),
             inner_instructions,
             transaction_logs,
+            return_data,
         )
     }
 
@@ -5154,6 +5409,27 @@ impl Bank {
             .is_active(&feature_set::secp256k1_program_enabled::id())
     }
 
+    pub fn ed25519_program_enabled(&self) -> bool {
+        self.feature_set
+            .is_active(&feature_set::sophon::ed25519_program_enabled::id())
+    }
+
+    pub fn compute_budget_program_enabled(&self) -> bool {
+        self.feature_set
+            .is_active(&feature_set::sophon::compute_budget_program_enabled::id())
+    }
+
+    /// Prioritization fee a message pays on top of its signature fees
+    pub fn get_prioritization_fee(&self, message: &Message) -> u64 {
+        if self.compute_budget_program_enabled() {
+            ComputeBudgetLimits::process_message(message)
+                .map(|limits| limits.prioritization_fee())
+                .unwrap_or_default()
+        } else {
+            0
+        }
+    }
+
     pub fn no_overflow_rent_distribution_enabled(&self) -> bool {
         self.feature_set
             .is_active(&feature_set::no_overflow_rent_distribution::id())
@@ -5471,7 +5747,7 @@ pub fn goto_end_of_slot(bank: &mut Bank) {
     }
 }
 
-fn is_simple_vote_transaction(transaction: &Transaction) -> bool {
+pub fn is_simple_vote_transaction(transaction: &Transaction) -> bool {
     if transaction.message.instructions.len() == 1 {
         let instruction = &transaction.message.instructions[0];
         let program_pubkey =
@@ -8371,6 +8647,53 @@ pub(crate) mod tests {
         bank.process_transaction(&tx).unwrap();
     }
 
+    #[test]
+    fn test_simulate_transaction_effects() {
+        solana_logger::setup();
+
+        let (genesis_config, mint_keypair) = create_genesis_config(20000 * 3);
+        let bank = Bank::new(&genesis_config);
+        let recent_hash = genesis_config.hash();
+
+        let evm_tx = solana_evm_loader_program::processor::dummy_call(0).0;
+        let caller = evm_tx.caller().unwrap();
+        let instructions = solana_evm_loader_program::transfer_native_to_evm_ixs(
+            mint_keypair.pubkey(),
+            20000,
+            caller,
+        );
+        let message = Message::new(&instructions, Some(&mint_keypair.pubkey()));
+        let tx = Transaction::new(&[&mint_keypair], message, recent_hash);
+        bank.process_transaction(&tx).unwrap();
+        bank.freeze();
+
+        // Native transfer, without evm effects
+        let tx = system_transaction::transfer(&mint_keypair, &Pubkey::new_unique(), 1, recent_hash);
+        let simulation = bank.simulate_transaction(&tx);
+        assert_eq!(simulation.result, Ok(()));
+        assert_eq!(simulation.units_consumed, vec![0]);
+        assert_eq!(simulation.inner_instructions, Some(vec![vec![]]));
+        assert!(simulation.evm_receipts.is_empty());
+        assert!(simulation.evm_state_changes.is_empty());
+
+        // Evm transaction, receipts and state changes are reported but not committed
+        let instruction =
+            solana_evm_loader_program::send_raw_tx(mint_keypair.pubkey(), evm_tx.clone(), None);
+        let message = Message::new(&[instruction], Some(&mint_keypair.pubkey()));
+        let tx = Transaction::new(&[&mint_keypair], message, recent_hash);
+        let processed_tx_len = bank.evm_state.read().unwrap().processed_tx_len();
+        let simulation = bank.simulate_transaction(&tx);
+        assert_eq!(simulation.result, Ok(()));
+        assert_eq!(simulation.units_consumed.len(), 1);
+        assert_eq!(simulation.evm_receipts.len(), 1);
+        assert_eq!(simulation.evm_receipts[0].0, evm_tx.tx_id_hash());
+        assert!(simulation.evm_state_changes.contains_key(&caller));
+        assert_eq!(
+            bank.evm_state.read().unwrap().processed_tx_len(),
+            processed_tx_len
+        );
+    }
+
     /// Process two batches, one with some slow routine, and second with evm state modification.
     /// Both batches are without conflicts, expect that with any size of sleep, evm batch will modify state root.
     #[test]
@@ -10887,15 +11210,20 @@ pub(crate) mod tests {
         let txs = vec![tx0, tx1, tx2];
 
         let lock_result = bank0.prepare_batch(txs.iter());
-        let (transaction_results, transaction_balances_set, inner_instructions, transaction_logs) =
-            bank0.load_execute_and_commit_transactions(
-                &lock_result,
-                MAX_PROCESSING_AGE,
-                true,
-                false,
-                false,
-                &mut ExecuteTimings::default(),
-            );
+        let (
+            transaction_results,
+            transaction_balances_set,
+            inner_instructions,
+            transaction_logs,
+            _return_data,
+        ) = bank0.load_execute_and_commit_transactions(
+            &lock_result,
+            MAX_PROCESSING_AGE,
+            true,
+            false,
+            false,
+            &mut ExecuteTimings::default(),
+        );
 
         assert!(inner_instructions[0].iter().all(|ix| ix.is_empty()));
         assert_eq!(transaction_logs.len(), 0);
@@ -13545,4 +13873,178 @@ pub(crate) mod tests {
         rent_debits.push(&Pubkey::default(), i64::MAX as u64, 0);
         assert_eq!(rent_debits.0.len(), 2);
     }
+
+    #[test]
+    fn test_compute_budget_prioritization_fee() {
+        use solana_sdk::compute_budget::ComputeBudgetInstruction;
+
+        let (mut genesis_config, mint_keypair) = create_genesis_config(sol_to_lamports(1.));
+        activate_all_features(&mut genesis_config);
+        let bank = Bank::new(&genesis_config);
+        let recipient = Pubkey::new_unique();
+
+        // Duplicate compute budget instructions are rejected and charged no fee
+        let balance = bank.get_balance(&mint_keypair.pubkey());
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_price(1),
+                ComputeBudgetInstruction::set_compute_unit_price(2),
+                system_instruction::transfer(&mint_keypair.pubkey(), &recipient, 10),
+            ],
+            Some(&mint_keypair.pubkey()),
+            &[&mint_keypair],
+            bank.last_blockhash(),
+        );
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::InstructionError(
+                1,
+                InstructionError::InvalidInstructionData
+            ))
+        );
+        assert_eq!(bank.get_balance(&mint_keypair.pubkey()), balance);
+
+        // 100_000 units at 2 lamports per unit
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_limit(100_000),
+                ComputeBudgetInstruction::set_compute_unit_price(2_000_000),
+                system_instruction::transfer(&mint_keypair.pubkey(), &recipient, 10),
+            ],
+            Some(&mint_keypair.pubkey()),
+            &[&mint_keypair],
+            bank.last_blockhash(),
+        );
+        let signature_fee = bank
+            .get_fee_calculator(&bank.last_blockhash())
+            .unwrap()
+            .calculate_fee(tx.message());
+        assert_eq!(bank.get_prioritization_fee(tx.message()), 200_000);
+        assert_eq!(bank.process_transaction(&tx), Ok(()));
+        assert_eq!(
+            bank.get_balance(&mint_keypair.pubkey()),
+            balance - 10 - signature_fee - 200_000
+        );
+
+        assert!(bank.get_recent_prioritization_fees(&[]).is_empty());
+        bank.freeze();
+        assert!(bank.get_recent_prioritization_fees(&[]).is_empty());
+        bank.squash();
+        assert_eq!(
+            bank.get_recent_prioritization_fees(&[recipient]),
+            vec![(bank.slot(), 2_000_000)]
+        );
+    }
+
+    #[test]
+    fn test_verify_versioned_transaction() {
+        use solana_address_lookup_table_program::{instruction as lookup_table_instruction, state};
+        use solana_sdk::message::v0;
+
+        let (mut genesis_config, mint_keypair) = create_genesis_config(sol_to_lamports(1.));
+        activate_all_features(&mut genesis_config);
+        let bank0 = Arc::new(Bank::new(&genesis_config));
+
+        let recipient = Pubkey::new_unique();
+        let table_keypair = Keypair::new();
+        let table_lamports = genesis_config
+            .rent
+            .minimum_balance(state::lookup_table_space(1));
+        let mut instructions = lookup_table_instruction::create_lookup_table(
+            &mint_keypair.pubkey(),
+            &table_keypair.pubkey(),
+            &mint_keypair.pubkey(),
+            table_lamports,
+            1,
+        );
+        instructions.push(lookup_table_instruction::extend_lookup_table(
+            &table_keypair.pubkey(),
+            &mint_keypair.pubkey(),
+            vec![recipient],
+        ));
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&mint_keypair.pubkey()),
+            &[&mint_keypair, &table_keypair],
+            bank0.last_blockhash(),
+        );
+        assert_eq!(bank0.process_transaction(&tx), Ok(()));
+
+        let message = v0::Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![mint_keypair.pubkey(), system_program::id()],
+            recent_blockhash: bank0.last_blockhash(),
+            instructions: vec![CompiledInstruction::new(
+                1,
+                &system_instruction::SystemInstruction::Transfer { lamports: 42 },
+                vec![0, 2],
+            )],
+            address_table_lookups: vec![v0::MessageAddressTableLookup {
+                account_key: table_keypair.pubkey(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![],
+            }],
+        };
+        let versioned_tx =
+            VersionedTransaction::try_new(VersionedMessage::V0(message), &[&mint_keypair]).unwrap();
+
+        // Addresses extended in the current slot can't be loaded yet
+        assert_eq!(
+            bank0
+                .verify_versioned_transaction(versioned_tx.clone())
+                .err(),
+            Some(TransactionError::InvalidAddressLookupTableIndex)
+        );
+
+        let mut bank1 = new_from_parent(&bank0);
+        bank1.deactivate_feature(&feature_set::sophon::versioned_tx_message_enabled::id());
+        assert_eq!(
+            bank1
+                .verify_versioned_transaction(versioned_tx.clone())
+                .err(),
+            Some(TransactionError::UnsupportedVersion)
+        );
+        bank1.activate_feature(&feature_set::sophon::versioned_tx_message_enabled::id());
+
+        let mut tampered_tx = versioned_tx.clone();
+        tampered_tx.signatures[0] = Signature::default();
+        assert_eq!(
+            bank1.verify_versioned_transaction(tampered_tx).err(),
+            Some(TransactionError::SignatureFailure)
+        );
+
+        let tx = bank1
+            .verify_versioned_transaction(versioned_tx.clone())
+            .unwrap();
+        assert_eq!(
+            tx.transaction().message.account_keys,
+            vec![mint_keypair.pubkey(), recipient, system_program::id()]
+        );
+        assert_eq!(
+            tx.loaded_addresses(),
+            Some(&LoadedAddresses {
+                writable: vec![recipient],
+                readonly: vec![],
+            })
+        );
+        assert_eq!(tx.to_versioned_transaction(), versioned_tx);
+
+        // Accounts loaded from lookup tables are locked like the ones listed inline
+        let other_tx =
+            system_transaction::transfer(&Keypair::new(), &recipient, 1, bank1.last_blockhash());
+        let txs = vec![tx, HashedTransaction::from(other_tx)];
+        let batch = bank1.prepare_hashed_batch(&txs);
+        assert_eq!(
+            batch.lock_results(),
+            &vec![Ok(()), Err(TransactionError::AccountInUse)]
+        );
+        drop(batch);
+
+        assert_eq!(bank1.process_transaction(txs[0].transaction()), Ok(()));
+        assert_eq!(bank1.get_balance(&recipient), 42);
+    }
 }
@@ -88,15 +88,46 @@ pub enum ActivationType {
 /// normal child Bank creation.
 /// https://github.com/solana-labs/solana/blob/84b139cc94b5be7c9e0c18c2ad91743231b85a0d/runtime/src/bank.rs#L1723
 fn feature_builtins() -> Vec<(Builtin, Pubkey, ActivationType)> {
-    vec![(
-        Builtin::new(
-            "secp256k1_program",
-            solana_sdk::secp256k1_program::id(),
-            solana_secp256k1_program::process_instruction,
+    vec![
+        (
+            Builtin::new(
+                "secp256k1_program",
+                solana_sdk::secp256k1_program::id(),
+                solana_secp256k1_program::process_instruction,
+            ),
+            feature_set::secp256k1_program_enabled::id(),
+            ActivationType::NewProgram,
+        ),
+        (
+            Builtin::new(
+                "address_lookup_table_program",
+                solana_address_lookup_table_program::id(),
+                with_program_logging!(
+                    solana_address_lookup_table_program::processor::process_instruction
+                ),
+            ),
+            feature_set::sophon::versioned_tx_message_enabled::id(),
+            ActivationType::NewProgram,
         ),
-        feature_set::secp256k1_program_enabled::id(),
-        ActivationType::NewProgram,
-    )]
+        (
+            Builtin::new(
+                "compute_budget_program",
+                solana_sdk::compute_budget::id(),
+                solana_compute_budget_program::process_instruction,
+            ),
+            feature_set::sophon::compute_budget_program_enabled::id(),
+            ActivationType::NewProgram,
+        ),
+        (
+            Builtin::new(
+                "ed25519_program",
+                solana_sdk::ed25519_program::id(),
+                solana_ed25519_program::process_instruction,
+            ),
+            feature_set::sophon::ed25519_program_enabled::id(),
+            ActivationType::NewProgram,
+        ),
+    ]
 }
 
 pub(crate) fn get() -> Builtins {
@@ -1,4 +1,11 @@
-use solana_sdk::{hash::Hash, transaction::Transaction};
+use solana_sdk::{
+    hash::Hash,
+    message::{
+        v0::{LoadedAddresses, LoadedMessage},
+        VersionedMessage,
+    },
+    transaction::{Transaction, VersionedTransaction},
+};
 use std::borrow::Cow;
 
 /// Transaction and the hash of its message
@@ -6,6 +13,9 @@ use std::borrow::Cow;
 pub struct HashedTransaction<'a> {
     transaction: Cow<'a, Transaction>,
     pub message_hash: Hash,
+    /// The v0 message, and the addresses loaded from its lookup tables, that `transaction`
+    /// was expanded from. The signatures are only valid for this message.
+    loaded_message: Option<Cow<'a, LoadedMessage>>,
 }
 
 impl<'a> HashedTransaction<'a> {
@@ -13,12 +23,63 @@ impl<'a> HashedTransaction<'a> {
         Self {
             transaction,
             message_hash,
+            loaded_message: None,
+        }
+    }
+
+    /// A legacy transaction expanded from a v0 message, listing every account inline
+    pub fn new_expanded(
+        transaction: Cow<'a, Transaction>,
+        message_hash: Hash,
+        loaded_message: Cow<'a, LoadedMessage>,
+    ) -> Self {
+        Self {
+            transaction,
+            message_hash,
+            loaded_message: Some(loaded_message),
         }
     }
 
     pub fn transaction(&self) -> &Transaction {
         self.transaction.as_ref()
     }
+
+    /// Borrows the transaction, without cloning it
+    pub fn borrowed(&self) -> HashedTransaction<'_> {
+        HashedTransaction {
+            transaction: Cow::Borrowed(self.transaction()),
+            message_hash: self.message_hash,
+            loaded_message: self.loaded_message.as_deref().map(Cow::Borrowed),
+        }
+    }
+
+    pub fn into_owned(self) -> HashedTransaction<'static> {
+        HashedTransaction {
+            transaction: Cow::Owned(self.transaction.into_owned()),
+            message_hash: self.message_hash,
+            loaded_message: self
+                .loaded_message
+                .map(|loaded_message| Cow::Owned(loaded_message.into_owned())),
+        }
+    }
+
+    /// Addresses loaded from lookup tables, if the transaction was expanded from a v0 message
+    pub fn loaded_addresses(&self) -> Option<&LoadedAddresses> {
+        self.loaded_message
+            .as_ref()
+            .map(|loaded_message| &loaded_message.loaded_addresses)
+    }
+
+    /// The transaction as it was signed, which is how it is recorded in entries
+    pub fn to_versioned_transaction(&self) -> VersionedTransaction {
+        match &self.loaded_message {
+            Some(loaded_message) => VersionedTransaction {
+                signatures: self.transaction.signatures.clone(),
+                message: VersionedMessage::V0(loaded_message.message.clone()),
+            },
+            None => VersionedTransaction::from(self.transaction().clone()),
+        }
+    }
 }
 
 impl<'a> From<Transaction> for HashedTransaction<'_> {
@@ -26,6 +87,7 @@ impl<'a> From<Transaction> for HashedTransaction<'_> {
         Self {
             message_hash: transaction.message().hash(),
             transaction: Cow::Owned(transaction),
+            loaded_message: None,
         }
     }
 }
@@ -35,6 +97,7 @@ impl<'a> From<&'a Transaction> for HashedTransaction<'a> {
         Self {
             message_hash: transaction.message().hash(),
             transaction: Cow::Borrowed(transaction),
+            loaded_message: None,
         }
     }
 }
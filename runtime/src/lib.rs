@@ -6,6 +6,7 @@ pub mod accounts_cache;
 pub mod accounts_db;
 pub mod accounts_hash;
 pub mod accounts_index;
+pub mod accounts_update_notifier_interface;
 pub mod append_vec;
 pub mod bank;
 pub mod bank_client;
@@ -26,6 +27,7 @@ pub mod loader_utils;
 pub mod log_collector;
 pub mod message_processor;
 mod native_loader;
+pub mod prioritization_fee;
 pub mod rent_collector;
 pub mod secondary_index;
 pub mod serde_snapshot;
@@ -9,9 +9,10 @@ use solana_sdk::{
     account::{AccountSharedData, ReadableAccount, WritableAccount},
     account_utils::StateMut,
     bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    entrypoint::MAX_PERMITTED_DATA_INCREASE,
     feature_set::{
         cpi_share_ro_and_exec_accounts, demote_sysvar_write_locks, instructions_sysvar_enabled,
-        FeatureSet,
+        sophon::account_data_realloc_enabled, FeatureSet,
     },
     ic_msg,
     instruction::{CompiledInstruction, Instruction, InstructionError},
@@ -20,10 +21,11 @@ use solana_sdk::{
     native_loader,
     process_instruction::{
         BpfComputeBudget, ComputeMeter, Executor, InvokeContext, Logger,
-        ProcessInstructionWithContext,
+        ProcessInstructionWithContext, TransactionReturnData,
     },
     pubkey::Pubkey,
     rent::Rent,
+    system_instruction::MAX_PERMITTED_DATA_LENGTH,
     system_program,
     sysvar::instructions,
     transaction::TransactionError,
@@ -108,6 +110,7 @@ impl PreAccount {
         rent: &Rent,
         post: &AccountSharedData,
         timings: &mut ExecuteDetailsTimings,
+        do_support_realloc: bool,
     ) -> Result<(), InstructionError> {
         let pre = self.account.borrow();
 
@@ -145,12 +148,21 @@ impl PreAccount {
 
         // Only the system program can change the size of the data
         //  and only if the system program owns the account
+        //  unless reallocation is supported, in which case the owner may
+        //  grow the data by at most MAX_PERMITTED_DATA_INCREASE per instruction
         let data_len_changed = pre.data().len() != post.data().len();
         if data_len_changed
             && (!system_program::check_id(program_id) // line coverage used to get branch coverage
                 || !system_program::check_id(&pre.owner))
         {
-            return Err(InstructionError::AccountDataSizeChanged);
+            if !do_support_realloc || *program_id != pre.owner || !is_writable || pre.executable {
+                return Err(InstructionError::AccountDataSizeChanged);
+            }
+            if post.data().len().saturating_sub(pre.data().len()) > MAX_PERMITTED_DATA_INCREASE
+                || post.data().len() as u64 > MAX_PERMITTED_DATA_LENGTH
+            {
+                return Err(InstructionError::InvalidRealloc);
+            }
         }
 
         // Only the owner may change account data
@@ -213,7 +225,7 @@ impl PreAccount {
         pre.owner = account.owner;
         pre.executable = account.executable;
         if pre.data().len() != account.data().len() {
-            // Only system account can change data size, copy with alloc
+            // Only the system program or a reallocating owner can change data size, copy with alloc
             pre.set_data(account.data().clone());
         } else {
             // Copy without allocate
@@ -276,6 +288,7 @@ pub struct ThisInvokeContext<'a> {
     #[allow(clippy::type_complexity)]
     sysvars: RefCell<Vec<(Pubkey, Option<Rc<Vec<u8>>>)>>,
     evm_executor: Option<Rc<RefCell<evm_state::Executor>>>,
+    return_data: (Pubkey, Vec<u8>),
 }
 impl<'a> ThisInvokeContext<'a> {
     #[allow(clippy::too_many_arguments)]
@@ -317,6 +330,17 @@ impl<'a> ThisInvokeContext<'a> {
             ancestors,
             sysvars: RefCell::new(vec![]),
             evm_executor,
+            return_data: (Pubkey::default(), Vec::new()),
+        }
+    }
+
+    /// Take the return data set during the instruction, `None` if it is empty
+    pub fn take_return_data(&mut self) -> Option<TransactionReturnData> {
+        let (program_id, data) = std::mem::take(&mut self.return_data);
+        if data.is_empty() {
+            None
+        } else {
+            Some(TransactionReturnData { program_id, data })
         }
     }
 }
@@ -356,6 +380,8 @@ impl<'a> InvokeContext for ThisInvokeContext<'a> {
                 caller_write_privileges,
                 &mut self.timings,
                 self.feature_set.is_active(&demote_sysvar_write_locks::id()),
+                self.feature_set
+                    .is_active(&account_data_realloc_enabled::id()),
             ),
             None => Err(InstructionError::GenericError), // Should never happen
         }
@@ -464,6 +490,13 @@ impl<'a> InvokeContext for ThisInvokeContext<'a> {
             None
         }
     }
+    fn set_return_data(&mut self, data: Vec<u8>) -> Result<(), InstructionError> {
+        self.return_data = (*self.get_caller()?, data);
+        Ok(())
+    }
+    fn get_return_data(&self) -> (Pubkey, &[u8]) {
+        (self.return_data.0, &self.return_data.1)
+    }
 }
 pub struct ThisLogger {
     log_collector: Option<Rc<LogCollector>>,
@@ -990,6 +1023,7 @@ impl MessageProcessor {
         rent: &Rent,
         timings: &mut ExecuteDetailsTimings,
         demote_sysvar_write_locks: bool,
+        do_support_realloc: bool,
     ) -> Result<(), InstructionError> {
         // Verify all executable accounts have zero outstanding refs
         Self::verify_account_references(executable_accounts)?;
@@ -1012,6 +1046,7 @@ impl MessageProcessor {
                     rent,
                     &account,
                     timings,
+                    do_support_realloc,
                 )?;
                 pre_sum += u128::from(pre_accounts[unique_index].lamports());
                 post_sum += u128::from(account.lamports);
@@ -1038,6 +1073,7 @@ impl MessageProcessor {
         caller_write_privileges: Option<&[bool]>,
         timings: &mut ExecuteDetailsTimings,
         demote_sysvar_write_locks: bool,
+        do_support_realloc: bool,
     ) -> Result<(), InstructionError> {
         // Verify the per-account instruction results
         let (mut pre_sum, mut post_sum) = (0_u128, 0_u128);
@@ -1060,7 +1096,14 @@ impl MessageProcessor {
                                 .map_err(|_| InstructionError::AccountBorrowOutstanding)?;
                         }
                         let account = account.borrow();
-                        pre_account.verify(program_id, is_writable, rent, &account, timings)?;
+                        pre_account.verify(
+                            program_id,
+                            is_writable,
+                            rent,
+                            &account,
+                            timings,
+                            do_support_realloc,
+                        )?;
                         pre_sum += u128::from(pre_account.lamports());
                         post_sum += u128::from(account.lamports);
                         if is_writable && !account.executable {
@@ -1106,6 +1149,8 @@ impl MessageProcessor {
         account_db: Arc<Accounts>,
         ancestors: &Ancestors,
         evm_executor: Option<Rc<RefCell<evm_state::Executor>>>,
+        return_data: &mut Option<TransactionReturnData>,
+        instruction_units_consumed: &mut Vec<u64>,
     ) -> Result<(), InstructionError> {
         // Fixup the special instructions key if present
         // before the account pre-values are taken care of
@@ -1122,6 +1167,7 @@ impl MessageProcessor {
             }
         }
 
+        let do_support_realloc = feature_set.is_active(&account_data_realloc_enabled::id());
         let pre_accounts = Self::create_pre_accounts(message, instruction, accounts);
         let program_id = instruction.program_id(&message.account_keys);
         let mut invoke_context = ThisInvokeContext::new(
@@ -1147,13 +1193,20 @@ impl MessageProcessor {
             accounts,
             demote_sysvar_write_locks,
         );
-        self.process_instruction(
+        let result = self.process_instruction(
             program_id,
             &keyed_accounts,
             &instruction.data,
             &mut invoke_context,
             false,
-        )?;
+        );
+        *return_data = invoke_context.take_return_data();
+        instruction_units_consumed.push(
+            bpf_compute_budget
+                .max_units
+                .saturating_sub(invoke_context.get_compute_meter().borrow().get_remaining()),
+        );
+        result?;
         Self::verify(
             message,
             instruction,
@@ -1163,6 +1216,7 @@ impl MessageProcessor {
             &rent_collector.rent,
             timings,
             demote_sysvar_write_locks,
+            do_support_realloc,
         )?;
 
         timings.accumulate(&invoke_context.timings);
@@ -1173,6 +1227,8 @@ impl MessageProcessor {
     /// Process a message.
     /// This method calls each instruction in the message over the set of loaded Accounts
     /// The accounts are committed back to the bank only if every instruction succeeds
+    /// The compute units consumed by each executed instruction are appended to
+    /// `instruction_units_consumed`
     #[allow(clippy::too_many_arguments)]
     #[allow(clippy::type_complexity)]
     pub fn process_message(
@@ -1191,8 +1247,16 @@ impl MessageProcessor {
         account_db: Arc<Accounts>,
         ancestors: &Ancestors,
         evm_executor: Option<Rc<RefCell<evm_state::Executor>>>,
+        return_data: &mut Option<TransactionReturnData>,
+        instruction_units_consumed: &mut Vec<u64>,
     ) -> Result<(), TransactionError> {
         let demote_sysvar_write_locks = feature_set.is_active(&demote_sysvar_write_locks::id());
+        let do_support_realloc = feature_set.is_active(&account_data_realloc_enabled::id());
+        let pre_account_states = if do_support_realloc {
+            Self::collect_account_states(accounts)
+        } else {
+            Vec::new()
+        };
         for (instruction_index, instruction) in message.instructions.iter().enumerate() {
             let instruction_recorder = instruction_recorders
                 .as_ref()
@@ -1215,9 +1279,63 @@ impl MessageProcessor {
                 account_db.clone(),
                 ancestors,
                 evm_executor.clone(),
+                return_data,
+                instruction_units_consumed,
             )
             .map_err(|err| TransactionError::InstructionError(instruction_index as u8, err))?;
         }
+        if do_support_realloc {
+            Self::verify_reallocated_accounts_rent(
+                &pre_account_states,
+                accounts,
+                &rent_collector.rent,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Record the owner, lamports and data length of each account before a message executes
+    fn collect_account_states(
+        accounts: &[Rc<RefCell<AccountSharedData>>],
+    ) -> Vec<(Pubkey, u64, usize)> {
+        accounts
+            .iter()
+            .map(|account| {
+                let account = account.borrow();
+                (account.owner, account.lamports, account.data().len())
+            })
+            .collect()
+    }
+
+    /// Verify that accounts reallocated by their owning program over the course
+    /// of a message are left rent-exempt, unless they were already paying rent
+    /// and their data did not grow
+    fn verify_reallocated_accounts_rent(
+        pre_account_states: &[(Pubkey, u64, usize)],
+        accounts: &[Rc<RefCell<AccountSharedData>>],
+        rent: &Rent,
+    ) -> Result<(), TransactionError> {
+        for ((pre_owner, pre_lamports, pre_data_len), account) in
+            pre_account_states.iter().zip(accounts)
+        {
+            let account = account.borrow();
+            // Accounts created or assigned by the system program are not reallocations
+            if account.owner != *pre_owner
+                || system_program::check_id(&account.owner)
+                || account.data().len() == *pre_data_len
+                || account.lamports == 0
+            {
+                continue;
+            }
+            let was_rent_paying =
+                *pre_lamports != 0 && !rent.is_exempt(*pre_lamports, *pre_data_len);
+            if was_rent_paying && account.data().len() <= *pre_data_len {
+                continue;
+            }
+            if !rent.is_exempt(account.lamports, account.data().len()) {
+                return Err(TransactionError::InvalidRentPayingAccount);
+            }
+        }
         Ok(())
     }
 }
@@ -1390,6 +1508,7 @@ mod tests {
         rent: Rent,
         pre: PreAccount,
         post: AccountSharedData,
+        do_support_realloc: bool,
     }
     impl Change {
         pub fn new(owner: &Pubkey, program_id: &Pubkey) -> Self {
@@ -1411,6 +1530,7 @@ mod tests {
                     lamports: std::u64::MAX,
                     ..Account::default()
                 }),
+                do_support_realloc: false,
             }
         }
         pub fn read_only(mut self) -> Self {
@@ -1436,6 +1556,10 @@ mod tests {
             self.post.set_data(post);
             self
         }
+        pub fn realloc(mut self) -> Self {
+            self.do_support_realloc = true;
+            self
+        }
         pub fn rent_epoch(mut self, pre: u64, post: u64) -> Self {
             self.pre.account.borrow_mut().rent_epoch = pre;
             self.post.rent_epoch = post;
@@ -1448,6 +1572,7 @@ mod tests {
                 &self.rent,
                 &self.post,
                 &mut ExecuteDetailsTimings::default(),
+                self.do_support_realloc,
             )
         }
     }
@@ -1770,6 +1895,141 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_verify_account_changes_realloc() {
+        let alice_program_id = solana_sdk::pubkey::new_rand();
+        let mallory_program_id = solana_sdk::pubkey::new_rand();
+
+        assert_eq!(
+            Change::new(&alice_program_id, &alice_program_id)
+                .realloc()
+                .data(vec![0], vec![0, 0])
+                .verify(),
+            Ok(()),
+            "owner should be able to grow its account data"
+        );
+        assert_eq!(
+            Change::new(&alice_program_id, &alice_program_id)
+                .realloc()
+                .data(vec![0, 0], vec![0])
+                .verify(),
+            Ok(()),
+            "owner should be able to shrink its account data"
+        );
+        assert_eq!(
+            Change::new(&alice_program_id, &alice_program_id)
+                .realloc()
+                .data(vec![0], vec![0; MAX_PERMITTED_DATA_INCREASE + 1])
+                .verify(),
+            Ok(()),
+            "owner should be able to grow its account data by the permitted increase"
+        );
+        assert_eq!(
+            Change::new(&alice_program_id, &alice_program_id)
+                .realloc()
+                .data(vec![0], vec![0; MAX_PERMITTED_DATA_INCREASE + 2])
+                .verify(),
+            Err(InstructionError::InvalidRealloc),
+            "owner should not be able to grow its account data beyond the permitted increase"
+        );
+        assert_eq!(
+            Change::new(&alice_program_id, &mallory_program_id)
+                .realloc()
+                .data(vec![0], vec![0, 0])
+                .verify(),
+            Err(InstructionError::AccountDataSizeChanged),
+            "non-owner should not be able to change the data length"
+        );
+        assert_eq!(
+            Change::new(&alice_program_id, &alice_program_id)
+                .realloc()
+                .read_only()
+                .data(vec![0], vec![0, 0])
+                .verify(),
+            Err(InstructionError::AccountDataSizeChanged),
+            "owner should not be able to change the data length of a read-only account"
+        );
+        assert_eq!(
+            Change::new(&alice_program_id, &alice_program_id)
+                .realloc()
+                .executable(true, true)
+                .data(vec![0], vec![0, 0])
+                .verify(),
+            Err(InstructionError::AccountDataSizeChanged),
+            "owner should not be able to change the data length of an executable account"
+        );
+    }
+
+    #[test]
+    fn test_verify_reallocated_accounts_rent() {
+        let rent = Rent::default();
+        let alice_program_id = solana_sdk::pubkey::new_rand();
+        let rent_exempt_lamports = rent.minimum_balance(2);
+
+        let accounts = vec![AccountSharedData::new_ref(
+            rent_exempt_lamports,
+            1,
+            &alice_program_id,
+        )];
+        let pre_account_states = MessageProcessor::collect_account_states(&accounts);
+        accounts[0].borrow_mut().set_data(vec![0; 2]);
+        assert_eq!(
+            MessageProcessor::verify_reallocated_accounts_rent(
+                &pre_account_states,
+                &accounts,
+                &rent
+            ),
+            Ok(())
+        );
+
+        accounts[0].borrow_mut().set_data(vec![0; 3]);
+        assert_eq!(
+            MessageProcessor::verify_reallocated_accounts_rent(
+                &pre_account_states,
+                &accounts,
+                &rent
+            ),
+            Err(TransactionError::InvalidRentPayingAccount)
+        );
+
+        // Rent-paying accounts may stay rent-paying as long as their data does not grow
+        let accounts = vec![AccountSharedData::new_ref(1, 3, &alice_program_id)];
+        let pre_account_states = MessageProcessor::collect_account_states(&accounts);
+        accounts[0].borrow_mut().set_data(vec![0; 2]);
+        assert_eq!(
+            MessageProcessor::verify_reallocated_accounts_rent(
+                &pre_account_states,
+                &accounts,
+                &rent
+            ),
+            Ok(())
+        );
+
+        accounts[0].borrow_mut().set_data(vec![0; 4]);
+        assert_eq!(
+            MessageProcessor::verify_reallocated_accounts_rent(
+                &pre_account_states,
+                &accounts,
+                &rent
+            ),
+            Err(TransactionError::InvalidRentPayingAccount)
+        );
+
+        // Accounts created by the system program are not subject to the check
+        let accounts = vec![AccountSharedData::new_ref(1, 0, &system_program::id())];
+        let pre_account_states = MessageProcessor::collect_account_states(&accounts);
+        accounts[0].borrow_mut().set_data(vec![0; 3]);
+        accounts[0].borrow_mut().owner = alice_program_id;
+        assert_eq!(
+            MessageProcessor::verify_reallocated_accounts_rent(
+                &pre_account_states,
+                &accounts,
+                &rent
+            ),
+            Ok(())
+        );
+    }
+
     #[test]
     fn test_process_message_readonly_handling() {
         #[derive(Serialize, Deserialize)]
@@ -1854,6 +2114,8 @@ mod tests {
             Arc::new(Accounts::default()),
             &ancestors,
             None,
+            &mut None,
+            &mut vec![],
         );
         assert_eq!(result, Ok(()));
         assert_eq!(accounts[0].borrow().lamports, 100);
@@ -1883,6 +2145,8 @@ mod tests {
             Arc::new(Accounts::default()),
             &ancestors,
             None,
+            &mut None,
+            &mut vec![],
         );
         assert_eq!(
             result.unwrap_err(),
@@ -1913,6 +2177,8 @@ mod tests {
             Arc::new(Accounts::default()),
             &ancestors,
             None,
+            &mut None,
+            &mut vec![],
         );
         assert_eq!(
             result.unwrap_err(),
@@ -2030,6 +2296,8 @@ mod tests {
             Arc::new(Accounts::default()),
             &ancestors,
             None,
+            &mut None,
+            &mut vec![],
         );
         assert_eq!(
             result.unwrap_err(),
@@ -2060,6 +2328,8 @@ mod tests {
             Arc::new(Accounts::default()),
             &ancestors,
             None,
+            &mut None,
+            &mut vec![],
         );
         assert_eq!(result, Ok(()));
 
@@ -2091,6 +2361,8 @@ mod tests {
             Arc::new(Accounts::default()),
             &ancestors,
             None,
+            &mut None,
+            &mut vec![],
         );
         assert_eq!(result, Ok(()));
         assert_eq!(accounts[0].borrow().lamports, 80);
@@ -2253,6 +2525,159 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_process_message_return_data() {
+        fn mock_process_instruction(
+            _program_id: &Pubkey,
+            _keyed_accounts: &[KeyedAccount],
+            data: &[u8],
+            invoke_context: &mut dyn InvokeContext,
+        ) -> Result<(), InstructionError> {
+            invoke_context.set_return_data(data.to_vec())
+        }
+
+        let mock_program_id = Pubkey::new(&[2u8; 32]);
+        let rent_collector = RentCollector::default();
+        let mut message_processor = MessageProcessor::default();
+        message_processor.add_program(mock_program_id, mock_process_instruction);
+
+        let accounts = vec![AccountSharedData::new_ref(100, 1, &mock_program_id)];
+        let loaders = vec![vec![(
+            mock_program_id,
+            Rc::new(RefCell::new(create_loadable_account_for_test(
+                "mock_program",
+            ))),
+        )]];
+        let executors = Rc::new(RefCell::new(Executors::default()));
+        let ancestors = Ancestors::default();
+
+        let from_pubkey = solana_sdk::pubkey::new_rand();
+        let process = |data: &[u8], return_data: &mut Option<TransactionReturnData>| {
+            let message = Message::new(
+                &[Instruction::new_with_bytes(
+                    mock_program_id,
+                    data,
+                    vec![AccountMeta::new(from_pubkey, true)],
+                )],
+                Some(&from_pubkey),
+            );
+            message_processor.process_message(
+                &message,
+                &loaders,
+                &accounts,
+                &[],
+                &rent_collector,
+                None,
+                executors.clone(),
+                None,
+                Arc::new(FeatureSet::all_enabled()),
+                BpfComputeBudget::new(),
+                &mut ExecuteDetailsTimings::default(),
+                Arc::new(Accounts::default()),
+                &ancestors,
+                None,
+                return_data,
+                &mut vec![],
+            )
+        };
+
+        let mut return_data = None;
+        assert_eq!(process(&[1, 2, 3], &mut return_data), Ok(()));
+        assert_eq!(
+            return_data,
+            Some(TransactionReturnData {
+                program_id: mock_program_id,
+                data: vec![1, 2, 3],
+            })
+        );
+
+        // Empty return data is not reported
+        let mut return_data = None;
+        assert_eq!(process(&[], &mut return_data), Ok(()));
+        assert_eq!(return_data, None);
+    }
+
+    #[test]
+    fn test_process_message_units_consumed() {
+        fn mock_process_instruction(
+            _program_id: &Pubkey,
+            _keyed_accounts: &[KeyedAccount],
+            data: &[u8],
+            invoke_context: &mut dyn InvokeContext,
+        ) -> Result<(), InstructionError> {
+            invoke_context
+                .get_compute_meter()
+                .borrow_mut()
+                .consume(data[0] as u64)
+        }
+
+        let mock_program_id = Pubkey::new(&[2u8; 32]);
+        let rent_collector = RentCollector::default();
+        let mut message_processor = MessageProcessor::default();
+        message_processor.add_program(mock_program_id, mock_process_instruction);
+
+        let accounts = vec![AccountSharedData::new_ref(100, 1, &mock_program_id)];
+        let loader = vec![(
+            mock_program_id,
+            Rc::new(RefCell::new(create_loadable_account_for_test(
+                "mock_program",
+            ))),
+        )];
+        let loaders = vec![loader.clone(), loader];
+        let executors = Rc::new(RefCell::new(Executors::default()));
+        let ancestors = Ancestors::default();
+
+        let from_pubkey = solana_sdk::pubkey::new_rand();
+        let process = |units: &[u8], instruction_units_consumed: &mut Vec<u64>| {
+            let instructions: Vec<_> = units
+                .iter()
+                .map(|units| {
+                    Instruction::new_with_bytes(
+                        mock_program_id,
+                        &[*units],
+                        vec![AccountMeta::new(from_pubkey, true)],
+                    )
+                })
+                .collect();
+            let message = Message::new(&instructions, Some(&from_pubkey));
+            let mut bpf_compute_budget = BpfComputeBudget::new();
+            bpf_compute_budget.max_units = 100;
+            message_processor.process_message(
+                &message,
+                &loaders,
+                &accounts,
+                &[],
+                &rent_collector,
+                None,
+                executors.clone(),
+                None,
+                Arc::new(FeatureSet::all_enabled()),
+                bpf_compute_budget,
+                &mut ExecuteDetailsTimings::default(),
+                Arc::new(Accounts::default()),
+                &ancestors,
+                None,
+                &mut None,
+                instruction_units_consumed,
+            )
+        };
+
+        let mut instruction_units_consumed = vec![];
+        assert_eq!(process(&[10, 42], &mut instruction_units_consumed), Ok(()));
+        assert_eq!(instruction_units_consumed, vec![10, 42]);
+
+        // Units of a failing instruction are reported, later instructions are not executed
+        let mut instruction_units_consumed = vec![];
+        assert_eq!(
+            process(&[101, 1], &mut instruction_units_consumed),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::ComputationalBudgetExceeded
+            ))
+        );
+        assert_eq!(instruction_units_consumed, vec![100]);
+    }
+
     #[test]
     fn test_debug() {
         let mut message_processor = MessageProcessor::default();
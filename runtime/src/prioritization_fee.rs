@@ -0,0 +1,171 @@
+//! Tracks the compute unit prices paid by the transactions of recent blocks, so that
+//! clients can estimate the prioritization fee needed to land a transaction.
+
+use solana_sdk::{clock::Slot, pubkey::Pubkey};
+use std::collections::{BTreeMap, HashMap};
+
+/// Number of recent slots kept in the `PrioritizationFeeCache`
+pub const MAX_PRIORITIZATION_FEE_CACHE_SLOTS: usize = 150;
+
+/// Compute unit prices paid by the non-vote transactions of a single block
+#[derive(AbiExample, Clone, Debug, Default, PartialEq)]
+pub struct PrioritizationFee {
+    /// Minimum compute unit price of the block's transactions
+    min_compute_unit_price: Option<u64>,
+
+    /// Minimum compute unit price of the transactions that write lock each account
+    min_writable_account_prices: HashMap<Pubkey, u64>,
+}
+
+impl PrioritizationFee {
+    /// Record a transaction which paid `compute_unit_price` and write locked `writable_accounts`
+    pub fn update<'a>(
+        &mut self,
+        compute_unit_price: u64,
+        writable_accounts: impl Iterator<Item = &'a Pubkey>,
+    ) {
+        self.min_compute_unit_price = Some(
+            self.min_compute_unit_price
+                .map_or(compute_unit_price, |price| price.min(compute_unit_price)),
+        );
+        for account in writable_accounts {
+            self.min_writable_account_prices
+                .entry(*account)
+                .and_modify(|price| *price = (*price).min(compute_unit_price))
+                .or_insert(compute_unit_price);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min_compute_unit_price.is_none()
+    }
+
+    /// Minimum compute unit price a transaction writing to all of `accounts` had to pay to
+    /// be included in this block
+    pub fn get_min_compute_unit_price(&self, accounts: &[Pubkey]) -> u64 {
+        accounts
+            .iter()
+            .filter_map(|account| self.min_writable_account_prices.get(account))
+            .chain(self.min_compute_unit_price.iter())
+            .copied()
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// Prioritization fees of the most recently rooted blocks, shared by all banks. Frozen
+/// blocks are held back until they are rooted, so that fees of abandoned forks are never
+/// reported.
+#[derive(AbiExample, Debug, Default)]
+pub struct PrioritizationFeeCache {
+    fees: BTreeMap<Slot, PrioritizationFee>,
+    unfinalized_fees: BTreeMap<Slot, PrioritizationFee>,
+}
+
+impl PrioritizationFeeCache {
+    /// Record the fees of a frozen block, which are reported once `finalize` roots its slot
+    pub fn insert(&mut self, slot: Slot, fee: PrioritizationFee) {
+        self.unfinalized_fees.insert(slot, fee);
+    }
+
+    /// Report the fees of the frozen blocks in `rooted_slots` and drop the ones of any other
+    /// block up to the highest root, which can no longer become rooted
+    pub fn finalize(&mut self, rooted_slots: &[Slot]) {
+        let max_root = match rooted_slots.iter().max() {
+            Some(max_root) => *max_root,
+            None => return,
+        };
+        let pending_fees = self.unfinalized_fees.split_off(&(max_root + 1));
+        let finalized_fees = std::mem::replace(&mut self.unfinalized_fees, pending_fees);
+        for (slot, fee) in finalized_fees {
+            if rooted_slots.contains(&slot) {
+                self.fees.insert(slot, fee);
+            }
+        }
+        while self.fees.len() > MAX_PRIORITIZATION_FEE_CACHE_SLOTS {
+            let oldest_slot = *self.fees.keys().next().unwrap();
+            self.fees.remove(&oldest_slot);
+        }
+    }
+
+    /// Minimum compute unit price that transactions writing to all of `accounts`
+    /// paid in each cached slot, ordered by slot
+    pub fn get_prioritization_fees(&self, accounts: &[Pubkey]) -> Vec<(Slot, u64)> {
+        self.fees
+            .iter()
+            .map(|(slot, fee)| (*slot, fee.get_min_compute_unit_price(accounts)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prioritization_fee() {
+        let account_a = Pubkey::new_unique();
+        let account_b = Pubkey::new_unique();
+
+        let mut fee = PrioritizationFee::default();
+        assert!(fee.is_empty());
+        assert_eq!(fee.get_min_compute_unit_price(&[]), 0);
+
+        fee.update(10, [account_a].iter());
+        fee.update(5, [account_b].iter());
+        fee.update(20, [account_a, account_b].iter());
+        assert!(!fee.is_empty());
+        assert_eq!(fee.get_min_compute_unit_price(&[]), 5);
+        assert_eq!(fee.get_min_compute_unit_price(&[account_a]), 10);
+        assert_eq!(fee.get_min_compute_unit_price(&[account_b]), 5);
+        assert_eq!(fee.get_min_compute_unit_price(&[account_a, account_b]), 10);
+        assert_eq!(fee.get_min_compute_unit_price(&[Pubkey::new_unique()]), 5);
+    }
+
+    #[test]
+    fn test_prioritization_fee_cache() {
+        let account = Pubkey::new_unique();
+        let mut cache = PrioritizationFeeCache::default();
+        for slot in 0..(MAX_PRIORITIZATION_FEE_CACHE_SLOTS as u64 + 10) {
+            let mut fee = PrioritizationFee::default();
+            fee.update(slot, [account].iter());
+            cache.insert(slot, fee);
+            cache.finalize(&[slot]);
+        }
+
+        let fees = cache.get_prioritization_fees(&[account]);
+        assert_eq!(fees.len(), MAX_PRIORITIZATION_FEE_CACHE_SLOTS);
+        assert_eq!(fees[0], (10, 10));
+        assert_eq!(
+            fees.last(),
+            Some(&(
+                MAX_PRIORITIZATION_FEE_CACHE_SLOTS as u64 + 9,
+                MAX_PRIORITIZATION_FEE_CACHE_SLOTS as u64 + 9
+            ))
+        );
+    }
+
+    #[test]
+    fn test_prioritization_fee_cache_finalize() {
+        let account = Pubkey::new_unique();
+        let mut cache = PrioritizationFeeCache::default();
+        for slot in [1, 2, 3, 4, 5] {
+            let mut fee = PrioritizationFee::default();
+            fee.update(slot, [account].iter());
+            cache.insert(slot, fee);
+        }
+        assert!(cache.get_prioritization_fees(&[account]).is_empty());
+
+        // 2 and 4 were on an abandoned fork, 5 is not rooted yet
+        cache.finalize(&[3, 1]);
+        assert_eq!(
+            cache.get_prioritization_fees(&[account]),
+            vec![(1, 1), (3, 3)]
+        );
+        cache.finalize(&[5]);
+        assert_eq!(
+            cache.get_prioritization_fees(&[account]),
+            vec![(1, 1), (3, 3), (5, 5)]
+        );
+    }
+}
@@ -15,6 +15,7 @@ use {
     },
     bincode,
     bincode::{config::Options, Error},
+    evm_state::storage::incremental::TrieDiff,
     log::*,
     serde::{de::DeserializeOwned, de::Error as _, Deserialize, Serialize},
     solana_sdk::{
@@ -151,7 +152,76 @@ pub(crate) fn bank_from_stream<R>(
 where
     R: Read,
 {
-    let (bank_fields, accounts_db_fields) = TypeContextFuture::deserialize_bank_fields(stream)?;
+    bank_from_streams(
+        evm_state_path,
+        evm_state_version,
+        stream,
+        None,
+        account_paths,
+        unpacked_append_vec_map,
+        genesis_config,
+        frozen_account_pubkeys,
+        debug_keys,
+        additional_builtins,
+        account_indexes,
+        caching_enabled,
+        evm_state_backup_path,
+        skip_purge_verify,
+        evm_archive,
+    )
+}
+
+/// Same as `bank_from_stream`, but optionally applies incremental snapshot
+/// (bank stream with newer accounts and evm-state diff) on top of the full snapshot stream.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn bank_from_streams<R>(
+    evm_state_path: &Path,
+    evm_state_version: EvmStateVersion,
+    stream: &mut BufReader<R>,
+    incremental: Option<(&mut BufReader<R>, TrieDiff)>,
+    account_paths: &[PathBuf],
+    unpacked_append_vec_map: UnpackedAppendVecMap,
+    genesis_config: &GenesisConfig,
+    frozen_account_pubkeys: &[Pubkey],
+    debug_keys: Option<Arc<HashSet<Pubkey>>>,
+    additional_builtins: Option<&Builtins>,
+    account_indexes: AccountSecondaryIndexes,
+    caching_enabled: bool,
+    evm_state_backup_path: &Path,
+    skip_purge_verify: bool,
+    evm_archive: Option<evm_state::Storage>,
+) -> std::result::Result<Bank, Error>
+where
+    R: Read,
+{
+    let (full_bank_fields, full_accounts_db_fields) =
+        TypeContextFuture::deserialize_bank_fields(stream)?;
+    let (bank_fields, accounts_db_fields, evm_state_diff) = match incremental {
+        Some((incremental_stream, evm_state_diff)) => {
+            let (bank_fields, accounts_db_fields) =
+                TypeContextFuture::deserialize_bank_fields(incremental_stream)?;
+            if bank_fields.slot <= full_bank_fields.slot {
+                return Err(Error::custom(format!(
+                    "Incremental snapshot slot {} should be newer than full snapshot slot {}",
+                    bank_fields.slot, full_bank_fields.slot
+                )));
+            }
+            if evm_state_diff.base_root != full_bank_fields.evm_persist_feilds.last_root()
+                || evm_state_diff.root != bank_fields.evm_persist_feilds.last_root()
+            {
+                return Err(Error::custom(format!(
+                    "Incremental snapshot evm-state diff {:?} -> {:?} doesn't match snapshots roots",
+                    evm_state_diff.base_root, evm_state_diff.root
+                )));
+            }
+            (
+                bank_fields,
+                merge_accounts_db_fields(full_accounts_db_fields, accounts_db_fields),
+                Some(evm_state_diff),
+            )
+        }
+        None => (full_bank_fields, full_accounts_db_fields, None),
+    };
     reconstruct_bank_from_fields(
         bank_fields,
         accounts_db_fields,
@@ -165,6 +235,7 @@ where
         account_indexes,
         caching_enabled,
         evm_state_backup_path,
+        evm_state_diff,
         evm_state_version.support_gc(),
         skip_purge_verify,
         true, // enable gc
@@ -176,6 +247,19 @@ where
     })
 }
 
+// Incremental snapshot contains storages only for slots newer than the full snapshot,
+// everything else (write version, bank hash) is taken from incremental snapshot as is.
+fn merge_accounts_db_fields<E>(
+    full_accounts_db_fields: AccountsDbFields<E>,
+    incremental_accounts_db_fields: AccountsDbFields<E>,
+) -> AccountsDbFields<E> {
+    let AccountsDbFields(mut storage, ..) = full_accounts_db_fields;
+    let AccountsDbFields(incremental_storage, version, slot, bank_hash_info) =
+        incremental_accounts_db_fields;
+    storage.extend(incremental_storage);
+    AccountsDbFields(storage, version, slot, bank_hash_info)
+}
+
 pub(crate) fn bank_to_stream<W>(
     evm_version: EvmStateVersion,
     stream: &mut BufWriter<W>,
@@ -254,6 +338,8 @@ fn reconstruct_bank_from_fields<E>(
     account_indexes: AccountSecondaryIndexes,
     caching_enabled: bool,
     evm_state_backup_path: &Path,
+    // nodes created since `evm_state_backup_path`, if we restoring from incremental snapshot
+    evm_state_diff: Option<TrieDiff>,
     // true if we restoring from full backup, or from gc
     load_full_backup: bool,
     skip_purge_verify: bool,
@@ -292,8 +378,9 @@ where
         enable_gc
     );
 
-    // if we force verify, or our gc settings is not equal to settings in snapshot
-    if !skip_purge_verify || enable_gc != load_full_backup {
+    // if we force verify, or our gc settings is not equal to settings in snapshot,
+    // or backup should be patched by incremental snapshot
+    if !skip_purge_verify || enable_gc != load_full_backup || evm_state_diff.is_some() {
         let mut tmp_evm_state_path_parent = evm_state_path.to_path_buf();
         tmp_evm_state_path_parent.pop();
         let tmp_dir = tempfile::TempDir::new_in(tmp_evm_state_path_parent)?;
@@ -307,6 +394,14 @@ where
             evm_state::Storage::open_persistent(tmp_dir.path(), load_full_backup).map_err(|e| {
                 Error::custom(format!("Unable to restore tmp evm backup storage {}", e))
             })?;
+        if let Some(evm_state_diff) = evm_state_diff {
+            let mut measure = Measure::start("EVM state diff apply");
+            evm_state_diff
+                .apply(&src)
+                .map_err(|e| Error::custom(format!("Unable to apply evm-state diff {}", e)))?;
+            measure.stop();
+            info!("{}", measure);
+        }
 
         let destination = evm_state::Storage::open_persistent(evm_state_path, enable_gc)
             .map_err(|e| Error::custom(format!("Unable to open destination evm-state {}", e)))?;
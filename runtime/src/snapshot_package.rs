@@ -15,6 +15,32 @@ pub type AccountsPackageSender = Sender<AccountsPackagePre>;
 pub type AccountsPackageReceiver = Receiver<AccountsPackagePre>;
 pub type AccountsPackageSendError = SendError<AccountsPackagePre>;
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SnapshotType {
+    /// Standalone snapshot with all accounts and evm-state.
+    Full,
+    /// Full snapshot, which also remembers its evm-state nodes,
+    /// so incremental snapshots can be built on top of it.
+    IncrementalBase,
+    /// Snapshot with accounts and evm-state nodes changed since the full snapshot at given slot.
+    Incremental(Slot),
+}
+
+impl SnapshotType {
+    pub fn incremental_base_slot(&self) -> Option<Slot> {
+        match self {
+            Self::Incremental(base_slot) => Some(*base_slot),
+            Self::Full | Self::IncrementalBase => None,
+        }
+    }
+}
+
+impl Default for SnapshotType {
+    fn default() -> Self {
+        Self::Full
+    }
+}
+
 #[derive(Debug)]
 pub struct AccountsPackagePre {
     pub slot: Slot,
@@ -32,6 +58,7 @@ pub struct AccountsPackagePre {
     pub evm_db: evm_state::storage::Storage,
     // TODO: Replace root/db/bank by root-guard.
     pub bank: Arc<Bank>,
+    pub snapshot_type: SnapshotType,
 }
 
 impl AccountsPackagePre {
@@ -67,6 +94,7 @@ impl AccountsPackagePre {
             evm_root,
             evm_db,
             bank,
+            snapshot_type: SnapshotType::default(),
         }
     }
 }
@@ -84,6 +112,7 @@ pub struct AccountsPackage {
     pub evm_root: evm_state::H256,
     pub evm_db: evm_state::storage::Storage,
     pub bank: Arc<Bank>,
+    pub snapshot_type: SnapshotType,
 }
 
 impl AccountsPackage {
@@ -115,6 +144,7 @@ impl AccountsPackage {
             evm_root,
             evm_db,
             bank,
+            snapshot_type: SnapshotType::default(),
         }
     }
 }
@@ -6,16 +6,20 @@ use crate::{
     hardened_unpack::ParallelSelector,
     hardened_unpack::{unpack_snapshot, UnpackError, UnpackedAppendVecMap},
     serde_snapshot::{
-        bank_from_stream, bank_to_stream, EvmStateVersion, SnapshotStorage, SnapshotStorages,
+        bank_from_streams, bank_to_stream, EvmStateVersion, SnapshotStorage, SnapshotStorages,
     },
     shared_buffer_reader::{SharedBuffer, SharedBufferReader},
     snapshot_package::{
         AccountsPackage, AccountsPackagePre, AccountsPackageSendError, AccountsPackageSender,
+        SnapshotType,
     },
 };
 use bincode::{config::Options, serialize_into};
 use bzip2::bufread::BzDecoder;
-use evm_state::AccountProvider;
+use evm_state::{
+    storage::incremental::{ReachableSet, TrieDiff},
+    AccountProvider,
+};
 use flate2::read::GzDecoder;
 use log::*;
 use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
@@ -43,7 +47,10 @@ pub const TAR_ACCOUNTS_DIR: &str = "accounts";
 pub const TAR_VERSION_FILE: &str = "version";
 
 pub const MAX_SNAPSHOTS: usize = 8; // Save some snapshots but not too many
+pub const MAX_INCREMENTAL_SNAPSHOT_ARCHIVES: usize = 2;
 const EVM_STATE_DIR: &str = "evm-state";
+const EVM_STATE_DIFF_FILE: &str = "evm-state-diff";
+const EVM_STATE_BASE_PREFIX: &str = "evm-state-base-";
 const MAX_SNAPSHOT_DATA_FILE_SIZE: u64 = 32 * 1024 * 1024 * 1024; // 32 GiB
 const VERSION_STRING_V1_4_0: &str = "1.4.0";
 const VERSION_STRING_V1_5_0: &str = "1.5.0";
@@ -113,6 +120,7 @@ pub struct SlotSnapshotPaths {
     pub slot: Slot,
     pub snapshot_file_path: PathBuf,
     pub evm_state_backup_path: PathBuf,
+    pub evm_state_diff_path: PathBuf,
 }
 
 #[derive(Error, Debug)]
@@ -313,26 +321,54 @@ pub fn archive_snapshot_package(snapshot_package: &AccountsPackage) -> Result<()
     let file_ext = get_archive_ext(snapshot_package.archive_format);
 
     //
-    // Create evm state backup
+    // Create evm state backup, or diff since the full snapshot for incremental ones
     //
     {
         let slot = snapshot_package.slot;
         let snapshot_tmpdir = snapshot_package.snapshot_links.path();
         let snapshot_hardlink_dir = snapshot_tmpdir.join(slot.to_string());
 
-        let evm_target = snapshot_hardlink_dir.join(EVM_STATE_DIR);
-        std::fs::create_dir_all(&evm_target)?;
-        let mut evm_state_backup = Measure::start("evm-state-backup-ms");
-        let backup_path = snapshot_package
-            .evm_db
-            .backup(evm_target.into())
-            .map_err(|e| SnapshotError::EvmStateError(e.into()))?;
-        evm_state_backup.stop();
-        inc_new_counter_info!("evm-state-backup-ms", evm_state_backup.as_ms() as usize);
-        info!(
-            "EVM state backup {} for slot {} at {:?}",
-            evm_state_backup, slot, backup_path
-        );
+        if let SnapshotType::Incremental(base_slot) = snapshot_package.snapshot_type {
+            let mut evm_state_diff = Measure::start("evm-state-diff-ms");
+            let base = load_evm_state_base(tar_dir, base_slot)?;
+            let diff =
+                TrieDiff::collect(&snapshot_package.evm_db, snapshot_package.evm_root, &base)
+                    .map_err(SnapshotError::EvmStateError)?;
+            let diff_path = snapshot_hardlink_dir.join(EVM_STATE_DIFF_FILE);
+            serialize_snapshot_data_file(&diff_path, |stream| {
+                serialize_into(stream, &diff)?;
+                Ok(())
+            })?;
+            evm_state_diff.stop();
+            inc_new_counter_info!("evm-state-diff-ms", evm_state_diff.as_ms() as usize);
+            info!(
+                "EVM state diff {} for slot {} (base slot {}) at {:?}",
+                evm_state_diff, slot, base_slot, diff_path
+            );
+        } else {
+            let evm_target = snapshot_hardlink_dir.join(EVM_STATE_DIR);
+            std::fs::create_dir_all(&evm_target)?;
+            let mut evm_state_backup = Measure::start("evm-state-backup-ms");
+            let backup_path = snapshot_package
+                .evm_db
+                .backup(evm_target.into())
+                .map_err(|e| SnapshotError::EvmStateError(e.into()))?;
+            evm_state_backup.stop();
+            inc_new_counter_info!("evm-state-backup-ms", evm_state_backup.as_ms() as usize);
+            info!(
+                "EVM state backup {} for slot {} at {:?}",
+                evm_state_backup, slot, backup_path
+            );
+
+            if snapshot_package.snapshot_type == SnapshotType::IncrementalBase {
+                store_evm_state_base(
+                    tar_dir,
+                    slot,
+                    &snapshot_package.evm_db,
+                    snapshot_package.evm_root,
+                )?;
+            }
+        }
     }
 
     // Tar the staging directory into the archive at `archive_path`
@@ -402,7 +438,15 @@ pub fn archive_snapshot_package(snapshot_package: &AccountsPackage) -> Result<()
     let metadata = fs::metadata(&archive_path)?;
     fs::rename(&archive_path, &snapshot_package.tar_output_file)?;
 
-    purge_old_snapshot_archives(snapshot_package.tar_output_file.parent().unwrap());
+    if snapshot_package
+        .snapshot_type
+        .incremental_base_slot()
+        .is_some()
+    {
+        purge_old_incremental_snapshot_archives(tar_dir);
+    } else {
+        purge_old_snapshot_archives(tar_dir);
+    }
 
     timer.stop();
     info!(
@@ -442,6 +486,7 @@ where
                         slot,
                         snapshot_file_path: snapshot_path.join(get_snapshot_file_name(slot)),
                         evm_state_backup_path: snapshot_path.join(EVM_STATE_DIR),
+                        evm_state_diff_path: snapshot_path.join(EVM_STATE_DIFF_FILE),
                     }
                 })
                 .collect::<Vec<SlotSnapshotPaths>>();
@@ -555,6 +600,7 @@ pub fn add_snapshot<P: AsRef<Path>>(
     // the bank snapshot is stored as snapshot_path/slot/slot
     let snapshot_bank_file_path = slot_snapshot_dir.join(get_snapshot_file_name(slot));
     let evm_state_backup_path = slot_snapshot_dir.join(EVM_STATE_DIR);
+    let evm_state_diff_path = slot_snapshot_dir.join(EVM_STATE_DIFF_FILE);
 
     info!(
         "Creating snapshot for slot {}, path: {:?}",
@@ -592,6 +638,7 @@ pub fn add_snapshot<P: AsRef<Path>>(
         slot,
         snapshot_file_path: snapshot_bank_file_path,
         evm_state_backup_path,
+        evm_state_diff_path,
     })
 }
 
@@ -643,32 +690,95 @@ pub fn bank_from_archive<P: AsRef<Path>>(
     accounts_db_caching_enabled: bool,
     verify_evm_state: bool,
     evm_archive: Option<evm_state::Storage>,
+) -> Result<Bank> {
+    bank_from_snapshot_archives(
+        evm_state_path,
+        account_paths,
+        frozen_account_pubkeys,
+        snapshot_path,
+        snapshot_tar.as_ref(),
+        archive_format,
+        None,
+        genesis_config,
+        debug_keys,
+        additional_builtins,
+        account_indexes,
+        accounts_db_caching_enabled,
+        verify_evm_state,
+        evm_archive,
+    )
+}
+
+/// Rebuild bank from the full snapshot archive,
+/// optionally with incremental snapshot archive on top of it.
+#[allow(clippy::too_many_arguments)]
+pub fn bank_from_snapshot_archives(
+    evm_state_path: &Path,
+    account_paths: &[PathBuf],
+    frozen_account_pubkeys: &[Pubkey],
+    snapshot_path: &Path,
+    snapshot_tar: &Path,
+    archive_format: ArchiveFormat,
+    incremental_snapshot_tar: Option<(&Path, ArchiveFormat)>,
+    genesis_config: &GenesisConfig,
+    debug_keys: Option<Arc<HashSet<Pubkey>>>,
+    additional_builtins: Option<&Builtins>,
+    account_indexes: AccountSecondaryIndexes,
+    accounts_db_caching_enabled: bool,
+    verify_evm_state: bool,
+    evm_archive: Option<evm_state::Storage>,
 ) -> Result<Bank> {
     // Untar the snapshot into a temporary directory
     let unpack_dir = tempfile::Builder::new()
         .prefix(TMP_SNAPSHOT_PREFIX)
         .tempdir_in(snapshot_path)?;
 
-    let unpacked_append_vec_map = untar_snapshot_in(
+    let mut unpacked_append_vec_map = untar_snapshot_in(
         &snapshot_tar,
         unpack_dir.as_ref(),
         account_paths,
         archive_format,
         PARALLEL_UNTAR_READERS_DEFAULT,
     )?;
+    let snapshot_version = read_snapshot_version(unpack_dir.as_ref())?;
+
+    let incremental_unpack_dir = match incremental_snapshot_tar {
+        Some((incremental_snapshot_tar, incremental_archive_format)) => {
+            let incremental_unpack_dir = tempfile::Builder::new()
+                .prefix(TMP_SNAPSHOT_PREFIX)
+                .tempdir_in(snapshot_path)?;
+            unpacked_append_vec_map.extend(untar_snapshot_in(
+                incremental_snapshot_tar,
+                incremental_unpack_dir.as_ref(),
+                account_paths,
+                incremental_archive_format,
+                PARALLEL_UNTAR_READERS_DEFAULT,
+            )?);
+            let incremental_snapshot_version =
+                read_snapshot_version(incremental_unpack_dir.as_ref())?;
+            if incremental_snapshot_version != snapshot_version {
+                return Err(get_io_error(&format!(
+                    "incremental snapshot version {} differs from full snapshot version {}",
+                    incremental_snapshot_version, snapshot_version
+                )));
+            }
+            Some(incremental_unpack_dir)
+        }
+        None => None,
+    };
 
     let mut measure = Measure::start("bank rebuild from snapshot");
     let unpacked_snapshots_dir = unpack_dir.as_ref().join(TAR_SNAPSHOTS_DIR);
-    let unpacked_version_file = unpack_dir.as_ref().join(TAR_VERSION_FILE);
-
-    let mut snapshot_version = String::new();
-    File::open(unpacked_version_file).and_then(|mut f| f.read_to_string(&mut snapshot_version))?;
+    let incremental_unpacked_snapshots_dir = incremental_unpack_dir
+        .as_ref()
+        .map(|incremental_unpack_dir| incremental_unpack_dir.path().join(TAR_SNAPSHOTS_DIR));
 
     let bank = rebuild_bank_from_snapshots(
-        snapshot_version.trim(),
+        &snapshot_version,
         evm_state_path,
         frozen_account_pubkeys,
         &unpacked_snapshots_dir,
+        incremental_unpacked_snapshots_dir.as_deref(),
         account_paths,
         unpacked_append_vec_map,
         genesis_config,
@@ -680,6 +790,21 @@ pub fn bank_from_archive<P: AsRef<Path>>(
         evm_archive,
     )?;
 
+    // Later incremental snapshots build on the same full snapshot, so the clean run by
+    // the verification below has to keep the zero-lamport accounts stored after it
+    if incremental_snapshot_tar.is_some() {
+        if let Some((full_snapshot_slot, ..)) = snapshot_tar
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+            .and_then(snapshot_hash_of)
+        {
+            bank.rc
+                .accounts
+                .accounts_db
+                .set_last_full_snapshot_slot(full_snapshot_slot);
+        }
+    }
+
     if !bank.verify_snapshot_bank() {
         panic!("Snapshot bank for slot {} failed to verify", bank.slot());
     }
@@ -689,6 +814,13 @@ pub fn bank_from_archive<P: AsRef<Path>>(
     Ok(bank)
 }
 
+fn read_snapshot_version(unpack_dir: &Path) -> Result<String> {
+    let unpacked_version_file = unpack_dir.join(TAR_VERSION_FILE);
+    let mut snapshot_version = String::new();
+    File::open(unpacked_version_file).and_then(|mut f| f.read_to_string(&mut snapshot_version))?;
+    Ok(snapshot_version.trim().to_string())
+}
+
 pub fn get_snapshot_archive_path(
     snapshot_output_dir: PathBuf,
     snapshot_hash: &(Slot, Hash),
@@ -702,6 +834,21 @@ pub fn get_snapshot_archive_path(
     ))
 }
 
+pub fn get_incremental_snapshot_archive_path(
+    snapshot_output_dir: PathBuf,
+    base_slot: Slot,
+    snapshot_hash: &(Slot, Hash),
+    archive_format: ArchiveFormat,
+) -> PathBuf {
+    snapshot_output_dir.join(format!(
+        "incremental-snapshot-{}-{}-{}{}",
+        base_slot,
+        snapshot_hash.0,
+        snapshot_hash.1,
+        get_archive_ext(archive_format),
+    ))
+}
+
 fn archive_format_from_str(archive_format: &str) -> Option<ArchiveFormat> {
     match archive_format {
         "tar.bz2" => Some(ArchiveFormat::TarBzip2),
@@ -732,6 +879,34 @@ fn snapshot_hash_of(archive_filename: &str) -> Option<(Slot, Hash, ArchiveFormat
     None
 }
 
+pub fn incremental_snapshot_hash_of(
+    archive_filename: &str,
+) -> Option<(Slot, Slot, Hash, ArchiveFormat)> {
+    let incremental_snapshot_filename_regex = Regex::new(
+        r"^incremental-snapshot-(\d+)-(\d+)-([[:alnum:]]+)\.(tar|tar\.bz2|tar\.zst|tar\.gz)$",
+    )
+    .unwrap();
+
+    if let Some(captures) = incremental_snapshot_filename_regex.captures(archive_filename) {
+        let base_slot_str = captures.get(1).unwrap().as_str();
+        let slot_str = captures.get(2).unwrap().as_str();
+        let hash_str = captures.get(3).unwrap().as_str();
+        let ext = captures.get(4).unwrap().as_str();
+
+        if let (Ok(base_slot), Ok(slot), Ok(hash), Some(archive_format)) = (
+            base_slot_str.parse::<Slot>(),
+            slot_str.parse::<Slot>(),
+            hash_str.parse::<Hash>(),
+            archive_format_from_str(ext),
+        ) {
+            if base_slot < slot {
+                return Some((base_slot, slot, hash, archive_format));
+            }
+        }
+    }
+    None
+}
+
 pub fn get_snapshot_archives<P: AsRef<Path>>(
     snapshot_output_dir: P,
 ) -> Vec<(PathBuf, (Slot, Hash, ArchiveFormat))> {
@@ -770,14 +945,133 @@ pub fn get_highest_snapshot_archive_path<P: AsRef<Path>>(
     archives.into_iter().next()
 }
 
+pub fn get_incremental_snapshot_archives<P: AsRef<Path>>(
+    snapshot_output_dir: P,
+) -> Vec<(PathBuf, (Slot, Slot, Hash, ArchiveFormat))> {
+    match fs::read_dir(&snapshot_output_dir) {
+        Err(err) => {
+            info!("Unable to read snapshot directory: {}", err);
+            vec![]
+        }
+        Ok(files) => {
+            let mut archives: Vec<_> = files
+                .filter_map(|entry| {
+                    let path = entry.ok()?.path();
+                    if !path.is_file() {
+                        return None;
+                    }
+                    let snapshot_hash = incremental_snapshot_hash_of(path.file_name()?.to_str()?)?;
+                    Some((path, snapshot_hash))
+                })
+                .collect();
+
+            archives.sort_by(|a, b| (b.1).1.cmp(&(a.1).1)); // reverse sort by slot
+            archives
+        }
+    }
+}
+
+/// Highest incremental snapshot, which is built on top of the full snapshot at `base_slot`.
+pub fn get_highest_incremental_snapshot_archive_path<P: AsRef<Path>>(
+    snapshot_output_dir: P,
+    base_slot: Slot,
+) -> Option<(PathBuf, (Slot, Slot, Hash, ArchiveFormat))> {
+    get_incremental_snapshot_archives(snapshot_output_dir)
+        .into_iter()
+        .find(|(_, (archive_base_slot, ..))| *archive_base_slot == base_slot)
+}
+
 pub fn purge_old_snapshot_archives<P: AsRef<Path>>(snapshot_output_dir: P) {
-    let mut archives = get_snapshot_archives(snapshot_output_dir);
+    let mut archives = get_snapshot_archives(&snapshot_output_dir);
     // Keep the oldest snapshot so we can always play the ledger from it.
     archives.pop();
     for old_archive in archives.into_iter().skip(2) {
         fs::remove_file(old_archive.0)
             .unwrap_or_else(|err| info!("Failed to remove old snapshot: {:}", err));
     }
+    purge_old_incremental_snapshot_archives(snapshot_output_dir);
+}
+
+/// Incremental snapshots are only kept for the highest full snapshot.
+pub fn purge_old_incremental_snapshot_archives<P: AsRef<Path>>(snapshot_output_dir: P) {
+    let highest_base_slot =
+        get_highest_snapshot_archive_path(&snapshot_output_dir).map(|(_, (slot, _, _))| slot);
+
+    let mut kept_archives = 0;
+    for (path, (base_slot, ..)) in get_incremental_snapshot_archives(&snapshot_output_dir) {
+        if Some(base_slot) == highest_base_slot && kept_archives < MAX_INCREMENTAL_SNAPSHOT_ARCHIVES
+        {
+            kept_archives += 1;
+            continue;
+        }
+        fs::remove_file(path)
+            .unwrap_or_else(|err| info!("Failed to remove old incremental snapshot: {:}", err));
+    }
+
+    for (path, slot) in get_evm_state_bases(&snapshot_output_dir) {
+        if Some(slot) != highest_base_slot {
+            fs::remove_file(path)
+                .unwrap_or_else(|err| info!("Failed to remove old evm-state base: {:}", err));
+        }
+    }
+}
+
+fn get_evm_state_base_path(snapshot_output_dir: &Path, slot: Slot) -> PathBuf {
+    snapshot_output_dir.join(format!("{}{}", EVM_STATE_BASE_PREFIX, slot))
+}
+
+fn get_evm_state_bases<P: AsRef<Path>>(snapshot_output_dir: P) -> Vec<(PathBuf, Slot)> {
+    fs::read_dir(&snapshot_output_dir)
+        .map(|files| {
+            files
+                .filter_map(|entry| {
+                    let path = entry.ok()?.path();
+                    let slot = path
+                        .file_name()?
+                        .to_str()?
+                        .strip_prefix(EVM_STATE_BASE_PREFIX)?
+                        .parse::<Slot>()
+                        .ok()?;
+                    Some((path, slot))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Remember evm-state nodes of the full snapshot, to build incremental snapshots on top of it.
+// Live storage can't be used for that, because base root may be already purged by gc.
+fn store_evm_state_base(
+    snapshot_output_dir: &Path,
+    slot: Slot,
+    evm_db: &evm_state::Storage,
+    evm_root: evm_state::H256,
+) -> Result<()> {
+    let mut measure = Measure::start("evm-state-base-ms");
+    let base = ReachableSet::collect(evm_db, evm_root).map_err(SnapshotError::EvmStateError)?;
+    serialize_snapshot_data_file(
+        &get_evm_state_base_path(snapshot_output_dir, slot),
+        |stream| {
+            serialize_into(stream, &base)?;
+            Ok(())
+        },
+    )?;
+    measure.stop();
+    inc_new_counter_info!("evm-state-base-ms", measure.as_ms() as usize);
+    Ok(())
+}
+
+fn load_evm_state_base(snapshot_output_dir: &Path, slot: Slot) -> Result<ReachableSet> {
+    deserialize_snapshot_data_file(
+        &get_evm_state_base_path(snapshot_output_dir, slot),
+        |stream| {
+            Ok(bincode::options()
+                .with_limit(MAX_SNAPSHOT_DATA_FILE_SIZE)
+                .with_fixint_encoding()
+                .allow_trailing_bytes()
+                .deserialize_from(stream)?)
+        },
+    )
 }
 
 // From testing, 4 seems to be a sweet spot for ranges of 60M-360M accounts and 16-64 cores. This may need to be tuned later.
@@ -863,6 +1157,7 @@ fn rebuild_bank_from_snapshots(
     evm_state_path: &Path,
     frozen_account_pubkeys: &[Pubkey],
     unpacked_snapshots_dir: &Path,
+    incremental_unpacked_snapshots_dir: Option<&Path>,
     account_paths: &[PathBuf],
     unpacked_append_vec_map: UnpackedAppendVecMap,
     genesis_config: &GenesisConfig,
@@ -882,42 +1177,68 @@ fn rebuild_bank_from_snapshots(
                 snapshot_version
             ))
         })?;
-    let mut snapshot_paths = get_snapshot_paths(&unpacked_snapshots_dir);
-    if snapshot_paths.len() > 1 {
-        return Err(get_io_error("invalid snapshot format"));
-    }
-    let root_paths = snapshot_paths
-        .pop()
-        .ok_or_else(|| get_io_error("No snapshots found in snapshots directory"))?;
+    let root_paths = get_unpacked_snapshot_paths(unpacked_snapshots_dir)?;
+    let incremental_root_paths = incremental_unpacked_snapshots_dir
+        .map(get_unpacked_snapshot_paths)
+        .transpose()?;
 
     info!(
         "Loading bank from {}",
         &root_paths.snapshot_file_path.display()
     );
-    let bank = deserialize_snapshot_data_file(&root_paths.snapshot_file_path, |mut stream| {
+    let bank = deserialize_snapshot_data_file(&root_paths.snapshot_file_path, |stream| {
         let evm_version = match snapshot_version_enum {
             SnapshotVersion::V1_4_0 => EvmStateVersion::V1_4_0,
             SnapshotVersion::V1_5_0 => EvmStateVersion::V1_5_0,
         };
-        Ok(bank_from_stream(
-            evm_state_path,
-            evm_version,
-            &mut stream,
-            account_paths,
-            unpacked_append_vec_map,
-            genesis_config,
-            frozen_account_pubkeys,
-            debug_keys,
-            additional_builtins,
-            account_indexes,
-            accounts_db_caching_enabled,
-            &root_paths.evm_state_backup_path,
-            !verify_evm_state,
-            evm_archive,
-        )?)
+        let load_bank = |incremental: Option<(&mut BufReader<File>, TrieDiff)>| -> Result<Bank> {
+            Ok(bank_from_streams(
+                evm_state_path,
+                evm_version,
+                stream,
+                incremental,
+                account_paths,
+                unpacked_append_vec_map,
+                genesis_config,
+                frozen_account_pubkeys,
+                debug_keys,
+                additional_builtins,
+                account_indexes,
+                accounts_db_caching_enabled,
+                &root_paths.evm_state_backup_path,
+                !verify_evm_state,
+                evm_archive,
+            )?)
+        };
+        match &incremental_root_paths {
+            Some(incremental_root_paths) => {
+                info!(
+                    "Applying incremental snapshot from {}",
+                    &incremental_root_paths.snapshot_file_path.display()
+                );
+                let evm_state_diff: TrieDiff = deserialize_snapshot_data_file(
+                    &incremental_root_paths.evm_state_diff_path,
+                    |stream| {
+                        Ok(bincode::options()
+                            .with_limit(MAX_SNAPSHOT_DATA_FILE_SIZE)
+                            .with_fixint_encoding()
+                            .allow_trailing_bytes()
+                            .deserialize_from(stream)?)
+                    },
+                )?;
+                deserialize_snapshot_data_file(
+                    &incremental_root_paths.snapshot_file_path,
+                    |incremental_stream| load_bank(Some((incremental_stream, evm_state_diff))),
+                )
+            }
+            None => load_bank(None),
+        }
     })?;
 
-    let status_cache_path = unpacked_snapshots_dir.join(SNAPSHOT_STATUS_CACHE_FILE_NAME);
+    // Status cache of the incremental snapshot is newer, and fully replaces full snapshot one.
+    let status_cache_path = incremental_unpacked_snapshots_dir
+        .unwrap_or(unpacked_snapshots_dir)
+        .join(SNAPSHOT_STATUS_CACHE_FILE_NAME);
     let slot_deltas = deserialize_snapshot_data_file(&status_cache_path, |stream| {
         info!(
             "Rebuilding status cache from {}",
@@ -937,6 +1258,16 @@ fn rebuild_bank_from_snapshots(
     Ok(bank)
 }
 
+fn get_unpacked_snapshot_paths(unpacked_snapshots_dir: &Path) -> Result<SlotSnapshotPaths> {
+    let mut snapshot_paths = get_snapshot_paths(&unpacked_snapshots_dir);
+    if snapshot_paths.len() > 1 {
+        return Err(get_io_error("invalid snapshot format"));
+    }
+    snapshot_paths
+        .pop()
+        .ok_or_else(|| get_io_error("No snapshots found in snapshots directory"))
+}
+
 fn get_snapshot_file_name(slot: Slot) -> String {
     slot.to_string()
 }
@@ -1006,8 +1337,17 @@ pub fn snapshot_bank(
     snapshot_version: SnapshotVersion,
     archive_format: &ArchiveFormat,
     hash_for_testing: Option<Hash>,
+    snapshot_type: SnapshotType,
 ) -> Result<()> {
-    let storages: Vec<_> = root_bank.get_snapshot_storages();
+    let mut storages: Vec<_> = root_bank.get_snapshot_storages();
+    if let Some(base_slot) = snapshot_type.incremental_base_slot() {
+        // Older storages are already part of the full snapshot
+        storages.retain(|slot_storages| {
+            slot_storages
+                .first()
+                .map_or(false, |storage| storage.slot() > base_slot)
+        });
+    }
     let mut add_snapshot_time = Measure::start("add-snapshot-ms");
     add_snapshot(snapshot_path, &root_bank, &storages, snapshot_version)?;
     add_snapshot_time.stop();
@@ -1019,7 +1359,7 @@ pub fn snapshot_bank(
         .last()
         .expect("no snapshots found in config snapshot_path");
 
-    let package = package_snapshot(
+    let mut package = package_snapshot(
         root_bank,
         latest_slot_snapshot_paths,
         snapshot_path,
@@ -1030,6 +1370,7 @@ pub fn snapshot_bank(
         snapshot_version,
         hash_for_testing,
     )?;
+    package.snapshot_type = snapshot_type;
 
     accounts_package_sender.send(package).map_err(Box::new)?;
 
@@ -1085,7 +1426,14 @@ pub fn process_accounts_package_pre(
     let mut time = Measure::start("hash");
 
     let hash = accounts_package.hash; // temporarily remaining here
-    if let Some(expected_hash) = accounts_package.hash_for_testing {
+                                      // Incremental package has only part of storages, so hash can't be recalculated from them
+    let hash_for_testing = accounts_package.hash_for_testing.filter(|_| {
+        accounts_package
+            .snapshot_type
+            .incremental_base_slot()
+            .is_none()
+    });
+    if let Some(expected_hash) = hash_for_testing {
         let (hash, lamports) = AccountsDb::calculate_accounts_hash_without_index(
             &accounts_package.storages,
             thread_pool,
@@ -1102,13 +1450,21 @@ pub fn process_accounts_package_pre(
         ("calculate_hash", time.as_us(), i64),
     );
 
-    let tar_output_file = get_snapshot_archive_path(
-        accounts_package.snapshot_output_dir,
-        &(accounts_package.slot, hash),
-        accounts_package.archive_format,
-    );
+    let tar_output_file = match accounts_package.snapshot_type.incremental_base_slot() {
+        Some(base_slot) => get_incremental_snapshot_archive_path(
+            accounts_package.snapshot_output_dir,
+            base_slot,
+            &(accounts_package.slot, hash),
+            accounts_package.archive_format,
+        ),
+        None => get_snapshot_archive_path(
+            accounts_package.snapshot_output_dir,
+            &(accounts_package.slot, hash),
+            accounts_package.archive_format,
+        ),
+    };
 
-    AccountsPackage::new(
+    let mut package = AccountsPackage::new(
         accounts_package.slot,
         accounts_package.block_height,
         accounts_package.slot_deltas,
@@ -1121,7 +1477,9 @@ pub fn process_accounts_package_pre(
         accounts_package.evm_root,
         accounts_package.evm_db,
         accounts_package.bank,
-    )
+    );
+    package.snapshot_type = accounts_package.snapshot_type;
+    package
 }
 
 #[cfg(test)]
@@ -1253,4 +1611,87 @@ mod tests {
 
         assert!(snapshot_hash_of("invalid").is_none());
     }
+
+    #[test]
+    fn test_incremental_snapshot_hash_of() {
+        assert_eq!(
+            incremental_snapshot_hash_of(&format!(
+                "incremental-snapshot-42-50-{}.tar.zst",
+                Hash::default()
+            )),
+            Some((42, 50, Hash::default(), ArchiveFormat::TarZstd))
+        );
+        assert_eq!(
+            incremental_snapshot_hash_of(&format!(
+                "incremental-snapshot-42-43-{}.tar",
+                Hash::default()
+            )),
+            Some((42, 43, Hash::default(), ArchiveFormat::Tar))
+        );
+
+        // full and incremental snapshots names don't intersect
+        assert!(
+            incremental_snapshot_hash_of(&format!("snapshot-42-{}.tar", Hash::default())).is_none()
+        );
+        assert!(snapshot_hash_of(&format!(
+            "incremental-snapshot-42-50-{}.tar",
+            Hash::default()
+        ))
+        .is_none());
+
+        // incremental snapshot should be newer than its base
+        assert!(incremental_snapshot_hash_of(&format!(
+            "incremental-snapshot-50-42-{}.tar",
+            Hash::default()
+        ))
+        .is_none());
+        assert!(incremental_snapshot_hash_of("invalid").is_none());
+    }
+
+    #[test]
+    fn test_purge_old_incremental_snapshot_archives() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let output_dir = temp_dir.path();
+        let touch = |path: PathBuf| {
+            File::create(&path).unwrap();
+        };
+
+        for slot in &[100, 200] {
+            touch(get_snapshot_archive_path(
+                output_dir.to_path_buf(),
+                &(*slot, Hash::default()),
+                ArchiveFormat::Tar,
+            ));
+            touch(get_evm_state_base_path(output_dir, *slot));
+        }
+        for (base_slot, slot) in &[(100, 150), (200, 210), (200, 220), (200, 230)] {
+            touch(get_incremental_snapshot_archive_path(
+                output_dir.to_path_buf(),
+                *base_slot,
+                &(*slot, Hash::default()),
+                ArchiveFormat::Tar,
+            ));
+        }
+
+        purge_old_incremental_snapshot_archives(output_dir);
+
+        let remaining: Vec<_> = get_incremental_snapshot_archives(output_dir)
+            .into_iter()
+            .map(|(_, (base_slot, slot, ..))| (base_slot, slot))
+            .collect();
+        assert_eq!(remaining, vec![(200, 230), (200, 220)]);
+        let evm_state_bases: Vec<_> = get_evm_state_bases(output_dir)
+            .into_iter()
+            .map(|(_, slot)| slot)
+            .collect();
+        assert_eq!(evm_state_bases, vec![200]);
+        assert_eq!(get_snapshot_archives(output_dir).len(), 2);
+
+        assert_eq!(
+            get_highest_incremental_snapshot_archive_path(output_dir, 200)
+                .map(|(_, (_, slot, ..))| slot),
+            Some(230)
+        );
+        assert!(get_highest_incremental_snapshot_archive_path(output_dir, 100).is_none());
+    }
 }
@@ -1,8 +1,12 @@
-use crate::{clock::Epoch, program_error::ProgramError, pubkey::Pubkey};
+use crate::{
+    clock::Epoch, entrypoint::MAX_PERMITTED_DATA_INCREASE, program_error::ProgramError,
+    pubkey::Pubkey,
+};
 use std::{
     cell::{Ref, RefCell, RefMut},
     cmp, fmt,
     rc::Rc,
+    slice::from_raw_parts_mut,
 };
 
 /// Account information
@@ -146,6 +150,58 @@ impl<'a> AccountInfo<'a> {
         }
         bincode::serialize_into(&mut self.data.borrow_mut()[..], state)
     }
+
+    /// Return the account's data length as it was serialized for the current
+    /// program invocation
+    ///
+    /// # Safety
+    ///
+    /// Only valid for an `AccountInfo` produced by `entrypoint::deserialize`,
+    /// which stores the original length in the 4 bytes preceding the key
+    pub unsafe fn original_data_len(&self) -> usize {
+        let key_ptr = self.key as *const _ as *const u8;
+        *(key_ptr.offset(-4) as *const u32) as usize
+    }
+
+    /// Resize the account's data, optionally zero-initializing any new space
+    ///
+    /// The data may grow by at most `MAX_PERMITTED_DATA_INCREASE` bytes over
+    /// its length at the start of the invocation.  The new space is already
+    /// zeroed on entry, so `zero_init` only matters when shrinking and then
+    /// growing again within the same invocation.
+    ///
+    /// Only supported for accounts passed to a program through the
+    /// entrypoint of the latest BPF loader.  Whether the resulting length is
+    /// rent-exempt is checked at the end of the transaction.
+    ///
+    /// Accounts passed to a cross-program invocation may only be resized by
+    /// the callee if they are empty, as `SystemProgram::CreateAccount` does.
+    /// Reallocating any other account in an inner instruction fails the
+    /// invocation with `InvalidRealloc`.
+    pub fn realloc(&self, new_len: usize, zero_init: bool) -> Result<(), ProgramError> {
+        let mut data = self.try_borrow_mut_data()?;
+        let old_len = data.len();
+        if new_len == old_len {
+            return Ok(());
+        }
+        let original_data_len = unsafe { self.original_data_len() };
+        if new_len.saturating_sub(original_data_len) > MAX_PERMITTED_DATA_INCREASE {
+            return Err(ProgramError::InvalidRealloc);
+        }
+
+        let data_ptr = data.as_mut_ptr();
+        unsafe {
+            // First update the length in the serialized input so the runtime
+            // picks it up when deserializing
+            *(data_ptr.offset(-8) as *mut u64) = new_len as u64;
+            // Then point the local slice at the new length
+            *data = from_raw_parts_mut(data_ptr, new_len);
+        }
+        if zero_init && new_len > old_len {
+            data[old_len..].fill(0);
+        }
+        Ok(())
+    }
 }
 
 /// Constructs an `AccountInfo` from self, used in conversion implementations.
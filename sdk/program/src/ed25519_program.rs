@@ -0,0 +1,92 @@
+//! The ed25519 native program, which verifies ed25519 signatures over arbitrary messages
+//! before a transaction is executed.
+//!
+//! Programs can check that a signature was verified by loading the ed25519 instruction through
+//! the instructions sysvar and parsing it with [`parse_signed_messages`].
+
+use crate::{program_error::ProgramError, pubkey::Pubkey};
+use serde_derive::{Deserialize, Serialize};
+
+crate::declare_id!("Ed25519SigVerify111111111111111111111111111");
+
+pub const PUBKEY_SERIALIZED_SIZE: usize = 32;
+pub const SIGNATURE_SERIALIZED_SIZE: usize = 64;
+pub const SIGNATURE_OFFSETS_SERIALIZED_SIZE: usize = 14;
+/// Offsets start after the signature count and a padding byte
+pub const SIGNATURE_OFFSETS_START: usize = 2;
+pub const DATA_START: usize = SIGNATURE_OFFSETS_SERIALIZED_SIZE + SIGNATURE_OFFSETS_START;
+/// Instruction index referring to the ed25519 instruction itself
+pub const CURRENT_INSTRUCTION_INDEX: u16 = u16::MAX;
+
+#[derive(Default, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ed25519SignatureOffsets {
+    pub signature_offset: u16, // offset to ed25519 signature of 64 bytes
+    pub signature_instruction_index: u16, // instruction index to find signature
+    pub public_key_offset: u16, // offset to public key of 32 bytes
+    pub public_key_instruction_index: u16, // instruction index to find public key
+    pub message_data_offset: u16, // offset to start of message data
+    pub message_data_size: u16, // size of message data
+    pub message_instruction_index: u16, // index of instruction data to get message data
+}
+
+/// Parse the signature offsets of an ed25519 instruction, which must verify at least one
+/// signature
+pub fn get_signature_offsets(data: &[u8]) -> Result<Vec<Ed25519SignatureOffsets>, ProgramError> {
+    let num_signatures = usize::from(*data.first().ok_or(ProgramError::InvalidInstructionData)?);
+    if num_signatures == 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let expected_data_size = num_signatures
+        .saturating_mul(SIGNATURE_OFFSETS_SERIALIZED_SIZE)
+        .saturating_add(SIGNATURE_OFFSETS_START);
+    if data.len() < expected_data_size {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    data[SIGNATURE_OFFSETS_START..expected_data_size]
+        .chunks(SIGNATURE_OFFSETS_SERIALIZED_SIZE)
+        .map(|offsets| {
+            bincode::deserialize(offsets).map_err(|_| ProgramError::InvalidInstructionData)
+        })
+        .collect()
+}
+
+/// Public key and message of a signature verified by an ed25519 instruction
+#[derive(Debug, PartialEq, Eq)]
+pub struct SignedMessage<'a> {
+    pub pubkey: Pubkey,
+    pub message: &'a [u8],
+}
+
+/// Parse the signed messages of an ed25519 instruction, as loaded from the instructions
+/// sysvar. Only signatures whose public key and message are stored in the instruction
+/// itself are supported, others fail with `InvalidInstructionData`.
+pub fn parse_signed_messages(data: &[u8]) -> Result<Vec<SignedMessage>, ProgramError> {
+    get_signature_offsets(data)?
+        .iter()
+        .map(|offsets| {
+            if offsets.public_key_instruction_index != CURRENT_INSTRUCTION_INDEX
+                || offsets.message_instruction_index != CURRENT_INSTRUCTION_INDEX
+            {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let pubkey = get_data_slice(
+                data,
+                offsets.public_key_offset,
+                PUBKEY_SERIALIZED_SIZE as u16,
+            )?;
+            let message =
+                get_data_slice(data, offsets.message_data_offset, offsets.message_data_size)?;
+            Ok(SignedMessage {
+                pubkey: Pubkey::new(pubkey),
+                message,
+            })
+        })
+        .collect()
+}
+
+fn get_data_slice(data: &[u8], offset: u16, size: u16) -> Result<&[u8], ProgramError> {
+    let start = usize::from(offset);
+    let end = start.saturating_add(usize::from(size));
+    data.get(start..end)
+        .ok_or(ProgramError::InvalidInstructionData)
+}
@@ -15,6 +15,11 @@ use itertools::Itertools;
 use lazy_static::lazy_static;
 use std::{convert::TryFrom, str::FromStr};
 
+pub mod v0;
+mod versions;
+
+pub use versions::*;
+
 lazy_static! {
     // Copied keys over since direct references create cyclical dependency.
     static ref BUILTIN_PROGRAMS_KEYS: [Pubkey; 10] = {
@@ -0,0 +1,499 @@
+//! Version 0 of the transaction message format.
+//!
+//! In addition to the accounts listed inline, a v0 message can reference accounts stored in
+//! on-chain address lookup tables. Keys loaded from lookup tables are appended after the static
+//! `account_keys`, writable ones first, and can't be signers or invoked programs.
+
+use crate::{
+    hash::Hash,
+    instruction::CompiledInstruction,
+    message::{Message as LegacyMessage, MessageHeader},
+    pubkey::Pubkey,
+    sanitize::{Sanitize, SanitizeError},
+    short_vec,
+};
+
+/// Maximum number of accounts a message can reference, including accounts loaded from
+/// lookup tables. Instructions refer to accounts by `u8` index.
+pub const MAX_ACCOUNT_KEYS: usize = u8::MAX as usize + 1;
+
+/// Address table lookup, used to load additional accounts for a transaction.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageAddressTableLookup {
+    /// Address lookup table account key
+    pub account_key: Pubkey,
+    /// List of indexes used to load writable account addresses
+    #[serde(with = "short_vec")]
+    pub writable_indexes: Vec<u8>,
+    /// List of indexes used to load readonly account addresses
+    #[serde(with = "short_vec")]
+    pub readonly_indexes: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    /// The message header, identifying signed and read-only `account_keys`.
+    /// Header values only describe static `account_keys`, loaded keys are not counted.
+    pub header: MessageHeader,
+
+    /// List of accounts loaded by this transaction, without the ones loaded from lookup tables
+    #[serde(with = "short_vec")]
+    pub account_keys: Vec<Pubkey>,
+
+    /// The id of a recent ledger entry.
+    pub recent_blockhash: Hash,
+
+    /// Instructions that invoke a designated program, are executed in sequence,
+    /// and committed in one atomic transaction if all succeed.
+    ///
+    /// Account indexes address the static `account_keys` followed by the loaded
+    /// writable and then the loaded readonly keys.
+    #[serde(with = "short_vec")]
+    pub instructions: Vec<CompiledInstruction>,
+
+    /// List of address table lookups used to load additional accounts
+    /// for this transaction.
+    #[serde(with = "short_vec")]
+    pub address_table_lookups: Vec<MessageAddressTableLookup>,
+}
+
+impl Sanitize for Message {
+    fn sanitize(&self) -> Result<(), SanitizeError> {
+        let num_static_account_keys = self.account_keys.len();
+        // signing area and read-only non-signing area should not overlap
+        if usize::from(self.header.num_required_signatures)
+            .saturating_add(usize::from(self.header.num_readonly_unsigned_accounts))
+            > num_static_account_keys
+        {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+
+        // there should be at least 1 RW fee-payer account.
+        if self.header.num_readonly_signed_accounts >= self.header.num_required_signatures {
+            return Err(SanitizeError::InvalidValue);
+        }
+
+        let mut num_loaded_accounts: usize = 0;
+        for lookup in &self.address_table_lookups {
+            let num_lookup_indexes = lookup
+                .writable_indexes
+                .len()
+                .saturating_add(lookup.readonly_indexes.len());
+
+            // each lookup table must be used to load at least one account
+            if num_lookup_indexes == 0 {
+                return Err(SanitizeError::InvalidValue);
+            }
+
+            num_loaded_accounts = num_loaded_accounts.saturating_add(num_lookup_indexes);
+        }
+
+        let num_account_keys = num_static_account_keys.saturating_add(num_loaded_accounts);
+        if num_account_keys > MAX_ACCOUNT_KEYS {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+
+        for ci in &self.instructions {
+            // A program cannot be a payer, and can't be loaded from a lookup table.
+            if ci.program_id_index == 0
+                || usize::from(ci.program_id_index) >= num_static_account_keys
+            {
+                return Err(SanitizeError::IndexOutOfBounds);
+            }
+            for ai in &ci.accounts {
+                if usize::from(*ai) >= num_account_keys {
+                    return Err(SanitizeError::IndexOutOfBounds);
+                }
+            }
+        }
+        self.account_keys.sanitize()?;
+        self.recent_blockhash.sanitize()?;
+        self.instructions.sanitize()?;
+        Ok(())
+    }
+}
+
+impl Message {
+    /// Serialize this message with a version #0 prefix, the result is the data to be signed.
+    pub fn serialize(&self) -> Vec<u8> {
+        bincode::serialize(&(super::MESSAGE_VERSION_PREFIX, self)).unwrap()
+    }
+
+    /// Returns true if any static account key is the program id of an instruction.
+    pub fn is_key_called_as_program(&self, key_index: usize) -> bool {
+        self.instructions
+            .iter()
+            .any(|ix| usize::from(ix.program_id_index) == key_index)
+    }
+}
+
+/// Collection of addresses loaded from on-chain lookup tables, split
+/// by readonly and writable.
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoadedAddresses {
+    /// List of addresses for writable loaded accounts
+    pub writable: Vec<Pubkey>,
+    /// List of addresses for read-only loaded accounts
+    pub readonly: Vec<Pubkey>,
+}
+
+impl LoadedAddresses {
+    pub fn is_empty(&self) -> bool {
+        self.writable.is_empty() && self.readonly.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.writable.len().saturating_add(self.readonly.len())
+    }
+}
+
+impl std::iter::FromIterator<LoadedAddresses> for LoadedAddresses {
+    fn from_iter<T: IntoIterator<Item = LoadedAddresses>>(iter: T) -> Self {
+        let (writable, readonly): (Vec<Vec<Pubkey>>, Vec<Vec<Pubkey>>) = iter
+            .into_iter()
+            .map(|addresses| (addresses.writable, addresses.readonly))
+            .unzip();
+        LoadedAddresses {
+            writable: writable.into_iter().flatten().collect(),
+            readonly: readonly.into_iter().flatten().collect(),
+        }
+    }
+}
+
+/// A v0 message together with the addresses loaded from its lookup tables.
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct LoadedMessage {
+    pub message: Message,
+    pub loaded_addresses: LoadedAddresses,
+}
+
+impl LoadedMessage {
+    pub fn new(message: Message, loaded_addresses: LoadedAddresses) -> Self {
+        Self {
+            message,
+            loaded_addresses,
+        }
+    }
+
+    /// Returns all account keys in the order instructions refer to them:
+    /// static keys, then loaded writable keys, then loaded readonly keys.
+    pub fn account_keys(&self) -> impl Iterator<Item = &Pubkey> {
+        self.message
+            .account_keys
+            .iter()
+            .chain(self.loaded_addresses.writable.iter())
+            .chain(self.loaded_addresses.readonly.iter())
+    }
+
+    pub fn num_account_keys(&self) -> usize {
+        self.message
+            .account_keys
+            .len()
+            .saturating_add(self.loaded_addresses.len())
+    }
+
+    /// Returns true if the account at the specified index was requested to be writable.
+    pub fn is_writable_index(&self, key_index: usize) -> bool {
+        let header = &self.message.header;
+        let num_account_keys = self.message.account_keys.len();
+        let num_signed_accounts = usize::from(header.num_required_signatures);
+        if key_index >= num_account_keys {
+            let loaded_addresses_index = key_index.saturating_sub(num_account_keys);
+            loaded_addresses_index < self.loaded_addresses.writable.len()
+        } else if key_index >= num_signed_accounts {
+            let num_unsigned_accounts = num_account_keys.saturating_sub(num_signed_accounts);
+            let num_writable_unsigned_accounts = num_unsigned_accounts
+                .saturating_sub(usize::from(header.num_readonly_unsigned_accounts));
+            let unsigned_account_index = key_index.saturating_sub(num_signed_accounts);
+            unsigned_account_index < num_writable_unsigned_accounts
+        } else {
+            let num_writable_signed_accounts = num_signed_accounts
+                .saturating_sub(usize::from(header.num_readonly_signed_accounts));
+            key_index < num_writable_signed_accounts
+        }
+    }
+
+    /// Convert into a legacy message with every account key inline, which is equivalent to
+    /// this message for execution: account locks, fees and instruction accounts are the same.
+    ///
+    /// Legacy layout requires readonly unsigned keys to be last, so loaded writable keys are
+    /// placed after the static writable ones, and instruction indexes are updated.
+    pub fn to_legacy_message(&self) -> LegacyMessage {
+        let header = &self.message.header;
+        let static_keys = &self.message.account_keys;
+        let num_signed = usize::from(header.num_required_signatures);
+        let num_static_writable = static_keys
+            .len()
+            .saturating_sub(usize::from(header.num_readonly_unsigned_accounts));
+
+        // (old key index, key) in the legacy order
+        let signed_and_writable_keys = static_keys[..num_static_writable]
+            .iter()
+            .enumerate()
+            .map(|(index, key)| (index, key));
+        let loaded_writable_keys = self
+            .loaded_addresses
+            .writable
+            .iter()
+            .enumerate()
+            .map(|(index, key)| (static_keys.len().saturating_add(index), key));
+        let readonly_unsigned_keys = static_keys[num_static_writable..]
+            .iter()
+            .enumerate()
+            .map(|(index, key)| (num_static_writable.saturating_add(index), key));
+        let loaded_readonly_keys =
+            self.loaded_addresses
+                .readonly
+                .iter()
+                .enumerate()
+                .map(|(index, key)| {
+                    let offset = static_keys
+                        .len()
+                        .saturating_add(self.loaded_addresses.writable.len());
+                    (offset.saturating_add(index), key)
+                });
+
+        let mut new_index_of = vec![0u8; self.num_account_keys()];
+        let mut account_keys = Vec::with_capacity(self.num_account_keys());
+        for (old_index, key) in signed_and_writable_keys
+            .chain(loaded_writable_keys)
+            .chain(readonly_unsigned_keys)
+            .chain(loaded_readonly_keys)
+        {
+            new_index_of[old_index] = account_keys.len() as u8;
+            account_keys.push(*key);
+        }
+        debug_assert!(account_keys[..num_signed] == static_keys[..num_signed]);
+
+        let instructions = self
+            .message
+            .instructions
+            .iter()
+            .map(|ix| CompiledInstruction {
+                program_id_index: new_index_of[usize::from(ix.program_id_index)],
+                accounts: ix
+                    .accounts
+                    .iter()
+                    .map(|index| new_index_of[usize::from(*index)])
+                    .collect(),
+                data: ix.data.clone(),
+            })
+            .collect();
+
+        LegacyMessage {
+            header: MessageHeader {
+                num_required_signatures: header.num_required_signatures,
+                num_readonly_signed_accounts: header.num_readonly_signed_accounts,
+                num_readonly_unsigned_accounts: header
+                    .num_readonly_unsigned_accounts
+                    .saturating_add(self.loaded_addresses.readonly.len() as u8),
+            },
+            account_keys,
+            recent_blockhash: self.message.recent_blockhash,
+            instructions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simple_message() -> Message {
+        Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![1],
+            }],
+            ..Message::default()
+        }
+    }
+
+    #[test]
+    fn test_sanitize() {
+        assert!(simple_message().sanitize().is_ok());
+    }
+
+    #[test]
+    fn test_sanitize_with_instruction() {
+        assert!(Message {
+            instructions: vec![CompiledInstruction {
+                program_id_index: 1,
+                accounts: vec![0, 2, 3],
+                data: vec![]
+            }],
+            ..simple_message()
+        }
+        .sanitize()
+        .is_ok());
+    }
+
+    #[test]
+    fn test_sanitize_without_signer() {
+        assert!(Message {
+            header: MessageHeader::default(),
+            account_keys: vec![Pubkey::new_unique()],
+            ..Message::default()
+        }
+        .sanitize()
+        .is_err());
+    }
+
+    #[test]
+    fn test_sanitize_with_empty_table_lookup() {
+        assert!(Message {
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![],
+                readonly_indexes: vec![],
+            }],
+            ..simple_message()
+        }
+        .sanitize()
+        .is_err());
+    }
+
+    #[test]
+    fn test_sanitize_with_max_account_keys() {
+        assert!(Message {
+            account_keys: (0..=u8::MAX).map(|_| Pubkey::new_unique()).collect(),
+            address_table_lookups: vec![],
+            ..simple_message()
+        }
+        .sanitize()
+        .is_ok());
+
+        assert!(Message {
+            account_keys: (0..=u8::MAX).map(|_| Pubkey::new_unique()).collect(),
+            ..simple_message()
+        }
+        .sanitize()
+        .is_err());
+    }
+
+    #[test]
+    fn test_sanitize_with_loaded_program_id() {
+        assert!(Message {
+            instructions: vec![CompiledInstruction {
+                program_id_index: 2,
+                accounts: vec![],
+                data: vec![]
+            }],
+            ..simple_message()
+        }
+        .sanitize()
+        .is_err());
+    }
+
+    #[test]
+    fn test_sanitize_with_invalid_account_index() {
+        assert!(Message {
+            instructions: vec![CompiledInstruction {
+                program_id_index: 1,
+                accounts: vec![4],
+                data: vec![]
+            }],
+            ..simple_message()
+        }
+        .sanitize()
+        .is_err());
+    }
+
+    #[test]
+    fn test_serialize_has_version_prefix() {
+        let message = simple_message();
+        let serialized = message.serialize();
+        assert_eq!(serialized[0], super::super::MESSAGE_VERSION_PREFIX);
+        assert_eq!(&serialized[1..], &bincode::serialize(&message).unwrap()[..]);
+    }
+
+    fn loaded_message() -> LoadedMessage {
+        // keys: [payer, writable, program, loaded writable, loaded readonly]
+        let message = Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+            ],
+            instructions: vec![CompiledInstruction {
+                program_id_index: 2,
+                accounts: vec![0, 1, 3, 4],
+                data: vec![1, 2, 3],
+            }],
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![1],
+            }],
+            ..Message::default()
+        };
+        LoadedMessage::new(
+            message,
+            LoadedAddresses {
+                writable: vec![Pubkey::new_unique()],
+                readonly: vec![Pubkey::new_unique()],
+            },
+        )
+    }
+
+    #[test]
+    fn test_is_writable_index() {
+        let message = loaded_message();
+        assert!(message.is_writable_index(0));
+        assert!(message.is_writable_index(1));
+        assert!(!message.is_writable_index(2));
+        assert!(message.is_writable_index(3));
+        assert!(!message.is_writable_index(4));
+    }
+
+    #[test]
+    fn test_to_legacy_message() {
+        let message = loaded_message();
+        let keys: Vec<Pubkey> = message.account_keys().copied().collect();
+        let legacy_message = message.to_legacy_message();
+
+        assert_eq!(
+            legacy_message.account_keys,
+            vec![keys[0], keys[1], keys[3], keys[2], keys[4]]
+        );
+        assert_eq!(legacy_message.header.num_required_signatures, 1);
+        assert_eq!(legacy_message.header.num_readonly_signed_accounts, 0);
+        assert_eq!(legacy_message.header.num_readonly_unsigned_accounts, 2);
+        assert_eq!(
+            legacy_message.instructions,
+            vec![CompiledInstruction {
+                program_id_index: 3,
+                accounts: vec![0, 1, 2, 4],
+                data: vec![1, 2, 3],
+            }]
+        );
+        assert!(legacy_message.sanitize().is_ok());
+
+        // Writability is preserved
+        for (index, key) in keys.iter().enumerate() {
+            let legacy_index = legacy_message
+                .account_keys
+                .iter()
+                .position(|legacy_key| legacy_key == key)
+                .unwrap();
+            assert_eq!(
+                message.is_writable_index(index),
+                legacy_message.is_writable(legacy_index, false)
+            );
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use crate::{
+    hash::Hash,
+    instruction::CompiledInstruction,
+    message::{v0, Message, MessageHeader},
+    pubkey::Pubkey,
+    sanitize::{Sanitize, SanitizeError},
+    short_vec,
+};
+use serde::{
+    de::{self, Deserializer, SeqAccess, Visitor},
+    ser::{SerializeTuple, Serializer},
+    Deserialize, Serialize,
+};
+use std::fmt;
+
+/// Bit mask that indicates whether a serialized message is versioned.
+///
+/// Legacy messages start with `num_required_signatures`, which can't exceed 127 because a
+/// transaction with that many signatures doesn't fit into a packet, so the highest bit of the
+/// first byte is free to mark the message version.
+pub const MESSAGE_VERSION_PREFIX: u8 = 0x80;
+
+/// Either a legacy message or a v0 message.
+///
+/// # Serialization
+///
+/// If the first bit is set, the remaining 7 bits will be used to determine
+/// which message version is serialized starting from version `0`. If the first
+/// is bit is not set, all bytes are used to encode the legacy `Message`
+/// format.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum VersionedMessage {
+    Legacy(Message),
+    V0(v0::Message),
+}
+
+impl VersionedMessage {
+    pub fn header(&self) -> &MessageHeader {
+        match self {
+            Self::Legacy(message) => &message.header,
+            Self::V0(message) => &message.header,
+        }
+    }
+
+    pub fn static_account_keys(&self) -> &[Pubkey] {
+        match self {
+            Self::Legacy(message) => &message.account_keys,
+            Self::V0(message) => &message.account_keys,
+        }
+    }
+
+    pub fn address_table_lookups(&self) -> Option<&[v0::MessageAddressTableLookup]> {
+        match self {
+            Self::Legacy(_) => None,
+            Self::V0(message) => Some(&message.address_table_lookups),
+        }
+    }
+
+    pub fn recent_blockhash(&self) -> &Hash {
+        match self {
+            Self::Legacy(message) => &message.recent_blockhash,
+            Self::V0(message) => &message.recent_blockhash,
+        }
+    }
+
+    pub fn set_recent_blockhash(&mut self, recent_blockhash: Hash) {
+        match self {
+            Self::Legacy(message) => message.recent_blockhash = recent_blockhash,
+            Self::V0(message) => message.recent_blockhash = recent_blockhash,
+        }
+    }
+
+    pub fn instructions(&self) -> &[CompiledInstruction] {
+        match self {
+            Self::Legacy(message) => &message.instructions,
+            Self::V0(message) => &message.instructions,
+        }
+    }
+
+    /// Program instructions can't be loaded from lookup tables, so this works for all versions.
+    pub fn is_key_called_as_program(&self, key_index: usize) -> bool {
+        self.instructions()
+            .iter()
+            .any(|ix| usize::from(ix.program_id_index) == key_index)
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap()
+    }
+
+    /// Compute the blake3 hash of this message
+    #[cfg(not(target_arch = "bpf"))]
+    pub fn hash(&self) -> Hash {
+        let message_bytes = self.serialize();
+        Message::hash_raw_message(&message_bytes)
+    }
+}
+
+impl Default for VersionedMessage {
+    fn default() -> Self {
+        Self::Legacy(Message::default())
+    }
+}
+
+impl Sanitize for VersionedMessage {
+    fn sanitize(&self) -> Result<(), SanitizeError> {
+        match self {
+            Self::Legacy(message) => message.sanitize(),
+            Self::V0(message) => message.sanitize(),
+        }
+    }
+}
+
+impl From<Message> for VersionedMessage {
+    fn from(message: Message) -> Self {
+        Self::Legacy(message)
+    }
+}
+
+impl From<v0::Message> for VersionedMessage {
+    fn from(message: v0::Message) -> Self {
+        Self::V0(message)
+    }
+}
+
+impl Serialize for VersionedMessage {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Legacy(message) => {
+                let mut seq = serializer.serialize_tuple(1)?;
+                seq.serialize_element(message)?;
+                seq.end()
+            }
+            Self::V0(message) => {
+                let mut seq = serializer.serialize_tuple(2)?;
+                seq.serialize_element(&MESSAGE_VERSION_PREFIX)?;
+                seq.serialize_element(message)?;
+                seq.end()
+            }
+        }
+    }
+}
+
+/// Legacy message without the first header byte, which was already consumed to detect
+/// the message version.
+#[derive(Deserialize)]
+struct RemainingLegacyMessage {
+    num_readonly_signed_accounts: u8,
+    num_readonly_unsigned_accounts: u8,
+    #[serde(with = "short_vec")]
+    account_keys: Vec<Pubkey>,
+    recent_blockhash: Hash,
+    #[serde(with = "short_vec")]
+    instructions: Vec<CompiledInstruction>,
+}
+
+struct MessageVisitor;
+
+impl<'de> Visitor<'de> for MessageVisitor {
+    type Value = VersionedMessage;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("message bytes")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<VersionedMessage, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let prefix: u8 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+
+        if prefix & MESSAGE_VERSION_PREFIX != 0 {
+            let version = prefix & !MESSAGE_VERSION_PREFIX;
+            match version {
+                0 => Ok(VersionedMessage::V0(
+                    seq.next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(1, &self))?,
+                )),
+                version => Err(de::Error::invalid_value(
+                    de::Unexpected::Unsigned(u64::from(version)),
+                    &"a valid message version",
+                )),
+            }
+        } else {
+            let message: RemainingLegacyMessage = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+            Ok(VersionedMessage::Legacy(Message {
+                header: MessageHeader {
+                    num_required_signatures: prefix,
+                    num_readonly_signed_accounts: message.num_readonly_signed_accounts,
+                    num_readonly_unsigned_accounts: message.num_readonly_unsigned_accounts,
+                },
+                account_keys: message.account_keys,
+                recent_blockhash: message.recent_blockhash,
+                instructions: message.instructions,
+            }))
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionedMessage {
+    fn deserialize<D>(deserializer: D) -> Result<VersionedMessage, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple(2, MessageVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::{AccountMeta, Instruction};
+
+    fn legacy_message() -> Message {
+        let program_id = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        Message::new(
+            &[Instruction::new_with_bincode(
+                program_id,
+                &0u8,
+                vec![
+                    AccountMeta::new(Pubkey::new_unique(), false),
+                    AccountMeta::new_readonly(Pubkey::new_unique(), true),
+                ],
+            )],
+            Some(&payer),
+        )
+    }
+
+    #[test]
+    fn test_legacy_message_serialization() {
+        let message = legacy_message();
+        let versioned_message = VersionedMessage::from(message.clone());
+
+        let bytes = bincode::serialize(&versioned_message).unwrap();
+        assert_eq!(bytes, message.serialize());
+        assert_eq!(
+            bincode::deserialize::<VersionedMessage>(&bytes).unwrap(),
+            versioned_message
+        );
+        assert_eq!(versioned_message.hash(), message.hash());
+    }
+
+    #[test]
+    fn test_v0_message_serialization() {
+        let legacy_message = legacy_message();
+        let message = v0::Message {
+            header: legacy_message.header.clone(),
+            account_keys: legacy_message.account_keys.clone(),
+            recent_blockhash: Hash::new_unique(),
+            instructions: legacy_message.instructions.clone(),
+            address_table_lookups: vec![v0::MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![1],
+                readonly_indexes: vec![0, 2],
+            }],
+        };
+        let versioned_message = VersionedMessage::from(message.clone());
+
+        let bytes = bincode::serialize(&versioned_message).unwrap();
+        assert_eq!(bytes, message.serialize());
+        assert_eq!(
+            bincode::deserialize::<VersionedMessage>(&bytes).unwrap(),
+            versioned_message
+        );
+    }
+
+    #[test]
+    fn test_unsupported_message_version() {
+        let mut bytes = v0::Message::default().serialize();
+        bytes[0] = MESSAGE_VERSION_PREFIX | 1;
+        assert!(bincode::deserialize::<VersionedMessage>(&bytes).is_err());
+    }
+}
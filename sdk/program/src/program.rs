@@ -1,4 +1,9 @@
-use crate::{account_info::AccountInfo, entrypoint::ProgramResult, instruction::Instruction};
+use crate::{
+    account_info::AccountInfo, entrypoint::ProgramResult, instruction::Instruction, pubkey::Pubkey,
+};
+
+/// Maximum size of the data a program can return with `set_return_data`
+pub const MAX_RETURN_DATA: usize = 1024;
 
 /// Invoke a cross-program instruction
 ///
@@ -64,3 +69,51 @@ extern "C" {
         signers_seeds_len: u64,
     ) -> u64;
 }
+
+/// Set the return data of the running program, which its caller can read with
+/// `get_return_data` after a cross-program invocation.
+///
+/// The return data is cleared before each instruction of a transaction, and is reported in
+/// the transaction's status metadata and simulation result. `data` must not be larger than
+/// `MAX_RETURN_DATA`; an empty `data` clears the return data.
+pub fn set_return_data(data: &[u8]) {
+    #[cfg(target_arch = "bpf")]
+    unsafe {
+        sol_set_return_data(data.as_ptr(), data.len() as u64)
+    };
+
+    #[cfg(not(target_arch = "bpf"))]
+    crate::program_stubs::sol_set_return_data(data)
+}
+
+/// Get the return data of the last program that set it, along with that program's id.
+///
+/// Returns `None` if no program has set return data during the current instruction.
+pub fn get_return_data() -> Option<(Pubkey, Vec<u8>)> {
+    #[cfg(target_arch = "bpf")]
+    {
+        use std::cmp::min;
+
+        let mut buf = [0u8; MAX_RETURN_DATA];
+        let mut program_id = Pubkey::default();
+
+        let size =
+            unsafe { sol_get_return_data(buf.as_mut_ptr(), buf.len() as u64, &mut program_id) };
+
+        if size == 0 {
+            None
+        } else {
+            let size = min(size as usize, MAX_RETURN_DATA);
+            Some((program_id, buf[..size].to_vec()))
+        }
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    crate::program_stubs::sol_get_return_data()
+}
+
+#[cfg(target_arch = "bpf")]
+extern "C" {
+    fn sol_set_return_data(data: *const u8, length: u64);
+    fn sol_get_return_data(data: *mut u8, length: u64, program_id: *mut Pubkey) -> u64;
+}
@@ -46,6 +46,8 @@ pub enum ProgramError {
     UnsupportedSysvar,
     #[error("Provided owner is not allowed")]
     IllegalOwner,
+    #[error("Account data reallocation was invalid")]
+    InvalidRealloc,
 }
 
 pub trait PrintProgramError {
@@ -84,6 +86,7 @@ impl PrintProgramError for ProgramError {
             Self::AccountNotRentExempt => msg!("Error: AccountNotRentExempt"),
             Self::UnsupportedSysvar => msg!("Error: UnsupportedSysvar"),
             Self::IllegalOwner => msg!("Error: IllegalOwner"),
+            Self::InvalidRealloc => msg!("Error: InvalidRealloc"),
         }
     }
 }
@@ -114,6 +117,7 @@ pub const BORSH_IO_ERROR: u64 = to_builtin!(15);
 pub const ACCOUNT_NOT_RENT_EXEMPT: u64 = to_builtin!(16);
 pub const UNSUPPORTED_SYSVAR: u64 = to_builtin!(17);
 pub const ILLEGAL_OWNER: u64 = to_builtin!(18);
+pub const INVALID_REALLOC: u64 = to_builtin!(19);
 
 impl From<ProgramError> for u64 {
     fn from(error: ProgramError) -> Self {
@@ -135,6 +139,7 @@ impl From<ProgramError> for u64 {
             ProgramError::AccountNotRentExempt => ACCOUNT_NOT_RENT_EXEMPT,
             ProgramError::UnsupportedSysvar => UNSUPPORTED_SYSVAR,
             ProgramError::IllegalOwner => ILLEGAL_OWNER,
+            ProgramError::InvalidRealloc => INVALID_REALLOC,
 
             ProgramError::Custom(error) => {
                 if error == 0 {
@@ -166,6 +171,7 @@ impl From<u64> for ProgramError {
             UNSUPPORTED_SYSVAR => ProgramError::UnsupportedSysvar,
             CUSTOM_ZERO => ProgramError::Custom(0),
             ILLEGAL_OWNER => ProgramError::IllegalOwner,
+            INVALID_REALLOC => ProgramError::InvalidRealloc,
             _ => ProgramError::Custom(error as u32),
         }
     }
@@ -193,6 +199,7 @@ impl TryFrom<InstructionError> for ProgramError {
             Self::Error::AccountNotRentExempt => Ok(Self::AccountNotRentExempt),
             Self::Error::UnsupportedSysvar => Ok(Self::UnsupportedSysvar),
             Self::Error::IllegalOwner => Ok(Self::IllegalOwner),
+            Self::Error::InvalidRealloc => Ok(Self::InvalidRealloc),
             _ => Err(error),
         }
     }
@@ -221,6 +228,7 @@ where
             INVALID_SEEDS => InstructionError::InvalidSeeds,
             UNSUPPORTED_SYSVAR => InstructionError::UnsupportedSysvar,
             ILLEGAL_OWNER => InstructionError::IllegalOwner,
+            INVALID_REALLOC => InstructionError::InvalidRealloc,
             _ => {
                 // A valid custom error has no bits set in the upper 32
                 if error >> BUILTIN_BIT_SHIFT == 0 {
@@ -0,0 +1,219 @@
+//! Compute budget program, whose instructions let a transaction request a compute unit
+//! limit and set a compute unit price that is paid on top of the signature fee.
+
+use crate::{
+    instruction::{Instruction, InstructionError},
+    message::Message,
+    transaction::TransactionError,
+};
+use std::convert::TryFrom;
+
+crate::declare_id!("ComputeBudget111111111111111111111111111111");
+
+/// Compute unit limit used to price transactions that do not request one
+pub const DEFAULT_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+
+/// Maximum compute unit limit a transaction can request
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Compute unit prices are denominated in micro-lamports
+pub const MICRO_LAMPORTS_PER_LAMPORT: u64 = 1_000_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputeBudgetInstruction {
+    /// Set the number of compute units each instruction of the transaction is
+    /// allowed to consume. Values above `MAX_COMPUTE_UNIT_LIMIT` are clamped, and
+    /// so are values above `DEFAULT_COMPUTE_UNIT_LIMIT` if no price is paid.
+    SetComputeUnitLimit(u32),
+
+    /// Set a compute unit price in micro-lamports, raising the transaction fee
+    /// by `compute_unit_limit * price` in exchange for a higher priority
+    SetComputeUnitPrice(u64),
+}
+
+impl ComputeBudgetInstruction {
+    /// Create a `SetComputeUnitLimit` instruction
+    pub fn set_compute_unit_limit(units: u32) -> Instruction {
+        Instruction::new_with_bincode(id(), &Self::SetComputeUnitLimit(units), vec![])
+    }
+
+    /// Create a `SetComputeUnitPrice` instruction
+    pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+        Instruction::new_with_bincode(id(), &Self::SetComputeUnitPrice(micro_lamports), vec![])
+    }
+}
+
+/// Compute budget requested by a transaction
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ComputeBudgetLimits {
+    /// Compute unit limit, `None` if the transaction did not request one
+    pub compute_unit_limit: Option<u32>,
+    /// Compute unit price in micro-lamports
+    pub compute_unit_price: u64,
+}
+
+impl ComputeBudgetLimits {
+    /// Collect the compute budget instructions of a message. Each kind of instruction
+    /// may only appear once, and both invalid and duplicate instructions fail with
+    /// `InvalidInstructionData` at the offending instruction index.
+    pub fn process_message(message: &Message) -> Result<Self, TransactionError> {
+        let mut limits = Self::default();
+        let mut has_price = false;
+        for (index, instruction) in message.instructions.iter().enumerate() {
+            let is_compute_budget_instruction = message
+                .account_keys
+                .get(usize::from(instruction.program_id_index))
+                .map(check_id)
+                .unwrap_or(false);
+            if !is_compute_budget_instruction {
+                continue;
+            }
+
+            let invalid_instruction_data_error = TransactionError::InstructionError(
+                index as u8,
+                InstructionError::InvalidInstructionData,
+            );
+            match bincode::deserialize(&instruction.data) {
+                Ok(ComputeBudgetInstruction::SetComputeUnitLimit(units))
+                    if limits.compute_unit_limit.is_none() =>
+                {
+                    limits.compute_unit_limit = Some(units.min(MAX_COMPUTE_UNIT_LIMIT));
+                }
+                Ok(ComputeBudgetInstruction::SetComputeUnitPrice(micro_lamports)) if !has_price => {
+                    has_price = true;
+                    limits.compute_unit_price = micro_lamports;
+                }
+                _ => return Err(invalid_instruction_data_error),
+            }
+        }
+        if limits.compute_unit_price == 0 {
+            // Raising the limit is only granted to transactions that pay for it
+            limits.compute_unit_limit = limits
+                .compute_unit_limit
+                .map(|units| units.min(DEFAULT_COMPUTE_UNIT_LIMIT));
+        }
+        Ok(limits)
+    }
+
+    /// Compute unit limit of the transaction, falling back to `default_units`
+    pub fn compute_unit_limit_or(&self, default_units: u64) -> u64 {
+        self.compute_unit_limit
+            .map(u64::from)
+            .unwrap_or(default_units)
+    }
+
+    /// Fee paid for prioritization, in lamports, rounded up. Transactions that do
+    /// not request a limit pay for `DEFAULT_COMPUTE_UNIT_LIMIT` units.
+    pub fn prioritization_fee(&self) -> u64 {
+        let compute_unit_limit = self
+            .compute_unit_limit
+            .unwrap_or(DEFAULT_COMPUTE_UNIT_LIMIT);
+        let micro_lamports =
+            u128::from(self.compute_unit_price).saturating_mul(u128::from(compute_unit_limit));
+        let lamports = micro_lamports
+            .saturating_add(u128::from(MICRO_LAMPORTS_PER_LAMPORT - 1))
+            .checked_div(u128::from(MICRO_LAMPORTS_PER_LAMPORT))
+            .unwrap_or_default();
+        u64::try_from(lamports).unwrap_or(u64::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pubkey::Pubkey, system_instruction};
+
+    fn message(instructions: &[Instruction]) -> Message {
+        Message::new(instructions, Some(&Pubkey::new_unique()))
+    }
+
+    #[test]
+    fn test_process_message() {
+        let payer = Pubkey::new_unique();
+        let transfer = system_instruction::transfer(&payer, &Pubkey::new_unique(), 1);
+
+        assert_eq!(
+            ComputeBudgetLimits::process_message(&message(&[transfer.clone()])),
+            Ok(ComputeBudgetLimits::default())
+        );
+        assert_eq!(
+            ComputeBudgetLimits::process_message(&message(&[
+                transfer.clone(),
+                ComputeBudgetInstruction::set_compute_unit_limit(u32::MAX),
+                ComputeBudgetInstruction::set_compute_unit_price(10),
+            ])),
+            Ok(ComputeBudgetLimits {
+                compute_unit_limit: Some(MAX_COMPUTE_UNIT_LIMIT),
+                compute_unit_price: 10,
+            })
+        );
+        assert_eq!(
+            ComputeBudgetLimits::process_message(&message(&[
+                transfer.clone(),
+                ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT),
+            ])),
+            Ok(ComputeBudgetLimits {
+                compute_unit_limit: Some(DEFAULT_COMPUTE_UNIT_LIMIT),
+                compute_unit_price: 0,
+            })
+        );
+        assert_eq!(
+            ComputeBudgetLimits::process_message(&message(&[
+                transfer.clone(),
+                ComputeBudgetInstruction::set_compute_unit_limit(1_000),
+                ComputeBudgetInstruction::set_compute_unit_price(0),
+            ])),
+            Ok(ComputeBudgetLimits {
+                compute_unit_limit: Some(1_000),
+                compute_unit_price: 0,
+            })
+        );
+        assert_eq!(
+            ComputeBudgetLimits::process_message(&message(&[
+                ComputeBudgetInstruction::set_compute_unit_price(1),
+                transfer,
+                ComputeBudgetInstruction::set_compute_unit_price(2),
+            ])),
+            Err(TransactionError::InstructionError(
+                2,
+                InstructionError::InvalidInstructionData
+            ))
+        );
+        assert_eq!(
+            ComputeBudgetLimits::process_message(&message(&[Instruction::new_with_bytes(
+                id(),
+                &[42],
+                vec![]
+            )])),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::InvalidInstructionData
+            ))
+        );
+    }
+
+    #[test]
+    fn test_prioritization_fee() {
+        let limits = ComputeBudgetLimits::default();
+        assert_eq!(limits.prioritization_fee(), 0);
+
+        let limits = ComputeBudgetLimits {
+            compute_unit_limit: None,
+            compute_unit_price: 1,
+        };
+        assert_eq!(limits.prioritization_fee(), 1);
+
+        let limits = ComputeBudgetLimits {
+            compute_unit_limit: Some(1_000_000),
+            compute_unit_price: 5_000,
+        };
+        assert_eq!(limits.compute_unit_limit_or(200_000), 1_000_000);
+        assert_eq!(limits.prioritization_fee(), 5_000);
+
+        let limits = ComputeBudgetLimits {
+            compute_unit_limit: Some(MAX_COMPUTE_UNIT_LIMIT),
+            compute_unit_price: u64::MAX,
+        };
+        assert_eq!(limits.prioritization_fee(), u64::MAX);
+    }
+}
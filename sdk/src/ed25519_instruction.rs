@@ -0,0 +1,241 @@
+#![allow(clippy::integer_arithmetic)]
+#![cfg(feature = "full")]
+
+use crate::{
+    ed25519_program::{
+        get_signature_offsets, Ed25519SignatureOffsets, CURRENT_INSTRUCTION_INDEX, DATA_START,
+        PUBKEY_SERIALIZED_SIZE, SIGNATURE_OFFSETS_START, SIGNATURE_SERIALIZED_SIZE,
+    },
+    instruction::Instruction,
+};
+use ed25519_dalek::Signer;
+use std::convert::TryFrom;
+
+#[derive(Debug, PartialEq)]
+pub enum Ed25519Error {
+    InvalidPublicKey,
+    InvalidSignature,
+    InvalidDataOffsets,
+    InvalidInstructionDataSize,
+}
+
+/// Create an ed25519 program instruction verifying a signature of `message` by `keypair`
+pub fn new_ed25519_instruction(keypair: &ed25519_dalek::Keypair, message: &[u8]) -> Instruction {
+    let signature = keypair.sign(message).to_bytes();
+    let pubkey = keypair.public.to_bytes();
+
+    let public_key_offset = DATA_START;
+    let signature_offset = public_key_offset + PUBKEY_SERIALIZED_SIZE;
+    let message_data_offset = signature_offset + SIGNATURE_SERIALIZED_SIZE;
+
+    let mut instruction_data = vec![0u8; message_data_offset + message.len()];
+    let num_signatures = 1;
+    instruction_data[0] = num_signatures;
+    let offsets = Ed25519SignatureOffsets {
+        signature_offset: signature_offset as u16,
+        signature_instruction_index: CURRENT_INSTRUCTION_INDEX,
+        public_key_offset: public_key_offset as u16,
+        public_key_instruction_index: CURRENT_INSTRUCTION_INDEX,
+        message_data_offset: message_data_offset as u16,
+        message_data_size: message.len() as u16,
+        message_instruction_index: CURRENT_INSTRUCTION_INDEX,
+    };
+    let writer = std::io::Cursor::new(&mut instruction_data[SIGNATURE_OFFSETS_START..DATA_START]);
+    bincode::serialize_into(writer, &offsets).unwrap();
+
+    instruction_data[public_key_offset..signature_offset].copy_from_slice(&pubkey);
+    instruction_data[signature_offset..message_data_offset].copy_from_slice(&signature);
+    instruction_data[message_data_offset..].copy_from_slice(message);
+
+    Instruction {
+        program_id: solana_sdk::ed25519_program::id(),
+        accounts: vec![],
+        data: instruction_data,
+    }
+}
+
+/// Verify all signatures of an ed25519 instruction, `instruction_datas` holds the data of
+/// every instruction of the transaction. An instruction without signatures is rejected.
+pub fn verify_signatures(data: &[u8], instruction_datas: &[&[u8]]) -> Result<(), Ed25519Error> {
+    let signature_offsets =
+        get_signature_offsets(data).map_err(|_| Ed25519Error::InvalidInstructionDataSize)?;
+    for offsets in signature_offsets {
+        // Parse out signature
+        let signature = get_data_slice(
+            data,
+            instruction_datas,
+            offsets.signature_instruction_index,
+            offsets.signature_offset,
+            SIGNATURE_SERIALIZED_SIZE,
+        )?;
+        let signature = ed25519_dalek::Signature::try_from(signature)
+            .map_err(|_| Ed25519Error::InvalidSignature)?;
+
+        // Parse out pubkey
+        let pubkey = get_data_slice(
+            data,
+            instruction_datas,
+            offsets.public_key_instruction_index,
+            offsets.public_key_offset,
+            PUBKEY_SERIALIZED_SIZE,
+        )?;
+        let pubkey = ed25519_dalek::PublicKey::from_bytes(pubkey)
+            .map_err(|_| Ed25519Error::InvalidPublicKey)?;
+
+        // Parse out message
+        let message = get_data_slice(
+            data,
+            instruction_datas,
+            offsets.message_instruction_index,
+            offsets.message_data_offset,
+            offsets.message_data_size as usize,
+        )?;
+
+        pubkey
+            .verify_strict(message, &signature)
+            .map_err(|_| Ed25519Error::InvalidSignature)?;
+    }
+    Ok(())
+}
+
+fn get_data_slice<'a>(
+    data: &'a [u8],
+    instruction_datas: &'a [&[u8]],
+    instruction_index: u16,
+    offset_start: u16,
+    size: usize,
+) -> Result<&'a [u8], Ed25519Error> {
+    let instruction = if instruction_index == CURRENT_INSTRUCTION_INDEX {
+        data
+    } else {
+        instruction_datas
+            .get(instruction_index as usize)
+            .ok_or(Ed25519Error::InvalidDataOffsets)?
+    };
+    let start = offset_start as usize;
+    let end = start + size;
+    instruction
+        .get(start..end)
+        .ok_or(Ed25519Error::InvalidSignature)
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::{
+        ed25519_program::{parse_signed_messages, SignedMessage},
+        feature_set::FeatureSet,
+        hash::Hash,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        signature::{Keypair, Signer as _},
+        transaction::Transaction,
+    };
+    use rand::thread_rng;
+
+    fn test_case(
+        num_signatures: u8,
+        offsets: &Ed25519SignatureOffsets,
+    ) -> Result<(), Ed25519Error> {
+        let mut instruction_data = vec![0u8; DATA_START];
+        instruction_data[0] = num_signatures;
+        let writer =
+            std::io::Cursor::new(&mut instruction_data[SIGNATURE_OFFSETS_START..DATA_START]);
+        bincode::serialize_into(writer, &offsets).unwrap();
+
+        verify_signatures(&instruction_data, &[&[0u8; 100]])
+    }
+
+    #[test]
+    fn test_invalid_offsets() {
+        let mut instruction_data = vec![0u8; DATA_START];
+        instruction_data[0] = 1;
+        instruction_data.truncate(DATA_START - 1);
+        assert_eq!(
+            verify_signatures(&instruction_data, &[&[0u8; 100]]),
+            Err(Ed25519Error::InvalidInstructionDataSize)
+        );
+
+        let offsets = Ed25519SignatureOffsets {
+            signature_instruction_index: 1,
+            ..Ed25519SignatureOffsets::default()
+        };
+        assert_eq!(
+            test_case(1, &offsets),
+            Err(Ed25519Error::InvalidDataOffsets)
+        );
+
+        let offsets = Ed25519SignatureOffsets {
+            signature_offset: 99,
+            ..Ed25519SignatureOffsets::default()
+        };
+        assert_eq!(test_case(1, &offsets), Err(Ed25519Error::InvalidSignature));
+    }
+
+    #[test]
+    fn test_zero_signatures() {
+        let instruction_data = vec![0u8; DATA_START];
+        assert_eq!(
+            get_signature_offsets(&instruction_data),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        assert_eq!(
+            verify_signatures(&instruction_data, &[]),
+            Err(Ed25519Error::InvalidInstructionDataSize)
+        );
+        assert_eq!(
+            parse_signed_messages(&instruction_data),
+            Err(ProgramError::InvalidInstructionData)
+        );
+
+        let mint_keypair = Keypair::new();
+        let tx = Transaction::new_signed_with_payer(
+            &[Instruction {
+                program_id: crate::ed25519_program::id(),
+                accounts: vec![],
+                data: vec![0u8; SIGNATURE_OFFSETS_START],
+            }],
+            Some(&mint_keypair.pubkey()),
+            &[&mint_keypair],
+            Hash::default(),
+        );
+        assert!(tx.verify_precompiles(&FeatureSet::all_enabled()).is_err());
+    }
+
+    #[test]
+    fn test_ed25519() {
+        let privkey = ed25519_dalek::Keypair::generate(&mut thread_rng());
+        let message = b"hello";
+        let instruction = new_ed25519_instruction(&privkey, message);
+        assert_eq!(verify_signatures(&instruction.data, &[]), Ok(()));
+        assert_eq!(
+            parse_signed_messages(&instruction.data),
+            Ok(vec![SignedMessage {
+                pubkey: Pubkey::new(&privkey.public.to_bytes()),
+                message,
+            }])
+        );
+
+        let mint_keypair = Keypair::new();
+        let mut tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&mint_keypair.pubkey()),
+            &[&mint_keypair],
+            Hash::default(),
+        );
+        assert!(tx.verify_precompiles(&FeatureSet::all_enabled()).is_ok());
+
+        // Tamper with the message
+        let index = tx.message.instructions[0].data.len() - 1;
+        tx.message.instructions[0].data[index] ^= 1;
+        assert!(tx.verify_precompiles(&FeatureSet::all_enabled()).is_err());
+
+        // Ed25519 instructions are not verified before the program is enabled, even if the
+        // secp256k1 program is
+        let mut feature_set = FeatureSet::all_enabled();
+        feature_set
+            .active
+            .remove(&crate::feature_set::sophon::ed25519_program_enabled::id());
+        assert!(tx.verify_precompiles(&feature_set).is_ok());
+    }
+}
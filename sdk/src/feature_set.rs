@@ -189,6 +189,34 @@ pub mod sophon {
     pub mod disable_durable_nonce {
         solana_sdk::declare_id!("AKAGpT85PfoGDxCBPsxGHy8iZdaJoeAiVXq5oRxFS5TL");
     }
+
+    pub mod versioned_tx_message_enabled {
+        solana_sdk::declare_id!("Gh39vRd3pat1RVGXbqx1QD48xuzbVB44NWZYqhuFptRg");
+    }
+
+    pub mod compute_budget_program_enabled {
+        solana_sdk::declare_id!("Fy5SsKbH7e9bCUvdnuW1XrzYyxSyWfZznkvCXqDHk2TC");
+    }
+
+    pub mod ed25519_program_enabled {
+        solana_sdk::declare_id!("8QE7bzYyAzYYpLWnJpPw1UrurHSX1LDAXk3MwmewQo5G");
+    }
+
+    pub mod return_data_syscall_enabled {
+        solana_sdk::declare_id!("9UJ6VEFb9rgYAyn2hKC2enmgzWgMkUMRUnq5ZfFvKa61");
+    }
+
+    pub mod account_data_realloc_enabled {
+        solana_sdk::declare_id!("5J1x3rJcQnKp8kXTVMofjFSXETgKN7CWWmz1dtKFecxT");
+    }
+
+    pub mod stake_redelegate {
+        solana_sdk::declare_id!("3s5PfUHh6WuUAvXqY6vYcUDnuV7tK8VHUJSpLLNbBNd4");
+    }
+
+    pub mod stake_deactivate_delinquent {
+        solana_sdk::declare_id!("8wtB9QWGdSbW8Wq3vNpcTSG5fDkGm2usk8XfiUCXi7hf");
+    }
 }
 lazy_static! {
     /// Map of feature identifiers to user-visible description
@@ -243,6 +271,13 @@ lazy_static! {
             (sophon::burn_fee::id(), "Burn fee during transaction execution."),
             (sophon::clear_logs_on_error::id(), "Clear logs from receipt if transaction is failed or reverted."),
             (sophon::disable_durable_nonce::id(), "Disable durable nonce."),
+            (sophon::versioned_tx_message_enabled::id(), "Versioned transactions and address lookup table program."),
+            (sophon::compute_budget_program_enabled::id(), "Compute budget program and prioritization fees."),
+            (sophon::ed25519_program_enabled::id(), "Ed25519 signature verification program."),
+            (sophon::return_data_syscall_enabled::id(), "Program return data syscalls."),
+            (sophon::account_data_realloc_enabled::id(), "Account data reallocation by the owning program."),
+            (sophon::stake_redelegate::id(), "Stake program Redelegate instruction."),
+            (sophon::stake_deactivate_delinquent::id(), "Stake program DeactivateDelinquent instruction."),
             /*************** ADD NEW FEATURES HERE ***************/
         ]
     ).collect();
@@ -23,7 +23,6 @@ use crate::{
 use bincode::{deserialize, serialize};
 use chrono::{TimeZone, Utc};
 use evm_state::H256;
-use itertools::Itertools;
 use log::warn;
 use memmap2::Mmap;
 use std::{
@@ -39,9 +38,6 @@ use std::{
 // deprecated default that is no longer used
 pub const UNUSED_DEFAULT: u64 = 1024;
 pub const EVM_GENESIS: &str = "evm-state-genesis";
-
-// Dont load to memory accounts, more specified count
-use evm_state::MAX_IN_MEMORY_EVM_ACCOUNTS;
 // The order can't align with release lifecycle only to remain ABI-compatible...
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, AbiEnumVisitor, AbiExample)]
 pub enum ClusterType {
@@ -220,25 +216,7 @@ impl GenesisConfig {
         ledger_path: &Path,
         evm_state_json: Option<&Path>,
     ) -> Result<(), std::io::Error> {
-        let evm_state_path = tempfile::TempDir::new()?;
-        let evm_state = evm_state::EvmState::new(evm_state_path.path())
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("{}.", e)))?;
-        let mut evm_state = if let evm_state::EvmState::Incomming(evm_state) = evm_state {
-            evm_state
-        } else {
-            unreachable!("Expected new evm-state to be writable.");
-        };
-
-        if let Some(evm_state_json) = evm_state_json {
-            let accounts = evm_genesis::read_accounts(evm_state_json)?;
-
-            for chunk in &accounts.chunks(MAX_IN_MEMORY_EVM_ACCOUNTS) {
-                let chunk: Result<Vec<_>, _> = chunk.collect();
-                let chunk = chunk?;
-                log::info!("Adding {} accounts to evm state.", chunk.len());
-                evm_state.set_initial(chunk);
-            }
-        } else {
+        if evm_state_json.is_none() {
             warn!("Generating genesis with empty evm state");
             match self.cluster_type {
                 ClusterType::Development | ClusterType::Devnet => (),
@@ -250,6 +228,8 @@ impl GenesisConfig {
                 }
             }
         };
+        let evm_state_path = tempfile::TempDir::new()?;
+        let evm_state = evm_genesis::open_genesis_state(evm_state_path.path(), evm_state_json)?;
         // create zero block
         let committed = evm_state.commit_block(0, H256::zero());
         let genesis_evm_block = &committed.state.block;
@@ -269,6 +249,18 @@ impl GenesisConfig {
         Ok(())
     }
 
+    /// Build evm genesis state from `evm_state_json` in temporary storage, and return its root hash.
+    pub fn compute_evm_root_hash(evm_state_json: &Path) -> Result<H256, std::io::Error> {
+        let evm_state_path = tempfile::TempDir::new()?;
+        let evm_state =
+            evm_genesis::open_genesis_state(evm_state_path.path(), Some(evm_state_json))?;
+        Ok(evm_state
+            .commit_block(0, H256::zero())
+            .state
+            .block
+            .state_root)
+    }
+
     pub fn set_evm_root_hash(&mut self, root_hash: H256) {
         self.evm_root_hash = root_hash;
     }
@@ -366,13 +358,14 @@ impl fmt::Display for GenesisConfig {
 
 pub mod evm_genesis {
     use evm_rpc::{Bytes, Hex};
-    use evm_state::{MemoryAccount, H160, H256, U256};
+    use evm_state::{MemoryAccount, H160, H256, MAX_IN_MEMORY_EVM_ACCOUNTS, U256};
+    use itertools::Itertools;
 
     use serde::{de, Deserialize, Serialize};
     use serde_json::{de::IoRead, Deserializer};
     use sha3::{Digest, Keccak256};
     use std::fs::File;
-    use std::io::{BufRead, BufReader, Error, ErrorKind};
+    use std::io::{BufRead, BufReader, Error, ErrorKind, Read};
     use std::iter;
     use std::path::Path;
     use std::{collections::BTreeMap, io::Write};
@@ -631,13 +624,186 @@ pub mod evm_genesis {
         }
     }
 
+    /// Header of evm state dump, that can be read by `StreamAccountReader`.
+    const STATE_DUMP_HEADER: &[u8] = b"{ \"state\": {\n";
+
+    /// Read accounts from evm state json.
+    ///
+    /// Supports two formats:
+    /// - evm state dump (`{ "state": { .. }}`), that is read in streaming manner;
+    /// - geth-style genesis.json, where accounts are taken from `alloc` section.
     pub fn read_accounts(
         evm_state_snapshot: &Path,
     ) -> Result<impl Iterator<Item = Result<(H160, MemoryAccount), Error>>, Error> {
-        let evm_file = BufReader::new(File::open(&evm_state_snapshot)?);
+        let mut evm_file = BufReader::new(File::open(&evm_state_snapshot)?);
+
+        let accounts: Box<dyn Iterator<Item = Result<(H160, MemoryAccount), Error>>> =
+            if evm_file.fill_buf()?.starts_with(STATE_DUMP_HEADER) {
+                let mut reader = StreamAccountReader::new(evm_file)?;
+                Box::new(iter::from_fn(move || reader.read_account().transpose()))
+            } else {
+                Box::new(read_geth_genesis_alloc(evm_file)?.into_iter().map(Ok))
+            };
+        Ok(accounts)
+    }
+
+    /// Create evm state in `evm_state_path`, fill it with accounts from `evm_state_json` (if any),
+    /// and return it without commiting genesis block.
+    pub fn open_genesis_state(
+        evm_state_path: &Path,
+        evm_state_json: Option<&Path>,
+    ) -> Result<evm_state::EvmBackend<evm_state::Incomming>, Error> {
+        let evm_state = evm_state::EvmState::new(evm_state_path)
+            .map_err(|e| Error::new(ErrorKind::Other, format!("{}.", e)))?;
+        let mut evm_state = if let evm_state::EvmState::Incomming(evm_state) = evm_state {
+            evm_state
+        } else {
+            unreachable!("Expected new evm-state to be writable.");
+        };
+
+        if let Some(evm_state_json) = evm_state_json {
+            let accounts = read_accounts(evm_state_json)?;
+
+            // Dont load to memory accounts, more specified count
+            for chunk in &accounts.chunks(MAX_IN_MEMORY_EVM_ACCOUNTS) {
+                let chunk: Result<Vec<_>, _> = chunk.collect();
+                let chunk = chunk?;
+                log::info!("Adding {} accounts to evm state.", chunk.len());
+                evm_state.set_initial(chunk);
+            }
+        }
+        Ok(evm_state)
+    }
+
+    /// Genesis file in format used by geth (`genesis.json`).
+    /// Only `alloc` section is used, other fields (chain config, difficulty, etc.) are ignored.
+    #[derive(Debug, Deserialize)]
+    struct GethGenesis {
+        alloc: BTreeMap<String, GethGenesisAccount>,
+    }
+
+    /// Account from `alloc` section of geth genesis.
+    #[derive(Debug, Deserialize)]
+    struct GethGenesisAccount {
+        #[serde(deserialize_with = "deserialize_hex_or_decimal")]
+        balance: U256,
+        #[serde(default, deserialize_with = "deserialize_optional_hex_or_decimal")]
+        nonce: Option<U256>,
+        #[serde(default)]
+        code: Option<String>,
+        #[serde(default)]
+        storage: BTreeMap<String, String>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(untagged)]
+    enum HexOrDecimal {
+        String(String),
+        Number(u64),
+    }
+
+    impl HexOrDecimal {
+        fn parse(self) -> Result<U256, String> {
+            match self {
+                HexOrDecimal::Number(n) => Ok(n.into()),
+                HexOrDecimal::String(s) => match s.strip_prefix("0x") {
+                    Some(hex) => U256::from_str_radix(hex, 16).map_err(|e| format!("{}", e)),
+                    None => U256::from_dec_str(&s).map_err(|e| format!("{:?}", e)),
+                },
+            }
+        }
+    }
+
+    fn deserialize_hex_or_decimal<'de, D>(deserializer: D) -> Result<U256, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        HexOrDecimal::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+
+    fn deserialize_optional_hex_or_decimal<'de, D>(
+        deserializer: D,
+    ) -> Result<Option<U256>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        Option::<HexOrDecimal>::deserialize(deserializer)?
+            .map(HexOrDecimal::parse)
+            .transpose()
+            .map_err(de::Error::custom)
+    }
+
+    /// Decode hex string with optional "0x" prefix.
+    fn decode_hex(data: &str) -> Result<Vec<u8>, Error> {
+        let data = data.strip_prefix("0x").unwrap_or(data);
+        let data = if data.len() % 2 == 1 {
+            hex::decode(format!("0{}", data))
+        } else {
+            hex::decode(data)
+        };
+        data.map_err(|e| Error::new(ErrorKind::InvalidData, format!("{}", e)))
+    }
+
+    /// Decode hex string into fixed size value, shorter values are left padded with zeros.
+    fn decode_fixed_hex<const N: usize>(data: &str) -> Result<[u8; N], Error> {
+        let bytes = decode_hex(data)?;
+        if bytes.len() > N {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Value {} is longer than {} bytes", data, N),
+            ));
+        }
+        let mut result = [0; N];
+        result[N - bytes.len()..].copy_from_slice(&bytes);
+        Ok(result)
+    }
+
+    impl GethGenesisAccount {
+        fn into_memory_account(self) -> Result<MemoryAccount, Error> {
+            let storage = self
+                .storage
+                .iter()
+                .map(|(k, v)| Ok((H256(decode_fixed_hex(k)?), H256(decode_fixed_hex(v)?))))
+                .collect::<Result<_, Error>>()?;
+            let code = self
+                .code
+                .as_deref()
+                .map(decode_hex)
+                .transpose()?
+                .unwrap_or_default();
+            Ok(MemoryAccount {
+                nonce: self.nonce.unwrap_or_default(),
+                balance: self.balance,
+                storage,
+                code,
+            })
+        }
+    }
+
+    /// Read accounts from `alloc` section of geth-style genesis.json.
+    pub fn read_geth_genesis_alloc<R: Read>(
+        reader: R,
+    ) -> Result<Vec<(H160, MemoryAccount)>, Error> {
+        let genesis: GethGenesis = serde_json::from_reader(reader).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Evm state json is neither evm state dump (should start with '{{ \"state\": {{'), nor geth genesis with 'alloc' section: {}",
+                    e
+                ),
+            )
+        })?;
 
-        let mut reader = StreamAccountReader::new(evm_file)?;
-        Ok(iter::from_fn(move || reader.read_account().transpose()))
+        genesis
+            .alloc
+            .into_iter()
+            .map(|(address, account)| {
+                let address = H160(decode_fixed_hex(&address)?);
+                Ok((address, account.into_memory_account()?))
+            })
+            .collect()
     }
 
     pub fn generate_evm_state_json(file: &Path) -> Result<H256, Error> {
@@ -650,7 +816,7 @@ pub mod evm_genesis {
 
 #[cfg(test)]
 mod tests {
-    use evm_state::{MemoryAccount, H160};
+    use evm_state::{MemoryAccount, H160, U256};
 
     use super::evm_genesis::*;
     use super::*;
@@ -837,4 +1003,100 @@ mod tests {
             3
         );
     }
+
+    #[test]
+    fn test_geth_genesis_alloc() {
+        let geth_genesis = r#"{
+            "config": { "chainId": 111 },
+            "difficulty": "0x1",
+            "gasLimit": "0x47b760",
+            "alloc": {
+                "ffbb13a995ddf6ad35cf533e69f38d38887e8f5c": { "balance": "10000000000000000000000" },
+                "0x984cf4e0001003d4ef5328d0fea9a3a430b78027": {
+                    "balance": "0x0",
+                    "nonce": "0x1",
+                    "code": "0x60102233",
+                    "storage": {
+                        "0x00": "0xc47fa223c0b394a6bebb360603c9505dcebdcbe6",
+                        "0x0000000000000000000000000000000000000000000000000000000000000003": "0x0000000000000000000000003a1a9a4f4167b8c55f13b7189f210cc7b989d52b"
+                    }
+                }
+            }
+        }"#;
+        let accounts: BTreeMap<_, _> = read_geth_genesis_alloc(geth_genesis.as_bytes())
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(accounts.len(), 2);
+
+        let user = &accounts[&"0xffbb13a995ddf6ad35cf533e69f38d38887e8f5c"
+            .parse()
+            .unwrap()];
+        assert_eq!(
+            user.balance,
+            U256::from_dec_str("10000000000000000000000").unwrap()
+        );
+        assert_eq!(user.nonce, U256::zero());
+        assert!(user.code.is_empty());
+
+        let contract = &accounts[&"0x984cf4e0001003d4ef5328d0fea9a3a430b78027"
+            .parse()
+            .unwrap()];
+        assert_eq!(contract.nonce, U256::one());
+        assert_eq!(contract.code, vec![0x60, 0x10, 0x22, 0x33]);
+        assert_eq!(
+            contract.storage[&H256::zero()],
+            "0x000000000000000000000000c47fa223c0b394a6bebb360603c9505dcebdcbe6"
+                .parse()
+                .unwrap()
+        );
+
+        let invalid_address = r#"{ "alloc": { "0xKKKK": { "balance": "0x0" } } }"#;
+        assert!(read_geth_genesis_alloc(invalid_address.as_bytes()).is_err());
+        let no_alloc = r#"{ "config": {} }"#;
+        assert!(read_geth_genesis_alloc(no_alloc.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_geth_genesis_root_matches_state_dump() {
+        let dir = make_tmp_path("geth_genesis");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let state_dump = dir.join("state.json");
+        std::fs::write(
+            &state_dump,
+            r#"{ "state": {
+            "0xffbb13a995ddf6ad35cf533e69f38d38887e8f5c": {"balance": "2544faa778090e00000", "nonce": "0"},
+            "0x984cf4e0001003d4ef5328d0fea9a3a430b78027": {"balance": "0", "nonce": "1", "code": "60102233", "storage": {
+                    "0x0000000000000000000000000000000000000000000000000000000000000000": "0x000000000000000000000000c47fa223c0b394a6bebb360603c9505dcebdcbe6"
+            }}
+        }}"#,
+        )
+        .unwrap();
+        let geth_genesis = dir.join("genesis.json");
+        std::fs::write(
+            &geth_genesis,
+            r#"{ "alloc": {
+                "0xffbb13a995ddf6ad35cf533e69f38d38887e8f5c": { "balance": "0x2544faa778090e00000" },
+                "984cf4e0001003d4ef5328d0fea9a3a430b78027": { "balance": "0", "nonce": 1, "code": "0x60102233", "storage": {
+                    "0x0": "0xc47fa223c0b394a6bebb360603c9505dcebdcbe6"
+                }}
+            }}"#,
+        )
+        .unwrap();
+
+        let dump_root = GenesisConfig::compute_evm_root_hash(&state_dump).unwrap();
+        let geth_root = GenesisConfig::compute_evm_root_hash(&geth_genesis).unwrap();
+        assert_ne!(geth_root, evm_state::empty_trie_hash());
+        assert_eq!(dump_root, geth_root);
+
+        let config = GenesisConfig {
+            evm_root_hash: geth_root,
+            ..GenesisConfig::default()
+        };
+        config
+            .generate_evm_state(&dir.join("ledger"), Some(&geth_genesis))
+            .expect("generate_evm_state");
+        let _ignored = std::fs::remove_dir_all(&dir);
+    }
 }
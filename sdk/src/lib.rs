@@ -16,8 +16,10 @@ pub mod arithmetic;
 pub mod builtins;
 pub mod client;
 pub mod commitment_config;
+pub mod compute_budget;
 pub mod derivation_path;
 pub mod deserialize_utils;
+pub mod ed25519_instruction;
 pub mod entrypoint;
 pub mod entrypoint_deprecated;
 pub mod entrypoint_native;
@@ -39,6 +41,7 @@ pub mod poh_config;
 pub mod process_instruction;
 pub mod program_utils;
 pub mod pubkey;
+pub mod quic;
 pub mod recent_blockhashes_account;
 pub mod recent_evm_blockhashes_account;
 pub mod rpc_port;
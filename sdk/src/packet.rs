@@ -25,6 +25,8 @@ pub struct Meta {
     pub seed: [u8; 32],
     pub slot: Slot,
     pub is_tracer_tx: bool,
+    /// Stake of the peer the packet was received from, zero if unstaked or unknown
+    pub sender_stake: u64,
 }
 
 #[derive(Clone)]
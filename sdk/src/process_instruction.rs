@@ -75,6 +75,17 @@ pub trait InvokeContext {
     );
     /// Get sysvar data
     fn get_sysvar_data(&self, id: &Pubkey) -> Option<Rc<Vec<u8>>>;
+    /// Set the return data of the currently executing program
+    fn set_return_data(&mut self, data: Vec<u8>) -> Result<(), InstructionError>;
+    /// Get the return data and the program ID of the last program that set it
+    fn get_return_data(&self) -> (Pubkey, &[u8]);
+}
+
+/// Data set by the last program of an instruction which called `sol_set_return_data`
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TransactionReturnData {
+    pub program_id: Pubkey,
+    pub data: Vec<u8>,
 }
 
 /// Convenience macro to log a message with an `Rc<RefCell<dyn Logger>>`
@@ -154,6 +165,9 @@ pub struct BpfComputeBudget {
     pub cpi_bytes_per_unit: u64,
     /// Base number of compute units consumed to get a sysvar
     pub sysvar_base_cost: u64,
+    /// Base number of compute units consumed to call a syscall, used by the return data
+    /// syscalls which also charge `cpi_bytes_per_unit` for the data they copy
+    pub syscall_base_cost: u64,
 }
 impl Default for BpfComputeBudget {
     fn default() -> Self {
@@ -177,6 +191,7 @@ impl BpfComputeBudget {
             max_cpi_instruction_size: 1280, // IPv6 Min MTU size
             cpi_bytes_per_unit: 250,        // ~50MB at 200,000 units
             sysvar_base_cost: 100,
+            syscall_base_cost: 100,
         }
     }
 }
@@ -307,6 +322,7 @@ pub struct MockInvokeContext {
     pub sysvars: Vec<(Pubkey, Option<Rc<Vec<u8>>>)>,
     pub evm_executor: Option<Rc<RefCell<evm_state::Executor>>>,
     pub disabled_features: BTreeSet<Pubkey>,
+    pub return_data: (Pubkey, Vec<u8>),
 }
 
 impl MockInvokeContext {
@@ -340,6 +356,7 @@ impl Default for MockInvokeContext {
             sysvars: vec![],
             evm_executor: None,
             disabled_features: BTreeSet::new(),
+            return_data: (Pubkey::default(), Vec::new()),
         }
     }
 }
@@ -429,4 +446,11 @@ impl InvokeContext for MockInvokeContext {
             .iter()
             .find_map(|(key, sysvar)| if id == key { sysvar.clone() } else { None })
     }
+    fn set_return_data(&mut self, data: Vec<u8>) -> Result<(), InstructionError> {
+        self.return_data = (self.key, data);
+        Ok(())
+    }
+    fn get_return_data(&self) -> (Pubkey, &[u8]) {
+        (self.return_data.0, &self.return_data.1)
+    }
 }
@@ -0,0 +1,22 @@
+/// The QUIC TPU listens on the UDP TPU port plus this offset
+pub const QUIC_PORT_OFFSET: u16 = 6;
+
+/// ALPN protocol identifier negotiated by TPU clients and servers
+pub const QUIC_TPU_ALPN: &[u8] = b"solana-tpu";
+
+/// Concurrent streams allowed on a connection from an unstaked peer
+pub const QUIC_MAX_UNSTAKED_CONCURRENT_STREAMS: u64 = 64;
+
+/// Concurrent streams allowed on a connection from a staked peer with negligible stake
+pub const QUIC_MIN_STAKED_CONCURRENT_STREAMS: u64 = 128;
+
+/// Concurrent streams shared by all staked peers in proportion to their stake
+pub const QUIC_TOTAL_STAKED_CONCURRENT_STREAMS: u64 = 100_000;
+
+pub const QUIC_MAX_CONCURRENT_CONNECTIONS: u32 = 2500;
+
+/// Connections allowed from a single staked identity, or from a single IP address for unstaked
+/// peers
+pub const QUIC_MAX_CONNECTIONS_PER_PEER: usize = 8;
+pub const QUIC_MAX_TIMEOUT_MS: u32 = 2_000;
+pub const QUIC_KEEP_ALIVE_MS: u64 = 1_000;
@@ -23,6 +23,9 @@ pub enum SignerError {
     #[error("not enough signers")]
     NotEnoughSigners,
 
+    #[error("too many signers")]
+    TooManySigners,
+
     #[error("transaction error")]
     TransactionError(#[from] TransactionError),
 
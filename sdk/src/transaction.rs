@@ -3,8 +3,9 @@
 #![cfg(feature = "full")]
 
 use crate::sanitize::{Sanitize, SanitizeError};
-use crate::secp256k1_instruction::verify_eth_addresses;
+use crate::{ed25519_instruction::verify_signatures, secp256k1_instruction::verify_eth_addresses};
 use crate::{
+    feature_set::{self, FeatureSet},
     hash::Hash,
     instruction::{CompiledInstruction, Instruction, InstructionError},
     message::Message,
@@ -19,6 +20,10 @@ use crate::{
 use std::result;
 use thiserror::Error;
 
+pub mod versioned;
+
+pub use versioned::VersionedTransaction;
+
 /// Reasons a transaction might be rejected.
 #[derive(
     Error, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, AbiExample, AbiEnumVisitor,
@@ -98,6 +103,30 @@ pub enum TransactionError {
     /// Transaction processing left an account with an outstanding borrowed reference
     #[error("Transaction processing left an account with an outstanding borrowed reference")]
     AccountBorrowOutstanding,
+
+    /// Transaction version is unsupported
+    #[error("Transaction version is unsupported")]
+    UnsupportedVersion,
+
+    /// Transaction loads an address table account that doesn't exist
+    #[error("Transaction loads an address table account that doesn't exist")]
+    AddressLookupTableNotFound,
+
+    /// Transaction loads an address table account with an invalid owner
+    #[error("Transaction loads an address table account with an invalid owner")]
+    InvalidAddressLookupTableOwner,
+
+    /// Transaction loads an address table account with invalid data
+    #[error("Transaction loads an address table account with invalid data")]
+    InvalidAddressLookupTableData,
+
+    /// Transaction address table lookup uses an invalid index
+    #[error("Transaction address table lookup uses an invalid index")]
+    InvalidAddressLookupTableIndex,
+
+    /// Transaction leaves a reallocated account below the rent-exempt minimum
+    #[error("Transaction leaves a reallocated account below the rent-exempt minimum")]
+    InvalidRentPayingAccount,
 }
 
 pub type Result<T> = result::Result<T, TransactionError>;
@@ -387,14 +416,22 @@ impl Transaction {
             .collect()
     }
 
-    pub fn verify_precompiles(&self) -> Result<()> {
+    /// Verify the instructions of the precompile programs which are active in `feature_set`
+    pub fn verify_precompiles(&self, feature_set: &FeatureSet) -> Result<()> {
+        let secp256k1_program_enabled =
+            feature_set.is_active(&feature_set::secp256k1_program_enabled::id());
+        let ed25519_program_enabled =
+            feature_set.is_active(&feature_set::sophon::ed25519_program_enabled::id());
+        if !secp256k1_program_enabled && !ed25519_program_enabled {
+            return Ok(());
+        }
         for instruction in &self.message().instructions {
             // The Transaction may not be sanitized at this point
             if instruction.program_id_index as usize >= self.message().account_keys.len() {
                 return Err(TransactionError::AccountNotFound);
             }
             let program_id = &self.message().account_keys[instruction.program_id_index as usize];
-            if crate::secp256k1_program::check_id(program_id) {
+            if secp256k1_program_enabled && crate::secp256k1_program::check_id(program_id) {
                 let instruction_datas: Vec<_> = self
                     .message()
                     .instructions
@@ -404,6 +441,15 @@ impl Transaction {
                 let data = &instruction.data;
                 let e = verify_eth_addresses(data, &*instruction_datas);
                 e.map_err(|_| TransactionError::InvalidAccountIndex)?;
+            } else if ed25519_program_enabled && crate::ed25519_program::check_id(program_id) {
+                let instruction_datas: Vec<_> = self
+                    .message()
+                    .instructions
+                    .iter()
+                    .map(|instruction| instruction.data.as_ref())
+                    .collect();
+                verify_signatures(&instruction.data, &instruction_datas)
+                    .map_err(|_| TransactionError::InvalidAccountIndex)?;
             }
         }
         Ok(())
@@ -0,0 +1,232 @@
+//! Defines a transaction which supports multiple versions of messages.
+
+use crate::{
+    hash::Hash,
+    message::{Message, VersionedMessage},
+    sanitize::{Sanitize, SanitizeError},
+    short_vec,
+    signature::{Signature, SignerError},
+    signers::Signers,
+    transaction::{Result, Transaction, TransactionError},
+};
+
+/// An atomic transaction, which can hold either a legacy or a versioned message.
+///
+/// Serialized legacy transactions are byte compatible with [`Transaction`].
+#[derive(Debug, PartialEq, Default, Eq, Clone, Serialize, Deserialize)]
+pub struct VersionedTransaction {
+    /// List of signatures
+    #[serde(with = "short_vec")]
+    pub signatures: Vec<Signature>,
+    /// Message to sign.
+    pub message: VersionedMessage,
+}
+
+impl Sanitize for VersionedTransaction {
+    fn sanitize(&self) -> std::result::Result<(), SanitizeError> {
+        self.message.sanitize()?;
+
+        let num_required_signatures = usize::from(self.message.header().num_required_signatures);
+        // Transaction may have more signatures than required, but signatures
+        // can only belong to static account keys.
+        if num_required_signatures > self.signatures.len()
+            || self.signatures.len() > self.message.static_account_keys().len()
+        {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+        Ok(())
+    }
+}
+
+impl From<Transaction> for VersionedTransaction {
+    fn from(transaction: Transaction) -> Self {
+        Self {
+            signatures: transaction.signatures,
+            message: VersionedMessage::Legacy(transaction.message),
+        }
+    }
+}
+
+impl VersionedTransaction {
+    /// Signs a versioned message and if successful, returns a signed
+    /// transaction.
+    pub fn try_new<T: Signers>(
+        message: VersionedMessage,
+        keypairs: &T,
+    ) -> std::result::Result<Self, SignerError> {
+        let num_required_signatures = usize::from(message.header().num_required_signatures);
+        let static_account_keys = message.static_account_keys();
+        if static_account_keys.len() < num_required_signatures {
+            return Err(SignerError::InvalidInput("invalid message".to_string()));
+        }
+
+        let signer_keys = keypairs.pubkeys();
+        let expected_signer_keys = &static_account_keys[0..num_required_signatures];
+        if signer_keys.len() < expected_signer_keys.len() {
+            return Err(SignerError::NotEnoughSigners);
+        } else if signer_keys.len() > expected_signer_keys.len() {
+            return Err(SignerError::TooManySigners);
+        }
+
+        // Signatures have to be ordered by the position of signer keys in the message.
+        let mut signatures = vec![Signature::default(); num_required_signatures];
+        let signed = keypairs.try_sign_message(&message.serialize())?;
+        for (signer_key, signature) in signer_keys.iter().zip(signed) {
+            let position = expected_signer_keys
+                .iter()
+                .position(|key| key == signer_key)
+                .ok_or(SignerError::KeypairPubkeyMismatch)?;
+            signatures[position] = signature;
+        }
+
+        Ok(Self {
+            signatures,
+            message,
+        })
+    }
+
+    /// Returns a legacy transaction if the transaction message is legacy.
+    pub fn into_legacy_transaction(self) -> Option<Transaction> {
+        match self.message {
+            VersionedMessage::Legacy(message) => Some(Transaction {
+                signatures: self.signatures,
+                message,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the message data that was signed.
+    pub fn message_data(&self) -> Vec<u8> {
+        self.message.serialize()
+    }
+
+    /// Verify the transaction and hash its message
+    pub fn verify_and_hash_message(&self) -> Result<Hash> {
+        let message_bytes = self.message_data();
+        if !self
+            ._verify_with_results(&message_bytes)
+            .iter()
+            .all(|verify_result| *verify_result)
+        {
+            Err(TransactionError::SignatureFailure)
+        } else {
+            Ok(Message::hash_raw_message(&message_bytes))
+        }
+    }
+
+    /// Verify each signature against the corresponding static account key
+    pub fn verify_with_results(&self) -> Vec<bool> {
+        self._verify_with_results(&self.message_data())
+    }
+
+    fn _verify_with_results(&self, message_bytes: &[u8]) -> Vec<bool> {
+        self.signatures
+            .iter()
+            .zip(self.message.static_account_keys())
+            .map(|(signature, pubkey)| signature.verify(pubkey.as_ref(), message_bytes))
+            .collect()
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.signatures
+            .iter()
+            .all(|signature| *signature != Signature::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        instruction::{AccountMeta, Instruction},
+        message::v0,
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_instruction,
+    };
+
+    #[test]
+    fn test_legacy_transaction_compatibility() {
+        let keypair = Keypair::new();
+        let to = Pubkey::new_unique();
+        let transaction = Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(&keypair.pubkey(), &to, 42)],
+            Some(&keypair.pubkey()),
+            &[&keypair],
+            Hash::new_unique(),
+        );
+
+        let versioned_transaction = VersionedTransaction::from(transaction.clone());
+        let bytes = bincode::serialize(&versioned_transaction).unwrap();
+        assert_eq!(bytes, bincode::serialize(&transaction).unwrap());
+        assert_eq!(
+            bincode::deserialize::<VersionedTransaction>(&bytes).unwrap(),
+            versioned_transaction
+        );
+        assert_eq!(
+            versioned_transaction.verify_and_hash_message(),
+            transaction.verify_and_hash_message()
+        );
+        assert_eq!(
+            versioned_transaction.into_legacy_transaction(),
+            Some(transaction)
+        );
+    }
+
+    #[test]
+    fn test_v0_transaction() {
+        let payer = Keypair::new();
+        let signer = Keypair::new();
+        let program_id = Pubkey::new_unique();
+        let legacy_message = Message::new(
+            &[Instruction::new_with_bincode(
+                program_id,
+                &0u8,
+                vec![AccountMeta::new_readonly(signer.pubkey(), true)],
+            )],
+            Some(&payer.pubkey()),
+        );
+        let message = VersionedMessage::V0(v0::Message {
+            header: legacy_message.header,
+            account_keys: legacy_message.account_keys,
+            recent_blockhash: Hash::new_unique(),
+            instructions: legacy_message.instructions,
+            address_table_lookups: vec![v0::MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![],
+            }],
+        });
+
+        // signers order doesn't matter
+        let transaction =
+            VersionedTransaction::try_new(message.clone(), &[&signer, &payer]).unwrap();
+        assert!(transaction.sanitize().is_ok());
+        assert!(transaction.is_signed());
+        assert!(transaction.verify_and_hash_message().is_ok());
+
+        let bytes = bincode::serialize(&transaction).unwrap();
+        assert_eq!(
+            bincode::deserialize::<VersionedTransaction>(&bytes).unwrap(),
+            transaction
+        );
+        assert_eq!(transaction.clone().into_legacy_transaction(), None);
+
+        let mut tampered = transaction;
+        tampered.message.set_recent_blockhash(Hash::new_unique());
+        assert_eq!(
+            tampered.verify_and_hash_message(),
+            Err(TransactionError::SignatureFailure)
+        );
+
+        assert_eq!(
+            VersionedTransaction::try_new(message.clone(), &[&payer]),
+            Err(SignerError::NotEnoughSigners)
+        );
+        assert_eq!(
+            VersionedTransaction::try_new(message, &[&payer, &Keypair::new()]),
+            Err(SignerError::KeypairPubkeyMismatch)
+        );
+    }
+}
@@ -11,7 +11,11 @@ use solana_stake_program::{stake_instruction::StakeInstruction, stake_state::Loc
 use solana_transaction_status::{
     EncodedConfirmedBlock, UiTransactionEncoding, UiTransactionStatusMeta,
 };
-use std::{collections::HashMap, thread::sleep, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    thread::sleep,
+    time::Duration,
+};
 
 pub type PubkeyString = String;
 pub type SignatureString = String;
@@ -25,6 +29,8 @@ pub enum AccountOperation {
     SystemAccountEnroll,
     FailedToMaintainMinimumBalance,
     MergeSource,
+    RedelegateSource,
+    RedelegateDestination,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -63,6 +69,14 @@ impl AccountsInfo {
             },
         );
     }
+
+    pub fn non_compliant_accounts(&self) -> HashSet<PubkeyString> {
+        self.account_info
+            .iter()
+            .filter(|(_, account_info)| account_info.compliant_since.is_none())
+            .map(|(account_address, _)| account_address.clone())
+            .collect()
+    }
 }
 
 fn process_transaction(
@@ -138,7 +152,8 @@ fn process_transaction(
                     StakeInstruction::Authorize(_, _)
                     | StakeInstruction::AuthorizeWithSeed(_)
                     | StakeInstruction::DelegateStake
-                    | StakeInstruction::Deactivate => {
+                    | StakeInstruction::Deactivate
+                    | StakeInstruction::DeactivateDelinquent => {
                         // These instructions are always permitted
                     }
                     StakeInstruction::Split(lamports) => {
@@ -177,6 +192,46 @@ fn process_transaction(
                             }
                         }
                     }
+                    StakeInstruction::Redelegate => {
+                        // Redelegate moves the active stake into a new account and propagates
+                        // compliance, like a split
+                        let source_stake_account_index = instruction.accounts[0] as usize;
+                        let redelegated_stake_account_index = instruction.accounts[1] as usize;
+
+                        let source_stake_pubkey =
+                            message.account_keys[source_stake_account_index].to_string();
+                        let redelegated_stake_pubkey =
+                            message.account_keys[redelegated_stake_account_index].to_string();
+
+                        // The amount moved is the new stake account's post balance
+                        let lamports = meta.post_balances[redelegated_stake_account_index];
+
+                        if let Some(mut source_account_info) =
+                            accounts.get_mut(&source_stake_pubkey)
+                        {
+                            if source_account_info.compliant_since.is_some() {
+                                source_account_info
+                                    .transactions
+                                    .push(AccountTransactionInfo {
+                                        op: AccountOperation::RedelegateSource,
+                                        slot,
+                                        signature: signature.clone(),
+                                    });
+                                source_account_info.lamports -= lamports;
+
+                                let redelegated_account_info = AccountInfo {
+                                    compliant_since: source_account_info.compliant_since,
+                                    lamports,
+                                    transactions: vec![AccountTransactionInfo {
+                                        op: AccountOperation::RedelegateDestination,
+                                        slot,
+                                        signature: signature.clone(),
+                                    }],
+                                };
+                                accounts.insert(redelegated_stake_pubkey, redelegated_account_info);
+                            }
+                        }
+                    }
                     StakeInstruction::Merge => {
                         // Merge invalidates the source account, but does not affect the
                         // destination account
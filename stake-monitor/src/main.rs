@@ -9,9 +9,10 @@ use solana_clap_utils::{
 };
 use solana_client::rpc_client::RpcClient;
 use solana_metrics::datapoint_error;
+use solana_notifier::{Alert, Notifier, Severity};
 use solana_sdk::{clock::Slot, native_token::lamports_to_sol, pubkey::Pubkey, system_program};
 use solana_stake_monitor::*;
-use std::{fs, io, process};
+use std::{collections::HashSet, fs, io, process};
 
 fn load_accounts_info(data_file: &str) -> AccountsInfo {
     let data_file_new = data_file.to_owned() + "new";
@@ -32,7 +33,43 @@ fn save_accounts_info(data_file: &str, accounts_info: &AccountsInfo) -> io::Resu
     fs::rename(&data_file_new, data_file)
 }
 
-fn command_record(data_file: &str, json_rpc_url: String, first_slot: Slot, batch_size: u64) {
+fn notify_compliance_changes(
+    notifier: &Notifier,
+    accounts_info: &AccountsInfo,
+    previously_non_compliant: &HashSet<PubkeyString>,
+) -> HashSet<PubkeyString> {
+    let non_compliant = accounts_info.non_compliant_accounts();
+    for account_address in non_compliant.difference(previously_non_compliant) {
+        let reason = accounts_info.account_info[account_address]
+            .transactions
+            .last()
+            .map(|transaction| format!("{:?} at slot {}", transaction.op, transaction.slot))
+            .unwrap_or_default();
+        notifier.send_alert(&Alert::firing(
+            format!("stake-monitor/{}", account_address),
+            Severity::Warning,
+            format!(
+                "solana-stake-monitor: {} is not compliant: {}",
+                account_address, reason
+            ),
+        ));
+    }
+    for account_address in previously_non_compliant.difference(&non_compliant) {
+        notifier.send_alert(&Alert::resolved(
+            format!("stake-monitor/{}", account_address),
+            format!("solana-stake-monitor: {} is compliant", account_address),
+        ));
+    }
+    non_compliant
+}
+
+fn command_record(
+    data_file: &str,
+    json_rpc_url: String,
+    first_slot: Slot,
+    batch_size: u64,
+    notifier: Notifier,
+) {
     let mut accounts_info = load_accounts_info(data_file);
 
     info!("RPC URL: {}", json_rpc_url);
@@ -41,18 +78,26 @@ fn command_record(data_file: &str, json_rpc_url: String, first_slot: Slot, batch
         accounts_info.slot = first_slot;
     }
 
+    // Only accounts that fall out of compliance while recording are notified
+    let mut non_compliant = accounts_info.non_compliant_accounts();
     loop {
         process_slots(&rpc_client, &mut accounts_info, batch_size);
-        save_accounts_info(data_file, &accounts_info).unwrap_or_else(|err| {
-            datapoint_error!(
-                "stake-monitor-failure",
-                (
-                    "err",
-                    format!("failed to save accounts_info: {}", err),
-                    String
-                )
-            );
-        });
+        non_compliant = notify_compliance_changes(&notifier, &accounts_info, &non_compliant);
+        match save_accounts_info(data_file, &accounts_info) {
+            Ok(()) => notifier.send_alert(&Alert::resolved(
+                "stake-monitor/save",
+                "solana-stake-monitor: accounts_info saved",
+            )),
+            Err(err) => {
+                let err = format!("failed to save accounts_info: {}", err);
+                datapoint_error!("stake-monitor-failure", ("err", err, String));
+                notifier.send_alert(&Alert::firing(
+                    "stake-monitor/save",
+                    Severity::Critical,
+                    format!("solana-stake-monitor: {}", err),
+                ));
+            }
+        }
     }
 }
 
@@ -173,6 +218,16 @@ fn main() {
                         .takes_value(true)
                         .default_value("10")
                         .help("Process up to this many slots in one batch"),
+                )
+                .arg(
+                    Arg::with_name("notifier_config")
+                        .long("notifier-config")
+                        .value_name("FILE")
+                        .takes_value(true)
+                        .help(
+                            "Notifier configuration file with alert channels and routing rules \
+                             [default: channels from the environment]",
+                        ),
                 ),
         )
         .subcommand(
@@ -215,7 +270,15 @@ fn main() {
         ("record", Some(matches)) => {
             let batch_size = value_t_or_exit!(matches, "batch_size", u64);
             let first_slot = value_t_or_exit!(matches, "first_slot", Slot);
-            command_record(&data_file, json_rpc_url, first_slot, batch_size);
+            let notifier = match matches.value_of("notifier_config") {
+                Some(notifier_config) => Notifier::from_config_file(notifier_config)
+                    .unwrap_or_else(|err| {
+                        eprintln!("{}", err);
+                        process::exit(1);
+                    }),
+                None => Notifier::default(),
+            };
+            command_record(&data_file, json_rpc_url, first_slot, batch_size, notifier);
         }
         ("check", Some(matches)) => {
             let account_address = pubkey_of(matches, "account_address").unwrap();
@@ -0,0 +1,293 @@
+// Storage backends for the long-term ledger archive
+
+use {
+    crate::{
+        bigtable::{
+            deserialize_bincode_cell_data, deserialize_protobuf_or_bincode_cell_data, CellData,
+            Error, Result, RowData, RowKey,
+        },
+        compression::compress_best,
+        object_storage::ObjectStorageConfig,
+    },
+    async_trait::async_trait,
+    log::*,
+    sha2::{Digest, Sha256},
+    std::{path::PathBuf, str::FromStr},
+};
+
+/// Row oriented storage, that keeps the ledger archive tables:
+///
+/// * `blocks` - native blocks by slot;
+/// * `tx` - native transaction location by signature;
+/// * `tx-by-addr` - transaction signatures by address;
+/// * `evm-blocks`, `evm-full-blocks`, `evm-blocks-by-hash` - evm blocks by number and hash;
+/// * `evm-tx` - evm receipts by transaction hash.
+///
+/// Rows of each table are ordered lexically by `RowKey`, encoding of cells is done by
+/// `LedgerStorage`, so every backend stores exactly the same data.
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// Get `table` row keys in lexical order.
+    ///
+    /// If `start_at` is provided, the row key listing will start with key.
+    /// Otherwise the listing will start from the start of the table.
+    ///
+    /// If `end_at` is provided, the row key listing will end at the key. Otherwise it will
+    /// continue until the `rows_limit` is reached or the end of the table, whichever comes first.
+    /// If `rows_limit` is zero, the listing will continue until the end of the table.
+    async fn get_row_keys(
+        &self,
+        table_name: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<RowKey>>;
+
+    /// Get data from `table` rows, bounds are the same as in `get_row_keys`.
+    async fn get_row_data(
+        &self,
+        table_name: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<(RowKey, RowData)>>;
+
+    /// Get data from a single row of `table`, returns `Error::RowNotFound` if that row does not
+    /// exist.
+    async fn get_single_row_data(&self, table_name: &str, row_key: RowKey) -> Result<RowData>;
+
+    /// Store data for one or more `table` rows, overwriting existing cells.
+    async fn put_row_data(&self, table_name: &str, row_data: &[(RowKey, RowData)]) -> Result<()>;
+}
+
+impl dyn StorageBackend {
+    pub(crate) async fn get_bincode_cell<T>(&self, table: &str, key: RowKey) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let row_data = self.get_single_row_data(table, key.clone()).await?;
+        deserialize_bincode_cell_data(&row_data, table, key)
+    }
+
+    pub(crate) async fn get_protobuf_or_bincode_cell<B, P>(
+        &self,
+        table: &str,
+        key: RowKey,
+    ) -> Result<CellData<B, P>>
+    where
+        B: serde::de::DeserializeOwned,
+        P: prost::Message + Default,
+    {
+        let row_data = self.get_single_row_data(table, key.clone()).await?;
+        deserialize_protobuf_or_bincode_cell_data(&row_data, table, key)
+    }
+
+    pub(crate) async fn put_bincode_cells<T>(
+        &self,
+        table: &str,
+        cells: &[(RowKey, T)],
+    ) -> Result<usize>
+    where
+        T: serde::ser::Serialize,
+    {
+        let mut bytes_written = 0;
+        let mut new_row_data = vec![];
+        for (row_key, data) in cells {
+            let data = compress_best(&bincode::serialize(&data).unwrap())?;
+            bytes_written += data.len();
+            new_row_data.push((row_key.clone(), vec![("bin".to_string(), data)]));
+        }
+
+        self.put_row_data(table, &new_row_data).await?;
+        Ok(bytes_written)
+    }
+
+    pub(crate) async fn put_protobuf_cells<T>(
+        &self,
+        table: &str,
+        cells: &[(RowKey, T)],
+    ) -> Result<usize>
+    where
+        T: prost::Message,
+    {
+        let mut bytes_written = 0;
+        let mut new_row_data = vec![];
+        for (row_key, data) in cells {
+            let mut buf = Vec::with_capacity(data.encoded_len());
+            data.encode(&mut buf).unwrap();
+            let data = compress_best(&buf)?;
+            bytes_written += data.len();
+            new_row_data.push((row_key.clone(), vec![("proto".to_string(), data)]));
+        }
+
+        self.put_row_data(table, &new_row_data).await?;
+        Ok(bytes_written)
+    }
+}
+
+/// Serialize row cells with bincode into a content-addressed object, returns the object hash
+/// (hex encoded sha256) and data. Rows keep only the hash, so equal rows share one object.
+pub(crate) fn row_object(row_data: &RowData) -> (String, Vec<u8>) {
+    let data = bincode::serialize(row_data).unwrap();
+    (hex::encode(Sha256::digest(&data)), data)
+}
+
+/// Object name of `hash`, objects are spread over 256 directories by the first byte of hash.
+pub(crate) fn object_name(hash: &str) -> String {
+    format!("objects/{}/{}", &hash[..2], hash)
+}
+
+/// Parse the object hash stored in a row.
+pub(crate) fn parse_row_hash(data: &[u8], table: &str, key: &str) -> Result<String> {
+    match std::str::from_utf8(data) {
+        Ok(hash) if hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            Ok(hash.to_ascii_lowercase())
+        }
+        _ => Err(Error::ObjectCorrupt(format!("{}/{}", table, key))),
+    }
+}
+
+/// Deserialize row cells from object `data`, checking that it matches the `hash`.
+pub(crate) fn decode_row_object(
+    data: &[u8],
+    hash: &str,
+    table: &str,
+    key: &str,
+) -> Result<RowData> {
+    if hex::encode(Sha256::digest(data)) != hash {
+        warn!("Object {} of {}/{} has wrong hash", hash, table, key);
+        return Err(Error::ObjectCorrupt(format!("{}/{}", table, key)));
+    }
+    bincode::deserialize(data).map_err(|err| {
+        warn!("Failed to deserialize {}/{}: {}", table, key, err);
+        Error::ObjectCorrupt(format!("{}/{}", table, key))
+    })
+}
+
+/// Check that `key` is inside of `start_at..=end_at` range.
+pub(crate) fn key_in_range(key: &str, start_at: Option<&str>, end_at: Option<&str>) -> bool {
+    start_at.map(|start_at| key >= start_at).unwrap_or(true)
+        && end_at.map(|end_at| key <= end_at).unwrap_or(true)
+}
+
+/// Convert `rows_limit` of `StorageBackend` api into optional limit.
+pub(crate) fn optional_rows_limit(rows_limit: i64) -> Option<usize> {
+    if rows_limit > 0 {
+        Some(rows_limit as usize)
+    } else {
+        None
+    }
+}
+
+/// Which backend should be used by `LedgerStorage`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LedgerStorageConfig {
+    /// Google BigTable instance, see `BigTableConnection::new` for connection settings.
+    BigTable,
+    /// Directory on local filesystem.
+    Local(PathBuf),
+    /// S3 compatible object storage (AWS S3, MinIO, ...).
+    ObjectStorage(ObjectStorageConfig),
+}
+
+impl Default for LedgerStorageConfig {
+    fn default() -> Self {
+        Self::BigTable
+    }
+}
+
+impl FromStr for LedgerStorageConfig {
+    type Err = String;
+
+    /// Parse storage location, accepted formats are:
+    ///
+    /// * `bigtable`;
+    /// * `file://PATH` or plain `PATH` to a local directory;
+    /// * `s3://BUCKET[/PREFIX]`, see `ObjectStorageConfig::from_env` for connection settings.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if s == "bigtable" {
+            return Ok(Self::BigTable);
+        }
+        if let Some(location) = s.strip_prefix("s3://") {
+            let (bucket, prefix) = match location.split_once('/') {
+                Some((bucket, prefix)) => (bucket, prefix.trim_end_matches('/')),
+                None => (location, ""),
+            };
+            if bucket.is_empty() {
+                return Err(format!("Missing bucket name in {}", s));
+            }
+            return Ok(Self::ObjectStorage(ObjectStorageConfig::from_env(
+                bucket, prefix,
+            )));
+        }
+        let path = s.strip_prefix("file://").unwrap_or(s);
+        if path.is_empty() {
+            return Err("Empty ledger storage path".to_string());
+        }
+        Ok(Self::Local(PathBuf::from(path)))
+    }
+}
+
+impl std::fmt::Display for LedgerStorageConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::BigTable => write!(f, "bigtable"),
+            Self::Local(path) => write!(f, "file://{}", path.display()),
+            Self::ObjectStorage(config) if config.prefix.is_empty() => {
+                write!(f, "s3://{}", config.bucket)
+            }
+            Self::ObjectStorage(config) => write!(f, "s3://{}/{}", config.bucket, config.prefix),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ledger_storage_config() {
+        assert_eq!(
+            "bigtable".parse::<LedgerStorageConfig>(),
+            Ok(LedgerStorageConfig::BigTable)
+        );
+        assert_eq!(
+            "file:///var/ledger-archive".parse::<LedgerStorageConfig>(),
+            Ok(LedgerStorageConfig::Local("/var/ledger-archive".into()))
+        );
+        assert_eq!(
+            "ledger-archive".parse::<LedgerStorageConfig>(),
+            Ok(LedgerStorageConfig::Local("ledger-archive".into()))
+        );
+        match "s3://archive/mainnet/".parse::<LedgerStorageConfig>() {
+            Ok(LedgerStorageConfig::ObjectStorage(config)) => {
+                assert_eq!(config.bucket, "archive");
+                assert_eq!(config.prefix, "mainnet");
+            }
+            other => panic!("Unexpected config {:?}", other),
+        }
+        assert!("s3://".parse::<LedgerStorageConfig>().is_err());
+        assert!("file://".parse::<LedgerStorageConfig>().is_err());
+
+        for config in &[
+            "bigtable",
+            "file://archive",
+            "s3://archive",
+            "s3://archive/mainnet",
+        ] {
+            assert_eq!(
+                &config.parse::<LedgerStorageConfig>().unwrap().to_string(),
+                config
+            );
+        }
+    }
+
+    #[test]
+    fn test_key_in_range() {
+        assert!(key_in_range("b", None, None));
+        assert!(key_in_range("b", Some("b"), Some("b")));
+        assert!(key_in_range("b", Some("a"), Some("c")));
+        assert!(!key_in_range("b", Some("c"), None));
+        assert!(!key_in_range("b", None, Some("a")));
+    }
+}
@@ -3,9 +3,11 @@
 use {
     crate::{
         access_token::{AccessToken, Scope},
-        compression::{compress_best, decompress},
+        backend::StorageBackend,
+        compression::decompress,
         root_ca_certificate,
     },
+    async_trait::async_trait,
     log::*,
     std::time::{Duration, Instant},
     thiserror::Error,
@@ -74,6 +76,9 @@ pub enum Error {
     #[error("RPC error: {0}")]
     RpcError(tonic::Status),
 
+    #[error("Object storage error: {0}")]
+    ObjectStorageError(String),
+
     #[error("Timeout error")]
     TimeoutError,
 }
@@ -208,38 +213,6 @@ impl BigTableConnection {
             timeout: self.timeout,
         }
     }
-
-    pub async fn put_bincode_cells_with_retry<T>(
-        &self,
-        table: &str,
-        cells: &[(RowKey, T)],
-    ) -> Result<usize>
-    where
-        T: serde::ser::Serialize,
-    {
-        use backoff::{future::retry, ExponentialBackoff};
-        retry(ExponentialBackoff::default(), || async {
-            let mut client = self.client();
-            Ok(client.put_bincode_cells(table, cells).await?)
-        })
-        .await
-    }
-
-    pub async fn put_protobuf_cells_with_retry<T>(
-        &self,
-        table: &str,
-        cells: &[(RowKey, T)],
-    ) -> Result<usize>
-    where
-        T: prost::Message,
-    {
-        use backoff::{future::retry, ExponentialBackoff};
-        retry(ExponentialBackoff::default(), || async {
-            let mut client = self.client();
-            Ok(client.put_protobuf_cells(table, cells).await?)
-        })
-        .await
-    }
 }
 
 pub struct BigTable<F: FnMut(Request<()>) -> InterceptedRequestResult> {
@@ -533,68 +506,49 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
 
         Ok(())
     }
+}
 
-    pub async fn get_bincode_cell<T>(&mut self, table: &str, key: RowKey) -> Result<T>
-    where
-        T: serde::de::DeserializeOwned,
-    {
-        let row_data = self.get_single_row_data(table, key.clone()).await?;
-        deserialize_bincode_cell_data(&row_data, table, key.to_string())
+#[async_trait]
+impl StorageBackend for BigTableConnection {
+    async fn get_row_keys(
+        &self,
+        table_name: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<RowKey>> {
+        self.client()
+            .get_row_keys(table_name, start_at, end_at, rows_limit)
+            .await
     }
 
-    pub async fn get_protobuf_or_bincode_cell<B, P>(
-        &mut self,
-        table: &str,
-        key: RowKey,
-    ) -> Result<CellData<B, P>>
-    where
-        B: serde::de::DeserializeOwned,
-        P: prost::Message + Default,
-    {
-        let row_data = self.get_single_row_data(table, key.clone()).await?;
-        deserialize_protobuf_or_bincode_cell_data(&row_data, table, key)
+    async fn get_row_data(
+        &self,
+        table_name: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<(RowKey, RowData)>> {
+        self.client()
+            .get_row_data(table_name, start_at, end_at, rows_limit)
+            .await
     }
 
-    pub async fn put_bincode_cells<T>(
-        &mut self,
-        table: &str,
-        cells: &[(RowKey, T)],
-    ) -> Result<usize>
-    where
-        T: serde::ser::Serialize,
-    {
-        let mut bytes_written = 0;
-        let mut new_row_data = vec![];
-        for (row_key, data) in cells {
-            let data = compress_best(&bincode::serialize(&data).unwrap())?;
-            bytes_written += data.len();
-            new_row_data.push((row_key, vec![("bin".to_string(), data)]));
-        }
-
-        self.put_row_data(table, "x", &new_row_data).await?;
-        Ok(bytes_written)
+    async fn get_single_row_data(&self, table_name: &str, row_key: RowKey) -> Result<RowData> {
+        self.client().get_single_row_data(table_name, row_key).await
     }
 
-    pub async fn put_protobuf_cells<T>(
-        &mut self,
-        table: &str,
-        cells: &[(RowKey, T)],
-    ) -> Result<usize>
-    where
-        T: prost::Message,
-    {
-        let mut bytes_written = 0;
-        let mut new_row_data = vec![];
-        for (row_key, data) in cells {
-            let mut buf = Vec::with_capacity(data.encoded_len());
-            data.encode(&mut buf).unwrap();
-            let data = compress_best(&buf)?;
-            bytes_written += data.len();
-            new_row_data.push((row_key, vec![("proto".to_string(), data)]));
-        }
-
-        self.put_row_data(table, "x", &new_row_data).await?;
-        Ok(bytes_written)
+    async fn put_row_data(&self, table_name: &str, row_data: &[(RowKey, RowData)]) -> Result<()> {
+        use backoff::{future::retry, ExponentialBackoff};
+        let row_data: Vec<_> = row_data
+            .iter()
+            .map(|(row_key, row_data)| (row_key, row_data.clone()))
+            .collect();
+        retry(ExponentialBackoff::default(), || async {
+            let mut client = self.client();
+            Ok(client.put_row_data(table_name, "x", &row_data).await?)
+        })
+        .await
     }
 }
 
@@ -677,7 +631,7 @@ mod tests {
         let recipient = solana_sdk::pubkey::new_rand();
         let transaction = system_transaction::transfer(&from, &recipient, 42, Hash::default());
         let with_meta = TransactionWithStatusMeta {
-            transaction,
+            transaction: transaction.into(),
             meta: Some(TransactionStatusMeta {
                 status: Ok(()),
                 fee: 1,
@@ -688,6 +642,8 @@ mod tests {
                 pre_token_balances: Some(vec![]),
                 post_token_balances: Some(vec![]),
                 rewards: Some(vec![]),
+                return_data: None,
+                loaded_addresses: None,
             }),
         };
         let block = ConfirmedBlock {
@@ -7,7 +7,7 @@ use solana_sdk::{
     pubkey::Pubkey,
     signature::Signature,
     sysvar::is_sysvar_id,
-    transaction::{Transaction, TransactionError},
+    transaction::{TransactionError, VersionedTransaction},
 };
 use solana_storage_proto::convert::generated;
 use solana_storage_proto::convert::generated_evm;
@@ -17,21 +17,31 @@ use solana_transaction_status::{
     TransactionByAddrInfo, TransactionConfirmationStatus, TransactionStatus, TransactionStatusMeta,
     TransactionWithStatusMeta,
 };
-use std::{collections::HashMap, convert::TryInto};
+use std::{collections::HashMap, convert::TryInto, sync::Arc};
 use thiserror::Error;
 
 #[macro_use]
 extern crate serde_derive;
 
 mod access_token;
+mod backend;
 #[allow(clippy::enum_variant_names)]
 mod bigtable;
 mod compression;
+mod local_storage;
+mod object_storage;
 mod root_ca_certificate;
 
+pub use {
+    backend::{LedgerStorageConfig, StorageBackend},
+    bigtable::{CellName, CellValue, Error as StorageBackendError, RowData, RowKey},
+    local_storage::LocalStorage,
+    object_storage::{ObjectStorage, ObjectStorageConfig, ObjectStorageCredentials},
+};
+
 #[derive(Debug, Error)]
 pub enum Error {
-    #[error("BigTable: {0}")]
+    #[error("Storage: {0}")]
     BigTableError(bigtable::Error),
 
     #[error("I/O Error: {0}")]
@@ -150,7 +160,7 @@ impl From<StoredConfirmedBlock> for ConfirmedBlock {
 
 #[derive(Serialize, Deserialize)]
 struct StoredConfirmedBlockTransaction {
-    transaction: Transaction,
+    transaction: VersionedTransaction,
     meta: Option<StoredConfirmedBlockTransactionStatusMeta>,
 }
 
@@ -202,6 +212,8 @@ impl From<StoredConfirmedBlockTransactionStatusMeta> for TransactionStatusMeta {
             pre_token_balances: None,
             post_token_balances: None,
             rewards: None,
+            return_data: None,
+            loaded_addresses: None,
         }
     }
 }
@@ -306,22 +318,45 @@ impl From<LegacyTransactionByAddrInfo> for TransactionByAddrInfo {
     }
 }
 
+/// Long-term ledger archive, keeps native and evm blocks with their indexes in one of
+/// `StorageBackend`s.
 #[derive(Clone)]
 pub struct LedgerStorage {
-    connection: bigtable::BigTableConnection,
+    backend: Arc<dyn StorageBackend>,
 }
 
 impl LedgerStorage {
+    /// Connect to BigTable instance, see `BigTableConnection::new` for connection settings.
     pub async fn new(read_only: bool, timeout: Option<std::time::Duration>) -> Result<Self> {
         let connection =
             bigtable::BigTableConnection::new("solana-ledger", read_only, timeout).await?;
-        Ok(Self { connection })
+        Ok(Self::new_with_backend(Arc::new(connection)))
+    }
+
+    pub async fn new_with_config(
+        config: LedgerStorageConfig,
+        read_only: bool,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<Self> {
+        match config {
+            LedgerStorageConfig::BigTable => Self::new(read_only, timeout).await,
+            LedgerStorageConfig::Local(path) => {
+                Ok(Self::new_with_backend(Arc::new(LocalStorage::new(path)?)))
+            }
+            LedgerStorageConfig::ObjectStorage(config) => Ok(Self::new_with_backend(Arc::new(
+                ObjectStorage::new(config, timeout)?,
+            ))),
+        }
+    }
+
+    pub fn new_with_backend(backend: Arc<dyn StorageBackend>) -> Self {
+        Self { backend }
     }
 
     /// Return the available slot that contains a block
     pub async fn get_first_available_block(&self) -> Result<Option<Slot>> {
-        let mut bigtable = self.connection.client();
-        let blocks = bigtable.get_row_keys("blocks", None, None, 1).await?;
+        let storage = self.backend.as_ref();
+        let blocks = storage.get_row_keys("blocks", None, None, 1).await?;
         if blocks.is_empty() {
             return Ok(None);
         }
@@ -334,8 +369,8 @@ impl LedgerStorage {
     /// limit: stop after this many slots have been found; if limit==0, all records in the table
     /// after start_slot will be read
     pub async fn get_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>> {
-        let mut bigtable = self.connection.client();
-        let blocks = bigtable
+        let storage = self.backend.as_ref();
+        let blocks = storage
             .get_row_keys("blocks", Some(slot_to_key(start_slot)), None, limit as i64)
             .await?;
         Ok(blocks.into_iter().filter_map(|s| key_to_slot(&s)).collect())
@@ -343,8 +378,8 @@ impl LedgerStorage {
 
     /// Fetch the confirmed block from the desired slot
     pub async fn get_confirmed_block(&self, slot: Slot) -> Result<ConfirmedBlock> {
-        let mut bigtable = self.connection.client();
-        let block_cell_data = bigtable
+        let storage = self.backend.as_ref();
+        let block_cell_data = storage
             .get_protobuf_or_bincode_cell::<StoredConfirmedBlock, generated::ConfirmedBlock>(
                 "blocks",
                 slot_to_key(slot),
@@ -364,16 +399,16 @@ impl LedgerStorage {
 
     /// Fetch the confirmed block from the desired slot
     pub async fn get_confirmed_block_hash(&self, slot: Slot) -> Result<String> {
-        let mut bigtable = self.connection.client();
-        let block = bigtable
+        let storage = self.backend.as_ref();
+        let block = storage
             .get_bincode_cell::<StoredConfirmedBlock>("blocks", slot_to_key(slot))
             .await?;
         Ok(block.blockhash)
     }
 
     pub async fn get_signature_status(&self, signature: &Signature) -> Result<TransactionStatus> {
-        let mut bigtable = self.connection.client();
-        let transaction_info = bigtable
+        let storage = self.backend.as_ref();
+        let transaction_info = storage
             .get_bincode_cell::<TransactionInfo>("tx", signature.to_string())
             .await
             .map_err(|err| match err {
@@ -388,10 +423,10 @@ impl LedgerStorage {
         &self,
         signature: &Signature,
     ) -> Result<Option<ConfirmedTransaction>> {
-        let mut bigtable = self.connection.client();
+        let storage = self.backend.as_ref();
 
         // Figure out which block the transaction is located in
-        let TransactionInfo { slot, index, .. } = bigtable
+        let TransactionInfo { slot, index, .. } = storage
             .get_bincode_cell("tx", signature.to_string())
             .await
             .map_err(|err| match err {
@@ -442,14 +477,14 @@ impl LedgerStorage {
             u32, /*slot index*/
         )>,
     > {
-        let mut bigtable = self.connection.client();
+        let storage = self.backend.as_ref();
         let address_prefix = format!("{}/", address);
 
         // Figure out where to start listing from based on `before_signature`
         let (first_slot, before_transaction_index) = match before_signature {
             None => (Slot::MAX, 0),
             Some(before_signature) => {
-                let TransactionInfo { slot, index, .. } = bigtable
+                let TransactionInfo { slot, index, .. } = storage
                     .get_bincode_cell("tx", before_signature.to_string())
                     .await?;
 
@@ -461,7 +496,7 @@ impl LedgerStorage {
         let (last_slot, until_transaction_index) = match until_signature {
             None => (0, u32::MAX),
             Some(until_signature) => {
-                let TransactionInfo { slot, index, .. } = bigtable
+                let TransactionInfo { slot, index, .. } = storage
                     .get_bincode_cell("tx", until_signature.to_string())
                     .await?;
 
@@ -471,7 +506,7 @@ impl LedgerStorage {
 
         let mut infos = vec![];
 
-        let starting_slot_tx_len = bigtable
+        let starting_slot_tx_len = storage
             .get_protobuf_or_bincode_cell::<Vec<LegacyTransactionByAddrInfo>, tx_by_addr::TransactionByAddr>(
                 "tx-by-addr",
                 format!("{}{}", address_prefix, slot_to_key(!first_slot)),
@@ -487,7 +522,7 @@ impl LedgerStorage {
 
         // Return the next tx-by-addr data of amount `limit` plus extra to account for the largest
         // number that might be flitered out
-        let tx_by_addr_data = bigtable
+        let tx_by_addr_data = storage
             .get_row_data(
                 "tx-by-addr",
                 Some(format!("{}{}", address_prefix, slot_to_key(!first_slot))),
@@ -570,7 +605,18 @@ impl LedgerStorage {
             let index = index as u32;
             let signature = transaction.signatures[0];
 
-            for address in &transaction.message.account_keys {
+            let loaded_addresses = meta
+                .as_ref()
+                .and_then(|meta| meta.loaded_addresses.as_ref());
+            let account_keys = transaction.message.static_account_keys().iter().chain(
+                loaded_addresses.into_iter().flat_map(|loaded_addresses| {
+                    loaded_addresses
+                        .writable
+                        .iter()
+                        .chain(&loaded_addresses.readonly)
+                }),
+            );
+            for address in account_keys {
                 if !is_sysvar_id(address) {
                     by_addr
                         .entry(address)
@@ -613,15 +659,15 @@ impl LedgerStorage {
 
         if !tx_cells.is_empty() {
             bytes_written += self
-                .connection
-                .put_bincode_cells_with_retry::<TransactionInfo>("tx", &tx_cells)
+                .backend
+                .put_bincode_cells::<TransactionInfo>("tx", &tx_cells)
                 .await?;
         }
 
         if !tx_by_addr_cells.is_empty() {
             bytes_written += self
-                .connection
-                .put_protobuf_cells_with_retry::<tx_by_addr::TransactionByAddr>(
+                .backend
+                .put_protobuf_cells::<tx_by_addr::TransactionByAddr>(
                     "tx-by-addr",
                     &tx_by_addr_cells,
                 )
@@ -635,8 +681,8 @@ impl LedgerStorage {
         // `get_confirmed_block()` and `get_confirmed_blocks()`
         let blocks_cells = [(slot_to_key(slot), confirmed_block.into())];
         bytes_written += self
-            .connection
-            .put_protobuf_cells_with_retry::<generated::ConfirmedBlock>("blocks", &blocks_cells)
+            .backend
+            .put_protobuf_cells::<generated::ConfirmedBlock>("blocks", &blocks_cells)
             .await?;
         info!(
             "uploaded block for slot {}: {} transactions, {} bytes",
@@ -652,8 +698,8 @@ impl LedgerStorage {
 
     /// Return the available slot that contains a block
     pub async fn get_evm_first_available_block(&self) -> Result<Option<evm_state::BlockNum>> {
-        let mut bigtable = self.connection.client();
-        let blocks = bigtable.get_row_keys("evm-blocks", None, None, 1).await?;
+        let storage = self.backend.as_ref();
+        let blocks = storage.get_row_keys("evm-blocks", None, None, 1).await?;
         if blocks.is_empty() {
             return Ok(None);
         }
@@ -670,8 +716,8 @@ impl LedgerStorage {
         start_block: evm_state::BlockNum,
         limit: usize,
     ) -> Result<Vec<evm_state::BlockNum>> {
-        let mut bigtable = self.connection.client();
-        let blocks = bigtable
+        let storage = self.backend.as_ref();
+        let blocks = storage
             .get_row_keys(
                 "evm-blocks",
                 Some(slot_to_key(start_block)),
@@ -686,8 +732,8 @@ impl LedgerStorage {
         &self,
         block_hash: evm_state::H256,
     ) -> Result<evm_state::BlockNum> {
-        let mut bigtable = self.connection.client();
-        let block = bigtable
+        let storage = self.backend.as_ref();
+        let block = storage
             .get_bincode_cell::<u64>("evm-blocks-by-hash", evm_rpc::Hex(block_hash).to_string())
             .await?;
         Ok(block)
@@ -698,8 +744,8 @@ impl LedgerStorage {
         &self,
         block_num: evm_state::BlockNum,
     ) -> Result<evm_state::BlockHeader> {
-        let mut bigtable = self.connection.client();
-        let block_cell_data = bigtable
+        let storage = self.backend.as_ref();
+        let block_cell_data = storage
             .get_protobuf_or_bincode_cell::<evm_state::BlockHeader, generated_evm::EvmBlockHeader>(
                 "evm-block",
                 slot_to_key(block_num),
@@ -721,28 +767,28 @@ impl LedgerStorage {
         first_block: evm_state::BlockNum,
         last_block: evm_state::BlockNum,
     ) -> Result<Vec<evm_state::Block>> {
-        let mut bigtable = self.connection.client();
+        let storage = self.backend.as_ref();
 
         // Bigtable only allow requests where start_key strictly less then end_key.
         // Special case for requesting single row.
 
         let first_block_key = slot_to_key(first_block);
         let evm_full_blocks = if first_block == last_block {
-            trace!("Requesting single block from bigtable {}", first_block_key);
+            trace!("Requesting single block from storage {}", first_block_key);
             vec![(
                 first_block_key.clone(),
-                bigtable
+                storage
                     .get_single_row_data("evm-full-blocks", first_block_key)
                     .await?,
             )]
         } else {
             let last_block = slot_to_key(last_block);
             trace!(
-                "Requesting multiple blocks from bigtable {}..{}",
+                "Requesting multiple blocks from storage {}..{}",
                 first_block_key,
                 last_block
             );
-            bigtable
+            storage
                 .get_row_data(
                     "evm-full-blocks",
                     Some(first_block_key),
@@ -778,8 +824,8 @@ impl LedgerStorage {
         &self,
         block_num: evm_state::BlockNum,
     ) -> Result<evm_state::Block> {
-        let mut bigtable = self.connection.client();
-        let block_cell_data = bigtable
+        let storage = self.backend.as_ref();
+        let block_cell_data = storage
             .get_protobuf_or_bincode_cell::<evm_state::Block, generated_evm::EvmFullBlock>(
                 "evm-full-blocks",
                 slot_to_key(block_num),
@@ -805,10 +851,10 @@ impl LedgerStorage {
         &self,
         hash: &evm_state::H256,
     ) -> Result<Option<evm_state::TransactionReceipt>> {
-        let mut bigtable = self.connection.client();
+        let storage = self.backend.as_ref();
 
         // Figure out which block the transaction is located in
-        let tx_cell = bigtable
+        let tx_cell = storage
             .get_protobuf_or_bincode_cell::<evm_state::TransactionReceipt, generated_evm::TransactionReceipt>(
                 "evm-tx",
                 evm_rpc::Hex(*hash).to_string(),
@@ -851,10 +897,8 @@ impl LedgerStorage {
 
         if !tx_cells.is_empty() {
             bytes_written += self
-                .connection
-                .put_protobuf_cells_with_retry::<generated_evm::TransactionReceipt>(
-                    "evm-tx", &tx_cells,
-                )
+                .backend
+                .put_protobuf_cells::<generated_evm::TransactionReceipt>("evm-tx", &tx_cells)
                 .await?;
         }
 
@@ -864,17 +908,14 @@ impl LedgerStorage {
             full_block.header.block_number,
         )];
         bytes_written += self
-            .connection
-            .put_bincode_cells_with_retry("evm-blocks-by-hash", &block_by_hash_cells)
+            .backend
+            .put_bincode_cells("evm-blocks-by-hash", &block_by_hash_cells)
             .await?;
 
         let block_header_cells = [(slot_to_key(block_num), full_block.header.clone().into())];
         bytes_written += self
-            .connection
-            .put_protobuf_cells_with_retry::<generated_evm::EvmBlockHeader>(
-                "evm-blocks",
-                &block_header_cells,
-            )
+            .backend
+            .put_protobuf_cells::<generated_evm::EvmBlockHeader>("evm-blocks", &block_header_cells)
             .await?;
         let blocks_cells = [(slot_to_key(block_num), full_block.into())];
 
@@ -882,11 +923,8 @@ impl LedgerStorage {
         // successfully stored.  This avoids partial uploaded blocks from becoming visible to
         // `get_confirmed_block()` and `get_confirmed_blocks()`
         bytes_written += self
-            .connection
-            .put_protobuf_cells_with_retry::<generated_evm::EvmFullBlock>(
-                "evm-full-blocks",
-                &blocks_cells,
-            )
+            .backend
+            .put_protobuf_cells::<generated_evm::EvmFullBlock>("evm-full-blocks", &blocks_cells)
             .await?;
         info!(
             "uploaded block num {}: {} transactions, {} bytes",
@@ -899,11 +937,106 @@ impl LedgerStorage {
 
 #[cfg(test)]
 mod test {
-    use super::*;
+    use {
+        super::*,
+        solana_sdk::{hash::Hash, signature::Keypair, system_transaction},
+    };
 
     #[test]
     fn test_slot_to_key() {
         assert_eq!(slot_to_key(0), "0000000000000000");
         assert_eq!(slot_to_key(!0), "ffffffffffffffff");
     }
+
+    #[tokio::test]
+    async fn test_local_ledger_storage() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let storage = LedgerStorage::new_with_config(
+            LedgerStorageConfig::Local(dir.path().to_path_buf()),
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(storage.get_first_available_block().await.unwrap(), None);
+
+        let from = Keypair::new();
+        let recipient = solana_sdk::pubkey::new_rand();
+        let make_block = |slot: Slot| {
+            let transaction =
+                system_transaction::transfer(&from, &recipient, slot, Hash::new_unique());
+            ConfirmedBlock {
+                transactions: vec![TransactionWithStatusMeta {
+                    transaction: transaction.into(),
+                    meta: Some(TransactionStatusMeta {
+                        status: Ok(()),
+                        fee: 1,
+                        pre_balances: vec![slot + 1, 0, 1],
+                        post_balances: vec![0, slot, 1],
+                        inner_instructions: Some(vec![]),
+                        log_messages: Some(vec![]),
+                        pre_token_balances: Some(vec![]),
+                        post_token_balances: Some(vec![]),
+                        rewards: Some(vec![]),
+                        return_data: None,
+                        loaded_addresses: None,
+                    }),
+                }],
+                parent_slot: slot.saturating_sub(1),
+                blockhash: Hash::new_unique().to_string(),
+                previous_blockhash: Hash::new_unique().to_string(),
+                rewards: vec![],
+                block_time: Some(1_234_567_890),
+                block_height: Some(slot),
+            }
+        };
+        let blocks: Vec<_> = (1..=3).map(|slot| (slot, make_block(slot))).collect();
+        for (slot, block) in &blocks {
+            storage
+                .upload_confirmed_block(*slot, block.clone())
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(storage.get_first_available_block().await.unwrap(), Some(1));
+        assert_eq!(
+            storage.get_confirmed_blocks(2, 0).await.unwrap(),
+            vec![2, 3]
+        );
+        assert_eq!(storage.get_confirmed_block(2).await.unwrap(), blocks[1].1);
+        assert!(matches!(
+            storage.get_confirmed_block(4).await,
+            Err(Error::BlockNotFound(4))
+        ));
+
+        let signature = blocks[2].1.transactions[0].transaction.signatures[0];
+        assert_eq!(
+            storage.get_signature_status(&signature).await.unwrap().slot,
+            3
+        );
+        let transaction = storage
+            .get_confirmed_transaction(&signature)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(transaction.slot, 3);
+        assert_eq!(transaction.transaction, blocks[2].1.transactions[0]);
+
+        // Signatures are returned from newest to oldest
+        let signatures: Vec<_> = storage
+            .get_confirmed_signatures_for_address(&recipient, None, None, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(status, _index)| (status.slot, status.signature))
+            .collect();
+        assert_eq!(
+            signatures,
+            blocks
+                .iter()
+                .rev()
+                .map(|(slot, block)| (*slot, block.transactions[0].transaction.signatures[0]))
+                .collect::<Vec<_>>()
+        );
+    }
 }
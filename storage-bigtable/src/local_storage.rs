@@ -0,0 +1,504 @@
+// Ledger archive stored in a local directory
+
+use {
+    crate::{
+        backend::{
+            decode_row_object, key_in_range, object_name, optional_rows_limit, parse_row_hash,
+            row_object, StorageBackend,
+        },
+        bigtable::{Error, Result, RowData, RowKey},
+    },
+    async_trait::async_trait,
+    log::*,
+    std::{
+        fs,
+        io::{self, ErrorKind},
+        path::{Path, PathBuf},
+    },
+};
+
+// Last components of row keys up to this length, like slot keys, are grouped into buckets
+const MAX_BUCKETED_KEY_LEN: usize = 16;
+// Bucket of slot keys holds up to 65536 consecutive slots
+const BUCKET_PREFIX_LEN: usize = 12;
+
+/// Stores the cells of every row, serialized by bincode, as a content-addressed object
+/// `<root>/objects/<aa>/<sha256>`, and the row itself as a file `<root>/tables/<table>/<row key>`
+/// holding the hash of that object. Cell values are already compressed by `LedgerStorage`.
+/// Objects are checked against their hash on every read.
+///
+/// Row keys containing '/' (`tx-by-addr` table) are split into nested directories, so listing
+/// signatures of a single address touches only the directory of that address. Short keys, like
+/// slots, are grouped into `.<first 12 characters>` bucket directories, so listing a range of
+/// slots reads only the buckets of that range instead of the whole table.
+///
+/// Filesystem calls are blocking, so they run on the blocking thread pool of tokio.
+#[derive(Debug, Clone)]
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        fs::create_dir_all(&root)?;
+        info!("Using local ledger storage at {}", root.display());
+        Ok(Self { root })
+    }
+
+    fn row_path(&self, table_name: &str, row_key: &str) -> Result<PathBuf> {
+        let mut path = self.root.join("tables").join(table_name);
+        let mut components = row_key.split('/').peekable();
+        while let Some(component) = components.next() {
+            if component.is_empty() || component.starts_with('.') {
+                return Err(Error::IoError(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Invalid row key {}/{}", table_name, row_key),
+                )));
+            }
+            if components.peek().is_none() && component.len() <= MAX_BUCKETED_KEY_LEN {
+                if let Some(bucket) = component
+                    .get(..BUCKET_PREFIX_LEN)
+                    .filter(|bucket| bucket.len() < component.len())
+                {
+                    path.push(format!(".{}", bucket));
+                }
+            }
+            path.push(component);
+        }
+        Ok(path)
+    }
+
+    /// Write `data` into `path` through a temporary file, so readers never observe partial
+    /// writes.
+    fn write_file(path: &Path, data: &[u8]) -> Result<()> {
+        let dir = path.parent().expect("path has parent directory");
+        fs::create_dir_all(dir)?;
+        let tmp_path = dir.join(format!(
+            ".{}.tmp",
+            path.file_name().unwrap().to_string_lossy()
+        ));
+        fs::write(&tmp_path, data)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    fn read_row(&self, table_name: &str, row_key: &str) -> Result<RowData> {
+        let path = self.row_path(table_name, row_key)?;
+        let data = fs::read(path).map_err(|err| match err.kind() {
+            ErrorKind::NotFound => Error::RowNotFound,
+            _ => err.into(),
+        })?;
+        let hash = parse_row_hash(&data, table_name, row_key)?;
+        let data = fs::read(self.root.join(object_name(&hash))).map_err(|err| {
+            warn!(
+                "Failed to read object {} of {}/{}: {}",
+                hash, table_name, row_key, err
+            );
+            match err.kind() {
+                ErrorKind::NotFound => Error::ObjectNotFound(format!("{}/{}", table_name, row_key)),
+                _ => err.into(),
+            }
+        })?;
+        decode_row_object(&data, &hash, table_name, row_key)
+    }
+
+    /// Write objects before the rows referencing them, rows with the same cells share objects.
+    fn write_rows(&self, rows: Vec<(PathBuf, String, Vec<u8>)>) -> Result<()> {
+        for (path, hash, data) in rows {
+            let object_path = self.root.join(object_name(&hash));
+            if !object_path.exists() {
+                Self::write_file(&object_path, &data)?;
+            }
+            Self::write_file(&path, hash.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn list_row_keys(
+        &self,
+        table_name: &str,
+        start_at: Option<&str>,
+        end_at: Option<&str>,
+        rows_limit: Option<usize>,
+    ) -> Result<Vec<RowKey>> {
+        let table_path = self.root.join("tables").join(table_name);
+        if !table_path.exists() {
+            return Ok(vec![]);
+        }
+        collect_row_keys(
+            &table_path,
+            "",
+            start_at,
+            end_at,
+            rows_limit.unwrap_or(usize::MAX),
+        )
+    }
+
+    /// Run `f` on the blocking thread pool, outside of the async runtime threads.
+    async fn run_blocking<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(LocalStorage) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let storage = self.clone();
+        tokio::task::spawn_blocking(move || f(storage))
+            .await
+            .map_err(|err| Error::IoError(io::Error::new(ErrorKind::Other, err)))?
+    }
+}
+
+/// Walk `dir` in lexical order of row keys, and collect up to `rows_limit` keys from
+/// `start_at..=end_at` range.
+fn collect_row_keys(
+    dir: &Path,
+    key_prefix: &str,
+    start_at: Option<&str>,
+    end_at: Option<&str>,
+    rows_limit: usize,
+) -> Result<Vec<RowKey>> {
+    let mut entries = vec![];
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = match entry.file_name().into_string() {
+            Ok(name) => name,
+            Err(name) => {
+                warn!("Skipping non UTF-8 entry {:?} in {}", name, dir.display());
+                continue;
+            }
+        };
+        let is_dir = entry.file_type()?.is_dir();
+        // Every entry covers the keys starting with its prefix, and is ordered as such
+        let prefix = match (is_dir, name.strip_prefix('.')) {
+            // Bucket of keys starting with its name
+            (true, Some(bucket)) => format!("{}{}", key_prefix, bucket),
+            // Unfinished write
+            (false, Some(_)) => continue,
+            // All keys from nested directory starts with "<name>/"
+            (true, None) => format!("{}{}/", key_prefix, name),
+            (false, None) => format!("{}{}", key_prefix, name),
+        };
+        entries.push((prefix, is_dir, entry.path()));
+    }
+    entries.sort_unstable();
+
+    let mut row_keys = vec![];
+    let mut entries = entries.into_iter().peekable();
+    while let Some((prefix, is_dir, path)) = entries.next() {
+        if row_keys.len() >= rows_limit {
+            break;
+        }
+        if end_at
+            .map(|end_at| prefix.as_str() > end_at)
+            .unwrap_or(false)
+        {
+            break;
+        }
+        if !is_dir {
+            if key_in_range(&prefix, start_at, end_at) {
+                row_keys.push(prefix);
+            }
+            continue;
+        }
+        // Skip directory if all of its keys are before `start_at`.
+        if start_at
+            .map(|start_at| start_at > prefix.as_str() && !start_at.starts_with(&prefix))
+            .unwrap_or(false)
+        {
+            continue;
+        }
+        let remaining = rows_limit - row_keys.len();
+        let mut keys = collect_row_keys(&path, &prefix, start_at, end_at, remaining)?;
+        // Keys longer than bucketed ones may start with the prefix of a bucket, they are listed
+        // right after it and are merged with its keys.
+        let mut merged = false;
+        while let Some((next_prefix, next_is_dir, next_path)) =
+            entries.next_if(|(next_prefix, ..)| next_prefix.starts_with(&prefix))
+        {
+            merged = true;
+            if next_is_dir {
+                keys.extend(collect_row_keys(
+                    &next_path,
+                    &next_prefix,
+                    start_at,
+                    end_at,
+                    remaining,
+                )?);
+            } else if key_in_range(&next_prefix, start_at, end_at) {
+                keys.push(next_prefix);
+            }
+        }
+        if merged {
+            keys.sort_unstable();
+            keys.truncate(remaining);
+        }
+        row_keys.extend(keys);
+    }
+    Ok(row_keys)
+}
+
+#[async_trait]
+impl StorageBackend for LocalStorage {
+    async fn get_row_keys(
+        &self,
+        table_name: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<RowKey>> {
+        let table_name = table_name.to_string();
+        self.run_blocking(move |storage| {
+            storage.list_row_keys(
+                &table_name,
+                start_at.as_deref(),
+                end_at.as_deref(),
+                optional_rows_limit(rows_limit),
+            )
+        })
+        .await
+    }
+
+    async fn get_row_data(
+        &self,
+        table_name: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<(RowKey, RowData)>> {
+        let table_name = table_name.to_string();
+        self.run_blocking(move |storage| {
+            storage
+                .list_row_keys(
+                    &table_name,
+                    start_at.as_deref(),
+                    end_at.as_deref(),
+                    optional_rows_limit(rows_limit),
+                )?
+                .into_iter()
+                .map(|row_key| {
+                    let row_data = storage.read_row(&table_name, &row_key)?;
+                    Ok((row_key, row_data))
+                })
+                .collect()
+        })
+        .await
+    }
+
+    async fn get_single_row_data(&self, table_name: &str, row_key: RowKey) -> Result<RowData> {
+        let table_name = table_name.to_string();
+        self.run_blocking(move |storage| storage.read_row(&table_name, &row_key))
+            .await
+    }
+
+    async fn put_row_data(&self, table_name: &str, row_data: &[(RowKey, RowData)]) -> Result<()> {
+        let rows = row_data
+            .iter()
+            .map(|(row_key, row_data)| {
+                let path = self.row_path(table_name, row_key)?;
+                let (hash, data) = row_object(row_data);
+                Ok((path, hash, data))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.run_blocking(move |storage| storage.write_rows(rows))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::slot_to_key};
+
+    fn row(value: &str) -> RowData {
+        vec![("bin".to_string(), value.as_bytes().to_vec())]
+    }
+
+    #[tokio::test]
+    async fn test_local_storage_rows() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let storage = LocalStorage::new(dir.path()).unwrap();
+
+        let rows: Vec<_> = (0..10)
+            .map(|slot| (slot_to_key(slot), row(&slot.to_string())))
+            .collect();
+        storage.put_row_data("blocks", &rows).await.unwrap();
+
+        assert_eq!(
+            storage
+                .get_single_row_data("blocks", slot_to_key(3))
+                .await
+                .unwrap(),
+            row("3")
+        );
+        assert!(matches!(
+            storage.get_single_row_data("blocks", slot_to_key(10)).await,
+            Err(Error::RowNotFound)
+        ));
+        assert!(matches!(
+            storage.get_single_row_data("tx", slot_to_key(1)).await,
+            Err(Error::RowNotFound)
+        ));
+
+        let all_keys = storage.get_row_keys("blocks", None, None, 0).await.unwrap();
+        assert_eq!(
+            all_keys,
+            rows.iter().map(|(key, _)| key.clone()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            storage
+                .get_row_keys("blocks", Some(slot_to_key(7)), None, 2)
+                .await
+                .unwrap(),
+            vec![slot_to_key(7), slot_to_key(8)]
+        );
+        assert_eq!(
+            storage
+                .get_row_data("blocks", Some(slot_to_key(2)), Some(slot_to_key(4)), 0)
+                .await
+                .unwrap(),
+            rows[2..=4].to_vec()
+        );
+        assert!(storage
+            .get_row_keys("tx", None, None, 0)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Overwrite existing row
+        storage
+            .put_row_data("blocks", &[(slot_to_key(3), row("updated"))])
+            .await
+            .unwrap();
+        assert_eq!(
+            storage
+                .get_single_row_data("blocks", slot_to_key(3))
+                .await
+                .unwrap(),
+            row("updated")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_local_storage_nested_keys() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let storage = LocalStorage::new(dir.path()).unwrap();
+
+        let keys = vec![
+            "addr/0000000000000001".to_string(),
+            "addr/0000000000000002".to_string(),
+            "addr0".to_string(),
+            "addr1/0000000000000001".to_string(),
+            "add".to_string(),
+        ];
+        let rows: Vec<_> = keys.iter().map(|key| (key.clone(), row(key))).collect();
+        storage.put_row_data("tx-by-addr", &rows).await.unwrap();
+
+        let mut sorted_keys = keys.clone();
+        sorted_keys.sort();
+        assert_eq!(
+            storage
+                .get_row_keys("tx-by-addr", None, None, 0)
+                .await
+                .unwrap(),
+            sorted_keys
+        );
+        assert_eq!(
+            storage
+                .get_row_keys(
+                    "tx-by-addr",
+                    Some("addr/0000000000000002".to_string()),
+                    Some("addr/ffffffffffffffff".to_string()),
+                    0
+                )
+                .await
+                .unwrap(),
+            vec!["addr/0000000000000002".to_string()]
+        );
+        assert_eq!(
+            storage
+                .get_row_keys("tx-by-addr", Some("addr0".to_string()), None, 0)
+                .await
+                .unwrap(),
+            vec!["addr0".to_string(), "addr1/0000000000000001".to_string()]
+        );
+
+        assert!(storage
+            .put_row_data("tx-by-addr", &[("addr//1".to_string(), row(""))])
+            .await
+            .is_err());
+        assert!(storage
+            .put_row_data("tx-by-addr", &[("../1".to_string(), row(""))])
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_local_storage_buckets() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let storage = LocalStorage::new(dir.path()).unwrap();
+
+        // Slot keys of different buckets, and longer keys sharing the prefix of a bucket
+        let keys = vec![
+            slot_to_key(0xfffe),
+            slot_to_key(0xffff),
+            slot_to_key(0x10000),
+            "000000000000ffff0".to_string(),
+            "000000000000".to_string(),
+        ];
+        let rows: Vec<_> = keys.iter().map(|key| (key.clone(), row(key))).collect();
+        storage.put_row_data("blocks", &rows).await.unwrap();
+        assert!(dir
+            .path()
+            .join("tables/blocks/.000000000000")
+            .join(slot_to_key(0xffff))
+            .exists());
+
+        let mut sorted_keys = keys.clone();
+        sorted_keys.sort();
+        assert_eq!(
+            storage.get_row_keys("blocks", None, None, 0).await.unwrap(),
+            sorted_keys
+        );
+        assert_eq!(
+            storage
+                .get_row_keys("blocks", Some(slot_to_key(0xffff)), None, 2)
+                .await
+                .unwrap(),
+            vec![slot_to_key(0xffff), "000000000000ffff0".to_string()]
+        );
+        assert_eq!(
+            storage
+                .get_row_keys("blocks", Some(slot_to_key(0x10000)), None, 0)
+                .await
+                .unwrap(),
+            vec![slot_to_key(0x10000)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_local_storage_objects() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let storage = LocalStorage::new(dir.path()).unwrap();
+
+        // Rows with the same cells share one object
+        let rows = vec![(slot_to_key(1), row("same")), (slot_to_key(2), row("same"))];
+        storage.put_row_data("blocks", &rows).await.unwrap();
+        let (hash, data) = row_object(&row("same"));
+        let object_path = dir.path().join(object_name(&hash));
+        assert_eq!(fs::read(&object_path).unwrap(), data);
+        assert_eq!(
+            fs::read_dir(object_path.parent().unwrap()).unwrap().count(),
+            1
+        );
+
+        fs::write(&object_path, b"corrupt").unwrap();
+        assert!(matches!(
+            storage.get_single_row_data("blocks", slot_to_key(1)).await,
+            Err(Error::ObjectCorrupt(_))
+        ));
+        fs::remove_file(&object_path).unwrap();
+        assert!(matches!(
+            storage.get_single_row_data("blocks", slot_to_key(2)).await,
+            Err(Error::ObjectNotFound(_))
+        ));
+    }
+}
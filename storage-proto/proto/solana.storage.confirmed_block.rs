@@ -39,6 +39,10 @@ pub struct Message {
     pub recent_blockhash: ::prost::alloc::vec::Vec<u8>,
     #[prost(message, repeated, tag = "4")]
     pub instructions: ::prost::alloc::vec::Vec<CompiledInstruction>,
+    #[prost(bool, tag = "5")]
+    pub versioned: bool,
+    #[prost(message, repeated, tag = "6")]
+    pub address_table_lookups: ::prost::alloc::vec::Vec<MessageAddressTableLookup>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct MessageHeader {
@@ -50,6 +54,15 @@ pub struct MessageHeader {
     pub num_readonly_unsigned_accounts: u32,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MessageAddressTableLookup {
+    #[prost(bytes = "vec", tag = "1")]
+    pub account_key: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub writable_indexes: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub readonly_indexes: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TransactionStatusMeta {
     #[prost(message, optional, tag = "1")]
     pub err: ::core::option::Option<TransactionError>,
@@ -69,6 +82,12 @@ pub struct TransactionStatusMeta {
     pub post_token_balances: ::prost::alloc::vec::Vec<TokenBalance>,
     #[prost(message, repeated, tag = "9")]
     pub rewards: ::prost::alloc::vec::Vec<Reward>,
+    #[prost(message, optional, tag = "10")]
+    pub return_data: ::core::option::Option<ReturnData>,
+    #[prost(bytes = "vec", repeated, tag = "11")]
+    pub loaded_writable_addresses: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "12")]
+    pub loaded_readonly_addresses: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TransactionError {
@@ -76,6 +95,13 @@ pub struct TransactionError {
     pub err: ::prost::alloc::vec::Vec<u8>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReturnData {
+    #[prost(bytes = "vec", tag = "1")]
+    pub program_id: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct InnerInstructions {
     #[prost(uint32, tag = "1")]
     pub index: u32,
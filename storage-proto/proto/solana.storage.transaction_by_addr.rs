@@ -67,6 +67,12 @@ pub enum TransactionErrorType {
     SanitizeFailure = 14,
     ClusterMaintenance = 15,
     AccountBorrowOutstandingTx = 16,
+    UnsupportedVersion = 17,
+    AddressLookupTableNotFound = 18,
+    InvalidAddressLookupTableOwner = 19,
+    InvalidAddressLookupTableData = 20,
+    InvalidAddressLookupTableIndex = 21,
+    InvalidRentPayingAccount = 22,
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -4,11 +4,15 @@ use solana_sdk::{
     hash::Hash,
     instruction::CompiledInstruction,
     instruction::InstructionError,
-    message::{Message, MessageHeader},
+    message::{
+        v0::{self, LoadedAddresses, MessageAddressTableLookup},
+        Message, MessageHeader, VersionedMessage,
+    },
+    process_instruction::TransactionReturnData,
     pubkey::Pubkey,
     signature::Signature,
-    transaction::Transaction,
     transaction::TransactionError,
+    transaction::VersionedTransaction,
 };
 use solana_transaction_status::{
     ConfirmedBlock, InnerInstructions, Reward, RewardType, TransactionByAddrInfo,
@@ -183,16 +187,21 @@ impl From<TransactionWithStatusMeta> for generated::ConfirmedTransaction {
 impl TryFrom<generated::ConfirmedTransaction> for TransactionWithStatusMeta {
     type Error = bincode::Error;
     fn try_from(value: generated::ConfirmedTransaction) -> std::result::Result<Self, Self::Error> {
-        let meta = value.meta.map(|meta| meta.try_into()).transpose()?;
-        Ok(Self {
-            transaction: value.transaction.expect("transaction is required").into(),
-            meta,
-        })
+        let transaction: VersionedTransaction =
+            value.transaction.expect("transaction is required").into();
+        let mut meta: Option<TransactionStatusMeta> =
+            value.meta.map(|meta| meta.try_into()).transpose()?;
+        // Empty loaded address lists are indistinguishable from missing ones on the wire
+        if let (VersionedMessage::V0(_), Some(meta)) = (&transaction.message, meta.as_mut()) {
+            meta.loaded_addresses
+                .get_or_insert_with(LoadedAddresses::default);
+        }
+        Ok(Self { transaction, meta })
     }
 }
 
-impl From<Transaction> for generated::Transaction {
-    fn from(value: Transaction) -> Self {
+impl From<VersionedTransaction> for generated::Transaction {
+    fn from(value: VersionedTransaction) -> Self {
         Self {
             signatures: value
                 .signatures
@@ -204,7 +213,7 @@ impl From<Transaction> for generated::Transaction {
     }
 }
 
-impl From<generated::Transaction> for Transaction {
+impl From<generated::Transaction> for VersionedTransaction {
     fn from(value: generated::Transaction) -> Self {
         Self {
             signatures: value
@@ -228,6 +237,8 @@ impl From<Message> for generated::Message {
                 .collect(),
             recent_blockhash: value.recent_blockhash.to_bytes().into(),
             instructions: value.instructions.into_iter().map(|ix| ix.into()).collect(),
+            versioned: false,
+            address_table_lookups: vec![],
         }
     }
 }
@@ -247,6 +258,77 @@ impl From<generated::Message> for Message {
     }
 }
 
+impl From<VersionedMessage> for generated::Message {
+    fn from(value: VersionedMessage) -> Self {
+        match value {
+            VersionedMessage::Legacy(message) => message.into(),
+            VersionedMessage::V0(message) => Self {
+                header: Some(message.header.into()),
+                account_keys: message
+                    .account_keys
+                    .into_iter()
+                    .map(|key| <Pubkey as AsRef<[u8]>>::as_ref(&key).into())
+                    .collect(),
+                recent_blockhash: message.recent_blockhash.to_bytes().into(),
+                instructions: message
+                    .instructions
+                    .into_iter()
+                    .map(|ix| ix.into())
+                    .collect(),
+                versioned: true,
+                address_table_lookups: message
+                    .address_table_lookups
+                    .into_iter()
+                    .map(|lookup| lookup.into())
+                    .collect(),
+            },
+        }
+    }
+}
+
+impl From<generated::Message> for VersionedMessage {
+    fn from(value: generated::Message) -> Self {
+        if !value.versioned {
+            return Self::Legacy(value.into());
+        }
+        Self::V0(v0::Message {
+            header: value.header.expect("header is required").into(),
+            account_keys: value
+                .account_keys
+                .into_iter()
+                .map(|key| Pubkey::new(&key))
+                .collect(),
+            recent_blockhash: Hash::new(&value.recent_blockhash),
+            instructions: value.instructions.into_iter().map(|ix| ix.into()).collect(),
+            address_table_lookups: value
+                .address_table_lookups
+                .into_iter()
+                .map(|lookup| lookup.into())
+                .collect(),
+        })
+    }
+}
+
+impl From<MessageAddressTableLookup> for generated::MessageAddressTableLookup {
+    fn from(value: MessageAddressTableLookup) -> Self {
+        Self {
+            account_key: <Pubkey as AsRef<[u8]>>::as_ref(&value.account_key).into(),
+            writable_indexes: value.writable_indexes,
+            readonly_indexes: value.readonly_indexes,
+        }
+    }
+}
+
+impl From<generated::MessageAddressTableLookup> for MessageAddressTableLookup {
+    fn from(value: generated::MessageAddressTableLookup) -> Self {
+        Self {
+            account_key: Pubkey::new(&value.account_key),
+            writable_indexes: value.writable_indexes,
+            readonly_indexes: value.readonly_indexes,
+        }
+    }
+}
+
 impl From<MessageHeader> for generated::MessageHeader {
     fn from(value: MessageHeader) -> Self {
         Self {
@@ -279,6 +361,8 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_addresses,
         } = value;
         let err = match status {
             Ok(()) => None,
@@ -307,6 +391,16 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             .into_iter()
             .map(|reward| reward.into())
             .collect();
+        let return_data = return_data.map(|return_data| return_data.into());
+        let LoadedAddresses { writable, readonly } = loaded_addresses.unwrap_or_default();
+        let loaded_writable_addresses = writable
+            .into_iter()
+            .map(|key| <Pubkey as AsRef<[u8]>>::as_ref(&key).into())
+            .collect();
+        let loaded_readonly_addresses = readonly
+            .into_iter()
+            .map(|key| <Pubkey as AsRef<[u8]>>::as_ref(&key).into())
+            .collect();
 
         Self {
             err,
@@ -318,6 +412,9 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_writable_addresses,
+            loaded_readonly_addresses,
         }
     }
 }
@@ -343,6 +440,9 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_writable_addresses,
+            loaded_readonly_addresses,
         } = value;
         let status = match &err {
             None => Ok(()),
@@ -368,6 +468,22 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
                 .collect(),
         );
         let rewards = Some(rewards.into_iter().map(|reward| reward.into()).collect());
+        let return_data = return_data.map(|return_data| return_data.into());
+        let loaded_addresses =
+            if loaded_writable_addresses.is_empty() && loaded_readonly_addresses.is_empty() {
+                None
+            } else {
+                Some(LoadedAddresses {
+                    writable: loaded_writable_addresses
+                        .into_iter()
+                        .map(|key| Pubkey::new(&key))
+                        .collect(),
+                    readonly: loaded_readonly_addresses
+                        .into_iter()
+                        .map(|key| Pubkey::new(&key))
+                        .collect(),
+                })
+            };
         Ok(Self {
             status,
             fee,
@@ -378,10 +494,30 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_addresses,
         })
     }
 }
 
+impl From<TransactionReturnData> for generated::ReturnData {
+    fn from(value: TransactionReturnData) -> Self {
+        Self {
+            program_id: value.program_id.to_bytes().into(),
+            data: value.data,
+        }
+    }
+}
+
+impl From<generated::ReturnData> for TransactionReturnData {
+    fn from(value: generated::ReturnData) -> Self {
+        Self {
+            program_id: Pubkey::new(&value.program_id),
+            data: value.data,
+        }
+    }
+}
+
 impl From<InnerInstructions> for generated::InnerInstructions {
     fn from(value: InnerInstructions) -> Self {
         Self {
@@ -551,6 +687,12 @@ impl TryFrom<tx_by_addr::TransactionError> for TransactionError {
             14 => TransactionError::SanitizeFailure,
             15 => TransactionError::ClusterMaintenance,
             16 => TransactionError::AccountBorrowOutstanding,
+            17 => TransactionError::UnsupportedVersion,
+            18 => TransactionError::AddressLookupTableNotFound,
+            19 => TransactionError::InvalidAddressLookupTableOwner,
+            20 => TransactionError::InvalidAddressLookupTableData,
+            21 => TransactionError::InvalidAddressLookupTableIndex,
+            22 => TransactionError::InvalidRentPayingAccount,
             _ => return Err("Invalid TransactionError"),
         })
     }
@@ -609,6 +751,24 @@ impl From<TransactionError> for tx_by_addr::TransactionError {
                 TransactionError::AccountBorrowOutstanding => {
                     tx_by_addr::TransactionErrorType::AccountBorrowOutstandingTx
                 }
+                TransactionError::UnsupportedVersion => {
+                    tx_by_addr::TransactionErrorType::UnsupportedVersion
+                }
+                TransactionError::AddressLookupTableNotFound => {
+                    tx_by_addr::TransactionErrorType::AddressLookupTableNotFound
+                }
+                TransactionError::InvalidAddressLookupTableOwner => {
+                    tx_by_addr::TransactionErrorType::InvalidAddressLookupTableOwner
+                }
+                TransactionError::InvalidAddressLookupTableData => {
+                    tx_by_addr::TransactionErrorType::InvalidAddressLookupTableData
+                }
+                TransactionError::InvalidAddressLookupTableIndex => {
+                    tx_by_addr::TransactionErrorType::InvalidAddressLookupTableIndex
+                }
+                TransactionError::InvalidRentPayingAccount => {
+                    tx_by_addr::TransactionErrorType::InvalidRentPayingAccount
+                }
             } as i32,
             instruction_error: match transaction_error {
                 TransactionError::InstructionError(index, ref instruction_error) => {
@@ -1359,6 +1519,71 @@ mod test {
         assert_eq!(info, tx_by_addr_transaction_info.try_into().unwrap());
     }
 
+    #[test]
+    fn test_versioned_transaction_encode() {
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::new(&[1; 64])],
+            message: VersionedMessage::V0(v0::Message {
+                header: MessageHeader {
+                    num_required_signatures: 1,
+                    num_readonly_signed_accounts: 0,
+                    num_readonly_unsigned_accounts: 1,
+                },
+                account_keys: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+                recent_blockhash: Hash::new_unique(),
+                instructions: vec![CompiledInstruction {
+                    program_id_index: 1,
+                    accounts: vec![0, 2],
+                    data: vec![],
+                }],
+                address_table_lookups: vec![MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: vec![0],
+                    readonly_indexes: vec![],
+                }],
+            }),
+        };
+        let meta = TransactionStatusMeta {
+            status: Ok(()),
+            fee: 5000,
+            pre_balances: vec![1, 2],
+            post_balances: vec![0, 2],
+            inner_instructions: Some(vec![]),
+            log_messages: Some(vec![]),
+            pre_token_balances: Some(vec![]),
+            post_token_balances: Some(vec![]),
+            rewards: Some(vec![]),
+            return_data: None,
+            loaded_addresses: Some(LoadedAddresses {
+                writable: vec![Pubkey::new_unique()],
+                readonly: vec![],
+            }),
+        };
+        let with_meta = TransactionWithStatusMeta {
+            transaction,
+            meta: Some(meta),
+        };
+
+        let gen_transaction: generated::ConfirmedTransaction = with_meta.clone().into();
+        assert!(
+            gen_transaction
+                .transaction
+                .as_ref()
+                .unwrap()
+                .message
+                .as_ref()
+                .unwrap()
+                .versioned
+        );
+        assert_eq!(with_meta, gen_transaction.try_into().unwrap());
+
+        // A v0 transaction that loaded nothing keeps an empty address list
+        let mut with_meta = with_meta;
+        with_meta.meta.as_mut().unwrap().loaded_addresses = Some(LoadedAddresses::default());
+        let gen_transaction: generated::ConfirmedTransaction = with_meta.clone().into();
+        assert_eq!(with_meta, gen_transaction.try_into().unwrap());
+    }
+
     #[test]
     fn test_transaction_error_encode() {
         let transaction_error = TransactionError::AccountInUse;
@@ -1409,6 +1634,30 @@ mod test {
             tx_by_addr_transaction_error.try_into().unwrap()
         );
 
+        let transaction_error = TransactionError::UnsupportedVersion;
+        let tx_by_addr_transaction_error: tx_by_addr::TransactionError =
+            transaction_error.clone().into();
+        assert_eq!(
+            transaction_error,
+            tx_by_addr_transaction_error.try_into().unwrap()
+        );
+
+        let transaction_error = TransactionError::InvalidAddressLookupTableIndex;
+        let tx_by_addr_transaction_error: tx_by_addr::TransactionError =
+            transaction_error.clone().into();
+        assert_eq!(
+            transaction_error,
+            tx_by_addr_transaction_error.try_into().unwrap()
+        );
+
+        let transaction_error = TransactionError::InvalidRentPayingAccount;
+        let tx_by_addr_transaction_error: tx_by_addr::TransactionError =
+            transaction_error.clone().into();
+        assert_eq!(
+            transaction_error,
+            tx_by_addr_transaction_error.try_into().unwrap()
+        );
+
         let transaction_error = TransactionError::AlreadyProcessed;
         let tx_by_addr_transaction_error: tx_by_addr::TransactionError =
             transaction_error.clone().into();
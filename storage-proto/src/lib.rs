@@ -3,7 +3,10 @@ use solana_account_decoder::{
     parse_token::{real_number_string_trimmed, UiTokenAmount},
     StringAmount,
 };
-use solana_sdk::{deserialize_utils::default_on_eof, transaction::Result};
+use solana_sdk::{
+    deserialize_utils::default_on_eof, message::v0::LoadedAddresses,
+    process_instruction::TransactionReturnData, transaction::Result,
+};
 use solana_transaction_status::{
     InnerInstructions, Reward, RewardType, TransactionStatusMeta, TransactionTokenBalance,
 };
@@ -152,6 +155,10 @@ pub struct StoredTransactionStatusMeta {
     pub post_token_balances: Option<Vec<StoredTransactionTokenBalance>>,
     #[serde(deserialize_with = "default_on_eof")]
     pub rewards: Option<Vec<StoredExtendedReward>>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub return_data: Option<TransactionReturnData>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub loaded_addresses: Option<LoadedAddresses>,
 }
 
 impl From<StoredTransactionStatusMeta> for TransactionStatusMeta {
@@ -166,6 +173,8 @@ impl From<StoredTransactionStatusMeta> for TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_addresses,
         } = value;
         Self {
             status,
@@ -180,6 +189,8 @@ impl From<StoredTransactionStatusMeta> for TransactionStatusMeta {
                 .map(|balances| balances.into_iter().map(|balance| balance.into()).collect()),
             rewards: rewards
                 .map(|rewards| rewards.into_iter().map(|reward| reward.into()).collect()),
+            return_data,
+            loaded_addresses,
         }
     }
 }
@@ -196,6 +207,8 @@ impl From<TransactionStatusMeta> for StoredTransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_addresses,
         } = value;
         Self {
             status,
@@ -210,6 +223,8 @@ impl From<TransactionStatusMeta> for StoredTransactionStatusMeta {
                 .map(|balances| balances.into_iter().map(|balance| balance.into()).collect()),
             rewards: rewards
                 .map(|rewards| rewards.into_iter().map(|reward| reward.into()).collect()),
+            return_data,
+            loaded_addresses,
         }
     }
 }
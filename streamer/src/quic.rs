@@ -0,0 +1,542 @@
+//! The `quic` module implements a QUIC listener for the TPU. Every unidirectional stream carries
+//! one transaction, which is forwarded into the same `Packets` pipeline as the UDP receivers.
+//!
+//! Clients authenticate with a self-signed certificate of their node identity, which the TLS
+//! handshake proves they hold the key for. Connections from staked identities are allowed more
+//! concurrent streams, in proportion to their stake. Each staked identity, or unstaked IP address,
+//! may only hold a few connections.
+
+use {
+    crate::{
+        packet::{Packet, Packets, PACKETS_PER_BATCH, PACKET_DATA_SIZE},
+        staked_nodes::StakedNodes,
+        streamer::PacketSender,
+        tls_certificates::{
+            get_pubkey_from_tls_certificate, new_self_signed_tls_certificate_chain,
+        },
+    },
+    futures_util::stream::StreamExt,
+    quinn::{
+        Connecting, Endpoint, EndpointConfig, IdleTimeout, IncomingUniStreams, NewConnection,
+        ServerConfig, VarInt,
+    },
+    rcgen::RcgenError,
+    rustls::server::{ClientCertVerified, ClientCertVerifier},
+    solana_sdk::{
+        pubkey::Pubkey,
+        quic::{
+            QUIC_MAX_CONCURRENT_CONNECTIONS, QUIC_MAX_CONNECTIONS_PER_PEER, QUIC_MAX_TIMEOUT_MS,
+            QUIC_MAX_UNSTAKED_CONCURRENT_STREAMS, QUIC_MIN_STAKED_CONCURRENT_STREAMS,
+            QUIC_TOTAL_STAKED_CONCURRENT_STREAMS, QUIC_TPU_ALPN,
+        },
+        signature::Keypair,
+    },
+    std::{
+        collections::{hash_map::Entry, HashMap},
+        io,
+        net::{IpAddr, SocketAddr, UdpSocket},
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Arc, Mutex, RwLock,
+        },
+        thread::{self, JoinHandle},
+        time::{Duration, SystemTime},
+    },
+    thiserror::Error,
+    tokio::{
+        runtime::{Builder, Runtime},
+        sync::mpsc,
+        time::{interval, timeout, timeout_at, Instant},
+    },
+};
+
+const WAIT_FOR_CONNECTION_TIMEOUT: Duration = Duration::from_secs(1);
+const WAIT_FOR_STREAM_TIMEOUT: Duration = Duration::from_secs(1);
+const STATS_REPORT_INTERVAL: Duration = Duration::from_secs(5);
+// Longest a received transaction waits for others to fill its batch
+const PACKET_BATCH_FLUSH_TIMEOUT: Duration = Duration::from_millis(10);
+const MAX_QUEUED_PACKETS: usize = 8 * PACKETS_PER_BATCH;
+
+#[derive(Error, Debug)]
+pub enum QuicServerError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("certificate error: {0}")]
+    Certificate(#[from] RcgenError),
+
+    #[error("TLS error: {0}")]
+    Tls(#[from] rustls::Error),
+}
+
+/// Accepts any client certificate, or none at all. The certificate chain is not meaningful for
+/// self-signed node identities, but rustls still checks that the client holds the certificate
+/// key when verifying the handshake signature.
+struct SkipClientVerification;
+
+impl ClientCertVerifier for SkipClientVerification {
+    fn client_auth_root_subjects(&self) -> Option<rustls::DistinguishedNames> {
+        Some(rustls::DistinguishedNames::new())
+    }
+
+    fn client_auth_mandatory(&self) -> Option<bool> {
+        Some(false)
+    }
+
+    fn verify_client_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _now: SystemTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        Ok(ClientCertVerified::assertion())
+    }
+}
+
+pub fn configure_server(
+    identity_keypair: &Keypair,
+    gossip_host: IpAddr,
+) -> Result<ServerConfig, QuicServerError> {
+    let (cert_chain, private_key) =
+        new_self_signed_tls_certificate_chain(identity_keypair, gossip_host)?;
+
+    let mut server_tls_config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_client_cert_verifier(Arc::new(SkipClientVerification))
+        .with_single_cert(cert_chain, private_key)?;
+    server_tls_config.alpn_protocols = vec![QUIC_TPU_ALPN.to_vec()];
+
+    let mut server_config = ServerConfig::with_crypto(Arc::new(server_tls_config));
+    server_config.concurrent_connections(QUIC_MAX_CONCURRENT_CONNECTIONS);
+    let transport_config = Arc::get_mut(&mut server_config.transport).unwrap();
+    // Raised per connection once the peer identity, and so its stake, is known
+    transport_config
+        .max_concurrent_uni_streams(VarInt::from(QUIC_MAX_UNSTAKED_CONCURRENT_STREAMS as u32));
+    transport_config.max_concurrent_bidi_streams(VarInt::from(0u8));
+    transport_config.stream_receive_window(VarInt::from(PACKET_DATA_SIZE as u32));
+    transport_config.datagram_receive_buffer_size(None);
+    transport_config.max_idle_timeout(Some(IdleTimeout::from(VarInt::from(QUIC_MAX_TIMEOUT_MS))));
+    Ok(server_config)
+}
+
+/// Concurrent streams granted to a connection from a peer with `stake`
+pub fn compute_max_allowed_uni_streams(stake: u64, total_stake: u64) -> u64 {
+    if stake == 0 || total_stake == 0 {
+        QUIC_MAX_UNSTAKED_CONCURRENT_STREAMS
+    } else {
+        let share = (u128::from(stake) * u128::from(QUIC_TOTAL_STAKED_CONCURRENT_STREAMS)
+            / u128::from(total_stake)) as u64;
+        share.max(QUIC_MIN_STAKED_CONCURRENT_STREAMS)
+    }
+}
+
+#[derive(Default)]
+struct StreamStats {
+    total_connections: AtomicUsize,
+    total_staked_connections: AtomicUsize,
+    total_streams: AtomicUsize,
+    total_packets: AtomicUsize,
+    total_invalid_chunks: AtomicUsize,
+    total_stream_read_errors: AtomicUsize,
+    total_handshake_errors: AtomicUsize,
+    total_refused_connections: AtomicUsize,
+}
+
+impl StreamStats {
+    fn report(&self) {
+        datapoint_info!(
+            "quic-connections",
+            (
+                "active_connections",
+                self.total_connections.load(Ordering::Relaxed),
+                i64
+            ),
+            (
+                "active_staked_connections",
+                self.total_staked_connections.load(Ordering::Relaxed),
+                i64
+            ),
+            (
+                "active_streams",
+                self.total_streams.load(Ordering::Relaxed),
+                i64
+            ),
+            (
+                "packets",
+                self.total_packets.swap(0, Ordering::Relaxed),
+                i64
+            ),
+            (
+                "invalid_chunks",
+                self.total_invalid_chunks.swap(0, Ordering::Relaxed),
+                i64
+            ),
+            (
+                "stream_read_errors",
+                self.total_stream_read_errors.swap(0, Ordering::Relaxed),
+                i64
+            ),
+            (
+                "handshake_errors",
+                self.total_handshake_errors.swap(0, Ordering::Relaxed),
+                i64
+            ),
+            (
+                "refused_connections",
+                self.total_refused_connections.swap(0, Ordering::Relaxed),
+                i64
+            ),
+        );
+    }
+}
+
+/// Identity of a peer when counting its connections. Unstaked identities cost nothing to mint,
+/// so unstaked peers are counted by IP address instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum ConnectionPeer {
+    Staked(Pubkey),
+    Unstaked(IpAddr),
+}
+
+#[derive(Default)]
+struct ConnectionTable {
+    connections: Mutex<HashMap<ConnectionPeer, usize>>,
+}
+
+impl ConnectionTable {
+    /// Returns false if `peer` already holds as many connections as it may
+    fn try_add(&self, peer: ConnectionPeer) -> bool {
+        let mut connections = self.connections.lock().unwrap();
+        let count = connections.entry(peer).or_default();
+        if *count >= QUIC_MAX_CONNECTIONS_PER_PEER {
+            return false;
+        }
+        *count += 1;
+        true
+    }
+
+    fn remove(&self, peer: &ConnectionPeer) {
+        let mut connections = self.connections.lock().unwrap();
+        if let Entry::Occupied(mut entry) = connections.entry(*peer) {
+            *entry.get_mut() -= 1;
+            if *entry.get() == 0 {
+                entry.remove();
+            }
+        }
+    }
+}
+
+fn rt() -> Runtime {
+    Builder::new_multi_thread()
+        .thread_name("solana-quic-server")
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+/// Spawns a QUIC listener on `sock`, sending received transactions to `packet_sender` until
+/// `exit` is set
+pub fn spawn_server(
+    sock: UdpSocket,
+    identity_keypair: &Keypair,
+    gossip_host: IpAddr,
+    packet_sender: PacketSender,
+    exit: Arc<AtomicBool>,
+    staked_nodes: Arc<RwLock<StakedNodes>>,
+) -> Result<JoinHandle<()>, QuicServerError> {
+    let runtime = rt();
+    let server_config = configure_server(identity_keypair, gossip_host)?;
+    let (endpoint, incoming) = {
+        let _guard = runtime.enter();
+        Endpoint::new(EndpointConfig::default(), Some(server_config), sock)?
+    };
+
+    let handle = thread::Builder::new()
+        .name("solana-quic-server".to_string())
+        .spawn(move || {
+            let stats = Arc::new(StreamStats::default());
+            let connection_table = Arc::new(ConnectionTable::default());
+            runtime.block_on(async move {
+                let (batch_sender, batch_receiver) = mpsc::channel(MAX_QUEUED_PACKETS);
+                tokio::spawn(send_packet_batches(batch_receiver, packet_sender));
+                let mut incoming = incoming;
+                let mut report_interval = interval(STATS_REPORT_INTERVAL);
+                while !exit.load(Ordering::Relaxed) {
+                    tokio::select! {
+                        connecting = timeout(WAIT_FOR_CONNECTION_TIMEOUT, incoming.next()) => {
+                            match connecting {
+                                Ok(Some(connecting)) => {
+                                    tokio::spawn(setup_connection(
+                                        connecting,
+                                        batch_sender.clone(),
+                                        exit.clone(),
+                                        staked_nodes.clone(),
+                                        connection_table.clone(),
+                                        stats.clone(),
+                                    ));
+                                }
+                                Ok(None) => break,
+                                Err(_) => {}
+                            }
+                        }
+                        _ = report_interval.tick() => stats.report(),
+                    }
+                }
+                endpoint.close(VarInt::from(0u8), b"exit");
+            });
+        })?;
+    Ok(handle)
+}
+
+fn peer_pubkey(connection: &quinn::Connection) -> Option<Pubkey> {
+    connection
+        .peer_identity()?
+        .downcast::<Vec<rustls::Certificate>>()
+        .ok()?
+        .first()
+        .and_then(get_pubkey_from_tls_certificate)
+}
+
+/// Coalesces the transactions received on all connections into batches of up to
+/// `PACKETS_PER_BATCH`, flushing a partial batch after `PACKET_BATCH_FLUSH_TIMEOUT`
+async fn send_packet_batches(
+    mut packet_receiver: mpsc::Receiver<Packet>,
+    packet_sender: PacketSender,
+) {
+    while let Some(packet) = packet_receiver.recv().await {
+        let mut batch = Vec::with_capacity(PACKETS_PER_BATCH);
+        batch.push(packet);
+        let deadline = Instant::now() + PACKET_BATCH_FLUSH_TIMEOUT;
+        while batch.len() < PACKETS_PER_BATCH {
+            match timeout_at(deadline, packet_receiver.recv()).await {
+                Ok(Some(packet)) => batch.push(packet),
+                Ok(None) | Err(_) => break,
+            }
+        }
+        if packet_sender.send(Packets::new(batch)).is_err() {
+            break;
+        }
+    }
+}
+
+async fn setup_connection(
+    connecting: Connecting,
+    packet_sender: mpsc::Sender<Packet>,
+    exit: Arc<AtomicBool>,
+    staked_nodes: Arc<RwLock<StakedNodes>>,
+    connection_table: Arc<ConnectionTable>,
+    stats: Arc<StreamStats>,
+) {
+    let NewConnection {
+        connection,
+        uni_streams,
+        ..
+    } = match connecting.await {
+        Ok(new_connection) => new_connection,
+        Err(err) => {
+            debug!("QUIC handshake failed: {:?}", err);
+            stats.total_handshake_errors.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+    };
+
+    let remote_addr = connection.remote_address();
+    let pubkey = peer_pubkey(&connection);
+    let (stake, total_stake) = {
+        let staked_nodes = staked_nodes.read().unwrap();
+        let stake = pubkey.map_or(0, |pubkey| staked_nodes.stake(&pubkey));
+        (stake, staked_nodes.total_stake())
+    };
+    let peer = match pubkey {
+        Some(pubkey) if stake > 0 => ConnectionPeer::Staked(pubkey),
+        _ => ConnectionPeer::Unstaked(remote_addr.ip()),
+    };
+    if !connection_table.try_add(peer) {
+        debug!(
+            "QUIC connection from {:?} refused: too many connections",
+            peer
+        );
+        stats
+            .total_refused_connections
+            .fetch_add(1, Ordering::Relaxed);
+        connection.close(VarInt::from(0u8), b"too many connections");
+        return;
+    }
+    connection.set_max_concurrent_uni_streams(
+        VarInt::from_u64(compute_max_allowed_uni_streams(stake, total_stake))
+            .unwrap_or(VarInt::MAX),
+    );
+
+    stats.total_connections.fetch_add(1, Ordering::Relaxed);
+    if stake > 0 {
+        stats
+            .total_staked_connections
+            .fetch_add(1, Ordering::Relaxed);
+    }
+    handle_connection(
+        uni_streams,
+        packet_sender,
+        remote_addr,
+        stake,
+        exit,
+        stats.clone(),
+    )
+    .await;
+    connection_table.remove(&peer);
+    stats.total_connections.fetch_sub(1, Ordering::Relaxed);
+    if stake > 0 {
+        stats
+            .total_staked_connections
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+async fn handle_connection(
+    mut uni_streams: IncomingUniStreams,
+    packet_sender: mpsc::Sender<Packet>,
+    remote_addr: SocketAddr,
+    stake: u64,
+    exit: Arc<AtomicBool>,
+    stats: Arc<StreamStats>,
+) {
+    while !exit.load(Ordering::Relaxed) {
+        match timeout(WAIT_FOR_STREAM_TIMEOUT, uni_streams.next()).await {
+            Ok(Some(Ok(mut stream))) => {
+                let packet_sender = packet_sender.clone();
+                let stats = stats.clone();
+                stats.total_streams.fetch_add(1, Ordering::Relaxed);
+                tokio::spawn(async move {
+                    let mut packet = Packet::default();
+                    packet.meta.set_addr(&remote_addr);
+                    packet.meta.sender_stake = stake;
+                    loop {
+                        match stream.read_chunk(PACKET_DATA_SIZE, false).await {
+                            Ok(Some(chunk)) => {
+                                if !handle_chunk(&mut packet, chunk.offset, &chunk.bytes) {
+                                    stats.total_invalid_chunks.fetch_add(1, Ordering::Relaxed);
+                                    break;
+                                }
+                            }
+                            Ok(None) => {
+                                // The client finished the stream, the transaction is complete
+                                if packet.meta.size > 0 {
+                                    stats.total_packets.fetch_add(1, Ordering::Relaxed);
+                                    let _ = packet_sender.send(packet).await;
+                                }
+                                break;
+                            }
+                            Err(err) => {
+                                debug!("QUIC stream read failed: {:?}", err);
+                                stats
+                                    .total_stream_read_errors
+                                    .fetch_add(1, Ordering::Relaxed);
+                                break;
+                            }
+                        }
+                    }
+                    stats.total_streams.fetch_sub(1, Ordering::Relaxed);
+                });
+            }
+            // The connection was closed
+            Ok(Some(Err(_))) | Ok(None) => break,
+            // Check for exit
+            Err(_) => {}
+        }
+    }
+}
+
+/// Copies a stream chunk into `packet`, returning false if it does not fit into a packet
+fn handle_chunk(packet: &mut Packet, offset: u64, bytes: &[u8]) -> bool {
+    let offset = offset as usize;
+    let end = match offset.checked_add(bytes.len()) {
+        Some(end) if end <= PACKET_DATA_SIZE => end,
+        _ => return false,
+    };
+    packet.data[offset..end].copy_from_slice(bytes);
+    packet.meta.size = packet.meta.size.max(end);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_max_allowed_uni_streams() {
+        assert_eq!(
+            compute_max_allowed_uni_streams(0, 0),
+            QUIC_MAX_UNSTAKED_CONCURRENT_STREAMS
+        );
+        assert_eq!(
+            compute_max_allowed_uni_streams(0, 1_000),
+            QUIC_MAX_UNSTAKED_CONCURRENT_STREAMS
+        );
+        assert_eq!(
+            compute_max_allowed_uni_streams(1, 1_000_000),
+            QUIC_MIN_STAKED_CONCURRENT_STREAMS
+        );
+        assert_eq!(
+            compute_max_allowed_uni_streams(500, 1_000),
+            QUIC_TOTAL_STAKED_CONCURRENT_STREAMS / 2
+        );
+        assert!(
+            compute_max_allowed_uni_streams(1, 1_000_000)
+                > compute_max_allowed_uni_streams(0, 1_000_000)
+        );
+    }
+
+    #[test]
+    fn test_handle_chunk() {
+        let mut packet = Packet::default();
+        assert!(handle_chunk(&mut packet, 4, &[5, 6]));
+        assert!(handle_chunk(&mut packet, 0, &[1, 2, 3, 4]));
+        assert_eq!(packet.meta.size, 6);
+        assert_eq!(&packet.data[..6], &[1, 2, 3, 4, 5, 6]);
+
+        assert!(handle_chunk(&mut packet, 0, &[0; PACKET_DATA_SIZE]));
+        assert!(!handle_chunk(&mut packet, 1, &[0; PACKET_DATA_SIZE]));
+        assert!(!handle_chunk(&mut packet, u64::MAX, &[0]));
+    }
+
+    #[test]
+    fn test_connection_table() {
+        let table = ConnectionTable::default();
+        let unstaked = ConnectionPeer::Unstaked(IpAddr::V4(std::net::Ipv4Addr::LOCALHOST));
+        let staked = ConnectionPeer::Staked(Pubkey::new_unique());
+        for _ in 0..QUIC_MAX_CONNECTIONS_PER_PEER {
+            assert!(table.try_add(unstaked));
+        }
+        assert!(!table.try_add(unstaked));
+        // Other peers have their own allowance
+        assert!(table.try_add(staked));
+
+        table.remove(&unstaked);
+        assert!(table.try_add(unstaked));
+        table.remove(&staked);
+        assert!(!table.connections.lock().unwrap().contains_key(&staked));
+    }
+
+    #[test]
+    fn test_send_packet_batches() {
+        let (packet_sender, packet_receiver) = std::sync::mpsc::channel();
+        rt().block_on(async move {
+            let (batch_sender, batch_receiver) = mpsc::channel(MAX_QUEUED_PACKETS);
+            for _ in 0..PACKETS_PER_BATCH + 1 {
+                batch_sender.send(Packet::default()).await.unwrap();
+            }
+            drop(batch_sender);
+            send_packet_batches(batch_receiver, packet_sender).await;
+        });
+        let batch_sizes: Vec<_> = packet_receiver
+            .iter()
+            .map(|packets| packets.packets.len())
+            .collect();
+        assert_eq!(batch_sizes, vec![PACKETS_PER_BATCH, 1]);
+    }
+
+    #[test]
+    fn test_configure_server() {
+        let keypair = Keypair::new();
+        assert!(configure_server(&keypair, IpAddr::V4(std::net::Ipv4Addr::LOCALHOST)).is_ok());
+    }
+}
@@ -0,0 +1,27 @@
+use {solana_sdk::pubkey::Pubkey, std::collections::HashMap};
+
+/// Stake delegated to each node identity, used to give staked peers a larger share of the
+/// ingestion capacity
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StakedNodes {
+    stakes: HashMap<Pubkey, u64>,
+    total_stake: u64,
+}
+
+impl StakedNodes {
+    pub fn new(stakes: HashMap<Pubkey, u64>) -> Self {
+        let total_stake = stakes.values().sum();
+        Self {
+            stakes,
+            total_stake,
+        }
+    }
+
+    pub fn stake(&self, pubkey: &Pubkey) -> u64 {
+        self.stakes.get(pubkey).copied().unwrap_or_default()
+    }
+
+    pub fn total_stake(&self) -> u64 {
+        self.total_stake
+    }
+}
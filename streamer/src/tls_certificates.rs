@@ -0,0 +1,75 @@
+//! Self-signed TLS certificates carrying a node identity, so that QUIC peers can be
+//! attributed to the identity pubkey they present.
+
+use {
+    rcgen::{CertificateParams, DistinguishedName, DnType, RcgenError, SanType},
+    solana_sdk::{pubkey::Pubkey, signature::Keypair},
+    std::net::IpAddr,
+    x509_parser::{prelude::*, public_key::PublicKey},
+};
+
+pub fn new_self_signed_tls_certificate_chain(
+    keypair: &Keypair,
+    san: IpAddr,
+) -> Result<(Vec<rustls::Certificate>, rustls::PrivateKey), RcgenError> {
+    // rcgen does not accept a raw ed25519 key, so wrap it into a PKCS#8 v1 document
+    // (RFC 8410, section 7). The prefix decodes to:
+    //
+    //   PrivateKeyInfo SEQUENCE (3 elem)
+    //     version Version INTEGER 0
+    //     privateKeyAlgorithm AlgorithmIdentifier SEQUENCE (1 elem)
+    //       algorithm OBJECT IDENTIFIER 1.3.101.112 curveEd25519
+    //     privateKey PrivateKey OCTET STRING (34 byte)
+    const PKCS8_PREFIX: [u8; 16] = [
+        0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04,
+        0x20,
+    ];
+    let mut key_pkcs8_der = Vec::with_capacity(PKCS8_PREFIX.len() + 32);
+    key_pkcs8_der.extend_from_slice(&PKCS8_PREFIX);
+    key_pkcs8_der.extend_from_slice(keypair.secret().as_bytes());
+
+    let mut cert_params = CertificateParams::new(vec![]);
+    cert_params.subject_alt_names = vec![SanType::IpAddress(san)];
+    cert_params.alg = &rcgen::PKCS_ED25519;
+    cert_params.key_pair = Some(rcgen::KeyPair::from_der(&key_pkcs8_der)?);
+    cert_params.distinguished_name = DistinguishedName::new();
+    cert_params
+        .distinguished_name
+        .push(DnType::CommonName, "Solana node");
+
+    let cert = rcgen::Certificate::from_params(cert_params)?;
+    let cert_der = cert.serialize_der()?;
+    let private_key = rustls::PrivateKey(cert.serialize_private_key_der());
+    Ok((vec![rustls::Certificate(cert_der)], private_key))
+}
+
+/// Identity pubkey of the ed25519 key a certificate was issued for
+pub fn get_pubkey_from_tls_certificate(certificate: &rustls::Certificate) -> Option<Pubkey> {
+    let (_, cert) = X509Certificate::from_der(certificate.as_ref()).ok()?;
+    match cert.public_key().parsed().ok()? {
+        PublicKey::Unknown(key) if key.len() == 32 => Some(Pubkey::new(key)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_sdk::signature::Signer, std::net::Ipv4Addr};
+
+    #[test]
+    fn test_generate_tls_certificate() {
+        let keypair = Keypair::new();
+        let (certs, _private_key) =
+            new_self_signed_tls_certificate_chain(&keypair, IpAddr::V4(Ipv4Addr::LOCALHOST))
+                .unwrap();
+        assert_eq!(certs.len(), 1);
+        assert_eq!(
+            get_pubkey_from_tls_certificate(&certs[0]),
+            Some(keypair.pubkey())
+        );
+        assert_eq!(
+            get_pubkey_from_tls_certificate(&rustls::Certificate(vec![0; 64])),
+            None
+        );
+    }
+}
@@ -8,6 +8,7 @@ pub mod parse_accounts;
 pub mod parse_associated_token;
 pub mod parse_bpf_loader;
 pub mod parse_evm;
+pub mod parse_evm_token;
 pub mod parse_instruction;
 pub mod parse_stake;
 pub mod parse_system;
@@ -26,13 +27,17 @@ use solana_sdk::{
     commitment_config::CommitmentConfig,
     deserialize_utils::default_on_eof,
     instruction::CompiledInstruction,
-    message::{Message, MessageHeader},
+    message::{
+        v0::{LoadedAddresses, LoadedMessage, MessageAddressTableLookup},
+        Message, MessageHeader, VersionedMessage,
+    },
+    process_instruction::TransactionReturnData,
     pubkey::Pubkey,
     sanitize::Sanitize,
     signature::Signature,
-    transaction::{Result, Transaction, TransactionError},
+    transaction::{Result, Transaction, TransactionError, VersionedTransaction},
 };
-use std::fmt;
+use std::{borrow::Cow, fmt};
 /// A duplicate representation of an Instruction for pretty JSON serialization
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", untagged)]
@@ -80,6 +85,25 @@ impl From<&CompiledInstruction> for UiCompiledInstruction {
     }
 }
 
+/// A duplicate representation of a MessageAddressTableLookup, for pretty JSON serialization
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiAddressTableLookup {
+    pub account_key: String,
+    pub writable_indexes: Vec<u8>,
+    pub readonly_indexes: Vec<u8>,
+}
+
+impl From<&MessageAddressTableLookup> for UiAddressTableLookup {
+    fn from(lookup: &MessageAddressTableLookup) -> Self {
+        Self {
+            account_key: lookup.account_key.to_string(),
+            writable_indexes: lookup.writable_indexes.clone(),
+            readonly_indexes: lookup.readonly_indexes.clone(),
+        }
+    }
+}
+
 /// A partially decoded CompiledInstruction that includes explicit account addresses
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -188,6 +212,10 @@ pub struct TransactionStatusMeta {
     pub post_token_balances: Option<Vec<TransactionTokenBalance>>,
     #[serde(deserialize_with = "default_on_eof")]
     pub rewards: Option<Rewards>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub return_data: Option<TransactionReturnData>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub loaded_addresses: Option<LoadedAddresses>,
 }
 
 impl Default for TransactionStatusMeta {
@@ -202,6 +230,8 @@ impl Default for TransactionStatusMeta {
             pre_token_balances: None,
             post_token_balances: None,
             rewards: None,
+            return_data: None,
+            loaded_addresses: None,
         }
     }
 }
@@ -220,6 +250,61 @@ pub struct UiTransactionStatusMeta {
     pub pre_token_balances: Option<Vec<UiTransactionTokenBalance>>,
     pub post_token_balances: Option<Vec<UiTransactionTokenBalance>>,
     pub rewards: Option<Rewards>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_data: Option<UiTransactionReturnData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loaded_addresses: Option<UiLoadedAddresses>,
+}
+
+/// A duplicate representation of LoadedAddresses
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiLoadedAddresses {
+    pub writable: Vec<String>,
+    pub readonly: Vec<String>,
+}
+
+impl From<&LoadedAddresses> for UiLoadedAddresses {
+    fn from(loaded_addresses: &LoadedAddresses) -> Self {
+        Self {
+            writable: loaded_addresses
+                .writable
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            readonly: loaded_addresses
+                .readonly
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        }
+    }
+}
+
+/// A duplicate representation of TransactionReturnData with base64 encoded data
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiTransactionReturnData {
+    pub program_id: String,
+    pub data: (String, UiReturnDataEncoding),
+}
+
+impl From<TransactionReturnData> for UiTransactionReturnData {
+    fn from(return_data: TransactionReturnData) -> Self {
+        Self {
+            program_id: return_data.program_id.to_string(),
+            data: (
+                base64::encode(return_data.data),
+                UiReturnDataEncoding::Base64,
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UiReturnDataEncoding {
+    Base64,
 }
 
 impl UiTransactionStatusMeta {
@@ -243,6 +328,8 @@ impl UiTransactionStatusMeta {
                 .post_token_balances
                 .map(|balance| balance.into_iter().map(|balance| balance.into()).collect()),
             rewards: meta.rewards,
+            return_data: meta.return_data.map(|return_data| return_data.into()),
+            loaded_addresses: meta.loaded_addresses.as_ref().map(UiLoadedAddresses::from),
         }
     }
 }
@@ -266,6 +353,8 @@ impl From<TransactionStatusMeta> for UiTransactionStatusMeta {
                 .post_token_balances
                 .map(|balance| balance.into_iter().map(|balance| balance.into()).collect()),
             rewards: meta.rewards,
+            return_data: meta.return_data.map(|return_data| return_data.into()),
+            loaded_addresses: meta.loaded_addresses.as_ref().map(UiLoadedAddresses::from),
         }
     }
 }
@@ -539,6 +628,8 @@ pub struct UiRawMessage {
     pub account_keys: Vec<String>,
     pub recent_blockhash: String,
     pub instructions: Vec<UiCompiledInstruction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_table_lookups: Option<Vec<UiAddressTableLookup>>,
 }
 
 /// A duplicate representation of a Message, in parsed format, for pretty JSON serialization
@@ -548,21 +639,46 @@ pub struct UiParsedMessage {
     pub account_keys: Vec<ParsedAccount>,
     pub recent_blockhash: String,
     pub instructions: Vec<UiInstruction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_table_lookups: Option<Vec<UiAddressTableLookup>>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TransactionWithStatusMeta {
-    pub transaction: Transaction,
+    pub transaction: VersionedTransaction,
     pub meta: Option<TransactionStatusMeta>,
 }
 
 impl TransactionWithStatusMeta {
+    /// The message with every account key inline, as it was executed. Unknown for v0 messages
+    /// without the addresses loaded from their lookup tables.
+    fn expanded_message(&self) -> Option<Cow<Message>> {
+        match &self.transaction.message {
+            VersionedMessage::Legacy(message) => Some(Cow::Borrowed(message)),
+            VersionedMessage::V0(message) => {
+                let loaded_addresses = self.meta.as_ref()?.loaded_addresses.clone()?;
+                let loaded_message = LoadedMessage::new(message.clone(), loaded_addresses);
+                Some(Cow::Owned(loaded_message.to_legacy_message()))
+            }
+        }
+    }
+
     fn encode(self, encoding: UiTransactionEncoding) -> EncodedTransactionWithStatusMeta {
-        let message = self.transaction.message();
-        let meta = self.meta.map(|meta| meta.encode(encoding, message));
+        let meta = match self.expanded_message() {
+            Some(message) => self
+                .meta
+                .clone()
+                .map(|meta| meta.encode(encoding, &message)),
+            None => self.meta.clone().map(UiTransactionStatusMeta::from),
+        };
+        let loaded_addresses = self.meta.and_then(|meta| meta.loaded_addresses);
         EncodedTransactionWithStatusMeta {
-            transaction: EncodedTransaction::encode(self.transaction, encoding),
+            transaction: EncodedTransaction::encode_versioned(
+                self.transaction,
+                loaded_addresses,
+                encoding,
+            ),
             meta,
         }
     }
@@ -641,6 +757,7 @@ impl EncodedTransaction {
                             .iter()
                             .map(|instruction| instruction.into())
                             .collect(),
+                        address_table_lookups: None,
                     })
                 } else {
                     UiMessage::Parsed(UiParsedMessage {
@@ -654,6 +771,7 @@ impl EncodedTransaction {
                                 UiInstruction::parse(instruction, &transaction.message)
                             })
                             .collect(),
+                        address_table_lookups: None,
                     })
                 };
                 EncodedTransaction::Json(UiTransaction {
@@ -667,6 +785,89 @@ impl EncodedTransaction {
             }
         }
     }
+    /// Encode a versioned transaction. Accounts loaded from address lookup tables are
+    /// resolved in the `JsonParsed` encoding if `loaded_addresses` are known, otherwise
+    /// the raw message is returned.
+    pub fn encode_versioned(
+        transaction: VersionedTransaction,
+        loaded_addresses: Option<LoadedAddresses>,
+        encoding: UiTransactionEncoding,
+    ) -> Self {
+        let message = match transaction.message {
+            VersionedMessage::Legacy(message) => {
+                let transaction = Transaction {
+                    signatures: transaction.signatures,
+                    message,
+                };
+                return Self::encode(transaction, encoding);
+            }
+            VersionedMessage::V0(ref message) => match encoding {
+                UiTransactionEncoding::Binary | UiTransactionEncoding::Base58 => {
+                    return EncodedTransaction::Binary(
+                        bs58::encode(bincode::serialize(&transaction).unwrap()).into_string(),
+                        UiTransactionEncoding::Base58,
+                    );
+                }
+                UiTransactionEncoding::Base64 => {
+                    return EncodedTransaction::Binary(
+                        base64::encode(bincode::serialize(&transaction).unwrap()),
+                        encoding,
+                    );
+                }
+                UiTransactionEncoding::Json | UiTransactionEncoding::JsonParsed => message.clone(),
+            },
+        };
+        let address_table_lookups = Some(
+            message
+                .address_table_lookups
+                .iter()
+                .map(UiAddressTableLookup::from)
+                .collect(),
+        );
+        let signatures = transaction
+            .signatures
+            .iter()
+            .map(|sig| sig.to_string())
+            .collect();
+
+        match (encoding, loaded_addresses) {
+            (UiTransactionEncoding::JsonParsed, Some(loaded_addresses)) => {
+                let message = LoadedMessage::new(message, loaded_addresses).to_legacy_message();
+                EncodedTransaction::Json(UiTransaction {
+                    signatures,
+                    message: UiMessage::Parsed(UiParsedMessage {
+                        account_keys: parse_accounts(&message),
+                        recent_blockhash: message.recent_blockhash.to_string(),
+                        instructions: message
+                            .instructions
+                            .iter()
+                            .map(|instruction| UiInstruction::parse(instruction, &message))
+                            .collect(),
+                        address_table_lookups,
+                    }),
+                })
+            }
+            _ => EncodedTransaction::Json(UiTransaction {
+                signatures,
+                message: UiMessage::Raw(UiRawMessage {
+                    header: message.header,
+                    account_keys: message
+                        .account_keys
+                        .iter()
+                        .map(|pubkey| pubkey.to_string())
+                        .collect(),
+                    recent_blockhash: message.recent_blockhash.to_string(),
+                    instructions: message
+                        .instructions
+                        .iter()
+                        .map(|instruction| instruction.into())
+                        .collect(),
+                    address_table_lookups,
+                }),
+            }),
+        }
+    }
+
     pub fn decode(&self) -> Option<Transaction> {
         let transaction: Option<Transaction> = match self {
             EncodedTransaction::Json(_) => None,
@@ -689,6 +890,24 @@ impl EncodedTransaction {
         };
         transaction.filter(|transaction| transaction.sanitize().is_ok())
     }
+
+    /// Decode a binary encoded transaction of any message version
+    pub fn decode_versioned(&self) -> Option<VersionedTransaction> {
+        let bytes = match self {
+            EncodedTransaction::Json(_) => None,
+            EncodedTransaction::LegacyBinary(blob) => bs58::decode(blob).into_vec().ok(),
+            EncodedTransaction::Binary(blob, encoding) => match *encoding {
+                UiTransactionEncoding::Base58 => bs58::decode(blob).into_vec().ok(),
+                UiTransactionEncoding::Base64 => base64::decode(blob).ok(),
+                UiTransactionEncoding::Binary
+                | UiTransactionEncoding::Json
+                | UiTransactionEncoding::JsonParsed => None,
+            },
+        };
+        bytes
+            .and_then(|bytes| bincode::deserialize::<VersionedTransaction>(&bytes).ok())
+            .filter(|transaction| transaction.sanitize().is_ok())
+    }
 }
 
 // A serialized `Vec<TransactionByAddrInfo>` is stored in the `tx-by-addr` table.  The row keys are
@@ -798,4 +1017,88 @@ mod test {
         };
         assert!(status.satisfies_commitment(CommitmentConfig::confirmed()));
     }
+
+    #[test]
+    fn test_encode_versioned_transaction() {
+        use solana_sdk::{hash::Hash, message::v0};
+
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let loaded_key = Pubkey::new_unique();
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::V0(v0::Message {
+                header: MessageHeader {
+                    num_required_signatures: 1,
+                    num_readonly_signed_accounts: 0,
+                    num_readonly_unsigned_accounts: 1,
+                },
+                account_keys: vec![payer, program_id],
+                recent_blockhash: Hash::new_unique(),
+                instructions: vec![CompiledInstruction {
+                    program_id_index: 1,
+                    accounts: vec![0, 2],
+                    data: vec![],
+                }],
+                address_table_lookups: vec![MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: vec![0],
+                    readonly_indexes: vec![],
+                }],
+            }),
+        };
+
+        let encoded = EncodedTransaction::encode_versioned(
+            transaction.clone(),
+            None,
+            UiTransactionEncoding::Base64,
+        );
+        assert_eq!(encoded.decode_versioned(), Some(transaction.clone()));
+        assert_eq!(encoded.decode(), None);
+
+        match EncodedTransaction::encode_versioned(
+            transaction.clone(),
+            None,
+            UiTransactionEncoding::JsonParsed,
+        ) {
+            EncodedTransaction::Json(UiTransaction {
+                message: UiMessage::Raw(message),
+                ..
+            }) => {
+                assert_eq!(message.account_keys.len(), 2);
+                assert_eq!(message.address_table_lookups.unwrap().len(), 1);
+            }
+            encoded => panic!("unexpected encoding: {:?}", encoded),
+        }
+
+        let loaded_addresses = LoadedAddresses {
+            writable: vec![loaded_key],
+            readonly: vec![],
+        };
+        match EncodedTransaction::encode_versioned(
+            transaction,
+            Some(loaded_addresses),
+            UiTransactionEncoding::JsonParsed,
+        ) {
+            EncodedTransaction::Json(UiTransaction {
+                message: UiMessage::Parsed(message),
+                ..
+            }) => {
+                let keys: Vec<_> = message
+                    .account_keys
+                    .iter()
+                    .map(|account| (account.pubkey.clone(), account.writable))
+                    .collect();
+                assert_eq!(
+                    keys,
+                    vec![
+                        (payer.to_string(), true),
+                        (loaded_key.to_string(), true),
+                        (program_id.to_string(), false),
+                    ]
+                );
+            }
+            encoded => panic!("unexpected encoding: {:?}", encoded),
+        }
+    }
 }
@@ -0,0 +1,327 @@
+use evm_state::{Log, H160, H256, U256};
+use std::collections::BTreeMap;
+
+/// `keccak256("Transfer(address,address,uint256)")`, shared by ERC-20 and ERC-721.
+pub const TRANSFER_EVENT_TOPIC: H256 = H256([
+    0xdd, 0xf2, 0x52, 0xad, 0x1b, 0xe2, 0xc8, 0x9b, 0x69, 0xc2, 0xb0, 0x68, 0xfc, 0x37, 0x8d, 0xaa,
+    0x95, 0x2b, 0xa7, 0xf1, 0x63, 0xc4, 0xa1, 0x16, 0x28, 0xf5, 0x5a, 0x4d, 0xf5, 0x23, 0xb3, 0xef,
+]);
+
+/// `keccak256("Approval(address,address,uint256)")`, shared by ERC-20 and ERC-721.
+pub const APPROVAL_EVENT_TOPIC: H256 = H256([
+    0x8c, 0x5b, 0xe1, 0xe5, 0xeb, 0xec, 0x7d, 0x5b, 0xd1, 0x4f, 0x71, 0x42, 0x7d, 0x1e, 0x84, 0xf3,
+    0xdd, 0x03, 0x14, 0xc0, 0xf7, 0xb2, 0x29, 0x1e, 0x5b, 0x20, 0x0a, 0xc8, 0xc7, 0xc3, 0xb9, 0x25,
+]);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EvmTokenStandard {
+    Erc20,
+    Erc721,
+}
+
+/// Standard token event, decoded from an EVM log.
+///
+/// `value` is the token amount for ERC-20, and the token id for ERC-721.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EvmTokenEvent {
+    Transfer {
+        token: H160,
+        standard: EvmTokenStandard,
+        from: H160,
+        to: H160,
+        value: U256,
+    },
+    Approval {
+        token: H160,
+        standard: EvmTokenStandard,
+        owner: H160,
+        spender: H160,
+        value: U256,
+    },
+}
+
+/// A token transfer, as stored in the blockstore for both sender and recipient.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvmTokenTransfer {
+    pub transaction_hash: H256,
+    pub token: H160,
+    pub standard: EvmTokenStandard,
+    pub from: H160,
+    pub to: H160,
+    pub value: U256,
+}
+
+/// Token holdings of a single address, accumulated from its transfers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvmTokenBalance {
+    pub token: H160,
+    pub standard: EvmTokenStandard,
+    /// ERC-20 amount, or number of owned ERC-721 tokens.
+    pub amount: U256,
+    /// Owned ERC-721 token ids, empty for ERC-20.
+    pub token_ids: Vec<U256>,
+}
+
+/// Indexed event parameter of type `address` is left padded with zeroes.
+fn topic_to_address(topic: &H256) -> Option<H160> {
+    let bytes = topic.as_bytes();
+    if bytes[..12].iter().any(|byte| *byte != 0) {
+        return None;
+    }
+    Some(H160::from_slice(&bytes[12..]))
+}
+
+/// Decode standard ERC-20 and ERC-721 `Transfer` and `Approval` events.
+///
+/// ERC-20 events keep amount in log data, while ERC-721 events have token id as the third
+/// indexed parameter, so the standard is detected by the number of topics.
+pub fn parse_evm_token_log(log: &Log) -> Option<EvmTokenEvent> {
+    let (signature, params) = log.topics.split_first()?;
+    if *signature != TRANSFER_EVENT_TOPIC && *signature != APPROVAL_EVENT_TOPIC {
+        return None;
+    }
+    let (standard, value) = match (params.len(), log.data.len()) {
+        (2, 32) => (EvmTokenStandard::Erc20, U256::from_big_endian(&log.data)),
+        (3, 0) => (
+            EvmTokenStandard::Erc721,
+            U256::from_big_endian(params[2].as_bytes()),
+        ),
+        _ => return None,
+    };
+    let first = topic_to_address(&params[0])?;
+    let second = topic_to_address(&params[1])?;
+
+    Some(if *signature == TRANSFER_EVENT_TOPIC {
+        EvmTokenEvent::Transfer {
+            token: log.address,
+            standard,
+            from: first,
+            to: second,
+            value,
+        }
+    } else {
+        EvmTokenEvent::Approval {
+            token: log.address,
+            standard,
+            owner: first,
+            spender: second,
+            value,
+        }
+    })
+}
+
+impl EvmTokenBalance {
+    pub fn new(token: H160, standard: EvmTokenStandard) -> Self {
+        Self {
+            token,
+            standard,
+            amount: U256::zero(),
+            token_ids: vec![],
+        }
+    }
+
+    /// Apply a transfer of this token sent or received by `holder`.
+    ///
+    /// Only transfers that were indexed are taken into account, so ERC-20 amounts saturate at
+    /// zero if outgoing transfers of earlier received tokens are observed.
+    pub fn apply(&mut self, holder: H160, transfer: &EvmTokenTransfer) {
+        match transfer.standard {
+            EvmTokenStandard::Erc20 => {
+                if transfer.from == holder {
+                    self.amount = self.amount.saturating_sub(transfer.value);
+                }
+                if transfer.to == holder {
+                    self.amount = self.amount.saturating_add(transfer.value);
+                }
+            }
+            EvmTokenStandard::Erc721 => {
+                if transfer.from == holder {
+                    if let Ok(position) = self.token_ids.binary_search(&transfer.value) {
+                        self.token_ids.remove(position);
+                    }
+                }
+                if transfer.to == holder {
+                    if let Err(position) = self.token_ids.binary_search(&transfer.value) {
+                        self.token_ids.insert(position, transfer.value);
+                    }
+                }
+                self.amount = self.token_ids.len().into();
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.amount.is_zero() && self.token_ids.is_empty()
+    }
+}
+
+/// Accumulate token holdings of `holder` from its transfers, in chronological order.
+pub fn accumulate_evm_token_balances<'a>(
+    holder: H160,
+    transfers: impl IntoIterator<Item = &'a EvmTokenTransfer>,
+) -> Vec<EvmTokenBalance> {
+    let mut balances: BTreeMap<(EvmTokenStandard, H160), EvmTokenBalance> = BTreeMap::new();
+    for transfer in transfers {
+        balances
+            .entry((transfer.standard, transfer.token))
+            .or_insert_with(|| EvmTokenBalance::new(transfer.token, transfer.standard))
+            .apply(holder, transfer);
+    }
+    balances
+        .into_values()
+        .filter(|balance| !balance.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn address_topic(address: H160) -> H256 {
+        let mut topic = H256::zero();
+        topic.as_bytes_mut()[12..].copy_from_slice(address.as_bytes());
+        topic
+    }
+
+    fn value_data(value: u64) -> Vec<u8> {
+        let mut data = vec![0; 32];
+        U256::from(value).to_big_endian(&mut data);
+        data
+    }
+
+    #[test]
+    fn test_event_topics() {
+        assert_eq!(
+            TRANSFER_EVENT_TOPIC.as_bytes(),
+            solana_sdk::keccak::hash(b"Transfer(address,address,uint256)").as_ref()
+        );
+        assert_eq!(
+            APPROVAL_EVENT_TOPIC.as_bytes(),
+            solana_sdk::keccak::hash(b"Approval(address,address,uint256)").as_ref()
+        );
+    }
+
+    #[test]
+    fn test_parse_evm_token_log() {
+        let token = H160::repeat_byte(0xaa);
+        let from = H160::repeat_byte(1);
+        let to = H160::repeat_byte(2);
+
+        let erc20_transfer = Log {
+            address: token,
+            topics: vec![TRANSFER_EVENT_TOPIC, address_topic(from), address_topic(to)],
+            data: value_data(1000),
+        };
+        assert_eq!(
+            parse_evm_token_log(&erc20_transfer),
+            Some(EvmTokenEvent::Transfer {
+                token,
+                standard: EvmTokenStandard::Erc20,
+                from,
+                to,
+                value: 1000.into(),
+            })
+        );
+
+        let erc721_approval = Log {
+            address: token,
+            topics: vec![
+                APPROVAL_EVENT_TOPIC,
+                address_topic(from),
+                address_topic(to),
+                H256::from_low_u64_be(42),
+            ],
+            data: vec![],
+        };
+        assert_eq!(
+            parse_evm_token_log(&erc721_approval),
+            Some(EvmTokenEvent::Approval {
+                token,
+                standard: EvmTokenStandard::Erc721,
+                owner: from,
+                spender: to,
+                value: 42.into(),
+            })
+        );
+
+        // Unknown event
+        let mut log = erc20_transfer.clone();
+        log.topics[0] = H256::repeat_byte(3);
+        assert_eq!(parse_evm_token_log(&log), None);
+
+        // Non standard amount encoding
+        let mut log = erc20_transfer.clone();
+        log.data.push(0);
+        assert_eq!(parse_evm_token_log(&log), None);
+
+        // Topic is not an address
+        let mut log = erc20_transfer;
+        log.topics[1] = H256::repeat_byte(1);
+        assert_eq!(parse_evm_token_log(&log), None);
+
+        assert_eq!(
+            parse_evm_token_log(&Log {
+                address: token,
+                topics: vec![],
+                data: vec![],
+            }),
+            None
+        );
+    }
+
+    #[test]
+    fn test_accumulate_evm_token_balances() {
+        let holder = H160::repeat_byte(1);
+        let other = H160::repeat_byte(2);
+        let erc20 = H160::repeat_byte(0xaa);
+        let erc721 = H160::repeat_byte(0xbb);
+        let transfer = |token, standard, from, to, value: u64| EvmTokenTransfer {
+            transaction_hash: H256::zero(),
+            token,
+            standard,
+            from,
+            to,
+            value: value.into(),
+        };
+
+        let transfers = vec![
+            transfer(erc20, EvmTokenStandard::Erc20, H160::zero(), holder, 100),
+            transfer(erc20, EvmTokenStandard::Erc20, holder, other, 30),
+            transfer(erc721, EvmTokenStandard::Erc721, other, holder, 1),
+            transfer(erc721, EvmTokenStandard::Erc721, other, holder, 2),
+            transfer(erc721, EvmTokenStandard::Erc721, holder, other, 1),
+            // self transfer doesn't change anything
+            transfer(erc20, EvmTokenStandard::Erc20, holder, holder, 70),
+        ];
+        assert_eq!(
+            accumulate_evm_token_balances(holder, &transfers),
+            vec![
+                EvmTokenBalance {
+                    token: erc20,
+                    standard: EvmTokenStandard::Erc20,
+                    amount: 70.into(),
+                    token_ids: vec![],
+                },
+                EvmTokenBalance {
+                    token: erc721,
+                    standard: EvmTokenStandard::Erc721,
+                    amount: 1.into(),
+                    token_ids: vec![2.into()],
+                },
+            ]
+        );
+
+        // Everything was sent away
+        assert_eq!(
+            accumulate_evm_token_balances(
+                holder,
+                &[
+                    transfer(erc20, EvmTokenStandard::Erc20, other, holder, 10),
+                    transfer(erc20, EvmTokenStandard::Erc20, holder, other, 10),
+                ]
+            ),
+            vec![]
+        );
+    }
+}
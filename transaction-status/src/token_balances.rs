@@ -3,7 +3,11 @@ use solana_account_decoder::parse_token::{
     spl_token_id_v2_0, spl_token_v2_0_native_mint, token_amount_to_ui_amount, UiTokenAmount,
 };
 use solana_runtime::{bank::Bank, transaction_batch::TransactionBatch};
-use solana_sdk::{account::ReadableAccount, pubkey::Pubkey};
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    pubkey::Pubkey,
+    transaction::Transaction,
+};
 use spl_token_v2_0::{
     solana_program::program_pack::Pack,
     state::{Account as TokenAccount, Mint},
@@ -48,6 +52,22 @@ fn get_mint_decimals(bank: &Bank, mint: &Pubkey) -> Option<u8> {
     }
 }
 
+/// Accounts of the transaction which may hold token balances, with their index in the message
+fn token_account_candidates(transaction: &Transaction) -> Vec<(usize, &Pubkey)> {
+    let account_keys = &transaction.message.account_keys;
+    if !account_keys.iter().any(|p| is_token_program(p)) {
+        return vec![];
+    }
+    let program_ids = transaction.message.program_ids();
+    account_keys
+        .iter()
+        .enumerate()
+        .filter(|(_, account_id)| {
+            !is_token_program(account_id) && !program_ids.contains(account_id)
+        })
+        .collect()
+}
+
 pub fn collect_token_balances(
     bank: &Bank,
     batch: &TransactionBatch,
@@ -56,27 +76,16 @@ pub fn collect_token_balances(
     let mut balances: TransactionTokenBalances = vec![];
 
     for transaction in batch.transactions_iter() {
-        let account_keys = &transaction.message.account_keys;
-        let has_token_program = account_keys.iter().any(|p| is_token_program(p));
-
         let mut transaction_balances: Vec<TransactionTokenBalance> = vec![];
-        if has_token_program {
-            for (index, account_id) in account_keys.iter().enumerate() {
-                if is_token_program(account_id)
-                    || transaction.message.program_ids().contains(&account_id)
-                {
-                    continue;
-                }
-
-                if let Some((mint, ui_token_amount)) =
-                    collect_token_balance_from_account(bank, account_id, &mut mint_decimals)
-                {
-                    transaction_balances.push(TransactionTokenBalance {
-                        account_index: index as u8,
-                        mint,
-                        ui_token_amount,
-                    });
-                }
+        for (index, account_id) in token_account_candidates(transaction) {
+            if let Some((mint, ui_token_amount)) =
+                collect_token_balance_from_account(bank, account_id, &mut mint_decimals)
+            {
+                transaction_balances.push(TransactionTokenBalance {
+                    account_index: index as u8,
+                    mint,
+                    ui_token_amount,
+                });
             }
         }
         balances.push(transaction_balances);
@@ -84,13 +93,54 @@ pub fn collect_token_balances(
     balances
 }
 
+/// Collect the token balances of a simulated transaction before and after its execution.
+/// `post_simulation_accounts` are ordered as the message account keys.
+pub fn collect_simulation_token_balances(
+    bank: &Bank,
+    transaction: &Transaction,
+    post_simulation_accounts: &[AccountSharedData],
+) -> (Vec<TransactionTokenBalance>, Vec<TransactionTokenBalance>) {
+    let mut mint_decimals = HashMap::new();
+    let mut pre_token_balances = vec![];
+    let mut post_token_balances = vec![];
+    for (index, account_id) in token_account_candidates(transaction) {
+        if let Some((mint, ui_token_amount)) =
+            collect_token_balance_from_account(bank, account_id, &mut mint_decimals)
+        {
+            pre_token_balances.push(TransactionTokenBalance {
+                account_index: index as u8,
+                mint,
+                ui_token_amount,
+            });
+        }
+        if let Some((mint, ui_token_amount)) = post_simulation_accounts
+            .get(index)
+            .and_then(|account| token_balance_from_account(bank, account, &mut mint_decimals))
+        {
+            post_token_balances.push(TransactionTokenBalance {
+                account_index: index as u8,
+                mint,
+                ui_token_amount,
+            });
+        }
+    }
+    (pre_token_balances, post_token_balances)
+}
+
 pub fn collect_token_balance_from_account(
     bank: &Bank,
     account_id: &Pubkey,
     mint_decimals: &mut HashMap<Pubkey, u8>,
 ) -> Option<(String, UiTokenAmount)> {
     let account = bank.get_account(account_id)?;
+    token_balance_from_account(bank, &account, mint_decimals)
+}
 
+fn token_balance_from_account(
+    bank: &Bank,
+    account: &impl ReadableAccount,
+    mint_decimals: &mut HashMap<Pubkey, u8>,
+) -> Option<(String, UiTokenAmount)> {
     let token_account = TokenAccount::unpack(account.data()).ok()?;
     let mint_string = &token_account.mint.to_string();
     let mint = &Pubkey::from_str(mint_string).unwrap_or_default();
@@ -28,19 +28,22 @@ use {
         contact_info::ContactInfo,
         gossip_service::GossipService,
         poh_service,
-        rpc::JsonRpcConfig,
+        rpc::{JsonRpcConfig, MAX_EVM_LOGS_BLOCK_RANGE},
         rpc_pubsub_service::PubSubConfig,
         tpu::DEFAULT_TPU_COALESCE_MS,
         validator::{
             is_snapshot_config_invalid, Validator, ValidatorConfig, ValidatorStartProgress,
         },
     },
-    solana_download_utils::{download_genesis_if_missing, download_snapshot},
+    solana_download_utils::{
+        download_genesis_if_missing, download_incremental_snapshot, download_snapshot,
+    },
     solana_ledger::blockstore_db::BlockstoreRecoveryMode,
     solana_perf::recycler::enable_recycler_warming,
     solana_runtime::{
         accounts_index::{
             AccountIndex, AccountSecondaryIndexes, AccountSecondaryIndexesIncludeExclude,
+            CustomAccountIndex,
         },
         bank_forks::{ArchiveFormat, SnapshotConfig, SnapshotVersion},
         hardened_unpack::{unpack_genesis_archive, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE},
@@ -54,6 +57,7 @@ use {
         pubkey::Pubkey,
         signature::{Keypair, Signer},
     },
+    solana_storage_bigtable::LedgerStorageConfig,
     std::{
         collections::{HashSet, VecDeque},
         env,
@@ -728,6 +732,7 @@ fn verify_reachable_ports(
     }
     if ContactInfo::is_valid_address(&node.info.tpu) {
         udp_sockets.extend(node.sockets.tpu.iter());
+        udp_sockets.push(&node.sockets.tpu_quic);
     }
     if ContactInfo::is_valid_address(&node.info.tpu_forwards) {
         udp_sockets.extend(node.sockets.tpu_forwards.iter());
@@ -962,6 +967,30 @@ fn rpc_bootstrap(
                                 snapshot_hash,
                                 use_progress_bar,
                             );
+                            // Incremental snapshot hashes aren't published in gossip, so
+                            // only take one straight from a trusted validator
+                            let incremental_snapshot_trusted = validator_config
+                                .trusted_validators
+                                .as_ref()
+                                .map_or(true, |trusted_validators| {
+                                    trusted_validators.contains(&rpc_contact_info.id)
+                                });
+                            if ret.is_ok() && !incremental_snapshot_trusted {
+                                info!(
+                                    "Skipping incremental snapshot from {}: not a trusted validator",
+                                    rpc_contact_info.id
+                                );
+                            } else if ret.is_ok() {
+                                // Incremental snapshot is optional, full one is enough to start
+                                if let Err(err) = download_incremental_snapshot(
+                                    &rpc_contact_info.rpc,
+                                    snapshot_output_dir,
+                                    snapshot_hash.0,
+                                    use_progress_bar,
+                                ) {
+                                    warn!("Incremental snapshot wasn't downloaded: {}", err);
+                                }
+                            }
                             gossip_service.join().unwrap();
                             ret
                         })
@@ -1025,6 +1054,7 @@ pub fn main() {
         &format!("{}-{}", VALIDATOR_PORT_RANGE.0, VALIDATOR_PORT_RANGE.1);
     let default_genesis_archive_unpacked_size = &MAX_GENESIS_ARCHIVE_UNPACKED_SIZE.to_string();
     let default_rpc_max_multiple_accounts = &MAX_MULTIPLE_ACCOUNTS.to_string();
+    let default_rpc_max_evm_logs_block_range = &MAX_EVM_LOGS_BLOCK_RANGE.to_string();
     let default_rpc_pubsub_max_connections = PubSubConfig::default().max_connections.to_string();
     let default_rpc_pubsub_max_fragment_size =
         PubSubConfig::default().max_fragment_size.to_string();
@@ -1040,6 +1070,9 @@ pub fn main() {
     let default_rpc_send_transaction_leader_forward_count = ValidatorConfig::default()
         .send_transaction_leader_forward_count
         .to_string();
+    let default_rpc_send_transaction_service_max_retries = ValidatorConfig::default()
+        .send_transaction_service_max_retries
+        .to_string();
     let default_rpc_threads = num_cpus::get().to_string();
 
     let matches = App::new(crate_name!()).about(crate_description!())
@@ -1221,6 +1254,20 @@ pub fn main() {
                 .takes_value(false)
                 .help("Upload new confirmed blocks into a BigTable instance"),
         )
+        .arg(
+            Arg::with_name("ledger_storage")
+                .long("ledger-storage")
+                .value_name("LOCATION")
+                .takes_value(true)
+                .validator(is_parsable::<LedgerStorageConfig>)
+                .default_value("bigtable")
+                .help("Long-term ledger storage used by --enable-rpc-bigtable-ledger-storage \
+                       and --enable-bigtable-ledger-upload. \
+                       One of: 'bigtable', local directory 'file://PATH', \
+                       or S3 compatible storage 's3://BUCKET[/PREFIX]' \
+                       configured by AWS_ENDPOINT_URL, AWS_REGION, AWS_ACCESS_KEY_ID \
+                       and AWS_SECRET_ACCESS_KEY environment variables"),
+        )
         .arg(
             Arg::with_name("enable_cpi_and_log_storage")
                 .long("enable-cpi-and-log-storage")
@@ -1238,6 +1285,34 @@ pub fn main() {
                 .help("Override the default maximum accounts accepted by \
                        the getMultipleAccounts JSON RPC method")
         )
+        .arg(
+            Arg::with_name("rpc_max_evm_logs_block_range")
+                .long("rpc-max-evm-logs-block-range")
+                .value_name("BLOCKS")
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .default_value(default_rpc_max_evm_logs_block_range)
+                .help("Override the default maximum number of blocks in a single \
+                       eth_getLogs JSON RPC request")
+        )
+        .arg(
+            Arg::with_name("enable_rpc_evm_log_index")
+                .long("enable-rpc-evm-log-index")
+                .takes_value(false)
+                .help("Use EVM log address and topic indexes of the ledger for eth_getLogs. \
+                       Only enable once the indexes cover the whole local ledger, \
+                       see `ledger-tool evm_blockstore reindex-logs`"),
+        )
+        .arg(
+            Arg::with_name("enable_rpc_evm_token_index")
+                .long("enable-rpc-evm-token-index")
+                .requires("enable_rpc_transaction_history")
+                .takes_value(false)
+                .help("Index ERC-20 and ERC-721 transfers of confirmed EVM blocks, \
+                       and enable the evm_getTokenTransfers and evm_getTokenBalances \
+                       RPC methods. Indexing starts from the first EVM block available \
+                       in the local ledger, and the index is never purged"),
+        )
         .arg(
             Arg::with_name("health_check_slot_distance")
                 .long("health-check-slot-distance")
@@ -1344,6 +1419,15 @@ pub fn main() {
                 .help("Number of slots between generating snapshots, \
                       0 to disable snapshots"),
         )
+        .arg(
+            Arg::with_name("incremental_snapshot_interval_slots")
+                .long("incremental-snapshot-interval-slots")
+                .value_name("NUMBER_OF_SLOTS")
+                .takes_value(true)
+                .default_value("0")
+                .help("Number of slots between generating incremental snapshots, \
+                      on top of the last full snapshot, 0 to disable incremental snapshots"),
+        )
         .arg(
             Arg::with_name("contact_debug_interval")
                 .long("contact-debug-interval")
@@ -1553,6 +1637,14 @@ pub fn main() {
                 .validator(solana_net_utils::is_host)
                 .help("IP address to bind the RPC port [default: use --bind-address]"),
         )
+        .arg(
+            Arg::with_name("prometheus_bind_address")
+                .long("prometheus-bind-address")
+                .value_name("HOST:PORT")
+                .takes_value(true)
+                .validator(solana_net_utils::is_host_port)
+                .help("Serve the validator metrics in Prometheus format on this address"),
+        )
         .arg(
             Arg::with_name("rpc_threads")
                 .long("rpc-threads")
@@ -1644,6 +1736,23 @@ pub fn main() {
                 .default_value(&default_rpc_send_transaction_leader_forward_count)
                 .help("The number of upcoming leaders to which to forward transactions sent via rpc service."),
         )
+        .arg(
+            Arg::with_name("rpc_send_transaction_default_max_retries")
+                .long("rpc-send-default-max-retries")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .help("The maximum number of transaction broadcast retries when unspecified by the request, otherwise retried until expiration."),
+        )
+        .arg(
+            Arg::with_name("rpc_send_transaction_service_max_retries")
+                .long("rpc-send-service-max-retries")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_send_transaction_service_max_retries)
+                .help("The maximum number of transaction broadcast retries, regardless of requested value."),
+        )
         .arg(
             Arg::with_name("rpc_scan_and_fix_roots")
                 .long("rpc-scan-and-fix-roots")
@@ -1758,6 +1867,18 @@ pub fn main() {
                 .value_name("INDEX")
                 .help("Enable an accounts index, indexed by the selected account field"),
         )
+        .arg(
+            Arg::with_name("account_index_custom")
+                .long("account-index-custom")
+                .takes_value(true)
+                .multiple(true)
+                .validator(|value| value.parse::<CustomAccountIndex>().map(|_| ()))
+                .value_name("PROGRAM_ID:OFFSET:LENGTH")
+                .help("Enable an accounts index over LENGTH bytes at OFFSET in the data of \
+                       the accounts owned by PROGRAM_ID. getProgramAccounts requests with a \
+                       memcmp filter at that offset are served from this index. \
+                       LENGTH must not exceed 32 bytes"),
+        )
         .arg(
             Arg::with_name("account_index_exclude_key")
                 .long(EXCLUDE_KEY)
@@ -1821,6 +1942,14 @@ pub fn main() {
                 .takes_value(false)
                 .help("Verify EVM state from snapshot")
         )
+        .arg(
+            Arg::with_name("accountsdb_plugin_config")
+                .long("accountsdb-plugin-config")
+                .value_name("FILE")
+                .takes_value(true)
+                .multiple(true)
+                .help("Specify the configuration file for the AccountsDb plugin."),
+        )
         .after_help("The default subcommand is run")
         .subcommand(
             SubCommand::with_name("exit")
@@ -2180,8 +2309,16 @@ pub fn main() {
             rpc_bigtable_timeout: value_t!(matches, "rpc_bigtable_timeout", u64)
                 .ok()
                 .map(Duration::from_secs),
+            ledger_storage_config: value_t_or_exit!(matches, "ledger_storage", LedgerStorageConfig),
             account_indexes: account_indexes.clone(),
             rpc_scan_and_fix_roots: matches.is_present("rpc_scan_and_fix_roots"),
+            max_evm_logs_block_range: Some(value_t_or_exit!(
+                matches,
+                "rpc_max_evm_logs_block_range",
+                u64
+            )),
+            enable_evm_log_index: matches.is_present("enable_rpc_evm_log_index"),
+            enable_evm_token_index: matches.is_present("enable_rpc_evm_token_index"),
         },
         rpc_addrs: value_t!(matches, "rpc_port", u16).ok().map(|rpc_port| {
             (
@@ -2232,6 +2369,17 @@ pub fn main() {
             "rpc_send_transaction_leader_forward_count",
             u64
         ),
+        send_transaction_default_max_retries: value_t!(
+            matches,
+            "rpc_send_transaction_default_max_retries",
+            usize
+        )
+        .ok(),
+        send_transaction_service_max_retries: value_t_or_exit!(
+            matches,
+            "rpc_send_transaction_service_max_retries",
+            usize
+        ),
         no_poh_speed_test: matches.is_present("no_poh_speed_test"),
         poh_pinned_cpu_core: value_of(&matches, "poh_pinned_cpu_core")
             .unwrap_or(poh_service::DEFAULT_PINNED_CPU_CORE),
@@ -2271,6 +2419,15 @@ pub fn main() {
         } else {
             vec![ledger_path.join("accounts")]
         };
+    if matches.is_present("accountsdb_plugin_config") {
+        validator_config.accountsdb_plugin_config_files = Some(
+            values_t_or_exit!(matches, "accountsdb_plugin_config", String)
+                .into_iter()
+                .map(PathBuf::from)
+                .collect(),
+        );
+    }
+
     let account_shrink_paths: Option<Vec<PathBuf>> =
         values_t!(matches, "account_shrink_path", String)
             .map(|shrink_paths| shrink_paths.into_iter().map(PathBuf::from).collect())
@@ -2314,6 +2471,8 @@ pub fn main() {
     });
 
     let snapshot_interval_slots = value_t_or_exit!(matches, "snapshot_interval_slots", u64);
+    let incremental_snapshot_interval_slots =
+        value_t_or_exit!(matches, "incremental_snapshot_interval_slots", u64);
     let maximum_local_snapshot_age = value_t_or_exit!(matches, "maximum_local_snapshot_age", u64);
     let snapshot_output_dir = if matches.is_present("snapshots") {
         PathBuf::from(matches.value_of("snapshots").unwrap())
@@ -2362,6 +2521,11 @@ pub fn main() {
         } else {
             std::u64::MAX
         },
+        incremental_snapshot_interval_slots: if incremental_snapshot_interval_slots > 0 {
+            incremental_snapshot_interval_slots
+        } else {
+            std::u64::MAX
+        },
         snapshot_path,
         snapshot_package_output_path: snapshot_output_dir.clone(),
         archive_format,
@@ -2384,6 +2548,26 @@ pub fn main() {
         );
         exit(1);
     }
+    if is_snapshot_config_invalid(
+        incremental_snapshot_interval_slots,
+        validator_config.accounts_hash_interval_slots,
+    ) {
+        eprintln!("Invalid incremental snapshot interval provided ({}), must be a multiple of accounts_hash_interval_slots ({})",
+            incremental_snapshot_interval_slots,
+            validator_config.accounts_hash_interval_slots,
+        );
+        exit(1);
+    }
+    if incremental_snapshot_interval_slots > 0
+        && (snapshot_interval_slots == 0
+            || incremental_snapshot_interval_slots >= snapshot_interval_slots)
+    {
+        eprintln!("Invalid incremental snapshot interval provided ({}), must be less than snapshot interval ({})",
+            incremental_snapshot_interval_slots,
+            snapshot_interval_slots,
+        );
+        exit(1);
+    }
 
     if matches.is_present("limit_ledger_size") {
         let limit_ledger_size = match matches.value_of("limit_ledger_size") {
@@ -2565,6 +2749,17 @@ pub fn main() {
 
     solana_metrics::set_host_id(identity_keypair.pubkey().to_string());
     solana_metrics::set_panic_hook("validator");
+    if let Some(address) = matches.value_of("prometheus_bind_address") {
+        let address = solana_net_utils::parse_host_port(address)
+            .expect("failed to parse prometheus bind address");
+        if let Err(err) = solana_metrics::prometheus::start_exporter(address) {
+            eprintln!(
+                "Unable to start the Prometheus exporter on {}: {}",
+                address, err
+            );
+            exit(1);
+        }
+    }
 
     if validator_config.cuda {
         solana_perf::perf_libs::init_cuda();
@@ -2637,6 +2832,12 @@ fn process_account_indexes(matches: &ArgMatches) -> AccountSecondaryIndexes {
             "sophon-relying-party-owners" => AccountIndex::SophonRelyingOwner,
             unexpected => panic!("Unable to handle 'account_indexes' flag {}", unexpected),
         })
+        .chain(
+            values_t!(matches, "account_index_custom", CustomAccountIndex)
+                .unwrap_or_default()
+                .into_iter()
+                .map(AccountIndex::Custom),
+        )
         .collect();
 
     let account_indexes_include_keys: HashSet<Pubkey> =
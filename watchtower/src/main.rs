@@ -5,21 +5,23 @@ use {
     clap::{crate_description, crate_name, value_t, value_t_or_exit, App, Arg},
     log::*,
     solana_clap_utils::{
-        input_parsers::pubkeys_of,
+        input_parsers::{pubkey_of, pubkeys_of},
         input_validators::{is_parsable, is_pubkey_or_keypair, is_url},
     },
     solana_cli_output::display::format_labeled_address,
     solana_client::{client_error, rpc_client::RpcClient, rpc_response::RpcVoteAccountStatus},
     solana_metrics::{datapoint_error, datapoint_info},
-    solana_notifier::Notifier,
+    solana_notifier::{Alert, Notifier, Severity},
     solana_sdk::{
+        clock::UnixTimestamp,
         hash::Hash,
         native_token::{sol_to_lamports, Sol},
         pubkey::Pubkey,
     },
     std::{
-        collections::HashMap,
+        collections::{HashMap, HashSet},
         error,
+        net::SocketAddr,
         thread::sleep,
         time::{Duration, Instant},
     },
@@ -27,15 +29,47 @@ use {
 
 struct Config {
     address_labels: HashMap<String, String>,
+    evm_bridge_fee_payer: Option<Pubkey>,
+    evm_bridge_max_block_lag: u64,
+    evm_bridge_url: Option<String>,
+    evm_max_block_time_lag: Duration,
     ignore_http_bad_gateway: bool,
     interval: Duration,
     json_rpc_url: String,
+    minimum_evm_bridge_fee_payer_balance: u64,
     minimum_validator_identity_balance: u64,
     monitor_active_stake: bool,
+    monitor_evm: bool,
+    notifier_config: Option<String>,
+    prometheus_bind_address: Option<SocketAddr>,
     unhealthy_threshold: usize,
     validator_identity_pubkeys: Vec<Pubkey>,
 }
 
+impl Config {
+    fn monitors_evm(&self) -> bool {
+        self.monitor_evm || self.evm_bridge_url.is_some() || self.evm_bridge_fee_payer.is_some()
+    }
+}
+
+/// EVM side of the cluster, as seen by the validator RPC and the evm-bridge
+struct EvmInfo {
+    block_number: u64,
+    block_timestamp: Option<u64>,
+    native_block_time: Option<UnixTimestamp>,
+    bridge_block_number: Option<Result<u64, String>>,
+    bridge_fee_payer_balance: Option<u64>,
+}
+
+/// Low balances leave time to react, everything else means the cluster is unhealthy
+fn failure_severity(failure_test_name: &str) -> Severity {
+    if failure_test_name.ends_with("balance") {
+        Severity::Warning
+    } else {
+        Severity::Critical
+    }
+}
+
 fn get_config() -> Config {
     let matches = App::new(crate_name!())
         .about(crate_description!())
@@ -134,6 +168,75 @@ fn get_config() -> Config {
                     no alerting should a Bad Gateway error be a side effect of \
                     the real problem")
         )
+        .arg(
+            Arg::with_name("monitor_evm")
+                .long("monitor-evm")
+                .takes_value(false)
+                .help("Alert when the EVM block number stops advancing or the EVM block \
+                    timestamps drift from the native block time"),
+        )
+        .arg(
+            Arg::with_name("evm_max_block_time_lag")
+                .long("evm-max-block-time-lag")
+                .value_name("SECONDS")
+                .takes_value(true)
+                .default_value("60")
+                .validator(is_parsable::<u64>)
+                .help("Alert when the latest EVM block timestamp differs from the native \
+                    block time by more than this many seconds"),
+        )
+        .arg(
+            Arg::with_name("evm_bridge_url")
+                .long("evm-bridge-url")
+                .value_name("URL")
+                .takes_value(true)
+                .validator(is_url)
+                .help("Alert when the evm-bridge at this URL fails to answer eth_blockNumber \
+                    consistently with the validator"),
+        )
+        .arg(
+            Arg::with_name("evm_bridge_max_block_lag")
+                .long("evm-bridge-max-block-lag")
+                .value_name("COUNT")
+                .takes_value(true)
+                .default_value("10")
+                .validator(is_parsable::<u64>)
+                .help("Alert when the evm-bridge block number differs from the validator \
+                    by more than this many blocks"),
+        )
+        .arg(
+            Arg::with_name("evm_bridge_fee_payer")
+                .long("evm-bridge-fee-payer")
+                .value_name("PUBKEY")
+                .takes_value(true)
+                .validator(is_pubkey_or_keypair)
+                .help("Fee payer of the evm-bridge to monitor for low balance"),
+        )
+        .arg(
+            Arg::with_name("minimum_evm_bridge_fee_payer_balance")
+                .long("minimum-evm-bridge-fee-payer-balance")
+                .value_name("SOPHON")
+                .takes_value(true)
+                .default_value("10")
+                .validator(is_parsable::<f64>)
+                .help("Alert when the evm-bridge fee payer balance is less than this amount of SOPHON"),
+        )
+        .arg(
+            Arg::with_name("notifier_config")
+                .long("notifier-config")
+                .value_name("FILE")
+                .takes_value(true)
+                .help("Notifier configuration file with alert channels and routing rules \
+                    [default: channels from the environment]"),
+        )
+        .arg(
+            Arg::with_name("prometheus_bind_address")
+                .long("prometheus-bind-address")
+                .value_name("HOST:PORT")
+                .takes_value(true)
+                .validator(is_parsable::<SocketAddr>)
+                .help("Serve the watchtower metrics in Prometheus format on this address")
+        )
         .get_matches();
 
     let config = if let Some(config_file) = matches.value_of("config_file") {
@@ -158,14 +261,35 @@ fn get_config() -> Config {
 
     let monitor_active_stake = matches.is_present("monitor_active_stake");
     let ignore_http_bad_gateway = matches.is_present("ignore_http_bad_gateway");
+    let prometheus_bind_address = value_t!(matches, "prometheus_bind_address", SocketAddr).ok();
+    let notifier_config = value_t!(matches, "notifier_config", String).ok();
+    let monitor_evm = matches.is_present("monitor_evm");
+    let evm_max_block_time_lag =
+        Duration::from_secs(value_t_or_exit!(matches, "evm_max_block_time_lag", u64));
+    let evm_bridge_url = value_t!(matches, "evm_bridge_url", String).ok();
+    let evm_bridge_max_block_lag = value_t_or_exit!(matches, "evm_bridge_max_block_lag", u64);
+    let evm_bridge_fee_payer = pubkey_of(&matches, "evm_bridge_fee_payer");
+    let minimum_evm_bridge_fee_payer_balance = sol_to_lamports(value_t_or_exit!(
+        matches,
+        "minimum_evm_bridge_fee_payer_balance",
+        f64
+    ));
 
     let config = Config {
         address_labels: config.address_labels,
+        evm_bridge_fee_payer,
+        evm_bridge_max_block_lag,
+        evm_bridge_url,
+        evm_max_block_time_lag,
         ignore_http_bad_gateway,
         interval,
         json_rpc_url,
+        minimum_evm_bridge_fee_payer_balance,
         minimum_validator_identity_balance,
         monitor_active_stake,
+        monitor_evm,
+        notifier_config,
+        prometheus_bind_address,
         unhealthy_threshold,
         validator_identity_pubkeys,
     };
@@ -178,10 +302,48 @@ fn get_config() -> Config {
     config
 }
 
+fn get_evm_info(
+    config: &Config,
+    rpc_client: &RpcClient,
+    bridge_client: Option<&RpcClient>,
+) -> client_error::Result<EvmInfo> {
+    let block_number = rpc_client.get_evm_block_number()?;
+    let block_timestamp = rpc_client
+        .get_evm_block_by_number(block_number)?
+        .map(|block| block.timestamp.0);
+    // The latest slot may not have a block time yet, that check is then skipped
+    let native_block_time = rpc_client.get_block_time(rpc_client.get_slot()?).ok();
+    let bridge_block_number = bridge_client.map(|bridge_client| {
+        bridge_client
+            .get_evm_block_number()
+            .map_err(|err| err.to_string())
+    });
+    let bridge_fee_payer_balance = match &config.evm_bridge_fee_payer {
+        Some(fee_payer) => Some(rpc_client.get_balance(fee_payer)?),
+        None => None,
+    };
+
+    Ok(EvmInfo {
+        block_number,
+        block_timestamp,
+        native_block_time,
+        bridge_block_number,
+        bridge_fee_payer_balance,
+    })
+}
+
+#[allow(clippy::type_complexity)]
 fn get_cluster_info(
     config: &Config,
     rpc_client: &RpcClient,
-) -> client_error::Result<(u64, Hash, RpcVoteAccountStatus, HashMap<Pubkey, u64>)> {
+    bridge_client: Option<&RpcClient>,
+) -> client_error::Result<(
+    u64,
+    Hash,
+    RpcVoteAccountStatus,
+    HashMap<Pubkey, u64>,
+    Option<EvmInfo>,
+)> {
     let transaction_count = rpc_client.get_transaction_count()?;
     let recent_blockhash = rpc_client.get_recent_blockhash()?.0;
     let vote_accounts = rpc_client.get_vote_accounts()?;
@@ -194,11 +356,18 @@ fn get_cluster_info(
         );
     }
 
+    let evm_info = if config.monitors_evm() {
+        Some(get_evm_info(config, rpc_client, bridge_client)?)
+    } else {
+        None
+    };
+
     Ok((
         transaction_count,
         recent_blockhash,
         vote_accounts,
         validator_balances,
+        evm_info,
     ))
 }
 
@@ -207,18 +376,33 @@ fn main() -> Result<(), Box<dyn error::Error>> {
     solana_metrics::set_panic_hook("watchtower");
 
     let config = get_config();
+    if let Some(address) = config.prometheus_bind_address {
+        solana_metrics::prometheus::start_exporter(address)?;
+    }
 
     let rpc_client = RpcClient::new(config.json_rpc_url.clone());
-    let notifier = Notifier::default();
+    let bridge_client = config.evm_bridge_url.clone().map(RpcClient::new);
+    let notifier = match &config.notifier_config {
+        Some(notifier_config) => Notifier::from_config_file(notifier_config)?,
+        None => Notifier::default(),
+    };
     let mut last_transaction_count = 0;
     let mut last_recent_blockhash = Hash::default();
+    let mut last_evm_block_number = 0;
     let mut last_notification_msg = "".into();
+    let mut open_alerts = HashSet::new();
     let mut num_consecutive_failures = 0;
     let mut last_success = Instant::now();
 
     loop {
-        let failure = match get_cluster_info(&config, &rpc_client) {
-            Ok((transaction_count, recent_blockhash, vote_accounts, validator_balances)) => {
+        let failure = match get_cluster_info(&config, &rpc_client, bridge_client.as_ref()) {
+            Ok((
+                transaction_count,
+                recent_blockhash,
+                vote_accounts,
+                validator_balances,
+                evm_info,
+            )) => {
                 info!("Current transaction count: {}", transaction_count);
                 info!("Recent blockhash: {}", recent_blockhash);
                 info!("Current validator count: {}", vote_accounts.current.len());
@@ -313,6 +497,81 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                     failures.push(("delinquent", validator_errors.join(",")));
                 }
 
+                if let Some(evm_info) = evm_info {
+                    info!("Current EVM block number: {}", evm_info.block_number);
+
+                    if config.monitor_evm {
+                        if evm_info.block_number > last_evm_block_number {
+                            last_evm_block_number = evm_info.block_number;
+                        } else {
+                            failures.push((
+                                "evm-block-number",
+                                format!(
+                                    "EVM block number is not advancing: {} <= {}",
+                                    evm_info.block_number, last_evm_block_number
+                                ),
+                            ));
+                        }
+
+                        if let (Some(block_timestamp), Some(native_block_time)) =
+                            (evm_info.block_timestamp, evm_info.native_block_time)
+                        {
+                            let lag = (native_block_time - block_timestamp as i64).abs() as u64;
+                            if lag > config.evm_max_block_time_lag.as_secs() {
+                                failures.push((
+                                    "evm-block-time",
+                                    format!(
+                                        "EVM block {} timestamp is {}s off the native block time",
+                                        evm_info.block_number, lag
+                                    ),
+                                ));
+                            }
+                        }
+                    }
+
+                    match evm_info.bridge_block_number {
+                        Some(Ok(bridge_block_number)) => {
+                            let lag = if bridge_block_number > evm_info.block_number {
+                                bridge_block_number - evm_info.block_number
+                            } else {
+                                evm_info.block_number - bridge_block_number
+                            };
+                            if lag > config.evm_bridge_max_block_lag {
+                                failures.push((
+                                    "evm-bridge",
+                                    format!(
+                                        "evm-bridge block number {} differs from validator {}",
+                                        bridge_block_number, evm_info.block_number
+                                    ),
+                                ));
+                            }
+                        }
+                        Some(Err(err)) => {
+                            failures.push(("evm-bridge", format!("evm-bridge error: {}", err)));
+                        }
+                        None => {}
+                    }
+
+                    if let (Some(fee_payer), Some(balance)) = (
+                        &config.evm_bridge_fee_payer,
+                        evm_info.bridge_fee_payer_balance,
+                    ) {
+                        if balance < config.minimum_evm_bridge_fee_payer_balance {
+                            failures.push((
+                                "evm-bridge-balance",
+                                format!(
+                                    "{} has {}",
+                                    format_labeled_address(
+                                        &fee_payer.to_string(),
+                                        &config.address_labels
+                                    ),
+                                    Sol(balance)
+                                ),
+                            ));
+                        }
+                    }
+                }
+
                 for failure in failures.iter() {
                     error!("{} sanity failure: {}", failure.0, failure.1);
                 }
@@ -343,9 +602,13 @@ fn main() -> Result<(), Box<dyn error::Error>> {
             num_consecutive_failures += 1;
             if num_consecutive_failures > config.unhealthy_threshold {
                 datapoint_info!("watchtower-sanity", ("ok", false, bool));
-                if last_notification_msg != notification_msg {
-                    notifier.send(&notification_msg);
-                }
+                let alert_key = format!("watchtower/{}", failure_test_name);
+                notifier.send_alert(&Alert::firing(
+                    &alert_key,
+                    failure_severity(failure_test_name),
+                    &notification_msg,
+                ));
+                open_alerts.insert(alert_key);
                 datapoint_error!(
                     "watchtower-sanity-failure",
                     ("test", failure_test_name, String),
@@ -370,7 +633,10 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                     humantime::format_duration(alarm_duration)
                 );
                 info!("{}", all_clear_msg);
-                notifier.send(&format!("solana-watchtower: {}", all_clear_msg));
+                let all_clear_msg = format!("solana-watchtower: {}", all_clear_msg);
+                for alert_key in open_alerts.drain() {
+                    notifier.send_alert(&Alert::resolved(alert_key, &all_clear_msg));
+                }
             }
             last_notification_msg = "".into();
             last_success = Instant::now();